@@ -0,0 +1,32 @@
+use std::{env, path::PathBuf};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // The services of fabric that aren't part of the demeter specs. protoc is vendored so the
+    // build doesn't depend on a system install.
+    env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+    tonic_build::configure()
+        .build_client(false)
+        .file_descriptor_set_path(out_dir.join("fabric_ops_descriptor.bin"))
+        .compile_protos(
+            &[
+                "proto/fabric/ops/v1/audit.proto",
+                "proto/fabric/ops/v1/ownership.proto",
+                "proto/fabric/ops/v1/page.proto",
+                "proto/fabric/ops/v1/pricing.proto",
+                "proto/fabric/ops/v1/quota.proto",
+                "proto/fabric/ops/v1/restore.proto",
+                "proto/fabric/ops/v1/search.proto",
+            ],
+            &["proto"],
+        )?;
+
+    // The events published with the `encoding: protobuf` header, only the messages are used.
+    tonic_build::configure()
+        .build_server(false)
+        .build_client(false)
+        .compile_protos(&["proto/fabric/event/v1/event.proto"], &["proto"])?;
+
+    Ok(())
+}
@@ -3,9 +3,12 @@ use std::{collections::HashMap, path::PathBuf};
 use anyhow::{bail, Result};
 use clap::{Parser, Subcommand};
 use dotenv::dotenv;
-use fabric::drivers::{
-    backoffice::{BackofficeConfig, OutputFormat},
-    cache::CacheConfig,
+use fabric::{
+    driven::bus::{memory::MemoryBus, Bus, BusConfig, Encoding},
+    drivers::{
+        backoffice::{BackofficeConfig, OutputFormat},
+        cache::{CacheConfig, CacheSnapshotConfig},
+    },
 };
 use serde::Deserialize;
 use tracing::Level;
@@ -32,6 +35,24 @@ pub struct UsageArgs {
     pub output: Option<String>,
 }
 
+#[derive(Parser, Clone)]
+pub struct InvoiceArgs {
+    /// closed period to invoice (year-month) e.g 2024-09
+    pub period: String,
+
+    /// Read the usage and record the invoices in the postgres cache at this url
+    #[arg(long)]
+    pub postgres_url: Option<String>,
+
+    /// table(log in terminal), json(log in terminal)
+    #[arg(short, long)]
+    pub output: Option<String>,
+
+    // Dry run
+    #[arg(short, long, action)]
+    pub dry_run: bool,
+}
+
 #[derive(Parser, Clone)]
 pub struct ProjectArgs {
     /// Project namespace
@@ -87,7 +108,7 @@ pub struct InviteUserArgs {
     #[arg(short, long)]
     pub email: String,
 
-    /// Role to grant on acceptance: owner or member
+    /// Role to grant on acceptance: owner, admin, developer, member, viewer or billing
     #[arg(short, long, default_value = "member")]
     pub role: String,
 
@@ -120,6 +141,142 @@ pub struct TransferProjectArgs {
     pub dry_run: bool,
 }
 
+#[derive(Parser, Clone)]
+pub struct CreateOrganizationArgs {
+    /// Organization name, also used as the name of its stripe customer
+    #[arg(short, long)]
+    pub name: String,
+
+    /// Email of the owner, who must already have an account
+    #[arg(short, long)]
+    pub owner_email: String,
+
+    // Dry run
+    #[arg(short, long, action)]
+    pub dry_run: bool,
+}
+
+#[derive(Parser, Clone)]
+pub struct AddOrganizationUserArgs {
+    /// Organization id
+    #[arg(short, long)]
+    pub organization_id: String,
+
+    /// Email of the user, who must already have an account
+    #[arg(short, long)]
+    pub email: String,
+
+    /// Role inherited on every project of the organization: owner, admin, developer, member,
+    /// viewer or billing
+    #[arg(short, long, default_value = "member")]
+    pub role: String,
+
+    // Dry run
+    #[arg(short, long, action)]
+    pub dry_run: bool,
+}
+
+#[derive(Parser, Clone)]
+pub struct DeleteOrganizationUserArgs {
+    /// Organization id
+    #[arg(short, long)]
+    pub organization_id: String,
+
+    /// Email of the user to remove
+    #[arg(short, long)]
+    pub email: String,
+
+    // Dry run
+    #[arg(short, long, action)]
+    pub dry_run: bool,
+}
+
+#[derive(Parser, Clone)]
+pub struct AttachProjectArgs {
+    /// Organization id
+    #[arg(short, long)]
+    pub organization_id: String,
+
+    /// Project id
+    #[arg(short, long)]
+    pub project_id: String,
+
+    // Dry run
+    #[arg(short, long, action)]
+    pub dry_run: bool,
+}
+
+#[derive(Parser, Clone)]
+pub struct DetachProjectArgs {
+    /// Project id, it gets a stripe customer of its owner back
+    #[arg(short, long)]
+    pub id: String,
+
+    // Dry run
+    #[arg(short, long, action)]
+    pub dry_run: bool,
+}
+
+#[derive(Parser, Clone)]
+pub struct SuspendProjectArgs {
+    /// Project id
+    #[arg(short, long)]
+    pub id: String,
+
+    /// Why the project is suspended, kept in the audit log
+    #[arg(short, long)]
+    pub reason: String,
+
+    // Dry run
+    #[arg(short, long, action)]
+    pub dry_run: bool,
+}
+
+#[derive(Parser, Clone)]
+pub struct ResumeProjectArgs {
+    /// Id of the suspended project
+    #[arg(short, long)]
+    pub id: String,
+
+    // Dry run
+    #[arg(short, long, action)]
+    pub dry_run: bool,
+}
+
+#[derive(Parser, Clone)]
+pub struct SetQuotaArgs {
+    /// Project id, the default quota of every project is set without it
+    #[arg(short, long)]
+    pub project_id: Option<String>,
+
+    /// Limit of active resources of a kind as KIND=N, repeat it for each kind
+    #[arg(short, long)]
+    pub resource: Vec<String>,
+
+    /// Highest throughputTier of the resources
+    #[arg(short, long)]
+    pub max_tier: Option<u32>,
+
+    /// Limit of active workers
+    #[arg(short, long)]
+    pub workers: Option<u32>,
+
+    // Dry run
+    #[arg(short, long, action)]
+    pub dry_run: bool,
+}
+
+#[derive(Parser, Clone)]
+pub struct QuotaArgs {
+    /// Project id, the default quota is shown without it
+    #[arg(short, long)]
+    pub project_id: Option<String>,
+
+    /// table(log in terminal), json(log in terminal)
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
 #[derive(Parser, Clone)]
 pub struct DeleteProjectArgs {
     /// Project id
@@ -131,6 +288,17 @@ pub struct DeleteProjectArgs {
     pub dry_run: bool,
 }
 
+#[derive(Parser, Clone)]
+pub struct RestoreProjectArgs {
+    /// Id of the deleted project, the resources deleted with it are restored too
+    #[arg(short, long)]
+    pub id: String,
+
+    // Dry run
+    #[arg(short, long, action)]
+    pub dry_run: bool,
+}
+
 #[derive(Parser, Clone)]
 pub struct ResourceArgs {
     /// Project namespace
@@ -157,6 +325,17 @@ pub struct DeleteResourceArgs {
     pub dry_run: bool,
 }
 
+#[derive(Parser, Clone)]
+pub struct RestoreResourceArgs {
+    /// UUID of the deleted resource, its project must be active.
+    #[arg(short, long)]
+    pub id: String,
+
+    // Dry run
+    #[arg(short, long, action)]
+    pub dry_run: bool,
+}
+
 #[derive(Parser, Clone)]
 pub struct PatchResourceArgs {
     /// UUID of the resource to patch.
@@ -193,6 +372,10 @@ pub struct CreateResourceArgs {
     #[arg(short, long)]
     pub spec: String,
 
+    /// Cluster to place the resource on, the one of the project by default.
+    #[arg(long)]
+    pub cluster_id: Option<String>,
+
     // Dry run
     #[arg(short, long, action)]
     pub dry_run: bool,
@@ -214,14 +397,173 @@ pub struct DiffArgs {
     pub output: Option<String>,
 }
 
+#[derive(Parser, Clone)]
+pub struct AuditArgs {
+    /// Project id
+    #[arg(short, long)]
+    pub project_id: Option<String>,
+
+    /// Resource id
+    #[arg(short, long)]
+    pub resource_id: Option<String>,
+
+    /// User id that triggered the event, e.g the `deleted_by` of a deletion
+    #[arg(short, long)]
+    pub actor: Option<String>,
+
+    /// Event type, e.g ResourceDeleted
+    #[arg(short, long)]
+    pub event_type: Option<String>,
+
+    /// Events since this date (year-month-day or rfc3339)
+    #[arg(short, long)]
+    pub from: Option<String>,
+
+    /// Events until this date (year-month-day or rfc3339)
+    #[arg(short, long)]
+    pub to: Option<String>,
+
+    #[arg(long, default_value_t = 1)]
+    pub page: u32,
+
+    #[arg(long, default_value_t = 50)]
+    pub page_size: u32,
+
+    /// table(log in terminal), json(log in terminal)
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+#[derive(Parser, Clone)]
+pub struct SearchArgs {
+    /// Words matched against the project and resource names and the resource spec values
+    pub text: Option<String>,
+
+    /// Resource kind, e.g CardanoNodePort
+    #[arg(short, long)]
+    pub kind: Option<String>,
+
+    /// Network of the resource spec, e.g mainnet
+    #[arg(short, long)]
+    pub network: Option<String>,
+
+    /// Throughput tier of the resource spec
+    #[arg(long)]
+    pub tier: Option<String>,
+
+    /// active or deleted
+    #[arg(short, long)]
+    pub status: Option<String>,
+
+    /// Resources created since this date (year-month-day or rfc3339)
+    #[arg(short, long)]
+    pub from: Option<String>,
+
+    /// Resources created until this date (year-month-day or rfc3339)
+    #[arg(short, long)]
+    pub to: Option<String>,
+
+    /// Email of the project owner
+    #[arg(long)]
+    pub owner_email: Option<String>,
+
+    #[arg(long, default_value_t = 1)]
+    pub page: u32,
+
+    #[arg(long, default_value_t = 50)]
+    pub page_size: u32,
+
+    /// table(log in terminal), json(log in terminal)
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+#[derive(Parser, Clone)]
+pub struct RebuildArgs {
+    /// Restore the projects from the snapshot topic and only replay the events after them
+    #[arg(long)]
+    pub snapshots: bool,
+}
+
+#[derive(Parser, Clone)]
+pub struct ChecksumArgs {
+    /// table(log in terminal), json(log in terminal)
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+#[derive(Parser, Clone)]
+pub struct DeadLettersArgs {
+    /// table(log in terminal), json(log in terminal)
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+#[derive(Parser, Clone)]
+pub struct RedriveArgs {
+    /// Position (partition:offset) in the dead-letter topic, can be repeated. Re-drive every
+    /// dead letter when omitted.
+    #[arg(short, long)]
+    pub position: Vec<String>,
+
+    // Dry run
+    #[arg(short, long, action)]
+    pub dry_run: bool,
+}
+
+#[derive(Parser, Clone)]
+pub struct MigrateArgs {
+    #[command(subcommand)]
+    pub command: MigrateCommands,
+
+    /// Migrate the postgres cache at this url instead of the sqlite file at db_path
+    #[arg(long)]
+    pub postgres_url: Option<String>,
+}
+
+#[derive(Subcommand, Clone)]
+pub enum MigrateCommands {
+    /// List the migrations, pending, applied or unknown to this binary
+    Status(MigrateStatusArgs),
+
+    /// Apply the pending migrations
+    Up,
+
+    /// Revert the latest applied migration
+    Down(MigrateDownArgs),
+}
+
+#[derive(Parser, Clone)]
+pub struct MigrateStatusArgs {
+    /// table(log in terminal), json(log in terminal)
+    #[arg(short, long)]
+    pub output: Option<String>,
+}
+
+#[derive(Parser, Clone)]
+pub struct MigrateDownArgs {
+    /// Revert every migration applied after this version, 0 reverts all of them
+    #[arg(short, long)]
+    pub target: Option<i64>,
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Sync cache
     Sync,
 
+    /// Replay the topics from the beginning into a new cache and swap it in
+    Rebuild(RebuildArgs),
+
+    /// Checksum of the cache tables, to compare caches across machines
+    Checksum(ChecksumArgs),
+
     /// Get the usage data
     Usage(UsageArgs),
 
+    /// Send the usage of a closed month to stripe as invoice items
+    Invoice(InvoiceArgs),
+
     /// Get projects by user
     Project(ProjectArgs),
 
@@ -237,6 +579,33 @@ enum Commands {
     /// Transfer a project to another member of the project
     TransferProject(TransferProjectArgs),
 
+    /// Create an organization with its own stripe customer
+    CreateOrganization(CreateOrganizationArgs),
+
+    /// Add a user to an organization, and so to all of its projects
+    AddOrganizationUser(AddOrganizationUserArgs),
+
+    /// Remove a user from an organization
+    DeleteOrganizationUser(DeleteOrganizationUserArgs),
+
+    /// Attach a project to an organization, billing it through the organization customer
+    AttachProject(AttachProjectArgs),
+
+    /// Detach a project from its organization
+    DetachProject(DetachProjectArgs),
+
+    /// Suspend a project, its resources are stopped and its API keys refused
+    SuspendProject(SuspendProjectArgs),
+
+    /// Resume a suspended project
+    ResumeProject(ResumeProjectArgs),
+
+    /// Show the quota of a project, or the default one
+    Quota(QuotaArgs),
+
+    /// Set the quota of a project, or the default one of every project
+    SetQuota(SetQuotaArgs),
+
     /// Get resource by project namespace
     Resource(ResourceArgs),
 
@@ -257,6 +626,27 @@ enum Commands {
 
     /// Delete resource
     DeleteResource(DeleteResourceArgs),
+
+    /// Restore a project deleted within the grace window
+    RestoreProject(RestoreProjectArgs),
+
+    /// Restore a resource deleted within the grace window
+    RestoreResource(RestoreResourceArgs),
+
+    /// List the events applied to the cache, newest first
+    Audit(AuditArgs),
+
+    /// Search resources by text and typed filters
+    Search(SearchArgs),
+
+    /// List the events that the consumers failed to apply
+    DeadLetters(DeadLettersArgs),
+
+    /// Publish dead-lettered events again on their source topic
+    Redrive(RedriveArgs),
+
+    /// Show, apply or revert the migrations of the cache
+    Migrate(MigrateArgs),
 }
 
 #[tokio::main]
@@ -280,6 +670,71 @@ async fn main() -> Result<()> {
         Commands::Sync => {
             fabric::drivers::cache::subscribe(config.clone().into()).await?;
         }
+        Commands::Rebuild(args) => {
+            fabric::drivers::cache::rebuild(config.clone().into(), args.snapshots).await?;
+        }
+        Commands::Audit(args) => {
+            let output = match args.output {
+                Some(output) => match output.as_str() {
+                    "table" => OutputFormat::Table,
+                    "json" => OutputFormat::Json,
+                    _ => bail!("invalid output format"),
+                },
+                None => OutputFormat::Table,
+            };
+
+            fabric::drivers::backoffice::fetch_audit(
+                config.clone().into(),
+                args.project_id,
+                args.resource_id,
+                args.actor,
+                args.event_type,
+                args.from,
+                args.to,
+                args.page,
+                args.page_size,
+                output,
+            )
+            .await?;
+        }
+        Commands::Search(args) => {
+            let output = match args.output {
+                Some(output) => match output.as_str() {
+                    "table" => OutputFormat::Table,
+                    "json" => OutputFormat::Json,
+                    _ => bail!("invalid output format"),
+                },
+                None => OutputFormat::Table,
+            };
+
+            fabric::drivers::backoffice::search(
+                config.clone().into(),
+                args.text,
+                args.kind,
+                args.network,
+                args.tier,
+                args.status,
+                args.from,
+                args.to,
+                args.owner_email,
+                args.page,
+                args.page_size,
+                output,
+            )
+            .await?;
+        }
+        Commands::Checksum(args) => {
+            let output = match args.output {
+                Some(output) => match output.as_str() {
+                    "table" => OutputFormat::Table,
+                    "json" => OutputFormat::Json,
+                    _ => bail!("invalid output format"),
+                },
+                None => OutputFormat::Table,
+            };
+
+            fabric::drivers::backoffice::fetch_checksum(config.clone().into(), output).await?;
+        }
         Commands::Diff(args) => {
             let output = match args.output {
                 Some(output) => match output.as_str() {
@@ -306,6 +761,25 @@ async fn main() -> Result<()> {
             fabric::drivers::backoffice::fetch_usage(config.clone().into(), &args.period, output)
                 .await?;
         }
+        Commands::Invoice(args) => {
+            let output = match args.output {
+                Some(output) => match output.as_str() {
+                    "table" => OutputFormat::Table,
+                    "json" => OutputFormat::Json,
+                    _ => bail!("invalid output format"),
+                },
+                None => OutputFormat::Table,
+            };
+
+            fabric::drivers::backoffice::invoice(
+                config.clone().into(),
+                &args.period,
+                args.postgres_url,
+                output,
+                args.dry_run,
+            )
+            .await?;
+        }
         Commands::Project(args) => {
             fabric::drivers::backoffice::fetch_projects(
                 config.clone().into(),
@@ -340,6 +814,7 @@ async fn main() -> Result<()> {
                 args.project_id,
                 args.kind,
                 args.spec,
+                args.cluster_id,
                 args.dry_run,
             ).await?
         }
@@ -390,6 +865,96 @@ async fn main() -> Result<()> {
             )
             .await?;
         }
+        Commands::CreateOrganization(args) => {
+            fabric::drivers::backoffice::create_organization(
+                config.clone().into(),
+                args.name,
+                args.owner_email,
+                args.dry_run,
+            )
+            .await?;
+        }
+        Commands::AddOrganizationUser(args) => {
+            fabric::drivers::backoffice::add_organization_user(
+                config.clone().into(),
+                args.organization_id,
+                args.email,
+                args.role,
+                args.dry_run,
+            )
+            .await?;
+        }
+        Commands::DeleteOrganizationUser(args) => {
+            fabric::drivers::backoffice::delete_organization_user(
+                config.clone().into(),
+                args.organization_id,
+                args.email,
+                args.dry_run,
+            )
+            .await?;
+        }
+        Commands::AttachProject(args) => {
+            fabric::drivers::backoffice::attach_project(
+                config.clone().into(),
+                args.organization_id,
+                args.project_id,
+                args.dry_run,
+            )
+            .await?;
+        }
+        Commands::DetachProject(args) => {
+            fabric::drivers::backoffice::detach_project(
+                config.clone().into(),
+                args.id,
+                args.dry_run,
+            )
+            .await?;
+        }
+        Commands::SuspendProject(args) => {
+            fabric::drivers::backoffice::suspend_project(
+                config.clone().into(),
+                args.id,
+                args.reason,
+                args.dry_run,
+            )
+            .await?;
+        }
+        Commands::ResumeProject(args) => {
+            fabric::drivers::backoffice::resume_project(
+                config.clone().into(),
+                args.id,
+                args.dry_run,
+            )
+            .await?;
+        }
+        Commands::Quota(args) => {
+            let output = match args.output {
+                Some(output) => match output.as_str() {
+                    "table" => OutputFormat::Table,
+                    "json" => OutputFormat::Json,
+                    _ => bail!("invalid output format"),
+                },
+                None => OutputFormat::Table,
+            };
+
+            fabric::drivers::backoffice::fetch_quota(
+                config.clone().into(),
+                args.project_id,
+                output,
+            )
+            .await?;
+        }
+        Commands::SetQuota(args) => {
+            fabric::drivers::backoffice::set_quota(
+                config.clone().into(),
+                args.project_id,
+                args.resource,
+                args.max_tier,
+                args.workers,
+                args.dry_run,
+            )
+            .await?;
+        }
         Commands::DeleteProject(args) => {
             fabric::drivers::backoffice::delete_project(
                 config.clone().into(),
@@ -407,6 +972,73 @@ async fn main() -> Result<()> {
             )
             .await?;
         }
+        Commands::RestoreProject(args) => {
+            fabric::drivers::backoffice::restore_project(
+                config.clone().into(),
+                args.id,
+                args.dry_run,
+            )
+            .await?;
+        }
+        Commands::RestoreResource(args) => {
+            fabric::drivers::backoffice::restore_resource(
+                config.clone().into(),
+                args.id,
+                args.dry_run,
+            )
+            .await?;
+        }
+        Commands::DeadLetters(args) => {
+            let output = match args.output {
+                Some(output) => match output.as_str() {
+                    "table" => OutputFormat::Table,
+                    "json" => OutputFormat::Json,
+                    _ => bail!("invalid output format"),
+                },
+                None => OutputFormat::Table,
+            };
+
+            fabric::drivers::backoffice::fetch_dead_letters(config.clone().into(), output).await?;
+        }
+        Commands::Migrate(args) => match args.command {
+            MigrateCommands::Status(status_args) => {
+                let output = match status_args.output {
+                    Some(output) => match output.as_str() {
+                        "table" => OutputFormat::Table,
+                        "json" => OutputFormat::Json,
+                        _ => bail!("invalid output format"),
+                    },
+                    None => OutputFormat::Table,
+                };
+
+                fabric::drivers::backoffice::fetch_migrations(
+                    config.clone().into(),
+                    args.postgres_url,
+                    output,
+                )
+                .await?;
+            }
+            MigrateCommands::Up => {
+                fabric::drivers::backoffice::migrate_up(config.clone().into(), args.postgres_url)
+                    .await?;
+            }
+            MigrateCommands::Down(down_args) => {
+                fabric::drivers::backoffice::migrate_down(
+                    config.clone().into(),
+                    args.postgres_url,
+                    down_args.target,
+                )
+                .await?;
+            }
+        },
+        Commands::Redrive(args) => {
+            fabric::drivers::backoffice::redrive_dead_letters(
+                config.clone().into(),
+                args.position,
+                args.dry_run,
+            )
+            .await?;
+        }
         Commands::NewUsers(args) => {
             let output = match args.output {
                 Some(output) => match output.as_str() {
@@ -454,8 +1086,12 @@ struct EmailConfig {
 #[derive(Debug, Clone, Deserialize)]
 struct Config {
     db_path: String,
+    /// Billing ledger of `invoice`, `<db_path>.ledger` when missing.
+    ledger_path: Option<String>,
     topic_events: String,
     topic_usage: Option<String>,
+    /// Only required by `dead-letters` and `redrive`.
+    topic_dead_letter: Option<String>,
     kafka_consumer: HashMap<String, String>,
     kafka_producer: HashMap<String, String>,
     auth: AuthConfig,
@@ -464,6 +1100,14 @@ struct Config {
     /// Only required by `invite-user`; every other subcommand works without it.
     email: Option<EmailConfig>,
     crds_path: PathBuf,
+    /// Kafka is used when missing, set `kind = "file"` to share the topics of a local rpc.
+    bus: Option<BusConfig>,
+    /// Only required by `rebuild --snapshots`.
+    topic_snapshots: Option<String>,
+    #[serde(default)]
+    event_encoding: Encoding,
+    #[serde(skip)]
+    memory_bus: MemoryBus,
 }
 impl Config {
     pub fn new(path: &str) -> Result<Self> {
@@ -474,11 +1118,24 @@ impl Config {
 
         Ok(config)
     }
+
+    fn bus(&self, kafka: &HashMap<String, String>) -> Bus {
+        Bus::new(
+            self.bus.as_ref(),
+            kafka,
+            self.event_encoding,
+            &self.memory_bus,
+        )
+    }
 }
 
 impl From<Config> for BackofficeConfig {
     fn from(value: Config) -> Self {
         Self {
+            bus: value.bus(&value.kafka_producer),
+            ledger_path: value
+                .ledger_path
+                .unwrap_or_else(|| format!("{}.ledger", value.db_path)),
             db_path: value.db_path,
             crds_path: value.crds_path,
             auth_url: value.auth.url,
@@ -496,7 +1153,9 @@ impl From<Config> for BackofficeConfig {
             ses_verified_email: value.email.as_ref().map(|e| e.ses_verified_email.clone()),
             invite_ttl_min: value.email.as_ref().and_then(|e| e.invite_ttl_min),
             topic_events: value.topic_events,
+            topic_dead_letter: value.topic_dead_letter,
             kafka_producer: value.kafka_producer,
+            kafka_consumer: value.kafka_consumer,
         }
     }
 }
@@ -504,13 +1163,21 @@ impl From<Config> for BackofficeConfig {
 impl From<Config> for CacheConfig {
     fn from(value: Config) -> Self {
         Self {
-            kafka: value.kafka_consumer,
+            bus: value.bus(&value.kafka_consumer),
             db_path: value.db_path,
-            topics: match value.topic_usage {
-                Some(topic) => [value.topic_events, topic].to_vec(),
+            postgres_url: None,
+            topics: match &value.topic_usage {
+                Some(topic) => [value.topic_events, topic.clone()].to_vec(),
                 None => [value.topic_events].to_vec(),
             },
             notify: None,
+            retry: Default::default(),
+            dead_letter: None,
+            snapshot: value.topic_snapshots.map(|topic| CacheSnapshotConfig {
+                topic,
+                interval: None,
+                usage_topic: value.topic_usage,
+            }),
         }
     }
 }
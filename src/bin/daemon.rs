@@ -3,8 +3,17 @@ use std::{collections::HashMap, env, sync::Arc, time::Duration};
 use anyhow::Result;
 use dotenv::dotenv;
 use fabric::{
-    driven::prometheus::metrics::MetricsDriven,
-    drivers::{cache::CacheConfig, monitor::MonitorConfig, usage::UsageConfig},
+    driven::{
+        bus::{memory::MemoryBus, Bus, BusConfig, Encoding},
+        kafka::dead_letter::DeadLetterConfig,
+        prometheus::metrics::MetricsDriven,
+    },
+    drivers::{
+        cache::{CacheConfig, CacheSnapshotConfig},
+        monitor::MonitorConfig,
+        retry::RetryPolicy,
+        usage::{RollupSchedule, UsageConfig, UsageRollupConfig},
+    },
 };
 use serde::{de::Visitor, Deserialize, Deserializer};
 use tokio::try_join;
@@ -29,6 +38,9 @@ async fn main() -> Result<()> {
         .init();
 
     let config = Config::new()?;
+    if !matches!(config.mode, Mode::Monitor) {
+        fabric::drivers::cache::check_schema(&config.db_path, None).await?;
+    }
     let metrics_driven = Arc::new(MetricsDriven::new()?);
 
     let metrics = fabric::drivers::metrics::server(&config.metrics.addr, metrics_driven.clone());
@@ -38,8 +50,9 @@ async fn main() -> Result<()> {
             let cache = fabric::drivers::cache::subscribe(config.clone().into());
             let usage =
                 fabric::drivers::usage::schedule(config.clone().into(), metrics_driven.clone());
+            let rollup = rollup(config.clone());
 
-            try_join!(cache, usage, metrics)?;
+            try_join!(cache, usage, rollup, metrics)?;
         }
         Mode::Monitor => {
             let monitor =
@@ -53,14 +66,25 @@ async fn main() -> Result<()> {
                 fabric::drivers::usage::schedule(config.clone().into(), metrics_driven.clone());
             let monitor =
                 fabric::drivers::monitor::subscribe(config.clone().into(), metrics_driven.clone());
+            let rollup = rollup(config.clone());
 
-            try_join!(cache, usage, monitor, metrics)?;
+            try_join!(cache, usage, rollup, monitor, metrics)?;
         }
     };
 
     Ok(())
 }
 
+async fn rollup(config: Config) -> Result<()> {
+    match config.usage_rollup {
+        Some(schedule) => {
+            let rollup_config = UsageRollupConfig::new(config.db_path, schedule);
+            fabric::drivers::usage::rollup(rollup_config).await
+        }
+        None => Ok(()),
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 enum Mode {
     Usage,
@@ -79,6 +103,14 @@ struct Metrics {
     addr: String,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+struct SnapshotConfig {
+    topic: String,
+    /// Publishes the snapshots of the projects in the cache at this interval, when missing the
+    /// topic is only used to restore the cache.
+    interval_sec: Option<u64>,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 struct Config {
     db_path: String,
@@ -93,6 +125,15 @@ struct Config {
     kafka_producer: HashMap<String, String>,
     kafka_monitor: HashMap<String, String>,
     kafka_cache: HashMap<String, String>,
+    topic_dead_letter: Option<String>,
+    retry: Option<RetryPolicy>,
+    bus: Option<BusConfig>,
+    #[serde(default)]
+    event_encoding: Encoding,
+    #[serde(skip)]
+    memory_bus: MemoryBus,
+    snapshot: Option<SnapshotConfig>,
+    usage_rollup: Option<RollupSchedule>,
     mode: Mode,
 }
 impl Config {
@@ -108,12 +149,33 @@ impl Config {
 
         Ok(config)
     }
+
+    fn bus(&self, kafka: &HashMap<String, String>) -> Bus {
+        Bus::new(
+            self.bus.as_ref(),
+            kafka,
+            self.event_encoding,
+            &self.memory_bus,
+        )
+    }
+
+    fn dead_letter(&self) -> Option<DeadLetterConfig> {
+        self.topic_dead_letter
+            .as_ref()
+            .map(|topic| DeadLetterConfig {
+                topic: topic.clone(),
+                kafka: self.kafka_producer.clone(),
+            })
+    }
 }
 
 impl From<Config> for MonitorConfig {
     fn from(value: Config) -> Self {
         Self {
-            kafka: value.kafka_monitor,
+            bus: value.bus(&value.kafka_monitor),
+            dead_letter: value.dead_letter(),
+            retry: value.retry.unwrap_or_default(),
+            cluster_id: value.cluster_id,
             topic: value.topic_events,
         }
     }
@@ -122,12 +184,12 @@ impl From<Config> for MonitorConfig {
 impl From<Config> for UsageConfig {
     fn from(value: Config) -> Self {
         Self {
+            bus: value.bus(&value.kafka_producer),
             db_path: value.db_path,
             cluster_id: value.cluster_id,
             prometheus_url: value.prometheus.url,
             prometheus_query_step: value.prometheus.query_step,
             delay: value.delay,
-            kafka: value.kafka_producer,
             topic: value.topic_usage,
         }
     }
@@ -136,10 +198,18 @@ impl From<Config> for UsageConfig {
 impl From<Config> for CacheConfig {
     fn from(value: Config) -> Self {
         Self {
-            kafka: value.kafka_cache,
+            bus: value.bus(&value.kafka_cache),
+            dead_letter: value.dead_letter(),
+            retry: value.retry.unwrap_or_default(),
             db_path: value.db_path,
-            topics: [value.topic_events, value.topic_usage].to_vec(),
+            postgres_url: None,
+            topics: [value.topic_events, value.topic_usage.clone()].to_vec(),
             notify: None,
+            snapshot: value.snapshot.map(|snapshot| CacheSnapshotConfig {
+                topic: snapshot.topic,
+                interval: snapshot.interval_sec.map(Duration::from_secs),
+                usage_topic: Some(value.topic_usage),
+            }),
         }
     }
 }
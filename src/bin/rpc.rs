@@ -1,12 +1,17 @@
 use std::sync::Arc;
 use std::{collections::HashMap, env, path::PathBuf, time::Duration};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use dotenv::dotenv;
+use fabric::driven::bus::{memory::MemoryBus, Bus, BusConfig, Encoding};
+use fabric::driven::kafka::dead_letter::DeadLetterConfig;
 use fabric::driven::prometheus::metrics::MetricsDriven;
 use fabric::drivers::{
-    cache::{CacheConfig, CacheNotifyConfig},
+    cache::{CacheConfig, CacheNotifyConfig, CacheSnapshotConfig},
     grpc::{GrpcConfig, GrpcTlsConfig},
+    outbox::RelayConfig,
+    retry::RetryPolicy,
+    usage::{RollupSchedule, UsageRollupConfig},
 };
 use serde::{de::Visitor, Deserialize, Deserializer};
 use tokio::try_join;
@@ -32,13 +37,48 @@ async fn main() -> Result<()> {
 
     let config = Config::new()?;
 
+    fabric::drivers::cache::check_schema(
+        &config.db_path,
+        config
+            .postgres
+            .as_ref()
+            .map(|postgres| postgres.url.as_str()),
+    )
+    .await?;
+    if config.postgres.is_some() && config.usage_rollup.is_some() {
+        bail!("the usage rollup is only supported by the sqlite cache");
+    }
+
     let metrics_driven = Arc::new(MetricsDriven::new()?);
 
     let grpc = fabric::drivers::grpc::server(config.clone().into(), metrics_driven.clone());
     let subscribe = fabric::drivers::cache::subscribe(config.clone().into());
     let metrics = fabric::drivers::metrics::server(&config.prometheus.addr, metrics_driven.clone());
+    let relay = async {
+        match Option::<RelayConfig>::from(config.clone()) {
+            Some(relay_config) => fabric::drivers::outbox::relay(relay_config).await,
+            None => Ok(()),
+        }
+    };
+
+    let rollup = async {
+        match config.usage_rollup.clone() {
+            Some(schedule) => {
+                let rollup_config = UsageRollupConfig::new(config.db_path.clone(), schedule);
+                fabric::drivers::usage::rollup(rollup_config).await
+            }
+            None => Ok(()),
+        }
+    };
+
+    let webhook = async {
+        match Option::<fabric::drivers::webhook::WebhookConfig>::from(config.clone()) {
+            Some(webhook_config) => fabric::drivers::webhook::server(webhook_config).await,
+            None => Ok(()),
+        }
+    };
 
-    try_join!(grpc, subscribe, metrics)?;
+    try_join!(grpc, subscribe, metrics, relay, rollup, webhook)?;
 
     Ok(())
 }
@@ -80,6 +120,32 @@ struct BaliusConfig {
     vault_address: String,
     vault_token: String,
 }
+#[derive(Debug, Clone, Deserialize)]
+struct OutboxConfig {
+    db_path: String,
+    delay_ms: Option<u64>,
+    max_backoff_ms: Option<u64>,
+}
+#[derive(Debug, Clone, Deserialize)]
+struct PostgresConfig {
+    /// Database with the projects, resources and usages, shared by every replica. Without it each
+    /// replica keeps them in the sqlite file at `db_path`.
+    url: String,
+}
+#[derive(Debug, Clone, Deserialize)]
+struct SnapshotConfig {
+    topic: String,
+    /// Publishes the snapshots of the projects in the cache at this interval, when missing the
+    /// topic is only used to restore the cache.
+    interval_sec: Option<u64>,
+}
+#[derive(Debug, Clone, Deserialize)]
+struct WebhookConfig {
+    addr: String,
+    /// Signing secret of the Stripe webhook endpoint.
+    stripe_secret: String,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 struct Config {
     addr: String,
@@ -97,6 +163,20 @@ struct Config {
     kafka_consumer: HashMap<String, String>,
     prometheus: PrometheusConfig,
     balius: Option<BaliusConfig>,
+    outbox: Option<OutboxConfig>,
+    topic_dead_letter: Option<String>,
+    retry: Option<RetryPolicy>,
+    bus: Option<BusConfig>,
+    #[serde(default)]
+    event_encoding: Encoding,
+    #[serde(skip)]
+    memory_bus: MemoryBus,
+    snapshot: Option<SnapshotConfig>,
+    postgres: Option<PostgresConfig>,
+    usage_rollup: Option<RollupSchedule>,
+    webhook: Option<WebhookConfig>,
+    #[serde(default)]
+    clusters: Vec<String>,
 }
 impl Config {
     pub fn new() -> Result<Self> {
@@ -111,13 +191,24 @@ impl Config {
 
         Ok(config)
     }
+
+    fn bus(&self, kafka: &HashMap<String, String>) -> Bus {
+        Bus::new(
+            self.bus.as_ref(),
+            kafka,
+            self.event_encoding,
+            &self.memory_bus,
+        )
+    }
 }
 
 impl From<Config> for GrpcConfig {
     fn from(value: Config) -> Self {
         Self {
+            bus: value.bus(&value.kafka_producer),
             addr: value.addr,
             db_path: value.db_path,
+            postgres_url: value.postgres.map(|postgres| postgres.url),
             crds_path: value.crds_path,
             auth_url: value.auth.url,
             auth_client_id: value.auth.client_id,
@@ -126,8 +217,8 @@ impl From<Config> for GrpcConfig {
             stripe_url: value.stripe.url,
             stripe_api_key: value.stripe.api_key,
             secret: value.secret,
-            kafka: value.kafka_producer,
             topic: value.topic_events,
+            outbox_path: value.outbox.map(|outbox| outbox.db_path),
             invite_ttl: value.email.invite_ttl,
             ses_access_key_id: value.email.ses_access_key_id,
             ses_secret_access_key: value.email.ses_secret_access_key,
@@ -140,6 +231,7 @@ impl From<Config> for GrpcConfig {
             balius_pg_url: value.balius.as_ref().map(|b| b.pg_url.clone()),
             balius_vault_address: value.balius.as_ref().map(|b| b.vault_address.clone()),
             balius_vault_token: value.balius.as_ref().map(|b| b.vault_token.clone()),
+            clusters: value.clusters,
         }
     }
 }
@@ -147,9 +239,15 @@ impl From<Config> for GrpcConfig {
 impl From<Config> for CacheConfig {
     fn from(value: Config) -> Self {
         Self {
-            kafka: value.kafka_consumer,
+            bus: value.bus(&value.kafka_consumer),
+            dead_letter: value.topic_dead_letter.map(|topic| DeadLetterConfig {
+                topic,
+                kafka: value.kafka_producer,
+            }),
+            retry: value.retry.unwrap_or_default(),
             db_path: value.db_path,
-            topics: [value.topic_events, value.topic_usage].to_vec(),
+            postgres_url: value.postgres.map(|postgres| postgres.url),
+            topics: [value.topic_events, value.topic_usage.clone()].to_vec(),
             notify: value.slack_webhook_url.map(|url| CacheNotifyConfig {
                 slack_webhook_url: url,
                 auth_url: value.auth.url,
@@ -157,10 +255,45 @@ impl From<Config> for CacheConfig {
                 auth_client_secret: value.auth.client_secret,
                 auth_audience: value.auth.audience,
             }),
+            snapshot: value.snapshot.map(|snapshot| CacheSnapshotConfig {
+                topic: snapshot.topic,
+                interval: snapshot.interval_sec.map(Duration::from_secs),
+                usage_topic: Some(value.topic_usage),
+            }),
         }
     }
 }
 
+impl From<Config> for Option<RelayConfig> {
+    fn from(value: Config) -> Self {
+        let bus = value.bus(&value.kafka_producer);
+        value.outbox.map(|outbox| RelayConfig {
+            db_path: outbox.db_path,
+            topic: value.topic_events,
+            bus,
+            delay: Duration::from_millis(outbox.delay_ms.unwrap_or(500)),
+            max_backoff: Duration::from_millis(outbox.max_backoff_ms.unwrap_or(30_000)),
+        })
+    }
+}
+
+impl From<Config> for Option<fabric::drivers::webhook::WebhookConfig> {
+    fn from(value: Config) -> Self {
+        let bus = value.bus(&value.kafka_producer);
+        value
+            .webhook
+            .map(|webhook| fabric::drivers::webhook::WebhookConfig {
+                addr: webhook.addr,
+                stripe_secret: webhook.stripe_secret,
+                db_path: value.db_path,
+                postgres_url: value.postgres.map(|postgres| postgres.url),
+                outbox_path: value.outbox.map(|outbox| outbox.db_path),
+                bus,
+                topic: value.topic_events,
+            })
+    }
+}
+
 fn deserialize_duration<'de, D>(deserializer: D) -> Result<Duration, D::Error>
 where
     D: Deserializer<'de>,
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use crate::domain::{event::EventEnvelope, Result};
+
+use super::{AuditEvent, AuditFilter};
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait::async_trait]
+pub trait AuditDrivenCache: Send + Sync {
+    async fn find(
+        &self,
+        filter: &AuditFilter,
+        page: &u32,
+        page_size: &u32,
+    ) -> Result<Vec<AuditEvent>>;
+    async fn create(&self, event: &AuditEvent) -> Result<()>;
+}
+
+pub async fn create(
+    cache: Arc<dyn AuditDrivenCache>,
+    event_id: &str,
+    envelope: &EventEnvelope,
+) -> Result<()> {
+    cache.create(&AuditEvent::new(event_id, envelope)?).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::event::ResourceDeleted;
+
+    #[tokio::test]
+    async fn it_should_create_audit_event() {
+        let mut audit_cache = MockAuditDrivenCache::new();
+        audit_cache.expect_create().return_once(|_| Ok(()));
+
+        let envelope = EventEnvelope::new(ResourceDeleted::default().into());
+
+        let result = create(Arc::new(audit_cache), "id", &envelope).await;
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,224 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::{
+    auth::{assert_permission, Credential, Permission},
+    error::Error,
+    project::cache::ProjectDrivenCache,
+    Result, PAGE_SIZE_DEFAULT, PAGE_SIZE_MAX,
+};
+
+use super::{cache::AuditDrivenCache, AuditEvent, AuditFilter};
+
+/// The audit log tells who did what in the project, so it's only read by its owner. An api key
+/// holds every permission on its project, but it's meant for the resources and never reads it.
+pub async fn fetch(
+    project_cache: Arc<dyn ProjectDrivenCache>,
+    audit_cache: Arc<dyn AuditDrivenCache>,
+    cmd: FetchCmd,
+) -> Result<Vec<AuditEvent>> {
+    if let Credential::ApiKey(_) = cmd.credential {
+        return Err(Error::Unauthorized(
+            "the audit log can't be read with a secret".into(),
+        ));
+    }
+
+    assert_permission(
+        project_cache.clone(),
+        &cmd.credential,
+        &cmd.project_id,
+        Permission::AuditRead,
+    )
+    .await?;
+
+    audit_cache
+        .find(&cmd.filter, &cmd.page, &cmd.page_size)
+        .await
+}
+
+#[derive(Debug, Clone)]
+pub struct FetchCmd {
+    pub credential: Credential,
+    pub project_id: String,
+    pub filter: AuditFilter,
+    pub page: u32,
+    pub page_size: u32,
+}
+impl FetchCmd {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        credential: Credential,
+        project_id: String,
+        resource_id: Option<String>,
+        actor: Option<String>,
+        event_type: Option<String>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Self> {
+        let page = page.unwrap_or(1);
+        let page_size = page_size.unwrap_or(PAGE_SIZE_DEFAULT);
+
+        if page_size >= PAGE_SIZE_MAX {
+            return Err(Error::CommandMalformed(format!(
+                "page_size exceeded the limit of {PAGE_SIZE_MAX}"
+            )));
+        }
+
+        if let (Some(from), Some(to)) = (&from, &to) {
+            if from > to {
+                return Err(Error::CommandMalformed("from must be before to".into()));
+            }
+        }
+
+        Ok(Self {
+            credential,
+            project_id: project_id.clone(),
+            filter: AuditFilter {
+                project_id: Some(project_id),
+                resource_id,
+                actor,
+                event_type,
+                from,
+                to,
+            },
+            page,
+            page_size,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::domain::{
+        audit::cache::MockAuditDrivenCache,
+        project::{cache::MockProjectDrivenCache, ProjectUser, ProjectUserRole},
+    };
+
+    impl Default for FetchCmd {
+        fn default() -> Self {
+            let project_id = Uuid::new_v4().to_string();
+            Self {
+                credential: Credential::Auth0("user id".into()),
+                project_id: project_id.clone(),
+                filter: AuditFilter {
+                    project_id: Some(project_id),
+                    ..Default::default()
+                },
+                page: 1,
+                page_size: 12,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_fetch_project_audit() {
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+
+        let mut audit_cache = MockAuditDrivenCache::new();
+        audit_cache.expect_find().return_once(|_, _, _| Ok(vec![]));
+
+        let result = fetch(
+            Arc::new(project_cache),
+            Arc::new(audit_cache),
+            FetchCmd::default(),
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_fetch_project_audit_when_user_is_not_owner() {
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| {
+                Ok(Some(ProjectUser {
+                    role: ProjectUserRole::Member,
+                    ..Default::default()
+                }))
+            });
+
+        let audit_cache = MockAuditDrivenCache::new();
+
+        let result = fetch(
+            Arc::new(project_cache),
+            Arc::new(audit_cache),
+            FetchCmd::default(),
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_fetch_project_audit_when_user_is_not_owner_for_every_role() {
+        for role in [
+            ProjectUserRole::Admin,
+            ProjectUserRole::Developer,
+            ProjectUserRole::Member,
+            ProjectUserRole::Viewer,
+            ProjectUserRole::Billing,
+        ] {
+            let mut project_cache = MockProjectDrivenCache::new();
+            project_cache
+                .expect_find_user_permission()
+                .return_once(move |_, _| {
+                    Ok(Some(ProjectUser {
+                        role,
+                        ..Default::default()
+                    }))
+                });
+
+            let result = fetch(
+                Arc::new(project_cache),
+                Arc::new(MockAuditDrivenCache::new()),
+                FetchCmd::default(),
+            )
+            .await;
+            assert!(matches!(result, Err(Error::Unauthorized(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_fetch_project_audit_with_api_key() {
+        let cmd = FetchCmd::default();
+        let cmd = FetchCmd {
+            credential: Credential::ApiKey(cmd.project_id.clone()),
+            ..cmd
+        };
+
+        let result = fetch(
+            Arc::new(MockProjectDrivenCache::new()),
+            Arc::new(MockAuditDrivenCache::new()),
+            cmd,
+        )
+        .await;
+        assert!(matches!(result, Err(Error::Unauthorized(_))));
+    }
+
+    #[test]
+    fn it_should_fail_new_fetch_cmd_when_range_is_inverted() {
+        let now = Utc::now();
+        let result = FetchCmd::new(
+            Credential::Auth0("user id".into()),
+            Uuid::new_v4().to_string(),
+            None,
+            None,
+            None,
+            Some(now),
+            Some(now - Duration::days(1)),
+            None,
+            None,
+        );
+        assert!(result.is_err());
+    }
+}
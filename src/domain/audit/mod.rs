@@ -0,0 +1,150 @@
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+
+use super::{
+    event::{Event, EventEnvelope},
+    Result,
+};
+
+pub mod cache;
+pub mod command;
+
+/// Fields of the event payloads that carry credentials, they are never copied to the audit.
+const REDACTED_FIELDS: [&str; 3] = ["phc", "secret", "code"];
+
+/// An event as recorded in the audit, with the ids and the actor pulled out of the payload so
+/// they can be filtered.
+#[derive(Debug, Clone)]
+pub struct AuditEvent {
+    pub id: String,
+    pub event_type: String,
+    pub project_id: Option<String>,
+    pub resource_id: Option<String>,
+    pub actor: Option<String>,
+    pub payload: String,
+    pub occurred_at: DateTime<Utc>,
+}
+impl AuditEvent {
+    pub fn new(id: &str, envelope: &EventEnvelope) -> Result<Self> {
+        let event = &envelope.event;
+
+        let mut payload = serde_json::to_value(event)?;
+        if let Value::Object(fields) = &mut payload {
+            for field in REDACTED_FIELDS {
+                fields.remove(field);
+            }
+        }
+
+        let (resource_id, actor) = match event {
+            Event::ProjectCreated(evt) => (None, Some(&evt.owner)),
+            Event::ProjectRestored(evt) => (None, Some(&evt.restored_by)),
+            Event::ProjectOwnerChanged(evt) => (None, Some(&evt.changed_by)),
+            Event::ProjectSecretDeleted(evt) => (None, Some(&evt.deleted_by)),
+            Event::ProjectUserInviteAccepted(evt) => (None, Some(&evt.user_id)),
+            Event::ProjectUserInviteDeleted(evt) => (None, Some(&evt.deleted_by)),
+            Event::ProjectUserDeleted(evt) => (None, Some(&evt.deleted_by)),
+            Event::ResourceCreated(evt) => (Some(&evt.id), evt.created_by.as_ref()),
+            Event::ResourceUpdated(evt) => (Some(&evt.id), evt.updated_by.as_ref()),
+            Event::ResourceDeleted(evt) => (Some(&evt.id), evt.deleted_by.as_ref()),
+            Event::ResourceRestored(evt) => (Some(&evt.id), Some(&evt.restored_by)),
+            Event::OrganizationCreated(evt) => (None, Some(&evt.owner)),
+            Event::OrganizationUserAdded(evt) => (None, Some(&evt.added_by)),
+            Event::OrganizationUserDeleted(evt) => (None, Some(&evt.deleted_by)),
+            Event::ProjectOrganizationChanged(evt) => (None, Some(&evt.changed_by)),
+            Event::ProjectOwnershipTransferProposed(evt) => (None, Some(&evt.previous_owner)),
+            Event::ProjectOwnershipTransferAccepted(evt) => (None, Some(&evt.new_owner)),
+            Event::ProjectOwnershipTransferCancelled(evt) => (None, Some(&evt.cancelled_by)),
+            Event::ProjectQuotaUpdated(evt) => (None, Some(&evt.updated_by)),
+            Event::ProjectSuspended(evt) => (None, Some(&evt.suspended_by)),
+            Event::ProjectResumed(evt) => (None, Some(&evt.resumed_by)),
+            Event::ProjectDeleted(evt) => (None, evt.deleted_by.as_ref()),
+            Event::ProjectUpdated(_)
+            | Event::ProjectSecretCreated(_)
+            | Event::ProjectUserInviteCreated(_)
+            | Event::UsageCreated(_) => (None, None),
+        };
+
+        Ok(Self {
+            id: id.to_string(),
+            event_type: event.key(),
+            project_id: event.project_id().map(String::from),
+            resource_id: resource_id.cloned(),
+            actor: actor.cloned(),
+            payload: serde_json::to_string(&payload)?,
+            occurred_at: envelope.occurred_at.unwrap_or_else(|| timestamp(event)),
+        })
+    }
+}
+
+// Events published before the envelope carried `occurred_at` fall back to their own timestamp.
+fn timestamp(event: &Event) -> DateTime<Utc> {
+    match event {
+        Event::ProjectCreated(evt) => evt.created_at,
+        Event::ProjectUpdated(evt) => evt.updated_at,
+        Event::ProjectDeleted(evt) => evt.deleted_at,
+        Event::ProjectRestored(evt) => evt.restored_at,
+        Event::ProjectOwnerChanged(evt) => evt.changed_at,
+        Event::ProjectSecretCreated(evt) => evt.created_at,
+        Event::ProjectSecretDeleted(evt) => evt.deleted_at,
+        Event::ProjectUserInviteCreated(evt) => evt.created_at,
+        Event::ProjectUserInviteAccepted(evt) => evt.created_at,
+        Event::ProjectUserInviteDeleted(evt) => evt.deleted_at,
+        Event::ProjectUserDeleted(evt) => evt.deleted_at,
+        Event::ResourceCreated(evt) => evt.created_at,
+        Event::ResourceUpdated(evt) => evt.updated_at,
+        Event::ResourceDeleted(evt) => evt.deleted_at,
+        Event::ResourceRestored(evt) => evt.restored_at,
+        Event::UsageCreated(evt) => evt.created_at,
+        Event::OrganizationCreated(evt) => evt.created_at,
+        Event::OrganizationUserAdded(evt) => evt.created_at,
+        Event::OrganizationUserDeleted(evt) => evt.deleted_at,
+        Event::ProjectOrganizationChanged(evt) => evt.changed_at,
+        Event::ProjectOwnershipTransferProposed(evt) => evt.created_at,
+        Event::ProjectOwnershipTransferAccepted(evt) => evt.accepted_at,
+        Event::ProjectOwnershipTransferCancelled(evt) => evt.cancelled_at,
+        Event::ProjectQuotaUpdated(evt) => evt.updated_at,
+        Event::ProjectSuspended(evt) => evt.suspended_at,
+        Event::ProjectResumed(evt) => evt.resumed_at,
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditFilter {
+    pub project_id: Option<String>,
+    pub resource_id: Option<String>,
+    pub actor: Option<String>,
+    pub event_type: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::event::{ProjectSecretCreated, ProjectUserInviteDeleted, ResourceDeleted};
+
+    #[test]
+    fn it_should_extract_ids_and_actor() {
+        let evt = ProjectUserInviteDeleted::default();
+        let audit = AuditEvent::new("id", &EventEnvelope::new(evt.clone().into())).unwrap();
+        assert!(audit.project_id == Some(evt.project_id));
+        assert!(audit.actor == Some(evt.deleted_by));
+        assert!(audit.event_type == "ProjectUserInviteDeleted");
+
+        let evt = ResourceDeleted::default();
+        let audit = AuditEvent::new("id", &EventEnvelope::new(evt.clone().into())).unwrap();
+        assert!(audit.resource_id == Some(evt.id));
+        assert!(audit.actor == evt.deleted_by);
+    }
+
+    #[test]
+    fn it_should_redact_secret_fields() {
+        let evt = ProjectSecretCreated::default();
+        let audit = AuditEvent::new("id", &EventEnvelope::new(evt.clone().into())).unwrap();
+
+        let payload: Value = serde_json::from_str(&audit.payload).unwrap();
+        assert!(payload.get("phc").is_none());
+        assert!(payload.get("secret").is_none());
+        assert!(payload.get("name") == Some(&Value::String(evt.name)));
+    }
+}
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{fmt::Display, sync::Arc};
 
 use serde::Deserialize;
 
@@ -19,11 +19,24 @@ pub trait Auth0Driven: Send + Sync {
 pub type UserId = String;
 pub type SecretId = String;
 
+/// Actor recorded in the events dispatched with an api key. The credential of a key only
+/// carries its project, so the keys of a project aren't told apart.
+pub const API_KEY_ACTOR: &str = "api-key";
+
 #[derive(Debug, Clone)]
 pub enum Credential {
     Auth0(UserId),
     ApiKey(SecretId),
 }
+impl Credential {
+    /// Who acts with the credential, as recorded in the events.
+    pub fn actor(&self) -> String {
+        match self {
+            Credential::Auth0(user_id) => user_id.clone(),
+            Credential::ApiKey(_) => API_KEY_ACTOR.into(),
+        }
+    }
+}
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Auth0Profile {
@@ -32,39 +45,82 @@ pub struct Auth0Profile {
     pub email: String,
 }
 
+/// Actions a project role can be granted, named `<scope>.<action>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    ProjectRead,
+    ProjectUpdate,
+    ProjectDelete,
+    ProjectTransfer,
+    ResourceRead,
+    ResourceCreate,
+    ResourceUpdate,
+    ResourceDelete,
+    ResourceRestore,
+    SecretRead,
+    SecretCreate,
+    SecretDelete,
+    UsageRead,
+    AuditRead,
+    UserRead,
+    UserInvite,
+    UserDelete,
+}
+impl Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Permission::ProjectRead => write!(f, "project.read"),
+            Permission::ProjectUpdate => write!(f, "project.update"),
+            Permission::ProjectDelete => write!(f, "project.delete"),
+            Permission::ProjectTransfer => write!(f, "project.transfer"),
+            Permission::ResourceRead => write!(f, "resource.read"),
+            Permission::ResourceCreate => write!(f, "resource.create"),
+            Permission::ResourceUpdate => write!(f, "resource.update"),
+            Permission::ResourceDelete => write!(f, "resource.delete"),
+            Permission::ResourceRestore => write!(f, "resource.restore"),
+            Permission::SecretRead => write!(f, "secret.read"),
+            Permission::SecretCreate => write!(f, "secret.create"),
+            Permission::SecretDelete => write!(f, "secret.delete"),
+            Permission::UsageRead => write!(f, "usage.read"),
+            Permission::AuditRead => write!(f, "audit.read"),
+            Permission::UserRead => write!(f, "user.read"),
+            Permission::UserInvite => write!(f, "user.invite"),
+            Permission::UserDelete => write!(f, "user.delete"),
+        }
+    }
+}
+
+/// Checks that the credential holds `permission` on the project and returns the role of the
+/// user, `None` for an api key, which is bound to its project and holds every permission on it.
 pub async fn assert_permission(
     project_cache: Arc<dyn ProjectDrivenCache>,
     credential: &Credential,
     project_id: &str,
-    role: Option<ProjectUserRole>,
-) -> Result<()> {
+    permission: Permission,
+) -> Result<Option<ProjectUserRole>> {
     match credential {
         Credential::Auth0(user_id) => {
-            let result = project_cache
+            let Some(project_user) = project_cache
                 .find_user_permission(user_id, project_id)
-                .await?;
-
-            if result.is_none() {
+                .await?
+            else {
                 return Err(Error::Unauthorized("user doesnt have permission".into()));
-            }
+            };
 
-            match role {
-                Some(role) => {
-                    let permission = result.unwrap();
-                    if role != permission.role {
-                        return Err(Error::Unauthorized("user doesnt have permission".into()));
-                    }
-                    Ok(())
-                }
-                None => Ok(()),
+            if !project_user.role.has_permission(permission) {
+                return Err(Error::Unauthorized(format!(
+                    "user doesnt have the {permission} permission"
+                )));
             }
+
+            Ok(Some(project_user.role))
         }
         Credential::ApiKey(secret_project_id) => {
             if project_id != secret_project_id {
                 return Err(Error::Unauthorized("secret doesnt have permission".into()));
             }
 
-            Ok(())
+            Ok(None)
         }
     }
 }
@@ -1,3 +1,4 @@
+use serde::Deserialize;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -11,6 +12,35 @@ pub enum Error {
     #[error("{0}")]
     Unexpected(String),
 }
+impl Error {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Unauthorized(_) => ErrorKind::Unauthorized,
+            Self::CommandMalformed(_) => ErrorKind::CommandMalformed,
+            Self::SecretExceeded(_) => ErrorKind::SecretExceeded,
+            Self::Unexpected(_) => ErrorKind::Unexpected,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    Unauthorized,
+    CommandMalformed,
+    SecretExceeded,
+    Unexpected,
+}
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unauthorized => write!(f, "unauthorized"),
+            Self::CommandMalformed => write!(f, "command_malformed"),
+            Self::SecretExceeded => write!(f, "secret_exceeded"),
+            Self::Unexpected => write!(f, "unexpected"),
+        }
+    }
+}
 
 impl From<argon2::password_hash::Error> for Error {
     fn from(value: argon2::password_hash::Error) -> Self {
@@ -37,6 +67,11 @@ impl From<serde_json::Error> for Error {
         Self::Unexpected(value.to_string())
     }
 }
+impl From<prost::DecodeError> for Error {
+    fn from(value: prost::DecodeError) -> Self {
+        Self::Unexpected(value.to_string())
+    }
+}
 impl From<sqlx::Error> for Error {
     fn from(value: sqlx::Error) -> Self {
         Self::Unexpected(value.to_string())
@@ -1,9 +1,19 @@
 use chrono::{DateTime, Utc};
+use prost::Message;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::HashMap, time::Duration};
+use uuid::Uuid;
 
 use crate::domain::Result;
 
-use super::{error::Error, DEFAULT_CATEGORY};
+use super::error::Error;
+
+mod proto;
+mod upcaster;
+
+const NAME: &str = env!("CARGO_PKG_NAME");
+const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 macro_rules! into_event {
     ($name:ident) => {
@@ -26,6 +36,8 @@ pub struct ProjectCreated {
     pub billing_provider_id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub billing_subscription_id: Option<String>,
+    /// Cluster the resources of the project are placed on when they don't choose one.
+    pub cluster_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -38,6 +50,13 @@ pub struct ProjectUpdated {
     pub name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub status: Option<String>,
+    /// Set by the billing webhooks, the subscription the project is charged on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub billing_subscription_id: Option<String>,
+    /// Set by the billing webhooks when the subscription was deleted, the project is no longer
+    /// charged on one.
+    #[serde(default)]
+    pub billing_subscription_deleted: bool,
     pub updated_at: DateTime<Utc>,
 }
 into_event!(ProjectUpdated);
@@ -46,10 +65,21 @@ into_event!(ProjectUpdated);
 pub struct ProjectDeleted {
     pub id: String,
     pub namespace: String,
+    /// `None` in the events published before the actor was recorded.
+    pub deleted_by: Option<String>,
     pub deleted_at: DateTime<Utc>,
 }
 into_event!(ProjectDeleted);
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectRestored {
+    pub id: String,
+    pub namespace: String,
+    pub restored_by: String,
+    pub restored_at: DateTime<Utc>,
+}
+into_event!(ProjectRestored);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectOwnerChanged {
     pub id: String,
@@ -61,6 +91,88 @@ pub struct ProjectOwnerChanged {
 }
 into_event!(ProjectOwnerChanged);
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectOrganizationChanged {
+    pub id: String,
+    pub project_id: String,
+    /// `None` when the project is detached from its organization.
+    pub organization_id: Option<String>,
+    /// Billing customer of the project from now on.
+    pub billing_provider_id: String,
+    pub changed_by: String,
+    pub changed_at: DateTime<Utc>,
+}
+into_event!(ProjectOrganizationChanged);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectOwnershipTransferProposed {
+    pub id: String,
+    pub project_id: String,
+    /// Owner when the transfer was proposed, it's only accepted while they still are.
+    pub previous_owner: String,
+    pub new_owner: String,
+    pub code: String,
+    pub expires_in: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+into_event!(ProjectOwnershipTransferProposed);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectOwnershipTransferAccepted {
+    pub id: String,
+    pub project_id: String,
+    pub previous_owner: String,
+    pub new_owner: String,
+    pub accepted_at: DateTime<Utc>,
+}
+into_event!(ProjectOwnershipTransferAccepted);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectOwnershipTransferCancelled {
+    pub id: String,
+    pub project_id: String,
+    pub cancelled_by: String,
+    pub cancelled_at: DateTime<Utc>,
+}
+into_event!(ProjectOwnershipTransferCancelled);
+
+/// Replaces the quota of a project, or the default quota of every project without
+/// `project_id`. A limit left empty isn't enforced, or comes from the default quota.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectQuotaUpdated {
+    pub id: String,
+    pub project_id: Option<String>,
+    /// Maximum of resources of each kind.
+    pub resources: HashMap<String, u32>,
+    /// Highest `throughputTier` of the resources.
+    pub max_tier: Option<u32>,
+    pub workers: Option<u32>,
+    pub updated_by: String,
+    pub updated_at: DateTime<Utc>,
+}
+into_event!(ProjectQuotaUpdated);
+
+/// Stops the project until it's resumed: the resources of its namespace are suspended on the
+/// cluster and its API keys are refused.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectSuspended {
+    pub id: String,
+    pub namespace: String,
+    pub reason: String,
+    pub suspended_by: String,
+    pub suspended_at: DateTime<Utc>,
+}
+into_event!(ProjectSuspended);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectResumed {
+    pub id: String,
+    pub namespace: String,
+    pub resumed_by: String,
+    pub resumed_at: DateTime<Utc>,
+}
+into_event!(ProjectResumed);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectSecretCreated {
     pub id: String,
@@ -129,19 +241,17 @@ pub struct ResourceCreated {
     pub project_namespace: String,
     pub name: String,
     pub kind: String,
-    #[serde(default = "default_resource_category")]
     pub category: String,
     pub spec: String,
     pub status: String,
+    pub cluster_id: Option<String>,
+    /// `None` in the events published before the actor was recorded.
+    pub created_by: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 into_event!(ResourceCreated);
 
-fn default_resource_category() -> String {
-    DEFAULT_CATEGORY.to_string()
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResourceUpdated {
     pub id: String,
@@ -150,6 +260,9 @@ pub struct ResourceUpdated {
     pub name: String,
     pub kind: String,
     pub spec_patch: String,
+    pub cluster_id: Option<String>,
+    /// `None` in the events published before the actor was recorded.
+    pub updated_by: Option<String>,
     pub updated_at: DateTime<Utc>,
 }
 into_event!(ResourceUpdated);
@@ -162,10 +275,61 @@ pub struct ResourceDeleted {
     pub name: String,
     pub kind: String,
     pub status: String,
+    pub cluster_id: Option<String>,
+    /// `None` in the events published before the actor was recorded.
+    pub deleted_by: Option<String>,
     pub deleted_at: DateTime<Utc>,
 }
 into_event!(ResourceDeleted);
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceRestored {
+    pub id: String,
+    pub project_id: String,
+    pub project_namespace: String,
+    pub name: String,
+    pub kind: String,
+    pub category: String,
+    pub spec: String,
+    pub cluster_id: Option<String>,
+    pub restored_by: String,
+    pub restored_at: DateTime<Utc>,
+}
+into_event!(ResourceRestored);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizationCreated {
+    pub id: String,
+    pub name: String,
+    pub owner: String,
+    pub billing_provider: String,
+    pub billing_provider_id: String,
+    pub created_at: DateTime<Utc>,
+}
+into_event!(OrganizationCreated);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizationUserAdded {
+    pub id: String,
+    pub organization_id: String,
+    pub user_id: String,
+    pub role: String,
+    pub added_by: String,
+    pub created_at: DateTime<Utc>,
+}
+into_event!(OrganizationUserAdded);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizationUserDeleted {
+    pub id: String,
+    pub organization_id: String,
+    pub user_id: String,
+    pub role: String,
+    pub deleted_by: String,
+    pub deleted_at: DateTime<Utc>,
+}
+into_event!(OrganizationUserDeleted);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsageUnitCreated {
     pub resource_id: String,
@@ -191,6 +355,7 @@ pub enum Event {
     ProjectCreated(ProjectCreated),
     ProjectUpdated(ProjectUpdated),
     ProjectDeleted(ProjectDeleted),
+    ProjectRestored(ProjectRestored),
     ProjectOwnerChanged(ProjectOwnerChanged),
     ProjectSecretCreated(ProjectSecretCreated),
     ProjectSecretDeleted(ProjectSecretDeleted),
@@ -201,7 +366,18 @@ pub enum Event {
     ResourceCreated(ResourceCreated),
     ResourceUpdated(ResourceUpdated),
     ResourceDeleted(ResourceDeleted),
+    ResourceRestored(ResourceRestored),
     UsageCreated(UsageCreated),
+    OrganizationCreated(OrganizationCreated),
+    OrganizationUserAdded(OrganizationUserAdded),
+    OrganizationUserDeleted(OrganizationUserDeleted),
+    ProjectOrganizationChanged(ProjectOrganizationChanged),
+    ProjectOwnershipTransferProposed(ProjectOwnershipTransferProposed),
+    ProjectOwnershipTransferAccepted(ProjectOwnershipTransferAccepted),
+    ProjectOwnershipTransferCancelled(ProjectOwnershipTransferCancelled),
+    ProjectQuotaUpdated(ProjectQuotaUpdated),
+    ProjectSuspended(ProjectSuspended),
+    ProjectResumed(ProjectResumed),
 }
 impl Event {
     pub fn key(&self) -> String {
@@ -209,6 +385,7 @@ impl Event {
             Event::ProjectCreated(_) => "ProjectCreated".into(),
             Event::ProjectUpdated(_) => "ProjectUpdated".into(),
             Event::ProjectDeleted(_) => "ProjectDeleted".into(),
+            Event::ProjectRestored(_) => "ProjectRestored".into(),
             Event::ProjectOwnerChanged(_) => "ProjectOwnerChanged".into(),
             Event::ProjectSecretCreated(_) => "ProjectSecretCreated".into(),
             Event::ProjectSecretDeleted(_) => "ProjectSecretDeleted".into(),
@@ -219,37 +396,129 @@ impl Event {
             Event::ResourceCreated(_) => "ResourceCreated".into(),
             Event::ResourceUpdated(_) => "ResourceUpdated".into(),
             Event::ResourceDeleted(_) => "ResourceDeleted".into(),
+            Event::ResourceRestored(_) => "ResourceRestored".into(),
             Event::UsageCreated(_) => "UsageCreated".into(),
+            Event::OrganizationCreated(_) => "OrganizationCreated".into(),
+            Event::OrganizationUserAdded(_) => "OrganizationUserAdded".into(),
+            Event::OrganizationUserDeleted(_) => "OrganizationUserDeleted".into(),
+            Event::ProjectOrganizationChanged(_) => "ProjectOrganizationChanged".into(),
+            Event::ProjectOwnershipTransferProposed(_) => "ProjectOwnershipTransferProposed".into(),
+            Event::ProjectOwnershipTransferAccepted(_) => "ProjectOwnershipTransferAccepted".into(),
+            Event::ProjectOwnershipTransferCancelled(_) => {
+                "ProjectOwnershipTransferCancelled".into()
+            }
+            Event::ProjectQuotaUpdated(_) => "ProjectQuotaUpdated".into(),
+            Event::ProjectSuspended(_) => "ProjectSuspended".into(),
+            Event::ProjectResumed(_) => "ProjectResumed".into(),
         }
     }
+    /// Project the event belongs to. A deleted secret only carries its own id, and the
+    /// organization events belong to every project of the organization.
+    pub fn project_id(&self) -> Option<&str> {
+        let project_id = match self {
+            Event::ProjectCreated(evt) => &evt.id,
+            Event::ProjectUpdated(evt) => &evt.id,
+            Event::ProjectDeleted(evt) => &evt.id,
+            Event::ProjectRestored(evt) => &evt.id,
+            Event::ProjectOwnerChanged(evt) => &evt.project_id,
+            Event::ProjectSecretCreated(evt) => &evt.project_id,
+            Event::ProjectSecretDeleted(_) => return None,
+            Event::ProjectUserInviteCreated(evt) => &evt.project_id,
+            Event::ProjectUserInviteAccepted(evt) => &evt.project_id,
+            Event::ProjectUserInviteDeleted(evt) => &evt.project_id,
+            Event::ProjectUserDeleted(evt) => &evt.project_id,
+            Event::ResourceCreated(evt) => &evt.project_id,
+            Event::ResourceUpdated(evt) => &evt.project_id,
+            Event::ResourceDeleted(evt) => &evt.project_id,
+            Event::ResourceRestored(evt) => &evt.project_id,
+            Event::UsageCreated(evt) => &evt.project_id,
+            Event::ProjectOrganizationChanged(evt) => &evt.project_id,
+            Event::ProjectOwnershipTransferProposed(evt) => &evt.project_id,
+            Event::ProjectOwnershipTransferAccepted(evt) => &evt.project_id,
+            Event::ProjectOwnershipTransferCancelled(evt) => &evt.project_id,
+            Event::ProjectQuotaUpdated(evt) => return evt.project_id.as_deref(),
+            Event::ProjectSuspended(evt) => &evt.id,
+            Event::ProjectResumed(evt) => &evt.id,
+            Event::OrganizationCreated(_)
+            | Event::OrganizationUserAdded(_)
+            | Event::OrganizationUserDeleted(_) => return None,
+        };
+        Some(project_id)
+    }
+    /// Cluster that must apply the event. `None` for the events that aren't placed, which
+    /// every cluster applies, e.g. the project namespace or a resource published before
+    /// placement existed.
+    pub fn cluster_id(&self) -> Option<&str> {
+        match self {
+            Event::ResourceCreated(evt) => evt.cluster_id.as_deref(),
+            Event::ResourceUpdated(evt) => evt.cluster_id.as_deref(),
+            Event::ResourceDeleted(evt) => evt.cluster_id.as_deref(),
+            Event::ResourceRestored(evt) => evt.cluster_id.as_deref(),
+            _ => None,
+        }
+    }
+    pub fn schema_version(&self) -> u32 {
+        upcaster::current_version(&self.key())
+    }
     pub fn from_key(key: &str, payload: &[u8]) -> Result<Self> {
+        Ok(EventEnvelope::from_key(key, payload)?.event)
+    }
+    fn from_value(key: &str, payload: Value) -> Result<Self> {
         match key {
-            "ProjectCreated" => Ok(Self::ProjectCreated(serde_json::from_slice(payload)?)),
-            "ProjectUpdated" => Ok(Self::ProjectUpdated(serde_json::from_slice(payload)?)),
-            "ProjectDeleted" => Ok(Self::ProjectDeleted(serde_json::from_slice(payload)?)),
+            "ProjectCreated" => Ok(Self::ProjectCreated(serde_json::from_value(payload)?)),
+            "ProjectUpdated" => Ok(Self::ProjectUpdated(serde_json::from_value(payload)?)),
+            "ProjectDeleted" => Ok(Self::ProjectDeleted(serde_json::from_value(payload)?)),
+            "ProjectRestored" => Ok(Self::ProjectRestored(serde_json::from_value(payload)?)),
             "ProjectOwnerChanged" => {
-                Ok(Self::ProjectOwnerChanged(serde_json::from_slice(payload)?))
+                Ok(Self::ProjectOwnerChanged(serde_json::from_value(payload)?))
             }
             "ProjectSecretCreated" => {
-                Ok(Self::ProjectSecretCreated(serde_json::from_slice(payload)?))
+                Ok(Self::ProjectSecretCreated(serde_json::from_value(payload)?))
             }
             "ProjectSecretDeleted" => {
-                Ok(Self::ProjectSecretDeleted(serde_json::from_slice(payload)?))
+                Ok(Self::ProjectSecretDeleted(serde_json::from_value(payload)?))
             }
             "ProjectUserInviteCreated" => Ok(Self::ProjectUserInviteCreated(
-                serde_json::from_slice(payload)?,
+                serde_json::from_value(payload)?,
             )),
             "ProjectUserInviteAccepted" => Ok(Self::ProjectUserInviteAccepted(
-                serde_json::from_slice(payload)?,
+                serde_json::from_value(payload)?,
             )),
             "ProjectUserInviteDeleted" => Ok(Self::ProjectUserInviteDeleted(
-                serde_json::from_slice(payload)?,
+                serde_json::from_value(payload)?,
+            )),
+            "ProjectUserDeleted" => Ok(Self::ProjectUserDeleted(serde_json::from_value(payload)?)),
+            "ResourceCreated" => Ok(Self::ResourceCreated(serde_json::from_value(payload)?)),
+            "ResourceUpdated" => Ok(Self::ResourceUpdated(serde_json::from_value(payload)?)),
+            "ResourceDeleted" => Ok(Self::ResourceDeleted(serde_json::from_value(payload)?)),
+            "ResourceRestored" => Ok(Self::ResourceRestored(serde_json::from_value(payload)?)),
+            "UsageCreated" => Ok(Self::UsageCreated(serde_json::from_value(payload)?)),
+            "OrganizationCreated" => {
+                Ok(Self::OrganizationCreated(serde_json::from_value(payload)?))
+            }
+            "OrganizationUserAdded" => Ok(Self::OrganizationUserAdded(serde_json::from_value(
+                payload,
+            )?)),
+            "OrganizationUserDeleted" => Ok(Self::OrganizationUserDeleted(serde_json::from_value(
+                payload,
+            )?)),
+            "ProjectOrganizationChanged" => Ok(Self::ProjectOrganizationChanged(
+                serde_json::from_value(payload)?,
+            )),
+            "ProjectOwnershipTransferProposed" => Ok(Self::ProjectOwnershipTransferProposed(
+                serde_json::from_value(payload)?,
+            )),
+            "ProjectOwnershipTransferAccepted" => Ok(Self::ProjectOwnershipTransferAccepted(
+                serde_json::from_value(payload)?,
+            )),
+            "ProjectOwnershipTransferCancelled" => Ok(Self::ProjectOwnershipTransferCancelled(
+                serde_json::from_value(payload)?,
             )),
-            "ProjectUserDeleted" => Ok(Self::ProjectUserDeleted(serde_json::from_slice(payload)?)),
-            "ResourceCreated" => Ok(Self::ResourceCreated(serde_json::from_slice(payload)?)),
-            "ResourceUpdated" => Ok(Self::ResourceUpdated(serde_json::from_slice(payload)?)),
-            "ResourceDeleted" => Ok(Self::ResourceDeleted(serde_json::from_slice(payload)?)),
-            "UsageCreated" => Ok(Self::UsageCreated(serde_json::from_slice(payload)?)),
+            "ProjectQuotaUpdated" => {
+                Ok(Self::ProjectQuotaUpdated(serde_json::from_value(payload)?))
+            }
+            "ProjectSuspended" => Ok(Self::ProjectSuspended(serde_json::from_value(payload)?)),
+            "ProjectResumed" => Ok(Self::ProjectResumed(serde_json::from_value(payload)?)),
             _ => Err(Error::Unexpected(format!(
                 "Event key '{key}' not implemented"
             ))),
@@ -257,10 +526,161 @@ impl Event {
     }
 }
 
+/// Metadata sent together with the event. On the wire it is the `annotation` field of the
+/// payload, events published before the envelope existed only have `source` there, or nothing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Annotation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    event_id: Option<String>,
+    #[serde(default = "default_schema_version")]
+    schema_version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    occurred_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    source: String,
+}
+fn default_schema_version() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone)]
+pub struct EventEnvelope {
+    pub event_id: Option<String>,
+    pub schema_version: u32,
+    pub occurred_at: Option<DateTime<Utc>>,
+    pub source: String,
+    pub event: Event,
+}
+impl EventEnvelope {
+    pub fn new(event: Event) -> Self {
+        Self {
+            event_id: Some(Uuid::new_v4().to_string()),
+            schema_version: event.schema_version(),
+            occurred_at: Some(Utc::now()),
+            source: format!("{NAME}-{VERSION}"),
+            event,
+        }
+    }
+
+    pub fn key(&self) -> String {
+        self.event.key()
+    }
+
+    pub fn to_payload(&self) -> Result<Vec<u8>> {
+        let Some(mut payload) = serde_json::to_value(&self.event)?.as_object().cloned() else {
+            return Err(Error::Unexpected("invalid event structure".into()));
+        };
+
+        let annotation = Annotation {
+            event_id: self.event_id.clone(),
+            schema_version: self.schema_version,
+            occurred_at: self.occurred_at,
+            source: self.source.clone(),
+        };
+        payload.insert("annotation".into(), serde_json::to_value(annotation)?);
+
+        Ok(serde_json::to_vec(&payload)?)
+    }
+
+    pub fn to_protobuf(&self) -> Result<Vec<u8>> {
+        Ok(proto::EventEnvelope::from(self.clone()).encode_to_vec())
+    }
+
+    /// Decodes the payload published with `key` in any of the encodings. Json payloads are
+    /// objects starting with `{`, a byte that would be a group tag in protobuf, which the
+    /// envelope doesn't use.
+    pub fn from_key(key: &str, payload: &[u8]) -> Result<Self> {
+        match payload.first() {
+            Some(b'{') => Self::from_json(key, payload),
+            _ => Self::from_protobuf(key, payload),
+        }
+    }
+
+    /// Decodes a json payload, upcasting it to the current schema version of the event when it
+    /// was written by an older version.
+    pub fn from_json(key: &str, payload: &[u8]) -> Result<Self> {
+        let Value::Object(mut payload) = serde_json::from_slice(payload)? else {
+            return Err(Error::Unexpected("invalid event structure".into()));
+        };
+
+        let annotation: Annotation = match payload.remove("annotation") {
+            Some(annotation) => serde_json::from_value(annotation)?,
+            None => Annotation {
+                event_id: None,
+                schema_version: default_schema_version(),
+                occurred_at: None,
+                source: String::default(),
+            },
+        };
+
+        let payload = upcaster::upcast(key, annotation.schema_version, payload)?;
+        let event = Event::from_value(key, Value::Object(payload))?;
+
+        Ok(Self {
+            event_id: annotation.event_id,
+            schema_version: event.schema_version(),
+            occurred_at: annotation.occurred_at,
+            source: annotation.source,
+            event,
+        })
+    }
+
+    /// Decodes a protobuf payload. Fields added after the message was written decode to their
+    /// defaults, so the event goes through the same upcasters as the json payloads to fill them
+    /// in. proto3 drops a zero `schema_version`, it's read as the first version.
+    pub fn from_protobuf(key: &str, payload: &[u8]) -> Result<Self> {
+        let envelope = proto::EventEnvelope::decode(payload)?;
+        let Some(event) = envelope.event else {
+            return Err(Error::Unexpected("event with empty payload".into()));
+        };
+        let event = Event::try_from(event)?;
+
+        if event.key() != key {
+            return Err(Error::Unexpected(format!(
+                "Event key '{key}' doesn't match the payload '{}'",
+                event.key()
+            )));
+        }
+
+        let Value::Object(payload) = serde_json::to_value(&event)? else {
+            return Err(Error::Unexpected("invalid event structure".into()));
+        };
+        let schema_version = envelope.schema_version.max(default_schema_version());
+        let payload = upcaster::upcast(key, schema_version, payload)?;
+        let event = Event::from_value(key, Value::Object(payload))?;
+
+        let occurred_at = envelope
+            .occurred_at
+            .and_then(|t| DateTime::from_timestamp(t.seconds, t.nanos as u32));
+
+        Ok(Self {
+            event_id: envelope.event_id,
+            schema_version: event.schema_version(),
+            occurred_at,
+            source: envelope.source,
+            event,
+        })
+    }
+}
+
+/// How long a command waits for the cache to apply the event it dispatched before reading it
+/// back, the read returns the previous state when it's exceeded.
+pub const APPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Position where a dispatched event was written in the topic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventOffset {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+}
+
 #[cfg_attr(test, mockall::automock)]
 #[async_trait::async_trait]
 pub trait EventDrivenBridge: Send + Sync {
-    async fn dispatch(&self, event: Event) -> Result<()>;
+    /// Returns the offset of the event when the bridge knows it once dispatched, the outbox only
+    /// publishes it later.
+    async fn dispatch(&self, event: Event) -> Result<Option<EventOffset>>;
 }
 
 #[cfg(test)]
@@ -274,6 +694,7 @@ mod tests {
         resource::ResourceStatus,
         tests::{PHC, SECRET},
         utils::get_random_salt,
+        DEFAULT_CATEGORY,
     };
 
     use super::*;
@@ -289,6 +710,7 @@ mod tests {
                 billing_provider: "stripe".into(),
                 billing_provider_id: "stripe id".into(),
                 billing_subscription_id: None,
+                cluster_id: None,
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
             }
@@ -306,6 +728,122 @@ mod tests {
             }
         }
     }
+    impl Default for ProjectOrganizationChanged {
+        fn default() -> Self {
+            Self {
+                id: Uuid::new_v4().to_string(),
+                project_id: Uuid::new_v4().to_string(),
+                organization_id: Some(Uuid::new_v4().to_string()),
+                billing_provider_id: "organization stripe id".into(),
+                changed_by: "user id".into(),
+                changed_at: Utc::now(),
+            }
+        }
+    }
+    impl Default for ProjectOwnershipTransferProposed {
+        fn default() -> Self {
+            Self {
+                id: Uuid::new_v4().to_string(),
+                project_id: Uuid::new_v4().to_string(),
+                previous_owner: "user id".into(),
+                new_owner: "member user id".into(),
+                code: "123".into(),
+                expires_in: Utc::now() + Duration::from_secs(15 * 60),
+                created_at: Utc::now(),
+            }
+        }
+    }
+    impl Default for ProjectOwnershipTransferAccepted {
+        fn default() -> Self {
+            Self {
+                id: Uuid::new_v4().to_string(),
+                project_id: Uuid::new_v4().to_string(),
+                previous_owner: "user id".into(),
+                new_owner: "member user id".into(),
+                accepted_at: Utc::now(),
+            }
+        }
+    }
+    impl Default for ProjectOwnershipTransferCancelled {
+        fn default() -> Self {
+            Self {
+                id: Uuid::new_v4().to_string(),
+                project_id: Uuid::new_v4().to_string(),
+                cancelled_by: "user id".into(),
+                cancelled_at: Utc::now(),
+            }
+        }
+    }
+    impl Default for ProjectQuotaUpdated {
+        fn default() -> Self {
+            Self {
+                id: Uuid::new_v4().to_string(),
+                project_id: Some(Uuid::new_v4().to_string()),
+                resources: HashMap::from([("CardanoNodePort".into(), 2)]),
+                max_tier: Some(1),
+                workers: Some(1),
+                updated_by: "backoffice".into(),
+                updated_at: Utc::now(),
+            }
+        }
+    }
+    impl Default for ProjectSuspended {
+        fn default() -> Self {
+            Self {
+                id: Uuid::new_v4().to_string(),
+                namespace: "sonic-vegas".into(),
+                reason: "payment method failed".into(),
+                suspended_by: "backoffice".into(),
+                suspended_at: Utc::now(),
+            }
+        }
+    }
+    impl Default for ProjectResumed {
+        fn default() -> Self {
+            Self {
+                id: Uuid::new_v4().to_string(),
+                namespace: "sonic-vegas".into(),
+                resumed_by: "backoffice".into(),
+                resumed_at: Utc::now(),
+            }
+        }
+    }
+    impl Default for OrganizationCreated {
+        fn default() -> Self {
+            Self {
+                id: Uuid::new_v4().to_string(),
+                name: "New Organization".into(),
+                owner: "user id".into(),
+                billing_provider: "stripe".into(),
+                billing_provider_id: "organization stripe id".into(),
+                created_at: Utc::now(),
+            }
+        }
+    }
+    impl Default for OrganizationUserAdded {
+        fn default() -> Self {
+            Self {
+                id: Uuid::new_v4().to_string(),
+                organization_id: Uuid::new_v4().to_string(),
+                user_id: "new user id".into(),
+                role: ProjectUserRole::Member.to_string(),
+                added_by: "user id".into(),
+                created_at: Utc::now(),
+            }
+        }
+    }
+    impl Default for OrganizationUserDeleted {
+        fn default() -> Self {
+            Self {
+                id: Uuid::new_v4().to_string(),
+                organization_id: Uuid::new_v4().to_string(),
+                user_id: "new user id".into(),
+                role: ProjectUserRole::Member.to_string(),
+                deleted_by: "user id".into(),
+                deleted_at: Utc::now(),
+            }
+        }
+    }
     impl Default for ProjectSecretCreated {
         fn default() -> Self {
             Self {
@@ -373,6 +911,8 @@ mod tests {
                     .into(),
                 category: DEFAULT_CATEGORY.to_string(),
                 status: ResourceStatus::Active.to_string(),
+                cluster_id: None,
+                created_by: Some("user id".into()),
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
             }
@@ -387,10 +927,39 @@ mod tests {
                 name: format!("cardanonode-{}", get_random_salt()),
                 kind: "CardanoNodePort".into(),
                 status: ResourceStatus::Deleted.to_string(),
+                cluster_id: None,
+                deleted_by: Some("user id".into()),
                 deleted_at: Utc::now(),
             }
         }
     }
+    impl Default for ProjectRestored {
+        fn default() -> Self {
+            Self {
+                id: Uuid::new_v4().to_string(),
+                namespace: "sonic-vegas".into(),
+                restored_by: "user id".into(),
+                restored_at: Utc::now(),
+            }
+        }
+    }
+    impl Default for ResourceRestored {
+        fn default() -> Self {
+            Self {
+                id: Uuid::new_v4().to_string(),
+                project_id: Uuid::new_v4().to_string(),
+                project_namespace: "test".into(),
+                name: format!("cardanonode-{}", get_random_salt()),
+                kind: "CardanoNodePort".into(),
+                category: DEFAULT_CATEGORY.to_string(),
+                spec: "{\"version\":\"stable\",\"network\":\"mainnet\",\"throughputTier\":\"1\"}"
+                    .into(),
+                cluster_id: None,
+                restored_by: "user id".into(),
+                restored_at: Utc::now(),
+            }
+        }
+    }
     impl Default for UsageCreated {
         fn default() -> Self {
             Self {
@@ -409,4 +978,129 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn it_should_decode_envelope_from_payload() {
+        let envelope = EventEnvelope::new(ProjectCreated::default().into());
+        let payload = envelope.to_payload().unwrap();
+
+        let decoded = EventEnvelope::from_key(&envelope.key(), &payload).unwrap();
+        assert!(decoded.event_id == envelope.event_id);
+        assert!(decoded.occurred_at == envelope.occurred_at);
+        assert!(decoded.source == envelope.source);
+        assert!(decoded.schema_version == 2);
+        assert!(matches!(decoded.event, Event::ProjectCreated(_)));
+    }
+
+    #[test]
+    fn it_should_decode_envelope_from_protobuf() {
+        let envelope = EventEnvelope::new(UsageCreated::default().into());
+        let payload = envelope.to_protobuf().unwrap();
+
+        let decoded = EventEnvelope::from_key(&envelope.key(), &payload).unwrap();
+        assert!(decoded.event_id == envelope.event_id);
+        assert!(decoded.occurred_at == envelope.occurred_at);
+        assert!(decoded.source == envelope.source);
+        let (Event::UsageCreated(decoded), Event::UsageCreated(expected)) =
+            (decoded.event, envelope.event)
+        else {
+            unreachable!("expected UsageCreated")
+        };
+        assert!(decoded.usages.len() == expected.usages.len());
+        assert!(decoded.usages[0].units == expected.usages[0].units);
+        assert!(decoded.created_at == expected.created_at);
+    }
+
+    #[test]
+    fn it_should_decode_organization_event_from_protobuf() {
+        let envelope = EventEnvelope::new(ProjectOrganizationChanged::default().into());
+        let payload = envelope.to_protobuf().unwrap();
+
+        let decoded = EventEnvelope::from_key(&envelope.key(), &payload).unwrap();
+        let (
+            Event::ProjectOrganizationChanged(decoded),
+            Event::ProjectOrganizationChanged(expected),
+        ) = (decoded.event, envelope.event)
+        else {
+            unreachable!("expected ProjectOrganizationChanged")
+        };
+        assert!(decoded.organization_id == expected.organization_id);
+        assert!(decoded.billing_provider_id == expected.billing_provider_id);
+    }
+
+    #[test]
+    fn it_should_fail_when_protobuf_key_does_not_match() {
+        let envelope = EventEnvelope::new(ProjectCreated::default().into());
+        let payload = envelope.to_protobuf().unwrap();
+
+        let result = EventEnvelope::from_key("ProjectDeleted", &payload);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_should_upcast_resource_created_from_older_protobuf() {
+        let mut envelope = EventEnvelope::new(
+            ResourceCreated {
+                category: String::default(),
+                ..Default::default()
+            }
+            .into(),
+        );
+        envelope.schema_version = 1;
+        let payload = envelope.to_protobuf().unwrap();
+
+        let decoded = EventEnvelope::from_key(&envelope.key(), &payload).unwrap();
+        assert!(decoded.schema_version == 4);
+        let Event::ResourceCreated(decoded) = decoded.event else {
+            unreachable!("expected ResourceCreated")
+        };
+        assert!(decoded.category == DEFAULT_CATEGORY);
+    }
+
+    #[test]
+    fn it_should_fail_when_protobuf_schema_version_is_newer() {
+        let mut envelope = EventEnvelope::new(ResourceCreated::default().into());
+        envelope.schema_version = 5;
+        let payload = envelope.to_protobuf().unwrap();
+
+        let result = EventEnvelope::from_key(&envelope.key(), &payload);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_should_upcast_resource_created_without_category() {
+        let mut payload = serde_json::to_value(ResourceCreated::default()).unwrap();
+        payload.as_object_mut().unwrap().remove("category");
+        payload.as_object_mut().unwrap().remove("created_by");
+        payload.as_object_mut().unwrap().insert(
+            "annotation".into(),
+            serde_json::json!({"source": "fabric-0.1.0"}),
+        );
+        let payload = serde_json::to_vec(&payload).unwrap();
+
+        let decoded = EventEnvelope::from_key("ResourceCreated", &payload).unwrap();
+        assert!(decoded.event_id.is_none());
+        assert!(decoded.schema_version == 4);
+        let Event::ResourceCreated(evt) = decoded.event else {
+            unreachable!("expected ResourceCreated")
+        };
+        assert!(evt.category == DEFAULT_CATEGORY);
+        assert!(evt.created_by.is_none());
+    }
+
+    #[test]
+    fn it_should_decode_payload_without_annotation() {
+        let payload = serde_json::to_vec(&ProjectCreated::default()).unwrap();
+
+        let result = Event::from_key("ProjectCreated", &payload);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn it_should_fail_when_key_is_unknown() {
+        let payload = serde_json::to_vec(&ProjectCreated::default()).unwrap();
+
+        let result = Event::from_key("ProjectArchived", &payload);
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,276 @@
+//! Protobuf form of the events, generated from `proto/fabric/event/v1/event.proto`.
+
+use chrono::{DateTime, Utc};
+use prost_types::Timestamp;
+
+use crate::domain::{error::Error, event, Result};
+
+include!(concat!(env!("OUT_DIR"), "/fabric.event.v1.rs"));
+
+use event_envelope::Event;
+
+fn timestamp(value: DateTime<Utc>) -> Timestamp {
+    Timestamp {
+        seconds: value.timestamp(),
+        nanos: value.timestamp_subsec_nanos() as i32,
+    }
+}
+
+fn datetime(value: Option<Timestamp>, field: &str) -> Result<DateTime<Utc>> {
+    value
+        .and_then(|t| DateTime::from_timestamp(t.seconds, t.nanos as u32))
+        .ok_or_else(|| Error::Unexpected(format!("invalid timestamp in field {field}")))
+}
+
+/// Converts between the domain event and its message, `$field`s are copied as they are and
+/// `$time`s go through `Timestamp`.
+macro_rules! convert {
+    ($name:ident { $($field:ident),* ; $($time:ident),* }) => {
+        impl From<event::$name> for $name {
+            fn from(value: event::$name) -> Self {
+                Self {
+                    $($field: value.$field,)*
+                    $($time: Some(timestamp(value.$time)),)*
+                }
+            }
+        }
+        impl TryFrom<$name> for event::$name {
+            type Error = Error;
+
+            fn try_from(value: $name) -> Result<Self> {
+                Ok(Self {
+                    $($field: value.$field,)*
+                    $($time: datetime(value.$time, stringify!($time))?,)*
+                })
+            }
+        }
+    };
+}
+
+convert!(ProjectCreated {
+    id, name, namespace, owner, status, billing_provider, billing_provider_id,
+    billing_subscription_id, cluster_id;
+    created_at, updated_at
+});
+convert!(ProjectUpdated {
+    id, name, status, billing_subscription_id, billing_subscription_deleted;
+    updated_at
+});
+convert!(ProjectDeleted { id, namespace, deleted_by; deleted_at });
+convert!(ProjectRestored { id, namespace, restored_by; restored_at });
+convert!(ProjectOwnerChanged {
+    id, project_id, previous_owner, new_owner, changed_by;
+    changed_at
+});
+convert!(ProjectOrganizationChanged {
+    id, project_id, organization_id, billing_provider_id, changed_by;
+    changed_at
+});
+convert!(ProjectOwnershipTransferProposed {
+    id, project_id, previous_owner, new_owner, code;
+    expires_in, created_at
+});
+convert!(ProjectOwnershipTransferAccepted {
+    id, project_id, previous_owner, new_owner;
+    accepted_at
+});
+convert!(ProjectOwnershipTransferCancelled { id, project_id, cancelled_by; cancelled_at });
+convert!(ProjectQuotaUpdated {
+    id, project_id, resources, max_tier, workers, updated_by;
+    updated_at
+});
+convert!(ProjectSuspended { id, namespace, reason, suspended_by; suspended_at });
+convert!(ProjectResumed { id, namespace, resumed_by; resumed_at });
+convert!(ProjectSecretCreated { id, project_id, name, phc, secret; created_at });
+convert!(ProjectSecretDeleted { id, deleted_by; deleted_at });
+convert!(ProjectUserInviteCreated {
+    id, project_id, email, role, code;
+    expires_in, created_at
+});
+convert!(ProjectUserInviteAccepted { id, project_id, user_id, role; created_at });
+convert!(ProjectUserInviteDeleted { id, project_id, deleted_by; deleted_at });
+convert!(ProjectUserDeleted {
+    id, project_id, user_id, role, deleted_by;
+    deleted_at
+});
+convert!(ResourceCreated {
+    id, project_id, project_namespace, name, kind, category, spec, status,
+    cluster_id, created_by;
+    created_at, updated_at
+});
+convert!(ResourceUpdated {
+    id, project_id, project_namespace, name, kind, spec_patch, cluster_id, updated_by;
+    updated_at
+});
+convert!(ResourceDeleted {
+    id, project_id, project_namespace, name, kind, status, cluster_id, deleted_by;
+    deleted_at
+});
+convert!(ResourceRestored {
+    id, project_id, project_namespace, name, kind, category, spec, cluster_id,
+    restored_by;
+    restored_at
+});
+convert!(OrganizationCreated {
+    id, name, owner, billing_provider, billing_provider_id;
+    created_at
+});
+convert!(OrganizationUserAdded {
+    id, organization_id, user_id, role, added_by;
+    created_at
+});
+convert!(OrganizationUserDeleted {
+    id, organization_id, user_id, role, deleted_by;
+    deleted_at
+});
+
+impl From<event::UsageCreated> for UsageCreated {
+    fn from(value: event::UsageCreated) -> Self {
+        Self {
+            id: value.id,
+            cluster_id: value.cluster_id,
+            project_id: value.project_id,
+            project_namespace: value.project_namespace,
+            usages: value
+                .usages
+                .into_iter()
+                .map(|usage| UsageUnitCreated {
+                    resource_id: usage.resource_id,
+                    resource_name: usage.resource_name,
+                    tier: usage.tier,
+                    units: usage.units,
+                    interval: usage.interval,
+                })
+                .collect(),
+            created_at: Some(timestamp(value.created_at)),
+        }
+    }
+}
+impl TryFrom<UsageCreated> for event::UsageCreated {
+    type Error = Error;
+
+    fn try_from(value: UsageCreated) -> Result<Self> {
+        Ok(Self {
+            id: value.id,
+            cluster_id: value.cluster_id,
+            project_id: value.project_id,
+            project_namespace: value.project_namespace,
+            usages: value
+                .usages
+                .into_iter()
+                .map(|usage| event::UsageUnitCreated {
+                    resource_id: usage.resource_id,
+                    resource_name: usage.resource_name,
+                    tier: usage.tier,
+                    units: usage.units,
+                    interval: usage.interval,
+                })
+                .collect(),
+            created_at: datetime(value.created_at, "created_at")?,
+        })
+    }
+}
+
+impl From<event::Event> for Event {
+    fn from(value: event::Event) -> Self {
+        match value {
+            event::Event::ProjectCreated(evt) => Self::ProjectCreated(evt.into()),
+            event::Event::ProjectUpdated(evt) => Self::ProjectUpdated(evt.into()),
+            event::Event::ProjectDeleted(evt) => Self::ProjectDeleted(evt.into()),
+            event::Event::ProjectRestored(evt) => Self::ProjectRestored(evt.into()),
+            event::Event::ProjectOwnerChanged(evt) => Self::ProjectOwnerChanged(evt.into()),
+            event::Event::ProjectSecretCreated(evt) => Self::ProjectSecretCreated(evt.into()),
+            event::Event::ProjectSecretDeleted(evt) => Self::ProjectSecretDeleted(evt.into()),
+            event::Event::ProjectUserInviteCreated(evt) => {
+                Self::ProjectUserInviteCreated(evt.into())
+            }
+            event::Event::ProjectUserInviteAccepted(evt) => {
+                Self::ProjectUserInviteAccepted(evt.into())
+            }
+            event::Event::ProjectUserInviteDeleted(evt) => {
+                Self::ProjectUserInviteDeleted(evt.into())
+            }
+            event::Event::ProjectUserDeleted(evt) => Self::ProjectUserDeleted(evt.into()),
+            event::Event::ResourceCreated(evt) => Self::ResourceCreated(evt.into()),
+            event::Event::ResourceUpdated(evt) => Self::ResourceUpdated(evt.into()),
+            event::Event::ResourceDeleted(evt) => Self::ResourceDeleted(evt.into()),
+            event::Event::ResourceRestored(evt) => Self::ResourceRestored(evt.into()),
+            event::Event::UsageCreated(evt) => Self::UsageCreated(evt.into()),
+            event::Event::OrganizationCreated(evt) => Self::OrganizationCreated(evt.into()),
+            event::Event::OrganizationUserAdded(evt) => Self::OrganizationUserAdded(evt.into()),
+            event::Event::OrganizationUserDeleted(evt) => Self::OrganizationUserDeleted(evt.into()),
+            event::Event::ProjectOrganizationChanged(evt) => {
+                Self::ProjectOrganizationChanged(evt.into())
+            }
+            event::Event::ProjectOwnershipTransferProposed(evt) => {
+                Self::ProjectOwnershipTransferProposed(evt.into())
+            }
+            event::Event::ProjectOwnershipTransferAccepted(evt) => {
+                Self::ProjectOwnershipTransferAccepted(evt.into())
+            }
+            event::Event::ProjectOwnershipTransferCancelled(evt) => {
+                Self::ProjectOwnershipTransferCancelled(evt.into())
+            }
+            event::Event::ProjectQuotaUpdated(evt) => Self::ProjectQuotaUpdated(evt.into()),
+            event::Event::ProjectSuspended(evt) => Self::ProjectSuspended(evt.into()),
+            event::Event::ProjectResumed(evt) => Self::ProjectResumed(evt.into()),
+        }
+    }
+}
+impl TryFrom<Event> for event::Event {
+    type Error = Error;
+
+    fn try_from(value: Event) -> Result<Self> {
+        let event = match value {
+            Event::ProjectCreated(evt) => Self::ProjectCreated(evt.try_into()?),
+            Event::ProjectUpdated(evt) => Self::ProjectUpdated(evt.try_into()?),
+            Event::ProjectDeleted(evt) => Self::ProjectDeleted(evt.try_into()?),
+            Event::ProjectRestored(evt) => Self::ProjectRestored(evt.try_into()?),
+            Event::ProjectOwnerChanged(evt) => Self::ProjectOwnerChanged(evt.try_into()?),
+            Event::ProjectSecretCreated(evt) => Self::ProjectSecretCreated(evt.try_into()?),
+            Event::ProjectSecretDeleted(evt) => Self::ProjectSecretDeleted(evt.try_into()?),
+            Event::ProjectUserInviteCreated(evt) => Self::ProjectUserInviteCreated(evt.try_into()?),
+            Event::ProjectUserInviteAccepted(evt) => {
+                Self::ProjectUserInviteAccepted(evt.try_into()?)
+            }
+            Event::ProjectUserInviteDeleted(evt) => Self::ProjectUserInviteDeleted(evt.try_into()?),
+            Event::ProjectUserDeleted(evt) => Self::ProjectUserDeleted(evt.try_into()?),
+            Event::ResourceCreated(evt) => Self::ResourceCreated(evt.try_into()?),
+            Event::ResourceUpdated(evt) => Self::ResourceUpdated(evt.try_into()?),
+            Event::ResourceDeleted(evt) => Self::ResourceDeleted(evt.try_into()?),
+            Event::ResourceRestored(evt) => Self::ResourceRestored(evt.try_into()?),
+            Event::UsageCreated(evt) => Self::UsageCreated(evt.try_into()?),
+            Event::OrganizationCreated(evt) => Self::OrganizationCreated(evt.try_into()?),
+            Event::OrganizationUserAdded(evt) => Self::OrganizationUserAdded(evt.try_into()?),
+            Event::OrganizationUserDeleted(evt) => Self::OrganizationUserDeleted(evt.try_into()?),
+            Event::ProjectOrganizationChanged(evt) => {
+                Self::ProjectOrganizationChanged(evt.try_into()?)
+            }
+            Event::ProjectOwnershipTransferProposed(evt) => {
+                Self::ProjectOwnershipTransferProposed(evt.try_into()?)
+            }
+            Event::ProjectOwnershipTransferAccepted(evt) => {
+                Self::ProjectOwnershipTransferAccepted(evt.try_into()?)
+            }
+            Event::ProjectOwnershipTransferCancelled(evt) => {
+                Self::ProjectOwnershipTransferCancelled(evt.try_into()?)
+            }
+            Event::ProjectQuotaUpdated(evt) => Self::ProjectQuotaUpdated(evt.try_into()?),
+            Event::ProjectSuspended(evt) => Self::ProjectSuspended(evt.try_into()?),
+            Event::ProjectResumed(evt) => Self::ProjectResumed(evt.try_into()?),
+        };
+        Ok(event)
+    }
+}
+
+impl From<event::EventEnvelope> for EventEnvelope {
+    fn from(value: event::EventEnvelope) -> Self {
+        Self {
+            event_id: value.event_id,
+            schema_version: value.schema_version,
+            occurred_at: value.occurred_at.map(timestamp),
+            source: value.source,
+            event: Some(value.event.into()),
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use serde_json::{Map, Value};
+
+use crate::domain::{error::Error, Result, DEFAULT_CATEGORY};
+
+type Payload = Map<String, Value>;
+
+/// Migrates a payload of `key` from `from_version` to `from_version + 1`.
+struct Upcaster {
+    key: &'static str,
+    from_version: u32,
+    upcast: fn(Payload) -> Result<Payload>,
+}
+
+/// Every change to the shape of an event adds an entry here. The current schema version of an
+/// event is one above its latest upcaster, so events that were never changed are version 1.
+const UPCASTERS: &[Upcaster] = &[
+    Upcaster {
+        key: "ResourceCreated",
+        from_version: 1,
+        upcast: resource_created_v1,
+    },
+    Upcaster {
+        key: "ProjectCreated",
+        from_version: 1,
+        upcast: unplaced,
+    },
+    Upcaster {
+        key: "ResourceCreated",
+        from_version: 2,
+        upcast: unplaced,
+    },
+    Upcaster {
+        key: "ResourceUpdated",
+        from_version: 1,
+        upcast: unplaced,
+    },
+    Upcaster {
+        key: "ResourceDeleted",
+        from_version: 1,
+        upcast: unplaced,
+    },
+    Upcaster {
+        key: "ResourceCreated",
+        from_version: 3,
+        upcast: |payload| unattributed(payload, "created_by"),
+    },
+    Upcaster {
+        key: "ResourceUpdated",
+        from_version: 2,
+        upcast: |payload| unattributed(payload, "updated_by"),
+    },
+    Upcaster {
+        key: "ResourceDeleted",
+        from_version: 2,
+        upcast: |payload| unattributed(payload, "deleted_by"),
+    },
+    Upcaster {
+        key: "ProjectDeleted",
+        from_version: 1,
+        upcast: |payload| unattributed(payload, "deleted_by"),
+    },
+];
+
+pub fn current_version(key: &str) -> u32 {
+    UPCASTERS
+        .iter()
+        .filter(|upcaster| upcaster.key == key)
+        .map(|upcaster| upcaster.from_version + 1)
+        .max()
+        .unwrap_or(1)
+}
+
+pub fn upcast(key: &str, version: u32, mut payload: Payload) -> Result<Payload> {
+    let current = current_version(key);
+    if version > current {
+        return Err(Error::Unexpected(format!(
+            "Event '{key}' schema version {version} is newer than the supported {current}"
+        )));
+    }
+
+    for from_version in version..current {
+        let Some(upcaster) = UPCASTERS
+            .iter()
+            .find(|upcaster| upcaster.key == key && upcaster.from_version == from_version)
+        else {
+            return Err(Error::Unexpected(format!(
+                "Event '{key}' has no upcaster from schema version {from_version}"
+            )));
+        };
+        payload = (upcaster.upcast)(payload)?;
+    }
+
+    Ok(payload)
+}
+
+// v1 was published before resources had a category. A protobuf message decodes the missing
+// field to an empty string.
+fn resource_created_v1(mut payload: Payload) -> Result<Payload> {
+    let category = payload.entry("category").or_insert(Value::Null);
+    if category.as_str().is_none_or(str::is_empty) {
+        *category = Value::String(DEFAULT_CATEGORY.into());
+    }
+    Ok(payload)
+}
+
+// Published before the cluster placement, these events are applied by every cluster.
+fn unplaced(mut payload: Payload) -> Result<Payload> {
+    payload.entry("cluster_id").or_insert(Value::Null);
+    Ok(payload)
+}
+
+// Published before the events recorded who acted, the actor is unknown.
+fn unattributed(mut payload: Payload, field: &str) -> Result<Payload> {
+    payload.entry(field).or_insert(Value::Null);
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn it_should_default_to_version_one() {
+        assert!(current_version("ProjectUpdated") == 1);
+        assert!(current_version("ProjectCreated") == 2);
+        assert!(current_version("ResourceCreated") == 4);
+    }
+
+    #[test]
+    fn it_should_add_category_to_resource_created_v1() {
+        let payload = json!({"id": "id"}).as_object().cloned().unwrap();
+
+        let result = upcast("ResourceCreated", 1, payload).unwrap();
+        assert!(result.get("category") == Some(&json!(DEFAULT_CATEGORY)));
+    }
+
+    #[test]
+    fn it_should_keep_category_when_already_present() {
+        let payload = json!({"id": "id", "category": "custom"})
+            .as_object()
+            .cloned()
+            .unwrap();
+
+        let result = upcast("ResourceCreated", 1, payload).unwrap();
+        assert!(result.get("category") == Some(&json!("custom")));
+    }
+
+    #[test]
+    fn it_should_fail_when_version_is_newer_than_supported() {
+        let payload = json!({"id": "id"}).as_object().cloned().unwrap();
+
+        let result = upcast("ProjectCreated", 3, payload);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn it_should_leave_resources_of_older_versions_unplaced() {
+        let payload = json!({"id": "id"}).as_object().cloned().unwrap();
+
+        let result = upcast("ResourceCreated", 1, payload).unwrap();
+        assert!(result.get("cluster_id") == Some(&Value::Null));
+        assert!(result.get("category") == Some(&json!(DEFAULT_CATEGORY)));
+    }
+
+    #[test]
+    fn it_should_leave_events_of_older_versions_without_actor() {
+        let payload = json!({"id": "id"}).as_object().cloned().unwrap();
+
+        let result = upcast("ResourceDeleted", 1, payload.clone()).unwrap();
+        assert!(result.get("deleted_by") == Some(&Value::Null));
+        let result = upcast("ProjectDeleted", 1, payload.clone()).unwrap();
+        assert!(result.get("deleted_by") == Some(&Value::Null));
+
+        let payload = json!({"id": "id", "updated_by": "user id"})
+            .as_object()
+            .cloned()
+            .unwrap();
+        let result = upcast("ResourceUpdated", 2, payload).unwrap();
+        assert!(result.get("updated_by") == Some(&json!("user id")));
+    }
+}
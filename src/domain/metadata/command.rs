@@ -1,17 +1,124 @@
 use std::sync::Arc;
 
+use crate::domain::{error::Error, resource::command::Spec};
+
 use super::{MetadataDriven, ResourceMetadata, Result};
 
 pub async fn fetch(metadata: Arc<dyn MetadataDriven>) -> Result<Vec<ResourceMetadata>> {
     metadata.find()
 }
 
+/// Monthly cost of a resource with the plan of its `throughputTier`, for an expected volume of
+/// units.
+pub async fn estimate_cost(
+    metadata: Arc<dyn MetadataDriven>,
+    cmd: EstimateCostCmd,
+) -> Result<CostEstimate> {
+    let Some(resource_metadata) = metadata.find_by_kind(&cmd.kind)? else {
+        return Err(Error::CommandMalformed(format!(
+            "kind {} not supported",
+            cmd.kind
+        )));
+    };
+
+    let tier = match cmd.spec.get("throughputTier") {
+        Some(serde_json::Value::String(tier)) => tier.clone(),
+        Some(serde_json::Value::Number(tier)) => tier.to_string(),
+        _ => return Err(Error::CommandMalformed("invalid throughputTier".into())),
+    };
+
+    let Some(plan) = resource_metadata.plan.get(&tier) else {
+        return Err(Error::CommandMalformed(format!(
+            "tier {tier} not available for {}",
+            cmd.kind
+        )));
+    };
+
+    // A plan without cost is free.
+    let (minimum, delta) = plan
+        .cost
+        .as_ref()
+        .map(|cost| (cost.minimum, cost.delta))
+        .unwrap_or_default();
+
+    // Like the invoices, the units are charged unless the minimum is higher.
+    let units_cost = (cmd.units as f64) * delta;
+    let total = (units_cost.max(minimum) * 100.).round() / 100.;
+
+    Ok(CostEstimate {
+        kind: cmd.kind,
+        tier,
+        minimum_cost: minimum,
+        unit_cost: delta,
+        units: cmd.units,
+        total_cost: total,
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct EstimateCostCmd {
+    pub kind: String,
+    pub spec: Spec,
+    /// Expected units in a month.
+    pub units: i64,
+}
+impl EstimateCostCmd {
+    pub fn new(kind: String, spec: String, units: i64) -> Result<Self> {
+        let value = serde_json::from_str(&spec)
+            .map_err(|_| Error::CommandMalformed("spec must be a json".into()))?;
+        let spec = match value {
+            serde_json::Value::Object(v) => Ok(v),
+            _ => Err(Error::CommandMalformed("invalid spec json".into())),
+        }?;
+
+        if units < 0 {
+            return Err(Error::CommandMalformed("units can't be negative".into()));
+        }
+
+        Ok(Self { kind, spec, units })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostEstimate {
+    pub kind: String,
+    pub tier: String,
+    /// Charged in a month whatever the units.
+    pub minimum_cost: f64,
+    pub unit_cost: f64,
+    pub units: i64,
+    /// Projected cost of the month.
+    pub total_cost: f64,
+}
+
 #[cfg(test)]
 mod tests {
     use crate::domain::metadata::MockMetadataDriven;
 
     use super::*;
 
+    fn cmd(units: i64) -> EstimateCostCmd {
+        EstimateCostCmd::new(
+            "CardanoNodePort".into(),
+            "{\"version\":\"stable\",\"network\":\"mainnet\",\"throughputTier\":\"0\"}".into(),
+            units,
+        )
+        .unwrap()
+    }
+
+    /// The costs are in dollars, compared in cents to avoid the float rounding.
+    fn cents(value: f64) -> i64 {
+        (value * 100.).round() as i64
+    }
+
+    fn metadata() -> MockMetadataDriven {
+        let mut metadata = MockMetadataDriven::new();
+        metadata
+            .expect_find_by_kind()
+            .return_once(|_| Ok(Some(ResourceMetadata::default())));
+        metadata
+    }
+
     #[tokio::test]
     async fn it_should_fetch_metadata() {
         let mut metadata = MockMetadataDriven::new();
@@ -22,4 +129,35 @@ mod tests {
         let result = fetch(Arc::new(metadata)).await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn it_should_estimate_cost_of_units_over_the_minimum() {
+        let result = estimate_cost(Arc::new(metadata()), cmd(1000)).await;
+        assert!(result.is_ok());
+
+        let estimate = result.unwrap();
+        assert!(cents(estimate.minimum_cost) == 20000);
+        assert!(cents(estimate.unit_cost) == 30);
+        assert!(cents(estimate.total_cost) == 30000);
+    }
+
+    #[tokio::test]
+    async fn it_should_estimate_minimum_cost_of_few_units() {
+        let result = estimate_cost(Arc::new(metadata()), cmd(10)).await;
+        assert!(result.is_ok());
+        assert!(cents(result.unwrap().total_cost) == 20000);
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_estimate_cost_when_tier_is_not_available() {
+        let cmd = EstimateCostCmd::new(
+            "CardanoNodePort".into(),
+            "{\"network\":\"mainnet\",\"throughputTier\":\"3\"}".into(),
+            1000,
+        )
+        .unwrap();
+
+        let result = estimate_cost(Arc::new(metadata()), cmd).await;
+        assert!(matches!(result, Err(Error::CommandMalformed(_))));
+    }
 }
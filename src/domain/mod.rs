@@ -1,12 +1,17 @@
 use error::Error;
 
+pub mod audit;
 pub mod auth;
 pub mod error;
 pub mod event;
 pub mod metadata;
 pub mod notify;
+pub mod organization;
+pub mod pagination;
 pub mod project;
 pub mod resource;
+pub mod search;
+pub mod snapshot;
 pub mod usage;
 pub mod utils;
 pub mod worker;
@@ -15,6 +20,10 @@ pub const PAGE_SIZE_DEFAULT: u32 = 12;
 pub const PAGE_SIZE_MAX: u32 = 120;
 pub const MAX_SECRET: usize = 2;
 pub const DEFAULT_CATEGORY: &str = "demeter-port";
+/// Category of the worker CRDs, the quota limits how many of them a project runs.
+pub const WORKER_CATEGORY: &str = "demeter-worker";
+/// Days a deleted project or resource is kept and can still be restored.
+pub const RESTORE_GRACE_DAYS: i64 = 30;
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
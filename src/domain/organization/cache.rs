@@ -0,0 +1,73 @@
+use std::sync::Arc;
+
+use crate::domain::event::{OrganizationCreated, OrganizationUserAdded, OrganizationUserDeleted};
+use crate::domain::{pagination::Page, Result};
+
+use super::{Organization, OrganizationUser};
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait::async_trait]
+pub trait OrganizationDrivenCache: Send + Sync {
+    async fn find(&self, user_id: &str, page: &Page) -> Result<Vec<Organization>>;
+    async fn find_by_id(&self, id: &str) -> Result<Option<Organization>>;
+    async fn find_users(&self, organization_id: &str, page: &Page)
+        -> Result<Vec<OrganizationUser>>;
+    async fn find_user_permission(
+        &self,
+        user_id: &str,
+        organization_id: &str,
+    ) -> Result<Option<OrganizationUser>>;
+    /// Creates the organization with its owner as the first member. An organization that
+    /// already exists is left as it is, it may come from a snapshot taken after its creation.
+    async fn create(&self, organization: &Organization) -> Result<()>;
+    async fn create_user(&self, user: &OrganizationUser) -> Result<()>;
+    async fn delete_user(&self, organization_id: &str, user_id: &str) -> Result<()>;
+}
+
+pub async fn create(
+    cache: Arc<dyn OrganizationDrivenCache>,
+    evt: OrganizationCreated,
+) -> Result<()> {
+    cache.create(&evt.into()).await
+}
+
+pub async fn create_user(
+    cache: Arc<dyn OrganizationDrivenCache>,
+    evt: OrganizationUserAdded,
+) -> Result<()> {
+    cache.create_user(&evt.try_into()?).await
+}
+
+pub async fn delete_user(
+    cache: Arc<dyn OrganizationDrivenCache>,
+    evt: OrganizationUserDeleted,
+) -> Result<()> {
+    cache.delete_user(&evt.organization_id, &evt.user_id).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn it_should_create_organization_cache() {
+        let mut cache = MockOrganizationDrivenCache::new();
+        cache.expect_create().return_once(|_| Ok(()));
+
+        let evt = OrganizationCreated::default();
+
+        let result = create(Arc::new(cache), evt).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_create_organization_user_cache() {
+        let mut cache = MockOrganizationDrivenCache::new();
+        cache.expect_create_user().return_once(|_| Ok(()));
+
+        let evt = OrganizationUserAdded::default();
+
+        let result = create_user(Arc::new(cache), evt).await;
+        assert!(result.is_ok());
+    }
+}
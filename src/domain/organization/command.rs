@@ -0,0 +1,880 @@
+use chrono::Utc;
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::domain::{
+    auth::{assert_permission, Auth0Driven, Credential, Permission, UserId},
+    error::Error,
+    event::{
+        EventDrivenBridge, OrganizationCreated, OrganizationUserAdded, OrganizationUserDeleted,
+        ProjectOrganizationChanged,
+    },
+    pagination::{Cursor, Page, Paged},
+    project::{cache::ProjectDrivenCache, ProjectUserRole, StripeDriven},
+    Result,
+};
+
+use super::{cache::OrganizationDrivenCache, Organization, OrganizationUser};
+
+pub async fn fetch(
+    cache: Arc<dyn OrganizationDrivenCache>,
+    cmd: FetchCmd,
+) -> Result<Paged<Organization>> {
+    let user_id = assert_credential(&cmd.credential)?;
+
+    let organizations = cache.find(&user_id, &cmd.page).await?;
+
+    Ok(cmd
+        .page
+        .paged(organizations, |o| Cursor::new(Some(o.created_at), &o.id)))
+}
+
+pub async fn create(
+    event: Arc<dyn EventDrivenBridge>,
+    auth0: Arc<dyn Auth0Driven>,
+    stripe: Arc<dyn StripeDriven>,
+    cmd: CreateCmd,
+) -> Result<()> {
+    let user_id = assert_credential(&cmd.credential)?;
+
+    apply_create(event, auth0, stripe, &cmd.id, &cmd.name, &user_id, false).await
+}
+
+/// Creates an organization owned by `owner`, with no authorization check of its own so the
+/// backoffice can create one on behalf of a user.
+///
+/// The organization gets its own Stripe customer, which every project attached to it shares.
+/// With `dry_run` the customer isn't created and the event isn't dispatched.
+pub async fn apply_create(
+    event: Arc<dyn EventDrivenBridge>,
+    auth0: Arc<dyn Auth0Driven>,
+    stripe: Arc<dyn StripeDriven>,
+    id: &str,
+    name: &str,
+    owner: &str,
+    dry_run: bool,
+) -> Result<()> {
+    let profile = auth0.find_info(&format!("user_id:{owner}")).await?;
+    if profile.is_empty() {
+        return Err(Error::Unexpected("Invalid user_id".into()));
+    }
+    let profile = profile.first().unwrap();
+
+    let mut evt = OrganizationCreated {
+        id: id.to_string(),
+        name: name.to_string(),
+        owner: owner.to_string(),
+        billing_provider: "stripe".into(),
+        billing_provider_id: String::new(),
+        created_at: Utc::now(),
+    };
+
+    if dry_run {
+        info!("event to dispath: {:?}", evt);
+        info!(
+            name = name,
+            email = &profile.email,
+            "stripe customer to create"
+        );
+        return Ok(());
+    }
+
+    evt.billing_provider_id = stripe.create_customer(name, &profile.email).await?;
+
+    event.dispatch(evt.into()).await?;
+    info!(organization = id, "new organization created");
+
+    Ok(())
+}
+
+pub async fn fetch_user(
+    cache: Arc<dyn OrganizationDrivenCache>,
+    cmd: FetchUserCmd,
+) -> Result<Paged<OrganizationUser>> {
+    let user_id = assert_credential(&cmd.credential)?;
+    assert_organization_permission(
+        cache.clone(),
+        &user_id,
+        &cmd.organization_id,
+        Permission::UserRead,
+    )
+    .await?;
+
+    let users = cache.find_users(&cmd.organization_id, &cmd.page).await?;
+
+    Ok(cmd
+        .page
+        .paged(users, |u| Cursor::new(Some(u.created_at), &u.user_id)))
+}
+
+pub async fn add_user(
+    cache: Arc<dyn OrganizationDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    cmd: AddUserCmd,
+) -> Result<()> {
+    let user_id = assert_credential(&cmd.credential)?;
+    let role = assert_organization_permission(
+        cache.clone(),
+        &user_id,
+        &cmd.organization_id,
+        Permission::UserInvite,
+    )
+    .await?;
+    if !role.can_grant(&cmd.role) {
+        return Err(Error::Unauthorized(format!(
+            "user can not add a user with the {} role",
+            cmd.role
+        )));
+    }
+
+    apply_add_user(
+        cache,
+        event,
+        &cmd.organization_id,
+        &cmd.user_id,
+        &cmd.role,
+        &user_id,
+        false,
+    )
+    .await
+}
+
+/// Adds a member to the organization, who gets `role` in each of its projects. Like
+/// `apply_create` it has no authorization check, `add_user` asserts the caller is an owner.
+pub async fn apply_add_user(
+    cache: Arc<dyn OrganizationDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    organization_id: &str,
+    user_id: &str,
+    role: &ProjectUserRole,
+    added_by: &str,
+    dry_run: bool,
+) -> Result<()> {
+    if cache.find_by_id(organization_id).await?.is_none() {
+        return Err(Error::CommandMalformed("invalid organization id".into()));
+    }
+
+    if cache
+        .find_user_permission(user_id, organization_id)
+        .await?
+        .is_some()
+    {
+        return Err(Error::CommandMalformed(
+            "user already is in the organization".into(),
+        ));
+    }
+
+    let evt = OrganizationUserAdded {
+        id: Uuid::new_v4().to_string(),
+        organization_id: organization_id.to_string(),
+        user_id: user_id.to_string(),
+        role: role.to_string(),
+        added_by: added_by.to_string(),
+        created_at: Utc::now(),
+    };
+
+    if dry_run {
+        info!("event to dispath: {:?}", evt);
+        return Ok(());
+    }
+
+    event.dispatch(evt.into()).await?;
+    info!(organization = organization_id, "organization user added");
+
+    Ok(())
+}
+
+pub async fn delete_user(
+    cache: Arc<dyn OrganizationDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    cmd: DeleteUserCmd,
+) -> Result<()> {
+    let user_id = assert_credential(&cmd.credential)?;
+    let role = assert_organization_permission(
+        cache.clone(),
+        &user_id,
+        &cmd.organization_id,
+        Permission::UserDelete,
+    )
+    .await?;
+    if let Some(user) = cache
+        .find_user_permission(&cmd.id, &cmd.organization_id)
+        .await?
+    {
+        if !role.can_grant(&user.role) {
+            return Err(Error::Unauthorized(format!(
+                "user can not delete a user with the {} role",
+                user.role
+            )));
+        }
+    }
+
+    apply_delete_user(cache, event, &cmd.organization_id, &cmd.id, &user_id, false).await
+}
+
+/// Removes a member from the organization and so from each of its projects, unless they are
+/// also a direct member of a project.
+pub async fn apply_delete_user(
+    cache: Arc<dyn OrganizationDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    organization_id: &str,
+    user_id: &str,
+    deleted_by: &str,
+    dry_run: bool,
+) -> Result<()> {
+    let Some(organization) = cache.find_by_id(organization_id).await? else {
+        return Err(Error::CommandMalformed("invalid organization id".into()));
+    };
+
+    if organization.owner == user_id {
+        return Err(Error::CommandMalformed("owner can not be deleted".into()));
+    }
+
+    let Some(permission) = cache.find_user_permission(user_id, organization_id).await? else {
+        return Err(Error::CommandMalformed("invalid user id".into()));
+    };
+
+    let evt = OrganizationUserDeleted {
+        id: Uuid::new_v4().to_string(),
+        organization_id: organization.id,
+        user_id: permission.user_id,
+        role: permission.role.to_string(),
+        deleted_by: deleted_by.to_string(),
+        deleted_at: Utc::now(),
+    };
+
+    if dry_run {
+        info!("event to dispath: {:?}", evt);
+        return Ok(());
+    }
+
+    event.dispatch(evt.into()).await?;
+    info!(organization = organization_id, "organization user deleted");
+
+    Ok(())
+}
+
+/// Attaches a project to an organization, the caller must own both.
+pub async fn attach_project(
+    organization_cache: Arc<dyn OrganizationDrivenCache>,
+    project_cache: Arc<dyn ProjectDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    cmd: AttachProjectCmd,
+) -> Result<()> {
+    let user_id = assert_credential(&cmd.credential)?;
+    assert_permission(
+        project_cache.clone(),
+        &cmd.credential,
+        &cmd.project_id,
+        Permission::ProjectTransfer,
+    )
+    .await?;
+    assert_organization_permission(
+        organization_cache.clone(),
+        &user_id,
+        &cmd.organization_id,
+        Permission::ProjectTransfer,
+    )
+    .await?;
+
+    apply_attach_project(
+        organization_cache,
+        project_cache,
+        event,
+        &cmd.organization_id,
+        &cmd.project_id,
+        &user_id,
+        false,
+    )
+    .await
+}
+
+/// Moves the project into the organization. Its members keep their roles, the organization's
+/// are added on top of them, and the project is billed to the organization's customer from now
+/// on. The project's own customer is left in Stripe, untouched.
+pub async fn apply_attach_project(
+    organization_cache: Arc<dyn OrganizationDrivenCache>,
+    project_cache: Arc<dyn ProjectDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    organization_id: &str,
+    project_id: &str,
+    changed_by: &str,
+    dry_run: bool,
+) -> Result<()> {
+    let Some(organization) = organization_cache.find_by_id(organization_id).await? else {
+        return Err(Error::CommandMalformed("invalid organization id".into()));
+    };
+    let Some(project) = project_cache.find_by_id(project_id).await? else {
+        return Err(Error::CommandMalformed("invalid project id".into()));
+    };
+
+    if project.organization_id.as_deref() == Some(organization_id) {
+        return Err(Error::CommandMalformed(
+            "project already is in the organization".into(),
+        ));
+    }
+
+    let evt = ProjectOrganizationChanged {
+        id: Uuid::new_v4().to_string(),
+        project_id: project.id,
+        organization_id: Some(organization.id),
+        billing_provider_id: organization.billing_provider_id,
+        changed_by: changed_by.to_string(),
+        changed_at: Utc::now(),
+    };
+
+    if dry_run {
+        info!("event to dispath: {:?}", evt);
+        return Ok(());
+    }
+
+    event.dispatch(evt.into()).await?;
+    info!(
+        project = project_id,
+        organization = organization_id,
+        "project attached to organization"
+    );
+
+    Ok(())
+}
+
+pub async fn detach_project(
+    project_cache: Arc<dyn ProjectDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    auth0: Arc<dyn Auth0Driven>,
+    stripe: Arc<dyn StripeDriven>,
+    cmd: DetachProjectCmd,
+) -> Result<()> {
+    let user_id = assert_credential(&cmd.credential)?;
+    assert_permission(
+        project_cache.clone(),
+        &cmd.credential,
+        &cmd.project_id,
+        Permission::ProjectTransfer,
+    )
+    .await?;
+
+    apply_detach_project(
+        project_cache,
+        event,
+        auth0,
+        stripe,
+        &cmd.project_id,
+        &user_id,
+        false,
+    )
+    .await
+}
+
+/// Takes the project out of its organization. The organization's customer keeps billing its
+/// other projects, so the project gets a new customer of its owner, as when it's created.
+pub async fn apply_detach_project(
+    project_cache: Arc<dyn ProjectDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    auth0: Arc<dyn Auth0Driven>,
+    stripe: Arc<dyn StripeDriven>,
+    project_id: &str,
+    changed_by: &str,
+    dry_run: bool,
+) -> Result<()> {
+    let Some(project) = project_cache.find_by_id(project_id).await? else {
+        return Err(Error::CommandMalformed("invalid project id".into()));
+    };
+    if project.organization_id.is_none() {
+        return Err(Error::CommandMalformed(
+            "project is not in an organization".into(),
+        ));
+    }
+
+    let profile = auth0
+        .find_info(&format!("user_id:{}", project.owner))
+        .await?;
+    if profile.is_empty() {
+        return Err(Error::Unexpected("Invalid user_id".into()));
+    }
+    let profile = profile.first().unwrap();
+
+    let mut evt = ProjectOrganizationChanged {
+        id: Uuid::new_v4().to_string(),
+        project_id: project.id,
+        organization_id: None,
+        billing_provider_id: String::new(),
+        changed_by: changed_by.to_string(),
+        changed_at: Utc::now(),
+    };
+
+    if dry_run {
+        info!("event to dispath: {:?}", evt);
+        info!(
+            name = &profile.name,
+            email = &profile.email,
+            "stripe customer to create"
+        );
+        return Ok(());
+    }
+
+    evt.billing_provider_id = stripe
+        .create_customer(&profile.name, &profile.email)
+        .await?;
+
+    event.dispatch(evt.into()).await?;
+    info!(project = project_id, "project detached from organization");
+
+    Ok(())
+}
+
+/// The role a user holds in the organization applies to each of its projects, so it's checked
+/// against the same permissions.
+async fn assert_organization_permission(
+    cache: Arc<dyn OrganizationDrivenCache>,
+    user_id: &str,
+    organization_id: &str,
+    permission: Permission,
+) -> Result<ProjectUserRole> {
+    let Some(organization_user) = cache.find_user_permission(user_id, organization_id).await?
+    else {
+        return Err(Error::Unauthorized("user doesnt have permission".into()));
+    };
+
+    if !organization_user.role.has_permission(permission) {
+        return Err(Error::Unauthorized(format!(
+            "user doesnt have the {permission} permission"
+        )));
+    }
+
+    Ok(organization_user.role)
+}
+
+fn assert_credential(credential: &Credential) -> Result<UserId> {
+    match credential {
+        Credential::Auth0(user_id) => Ok(user_id.into()),
+        Credential::ApiKey(_) => Err(Error::Unauthorized(
+            "organization rpc doesnt support secret".into(),
+        )),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FetchCmd {
+    pub credential: Credential,
+    pub page: Page,
+}
+impl FetchCmd {
+    pub fn new(
+        credential: Credential,
+        page: Option<u32>,
+        page_size: Option<u32>,
+        cursor: Option<String>,
+    ) -> Result<Self> {
+        Ok(Self {
+            credential,
+            page: Page::new(page, page_size, cursor)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateCmd {
+    pub credential: Credential,
+    pub id: String,
+    pub name: String,
+}
+impl CreateCmd {
+    pub fn new(credential: Credential, name: String) -> Self {
+        Self {
+            credential,
+            id: Uuid::new_v4().to_string(),
+            name,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FetchUserCmd {
+    pub credential: Credential,
+    pub page: Page,
+    pub organization_id: String,
+}
+impl FetchUserCmd {
+    pub fn new(
+        credential: Credential,
+        page: Option<u32>,
+        page_size: Option<u32>,
+        cursor: Option<String>,
+        organization_id: String,
+    ) -> Result<Self> {
+        Ok(Self {
+            credential,
+            page: Page::new(page, page_size, cursor)?,
+            organization_id,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AddUserCmd {
+    pub credential: Credential,
+    pub organization_id: String,
+    pub user_id: String,
+    pub role: ProjectUserRole,
+}
+impl AddUserCmd {
+    pub fn new(
+        credential: Credential,
+        organization_id: String,
+        user_id: String,
+        role: String,
+    ) -> Result<Self> {
+        Ok(Self {
+            credential,
+            organization_id,
+            user_id,
+            role: role.parse()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeleteUserCmd {
+    pub credential: Credential,
+    pub organization_id: String,
+    pub id: String,
+}
+impl DeleteUserCmd {
+    pub fn new(credential: Credential, organization_id: String, id: String) -> Self {
+        Self {
+            credential,
+            organization_id,
+            id,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AttachProjectCmd {
+    pub credential: Credential,
+    pub organization_id: String,
+    pub project_id: String,
+}
+impl AttachProjectCmd {
+    pub fn new(credential: Credential, organization_id: String, project_id: String) -> Self {
+        Self {
+            credential,
+            organization_id,
+            project_id,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DetachProjectCmd {
+    pub credential: Credential,
+    pub project_id: String,
+}
+impl DetachProjectCmd {
+    pub fn new(credential: Credential, project_id: String) -> Self {
+        Self {
+            credential,
+            project_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        auth::{Auth0Profile, MockAuth0Driven},
+        event::{Event, MockEventDrivenBridge},
+        organization::cache::MockOrganizationDrivenCache,
+        project::{cache::MockProjectDrivenCache, MockStripeDriven, Project, ProjectUser},
+        tests::SECRET,
+    };
+
+    impl Default for CreateCmd {
+        fn default() -> Self {
+            Self {
+                credential: Credential::Auth0("user id".into()),
+                id: Uuid::new_v4().to_string(),
+                name: "New Organization".into(),
+            }
+        }
+    }
+    impl Default for AddUserCmd {
+        fn default() -> Self {
+            Self {
+                credential: Credential::Auth0("user id".into()),
+                organization_id: Uuid::new_v4().to_string(),
+                user_id: "new user id".into(),
+                role: ProjectUserRole::Member,
+            }
+        }
+    }
+    impl Default for DeleteUserCmd {
+        fn default() -> Self {
+            Self {
+                credential: Credential::Auth0("user id".into()),
+                organization_id: Uuid::new_v4().to_string(),
+                id: "new user id".into(),
+            }
+        }
+    }
+    impl Default for AttachProjectCmd {
+        fn default() -> Self {
+            Self {
+                credential: Credential::Auth0("user id".into()),
+                organization_id: Uuid::new_v4().to_string(),
+                project_id: Uuid::new_v4().to_string(),
+            }
+        }
+    }
+    impl Default for DetachProjectCmd {
+        fn default() -> Self {
+            Self {
+                credential: Credential::Auth0("user id".into()),
+                project_id: Uuid::new_v4().to_string(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_create_organization() {
+        let mut auth0 = MockAuth0Driven::new();
+        auth0
+            .expect_find_info()
+            .return_once(|_| Ok(vec![Auth0Profile::default()]));
+
+        let mut stripe = MockStripeDriven::new();
+        stripe
+            .expect_create_customer()
+            .return_once(|_, _| Ok("organization stripe id".into()));
+
+        let mut event = MockEventDrivenBridge::new();
+        event
+            .expect_dispatch()
+            .withf(|evt| {
+                matches!(evt, Event::OrganizationCreated(evt) if evt.billing_provider_id == "organization stripe id")
+            })
+            .return_once(|_| Ok(None));
+
+        let cmd = CreateCmd::default();
+
+        let result = create(Arc::new(event), Arc::new(auth0), Arc::new(stripe), cmd).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_create_organization_when_credential_is_secret() {
+        let auth0 = MockAuth0Driven::new();
+        let stripe = MockStripeDriven::new();
+        let event = MockEventDrivenBridge::new();
+
+        let cmd = CreateCmd {
+            credential: Credential::ApiKey(SECRET.into()),
+            ..Default::default()
+        };
+
+        let result = create(Arc::new(event), Arc::new(auth0), Arc::new(stripe), cmd).await;
+        assert!(matches!(result, Err(Error::Unauthorized(_))));
+    }
+
+    #[tokio::test]
+    async fn it_should_add_organization_user() {
+        let mut cache = MockOrganizationDrivenCache::new();
+        cache
+            .expect_find_user_permission()
+            .withf(|user_id, _| user_id == "user id")
+            .return_once(|_, _| Ok(Some(OrganizationUser::default())));
+        cache
+            .expect_find_user_permission()
+            .withf(|user_id, _| user_id == "new user id")
+            .return_once(|_, _| Ok(None));
+        cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Organization::default())));
+
+        let mut event = MockEventDrivenBridge::new();
+        event.expect_dispatch().return_once(|_| Ok(None));
+
+        let cmd = AddUserCmd::default();
+
+        let result = add_user(Arc::new(cache), Arc::new(event), cmd).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_add_organization_user_when_caller_is_not_owner() {
+        let mut cache = MockOrganizationDrivenCache::new();
+        cache.expect_find_user_permission().return_once(|_, _| {
+            Ok(Some(OrganizationUser {
+                role: ProjectUserRole::Member,
+                ..Default::default()
+            }))
+        });
+
+        let event = MockEventDrivenBridge::new();
+
+        let cmd = AddUserCmd::default();
+
+        let result = add_user(Arc::new(cache), Arc::new(event), cmd).await;
+        assert!(matches!(result, Err(Error::Unauthorized(_))));
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_delete_organization_owner() {
+        let mut cache = MockOrganizationDrivenCache::new();
+        cache
+            .expect_find_user_permission()
+            .returning(|_, _| Ok(Some(OrganizationUser::default())));
+        cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Organization::default())));
+
+        let event = MockEventDrivenBridge::new();
+
+        let cmd = DeleteUserCmd {
+            id: "user id".into(),
+            ..Default::default()
+        };
+
+        let result = delete_user(Arc::new(cache), Arc::new(event), cmd).await;
+        assert!(matches!(result, Err(Error::CommandMalformed(_))));
+    }
+
+    #[tokio::test]
+    async fn it_should_attach_project_sharing_organization_billing() {
+        let mut organization_cache = MockOrganizationDrivenCache::new();
+        organization_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(OrganizationUser::default())));
+        organization_cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Organization::default())));
+
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        project_cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Project::default())));
+
+        let mut event = MockEventDrivenBridge::new();
+        event
+            .expect_dispatch()
+            .withf(|evt| {
+                matches!(evt, Event::ProjectOrganizationChanged(evt) if evt.billing_provider_id == "organization stripe id")
+            })
+            .return_once(|_| Ok(None));
+
+        let cmd = AttachProjectCmd::default();
+
+        let result = attach_project(
+            Arc::new(organization_cache),
+            Arc::new(project_cache),
+            Arc::new(event),
+            cmd,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_attach_project_when_not_organization_member() {
+        let mut organization_cache = MockOrganizationDrivenCache::new();
+        organization_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(None));
+
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+
+        let event = MockEventDrivenBridge::new();
+
+        let cmd = AttachProjectCmd::default();
+
+        let result = attach_project(
+            Arc::new(organization_cache),
+            Arc::new(project_cache),
+            Arc::new(event),
+            cmd,
+        )
+        .await;
+        assert!(matches!(result, Err(Error::Unauthorized(_))));
+    }
+
+    #[tokio::test]
+    async fn it_should_detach_project_with_new_billing_customer() {
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        project_cache.expect_find_by_id().return_once(|_| {
+            Ok(Some(Project {
+                organization_id: Some(Uuid::new_v4().to_string()),
+                ..Default::default()
+            }))
+        });
+
+        let mut auth0 = MockAuth0Driven::new();
+        auth0
+            .expect_find_info()
+            .return_once(|_| Ok(vec![Auth0Profile::default()]));
+
+        let mut stripe = MockStripeDriven::new();
+        stripe
+            .expect_create_customer()
+            .return_once(|_, _| Ok("new stripe id".into()));
+
+        let mut event = MockEventDrivenBridge::new();
+        event
+            .expect_dispatch()
+            .withf(|evt| {
+                matches!(evt, Event::ProjectOrganizationChanged(evt) if evt.organization_id.is_none() && evt.billing_provider_id == "new stripe id")
+            })
+            .return_once(|_| Ok(None));
+
+        let cmd = DetachProjectCmd::default();
+
+        let result = detach_project(
+            Arc::new(project_cache),
+            Arc::new(event),
+            Arc::new(auth0),
+            Arc::new(stripe),
+            cmd,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_not_dispatch_attach_project_in_dry_run() {
+        let mut organization_cache = MockOrganizationDrivenCache::new();
+        organization_cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Organization::default())));
+
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Project::default())));
+
+        let mut event = MockEventDrivenBridge::new();
+        event.expect_dispatch().never();
+
+        let result = apply_attach_project(
+            Arc::new(organization_cache),
+            Arc::new(project_cache),
+            Arc::new(event),
+            "organization id",
+            "project id",
+            "backoffice",
+            true,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+}
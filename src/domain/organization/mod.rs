@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+
+use super::{
+    error::Error,
+    event::{OrganizationCreated, OrganizationUserAdded},
+    project::ProjectUserRole,
+};
+
+pub mod cache;
+pub mod command;
+
+/// Groups projects that share their members and a single billing customer.
+#[derive(Debug, Clone)]
+pub struct Organization {
+    pub id: String,
+    pub name: String,
+    pub owner: String,
+    pub billing_provider: String,
+    pub billing_provider_id: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+impl From<OrganizationCreated> for Organization {
+    fn from(value: OrganizationCreated) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            owner: value.owner,
+            billing_provider: value.billing_provider,
+            billing_provider_id: value.billing_provider_id,
+            created_at: value.created_at,
+            updated_at: value.created_at,
+        }
+    }
+}
+
+/// Member of an organization, the role is the one they get in each project of it.
+#[derive(Debug, Clone)]
+pub struct OrganizationUser {
+    pub user_id: String,
+    pub organization_id: String,
+    pub role: ProjectUserRole,
+    pub created_at: DateTime<Utc>,
+}
+impl TryFrom<OrganizationUserAdded> for OrganizationUser {
+    type Error = Error;
+
+    fn try_from(value: OrganizationUserAdded) -> std::result::Result<Self, Self::Error> {
+        Ok(Self {
+            user_id: value.user_id,
+            organization_id: value.organization_id,
+            role: value.role.parse()?,
+            created_at: value.created_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    impl Default for Organization {
+        fn default() -> Self {
+            Self {
+                id: Uuid::new_v4().to_string(),
+                name: "New Organization".into(),
+                owner: "user id".into(),
+                billing_provider: "stripe".into(),
+                billing_provider_id: "organization stripe id".into(),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            }
+        }
+    }
+    impl Default for OrganizationUser {
+        fn default() -> Self {
+            Self {
+                user_id: "user id".into(),
+                organization_id: Uuid::new_v4().to_string(),
+                role: ProjectUserRole::Owner,
+                created_at: Utc::now(),
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use base64::{prelude::BASE64_URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{error::Error, Result, PAGE_SIZE_DEFAULT, PAGE_SIZE_MAX};
+
+/// Position of the last row of a page. The lists are ordered by `created_at` and `id`, newest
+/// first, so the next page starts right after it even when rows are inserted in between. The
+/// worker key-values have no creation time and are ordered by key alone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub created_at: Option<DateTime<Utc>>,
+    pub id: String,
+}
+impl Cursor {
+    pub fn new(created_at: Option<DateTime<Utc>>, id: &str) -> Self {
+        Self {
+            created_at,
+            id: id.to_string(),
+        }
+    }
+
+    /// Opaque to the clients, they only send back the value they received.
+    pub fn encode(&self) -> String {
+        BASE64_URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(value: &str) -> Result<Self> {
+        BASE64_URL_SAFE_NO_PAD
+            .decode(value)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or(Error::CommandMalformed("invalid cursor".into()))
+    }
+}
+
+/// A page by offset, or the rows after `cursor`. The offset paging is kept for the clients that
+/// don't send cursors, `page` is ignored once they do. An empty cursor asks for the first page.
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub page: u32,
+    pub page_size: u32,
+    pub cursor: Option<Cursor>,
+    /// Whether the client pages by cursor. Only then a next cursor is returned, as the offset
+    /// paging of the projects keeps an order the cursors don't follow.
+    pub by_cursor: bool,
+}
+impl Page {
+    pub fn new(page: Option<u32>, page_size: Option<u32>, cursor: Option<String>) -> Result<Self> {
+        let page = page.unwrap_or(1);
+        let page_size = page_size.unwrap_or(PAGE_SIZE_DEFAULT);
+
+        if page_size >= PAGE_SIZE_MAX {
+            return Err(Error::CommandMalformed(format!(
+                "page_size exceeded the limit of {PAGE_SIZE_MAX}"
+            )));
+        }
+
+        let by_cursor = cursor.is_some();
+        let cursor = cursor
+            .filter(|cursor| !cursor.is_empty())
+            .as_deref()
+            .map(Cursor::decode)
+            .transpose()?;
+
+        Ok(Self {
+            page,
+            page_size,
+            cursor,
+            by_cursor,
+        })
+    }
+
+    pub fn after(cursor: Option<Cursor>, page_size: u32) -> Self {
+        Self {
+            page: 1,
+            page_size,
+            cursor,
+            by_cursor: true,
+        }
+    }
+
+    pub fn offset(&self) -> u32 {
+        match self.by_cursor {
+            true => 0,
+            false => self.page_size * self.page.saturating_sub(1),
+        }
+    }
+
+    pub fn cursor_created_at(&self) -> Option<DateTime<Utc>> {
+        self.cursor.as_ref().and_then(|c| c.created_at)
+    }
+
+    pub fn cursor_id(&self) -> Option<&str> {
+        self.cursor.as_ref().map(|c| c.id.as_str())
+    }
+
+    /// Cursor of the page after `records`, none when the page isn't full as there is nothing
+    /// left to read or when paging by offset.
+    pub fn paged<T>(&self, records: Vec<T>, cursor: impl Fn(&T) -> Cursor) -> Paged<T> {
+        let next_cursor = match records.last() {
+            Some(last) if self.by_cursor && records.len() as u32 >= self.page_size => {
+                Some(cursor(last))
+            }
+            _ => None,
+        };
+
+        Paged {
+            records,
+            next_cursor,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Paged<T> {
+    pub records: Vec<T>,
+    pub next_cursor: Option<Cursor>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl Default for Page {
+        fn default() -> Self {
+            Self {
+                page: 1,
+                page_size: 12,
+                cursor: None,
+                by_cursor: false,
+            }
+        }
+    }
+
+    #[test]
+    fn it_should_decode_encoded_cursor() {
+        let cursor = Cursor::new(Some(Utc::now()), "id");
+        assert!(Cursor::decode(&cursor.encode()).unwrap() == cursor);
+        assert!(Cursor::decode("invalid").is_err());
+    }
+
+    #[test]
+    fn it_should_return_next_cursor_only_for_full_pages() {
+        let page = Page::after(None, 2);
+
+        let paged = page.paged(vec!["a", "b"], |v| Cursor::new(None, v));
+        assert!(paged.next_cursor == Some(Cursor::new(None, "b")));
+
+        let paged = page.paged(vec!["a"], |v| Cursor::new(None, v));
+        assert!(paged.next_cursor.is_none());
+    }
+
+    #[test]
+    fn it_should_ignore_offset_when_cursor_is_given() {
+        let page = Page::new(Some(3), Some(10), None).unwrap();
+        assert!(page.offset() == 20);
+
+        let cursor = Cursor::new(None, "id").encode();
+        let page = Page::new(Some(3), Some(10), Some(cursor)).unwrap();
+        assert!(page.offset() == 0);
+
+        let page = Page::new(Some(3), Some(10), Some(String::new())).unwrap();
+        assert!(page.cursor.is_none());
+        assert!(page.offset() == 0);
+    }
+
+    #[test]
+    fn it_should_not_return_next_cursor_when_paging_by_offset() {
+        let page = Page::new(None, Some(2), None).unwrap();
+
+        let paged = page.paged(vec!["a", "b"], |v| Cursor::new(None, v));
+        assert!(paged.next_cursor.is_none());
+    }
+}
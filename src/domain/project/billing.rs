@@ -0,0 +1,616 @@
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+use chrono::Utc;
+use tracing::{info, warn};
+
+use crate::domain::{
+    audit::{cache::AuditDrivenCache, AuditFilter},
+    error::Error,
+    event::{EventDrivenBridge, ProjectSuspended, ProjectUpdated},
+    Result,
+};
+
+use super::{cache::ProjectDrivenCache, command::apply_resume, Project, ProjectStatus};
+
+/// Actor of the changes coming from the billing provider webhooks.
+pub const BILLING_ACTOR: &str = "stripe";
+
+/// State of a subscription on the billing provider, named as Stripe does.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubscriptionStatus {
+    Active,
+    Trialing,
+    PastDue,
+    Unpaid,
+    Canceled,
+    Incomplete,
+    IncompleteExpired,
+    Paused,
+}
+impl SubscriptionStatus {
+    /// Status of the projects charged on a subscription in this state, the states waiting on the
+    /// customer leave it as it is.
+    fn project_status(&self) -> Option<ProjectStatus> {
+        match self {
+            Self::Active | Self::Trialing => Some(ProjectStatus::Active),
+            Self::PastDue | Self::Unpaid | Self::Canceled | Self::IncompleteExpired => {
+                Some(ProjectStatus::PaymentMethodFailed)
+            }
+            Self::Incomplete | Self::Paused => None,
+        }
+    }
+}
+impl FromStr for SubscriptionStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(Self::Active),
+            "trialing" => Ok(Self::Trialing),
+            "past_due" => Ok(Self::PastDue),
+            "unpaid" => Ok(Self::Unpaid),
+            "canceled" => Ok(Self::Canceled),
+            "incomplete" => Ok(Self::Incomplete),
+            "incomplete_expired" => Ok(Self::IncompleteExpired),
+            "paused" => Ok(Self::Paused),
+            _ => Err(Error::CommandMalformed(format!(
+                "subscription status not supported: {s}"
+            ))),
+        }
+    }
+}
+impl Display for SubscriptionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Active => write!(f, "active"),
+            Self::Trialing => write!(f, "trialing"),
+            Self::PastDue => write!(f, "past_due"),
+            Self::Unpaid => write!(f, "unpaid"),
+            Self::Canceled => write!(f, "canceled"),
+            Self::Incomplete => write!(f, "incomplete"),
+            Self::IncompleteExpired => write!(f, "incomplete_expired"),
+            Self::Paused => write!(f, "paused"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    pub id: String,
+    pub customer_id: String,
+    /// `project_id` of the subscription metadata, when the customer pays for several projects.
+    pub project_id: Option<String>,
+    pub status: SubscriptionStatus,
+}
+
+impl Subscription {
+    /// Why the projects charged on the subscription are suspended, `None` when they aren't.
+    fn suspension_reason(&self) -> Option<String> {
+        match self.status {
+            SubscriptionStatus::Unpaid => Some(format!("subscription {} is unpaid", self.id)),
+            SubscriptionStatus::Canceled => Some(format!("subscription {} was canceled", self.id)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PaymentFailure {
+    pub customer_id: String,
+    pub subscription_id: Option<String>,
+}
+
+/// Brings the projects charged on a subscription in line with it, whether it was created,
+/// updated or deleted. An unpaid subscription suspends them, and they're resumed once it's active
+/// again, only if it was billing that suspended them. There's no authorization check, it's only
+/// run from the billing webhooks.
+pub async fn apply_subscription(
+    cache: Arc<dyn ProjectDrivenCache>,
+    audit: Arc<dyn AuditDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    subscription: Subscription,
+) -> Result<()> {
+    let projects = cache
+        .find_by_billing_provider_id(&subscription.customer_id)
+        .await?
+        .into_iter()
+        .filter(|p| {
+            subscription
+                .project_id
+                .as_ref()
+                .is_none_or(|id| id == &p.id)
+        })
+        .collect::<Vec<_>>();
+
+    if projects.is_empty() {
+        warn!(
+            customer = subscription.customer_id,
+            subscription = subscription.id,
+            "no project charged on the subscription"
+        );
+        return Ok(());
+    }
+
+    let canceled = subscription.status == SubscriptionStatus::Canceled;
+    for project in projects {
+        // A project already moved to another subscription isn't affected by the old one ending.
+        if canceled && !is_charged_on(&project, Some(&subscription.id)) {
+            continue;
+        }
+
+        let status = subscription
+            .status
+            .project_status()
+            .filter(|status| is_billing_change(&project.status, status));
+        let billing_subscription_id = (!canceled
+            && project.billing_subscription_id.as_ref() != Some(&subscription.id))
+        .then(|| subscription.id.clone());
+        let billing_subscription_deleted = canceled && project.billing_subscription_id.is_some();
+
+        if status.is_some() || billing_subscription_id.is_some() || billing_subscription_deleted {
+            let evt = ProjectUpdated {
+                id: project.id.clone(),
+                name: None,
+                status: status.map(|status| status.to_string()),
+                billing_subscription_id,
+                billing_subscription_deleted,
+                updated_at: Utc::now(),
+            };
+            event.dispatch(evt.into()).await?;
+            info!(
+                project = project.id,
+                subscription = subscription.id,
+                status = subscription.status.to_string(),
+                "project billing updated"
+            );
+        }
+
+        let suspended = matches!(project.status, ProjectStatus::Suspended);
+        match (&subscription.status, subscription.suspension_reason()) {
+            (_, Some(reason)) if !suspended => {
+                // Dispatched from the project in hand, the cache may not have applied the update
+                // above yet.
+                let evt = ProjectSuspended {
+                    id: project.id.clone(),
+                    namespace: project.namespace.clone(),
+                    reason,
+                    suspended_by: BILLING_ACTOR.into(),
+                    suspended_at: Utc::now(),
+                };
+                event.dispatch(evt.into()).await?;
+                info!(
+                    project = project.id,
+                    subscription = subscription.id,
+                    "project suspended"
+                );
+            }
+            (SubscriptionStatus::Active, _)
+                if suspended && is_suspended_by_billing(audit.clone(), &project.id).await? =>
+            {
+                apply_resume(
+                    cache.clone(),
+                    event.clone(),
+                    &project.id,
+                    BILLING_ACTOR,
+                    false,
+                )
+                .await?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Flags the projects of a failed invoice, the subscription updates that follow decide whether
+/// they're suspended.
+pub async fn apply_payment_failure(
+    cache: Arc<dyn ProjectDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    failure: PaymentFailure,
+) -> Result<()> {
+    let projects = cache
+        .find_by_billing_provider_id(&failure.customer_id)
+        .await?
+        .into_iter()
+        .filter(|p| is_charged_on(p, failure.subscription_id.as_deref()))
+        .filter(|p| is_billing_change(&p.status, &ProjectStatus::PaymentMethodFailed))
+        .collect::<Vec<_>>();
+
+    for project in projects {
+        let evt = ProjectUpdated {
+            id: project.id.clone(),
+            name: None,
+            status: Some(ProjectStatus::PaymentMethodFailed.to_string()),
+            billing_subscription_id: failure.subscription_id.clone(),
+            billing_subscription_deleted: false,
+            updated_at: Utc::now(),
+        };
+        event.dispatch(evt.into()).await?;
+        info!(project = project.id, "project payment method failed");
+    }
+
+    Ok(())
+}
+
+/// Billing only moves projects between active and payment failed, a suspended or deleted
+/// project keeps its status.
+fn is_billing_change(current: &ProjectStatus, status: &ProjectStatus) -> bool {
+    matches!(
+        current,
+        ProjectStatus::Active | ProjectStatus::PaymentMethodFailed
+    ) && current.to_string() != status.to_string()
+}
+
+/// Whether the last suspension of the project came from billing, the ones from the backoffice are
+/// never lifted by a payment.
+async fn is_suspended_by_billing(
+    audit: Arc<dyn AuditDrivenCache>,
+    project_id: &str,
+) -> Result<bool> {
+    let filter = AuditFilter {
+        project_id: Some(project_id.into()),
+        event_type: Some("ProjectSuspended".into()),
+        ..Default::default()
+    };
+    let suspensions = audit.find(&filter, &1, &1).await?;
+
+    Ok(suspensions
+        .first()
+        .is_some_and(|suspension| suspension.actor.as_deref() == Some(BILLING_ACTOR)))
+}
+
+/// A project whose subscription isn't known yet can still be the one of the invoice.
+fn is_charged_on(project: &Project, subscription_id: Option<&str>) -> bool {
+    match (project.billing_subscription_id.as_deref(), subscription_id) {
+        (Some(current), Some(subscription_id)) => current == subscription_id,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{
+        audit::{cache::MockAuditDrivenCache, AuditEvent},
+        event::{Event, MockEventDrivenBridge},
+        project::cache::MockProjectDrivenCache,
+    };
+
+    use super::*;
+
+    impl Default for Subscription {
+        fn default() -> Self {
+            Self {
+                id: "sub_123".into(),
+                customer_id: "stripe id".into(),
+                project_id: None,
+                status: SubscriptionStatus::Active,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_set_subscription_of_customer_projects() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_by_billing_provider_id()
+            .return_once(|_| Ok(vec![Project::default()]));
+
+        let mut event = MockEventDrivenBridge::new();
+        event
+            .expect_dispatch()
+            .withf(|evt| match evt {
+                Event::ProjectUpdated(evt) => {
+                    evt.status.is_none()
+                        && evt.billing_subscription_id.as_deref() == Some("sub_123")
+                }
+                _ => false,
+            })
+            .return_once(|_| Ok(None));
+
+        let result = apply_subscription(
+            Arc::new(cache),
+            Arc::new(MockAuditDrivenCache::new()),
+            Arc::new(event),
+            Subscription::default(),
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_not_dispatch_when_subscription_is_unchanged() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache.expect_find_by_billing_provider_id().return_once(|_| {
+            Ok(vec![Project {
+                billing_subscription_id: Some("sub_123".into()),
+                ..Default::default()
+            }])
+        });
+
+        // No dispatch expectation: the mock panics if the event is dispatched.
+        let event = MockEventDrivenBridge::new();
+
+        let result = apply_subscription(
+            Arc::new(cache),
+            Arc::new(MockAuditDrivenCache::new()),
+            Arc::new(event),
+            Subscription::default(),
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    fn suspension(actor: &str) -> AuditEvent {
+        AuditEvent {
+            id: "event id".into(),
+            event_type: "ProjectSuspended".into(),
+            project_id: Some("project id".into()),
+            resource_id: None,
+            actor: Some(actor.into()),
+            payload: "{}".into(),
+            occurred_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_suspend_projects_of_unpaid_subscription() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache.expect_find_by_billing_provider_id().return_once(|_| {
+            Ok(vec![Project {
+                status: ProjectStatus::PaymentMethodFailed,
+                billing_subscription_id: Some("sub_123".into()),
+                ..Default::default()
+            }])
+        });
+
+        let mut event = MockEventDrivenBridge::new();
+        event
+            .expect_dispatch()
+            .withf(|evt| matches!(evt, Event::ProjectSuspended(evt) if evt.suspended_by == BILLING_ACTOR))
+            .return_once(|_| Ok(None));
+
+        let subscription = Subscription {
+            status: SubscriptionStatus::Unpaid,
+            ..Default::default()
+        };
+
+        let result = apply_subscription(
+            Arc::new(cache),
+            Arc::new(MockAuditDrivenCache::new()),
+            Arc::new(event),
+            subscription,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_clear_subscription_and_suspend_projects_of_canceled_subscription() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache.expect_find_by_billing_provider_id().return_once(|_| {
+            Ok(vec![Project {
+                billing_subscription_id: Some("sub_123".into()),
+                ..Default::default()
+            }])
+        });
+
+        let mut event = MockEventDrivenBridge::new();
+        let mut sequence = mockall::Sequence::new();
+        event
+            .expect_dispatch()
+            .withf(|evt| {
+                matches!(evt, Event::ProjectUpdated(evt) if evt.billing_subscription_deleted && evt.billing_subscription_id.is_none())
+            })
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| Ok(None));
+        event
+            .expect_dispatch()
+            .withf(|evt| {
+                matches!(evt, Event::ProjectSuspended(evt) if evt.reason == "subscription sub_123 was canceled")
+            })
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| Ok(None));
+
+        let subscription = Subscription {
+            status: SubscriptionStatus::Canceled,
+            ..Default::default()
+        };
+
+        let result = apply_subscription(
+            Arc::new(cache),
+            Arc::new(MockAuditDrivenCache::new()),
+            Arc::new(event),
+            subscription,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_ignore_canceled_subscription_the_project_moved_from() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache.expect_find_by_billing_provider_id().return_once(|_| {
+            Ok(vec![Project {
+                billing_subscription_id: Some("sub_456".into()),
+                ..Default::default()
+            }])
+        });
+
+        let subscription = Subscription {
+            status: SubscriptionStatus::Canceled,
+            ..Default::default()
+        };
+
+        let result = apply_subscription(
+            Arc::new(cache),
+            Arc::new(MockAuditDrivenCache::new()),
+            Arc::new(MockEventDrivenBridge::new()),
+            subscription,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_suspend_active_project_without_reading_the_cache_again() {
+        // No find_by_id expectation: the cache hasn't applied the status update yet, the mock
+        // panics if the project is read again.
+        let mut cache = MockProjectDrivenCache::new();
+        cache.expect_find_by_billing_provider_id().return_once(|_| {
+            Ok(vec![Project {
+                billing_subscription_id: Some("sub_123".into()),
+                ..Default::default()
+            }])
+        });
+
+        let mut event = MockEventDrivenBridge::new();
+        let mut sequence = mockall::Sequence::new();
+        event
+            .expect_dispatch()
+            .withf(|evt| {
+                matches!(evt, Event::ProjectUpdated(evt) if evt.status == Some(ProjectStatus::PaymentMethodFailed.to_string()))
+            })
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| Ok(None));
+        event
+            .expect_dispatch()
+            .withf(|evt| matches!(evt, Event::ProjectSuspended(_)))
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| Ok(None));
+
+        let subscription = Subscription {
+            status: SubscriptionStatus::Unpaid,
+            ..Default::default()
+        };
+
+        let result = apply_subscription(
+            Arc::new(cache),
+            Arc::new(MockAuditDrivenCache::new()),
+            Arc::new(event),
+            subscription,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_resume_project_suspended_by_billing_on_new_subscription() {
+        let project = Project {
+            status: ProjectStatus::Suspended,
+            billing_subscription_id: Some("sub_123".into()),
+            ..Default::default()
+        };
+
+        let mut cache = MockProjectDrivenCache::new();
+        let found = project.clone();
+        cache
+            .expect_find_by_billing_provider_id()
+            .return_once(move |_| Ok(vec![found]));
+        cache
+            .expect_find_by_id()
+            .return_once(move |_| Ok(Some(project)));
+
+        let mut audit = MockAuditDrivenCache::new();
+        audit
+            .expect_find()
+            .withf(|filter, _, _| filter.event_type.as_deref() == Some("ProjectSuspended"))
+            .return_once(|_, _, _| Ok(vec![suspension(BILLING_ACTOR)]));
+
+        let mut event = MockEventDrivenBridge::new();
+        let mut sequence = mockall::Sequence::new();
+        event
+            .expect_dispatch()
+            .withf(|evt| {
+                matches!(evt, Event::ProjectUpdated(evt) if evt.billing_subscription_id.as_deref() == Some("sub_new"))
+            })
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| Ok(None));
+        event
+            .expect_dispatch()
+            .withf(
+                |evt| matches!(evt, Event::ProjectResumed(evt) if evt.resumed_by == BILLING_ACTOR),
+            )
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|_| Ok(None));
+
+        // The customer canceled and subscribed again, there's no previous status.
+        let subscription = Subscription {
+            id: "sub_new".into(),
+            ..Default::default()
+        };
+
+        let result = apply_subscription(
+            Arc::new(cache),
+            Arc::new(audit),
+            Arc::new(event),
+            subscription,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_not_resume_project_suspended_outside_billing() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache.expect_find_by_billing_provider_id().return_once(|_| {
+            Ok(vec![Project {
+                status: ProjectStatus::Suspended,
+                billing_subscription_id: Some("sub_123".into()),
+                ..Default::default()
+            }])
+        });
+
+        let mut audit = MockAuditDrivenCache::new();
+        audit
+            .expect_find()
+            .return_once(|_, _, _| Ok(vec![suspension("backoffice")]));
+
+        // No dispatch expectation: the mock panics if the project is resumed.
+        let event = MockEventDrivenBridge::new();
+
+        let result = apply_subscription(
+            Arc::new(cache),
+            Arc::new(audit),
+            Arc::new(event),
+            Subscription::default(),
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_flag_projects_of_failed_payment() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache.expect_find_by_billing_provider_id().return_once(|_| {
+            Ok(vec![
+                Project {
+                    billing_subscription_id: Some("sub_123".into()),
+                    ..Default::default()
+                },
+                Project {
+                    billing_subscription_id: Some("sub_other".into()),
+                    ..Default::default()
+                },
+            ])
+        });
+
+        let mut event = MockEventDrivenBridge::new();
+        event.expect_dispatch().times(1).returning(|_| Ok(None));
+
+        let failure = PaymentFailure {
+            customer_id: "stripe id".into(),
+            subscription_id: Some("sub_123".into()),
+        };
+
+        let result = apply_payment_failure(Arc::new(cache), Arc::new(event), failure).await;
+        assert!(result.is_ok());
+    }
+}
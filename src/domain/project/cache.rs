@@ -1,38 +1,47 @@
 use chrono::{DateTime, Utc};
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use crate::domain::event::{
-    ProjectCreated, ProjectDeleted, ProjectOwnerChanged, ProjectSecretCreated,
-    ProjectSecretDeleted, ProjectUpdated, ProjectUserDeleted, ProjectUserInviteAccepted,
-    ProjectUserInviteCreated, ProjectUserInviteDeleted,
+    EventOffset, ProjectCreated, ProjectDeleted, ProjectOrganizationChanged, ProjectOwnerChanged,
+    ProjectOwnershipTransferAccepted, ProjectOwnershipTransferCancelled,
+    ProjectOwnershipTransferProposed, ProjectQuotaUpdated, ProjectRestored, ProjectResumed,
+    ProjectSecretCreated, ProjectSecretDeleted, ProjectSuspended, ProjectUpdated,
+    ProjectUserDeleted, ProjectUserInviteAccepted, ProjectUserInviteCreated,
+    ProjectUserInviteDeleted,
 };
-use crate::domain::Result;
+use crate::domain::{pagination::Page, Result};
 
 use super::{
-    Project, ProjectOwnerChange, ProjectSecret, ProjectUpdate, ProjectUser, ProjectUserInvite,
-    ProjectUserProject,
+    quota::ProjectQuota, Project, ProjectOrganizationChange, ProjectOwnerChange,
+    ProjectOwnershipTransfer, ProjectSecret, ProjectStatus, ProjectUpdate, ProjectUser,
+    ProjectUserInvite, ProjectUserProject,
 };
 
 #[cfg_attr(test, mockall::automock)]
 #[async_trait::async_trait]
 pub trait ProjectDrivenCache: Send + Sync {
-    async fn find(&self, user_id: &str, page: &u32, page_size: &u32) -> Result<Vec<Project>>;
+    /// Projects the user is a member of, directly or through their organization.
+    async fn find(&self, user_id: &str, page: &Page) -> Result<Vec<Project>>;
     async fn find_by_namespace(&self, namespace: &str) -> Result<Option<Project>>;
     async fn find_by_id(&self, id: &str) -> Result<Option<Project>>;
+    /// Projects charged to the billing customer, the projects of an organization share one.
+    async fn find_by_billing_provider_id(&self, billing_provider_id: &str) -> Result<Vec<Project>>;
+    /// The deleted project, its `updated_at` is the moment it was deleted.
+    async fn find_deleted_by_id(&self, id: &str) -> Result<Option<Project>>;
     async fn create(&self, project: &Project) -> Result<()>;
     async fn update(&self, project: &ProjectUpdate) -> Result<()>;
     async fn change_owner(&self, change: &ProjectOwnerChange) -> Result<()>;
+    async fn change_organization(&self, change: &ProjectOrganizationChange) -> Result<()>;
     async fn delete(&self, id: &str, deleted_at: &DateTime<Utc>) -> Result<()>;
+    async fn restore(&self, id: &str, restored_at: &DateTime<Utc>) -> Result<()>;
     async fn create_secret(&self, secret: &ProjectSecret) -> Result<()>;
     async fn find_secrets(&self, project: &str) -> Result<Vec<ProjectSecret>>;
     async fn find_secret_by_id(&self, id: &str) -> Result<Option<ProjectSecret>>;
     async fn delete_secret(&self, id: &str) -> Result<()>;
-    async fn find_users(
-        &self,
-        project_id: &str,
-        page: &u32,
-        page_size: &u32,
-    ) -> Result<Vec<ProjectUser>>;
+    async fn find_users(&self, project_id: &str, page: &Page) -> Result<Vec<ProjectUser>>;
+    /// Role of the user in the project. A member of the project's organization has the role they
+    /// hold in the organization, the one with more permissions wins when the user is also a
+    /// direct member.
     async fn find_user_permission(
         &self,
         user_id: &str,
@@ -41,8 +50,7 @@ pub trait ProjectDrivenCache: Send + Sync {
     async fn find_user_invites(
         &self,
         project_id: &str,
-        page: &u32,
-        page_size: &u32,
+        page: &Page,
     ) -> Result<Vec<ProjectUserInvite>>;
     async fn find_user_invite_by_id(&self, id: &str) -> Result<Option<ProjectUserInvite>>;
     async fn find_user_invite_by_code(&self, code: &str) -> Result<Option<ProjectUserInvite>>;
@@ -50,6 +58,32 @@ pub trait ProjectDrivenCache: Send + Sync {
     async fn create_user_acceptance(&self, invite_id: &str, user: &ProjectUser) -> Result<()>;
     async fn delete_user_invite(&self, invite_id: &str) -> Result<()>;
     async fn delete_user(&self, project_id: &str, id: &str) -> Result<()>;
+    async fn find_ownership_transfer_by_id(
+        &self,
+        id: &str,
+    ) -> Result<Option<ProjectOwnershipTransfer>>;
+    async fn find_ownership_transfer_by_code(
+        &self,
+        code: &str,
+    ) -> Result<Option<ProjectOwnershipTransfer>>;
+    async fn create_ownership_transfer(&self, transfer: &ProjectOwnershipTransfer) -> Result<()>;
+    /// Changes the owner as `change_owner` does and marks the transfer accepted.
+    async fn accept_ownership_transfer(
+        &self,
+        transfer_id: &str,
+        change: &ProjectOwnerChange,
+    ) -> Result<()>;
+    async fn cancel_ownership_transfer(
+        &self,
+        transfer_id: &str,
+        cancelled_at: &DateTime<Utc>,
+    ) -> Result<()>;
+    async fn find_quota(&self, project_id: &str) -> Result<Option<ProjectQuota>>;
+    /// Quota of the projects without their own limits.
+    async fn find_default_quota(&self) -> Result<Option<ProjectQuota>>;
+    async fn update_quota(&self, quota: &ProjectQuota) -> Result<()>;
+    /// Waits until the event at `offset` is applied, `false` when the timeout is reached first.
+    async fn wait_applied(&self, offset: &EventOffset, timeout: Duration) -> Result<bool>;
 }
 
 #[cfg_attr(test, mockall::automock)]
@@ -77,10 +111,75 @@ pub async fn change_owner(
     cache.change_owner(&evt.into()).await
 }
 
+pub async fn create_ownership_transfer(
+    cache: Arc<dyn ProjectDrivenCache>,
+    evt: ProjectOwnershipTransferProposed,
+) -> Result<()> {
+    cache.create_ownership_transfer(&evt.into()).await
+}
+
+pub async fn accept_ownership_transfer(
+    cache: Arc<dyn ProjectDrivenCache>,
+    evt: ProjectOwnershipTransferAccepted,
+) -> Result<()> {
+    cache
+        .accept_ownership_transfer(&evt.id.clone(), &evt.into())
+        .await
+}
+
+pub async fn cancel_ownership_transfer(
+    cache: Arc<dyn ProjectDrivenCache>,
+    evt: ProjectOwnershipTransferCancelled,
+) -> Result<()> {
+    cache
+        .cancel_ownership_transfer(&evt.id, &evt.cancelled_at)
+        .await
+}
+
+pub async fn update_quota(
+    cache: Arc<dyn ProjectDrivenCache>,
+    evt: ProjectQuotaUpdated,
+) -> Result<()> {
+    cache.update_quota(&evt.into()).await
+}
+
+pub async fn change_organization(
+    cache: Arc<dyn ProjectDrivenCache>,
+    evt: ProjectOrganizationChanged,
+) -> Result<()> {
+    cache.change_organization(&evt.into()).await
+}
+
 pub async fn delete(cache: Arc<dyn ProjectDrivenCache>, evt: ProjectDeleted) -> Result<()> {
     cache.delete(&evt.id, &evt.deleted_at).await
 }
 
+pub async fn restore(cache: Arc<dyn ProjectDrivenCache>, evt: ProjectRestored) -> Result<()> {
+    cache.restore(&evt.id, &evt.restored_at).await
+}
+
+pub async fn suspend(cache: Arc<dyn ProjectDrivenCache>, evt: ProjectSuspended) -> Result<()> {
+    let update = ProjectUpdate {
+        id: evt.id,
+        name: None,
+        status: Some(ProjectStatus::Suspended),
+        billing_subscription_id: None,
+        updated_at: evt.suspended_at,
+    };
+    cache.update(&update).await
+}
+
+pub async fn resume(cache: Arc<dyn ProjectDrivenCache>, evt: ProjectResumed) -> Result<()> {
+    let update = ProjectUpdate {
+        id: evt.id,
+        name: None,
+        status: Some(ProjectStatus::Active),
+        billing_subscription_id: None,
+        updated_at: evt.resumed_at,
+    };
+    cache.update(&update).await
+}
+
 pub async fn create_secret(
     cache: Arc<dyn ProjectDrivenCache>,
     evt: ProjectSecretCreated,
@@ -150,6 +249,59 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn it_should_change_project_organization_cache() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache.expect_change_organization().return_once(|_| Ok(()));
+
+        let evt = ProjectOrganizationChanged::default();
+
+        let result = change_organization(Arc::new(cache), evt).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_update_project_quota_cache() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_update_quota()
+            .withf(|quota| quota.project_id.is_some() && quota.max_tier == Some(1))
+            .return_once(|_| Ok(()));
+
+        let evt = ProjectQuotaUpdated::default();
+
+        let result = update_quota(Arc::new(cache), evt).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_suspend_project_cache() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_update()
+            .withf(|update| matches!(update.status, Some(ProjectStatus::Suspended)))
+            .return_once(|_| Ok(()));
+
+        let evt = ProjectSuspended::default();
+
+        let result = suspend(Arc::new(cache), evt).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_accept_project_ownership_transfer_cache() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_accept_ownership_transfer()
+            .withf(|_, change| change.new_owner == "member user id")
+            .return_once(|_, _| Ok(()));
+
+        let evt = ProjectOwnershipTransferAccepted::default();
+
+        let result = accept_ownership_transfer(Arc::new(cache), evt).await;
+        assert!(result.is_ok());
+    }
+
     #[tokio::test]
     async fn it_should_create_project_secret_cache() {
         let mut cache = MockProjectDrivenCache::new();
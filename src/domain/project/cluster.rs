@@ -5,16 +5,24 @@ use kube::{api::ObjectMeta, ResourceExt};
 use tracing::info;
 
 use crate::domain::{
-    event::{ProjectCreated, ProjectDeleted},
+    event::{ProjectCreated, ProjectDeleted, ProjectRestored, ProjectResumed, ProjectSuspended},
     utils::cluster_namespace,
     Result,
 };
 
+/// Annotation set on every resource of a suspended project, the operators scale them down while
+/// it's there.
+pub const SUSPENDED_ANNOTATION: &str = "demeter.run/suspended";
+
 #[cfg_attr(test, mockall::automock)]
 #[async_trait::async_trait]
 pub trait ProjectDrivenCluster: Send + Sync {
     async fn create(&self, namespace: &Namespace) -> Result<()>;
     async fn delete(&self, namespace: &Namespace) -> Result<()>;
+    /// Sets `SUSPENDED_ANNOTATION` on the resources of the namespace.
+    async fn suspend(&self, namespace: &str) -> Result<()>;
+    /// Removes `SUSPENDED_ANNOTATION` from the resources of the namespace.
+    async fn resume(&self, namespace: &str) -> Result<()>;
 }
 
 pub async fn apply_manifest(
@@ -55,6 +63,49 @@ pub async fn delete_manifest(
     Ok(())
 }
 
+/// Creates the namespace again, the resources are re-applied by their own restore events.
+pub async fn restore_manifest(
+    cluster: Arc<dyn ProjectDrivenCluster>,
+    evt: ProjectRestored,
+) -> Result<()> {
+    let namespace = Namespace {
+        metadata: ObjectMeta {
+            name: Some(cluster_namespace(&evt.namespace)),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    cluster.create(&namespace).await?;
+
+    info!(namespace = namespace.name_any(), "namespace restored");
+
+    Ok(())
+}
+
+pub async fn suspend_manifest(
+    cluster: Arc<dyn ProjectDrivenCluster>,
+    evt: ProjectSuspended,
+) -> Result<()> {
+    let namespace = cluster_namespace(&evt.namespace);
+    cluster.suspend(&namespace).await?;
+
+    info!(namespace, reason = evt.reason, "namespace suspended");
+
+    Ok(())
+}
+
+pub async fn resume_manifest(
+    cluster: Arc<dyn ProjectDrivenCluster>,
+    evt: ProjectResumed,
+) -> Result<()> {
+    let namespace = cluster_namespace(&evt.namespace);
+    cluster.resume(&namespace).await?;
+
+    info!(namespace, "namespace resumed");
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -69,4 +120,18 @@ mod tests {
         let result = apply_manifest(Arc::new(cluster), project).await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn it_should_suspend_manifest_in_project_namespace() {
+        let mut cluster = MockProjectDrivenCluster::new();
+        cluster
+            .expect_suspend()
+            .withf(|namespace| namespace == "prj-sonic-vegas")
+            .return_once(|_| Ok(()));
+
+        let evt = ProjectSuspended::default();
+
+        let result = suspend_manifest(Arc::new(cluster), evt).await;
+        assert!(result.is_ok());
+    }
 }
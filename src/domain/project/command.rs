@@ -6,19 +6,27 @@ use rand::{
     rngs::OsRng,
 };
 use std::{sync::Arc, time::Duration};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use crate::domain::{
-    auth::{assert_permission, Auth0Driven, Credential, UserId},
+    auth::{assert_permission, Auth0Driven, Credential, Permission, UserId},
     error::Error,
     event::{
-        EventDrivenBridge, ProjectCreated, ProjectDeleted, ProjectOwnerChanged,
-        ProjectSecretCreated, ProjectSecretDeleted, ProjectUpdated, ProjectUserDeleted,
+        Event, EventDrivenBridge, ProjectCreated, ProjectDeleted, ProjectOwnerChanged,
+        ProjectOwnershipTransferAccepted, ProjectOwnershipTransferCancelled,
+        ProjectOwnershipTransferProposed, ProjectRestored, ProjectResumed, ProjectSecretCreated,
+        ProjectSecretDeleted, ProjectSuspended, ProjectUpdated, ProjectUserDeleted,
         ProjectUserInviteAccepted, ProjectUserInviteCreated, ProjectUserInviteDeleted,
+        ResourceRestored, APPLY_TIMEOUT,
     },
-    project::{ProjectStatus, ProjectUserAggregated, ProjectUserInviteStatus},
-    utils, Result, MAX_SECRET, PAGE_SIZE_DEFAULT, PAGE_SIZE_MAX,
+    pagination::{Cursor, Page, Paged},
+    project::{
+        ProjectOwnershipTransferStatus, ProjectStatus, ProjectUserAggregated,
+        ProjectUserInviteStatus,
+    },
+    resource::cache::ResourceDrivenCache,
+    utils, Result, MAX_SECRET,
 };
 
 use super::{
@@ -26,17 +34,27 @@ use super::{
     ProjectUserRole, StripeDriven,
 };
 
-pub async fn fetch(cache: Arc<dyn ProjectDrivenCache>, cmd: FetchCmd) -> Result<Vec<Project>> {
+pub async fn fetch(cache: Arc<dyn ProjectDrivenCache>, cmd: FetchCmd) -> Result<Paged<Project>> {
     let user_id = assert_credential(&cmd.credential)?;
 
-    cache.find(&user_id, &cmd.page, &cmd.page_size).await
+    let projects = cache.find(&user_id, &cmd.page).await?;
+
+    Ok(cmd
+        .page
+        .paged(projects, |p| Cursor::new(Some(p.created_at), &p.id)))
 }
 
 pub async fn fetch_by_id(cache: Arc<dyn ProjectDrivenCache>, cmd: FetchByIdCmd) -> Result<Project> {
     let Some(project) = cache.find_by_id(&cmd.id).await? else {
         return Err(Error::CommandMalformed("invalid project id".into()));
     };
-    assert_permission(cache.clone(), &cmd.credential, &project.id, None).await?;
+    assert_permission(
+        cache.clone(),
+        &cmd.credential,
+        &project.id,
+        Permission::ProjectRead,
+    )
+    .await?;
 
     Ok(project)
 }
@@ -48,7 +66,13 @@ pub async fn fetch_by_namespace(
     let Some(project) = cache.find_by_namespace(&cmd.namespace).await? else {
         return Err(Error::CommandMalformed("invalid project namespace".into()));
     };
-    assert_permission(cache.clone(), &cmd.credential, &project.id, None).await?;
+    assert_permission(
+        cache.clone(),
+        &cmd.credential,
+        &project.id,
+        Permission::ProjectRead,
+    )
+    .await?;
 
     Ok(project)
 }
@@ -85,6 +109,7 @@ pub async fn create(
         billing_provider: "stripe".into(),
         billing_provider_id,
         billing_subscription_id: None,
+        cluster_id: cmd.cluster_id,
         created_at: Utc::now(),
         updated_at: Utc::now(),
     };
@@ -105,7 +130,7 @@ pub async fn update(
         cache.clone(),
         &cmd.credential,
         &cmd.id,
-        Some(ProjectUserRole::Owner),
+        Permission::ProjectUpdate,
     )
     .await?;
 
@@ -113,12 +138,23 @@ pub async fn update(
         id: cmd.id.clone(),
         name: Some(cmd.name.clone()),
         status: None,
+        billing_subscription_id: None,
+        billing_subscription_deleted: false,
         updated_at: Utc::now(),
     };
 
-    event.dispatch(evt.into()).await?;
+    let offset = event.dispatch(evt.into()).await?;
     info!(project = &cmd.id, "project updated");
 
+    if let Some(offset) = offset {
+        if !cache.wait_applied(&offset, APPLY_TIMEOUT).await? {
+            warn!(
+                project = &cmd.id,
+                "project update not applied to the cache in time"
+            );
+        }
+    }
+
     let Some(project) = cache.find_by_id(&cmd.id).await? else {
         return Err(Error::CommandMalformed("Missing project".into()));
     };
@@ -126,46 +162,200 @@ pub async fn update(
     Ok(project)
 }
 
-/// Self-service entry point for an ownership transfer: only the current owner may transfer.
-///
-/// Not yet reachable over gRPC — transfers are run from the backoffice CLI, which calls
-/// `apply_ownership_transfer` directly. This exists so the authorization rule for the self-service
-/// path is settled and tested ahead of the RPC being added.
-#[allow(dead_code)]
-pub async fn transfer_ownership(
+/// First half of a self-service ownership transfer: the owner picks a member, who is mailed a code
+/// and only becomes the owner once they accept it with `accept_ownership_transfer`. The backoffice
+/// keeps its direct `transfer-project` command through `apply_ownership_transfer`.
+pub async fn propose_ownership_transfer(
     cache: Arc<dyn ProjectDrivenCache>,
+    email: Arc<dyn ProjectEmailDriven>,
     event: Arc<dyn EventDrivenBridge>,
     auth0: Arc<dyn Auth0Driven>,
-    stripe: Arc<dyn StripeDriven>,
-    cmd: TransferOwnershipCmd,
+    cmd: ProposeOwnershipTransferCmd,
 ) -> Result<()> {
-    let user_id = assert_credential(&cmd.credential)?;
+    assert_credential(&cmd.credential)?;
     assert_permission(
         cache.clone(),
         &cmd.credential,
         &cmd.project_id,
-        Some(ProjectUserRole::Owner),
+        Permission::ProjectTransfer,
     )
     .await?;
 
-    apply_ownership_transfer(
-        cache,
-        event,
-        auth0,
-        Some(stripe),
-        &cmd.project_id,
-        &cmd.new_owner,
-        &user_id,
-        false,
-    )
-    .await
+    let Some(project) = cache.find_by_id(&cmd.project_id).await? else {
+        return Err(Error::CommandMalformed("invalid project id".into()));
+    };
+
+    if project.owner == cmd.new_owner {
+        return Err(Error::CommandMalformed(
+            "user already is the project owner".into(),
+        ));
+    }
+
+    if cache
+        .find_user_permission(&cmd.new_owner, &project.id)
+        .await?
+        .is_none()
+    {
+        return Err(Error::CommandMalformed(
+            "new owner is not a member of the project".into(),
+        ));
+    }
+
+    let profile = auth0
+        .find_info(&format!("user_id:{}", cmd.new_owner))
+        .await?;
+    if profile.is_empty() {
+        return Err(Error::Unexpected("Invalid user_id".into()));
+    }
+    let profile = profile.first().unwrap();
+
+    let code = Uuid::new_v4().to_string();
+    let expires_in = Utc::now() + cmd.ttl;
+
+    email
+        .send_ownership_transfer(&project.name, &profile.email, &code, &expires_in)
+        .await?;
+
+    let evt = ProjectOwnershipTransferProposed {
+        id: cmd.id,
+        project_id: project.id,
+        previous_owner: project.owner,
+        new_owner: cmd.new_owner,
+        code,
+        expires_in,
+        created_at: Utc::now(),
+    };
+
+    event.dispatch(evt.into()).await?;
+    info!(?expires_in, "project ownership transfer proposed");
+
+    Ok(())
+}
+
+/// Second half of a self-service ownership transfer, run by the proposed owner with their code.
+///
+/// The transfer is refused if the project changed hands after it was proposed, so an old code
+/// can't undo a later transfer. Stripe is updated before dispatching, as in
+/// `apply_ownership_transfer`.
+pub async fn accept_ownership_transfer(
+    cache: Arc<dyn ProjectDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    auth0: Arc<dyn Auth0Driven>,
+    stripe: Arc<dyn StripeDriven>,
+    cmd: AcceptOwnershipTransferCmd,
+) -> Result<()> {
+    let user_id = assert_credential(&cmd.credential)?;
+
+    let Some(transfer) = cache.find_ownership_transfer_by_code(&cmd.code).await? else {
+        return Err(Error::CommandMalformed("invalid transfer code".into()));
+    };
+    if transfer.status != ProjectOwnershipTransferStatus::Proposed {
+        return Err(Error::CommandMalformed(
+            "transfer is not available anymore".into(),
+        ));
+    }
+    if Utc::now() > transfer.expires_in {
+        return Err(Error::CommandMalformed("transfer code expired".into()));
+    }
+    if transfer.new_owner != user_id {
+        return Err(Error::Unauthorized(
+            "transfer was proposed to another user".into(),
+        ));
+    }
+
+    let Some(project) = cache.find_by_id(&transfer.project_id).await? else {
+        return Err(Error::CommandMalformed("invalid project id".into()));
+    };
+    if project.owner != transfer.previous_owner {
+        return Err(Error::CommandMalformed(
+            "project owner changed since the transfer was proposed".into(),
+        ));
+    }
+
+    if cache
+        .find_user_permission(&user_id, &project.id)
+        .await?
+        .is_none()
+    {
+        return Err(Error::CommandMalformed(
+            "new owner is not a member of the project".into(),
+        ));
+    }
+
+    let profile = auth0.find_info(&format!("user_id:{user_id}")).await?;
+    if profile.is_empty() {
+        return Err(Error::Unexpected("Invalid user_id".into()));
+    }
+    let profile = profile.first().unwrap();
+
+    stripe
+        .update_customer(&project.billing_provider_id, &profile.name, &profile.email)
+        .await?;
+
+    let evt = ProjectOwnershipTransferAccepted {
+        id: transfer.id,
+        project_id: project.id,
+        previous_owner: project.owner,
+        new_owner: user_id,
+        accepted_at: Utc::now(),
+    };
+
+    event.dispatch(evt.into()).await?;
+    info!(
+        project = &transfer.project_id,
+        "project ownership transfer accepted"
+    );
+
+    Ok(())
+}
+
+/// Withdraws a proposed transfer. The proposed owner may always decline it, anyone else needs
+/// the permission to transfer the project.
+pub async fn cancel_ownership_transfer(
+    cache: Arc<dyn ProjectDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    cmd: CancelOwnershipTransferCmd,
+) -> Result<()> {
+    let user_id = assert_credential(&cmd.credential)?;
+
+    let Some(transfer) = cache.find_ownership_transfer_by_id(&cmd.id).await? else {
+        return Err(Error::CommandMalformed("invalid transfer id".into()));
+    };
+
+    if transfer.new_owner != user_id {
+        assert_permission(
+            cache.clone(),
+            &cmd.credential,
+            &transfer.project_id,
+            Permission::ProjectTransfer,
+        )
+        .await?;
+    }
+
+    if transfer.status != ProjectOwnershipTransferStatus::Proposed {
+        return Err(Error::CommandMalformed(
+            "transfer is not available anymore".into(),
+        ));
+    }
+
+    let evt = ProjectOwnershipTransferCancelled {
+        id: transfer.id,
+        project_id: transfer.project_id,
+        cancelled_by: user_id,
+        cancelled_at: Utc::now(),
+    };
+
+    event.dispatch(evt.into()).await?;
+    info!("project ownership transfer cancelled");
+
+    Ok(())
 }
 
 /// Validates and performs an ownership transfer, with no authorization check of its own.
 ///
-/// Callers are responsible for authorization: `transfer_ownership` asserts the caller holds the
-/// `Owner` role, while the backoffice driver is already privileged and passes `changed_by` to
-/// record who ran it.
+/// Callers are responsible for authorization: the backoffice driver is already privileged and
+/// passes `changed_by` to record who ran it. Users go through the two-step
+/// `propose_ownership_transfer` instead, which needs the new owner's consent.
 ///
 /// With `dry_run`, every validation still runs but neither the Stripe update nor the event
 /// dispatch happens, so an operator can confirm a transfer is viable before committing to it.
@@ -267,7 +457,7 @@ pub async fn delete(
         cache.clone(),
         &cmd.credential,
         &cmd.id,
-        Some(ProjectUserRole::Owner),
+        Permission::ProjectDelete,
     )
     .await?;
 
@@ -285,6 +475,7 @@ pub async fn delete(
     let evt = ProjectDeleted {
         id: cmd.id.clone(),
         namespace: project.namespace,
+        deleted_by: Some(user_id),
         deleted_at: Utc::now(),
     };
 
@@ -294,6 +485,162 @@ pub async fn delete(
     Ok(())
 }
 
+pub async fn restore(
+    project_cache: Arc<dyn ProjectDrivenCache>,
+    resource_cache: Arc<dyn ResourceDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    cmd: RestoreCmd,
+) -> Result<()> {
+    let user_id = assert_credential(&cmd.credential)?;
+    assert_permission(
+        project_cache.clone(),
+        &cmd.credential,
+        &cmd.id,
+        Permission::ProjectDelete,
+    )
+    .await?;
+
+    apply_restore(
+        project_cache,
+        resource_cache,
+        event,
+        &cmd.id,
+        &user_id,
+        false,
+    )
+    .await
+}
+
+/// Restores a deleted project together with the resources deleted with it, the ones deleted
+/// before must be restored one by one. Like `apply_ownership_transfer` it has no authorization
+/// check, the backoffice calls it directly.
+pub async fn apply_restore(
+    project_cache: Arc<dyn ProjectDrivenCache>,
+    resource_cache: Arc<dyn ResourceDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    project_id: &str,
+    restored_by: &str,
+    dry_run: bool,
+) -> Result<()> {
+    let Some(project) = project_cache.find_deleted_by_id(project_id).await? else {
+        return Err(Error::CommandMalformed("invalid project id".into()));
+    };
+    utils::assert_restorable(&project.updated_at)?;
+
+    let resources = resource_cache
+        .find_deleted_with_project(&project.id, &project.updated_at)
+        .await?;
+
+    let restored_at = Utc::now();
+    let mut events: Vec<Event> = vec![ProjectRestored {
+        id: project.id.clone(),
+        namespace: project.namespace.clone(),
+        restored_by: restored_by.to_string(),
+        restored_at,
+    }
+    .into()];
+    events.extend(resources.into_iter().map(|resource| {
+        ResourceRestored {
+            id: resource.id,
+            project_id: project.id.clone(),
+            project_namespace: project.namespace.clone(),
+            name: resource.name,
+            kind: resource.kind,
+            category: resource.category,
+            spec: resource.spec,
+            cluster_id: resource.cluster_id,
+            restored_by: restored_by.to_string(),
+            restored_at,
+        }
+        .into()
+    }));
+
+    if dry_run {
+        for evt in events {
+            info!("event to dispath: {:?}", evt);
+        }
+        return Ok(());
+    }
+
+    for evt in events {
+        event.dispatch(evt).await?;
+    }
+    info!(project = project_id, "project restored");
+
+    Ok(())
+}
+
+/// Suspends the project, its resources are stopped by the monitor and its API keys refused.
+/// There's no authorization check, it's run from the backoffice and the billing webhooks.
+pub async fn apply_suspend(
+    cache: Arc<dyn ProjectDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    project_id: &str,
+    reason: &str,
+    suspended_by: &str,
+    dry_run: bool,
+) -> Result<()> {
+    let Some(project) = cache.find_by_id(project_id).await? else {
+        return Err(Error::CommandMalformed("invalid project id".into()));
+    };
+    if matches!(project.status, ProjectStatus::Suspended) {
+        return Err(Error::CommandMalformed(
+            "project is already suspended".into(),
+        ));
+    }
+
+    let evt = ProjectSuspended {
+        id: project.id,
+        namespace: project.namespace,
+        reason: reason.to_string(),
+        suspended_by: suspended_by.to_string(),
+        suspended_at: Utc::now(),
+    };
+
+    if dry_run {
+        info!("event to dispath: {:?}", evt);
+        return Ok(());
+    }
+
+    event.dispatch(evt.into()).await?;
+    info!(project = project_id, reason, "project suspended");
+
+    Ok(())
+}
+
+/// Brings a suspended project back to active.
+pub async fn apply_resume(
+    cache: Arc<dyn ProjectDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    project_id: &str,
+    resumed_by: &str,
+    dry_run: bool,
+) -> Result<()> {
+    let Some(project) = cache.find_by_id(project_id).await? else {
+        return Err(Error::CommandMalformed("invalid project id".into()));
+    };
+    if !matches!(project.status, ProjectStatus::Suspended) {
+        return Err(Error::CommandMalformed("project isn't suspended".into()));
+    }
+
+    let evt = ProjectResumed {
+        id: project.id,
+        namespace: project.namespace,
+        resumed_by: resumed_by.to_string(),
+        resumed_at: Utc::now(),
+    };
+
+    if dry_run {
+        info!("event to dispath: {:?}", evt);
+        return Ok(());
+    }
+
+    event.dispatch(evt.into()).await?;
+    info!(project = project_id, "project resumed");
+
+    Ok(())
+}
+
 pub async fn fetch_secret(
     cache: Arc<dyn ProjectDrivenCache>,
     cmd: FetchSecretCmd,
@@ -303,7 +650,7 @@ pub async fn fetch_secret(
         cache.clone(),
         &cmd.credential,
         &cmd.project_id,
-        Some(ProjectUserRole::Owner),
+        Permission::SecretRead,
     )
     .await?;
 
@@ -320,7 +667,7 @@ pub async fn create_secret(
         cache.clone(),
         &cmd.credential,
         &cmd.project_id,
-        Some(ProjectUserRole::Owner),
+        Permission::SecretCreate,
     )
     .await?;
 
@@ -415,6 +762,11 @@ pub async fn verify_secret(
         return Err(Error::Unauthorized("invalid project secret".into()));
     };
 
+    let project = cache.find_by_id(&secret.project_id).await?;
+    if project.is_some_and(|p| matches!(p.status, ProjectStatus::Suspended)) {
+        return Err(Error::Unauthorized("project is suspended".into()));
+    }
+
     Ok(secret)
 }
 pub async fn delete_secret(
@@ -432,7 +784,7 @@ pub async fn delete_secret(
         cache.clone(),
         &cmd.credential,
         &secret.project_id,
-        Some(ProjectUserRole::Owner),
+        Permission::SecretDelete,
     )
     .await?;
 
@@ -452,17 +804,17 @@ pub async fn fetch_user(
     cache: Arc<dyn ProjectDrivenCache>,
     auth0: Arc<dyn Auth0Driven>,
     cmd: FetchUserCmd,
-) -> Result<Vec<ProjectUserAggregated>> {
+) -> Result<Paged<ProjectUserAggregated>> {
     assert_credential(&cmd.credential)?;
     assert_permission(
         cache.clone(),
         &cmd.credential,
         &cmd.project_id,
-        Some(ProjectUserRole::Owner),
+        Permission::UserRead,
     )
     .await?;
 
-    list_users(cache, auth0, &cmd.project_id, &cmd.page, &cmd.page_size).await
+    list_users(cache, auth0, &cmd.project_id, &cmd.page).await
 }
 
 /// Lists a project's users with their roles, enriched with their Auth0 profiles.
@@ -473,15 +825,17 @@ pub async fn list_users(
     cache: Arc<dyn ProjectDrivenCache>,
     auth0: Arc<dyn Auth0Driven>,
     project_id: &str,
-    page: &u32,
-    page_size: &u32,
-) -> Result<Vec<ProjectUserAggregated>> {
-    let project_users = cache.find_users(project_id, page, page_size).await?;
+    page: &Page,
+) -> Result<Paged<ProjectUserAggregated>> {
+    let project_users = cache.find_users(project_id, page).await?;
 
     // An empty page would otherwise build an empty Auth0 query, which is not a search for nothing
     // — it returns every user in the tenant.
     if project_users.is_empty() {
-        return Ok(Vec::new());
+        return Ok(Paged {
+            records: Vec::new(),
+            next_cursor: None,
+        });
     }
 
     let ids: Vec<String> = project_users
@@ -512,7 +866,9 @@ pub async fn list_users(
         })
         .collect();
 
-    Ok(project_users_aggregated)
+    Ok(page.paged(project_users_aggregated, |u| {
+        Cursor::new(Some(u.created_at), &u.user_id)
+    }))
 }
 
 pub async fn fetch_me_user(
@@ -521,7 +877,13 @@ pub async fn fetch_me_user(
     cmd: FetchMeUserCmd,
 ) -> Result<ProjectUserAggregated> {
     let user_id = assert_credential(&cmd.credential)?;
-    assert_permission(cache.clone(), &cmd.credential, &cmd.project_id, None).await?;
+    assert_permission(
+        cache.clone(),
+        &cmd.credential,
+        &cmd.project_id,
+        Permission::ProjectRead,
+    )
+    .await?;
 
     let Some(project_user) = cache
         .find_user_permission(&user_id, &cmd.project_id)
@@ -556,11 +918,11 @@ pub async fn delete_user(
     cmd: DeleteUserCmd,
 ) -> Result<()> {
     let user_id = assert_credential(&cmd.credential)?;
-    assert_permission(
+    let role = assert_permission(
         cache.clone(),
         &cmd.credential,
         &cmd.project_id,
-        Some(ProjectUserRole::Owner),
+        Permission::UserDelete,
     )
     .await?;
 
@@ -575,6 +937,12 @@ pub async fn delete_user(
     let Some(user_permission) = cache.find_user_permission(&cmd.id, &cmd.project_id).await? else {
         return Err(Error::CommandMalformed("invalid user id".into()));
     };
+    if role.is_some_and(|role| !role.can_grant(&user_permission.role)) {
+        return Err(Error::Unauthorized(format!(
+            "user can not delete a user with the {} role",
+            user_permission.role
+        )));
+    }
 
     let evt = ProjectUserDeleted {
         id: Uuid::new_v4().to_string(),
@@ -594,19 +962,21 @@ pub async fn delete_user(
 pub async fn fetch_user_invite(
     cache: Arc<dyn ProjectDrivenCache>,
     cmd: FetchUserInviteCmd,
-) -> Result<Vec<ProjectUserInvite>> {
+) -> Result<Paged<ProjectUserInvite>> {
     assert_credential(&cmd.credential)?;
     assert_permission(
         cache.clone(),
         &cmd.credential,
         &cmd.project_id,
-        Some(ProjectUserRole::Owner),
+        Permission::UserRead,
     )
     .await?;
 
-    cache
-        .find_user_invites(&cmd.project_id, &cmd.page, &cmd.page_size)
-        .await
+    let invites = cache.find_user_invites(&cmd.project_id, &cmd.page).await?;
+
+    Ok(cmd
+        .page
+        .paged(invites, |i| Cursor::new(Some(i.created_at), &i.id)))
 }
 
 pub async fn create_user_invite(
@@ -616,13 +986,19 @@ pub async fn create_user_invite(
     cmd: CreateUserInviteCmd,
 ) -> Result<()> {
     assert_credential(&cmd.credential)?;
-    assert_permission(
+    let role = assert_permission(
         cache.clone(),
         &cmd.credential,
         &cmd.project_id,
-        Some(ProjectUserRole::Owner),
+        Permission::UserInvite,
     )
     .await?;
+    if role.is_some_and(|role| !role.can_grant(&cmd.role)) {
+        return Err(Error::Unauthorized(format!(
+            "user can not invite with the {} role",
+            cmd.role
+        )));
+    }
 
     apply_user_invite(
         cache,
@@ -805,7 +1181,7 @@ pub async fn resend_user_invite(
         cache.clone(),
         &cmd.credential,
         &user_invite.project_id,
-        Some(ProjectUserRole::Owner),
+        Permission::UserInvite,
     )
     .await?;
 
@@ -846,7 +1222,7 @@ pub async fn delete_user_invite(
         cache.clone(),
         &cmd.credential,
         &user_invite.project_id,
-        Some(ProjectUserRole::Owner),
+        Permission::UserInvite,
     )
     .await?;
 
@@ -879,24 +1255,18 @@ fn assert_credential(credential: &Credential) -> Result<UserId> {
 #[derive(Debug, Clone)]
 pub struct FetchCmd {
     pub credential: Credential,
-    pub page: u32,
-    pub page_size: u32,
+    pub page: Page,
 }
 impl FetchCmd {
-    pub fn new(credential: Credential, page: Option<u32>, page_size: Option<u32>) -> Result<Self> {
-        let page = page.unwrap_or(1);
-        let page_size = page_size.unwrap_or(PAGE_SIZE_DEFAULT);
-
-        if page_size >= PAGE_SIZE_MAX {
-            return Err(Error::CommandMalformed(format!(
-                "page_size exceeded the limit of {PAGE_SIZE_MAX}"
-            )));
-        }
-
+    pub fn new(
+        credential: Credential,
+        page: Option<u32>,
+        page_size: Option<u32>,
+        cursor: Option<String>,
+    ) -> Result<Self> {
         Ok(Self {
             credential,
-            page,
-            page_size,
+            page: Page::new(page, page_size, cursor)?,
         })
     }
 }
@@ -932,9 +1302,10 @@ pub struct CreateCmd {
     pub id: String,
     pub name: String,
     pub namespace: String,
+    pub cluster_id: Option<String>,
 }
 impl CreateCmd {
-    pub fn new(credential: Credential, name: String) -> Self {
+    pub fn new(credential: Credential, name: String, cluster_id: Option<String>) -> Self {
         let id = Uuid::new_v4().to_string();
         let namespace = utils::get_random_name();
 
@@ -943,6 +1314,7 @@ impl CreateCmd {
             id,
             name,
             namespace,
+            cluster_id,
         }
     }
 }
@@ -974,6 +1346,17 @@ impl DeleteCmd {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct RestoreCmd {
+    pub credential: Credential,
+    pub id: String,
+}
+impl RestoreCmd {
+    pub fn new(credential: Credential, id: String) -> Self {
+        Self { credential, id }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FetchSecretCmd {
     pub credential: Credential,
@@ -1029,8 +1412,7 @@ impl DeleteSecretCmd {
 #[derive(Debug, Clone)]
 pub struct FetchUserCmd {
     pub credential: Credential,
-    pub page: u32,
-    pub page_size: u32,
+    pub page: Page,
     pub project_id: String,
 }
 impl FetchUserCmd {
@@ -1038,21 +1420,12 @@ impl FetchUserCmd {
         credential: Credential,
         page: Option<u32>,
         page_size: Option<u32>,
+        cursor: Option<String>,
         project_id: String,
     ) -> Result<Self> {
-        let page = page.unwrap_or(1);
-        let page_size = page_size.unwrap_or(PAGE_SIZE_DEFAULT);
-
-        if page_size >= PAGE_SIZE_MAX {
-            return Err(Error::CommandMalformed(format!(
-                "page_size exceeded the limit of {PAGE_SIZE_MAX}"
-            )));
-        }
-
         Ok(Self {
             credential,
-            page,
-            page_size,
+            page: Page::new(page, page_size, cursor)?,
             project_id,
         })
     }
@@ -1073,11 +1446,53 @@ impl FetchMeUserCmd {
 }
 
 #[derive(Debug, Clone)]
-pub struct TransferOwnershipCmd {
+pub struct ProposeOwnershipTransferCmd {
     pub credential: Credential,
+    pub ttl: Duration,
+    pub id: String,
     pub project_id: String,
     pub new_owner: String,
 }
+impl ProposeOwnershipTransferCmd {
+    pub fn new(
+        credential: Credential,
+        ttl: Duration,
+        project_id: String,
+        new_owner: String,
+    ) -> Self {
+        let id = Uuid::new_v4().to_string();
+
+        Self {
+            credential,
+            ttl,
+            id,
+            project_id,
+            new_owner,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AcceptOwnershipTransferCmd {
+    pub credential: Credential,
+    pub code: String,
+}
+impl AcceptOwnershipTransferCmd {
+    pub fn new(credential: Credential, code: String) -> Self {
+        Self { credential, code }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CancelOwnershipTransferCmd {
+    pub credential: Credential,
+    pub id: String,
+}
+impl CancelOwnershipTransferCmd {
+    pub fn new(credential: Credential, id: String) -> Self {
+        Self { credential, id }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct DeleteUserCmd {
@@ -1098,8 +1513,7 @@ impl DeleteUserCmd {
 #[derive(Debug, Clone)]
 pub struct FetchUserInviteCmd {
     pub credential: Credential,
-    pub page: u32,
-    pub page_size: u32,
+    pub page: Page,
     pub project_id: String,
 }
 impl FetchUserInviteCmd {
@@ -1107,21 +1521,12 @@ impl FetchUserInviteCmd {
         credential: Credential,
         page: Option<u32>,
         page_size: Option<u32>,
+        cursor: Option<String>,
         project_id: String,
     ) -> Result<Self> {
-        let page = page.unwrap_or(1);
-        let page_size = page_size.unwrap_or(PAGE_SIZE_DEFAULT);
-
-        if page_size >= PAGE_SIZE_MAX {
-            return Err(Error::CommandMalformed(format!(
-                "page_size exceeded the limit of {PAGE_SIZE_MAX}"
-            )));
-        }
-
         Ok(Self {
             credential,
-            page,
-            page_size,
+            page: Page::new(page, page_size, cursor)?,
             project_id,
         })
     }
@@ -1197,20 +1602,21 @@ mod tests {
     use super::*;
     use crate::domain::{
         auth::{Auth0Profile, MockAuth0Driven},
-        event::MockEventDrivenBridge,
+        event::{EventOffset, MockEventDrivenBridge},
         project::{
-            cache::MockProjectDrivenCache, MockProjectEmailDriven, MockStripeDriven, ProjectUser,
-            ProjectUserInvite,
+            cache::MockProjectDrivenCache, MockProjectEmailDriven, MockStripeDriven,
+            ProjectOwnershipTransfer, ProjectUser, ProjectUserInvite,
         },
+        resource::{cache::MockResourceDrivenCache, Resource},
         tests::{INVALID_HRP_KEY, INVALID_KEY, KEY, SECRET},
+        RESTORE_GRACE_DAYS,
     };
 
     impl Default for FetchCmd {
         fn default() -> Self {
             Self {
                 credential: Credential::Auth0("user id".into()),
-                page: 1,
-                page_size: 12,
+                page: Page::default(),
             }
         }
     }
@@ -1237,6 +1643,7 @@ mod tests {
                 id: Uuid::new_v4().to_string(),
                 name: "New Project".into(),
                 namespace: "sonic-vegas".into(),
+                cluster_id: None,
             }
         }
     }
@@ -1249,6 +1656,14 @@ mod tests {
             }
         }
     }
+    impl Default for RestoreCmd {
+        fn default() -> Self {
+            Self {
+                credential: Credential::Auth0("user id".into()),
+                id: Uuid::new_v4().to_string(),
+            }
+        }
+    }
     impl Default for FetchSecretCmd {
         fn default() -> Self {
             Self {
@@ -1288,8 +1703,7 @@ mod tests {
         fn default() -> Self {
             Self {
                 credential: Credential::Auth0("user id".into()),
-                page: 1,
-                page_size: 12,
+                page: Page::default(),
                 project_id: Uuid::new_v4().to_string(),
             }
         }
@@ -1334,8 +1748,7 @@ mod tests {
         fn default() -> Self {
             Self {
                 credential: Credential::Auth0("user id".into()),
-                page: 1,
-                page_size: 12,
+                page: Page::default(),
                 project_id: Uuid::new_v4().to_string(),
             }
         }
@@ -1357,15 +1770,33 @@ mod tests {
             }
         }
     }
-    impl Default for TransferOwnershipCmd {
+    impl Default for ProposeOwnershipTransferCmd {
         fn default() -> Self {
             Self {
                 credential: Credential::Auth0("user id".into()),
+                ttl: Duration::from_secs(15 * 60),
+                id: Uuid::new_v4().to_string(),
                 project_id: Uuid::new_v4().to_string(),
                 new_owner: "member user id".into(),
             }
         }
     }
+    impl Default for AcceptOwnershipTransferCmd {
+        fn default() -> Self {
+            Self {
+                credential: Credential::Auth0("member user id".into()),
+                code: "123".into(),
+            }
+        }
+    }
+    impl Default for CancelOwnershipTransferCmd {
+        fn default() -> Self {
+            Self {
+                credential: Credential::Auth0("user id".into()),
+                id: Uuid::new_v4().to_string(),
+            }
+        }
+    }
     impl Default for DeleteCmd {
         fn default() -> Self {
             Self {
@@ -1380,7 +1811,7 @@ mod tests {
         let mut cache = MockProjectDrivenCache::new();
         cache
             .expect_find()
-            .return_once(|_, _, _| Ok(vec![Project::default()]));
+            .return_once(|_, _| Ok(vec![Project::default()]));
 
         let cmd = FetchCmd::default();
 
@@ -1388,6 +1819,33 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn it_should_return_cursor_of_next_user_projects_page() {
+        let project = Project::default();
+        let last_project = project.clone();
+
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find()
+            .withf(|_, page| page.cursor.is_some())
+            .return_once(|_, _| Ok(vec![project]));
+
+        let cursor = Cursor::new(Some(Utc::now()), "project id").encode();
+        let cmd = FetchCmd::new(
+            Credential::Auth0("user id".into()),
+            None,
+            Some(1),
+            Some(cursor),
+        )
+        .unwrap();
+
+        let result = fetch(Arc::new(cache), cmd).await.unwrap();
+        assert!(
+            result.next_cursor
+                == Some(Cursor::new(Some(last_project.created_at), &last_project.id))
+        );
+    }
+
     #[tokio::test]
     async fn it_should_fetch_project_by_id() {
         let mut cache = MockProjectDrivenCache::new();
@@ -1468,7 +1926,7 @@ mod tests {
             .return_once(|_, _| Ok("stripe id".into()));
 
         let mut event = MockEventDrivenBridge::new();
-        event.expect_dispatch().return_once(|_| Ok(()));
+        event.expect_dispatch().return_once(|_| Ok(None));
 
         let cmd = CreateCmd::default();
 
@@ -1531,42 +1989,288 @@ mod tests {
 
         assert!(result.is_err());
     }
-
+
+    #[tokio::test]
+    async fn it_should_update_project() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Project::default())));
+        cache.expect_find_secrets().return_once(|_| Ok(Vec::new()));
+
+        let mut event = MockEventDrivenBridge::new();
+        event.expect_dispatch().return_once(|_| Ok(None));
+
+        let cmd = UpdateCmd::default();
+
+        let result = update(Arc::new(cache), Arc::new(event), cmd).await;
+        assert!(result.is_ok());
+    }
+    #[tokio::test]
+    async fn it_should_wait_the_cache_to_apply_the_project_update() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        cache
+            .expect_wait_applied()
+            .withf(|offset, _| offset.offset == 7)
+            .times(1)
+            .return_once(|_, _| Ok(true));
+        cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Project::default())));
+
+        let mut event = MockEventDrivenBridge::new();
+        event.expect_dispatch().return_once(|_| {
+            Ok(Some(EventOffset {
+                topic: "events".into(),
+                partition: 0,
+                offset: 7,
+            }))
+        });
+
+        let cmd = UpdateCmd::default();
+
+        let result = update(Arc::new(cache), Arc::new(event), cmd).await;
+        assert!(result.is_ok());
+    }
+    #[tokio::test]
+    async fn it_should_read_the_project_update_back_when_the_cache_times_out() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        cache
+            .expect_wait_applied()
+            .times(1)
+            .return_once(|_, _| Ok(false));
+        cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Project::default())));
+
+        let mut event = MockEventDrivenBridge::new();
+        event.expect_dispatch().return_once(|_| {
+            Ok(Some(EventOffset {
+                topic: "events".into(),
+                partition: 0,
+                offset: 7,
+            }))
+        });
+
+        let cmd = UpdateCmd::default();
+
+        let result = update(Arc::new(cache), Arc::new(event), cmd).await;
+        assert!(result.is_ok());
+    }
+    #[tokio::test]
+    async fn it_should_not_wait_the_cache_when_the_offset_is_unknown() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        cache.expect_wait_applied().never();
+        cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Project::default())));
+
+        // The outbox and the file bus don't know the offset when dispatching.
+        let mut event = MockEventDrivenBridge::new();
+        event.expect_dispatch().return_once(|_| Ok(None));
+
+        let cmd = UpdateCmd::default();
+
+        let result = update(Arc::new(cache), Arc::new(event), cmd).await;
+        assert!(result.is_ok());
+    }
+    #[tokio::test]
+    async fn it_should_fail_update_project_when_invalid_permission_member() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache.expect_find_user_permission().return_once(|_, _| {
+            Ok(Some(ProjectUser {
+                role: ProjectUserRole::Member,
+                ..Default::default()
+            }))
+        });
+
+        let event = MockEventDrivenBridge::new();
+
+        let cmd = UpdateCmd::default();
+
+        let result = update(Arc::new(cache), Arc::new(event), cmd).await;
+        assert!(result.is_err());
+        assert!(matches!(result, Err(Error::Unauthorized(_))));
+    }
+
+    #[tokio::test]
+    async fn it_should_restore_project_with_its_resources() {
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        project_cache
+            .expect_find_deleted_by_id()
+            .return_once(|_| Ok(Some(Project::default())));
+
+        let mut resource_cache = MockResourceDrivenCache::new();
+        resource_cache
+            .expect_find_deleted_with_project()
+            .return_once(|_, _| Ok(vec![Resource::default(), Resource::default()]));
+
+        let mut event = MockEventDrivenBridge::new();
+        event.expect_dispatch().times(3).returning(|_| Ok(None));
+
+        let cmd = RestoreCmd::default();
+
+        let result = restore(
+            Arc::new(project_cache),
+            Arc::new(resource_cache),
+            Arc::new(event),
+            cmd,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+    #[tokio::test]
+    async fn it_should_suspend_project() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Project::default())));
+
+        let mut event = MockEventDrivenBridge::new();
+        event.expect_dispatch().return_once(|_| Ok(None));
+
+        let result = apply_suspend(
+            Arc::new(cache),
+            Arc::new(event),
+            "project id",
+            "payment method failed",
+            "backoffice",
+            false,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+    #[tokio::test]
+    async fn it_should_fail_suspend_project_when_already_suspended() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache.expect_find_by_id().return_once(|_| {
+            Ok(Some(Project {
+                status: ProjectStatus::Suspended,
+                ..Default::default()
+            }))
+        });
+
+        let event = MockEventDrivenBridge::new();
+
+        let result = apply_suspend(
+            Arc::new(cache),
+            Arc::new(event),
+            "project id",
+            "payment method failed",
+            "backoffice",
+            false,
+        )
+        .await;
+        assert!(matches!(result, Err(Error::CommandMalformed(_))));
+    }
+    #[tokio::test]
+    async fn it_should_resume_suspended_project() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache.expect_find_by_id().return_once(|_| {
+            Ok(Some(Project {
+                status: ProjectStatus::Suspended,
+                ..Default::default()
+            }))
+        });
+
+        let mut event = MockEventDrivenBridge::new();
+        event.expect_dispatch().return_once(|_| Ok(None));
+
+        let result = apply_resume(
+            Arc::new(cache),
+            Arc::new(event),
+            "project id",
+            "backoffice",
+            false,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
     #[tokio::test]
-    async fn it_should_update_project() {
+    async fn it_should_fail_resume_project_when_not_suspended() {
         let mut cache = MockProjectDrivenCache::new();
-        cache
-            .expect_find_user_permission()
-            .return_once(|_, _| Ok(Some(ProjectUser::default())));
         cache
             .expect_find_by_id()
             .return_once(|_| Ok(Some(Project::default())));
-        cache.expect_find_secrets().return_once(|_| Ok(Vec::new()));
-
-        let mut event = MockEventDrivenBridge::new();
-        event.expect_dispatch().return_once(|_| Ok(()));
 
-        let cmd = UpdateCmd::default();
+        let event = MockEventDrivenBridge::new();
 
-        let result = update(Arc::new(cache), Arc::new(event), cmd).await;
-        assert!(result.is_ok());
+        let result = apply_resume(
+            Arc::new(cache),
+            Arc::new(event),
+            "project id",
+            "backoffice",
+            false,
+        )
+        .await;
+        assert!(matches!(result, Err(Error::CommandMalformed(_))));
     }
     #[tokio::test]
-    async fn it_should_fail_update_project_when_invalid_permission_member() {
-        let mut cache = MockProjectDrivenCache::new();
-        cache.expect_find_user_permission().return_once(|_, _| {
-            Ok(Some(ProjectUser {
-                role: ProjectUserRole::Member,
+    async fn it_should_fail_restore_project_when_grace_window_is_over() {
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        project_cache.expect_find_deleted_by_id().return_once(|_| {
+            Ok(Some(Project {
+                updated_at: Utc::now() - chrono::Duration::days(RESTORE_GRACE_DAYS + 1),
                 ..Default::default()
             }))
         });
 
+        let resource_cache = MockResourceDrivenCache::new();
         let event = MockEventDrivenBridge::new();
 
-        let cmd = UpdateCmd::default();
+        let cmd = RestoreCmd::default();
 
-        let result = update(Arc::new(cache), Arc::new(event), cmd).await;
-        assert!(result.is_err());
+        let result = restore(
+            Arc::new(project_cache),
+            Arc::new(resource_cache),
+            Arc::new(event),
+            cmd,
+        )
+        .await;
+        assert!(matches!(result, Err(Error::CommandMalformed(_))));
+    }
+    #[tokio::test]
+    async fn it_should_fail_restore_project_when_invalid_permission_member() {
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| {
+                Ok(Some(ProjectUser {
+                    role: ProjectUserRole::Member,
+                    ..Default::default()
+                }))
+            });
+
+        let resource_cache = MockResourceDrivenCache::new();
+        let event = MockEventDrivenBridge::new();
+
+        let cmd = RestoreCmd::default();
+
+        let result = restore(
+            Arc::new(project_cache),
+            Arc::new(resource_cache),
+            Arc::new(event),
+            cmd,
+        )
+        .await;
         assert!(matches!(result, Err(Error::Unauthorized(_))));
     }
 
@@ -1602,6 +2306,24 @@ mod tests {
         assert!(matches!(result, Err(Error::Unauthorized(_))));
     }
     #[tokio::test]
+    async fn it_should_fetch_project_secrets_as_developer() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache.expect_find_user_permission().return_once(|_, _| {
+            Ok(Some(ProjectUser {
+                role: ProjectUserRole::Developer,
+                ..Default::default()
+            }))
+        });
+        cache
+            .expect_find_secrets()
+            .return_once(|_| Ok(vec![ProjectSecret::default()]));
+
+        let cmd = FetchSecretCmd::default();
+
+        let result = fetch_secret(Arc::new(cache), cmd).await;
+        assert!(result.is_ok());
+    }
+    #[tokio::test]
     async fn it_should_create_project_secret() {
         let mut cache = MockProjectDrivenCache::new();
         cache
@@ -1613,7 +2335,7 @@ mod tests {
         cache.expect_find_secrets().return_once(|_| Ok(Vec::new()));
 
         let mut event = MockEventDrivenBridge::new();
-        event.expect_dispatch().return_once(|_| Ok(()));
+        event.expect_dispatch().return_once(|_| Ok(None));
 
         let cmd = CreateSecretCmd::default();
 
@@ -1706,6 +2428,9 @@ mod tests {
         cache
             .expect_find_secrets()
             .return_once(|_| Ok(vec![ProjectSecret::default()]));
+        cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Project::default())));
 
         let cmd = VerifySecretCmd::default();
 
@@ -1713,6 +2438,24 @@ mod tests {
         assert!(result.is_ok());
     }
     #[tokio::test]
+    async fn it_should_fail_verify_secret_when_project_is_suspended() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_secrets()
+            .return_once(|_| Ok(vec![ProjectSecret::default()]));
+        cache.expect_find_by_id().return_once(|_| {
+            Ok(Some(Project {
+                status: ProjectStatus::Suspended,
+                ..Default::default()
+            }))
+        });
+
+        let cmd = VerifySecretCmd::default();
+
+        let result = verify_secret(Arc::new(cache), cmd).await;
+        assert!(matches!(result, Err(Error::Unauthorized(_))));
+    }
+    #[tokio::test]
     async fn it_should_fail_verify_secret_when_invalid_key() {
         let mut cache = MockProjectDrivenCache::new();
         cache
@@ -1774,7 +2517,7 @@ mod tests {
             .return_once(|_| Ok(Some(ProjectSecret::default())));
 
         let mut event = MockEventDrivenBridge::new();
-        event.expect_dispatch().return_once(|_| Ok(()));
+        event.expect_dispatch().return_once(|_| Ok(None));
 
         let cmd = DeleteSecretCmd::default();
 
@@ -1811,7 +2554,7 @@ mod tests {
             .return_once(|_, _| Ok(Some(ProjectUser::default())));
         cache
             .expect_find_user_invites()
-            .return_once(|_, _, _| Ok(vec![ProjectUserInvite::default()]));
+            .return_once(|_, _| Ok(vec![ProjectUserInvite::default()]));
 
         let cmd = FetchUserInviteCmd::default();
 
@@ -1849,7 +2592,7 @@ mod tests {
         email.expect_send_invite().return_once(|_, _, _, _| Ok(()));
 
         let mut event = MockEventDrivenBridge::new();
-        event.expect_dispatch().return_once(|_| Ok(()));
+        event.expect_dispatch().return_once(|_| Ok(None));
 
         let cmd = CreateUserInviteCmd::default();
 
@@ -1927,6 +2670,26 @@ mod tests {
         assert!(result.is_err());
         assert!(matches!(result, Err(Error::Unauthorized(_))));
     }
+    #[tokio::test]
+    async fn it_should_fail_create_project_user_invite_when_admin_invites_an_owner() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache.expect_find_user_permission().return_once(|_, _| {
+            Ok(Some(ProjectUser {
+                role: ProjectUserRole::Admin,
+                ..Default::default()
+            }))
+        });
+
+        let email = MockProjectEmailDriven::new();
+        let event = MockEventDrivenBridge::new();
+
+        let cmd = CreateUserInviteCmd::default();
+
+        let result =
+            create_user_invite(Arc::new(cache), Arc::new(email), Arc::new(event), cmd).await;
+
+        assert!(matches!(result, Err(Error::Unauthorized(_))));
+    }
 
     #[tokio::test]
     async fn it_should_create_project_user_invite_from_backoffice() {
@@ -1941,7 +2704,7 @@ mod tests {
         email.expect_send_invite().return_once(|_, _, _, _| Ok(()));
 
         let mut event = MockEventDrivenBridge::new();
-        event.expect_dispatch().return_once(|_| Ok(()));
+        event.expect_dispatch().return_once(|_| Ok(None));
 
         let result = create_user_invite_from_backoffice(
             Arc::new(cache),
@@ -2029,7 +2792,7 @@ mod tests {
         });
 
         let mut event = MockEventDrivenBridge::new();
-        event.expect_dispatch().return_once(|_| Ok(()));
+        event.expect_dispatch().return_once(|_| Ok(None));
 
         let cmd = AcceptUserInviteCmd::default();
 
@@ -2255,7 +3018,7 @@ mod tests {
             .return_once(|_, _| Ok(Some(ProjectUser::default())));
 
         let mut event = MockEventDrivenBridge::new();
-        event.expect_dispatch().return_once(|_| Ok(()));
+        event.expect_dispatch().return_once(|_| Ok(None));
 
         let cmd = DeleteUserInviteCmd::default();
 
@@ -2330,7 +3093,7 @@ mod tests {
             .return_once(|_, _| Ok(Some(ProjectUser::default())));
         cache
             .expect_find_users()
-            .return_once(|_, _, _| Ok(vec![ProjectUser::default()]));
+            .return_once(|_, _| Ok(vec![ProjectUser::default()]));
 
         let mut auth0 = MockAuth0Driven::new();
         auth0
@@ -2347,29 +3110,39 @@ mod tests {
         let mut cache = MockProjectDrivenCache::new();
         cache
             .expect_find_users()
-            .return_once(|_, _, _| Ok(vec![ProjectUser::default()]));
+            .return_once(|_, _| Ok(vec![ProjectUser::default()]));
 
         let mut auth0 = MockAuth0Driven::new();
         auth0
             .expect_find_info()
             .return_once(|_| Ok(vec![Auth0Profile::default()]));
 
-        let result = list_users(Arc::new(cache), Arc::new(auth0), "project id", &1, &12).await;
+        let result = list_users(
+            Arc::new(cache),
+            Arc::new(auth0),
+            "project id",
+            &Page::default(),
+        )
+        .await;
         assert!(result.is_ok());
-        assert_eq!(result.unwrap().len(), 1);
+        assert_eq!(result.unwrap().records.len(), 1);
     }
     #[tokio::test]
     async fn it_should_not_query_auth0_when_the_page_has_no_users() {
         let mut cache = MockProjectDrivenCache::new();
-        cache.expect_find_users().return_once(|_, _, _| Ok(vec![]));
+        cache.expect_find_users().return_once(|_, _| Ok(vec![]));
 
         // No expectation: an empty page must not reach Auth0 at all. An empty `q` is not a search
         // for nothing — it matches every user in the tenant.
         let auth0 = MockAuth0Driven::new();
 
-        let result = list_users(Arc::new(cache), Arc::new(auth0), "project id", &9, &12).await;
+        let page = Page {
+            page: 9,
+            ..Default::default()
+        };
+        let result = list_users(Arc::new(cache), Arc::new(auth0), "project id", &page).await;
         assert!(result.is_ok());
-        assert!(result.unwrap().is_empty());
+        assert!(result.unwrap().records.is_empty());
     }
     #[tokio::test]
     async fn it_should_fail_fetch_project_users_when_invalid_permission_member() {
@@ -2403,7 +3176,7 @@ mod tests {
             .return_once(|_| Ok(Some(Project::default())));
 
         let mut event = MockEventDrivenBridge::new();
-        event.expect_dispatch().return_once(|_| Ok(()));
+        event.expect_dispatch().return_once(|_| Ok(None));
 
         let cmd = DeleteUserCmd::default();
 
@@ -2412,7 +3185,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn it_should_transfer_project_ownership() {
+    async fn it_should_propose_project_ownership_transfer() {
         let mut cache = MockProjectDrivenCache::new();
         cache
             .expect_find_user_permission()
@@ -2427,6 +3200,51 @@ mod tests {
             .expect_find_info()
             .return_once(|_| Ok(vec![Auth0Profile::default()]));
 
+        let mut email = MockProjectEmailDriven::new();
+        email
+            .expect_send_ownership_transfer()
+            .times(1)
+            .return_once(|_, _, _, _| Ok(()));
+
+        let mut event = MockEventDrivenBridge::new();
+        event
+            .expect_dispatch()
+            .withf(|evt| {
+                matches!(evt, Event::ProjectOwnershipTransferProposed(evt) if evt.previous_owner == "user id" && evt.new_owner == "member user id")
+            })
+            .times(1)
+            .return_once(|_| Ok(None));
+
+        let cmd = ProposeOwnershipTransferCmd::default();
+
+        let result = propose_ownership_transfer(
+            Arc::new(cache),
+            Arc::new(email),
+            Arc::new(event),
+            Arc::new(auth0),
+            cmd,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+    #[tokio::test]
+    async fn it_should_accept_project_ownership_transfer() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_ownership_transfer_by_code()
+            .return_once(|_| Ok(Some(ProjectOwnershipTransfer::default())));
+        cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Project::default())));
+        cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+
+        let mut auth0 = MockAuth0Driven::new();
+        auth0
+            .expect_find_info()
+            .return_once(|_| Ok(vec![Auth0Profile::default()]));
+
         let mut stripe = MockStripeDriven::new();
         stripe
             .expect_update_customer()
@@ -2434,11 +3252,17 @@ mod tests {
             .returning(|_, _, _| Ok(()));
 
         let mut event = MockEventDrivenBridge::new();
-        event.expect_dispatch().times(1).return_once(|_| Ok(()));
+        event
+            .expect_dispatch()
+            .withf(|evt| {
+                matches!(evt, Event::ProjectOwnershipTransferAccepted(evt) if evt.new_owner == "member user id")
+            })
+            .times(1)
+            .return_once(|_| Ok(None));
 
-        let cmd = TransferOwnershipCmd::default();
+        let cmd = AcceptOwnershipTransferCmd::default();
 
-        let result = transfer_ownership(
+        let result = accept_ownership_transfer(
             Arc::new(cache),
             Arc::new(event),
             Arc::new(auth0),
@@ -2499,7 +3323,7 @@ mod tests {
 
         // The event must still be dispatched — only the billing contact is left alone.
         let mut event = MockEventDrivenBridge::new();
-        event.expect_dispatch().times(1).return_once(|_| Ok(()));
+        event.expect_dispatch().times(1).return_once(|_| Ok(None));
 
         let result = apply_ownership_transfer(
             Arc::new(cache),
@@ -2545,7 +3369,7 @@ mod tests {
         assert!(matches!(result, Err(Error::CommandMalformed(_))));
     }
     #[tokio::test]
-    async fn it_should_fail_transfer_ownership_when_invalid_permission_member() {
+    async fn it_should_fail_propose_ownership_transfer_when_invalid_permission_member() {
         let mut cache = MockProjectDrivenCache::new();
         cache.expect_find_user_permission().return_once(|_, _| {
             Ok(Some(ProjectUser {
@@ -2554,17 +3378,17 @@ mod tests {
             }))
         });
 
-        let auth0 = MockAuth0Driven::new();
-        let stripe = MockStripeDriven::new();
+        let email = MockProjectEmailDriven::new();
         let event = MockEventDrivenBridge::new();
+        let auth0 = MockAuth0Driven::new();
 
-        let cmd = TransferOwnershipCmd::default();
+        let cmd = ProposeOwnershipTransferCmd::default();
 
-        let result = transfer_ownership(
+        let result = propose_ownership_transfer(
             Arc::new(cache),
+            Arc::new(email),
             Arc::new(event),
             Arc::new(auth0),
-            Arc::new(stripe),
             cmd,
         )
         .await;
@@ -2573,7 +3397,7 @@ mod tests {
         assert!(matches!(result, Err(Error::Unauthorized(_))));
     }
     #[tokio::test]
-    async fn it_should_fail_transfer_ownership_when_the_user_is_already_the_owner() {
+    async fn it_should_fail_propose_ownership_transfer_when_the_user_is_already_the_owner() {
         let mut cache = MockProjectDrivenCache::new();
         cache
             .expect_find_user_permission()
@@ -2582,21 +3406,21 @@ mod tests {
             .expect_find_by_id()
             .return_once(|_| Ok(Some(Project::default())));
 
-        let auth0 = MockAuth0Driven::new();
-        let stripe = MockStripeDriven::new();
+        let email = MockProjectEmailDriven::new();
         let event = MockEventDrivenBridge::new();
+        let auth0 = MockAuth0Driven::new();
 
         // Project::default() is owned by "user id".
-        let cmd = TransferOwnershipCmd {
+        let cmd = ProposeOwnershipTransferCmd {
             new_owner: "user id".into(),
             ..Default::default()
         };
 
-        let result = transfer_ownership(
+        let result = propose_ownership_transfer(
             Arc::new(cache),
+            Arc::new(email),
             Arc::new(event),
             Arc::new(auth0),
-            Arc::new(stripe),
             cmd,
         )
         .await;
@@ -2605,7 +3429,7 @@ mod tests {
         assert!(matches!(result, Err(Error::CommandMalformed(_))));
     }
     #[tokio::test]
-    async fn it_should_fail_transfer_ownership_when_new_owner_is_not_a_member() {
+    async fn it_should_fail_propose_ownership_transfer_when_new_owner_is_not_a_member() {
         let mut cache = MockProjectDrivenCache::new();
         // First call authorizes the caller, second looks up the new owner.
         cache
@@ -2620,13 +3444,72 @@ mod tests {
             .expect_find_by_id()
             .return_once(|_| Ok(Some(Project::default())));
 
+        // No send expectation: the new owner must not be mailed a code.
+        let email = MockProjectEmailDriven::new();
+        let event = MockEventDrivenBridge::new();
+        let auth0 = MockAuth0Driven::new();
+
+        let cmd = ProposeOwnershipTransferCmd::default();
+
+        let result = propose_ownership_transfer(
+            Arc::new(cache),
+            Arc::new(email),
+            Arc::new(event),
+            Arc::new(auth0),
+            cmd,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(matches!(result, Err(Error::CommandMalformed(_))));
+    }
+    #[tokio::test]
+    async fn it_should_fail_accept_ownership_transfer_when_user_is_not_the_new_owner() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_ownership_transfer_by_code()
+            .return_once(|_| Ok(Some(ProjectOwnershipTransfer::default())));
+
+        let auth0 = MockAuth0Driven::new();
+        let stripe = MockStripeDriven::new();
+        let event = MockEventDrivenBridge::new();
+
+        let cmd = AcceptOwnershipTransferCmd {
+            credential: Credential::Auth0("other user id".into()),
+            ..Default::default()
+        };
+
+        let result = accept_ownership_transfer(
+            Arc::new(cache),
+            Arc::new(event),
+            Arc::new(auth0),
+            Arc::new(stripe),
+            cmd,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(matches!(result, Err(Error::Unauthorized(_))));
+    }
+    #[tokio::test]
+    async fn it_should_fail_accept_ownership_transfer_when_code_expired() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_ownership_transfer_by_code()
+            .return_once(|_| {
+                Ok(Some(ProjectOwnershipTransfer {
+                    expires_in: Utc::now() - Duration::from_secs(60),
+                    ..Default::default()
+                }))
+            });
+
         let auth0 = MockAuth0Driven::new();
         let stripe = MockStripeDriven::new();
         let event = MockEventDrivenBridge::new();
 
-        let cmd = TransferOwnershipCmd::default();
+        let cmd = AcceptOwnershipTransferCmd::default();
 
-        let result = transfer_ownership(
+        let result = accept_ownership_transfer(
             Arc::new(cache),
             Arc::new(event),
             Arc::new(auth0),
@@ -2639,15 +3522,48 @@ mod tests {
         assert!(matches!(result, Err(Error::CommandMalformed(_))));
     }
     #[tokio::test]
-    async fn it_should_not_dispatch_transfer_ownership_when_stripe_fails() {
+    async fn it_should_fail_accept_ownership_transfer_when_owner_changed() {
         let mut cache = MockProjectDrivenCache::new();
         cache
-            .expect_find_user_permission()
-            .times(2)
-            .returning(|_, _| Ok(Some(ProjectUser::default())));
+            .expect_find_ownership_transfer_by_code()
+            .return_once(|_| Ok(Some(ProjectOwnershipTransfer::default())));
+        cache.expect_find_by_id().return_once(|_| {
+            Ok(Some(Project {
+                owner: "another owner id".into(),
+                ..Default::default()
+            }))
+        });
+
+        let auth0 = MockAuth0Driven::new();
+        let stripe = MockStripeDriven::new();
+        let event = MockEventDrivenBridge::new();
+
+        let cmd = AcceptOwnershipTransferCmd::default();
+
+        let result = accept_ownership_transfer(
+            Arc::new(cache),
+            Arc::new(event),
+            Arc::new(auth0),
+            Arc::new(stripe),
+            cmd,
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(matches!(result, Err(Error::CommandMalformed(_))));
+    }
+    #[tokio::test]
+    async fn it_should_not_dispatch_accept_ownership_transfer_when_stripe_fails() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_ownership_transfer_by_code()
+            .return_once(|_| Ok(Some(ProjectOwnershipTransfer::default())));
         cache
             .expect_find_by_id()
             .return_once(|_| Ok(Some(Project::default())));
+        cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
 
         let mut auth0 = MockAuth0Driven::new();
         auth0
@@ -2662,9 +3578,9 @@ mod tests {
         // No dispatch expectation: the mock panics if the event is dispatched anyway.
         let event = MockEventDrivenBridge::new();
 
-        let cmd = TransferOwnershipCmd::default();
+        let cmd = AcceptOwnershipTransferCmd::default();
 
-        let result = transfer_ownership(
+        let result = accept_ownership_transfer(
             Arc::new(cache),
             Arc::new(event),
             Arc::new(auth0),
@@ -2675,6 +3591,49 @@ mod tests {
 
         assert!(result.is_err());
     }
+    #[tokio::test]
+    async fn it_should_cancel_ownership_transfer_as_new_owner() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_ownership_transfer_by_id()
+            .return_once(|_| Ok(Some(ProjectOwnershipTransfer::default())));
+
+        let mut event = MockEventDrivenBridge::new();
+        event.expect_dispatch().times(1).return_once(|_| Ok(None));
+
+        let cmd = CancelOwnershipTransferCmd {
+            credential: Credential::Auth0("member user id".into()),
+            ..Default::default()
+        };
+
+        let result = cancel_ownership_transfer(Arc::new(cache), Arc::new(event), cmd).await;
+        assert!(result.is_ok());
+    }
+    #[tokio::test]
+    async fn it_should_fail_cancel_ownership_transfer_when_invalid_permission_member() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_ownership_transfer_by_id()
+            .return_once(|_| Ok(Some(ProjectOwnershipTransfer::default())));
+        cache.expect_find_user_permission().return_once(|_, _| {
+            Ok(Some(ProjectUser {
+                role: ProjectUserRole::Member,
+                ..Default::default()
+            }))
+        });
+
+        let event = MockEventDrivenBridge::new();
+
+        let cmd = CancelOwnershipTransferCmd {
+            credential: Credential::Auth0("other user id".into()),
+            ..Default::default()
+        };
+
+        let result = cancel_ownership_transfer(Arc::new(cache), Arc::new(event), cmd).await;
+
+        assert!(result.is_err());
+        assert!(matches!(result, Err(Error::Unauthorized(_))));
+    }
 
     #[tokio::test]
     async fn it_should_fail_delete_project_user_when_invalid_permission_member() {
@@ -2706,7 +3665,7 @@ mod tests {
             .return_once(|_| Ok(Some(Project::default())));
 
         let mut event = MockEventDrivenBridge::new();
-        event.expect_dispatch().return_once(|_| Ok(()));
+        event.expect_dispatch().return_once(|_| Ok(None));
 
         let cmd = DeleteUserCmd {
             id: "user id".into(),
@@ -2732,7 +3691,7 @@ mod tests {
         });
 
         let mut event = MockEventDrivenBridge::new();
-        event.expect_dispatch().return_once(|_| Ok(()));
+        event.expect_dispatch().return_once(|_| Ok(None));
 
         let cmd = DeleteCmd {
             credential: Credential::Auth0("user id".into()),
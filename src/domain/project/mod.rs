@@ -3,23 +3,36 @@ use std::{fmt::Display, str::FromStr};
 use chrono::{DateTime, Utc};
 
 use super::{
+    auth::Permission,
     error::Error,
     event::{
-        ProjectCreated, ProjectOwnerChanged, ProjectSecretCreated, ProjectUpdated,
-        ProjectUserInviteAccepted, ProjectUserInviteCreated,
+        ProjectCreated, ProjectOrganizationChanged, ProjectOwnerChanged,
+        ProjectOwnershipTransferAccepted, ProjectOwnershipTransferProposed, ProjectSecretCreated,
+        ProjectUpdated, ProjectUserInviteAccepted, ProjectUserInviteCreated,
     },
     Result,
 };
 
+pub mod billing;
 pub mod cache;
 pub mod cluster;
 pub mod command;
+pub mod quota;
 
 #[cfg_attr(test, mockall::automock)]
 #[async_trait::async_trait]
 pub trait StripeDriven: Send + Sync {
     async fn create_customer(&self, name: &str, email: &str) -> Result<String>;
     async fn update_customer(&self, customer_id: &str, name: &str, email: &str) -> Result<()>;
+    /// Adds a pending item, in cents, to the next invoice of the customer and returns its id.
+    /// Retrying with the same `idempotency_key` returns the item created the first time.
+    async fn create_invoice_item(
+        &self,
+        customer_id: &str,
+        amount: i64,
+        description: &str,
+        idempotency_key: &str,
+    ) -> Result<String>;
 }
 
 #[cfg_attr(test, mockall::automock)]
@@ -32,6 +45,13 @@ pub trait ProjectEmailDriven: Send + Sync {
         code: &str,
         expires_in: &DateTime<Utc>,
     ) -> Result<()>;
+    async fn send_ownership_transfer(
+        &self,
+        project_name: &str,
+        email: &str,
+        code: &str,
+        expires_in: &DateTime<Utc>,
+    ) -> Result<()>;
 }
 
 #[derive(Debug, Clone)]
@@ -44,6 +64,9 @@ pub struct Project {
     pub billing_provider: String,
     pub billing_provider_id: String,
     pub billing_subscription_id: Option<String>,
+    pub cluster_id: Option<String>,
+    /// Organization whose members are also members of the project.
+    pub organization_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -60,6 +83,8 @@ impl TryFrom<ProjectCreated> for Project {
             billing_provider: value.billing_provider,
             billing_provider_id: value.billing_provider_id,
             billing_subscription_id: value.billing_subscription_id,
+            cluster_id: value.cluster_id,
+            organization_id: None,
             created_at: value.created_at,
             updated_at: value.updated_at,
         })
@@ -71,6 +96,8 @@ pub struct ProjectUpdate {
     pub id: String,
     pub name: Option<String>,
     pub status: Option<ProjectStatus>,
+    /// `Some(None)` clears the subscription, `None` leaves it as it is.
+    pub billing_subscription_id: Option<Option<String>>,
     pub updated_at: DateTime<Utc>,
 }
 impl TryFrom<ProjectUpdated> for ProjectUpdate {
@@ -84,6 +111,10 @@ impl TryFrom<ProjectUpdated> for ProjectUpdate {
                 Some(status) => Some(status.parse()?),
                 None => None,
             },
+            billing_subscription_id: match value.billing_subscription_deleted {
+                true => Some(None),
+                false => value.billing_subscription_id.map(Some),
+            },
             updated_at: value.updated_at,
         })
     }
@@ -106,12 +137,41 @@ impl From<ProjectOwnerChanged> for ProjectOwnerChange {
         }
     }
 }
+impl From<ProjectOwnershipTransferAccepted> for ProjectOwnerChange {
+    fn from(value: ProjectOwnershipTransferAccepted) -> Self {
+        Self {
+            project_id: value.project_id,
+            previous_owner: value.previous_owner,
+            new_owner: value.new_owner,
+            changed_at: value.accepted_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProjectOrganizationChange {
+    pub project_id: String,
+    pub organization_id: Option<String>,
+    pub billing_provider_id: String,
+    pub changed_at: DateTime<Utc>,
+}
+impl From<ProjectOrganizationChanged> for ProjectOrganizationChange {
+    fn from(value: ProjectOrganizationChanged) -> Self {
+        Self {
+            project_id: value.project_id,
+            organization_id: value.organization_id,
+            billing_provider_id: value.billing_provider_id,
+            changed_at: value.changed_at,
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum ProjectStatus {
     Active,
     Deleted,
     PaymentMethodFailed,
+    Suspended,
 }
 impl FromStr for ProjectStatus {
     type Err = Error;
@@ -122,6 +182,7 @@ impl FromStr for ProjectStatus {
             "dcu-consumed" => Ok(ProjectStatus::Active),
             "pm-failed" => Ok(ProjectStatus::PaymentMethodFailed),
             "deleted" => Ok(ProjectStatus::Deleted),
+            "suspended" => Ok(ProjectStatus::Suspended),
             _ => Err(Error::Unexpected(format!(
                 "project status not supported: {s}"
             ))),
@@ -134,6 +195,7 @@ impl Display for ProjectStatus {
             ProjectStatus::Active => write!(f, "active"),
             ProjectStatus::Deleted => write!(f, "deleted"),
             ProjectStatus::PaymentMethodFailed => write!(f, "pm-failed"),
+            ProjectStatus::Suspended => write!(f, "suspended"),
         }
     }
 }
@@ -216,6 +278,65 @@ impl Display for ProjectUserInviteStatus {
     }
 }
 
+/// Ownership transfer proposed by the owner, it only takes effect once the new owner accepts it
+/// with the code they were sent.
+#[derive(Debug, Clone)]
+pub struct ProjectOwnershipTransfer {
+    pub id: String,
+    pub project_id: String,
+    pub previous_owner: String,
+    pub new_owner: String,
+    pub code: String,
+    pub status: ProjectOwnershipTransferStatus,
+    pub expires_in: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+impl From<ProjectOwnershipTransferProposed> for ProjectOwnershipTransfer {
+    fn from(value: ProjectOwnershipTransferProposed) -> Self {
+        Self {
+            id: value.id,
+            project_id: value.project_id,
+            previous_owner: value.previous_owner,
+            new_owner: value.new_owner,
+            code: value.code,
+            status: ProjectOwnershipTransferStatus::Proposed,
+            expires_in: value.expires_in,
+            created_at: value.created_at,
+            updated_at: value.created_at,
+        }
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProjectOwnershipTransferStatus {
+    Proposed,
+    Accepted,
+    Cancelled,
+}
+impl FromStr for ProjectOwnershipTransferStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "proposed" => Ok(ProjectOwnershipTransferStatus::Proposed),
+            "accepted" => Ok(ProjectOwnershipTransferStatus::Accepted),
+            "cancelled" => Ok(ProjectOwnershipTransferStatus::Cancelled),
+            _ => Err(Error::Unexpected(format!(
+                "project ownership transfer status not supported: {s}"
+            ))),
+        }
+    }
+}
+impl Display for ProjectOwnershipTransferStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectOwnershipTransferStatus::Proposed => write!(f, "proposed"),
+            ProjectOwnershipTransferStatus::Accepted => write!(f, "accepted"),
+            ProjectOwnershipTransferStatus::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ProjectUser {
     pub user_id: String,
@@ -246,10 +367,89 @@ pub struct ProjectUserAggregated {
     pub created_at: DateTime<Utc>,
 }
 
+/// Built-in roles of a project user. `Member` is the role given before the others existed and
+/// keeps what it could do then.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ProjectUserRole {
     Owner,
+    Admin,
+    Developer,
     Member,
+    Viewer,
+    Billing,
+}
+impl ProjectUserRole {
+    pub fn permissions(&self) -> &'static [Permission] {
+        match self {
+            ProjectUserRole::Owner => &[
+                Permission::ProjectRead,
+                Permission::ProjectUpdate,
+                Permission::ProjectDelete,
+                Permission::ProjectTransfer,
+                Permission::ResourceRead,
+                Permission::ResourceCreate,
+                Permission::ResourceUpdate,
+                Permission::ResourceDelete,
+                Permission::ResourceRestore,
+                Permission::SecretRead,
+                Permission::SecretCreate,
+                Permission::SecretDelete,
+                Permission::UsageRead,
+                Permission::AuditRead,
+                Permission::UserRead,
+                Permission::UserInvite,
+                Permission::UserDelete,
+            ],
+            ProjectUserRole::Admin => &[
+                Permission::ProjectRead,
+                Permission::ProjectUpdate,
+                Permission::ResourceRead,
+                Permission::ResourceCreate,
+                Permission::ResourceUpdate,
+                Permission::ResourceDelete,
+                Permission::SecretRead,
+                Permission::SecretCreate,
+                Permission::SecretDelete,
+                Permission::UsageRead,
+                Permission::UserRead,
+                Permission::UserInvite,
+                Permission::UserDelete,
+            ],
+            ProjectUserRole::Developer => &[
+                Permission::ProjectRead,
+                Permission::ResourceRead,
+                Permission::ResourceCreate,
+                Permission::ResourceUpdate,
+                Permission::ResourceDelete,
+                Permission::SecretRead,
+                Permission::UsageRead,
+                Permission::UserRead,
+            ],
+            ProjectUserRole::Member => &[
+                Permission::ProjectRead,
+                Permission::ResourceRead,
+                Permission::ResourceCreate,
+                Permission::ResourceUpdate,
+                Permission::ResourceDelete,
+            ],
+            ProjectUserRole::Viewer => &[
+                Permission::ProjectRead,
+                Permission::ResourceRead,
+                Permission::UsageRead,
+                Permission::UserRead,
+            ],
+            ProjectUserRole::Billing => &[Permission::ProjectRead, Permission::UsageRead],
+        }
+    }
+
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.permissions().contains(&permission)
+    }
+
+    /// A user can only give a role whose permissions they hold, so an admin can't make owners.
+    pub fn can_grant(&self, role: &ProjectUserRole) -> bool {
+        role.permissions().iter().all(|p| self.has_permission(*p))
+    }
 }
 impl FromStr for ProjectUserRole {
     type Err = Error;
@@ -257,7 +457,11 @@ impl FromStr for ProjectUserRole {
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         match s {
             "owner" => Ok(ProjectUserRole::Owner),
+            "admin" => Ok(ProjectUserRole::Admin),
+            "developer" => Ok(ProjectUserRole::Developer),
             "member" => Ok(ProjectUserRole::Member),
+            "viewer" => Ok(ProjectUserRole::Viewer),
+            "billing" => Ok(ProjectUserRole::Billing),
             _ => Err(Error::Unexpected(format!(
                 "project user role not supported: {s}"
             ))),
@@ -268,7 +472,11 @@ impl Display for ProjectUserRole {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ProjectUserRole::Owner => write!(f, "owner"),
+            ProjectUserRole::Admin => write!(f, "admin"),
+            ProjectUserRole::Developer => write!(f, "developer"),
             ProjectUserRole::Member => write!(f, "member"),
+            ProjectUserRole::Viewer => write!(f, "viewer"),
+            ProjectUserRole::Billing => write!(f, "billing"),
         }
     }
 }
@@ -309,6 +517,8 @@ mod tests {
                 billing_provider: "stripe".into(),
                 billing_provider_id: "stripe id".into(),
                 billing_subscription_id: None,
+                cluster_id: None,
+                organization_id: None,
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
             }
@@ -351,4 +561,19 @@ mod tests {
             }
         }
     }
+    impl Default for ProjectOwnershipTransfer {
+        fn default() -> Self {
+            Self {
+                id: Uuid::new_v4().to_string(),
+                project_id: Uuid::new_v4().to_string(),
+                previous_owner: "user id".into(),
+                new_owner: "member user id".into(),
+                code: "123".into(),
+                status: ProjectOwnershipTransferStatus::Proposed,
+                expires_in: Utc::now() + Duration::from_secs(15 * 60),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            }
+        }
+    }
 }
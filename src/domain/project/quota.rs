@@ -0,0 +1,401 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::domain::{
+    auth::{assert_permission, Credential, Permission},
+    error::Error,
+    event::{EventDrivenBridge, ProjectQuotaUpdated},
+    resource::{cache::ResourceDrivenCache, command::Spec},
+    Result, WORKER_CATEGORY,
+};
+
+use super::{cache::ProjectDrivenCache, Project, ProjectStatus};
+
+/// Highest tier a project can use while its payment method is failing, whatever its quota.
+pub const PAYMENT_FAILED_MAX_TIER: u32 = 0;
+
+/// Limits of what a project can create. The quota without `project_id` is the default of every
+/// project, a limit missing in the quota of the project comes from it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProjectQuota {
+    pub project_id: Option<String>,
+    /// Maximum of active resources of each kind, the kinds missing aren't limited.
+    pub resources: HashMap<String, u32>,
+    /// Highest `throughputTier` of the resources.
+    pub max_tier: Option<u32>,
+    /// Maximum of active workers.
+    pub workers: Option<u32>,
+    pub updated_at: DateTime<Utc>,
+}
+impl ProjectQuota {
+    /// Limits of the project over the default ones.
+    pub fn merge(default: Option<Self>, quota: Option<Self>) -> Self {
+        let default = default.unwrap_or_default();
+        let Some(quota) = quota else {
+            return default;
+        };
+
+        let mut resources = default.resources;
+        resources.extend(quota.resources);
+
+        Self {
+            project_id: quota.project_id,
+            resources,
+            max_tier: quota.max_tier.or(default.max_tier),
+            workers: quota.workers.or(default.workers),
+            updated_at: quota.updated_at.max(default.updated_at),
+        }
+    }
+}
+impl From<ProjectQuotaUpdated> for ProjectQuota {
+    fn from(value: ProjectQuotaUpdated) -> Self {
+        Self {
+            project_id: value.project_id,
+            resources: value.resources,
+            max_tier: value.max_tier,
+            workers: value.workers,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+/// Quota of the project, with the default limits it doesn't override.
+pub async fn find_limits(
+    cache: Arc<dyn ProjectDrivenCache>,
+    project_id: &str,
+) -> Result<ProjectQuota> {
+    let default = cache.find_default_quota().await?;
+    let quota = cache.find_quota(project_id).await?;
+
+    Ok(ProjectQuota::merge(default, quota))
+}
+
+/// Checks a new resource against the quota of its project.
+pub async fn assert_create(
+    project_cache: Arc<dyn ProjectDrivenCache>,
+    resource_cache: Arc<dyn ResourceDrivenCache>,
+    project: &Project,
+    kind: &str,
+    category: &str,
+    spec: &Spec,
+) -> Result<()> {
+    let quota = find_limits(project_cache, &project.id).await?;
+
+    if let Some(max) = quota.resources.get(kind) {
+        let count = resource_cache.count_by_kind(&project.id, kind).await?;
+        if count >= u64::from(*max) {
+            return Err(Error::CommandMalformed(format!(
+                "the project reached its limit of {max} {kind} resources"
+            )));
+        }
+    }
+
+    if let (true, Some(max)) = (category == WORKER_CATEGORY, quota.workers) {
+        let count = resource_cache
+            .count_by_category(&project.id, WORKER_CATEGORY)
+            .await?;
+        if count >= u64::from(max) {
+            return Err(Error::CommandMalformed(format!(
+                "the project reached its limit of {max} workers"
+            )));
+        }
+    }
+
+    assert_tier(project, &quota, spec)
+}
+
+/// Checks a spec patch against the quota of its project, only the tier can change.
+pub async fn assert_update(
+    project_cache: Arc<dyn ProjectDrivenCache>,
+    project: &Project,
+    spec: &Spec,
+) -> Result<()> {
+    let quota = find_limits(project_cache, &project.id).await?;
+
+    assert_tier(project, &quota, spec)
+}
+
+fn assert_tier(project: &Project, quota: &ProjectQuota, spec: &Spec) -> Result<()> {
+    let Some(tier) = spec.get("throughputTier") else {
+        return Ok(());
+    };
+    let tier = match tier {
+        serde_json::Value::String(tier) => tier.parse::<u32>().ok(),
+        serde_json::Value::Number(tier) => tier.as_u64().and_then(|t| u32::try_from(t).ok()),
+        _ => None,
+    };
+    let Some(tier) = tier else {
+        return Err(Error::CommandMalformed("invalid throughputTier".into()));
+    };
+
+    if matches!(project.status, ProjectStatus::PaymentMethodFailed)
+        && tier > PAYMENT_FAILED_MAX_TIER
+    {
+        return Err(Error::CommandMalformed(format!(
+            "the payment method of the project failed, the highest tier allowed is {PAYMENT_FAILED_MAX_TIER}"
+        )));
+    }
+
+    if let Some(max_tier) = quota.max_tier {
+        if tier > max_tier {
+            return Err(Error::CommandMalformed(format!(
+                "the highest tier allowed for the project is {max_tier}"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn fetch(cache: Arc<dyn ProjectDrivenCache>, cmd: FetchCmd) -> Result<ProjectQuota> {
+    assert_permission(
+        cache.clone(),
+        &cmd.credential,
+        &cmd.project_id,
+        Permission::ProjectRead,
+    )
+    .await?;
+
+    find_limits(cache, &cmd.project_id).await
+}
+
+/// Replaces the quota of a project, or the default one without `project_id`. There's no
+/// authorization check, it's only run from the backoffice.
+pub async fn apply_update(
+    cache: Arc<dyn ProjectDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    quota: ProjectQuota,
+    updated_by: &str,
+    dry_run: bool,
+) -> Result<()> {
+    if let Some(project_id) = &quota.project_id {
+        if cache.find_by_id(project_id).await?.is_none() {
+            return Err(Error::CommandMalformed("invalid project id".into()));
+        }
+    }
+
+    let evt = ProjectQuotaUpdated {
+        id: Uuid::new_v4().to_string(),
+        project_id: quota.project_id,
+        resources: quota.resources,
+        max_tier: quota.max_tier,
+        workers: quota.workers,
+        updated_by: updated_by.to_string(),
+        updated_at: Utc::now(),
+    };
+
+    if dry_run {
+        info!("event to dispath: {:?}", evt);
+        return Ok(());
+    }
+
+    event.dispatch(evt.into()).await?;
+    info!("project quota updated");
+
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct FetchCmd {
+    pub credential: Credential,
+    pub project_id: String,
+}
+impl FetchCmd {
+    pub fn new(credential: Credential, project_id: String) -> Self {
+        Self {
+            credential,
+            project_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{
+        event::MockEventDrivenBridge,
+        project::{cache::MockProjectDrivenCache, ProjectUser},
+        resource::cache::MockResourceDrivenCache,
+    };
+
+    use super::*;
+
+    impl Default for FetchCmd {
+        fn default() -> Self {
+            Self {
+                credential: Credential::Auth0("user id".into()),
+                project_id: Uuid::new_v4().to_string(),
+            }
+        }
+    }
+
+    fn spec(tier: &str) -> Spec {
+        let mut spec = Spec::new();
+        spec.insert("throughputTier".into(), tier.into());
+        spec
+    }
+
+    #[test]
+    fn it_should_merge_project_quota_over_default() {
+        let default = ProjectQuota {
+            resources: HashMap::from([("KupoPort".into(), 1), ("OgmiosPort".into(), 1)]),
+            max_tier: Some(1),
+            workers: Some(1),
+            ..Default::default()
+        };
+        let quota = ProjectQuota {
+            project_id: Some("project id".into()),
+            resources: HashMap::from([("KupoPort".into(), 3)]),
+            max_tier: Some(2),
+            ..Default::default()
+        };
+
+        let limits = ProjectQuota::merge(Some(default), Some(quota));
+
+        assert_eq!(limits.project_id.as_deref(), Some("project id"));
+        assert_eq!(limits.resources.get("KupoPort"), Some(&3));
+        assert_eq!(limits.resources.get("OgmiosPort"), Some(&1));
+        assert_eq!(limits.max_tier, Some(2));
+        assert_eq!(limits.workers, Some(1));
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_create_when_kind_limit_is_reached() {
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_default_quota()
+            .return_once(|| Ok(None));
+        project_cache.expect_find_quota().return_once(|_| {
+            Ok(Some(ProjectQuota {
+                resources: HashMap::from([("CardanoNodePort".into(), 2)]),
+                ..Default::default()
+            }))
+        });
+
+        let mut resource_cache = MockResourceDrivenCache::new();
+        resource_cache
+            .expect_count_by_kind()
+            .return_once(|_, _| Ok(2));
+
+        let result = assert_create(
+            Arc::new(project_cache),
+            Arc::new(resource_cache),
+            &Project::default(),
+            "CardanoNodePort",
+            "demeter-port",
+            &spec("0"),
+        )
+        .await;
+
+        assert!(matches!(result, Err(Error::CommandMalformed(_))));
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_create_when_worker_limit_is_reached() {
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache.expect_find_default_quota().return_once(|| {
+            Ok(Some(ProjectQuota {
+                workers: Some(1),
+                ..Default::default()
+            }))
+        });
+        project_cache.expect_find_quota().return_once(|_| Ok(None));
+
+        let mut resource_cache = MockResourceDrivenCache::new();
+        resource_cache
+            .expect_count_by_category()
+            .return_once(|_, _| Ok(1));
+
+        let result = assert_create(
+            Arc::new(project_cache),
+            Arc::new(resource_cache),
+            &Project::default(),
+            "Worker",
+            WORKER_CATEGORY,
+            &Spec::new(),
+        )
+        .await;
+
+        assert!(matches!(result, Err(Error::CommandMalformed(_))));
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_update_when_tier_is_above_the_quota() {
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache.expect_find_default_quota().return_once(|| {
+            Ok(Some(ProjectQuota {
+                max_tier: Some(1),
+                ..Default::default()
+            }))
+        });
+        project_cache.expect_find_quota().return_once(|_| Ok(None));
+
+        let result = assert_update(Arc::new(project_cache), &Project::default(), &spec("2")).await;
+
+        assert!(matches!(result, Err(Error::CommandMalformed(_))));
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_update_tier_when_payment_method_failed() {
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_default_quota()
+            .return_once(|| Ok(None));
+        project_cache.expect_find_quota().return_once(|_| Ok(None));
+
+        let project = Project {
+            status: ProjectStatus::PaymentMethodFailed,
+            ..Default::default()
+        };
+
+        let result = assert_update(Arc::new(project_cache), &project, &spec("1")).await;
+
+        assert!(matches!(result, Err(Error::CommandMalformed(_))));
+    }
+
+    #[tokio::test]
+    async fn it_should_fetch_project_quota() {
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        project_cache
+            .expect_find_default_quota()
+            .return_once(|| Ok(None));
+        project_cache.expect_find_quota().return_once(|_| Ok(None));
+
+        let result = fetch(Arc::new(project_cache), FetchCmd::default()).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_not_dispatch_quota_update_on_dry_run() {
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Project::default())));
+
+        // No dispatch expectation: the mock panics if the event is dispatched.
+        let event = MockEventDrivenBridge::new();
+
+        let quota = ProjectQuota {
+            project_id: Some("project id".into()),
+            max_tier: Some(1),
+            ..Default::default()
+        };
+
+        let result = apply_update(
+            Arc::new(project_cache),
+            Arc::new(event),
+            quota,
+            "backoffice",
+            true,
+        )
+        .await;
+
+        assert!(result.is_ok());
+    }
+}
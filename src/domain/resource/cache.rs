@@ -1,7 +1,8 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use crate::domain::{
-    event::{ResourceCreated, ResourceDeleted, ResourceUpdated},
+    event::{EventOffset, ResourceCreated, ResourceDeleted, ResourceRestored, ResourceUpdated},
+    pagination::Page,
     Result,
 };
 
@@ -12,13 +13,27 @@ use chrono::{DateTime, Utc};
 #[cfg_attr(test, mockall::automock)]
 #[async_trait::async_trait]
 pub trait ResourceDrivenCache: Send + Sync {
-    async fn find(&self, project_id: &str, page: &u32, page_size: &u32, category: &str) -> Result<Vec<Resource>>;
+    async fn find(&self, project_id: &str, page: &Page, category: &str) -> Result<Vec<Resource>>;
     async fn find_by_id(&self, id: &str) -> Result<Option<Resource>>;
+    async fn find_deleted_by_id(&self, id: &str) -> Result<Option<Resource>>;
+    /// Resources deleted together with the project, they share its deletion time.
+    async fn find_deleted_with_project(
+        &self,
+        project_id: &str,
+        deleted_at: &DateTime<Utc>,
+    ) -> Result<Vec<Resource>>;
     async fn find_by_name(&self, project_id: &str, name: &str) -> Result<Option<Resource>>;
+    /// Active resources of the kind in the project.
+    async fn count_by_kind(&self, project_id: &str, kind: &str) -> Result<u64>;
+    /// Active resources of the category in the project.
+    async fn count_by_category(&self, project_id: &str, category: &str) -> Result<u64>;
 
     async fn create(&self, resource: &Resource) -> Result<()>;
     async fn update(&self, resource: &ResourceUpdate) -> Result<()>;
     async fn delete(&self, id: &str, deleted_at: &DateTime<Utc>) -> Result<()>;
+    async fn restore(&self, id: &str, restored_at: &DateTime<Utc>) -> Result<()>;
+    /// Waits until the event at `offset` is applied, `false` when the timeout is reached first.
+    async fn wait_applied(&self, offset: &EventOffset, timeout: Duration) -> Result<bool>;
 }
 
 #[cfg_attr(test, mockall::automock)]
@@ -41,6 +56,10 @@ pub async fn delete(cache: Arc<dyn ResourceDrivenCache>, evt: ResourceDeleted) -
     cache.delete(&evt.id, &evt.deleted_at).await
 }
 
+pub async fn restore(cache: Arc<dyn ResourceDrivenCache>, evt: ResourceRestored) -> Result<()> {
+    cache.restore(&evt.id, &evt.restored_at).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -66,4 +85,15 @@ mod tests {
         let result = delete(Arc::new(cache), evt).await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn it_should_restore_resource_cache() {
+        let mut cache = MockResourceDrivenCache::new();
+        cache.expect_restore().return_once(|_, _| Ok(()));
+
+        let evt = ResourceRestored::default();
+
+        let result = restore(Arc::new(cache), evt).await;
+        assert!(result.is_ok());
+    }
 }
@@ -7,7 +7,7 @@ use kube::{
 use tracing::info;
 
 use crate::domain::{
-    event::{ResourceCreated, ResourceDeleted, ResourceUpdated},
+    event::{ResourceCreated, ResourceDeleted, ResourceRestored, ResourceUpdated},
     utils::cluster_namespace,
     Result,
 };
@@ -93,6 +93,29 @@ pub async fn delete_manifest(
     Ok(())
 }
 
+pub async fn restore_manifest(
+    cluster: Arc<dyn ResourceDrivenCluster>,
+    evt: ResourceRestored,
+) -> Result<()> {
+    let api = build_api_resource(&evt.kind);
+
+    let mut obj = DynamicObject::new(&evt.name, &api);
+    obj.metadata = ObjectMeta {
+        name: Some(evt.name),
+        namespace: Some(cluster_namespace(&evt.project_namespace)),
+        ..Default::default()
+    };
+
+    let spec = serde_json::from_str(&evt.spec)?;
+    obj.data = serde_json::json!({ "spec": serde_json::Value::Object(spec) });
+
+    cluster.create(&obj).await?;
+
+    info!(resource = obj.name_any(), "resource restored");
+
+    Ok(())
+}
+
 fn build_api_resource(kind: &str) -> ApiResource {
     ApiResource {
         kind: kind.into(),
@@ -117,4 +140,15 @@ mod tests {
         let result = apply_manifest(Arc::new(cluster), evt).await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn it_should_restore_manifest() {
+        let mut cluster = MockResourceDrivenCluster::new();
+        cluster.expect_create().return_once(|_| Ok(()));
+
+        let evt = ResourceRestored::default();
+
+        let result = restore_manifest(Arc::new(cluster), evt).await;
+        assert!(result.is_ok());
+    }
 }
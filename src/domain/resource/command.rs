@@ -5,18 +5,19 @@ use base64::{prelude::BASE64_STANDARD_NO_PAD, Engine};
 use bech32::{Bech32m, Hrp};
 use chrono::Utc;
 use rand::rngs::OsRng;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use uuid::Uuid;
 
 use crate::domain::{
-    auth::{assert_permission, Credential},
+    auth::{assert_permission, Credential, Permission},
     error::Error,
-    event::{EventDrivenBridge, ResourceCreated, ResourceDeleted},
+    event::{EventDrivenBridge, ResourceCreated, ResourceDeleted, ResourceRestored, APPLY_TIMEOUT},
     metadata::{KnownField, MetadataDriven},
-    project::cache::ProjectDrivenCache,
+    pagination::{Cursor, Page, Paged},
+    project::{cache::ProjectDrivenCache, quota, Project, ProjectStatus},
     resource::{ResourceStatus, ResourceUpdated},
     utils::{self, get_schema_from_crd},
-    Result, DEFAULT_CATEGORY, PAGE_SIZE_DEFAULT, PAGE_SIZE_MAX,
+    Result, DEFAULT_CATEGORY,
 };
 
 use super::{cache::ResourceDrivenCache, Resource};
@@ -26,17 +27,17 @@ pub async fn fetch(
     resource_cache: Arc<dyn ResourceDrivenCache>,
     metadata: Arc<dyn MetadataDriven>,
     cmd: FetchCmd,
-) -> Result<Vec<Resource>> {
+) -> Result<Paged<Resource>> {
     assert_permission(
         project_cache.clone(),
         &cmd.credential,
         &cmd.project_id,
-        None,
+        Permission::ResourceRead,
     )
     .await?;
 
     let resources = resource_cache
-        .find(&cmd.project_id, &cmd.page, &cmd.page_size, &cmd.category)
+        .find(&cmd.project_id, &cmd.page, &cmd.category)
         .await?
         .into_iter()
         .map(|mut resource| {
@@ -49,7 +50,9 @@ pub async fn fetch(
         })
         .collect();
 
-    Ok(resources)
+    Ok(cmd
+        .page
+        .paged(resources, |r| Cursor::new(Some(r.created_at), &r.id)))
 }
 
 pub async fn fetch_by_id(
@@ -66,7 +69,7 @@ pub async fn fetch_by_id(
         project_cache.clone(),
         &cmd.credential,
         &resource.project_id,
-        None,
+        Permission::ResourceRead,
     )
     .await?;
 
@@ -89,7 +92,7 @@ pub async fn create(
         project_cache.clone(),
         &cmd.credential,
         &cmd.project_id,
-        None,
+        Permission::ResourceCreate,
     )
     .await?;
 
@@ -108,6 +111,7 @@ pub async fn create(
     let Some(project) = project_cache.find_by_id(&cmd.project_id).await? else {
         return Err(Error::CommandMalformed("invalid project id".into()));
     };
+    assert_not_suspended(&project)?;
 
     let mut spec = cmd.spec.clone();
     if let Some(status_schema) = get_schema_from_crd(&metadata.crd, "status") {
@@ -132,6 +136,24 @@ pub async fn create(
         }
     };
 
+    let category = metadata
+        .crd
+        .spec
+        .names
+        .categories
+        .and_then(|c| c.first().map(String::to_owned))
+        .unwrap_or(DEFAULT_CATEGORY.to_string());
+
+    quota::assert_create(
+        project_cache.clone(),
+        resource_cache.clone(),
+        &project,
+        &cmd.kind,
+        &category,
+        &spec,
+    )
+    .await?;
+
     // TODO: add data from crd to build api resource
     let evt: ResourceCreated = ResourceCreated {
         id: cmd.id,
@@ -139,15 +161,11 @@ pub async fn create(
         project_namespace: project.namespace,
         name: cmd.name,
         kind: cmd.kind.clone(),
-        category: metadata
-            .crd
-            .spec
-            .names
-            .categories
-            .and_then(|c| c.first().map(String::to_owned))
-            .unwrap_or(DEFAULT_CATEGORY.to_string()),
+        category,
         spec: serde_json::to_string(&spec)?,
         status: ResourceStatus::Active.to_string(),
+        cluster_id: cmd.cluster_id.or(project.cluster_id),
+        created_by: Some(cmd.credential.actor()),
         created_at: Utc::now(),
         updated_at: Utc::now(),
     };
@@ -172,7 +190,7 @@ pub async fn update(
         project_cache.clone(),
         &cmd.credential,
         &resource.project_id,
-        None,
+        Permission::ResourceUpdate,
     )
     .await?;
 
@@ -180,6 +198,9 @@ pub async fn update(
         return Err(Error::CommandMalformed("invalid project id".into()));
     };
 
+    assert_not_suspended(&project)?;
+    quota::assert_update(project_cache.clone(), &project, &cmd.spec).await?;
+
     let evt = ResourceUpdated {
         id: cmd.id.clone(),
         project_id: project.id,
@@ -187,12 +208,23 @@ pub async fn update(
         name: resource.name,
         kind: resource.kind,
         spec_patch: serde_json::to_string(&cmd.spec)?,
+        cluster_id: resource.cluster_id,
+        updated_by: Some(cmd.credential.actor()),
         updated_at: Utc::now(),
     };
 
-    event.dispatch(evt.into()).await?;
+    let offset = event.dispatch(evt.into()).await?;
     info!(resource = cmd.id, "resource updated");
 
+    if let Some(offset) = offset {
+        if !resource_cache.wait_applied(&offset, APPLY_TIMEOUT).await? {
+            warn!(
+                resource = cmd.id,
+                "resource update not applied to the cache in time"
+            );
+        }
+    }
+
     let Some(resource) = resource_cache.find_by_id(&cmd.id).await? else {
         return Err(Error::CommandMalformed("Missing resource".into()));
     };
@@ -214,7 +246,7 @@ pub async fn delete(
         project_cache.clone(),
         &cmd.credential,
         &resource.project_id,
-        None,
+        Permission::ResourceDelete,
     )
     .await?;
 
@@ -229,6 +261,8 @@ pub async fn delete(
         name: resource.name,
         kind: resource.kind.clone(),
         status: ResourceStatus::Deleted.to_string(),
+        cluster_id: resource.cluster_id,
+        deleted_by: Some(cmd.credential.actor()),
         deleted_at: Utc::now(),
     };
 
@@ -238,6 +272,106 @@ pub async fn delete(
     Ok(())
 }
 
+pub async fn restore(
+    project_cache: Arc<dyn ProjectDrivenCache>,
+    resource_cache: Arc<dyn ResourceDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    cmd: RestoreCmd,
+) -> Result<()> {
+    let Credential::Auth0(user_id) = &cmd.credential else {
+        return Err(Error::Unauthorized(
+            "resource restore doesnt support secret".into(),
+        ));
+    };
+
+    let Some(resource) = resource_cache.find_deleted_by_id(&cmd.id).await? else {
+        return Err(Error::CommandMalformed("invalid resource id".into()));
+    };
+
+    assert_permission(
+        project_cache.clone(),
+        &cmd.credential,
+        &resource.project_id,
+        Permission::ResourceRestore,
+    )
+    .await?;
+
+    apply_restore(
+        project_cache,
+        resource_cache,
+        event,
+        &cmd.id,
+        user_id,
+        false,
+    )
+    .await
+}
+
+/// Restores a deleted resource of an active project, without authorization check.
+pub async fn apply_restore(
+    project_cache: Arc<dyn ProjectDrivenCache>,
+    resource_cache: Arc<dyn ResourceDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    id: &str,
+    restored_by: &str,
+    dry_run: bool,
+) -> Result<()> {
+    let Some(resource) = resource_cache.find_deleted_by_id(id).await? else {
+        return Err(Error::CommandMalformed("invalid resource id".into()));
+    };
+    utils::assert_restorable(&resource.updated_at)?;
+
+    let Some(project) = project_cache.find_by_id(&resource.project_id).await? else {
+        return Err(Error::CommandMalformed(
+            "the project is deleted, it must be restored first".into(),
+        ));
+    };
+    assert_not_suspended(&project)?;
+
+    if resource_cache
+        .find_by_name(&project.id, &resource.name)
+        .await?
+        .is_some()
+    {
+        return Err(Error::CommandMalformed(format!(
+            "the name {} is used by another resource",
+            resource.name
+        )));
+    }
+
+    let evt = ResourceRestored {
+        id: resource.id,
+        project_id: project.id,
+        project_namespace: project.namespace,
+        name: resource.name,
+        kind: resource.kind.clone(),
+        category: resource.category,
+        spec: resource.spec,
+        cluster_id: resource.cluster_id,
+        restored_by: restored_by.to_string(),
+        restored_at: Utc::now(),
+    };
+
+    if dry_run {
+        info!("event to dispath: {:?}", evt);
+        return Ok(());
+    }
+
+    event.dispatch(evt.into()).await?;
+    info!(resource = resource.kind, "resource restored");
+
+    Ok(())
+}
+
+// The monitor only annotates the resources of a project as suspended when it's suspended, a
+// resource created, updated or restored afterwards would run without the annotation.
+fn assert_not_suspended(project: &Project) -> Result<()> {
+    if matches!(project.status, ProjectStatus::Suspended) {
+        return Err(Error::CommandMalformed("project is suspended".into()));
+    }
+    Ok(())
+}
+
 pub fn build_key(project_id: &str, resource_id: &str) -> Result<Vec<u8>> {
     let argon2 = Argon2::default();
     let key = format!("{project_id}{resource_id}").as_bytes().to_vec();
@@ -261,8 +395,7 @@ pub fn encode_key(key: Vec<u8>, prefix: &str) -> Result<String> {
 pub struct FetchCmd {
     pub credential: Credential,
     pub project_id: String,
-    pub page: u32,
-    pub page_size: u32,
+    pub page: Page,
     pub category: String,
 }
 impl FetchCmd {
@@ -271,23 +404,15 @@ impl FetchCmd {
         project_id: String,
         page: Option<u32>,
         page_size: Option<u32>,
+        cursor: Option<String>,
         category: Option<String>,
     ) -> Result<Self> {
-        let page = page.unwrap_or(1);
-        let page_size = page_size.unwrap_or(PAGE_SIZE_DEFAULT);
         let category = category.unwrap_or(DEFAULT_CATEGORY.to_string());
 
-        if page_size >= PAGE_SIZE_MAX {
-            return Err(Error::CommandMalformed(format!(
-                "page_size exceeded the limit of {PAGE_SIZE_MAX}"
-            )));
-        }
-
         Ok(Self {
             credential,
             project_id,
-            page,
-            page_size,
+            page: Page::new(page, page_size, cursor)?,
             category,
         })
     }
@@ -308,6 +433,8 @@ pub struct CreateCmd {
     pub project_id: String,
     pub kind: String,
     pub spec: Spec,
+    /// Cluster to place the resource on, the one of the project when missing.
+    pub cluster_id: Option<String>,
 }
 impl CreateCmd {
     pub fn new(
@@ -315,6 +442,7 @@ impl CreateCmd {
         project_id: String,
         kind: String,
         spec: String,
+        cluster_id: Option<String>,
     ) -> Result<Self> {
         let id = Uuid::new_v4().to_string();
         let name = format!(
@@ -337,6 +465,7 @@ impl CreateCmd {
             project_id,
             kind,
             spec,
+            cluster_id,
         })
     }
 }
@@ -370,14 +499,28 @@ pub struct DeleteCmd {
     pub id: String,
 }
 
+#[derive(Debug, Clone)]
+pub struct RestoreCmd {
+    pub credential: Credential,
+    pub id: String,
+}
+impl RestoreCmd {
+    pub fn new(credential: Credential, id: String) -> Self {
+        Self { credential, id }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use uuid::Uuid;
 
-    use crate::domain::event::MockEventDrivenBridge;
+    use crate::domain::auth::API_KEY_ACTOR;
+    use crate::domain::event::{Event, MockEventDrivenBridge};
     use crate::domain::metadata::{MockMetadataDriven, ResourceMetadata};
     use crate::domain::project::cache::MockProjectDrivenCache;
-    use crate::domain::project::{Project, ProjectUser};
+    use crate::domain::project::{quota::ProjectQuota, Project, ProjectUser, ProjectUserRole};
     use crate::domain::resource::cache::MockResourceDrivenCache;
 
     use super::*;
@@ -387,8 +530,7 @@ mod tests {
             Self {
                 credential: Credential::Auth0("user id".into()),
                 project_id: Uuid::new_v4().to_string(),
-                page: 1,
-                page_size: 12,
+                page: Page::default(),
                 category: DEFAULT_CATEGORY.to_string(),
             }
         }
@@ -410,6 +552,7 @@ mod tests {
                 project_id: Uuid::new_v4().to_string(),
                 kind: "CardanoNodePort".into(),
                 spec: serde_json::Map::default(),
+                cluster_id: None,
             }
         }
     }
@@ -421,6 +564,14 @@ mod tests {
             }
         }
     }
+    impl Default for RestoreCmd {
+        fn default() -> Self {
+            Self {
+                credential: Credential::Auth0("user id".into()),
+                id: Uuid::new_v4().to_string(),
+            }
+        }
+    }
 
     #[tokio::test]
     async fn it_should_fetch_project_resources() {
@@ -432,7 +583,7 @@ mod tests {
         let mut resource_cache = MockResourceDrivenCache::new();
         resource_cache
             .expect_find()
-            .return_once(|_, _, _, _| Ok(vec![Resource::default()]));
+            .return_once(|_, _, _| Ok(vec![Resource::default()]));
 
         let mut metadata = MockMetadataDriven::new();
         metadata
@@ -538,6 +689,53 @@ mod tests {
         project_cache
             .expect_find_by_id()
             .return_once(|_| Ok(Some(Project::default())));
+        project_cache
+            .expect_find_default_quota()
+            .return_once(|| Ok(None));
+        project_cache.expect_find_quota().return_once(|_| Ok(None));
+
+        let mut metadata = MockMetadataDriven::new();
+        metadata
+            .expect_find_by_kind()
+            .return_once(|_| Ok(Some(ResourceMetadata::default())));
+
+        let mut event = MockEventDrivenBridge::new();
+        event.expect_dispatch().return_once(|_| Ok(None));
+
+        let cmd = CreateCmd::default();
+
+        let result = create(
+            Arc::new(resource_cache),
+            Arc::new(project_cache),
+            Arc::new(metadata),
+            Arc::new(event),
+            cmd,
+        )
+        .await;
+
+        assert!(result.is_ok());
+    }
+    #[tokio::test]
+    async fn it_should_place_resource_on_project_cluster() {
+        let mut resource_cache = MockResourceDrivenCache::new();
+        resource_cache
+            .expect_find_by_name()
+            .return_once(|_, _| Ok(None));
+
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        project_cache.expect_find_by_id().return_once(|_| {
+            Ok(Some(Project {
+                cluster_id: Some("cluster-a".into()),
+                ..Default::default()
+            }))
+        });
+        project_cache
+            .expect_find_default_quota()
+            .return_once(|| Ok(None));
+        project_cache.expect_find_quota().return_once(|_| Ok(None));
 
         let mut metadata = MockMetadataDriven::new();
         metadata
@@ -545,7 +743,10 @@ mod tests {
             .return_once(|_| Ok(Some(ResourceMetadata::default())));
 
         let mut event = MockEventDrivenBridge::new();
-        event.expect_dispatch().return_once(|_| Ok(()));
+        event
+            .expect_dispatch()
+            .withf(|event| event.cluster_id() == Some("cluster-a"))
+            .return_once(|_| Ok(None));
 
         let cmd = CreateCmd::default();
 
@@ -561,6 +762,90 @@ mod tests {
         assert!(result.is_ok());
     }
     #[tokio::test]
+    async fn it_should_fail_create_resource_when_quota_is_reached() {
+        let mut resource_cache = MockResourceDrivenCache::new();
+        resource_cache
+            .expect_find_by_name()
+            .return_once(|_, _| Ok(None));
+        resource_cache
+            .expect_count_by_kind()
+            .return_once(|_, _| Ok(1));
+
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        project_cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Project::default())));
+        project_cache.expect_find_default_quota().return_once(|| {
+            Ok(Some(ProjectQuota {
+                resources: HashMap::from([("CardanoNodePort".into(), 1)]),
+                ..Default::default()
+            }))
+        });
+        project_cache.expect_find_quota().return_once(|_| Ok(None));
+
+        let mut metadata = MockMetadataDriven::new();
+        metadata
+            .expect_find_by_kind()
+            .return_once(|_| Ok(Some(ResourceMetadata::default())));
+
+        // No dispatch expectation: the mock panics if the event is dispatched.
+        let event = MockEventDrivenBridge::new();
+
+        let cmd = CreateCmd::default();
+
+        let result = create(
+            Arc::new(resource_cache),
+            Arc::new(project_cache),
+            Arc::new(metadata),
+            Arc::new(event),
+            cmd,
+        )
+        .await;
+
+        assert!(matches!(result, Err(Error::CommandMalformed(_))));
+    }
+    #[tokio::test]
+    async fn it_should_fail_create_resource_when_project_is_suspended() {
+        let mut resource_cache = MockResourceDrivenCache::new();
+        resource_cache
+            .expect_find_by_name()
+            .return_once(|_, _| Ok(None));
+
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        project_cache.expect_find_by_id().return_once(|_| {
+            Ok(Some(Project {
+                status: ProjectStatus::Suspended,
+                ..Default::default()
+            }))
+        });
+
+        let mut metadata = MockMetadataDriven::new();
+        metadata
+            .expect_find_by_kind()
+            .return_once(|_| Ok(Some(ResourceMetadata::default())));
+
+        let event = MockEventDrivenBridge::new();
+
+        let cmd = CreateCmd::default();
+
+        let result = create(
+            Arc::new(resource_cache),
+            Arc::new(project_cache),
+            Arc::new(metadata),
+            Arc::new(event),
+            cmd,
+        )
+        .await;
+
+        assert!(matches!(result, Err(Error::CommandMalformed(_))));
+    }
+    #[tokio::test]
     async fn it_should_fail_create_resource_when_crd_doesnt_exist() {
         let mut resource_cache = MockResourceDrivenCache::new();
         resource_cache
@@ -670,6 +955,44 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn it_should_fail_update_resource_when_project_is_suspended() {
+        let mut resource_cache = MockResourceDrivenCache::new();
+        resource_cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Resource::default())));
+
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        project_cache.expect_find_by_id().return_once(|_| {
+            Ok(Some(Project {
+                status: ProjectStatus::Suspended,
+                ..Default::default()
+            }))
+        });
+
+        let event = MockEventDrivenBridge::new();
+
+        let cmd = UpdateCmd::new(
+            Credential::Auth0("user id".into()),
+            "resource id".into(),
+            "{\"throughputTier\":\"1\"}".into(),
+        )
+        .unwrap();
+
+        let result = update(
+            Arc::new(project_cache),
+            Arc::new(resource_cache),
+            Arc::new(event),
+            cmd,
+        )
+        .await;
+
+        assert!(matches!(result, Err(Error::CommandMalformed(_))));
+    }
+
     #[tokio::test]
     async fn it_should_delete_resource() {
         let mut resource_cache = MockResourceDrivenCache::new();
@@ -686,7 +1009,13 @@ mod tests {
             .return_once(|_| Ok(Some(Project::default())));
 
         let mut event = MockEventDrivenBridge::new();
-        event.expect_dispatch().return_once(|_| Ok(()));
+        event
+            .expect_dispatch()
+            .withf(|evt| match evt {
+                Event::ResourceDeleted(evt) => evt.deleted_by.as_deref() == Some("user id"),
+                _ => false,
+            })
+            .return_once(|_| Ok(None));
 
         let cmd = DeleteCmd::default();
 
@@ -701,6 +1030,45 @@ mod tests {
         assert!(result.is_ok());
     }
     #[tokio::test]
+    async fn it_should_record_the_api_key_as_actor_of_deleted_resource() {
+        let resource = Resource::default();
+        let project_id = resource.project_id.clone();
+
+        let mut resource_cache = MockResourceDrivenCache::new();
+        resource_cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(resource)));
+
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Project::default())));
+
+        let mut event = MockEventDrivenBridge::new();
+        event
+            .expect_dispatch()
+            .withf(|evt| match evt {
+                Event::ResourceDeleted(evt) => evt.deleted_by.as_deref() == Some(API_KEY_ACTOR),
+                _ => false,
+            })
+            .return_once(|_| Ok(None));
+
+        let cmd = DeleteCmd {
+            credential: Credential::ApiKey(project_id),
+            ..Default::default()
+        };
+
+        let result = delete(
+            Arc::new(project_cache),
+            Arc::new(resource_cache),
+            Arc::new(event),
+            cmd,
+        )
+        .await;
+
+        assert!(result.is_ok());
+    }
+    #[tokio::test]
     async fn it_should_fail_delete_resource_when_user_doesnt_have_permission() {
         let mut resource_cache = MockResourceDrivenCache::new();
         resource_cache
@@ -751,4 +1119,149 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn it_should_restore_resource() {
+        let mut resource_cache = MockResourceDrivenCache::new();
+        resource_cache
+            .expect_find_deleted_by_id()
+            .returning(|_| Ok(Some(Resource::default())));
+        resource_cache
+            .expect_find_by_name()
+            .return_once(|_, _| Ok(None));
+
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        project_cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Project::default())));
+
+        let mut event = MockEventDrivenBridge::new();
+        event.expect_dispatch().return_once(|_| Ok(None));
+
+        let cmd = RestoreCmd::default();
+
+        let result = restore(
+            Arc::new(project_cache),
+            Arc::new(resource_cache),
+            Arc::new(event),
+            cmd,
+        )
+        .await;
+
+        assert!(result.is_ok());
+    }
+    #[tokio::test]
+    async fn it_should_fail_restore_resource_when_project_is_deleted() {
+        let mut resource_cache = MockResourceDrivenCache::new();
+        resource_cache
+            .expect_find_deleted_by_id()
+            .returning(|_| Ok(Some(Resource::default())));
+
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        project_cache.expect_find_by_id().return_once(|_| Ok(None));
+
+        let event = MockEventDrivenBridge::new();
+
+        let cmd = RestoreCmd::default();
+
+        let result = restore(
+            Arc::new(project_cache),
+            Arc::new(resource_cache),
+            Arc::new(event),
+            cmd,
+        )
+        .await;
+
+        assert!(matches!(result, Err(Error::CommandMalformed(_))));
+    }
+    #[tokio::test]
+    async fn it_should_fail_restore_resource_when_project_is_suspended() {
+        let mut resource_cache = MockResourceDrivenCache::new();
+        resource_cache
+            .expect_find_deleted_by_id()
+            .returning(|_| Ok(Some(Resource::default())));
+
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        project_cache.expect_find_by_id().return_once(|_| {
+            Ok(Some(Project {
+                status: ProjectStatus::Suspended,
+                ..Default::default()
+            }))
+        });
+
+        let event = MockEventDrivenBridge::new();
+
+        let cmd = RestoreCmd::default();
+
+        let result = restore(
+            Arc::new(project_cache),
+            Arc::new(resource_cache),
+            Arc::new(event),
+            cmd,
+        )
+        .await;
+
+        assert!(matches!(result, Err(Error::CommandMalformed(_))));
+    }
+    #[tokio::test]
+    async fn it_should_fail_restore_resource_when_user_is_not_owner() {
+        let mut resource_cache = MockResourceDrivenCache::new();
+        resource_cache
+            .expect_find_deleted_by_id()
+            .returning(|_| Ok(Some(Resource::default())));
+
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| {
+                Ok(Some(ProjectUser {
+                    role: ProjectUserRole::Admin,
+                    ..Default::default()
+                }))
+            });
+
+        let event = MockEventDrivenBridge::new();
+
+        let cmd = RestoreCmd::default();
+
+        let result = restore(
+            Arc::new(project_cache),
+            Arc::new(resource_cache),
+            Arc::new(event),
+            cmd,
+        )
+        .await;
+
+        assert!(matches!(result, Err(Error::Unauthorized(_))));
+    }
+    #[tokio::test]
+    async fn it_should_fail_restore_resource_when_secret_is_used() {
+        let resource_cache = MockResourceDrivenCache::new();
+        let project_cache = MockProjectDrivenCache::new();
+        let event = MockEventDrivenBridge::new();
+
+        let cmd = RestoreCmd {
+            credential: Credential::ApiKey(Uuid::new_v4().to_string()),
+            ..Default::default()
+        };
+
+        let result = restore(
+            Arc::new(project_cache),
+            Arc::new(resource_cache),
+            Arc::new(event),
+            cmd,
+        )
+        .await;
+
+        assert!(matches!(result, Err(Error::Unauthorized(_))));
+    }
 }
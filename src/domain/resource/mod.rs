@@ -20,6 +20,7 @@ pub struct Resource {
     pub spec: String,
     pub annotations: Option<String>,
     pub status: ResourceStatus,
+    pub cluster_id: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -36,6 +37,7 @@ impl TryFrom<ResourceCreated> for Resource {
             spec: value.spec,
             annotations: None,
             status: value.status.parse()?,
+            cluster_id: value.cluster_id,
             created_at: value.created_at,
             updated_at: value.updated_at,
         })
@@ -120,6 +122,7 @@ mod tests {
                 category: DEFAULT_CATEGORY.to_string(),
                 annotations: None,
                 status: ResourceStatus::Active,
+                cluster_id: None,
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
             }
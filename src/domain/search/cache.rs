@@ -0,0 +1,14 @@
+use crate::domain::Result;
+
+use super::{SearchFilter, SearchResult};
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait::async_trait]
+pub trait SearchDrivenCache: Send + Sync {
+    async fn search(
+        &self,
+        filter: &SearchFilter,
+        page: &u32,
+        page_size: &u32,
+    ) -> Result<Vec<SearchResult>>;
+}
@@ -0,0 +1,222 @@
+use std::{str::FromStr, sync::Arc};
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::{
+    auth::{Auth0Driven, Credential},
+    error::Error,
+    resource::ResourceStatus,
+    Result, PAGE_SIZE_DEFAULT, PAGE_SIZE_MAX,
+};
+
+use super::{cache::SearchDrivenCache, SearchFilter, SearchResult};
+
+/// Searches the resources of the projects the credential can access, a user sees the projects
+/// they are a member of and an api key only its own project.
+pub async fn fetch(
+    search_cache: Arc<dyn SearchDrivenCache>,
+    auth0: Arc<dyn Auth0Driven>,
+    cmd: FetchCmd,
+) -> Result<Vec<SearchResult>> {
+    let mut filter = cmd.filter;
+    match cmd.credential {
+        Credential::Auth0(user_id) => filter.member = Some(user_id),
+        Credential::ApiKey(project_id) => filter.project_id = Some(project_id),
+    }
+
+    if let Some(owner_email) = cmd.owner_email {
+        let profiles = auth0.find_info(&format!("email:{owner_email}")).await?;
+        match profiles.first() {
+            Some(profile) => filter.owner = Some(profile.user_id.clone()),
+            None => return Ok(Vec::new()),
+        }
+    }
+
+    search_cache
+        .search(&filter, &cmd.page, &cmd.page_size)
+        .await
+}
+
+#[derive(Debug, Clone)]
+pub struct FetchCmd {
+    pub credential: Credential,
+    pub filter: SearchFilter,
+    pub owner_email: Option<String>,
+    pub page: u32,
+    pub page_size: u32,
+}
+impl FetchCmd {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        credential: Credential,
+        text: Option<String>,
+        kind: Option<String>,
+        network: Option<String>,
+        tier: Option<String>,
+        status: Option<String>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        owner_email: Option<String>,
+        page: Option<u32>,
+        page_size: Option<u32>,
+    ) -> Result<Self> {
+        let page = page.unwrap_or(1);
+        let page_size = page_size.unwrap_or(PAGE_SIZE_DEFAULT);
+
+        if page_size >= PAGE_SIZE_MAX {
+            return Err(Error::CommandMalformed(format!(
+                "page_size exceeded the limit of {PAGE_SIZE_MAX}"
+            )));
+        }
+
+        if let (Some(from), Some(to)) = (&from, &to) {
+            if from > to {
+                return Err(Error::CommandMalformed("from must be before to".into()));
+            }
+        }
+
+        let status = match status {
+            Some(status) => ResourceStatus::from_str(&status)
+                .map_err(|_| Error::CommandMalformed(format!("invalid status {status}")))?,
+            None => ResourceStatus::Active,
+        };
+
+        Ok(Self {
+            credential,
+            filter: SearchFilter {
+                text,
+                kind,
+                network,
+                tier,
+                status: Some(status.to_string()),
+                from,
+                to,
+                ..Default::default()
+            },
+            owner_email,
+            page,
+            page_size,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{
+        auth::{Auth0Profile, MockAuth0Driven},
+        search::cache::MockSearchDrivenCache,
+    };
+
+    impl Default for FetchCmd {
+        fn default() -> Self {
+            Self {
+                credential: Credential::Auth0("user id".into()),
+                filter: SearchFilter {
+                    text: Some("mainnet".into()),
+                    ..Default::default()
+                },
+                owner_email: None,
+                page: 1,
+                page_size: 12,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_scope_search_to_user_projects() {
+        let mut search_cache = MockSearchDrivenCache::new();
+        search_cache
+            .expect_search()
+            .withf(|filter, _, _| {
+                filter.member == Some("user id".into()) && filter.project_id.is_none()
+            })
+            .return_once(|_, _, _| Ok(vec![]));
+
+        let result = fetch(
+            Arc::new(search_cache),
+            Arc::new(MockAuth0Driven::new()),
+            FetchCmd::default(),
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_scope_search_to_secret_project() {
+        let mut search_cache = MockSearchDrivenCache::new();
+        search_cache
+            .expect_search()
+            .withf(|filter, _, _| {
+                filter.project_id == Some("project id".into()) && filter.member.is_none()
+            })
+            .return_once(|_, _, _| Ok(vec![]));
+
+        let cmd = FetchCmd {
+            credential: Credential::ApiKey("project id".into()),
+            ..Default::default()
+        };
+
+        let result = fetch(
+            Arc::new(search_cache),
+            Arc::new(MockAuth0Driven::new()),
+            cmd,
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_filter_search_by_owner_email() {
+        let mut auth0 = MockAuth0Driven::new();
+        auth0
+            .expect_find_info()
+            .return_once(|_| Ok(vec![Auth0Profile::default()]));
+
+        let mut search_cache = MockSearchDrivenCache::new();
+        search_cache
+            .expect_search()
+            .withf(|filter, _, _| filter.owner == Some(Auth0Profile::default().user_id))
+            .return_once(|_, _, _| Ok(vec![]));
+
+        let cmd = FetchCmd {
+            owner_email: Some("user email".into()),
+            ..Default::default()
+        };
+
+        let result = fetch(Arc::new(search_cache), Arc::new(auth0), cmd).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_return_empty_search_when_owner_email_is_unknown() {
+        let mut auth0 = MockAuth0Driven::new();
+        auth0.expect_find_info().return_once(|_| Ok(vec![]));
+
+        let cmd = FetchCmd {
+            owner_email: Some("unknown email".into()),
+            ..Default::default()
+        };
+
+        let result = fetch(Arc::new(MockSearchDrivenCache::new()), Arc::new(auth0), cmd).await;
+        assert!(result.is_ok_and(|results| results.is_empty()));
+    }
+
+    #[test]
+    fn it_should_fail_new_fetch_cmd_when_status_is_invalid() {
+        let result = FetchCmd::new(
+            Credential::Auth0("user id".into()),
+            None,
+            None,
+            None,
+            None,
+            Some("paused".into()),
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert!(result.is_err());
+    }
+}
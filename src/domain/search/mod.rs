@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+
+pub mod cache;
+pub mod command;
+
+/// Typed filters of a search, `text` is matched against the project and resource names and the
+/// values of the resource spec.
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilter {
+    pub text: Option<String>,
+    pub kind: Option<String>,
+    pub network: Option<String>,
+    pub tier: Option<String>,
+    pub status: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub owner: Option<String>,
+    /// Only the projects this user is a member of.
+    pub member: Option<String>,
+    pub project_id: Option<String>,
+}
+
+/// A resource found by a search, with the project it belongs to.
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub project_id: String,
+    pub project_namespace: String,
+    pub project_name: String,
+    pub project_owner: String,
+    pub resource_id: String,
+    pub resource_name: String,
+    pub kind: String,
+    pub spec: String,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,13 @@
+use crate::domain::Result;
+
+use super::ProjectSnapshot;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait::async_trait]
+pub trait SnapshotDrivenCache: Send + Sync {
+    async fn find_project_ids(&self) -> Result<Vec<String>>;
+    /// The project with its users, pending invites, secrets, active resources and organization.
+    /// Positions are left empty, they belong to the consumer and not to the projection.
+    async fn find(&self, project_id: &str) -> Result<Option<ProjectSnapshot>>;
+    async fn restore(&self, snapshot: &ProjectSnapshot) -> Result<()>;
+}
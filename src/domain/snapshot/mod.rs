@@ -0,0 +1,306 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc};
+use tracing::info;
+
+use super::{event::Event, Result};
+
+pub mod cache;
+
+/// State of a project in the cache after applying the events up to `positions`. It's published
+/// keyed by the project id, so a compacted topic only keeps the latest one of each project.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectSnapshot {
+    pub project: SnapshotProject,
+    pub users: Vec<SnapshotUser>,
+    pub invites: Vec<SnapshotInvite>,
+    pub secrets: Vec<SnapshotSecret>,
+    pub resources: Vec<SnapshotResource>,
+    /// Organization of the project with its members. Their events have no project and are
+    /// replayed from the earliest snapshot, so without it an older organization would be lost.
+    #[serde(default)]
+    pub organization: Option<SnapshotOrganization>,
+    /// Quota of the project and the default one, which is kept for the same reason.
+    #[serde(default)]
+    pub quotas: Vec<SnapshotQuota>,
+    pub positions: Vec<SnapshotPosition>,
+    pub taken_at: DateTime<Utc>,
+}
+impl ProjectSnapshot {
+    /// Whether the event was already applied when the snapshot was taken.
+    pub fn contains(&self, topic: &str, partition: i32, offset: i64) -> bool {
+        self.positions
+            .iter()
+            .any(|p| p.topic == topic && p.partition == partition && offset <= p.offset)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotProject {
+    pub id: String,
+    pub name: String,
+    pub namespace: String,
+    pub owner: String,
+    pub status: String,
+    pub billing_provider: String,
+    pub billing_provider_id: String,
+    pub billing_subscription_id: Option<String>,
+    pub cluster_id: Option<String>,
+    #[serde(default)]
+    pub organization_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotOrganization {
+    pub id: String,
+    pub name: String,
+    pub owner: String,
+    pub billing_provider: String,
+    pub billing_provider_id: String,
+    pub users: Vec<SnapshotUser>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotUser {
+    pub user_id: String,
+    pub role: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInvite {
+    pub id: String,
+    pub email: String,
+    pub role: String,
+    pub code: String,
+    pub status: String,
+    pub expires_in: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Row of the `project_quota` table, the default quota is the one keyed `default`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotQuota {
+    pub project_id: String,
+    pub resources: String,
+    pub max_tier: Option<i64>,
+    pub workers: Option<i64>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Carries the same fields as `ProjectSecretCreated`, without them the api keys of the project
+/// can't be verified by a cache restored from the snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotSecret {
+    pub id: String,
+    pub name: String,
+    pub phc: String,
+    pub secret: Vec<u8>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotResource {
+    pub id: String,
+    pub name: String,
+    pub kind: String,
+    pub category: String,
+    pub spec: String,
+    pub status: String,
+    pub cluster_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Last offset applied in a topic partition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotPosition {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+}
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait::async_trait]
+pub trait SnapshotDrivenBridge: Send + Sync {
+    async fn publish(&self, snapshot: &ProjectSnapshot) -> Result<()>;
+}
+
+/// Publishes a snapshot of every project in the cache, deleted ones included so their older
+/// events are also skipped by a restore.
+pub async fn publish(
+    cache: Arc<dyn cache::SnapshotDrivenCache>,
+    bridge: Arc<dyn SnapshotDrivenBridge>,
+    positions: Vec<SnapshotPosition>,
+) -> Result<usize> {
+    let project_ids = cache.find_project_ids().await?;
+    let taken_at = Utc::now();
+
+    let mut published = 0;
+    for project_id in project_ids {
+        let Some(mut snapshot) = cache.find(&project_id).await? else {
+            continue;
+        };
+        snapshot.positions = positions.clone();
+        snapshot.taken_at = taken_at;
+
+        bridge.publish(&snapshot).await?;
+        published += 1;
+    }
+
+    info!(published, "Project snapshots published");
+    Ok(published)
+}
+
+/// Latest snapshot of each project, as read from the snapshot topic.
+#[derive(Debug, Default)]
+pub struct Snapshots {
+    projects: HashMap<String, ProjectSnapshot>,
+}
+impl Snapshots {
+    pub fn insert(&mut self, snapshot: ProjectSnapshot) {
+        self.projects.insert(snapshot.project.id.clone(), snapshot);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.projects.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &ProjectSnapshot> {
+        self.projects.values()
+    }
+
+    /// Offset up to which every snapshot already applied the partition, the events after it
+    /// must be replayed. `None` when a snapshot doesn't cover the partition.
+    pub fn start(&self, topic: &str, partition: i32) -> Option<i64> {
+        self.projects
+            .values()
+            .map(|snapshot| {
+                snapshot
+                    .positions
+                    .iter()
+                    .find(|p| p.topic == topic && p.partition == partition)
+                    .map(|p| p.offset)
+            })
+            .min()
+            .flatten()
+    }
+
+    /// Whether the event is already part of the snapshot of its project. Events without a
+    /// project, e.g. `ProjectSecretDeleted`, and the usage, which isn't snapshotted, are always
+    /// applied.
+    pub fn contains(&self, event: &Event, topic: &str, partition: i32, offset: i64) -> bool {
+        if matches!(event, Event::UsageCreated(_)) {
+            return false;
+        }
+
+        event
+            .project_id()
+            .and_then(|project_id| self.projects.get(project_id))
+            .is_some_and(|snapshot| snapshot.contains(topic, partition, offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::domain::event::{ProjectSecretDeleted, ResourceCreated, UsageCreated};
+
+    use super::{cache::MockSnapshotDrivenCache, *};
+
+    impl Default for ProjectSnapshot {
+        fn default() -> Self {
+            Self {
+                project: SnapshotProject {
+                    id: Uuid::new_v4().to_string(),
+                    name: "New Project".into(),
+                    namespace: "sonic-vegas".into(),
+                    owner: "user id".into(),
+                    status: "active".into(),
+                    billing_provider: "stripe".into(),
+                    billing_provider_id: "stripe id".into(),
+                    billing_subscription_id: None,
+                    cluster_id: None,
+                    organization_id: None,
+                    created_at: Utc::now(),
+                    updated_at: Utc::now(),
+                },
+                users: vec![SnapshotUser {
+                    user_id: "user id".into(),
+                    role: "owner".into(),
+                    created_at: Utc::now(),
+                }],
+                invites: Default::default(),
+                secrets: Default::default(),
+                resources: Default::default(),
+                organization: None,
+                quotas: Default::default(),
+                positions: vec![SnapshotPosition {
+                    topic: "events".into(),
+                    partition: 0,
+                    offset: 10,
+                }],
+                taken_at: Utc::now(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_publish_snapshots_with_positions() {
+        let mut cache = MockSnapshotDrivenCache::new();
+        cache
+            .expect_find_project_ids()
+            .return_once(|| Ok(vec!["a".into(), "b".into()]));
+        cache
+            .expect_find()
+            .returning(|_| Ok(Some(ProjectSnapshot::default())));
+
+        let mut bridge = MockSnapshotDrivenBridge::new();
+        bridge
+            .expect_publish()
+            .withf(|snapshot| snapshot.positions[0].offset == 42)
+            .times(2)
+            .returning(|_| Ok(()));
+
+        let positions = vec![SnapshotPosition {
+            topic: "events".into(),
+            partition: 0,
+            offset: 42,
+        }];
+        let result = publish(Arc::new(cache), Arc::new(bridge), positions).await;
+        assert!(matches!(result, Ok(2)));
+    }
+
+    #[test]
+    fn it_should_skip_events_applied_in_the_snapshot() {
+        let snapshot = ProjectSnapshot::default();
+        let event: Event = ResourceCreated {
+            project_id: snapshot.project.id.clone(),
+            ..Default::default()
+        }
+        .into();
+
+        let mut snapshots = Snapshots::default();
+        snapshots.insert(snapshot);
+
+        assert!(snapshots.contains(&event, "events", 0, 10));
+        assert!(!snapshots.contains(&event, "events", 0, 11));
+        assert!(!snapshots.contains(&event, "usage", 0, 1));
+        assert!(!snapshots.contains(&ProjectSecretDeleted::default().into(), "events", 0, 1));
+
+        let usage: Event = UsageCreated {
+            project_id: snapshots.iter().next().unwrap().project.id.clone(),
+            ..Default::default()
+        }
+        .into();
+        assert!(!snapshots.contains(&usage, "events", 0, 1));
+        assert!(snapshots.start("events", 0) == Some(10));
+        assert!(snapshots.start("usage", 0).is_none());
+    }
+}
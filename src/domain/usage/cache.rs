@@ -1,8 +1,10 @@
+use chrono::{DateTime, Duration, Utc};
 use std::sync::Arc;
+use tracing::info;
 
 use crate::domain::{event::UsageCreated, Result};
 
-use super::{Usage, UsageReport, UsageResource};
+use super::{invoice::Invoice, Usage, UsageReport, UsageResource};
 
 #[cfg_attr(test, mockall::automock)]
 #[async_trait::async_trait]
@@ -31,10 +33,42 @@ pub trait UsageDrivenCacheBackoffice: Send + Sync {
     async fn find_clusters(&self, period: &str) -> Result<Vec<String>>;
 }
 
+/// Monthly totals of the raw usage rows, so the closed months don't need the rows themselves.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait::async_trait]
+pub trait UsageDrivenCacheRollup: Send + Sync {
+    /// Adds the rows of the months before `period` that weren't rolled up yet to the monthly
+    /// totals, returning how many rows were rolled up.
+    async fn rollup(&self, period: &str, rolled_up_at: &DateTime<Utc>) -> Result<u64>;
+    /// Deletes the rows already rolled up that were created before `before`.
+    async fn purge(&self, before: &DateTime<Utc>) -> Result<u64>;
+}
+
+/// Ledger of the usage already invoiced, one invoice per project and period.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait::async_trait]
+pub trait UsageDrivenCacheInvoice: Send + Sync {
+    async fn find_invoice(&self, project_id: &str, period: &str) -> Result<Option<Invoice>>;
+    async fn create_invoice(&self, invoice: &Invoice) -> Result<()>;
+}
+
 pub async fn create(cache: Arc<dyn UsageDrivenCache>, evt: UsageCreated) -> Result<()> {
     cache.create(evt.into()).await
 }
 
+/// Rolls up the months closed so far and keeps the raw rows for `retention`. The current month
+/// is never rolled up, its rows keep arriving.
+pub async fn rollup(cache: Arc<dyn UsageDrivenCacheRollup>, retention: Duration) -> Result<()> {
+    let now = Utc::now();
+    let period = now.format("%Y-%m").to_string();
+
+    let rolled_up = cache.rollup(&period, &now).await?;
+    let purged = cache.purge(&(now - retention)).await?;
+    info!(rolled_up, purged, "Usage rolled up");
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -49,5 +83,22 @@ mod tests {
         let result = create(Arc::new(usage_cache), evt).await;
         assert!(result.is_ok());
     }
-}
 
+    #[tokio::test]
+    async fn it_should_rollup_months_before_the_current_one() {
+        let period = Utc::now().format("%Y-%m").to_string();
+
+        let mut usage_cache = MockUsageDrivenCacheRollup::new();
+        usage_cache
+            .expect_rollup()
+            .withf(move |p, _| p == period)
+            .return_once(|_, _| Ok(2));
+        usage_cache
+            .expect_purge()
+            .withf(|before| *before < Utc::now() - Duration::days(29))
+            .return_once(|_| Ok(2));
+
+        let result = rollup(Arc::new(usage_cache), Duration::days(30)).await;
+        assert!(result.is_ok());
+    }
+}
@@ -153,7 +153,7 @@ mod tests {
             .return_once(|| Ok(vec![UsageResource::default()]));
 
         let mut event = MockEventDrivenBridge::new();
-        event.expect_dispatch().return_once(|_| Ok(()));
+        event.expect_dispatch().return_once(|_| Ok(None));
 
         let result = sync_usage(
             Arc::new(cache),
@@ -1,10 +1,10 @@
 use std::sync::Arc;
 
 use crate::domain::{
-    auth::{assert_permission, Credential},
+    auth::{assert_permission, Credential, Permission},
     error::Error,
     metadata::MetadataDriven,
-    project::{cache::ProjectDrivenCache, ProjectUserRole},
+    project::cache::ProjectDrivenCache,
     Result, PAGE_SIZE_DEFAULT, PAGE_SIZE_MAX,
 };
 
@@ -20,7 +20,7 @@ pub async fn fetch_report(
         project_cache.clone(),
         &cmd.credential,
         &cmd.project_id,
-        Some(ProjectUserRole::Owner),
+        Permission::UsageRead,
     )
     .await?;
 
@@ -41,7 +41,7 @@ pub async fn fetch_clusters(
         project_cache.clone(),
         &cmd.credential,
         &cmd.project_id,
-        Some(ProjectUserRole::Owner),
+        Permission::UsageRead,
     )
     .await?;
 
@@ -94,7 +94,7 @@ mod tests {
     use super::*;
     use crate::domain::{
         metadata::{MockMetadataDriven, ResourceMetadata},
-        project::{cache::MockProjectDrivenCache, ProjectUser},
+        project::{cache::MockProjectDrivenCache, ProjectUser, ProjectUserRole},
         usage::cache::MockUsageDrivenCache,
     };
 
@@ -137,6 +137,10 @@ mod tests {
         )
         .await;
         assert!(result.is_ok());
+
+        // The report shows the minimum of a whole month, not prorated.
+        let minimum_cost = result.unwrap()[0].minimum_cost.unwrap();
+        assert!((minimum_cost * 100.).round() as i64 == 20000);
     }
     #[tokio::test]
     async fn it_should_fail_fetch_project_usage_report_when_user_doesnt_have_permission() {
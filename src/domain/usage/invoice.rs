@@ -0,0 +1,405 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use tracing::{info, warn};
+
+use crate::domain::{error::Error, metadata::MetadataDriven, project::StripeDriven, Result};
+
+use super::{
+    cache::{UsageDrivenCacheBackoffice, UsageDrivenCacheInvoice},
+    UsageReport, UsageReportImpl,
+};
+
+/// Usage of a project in a closed month, as it was sent to the billing provider.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invoice {
+    pub project_id: String,
+    pub period: String,
+    pub billing_provider: String,
+    pub billing_provider_id: String,
+    /// Amount in cents.
+    pub amount: i64,
+    /// Invoice item on the billing provider, missing when there was nothing to charge.
+    pub invoice_item_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Sends the usage of every project in the `%Y-%m` period to stripe, as an item of the next
+/// invoice of its customer, and records it in the ledger. The projects already in the ledger are
+/// skipped, so a failed run can be started again.
+pub async fn apply_invoice(
+    usage_cache: Arc<dyn UsageDrivenCacheBackoffice>,
+    invoice_cache: Arc<dyn UsageDrivenCacheInvoice>,
+    stripe: Arc<dyn StripeDriven>,
+    metadata: Arc<dyn MetadataDriven>,
+    period: &str,
+    dry_run: bool,
+) -> Result<Vec<Invoice>> {
+    let Ok(first_day) = NaiveDate::parse_from_str(&format!("{period}-01"), "%Y-%m-%d") else {
+        return Err(Error::CommandMalformed(format!(
+            "invalid period {period}, the format is YYYY-MM"
+        )));
+    };
+    let current_month = Utc::now().date_naive().with_day(1).unwrap();
+    if first_day >= current_month {
+        return Err(Error::CommandMalformed(format!(
+            "period {period} isn't closed yet"
+        )));
+    }
+    // The ledger is keyed by the period, `2024-9` and `2024-09` are the same month.
+    let period = first_day.format("%Y-%m").to_string();
+    let period = period.as_str();
+
+    // The minimum is prorated over the invoiced month, not the current one.
+    let month_interval = ((first_day + Months::new(1)) - first_day).num_seconds() as f64;
+
+    // A project has a report in each cluster where its resources ran.
+    let mut reports: BTreeMap<String, Vec<UsageReport>> = BTreeMap::new();
+    for cluster in usage_cache.find_clusters(period).await? {
+        let report = usage_cache
+            .find_report_aggregated(period, &cluster)
+            .await?
+            .calculate_cost(metadata.clone(), false);
+
+        for mut usage in report {
+            usage.minimum_cost = usage.minimum_cost.map(|minimum| {
+                let value = (minimum / month_interval) * (usage.interval as f64);
+                (value * 100.0).round() / 100.0
+            });
+            reports
+                .entry(usage.project_id.clone())
+                .or_default()
+                .push(usage);
+        }
+    }
+
+    let mut invoices = Vec::new();
+    for (project_id, report) in reports {
+        if invoice_cache
+            .find_invoice(&project_id, period)
+            .await?
+            .is_some()
+        {
+            info!(project = project_id, period, "project already invoiced");
+            continue;
+        }
+
+        let usage = &report[0];
+        if usage.project_billing_provider != "stripe" {
+            warn!(
+                project = project_id,
+                billing_provider = usage.project_billing_provider,
+                "billing provider not supported"
+            );
+            continue;
+        }
+
+        let mut invoice = Invoice {
+            project_id: project_id.clone(),
+            period: period.into(),
+            billing_provider: usage.project_billing_provider.clone(),
+            billing_provider_id: usage.project_billing_provider_id.clone(),
+            amount: amount(&report),
+            invoice_item_id: None,
+            created_at: Utc::now(),
+        };
+
+        if dry_run {
+            info!("invoice to create: {:?}", invoice);
+            invoices.push(invoice);
+            continue;
+        }
+
+        if invoice.amount > 0 {
+            let description = format!("Usage of {} in {period}", usage.project_namespace);
+            let idempotency_key = format!("invoice-{project_id}-{period}");
+            let invoice_item_id = stripe
+                .create_invoice_item(
+                    &invoice.billing_provider_id,
+                    invoice.amount,
+                    &description,
+                    &idempotency_key,
+                )
+                .await?;
+            invoice.invoice_item_id = Some(invoice_item_id);
+        }
+
+        invoice_cache.create_invoice(&invoice).await?;
+        info!(
+            project = project_id,
+            period,
+            amount = invoice.amount,
+            "project invoiced"
+        );
+        invoices.push(invoice);
+    }
+
+    Ok(invoices)
+}
+
+/// Total in cents, each resource and tier is charged its units or the minimum of the time it ran,
+/// whichever is higher.
+fn amount(report: &[UsageReport]) -> i64 {
+    let total: f64 = report
+        .iter()
+        .map(|usage| {
+            usage
+                .units_cost
+                .unwrap_or(0.)
+                .max(usage.minimum_cost.unwrap_or(0.))
+        })
+        .sum();
+
+    (total * 100.).round() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{
+        metadata::{MockMetadataDriven, ResourceMetadata},
+        project::MockStripeDriven,
+        usage::cache::{MockUsageDrivenCacheBackoffice, MockUsageDrivenCacheInvoice},
+    };
+
+    use super::*;
+
+    impl Default for Invoice {
+        fn default() -> Self {
+            Self {
+                project_id: "project id".into(),
+                period: "2024-08".into(),
+                billing_provider: "stripe".into(),
+                billing_provider_id: "stripe id".into(),
+                amount: 3600,
+                invoice_item_id: Some("ii_123".into()),
+                created_at: Utc::now(),
+            }
+        }
+    }
+
+    fn metadata() -> MockMetadataDriven {
+        let mut metadata = MockMetadataDriven::new();
+        metadata
+            .expect_find_by_kind()
+            .returning(|_| Ok(Some(ResourceMetadata::default())));
+        metadata
+    }
+
+    #[tokio::test]
+    async fn it_should_invoice_the_usage_of_each_project() {
+        let mut usage_cache = MockUsageDrivenCacheBackoffice::new();
+        usage_cache
+            .expect_find_clusters()
+            .return_once(|_| Ok(vec!["cluster a".into(), "cluster b".into()]));
+        usage_cache
+            .expect_find_report_aggregated()
+            .returning(|_, cluster| {
+                let mut reports = vec![UsageReport {
+                    project_id: "project a".into(),
+                    ..Default::default()
+                }];
+                if cluster == "cluster a" {
+                    reports.push(UsageReport {
+                        project_id: "project b".into(),
+                        ..Default::default()
+                    });
+                }
+                Ok(reports)
+            });
+
+        let mut invoice_cache = MockUsageDrivenCacheInvoice::new();
+        invoice_cache
+            .expect_find_invoice()
+            .returning(|_, _| Ok(None));
+        invoice_cache
+            .expect_create_invoice()
+            .times(2)
+            .returning(|_| Ok(()));
+
+        let mut stripe = MockStripeDriven::new();
+        stripe
+            .expect_create_invoice_item()
+            .times(2)
+            .returning(|_, _, _, _| Ok("ii_123".into()));
+
+        let result = apply_invoice(
+            Arc::new(usage_cache),
+            Arc::new(invoice_cache),
+            Arc::new(stripe),
+            Arc::new(metadata()),
+            "2024-08",
+            false,
+        )
+        .await;
+        assert!(result.is_ok());
+
+        let invoices = result.unwrap();
+        assert!(invoices.len() == 2);
+        // 120 units at 0.3 in each cluster, over the minimum of one minute.
+        assert!(invoices[0].project_id == "project a" && invoices[0].amount == 7200);
+        assert!(invoices[1].project_id == "project b" && invoices[1].amount == 3600);
+    }
+
+    #[tokio::test]
+    async fn it_should_prorate_the_minimum_cost_over_the_invoiced_month() {
+        let mut usage_cache = MockUsageDrivenCacheBackoffice::new();
+        usage_cache
+            .expect_find_clusters()
+            .return_once(|_| Ok(vec!["cluster a".into()]));
+        usage_cache
+            .expect_find_report_aggregated()
+            .return_once(|_, _| {
+                // Running the whole of february 2024, 29 days.
+                Ok(vec![UsageReport {
+                    units: 0,
+                    interval: 29 * 24 * 60 * 60,
+                    period: "2024-02".into(),
+                    ..Default::default()
+                }])
+            });
+
+        let mut invoice_cache = MockUsageDrivenCacheInvoice::new();
+        invoice_cache
+            .expect_find_invoice()
+            .return_once(|_, _| Ok(None));
+
+        let result = apply_invoice(
+            Arc::new(usage_cache),
+            Arc::new(invoice_cache),
+            Arc::new(MockStripeDriven::new()),
+            Arc::new(metadata()),
+            "2024-02",
+            true,
+        )
+        .await;
+        assert!(result.is_ok());
+
+        // The whole minimum of the tier.
+        let invoices = result.unwrap();
+        assert!(invoices.len() == 1 && invoices[0].amount == 20000);
+    }
+
+    #[tokio::test]
+    async fn it_should_skip_projects_already_invoiced() {
+        let mut usage_cache = MockUsageDrivenCacheBackoffice::new();
+        usage_cache
+            .expect_find_clusters()
+            .return_once(|_| Ok(vec!["cluster a".into()]));
+        usage_cache
+            .expect_find_report_aggregated()
+            .return_once(|_, _| Ok(vec![UsageReport::default()]));
+
+        let mut invoice_cache = MockUsageDrivenCacheInvoice::new();
+        invoice_cache
+            .expect_find_invoice()
+            .return_once(|_, _| Ok(Some(Invoice::default())));
+
+        // No stripe expectation: the mock panics if an invoice item is created.
+        let stripe = MockStripeDriven::new();
+
+        let result = apply_invoice(
+            Arc::new(usage_cache),
+            Arc::new(invoice_cache),
+            Arc::new(stripe),
+            Arc::new(metadata()),
+            "2024-08",
+            false,
+        )
+        .await;
+        assert!(result.is_ok());
+        assert!(result.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn it_should_not_invoice_in_dry_run() {
+        let mut usage_cache = MockUsageDrivenCacheBackoffice::new();
+        usage_cache
+            .expect_find_clusters()
+            .return_once(|_| Ok(vec!["cluster a".into()]));
+        usage_cache
+            .expect_find_report_aggregated()
+            .return_once(|_, _| Ok(vec![UsageReport::default()]));
+
+        let mut invoice_cache = MockUsageDrivenCacheInvoice::new();
+        invoice_cache
+            .expect_find_invoice()
+            .return_once(|_, _| Ok(None));
+
+        let stripe = MockStripeDriven::new();
+
+        let result = apply_invoice(
+            Arc::new(usage_cache),
+            Arc::new(invoice_cache),
+            Arc::new(stripe),
+            Arc::new(metadata()),
+            "2024-08",
+            true,
+        )
+        .await;
+        assert!(result.is_ok());
+        assert!(result.unwrap().len() == 1);
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_when_period_is_not_closed() {
+        let period = Utc::now().format("%Y-%m").to_string();
+
+        let result = apply_invoice(
+            Arc::new(MockUsageDrivenCacheBackoffice::new()),
+            Arc::new(MockUsageDrivenCacheInvoice::new()),
+            Arc::new(MockStripeDriven::new()),
+            Arc::new(MockMetadataDriven::new()),
+            &period,
+            false,
+        )
+        .await;
+        assert!(matches!(result, Err(Error::CommandMalformed(_))));
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_when_period_is_in_the_future() {
+        let period = (Utc::now().date_naive() + Months::new(12))
+            .format("%Y-%-m")
+            .to_string();
+
+        let result = apply_invoice(
+            Arc::new(MockUsageDrivenCacheBackoffice::new()),
+            Arc::new(MockUsageDrivenCacheInvoice::new()),
+            Arc::new(MockStripeDriven::new()),
+            Arc::new(MockMetadataDriven::new()),
+            &period,
+            false,
+        )
+        .await;
+        assert!(matches!(result, Err(Error::CommandMalformed(_))));
+    }
+
+    #[tokio::test]
+    async fn it_should_record_the_period_with_two_digits_month() {
+        let mut usage_cache = MockUsageDrivenCacheBackoffice::new();
+        usage_cache
+            .expect_find_clusters()
+            .withf(|period| period == "2024-08")
+            .return_once(|_| Ok(vec!["cluster a".into()]));
+        usage_cache
+            .expect_find_report_aggregated()
+            .return_once(|_, _| Ok(vec![UsageReport::default()]));
+
+        let mut invoice_cache = MockUsageDrivenCacheInvoice::new();
+        invoice_cache
+            .expect_find_invoice()
+            .withf(|_, period| period == "2024-08")
+            .return_once(|_, _| Ok(Some(Invoice::default())));
+
+        let result = apply_invoice(
+            Arc::new(usage_cache),
+            Arc::new(invoice_cache),
+            Arc::new(MockStripeDriven::new()),
+            Arc::new(metadata()),
+            "2024-8",
+            false,
+        )
+        .await;
+        assert!(result.is_ok_and(|invoices| invoices.is_empty()));
+    }
+}
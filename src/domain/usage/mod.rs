@@ -9,6 +9,7 @@ use super::{event::UsageCreated, metadata::MetadataDriven};
 pub mod cache;
 pub mod cluster;
 pub mod command;
+pub mod invoice;
 
 pub struct Usage {
     pub id: String,
@@ -144,7 +145,10 @@ pub struct UsageResourceUnit {
 mod tests {
     use uuid::Uuid;
 
-    use crate::domain::utils;
+    use crate::domain::{
+        metadata::{MockMetadataDriven, ResourceMetadata},
+        utils,
+    };
 
     use super::*;
 
@@ -200,4 +204,41 @@ mod tests {
             }
         }
     }
+
+    fn metadata() -> MockMetadataDriven {
+        let mut metadata = MockMetadataDriven::new();
+        metadata
+            .expect_find_by_kind()
+            .returning(|_| Ok(Some(ResourceMetadata::default())));
+        metadata
+    }
+
+    fn cents(value: Option<f64>) -> Option<i64> {
+        value.map(|value| (value * 100.).round() as i64)
+    }
+
+    #[test]
+    fn it_should_calculate_the_full_minimum_cost() {
+        let report = vec![UsageReport::default()].calculate_cost(Arc::new(metadata()), false);
+
+        // 120 units at 0.3 and the minimum of the tier, whatever the interval.
+        assert!(cents(report[0].units_cost) == Some(3600));
+        assert!(cents(report[0].minimum_cost) == Some(20000));
+    }
+
+    #[test]
+    fn it_should_prorate_the_minimum_cost_over_the_current_month() {
+        let now = Utc::now();
+        let first_day = chrono::NaiveDate::from_ymd_opt(now.year(), now.month(), 1).unwrap();
+        let next_month = first_day + chrono::Months::new(1);
+
+        let report = vec![UsageReport {
+            interval: (next_month - first_day).num_seconds(),
+            ..Default::default()
+        }]
+        .calculate_cost(Arc::new(metadata()), true);
+
+        assert!(cents(report[0].units_cost) == Some(3600));
+        assert!(cents(report[0].minimum_cost) == Some(20000));
+    }
 }
@@ -1,11 +1,14 @@
 use std::collections::BTreeMap;
 
+use chrono::{DateTime, Duration, Utc};
 use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::{
     CustomResourceDefinition, JSONSchemaProps,
 };
 use rand::distributions::Alphanumeric;
 use rand::Rng;
 
+use super::{error::Error, Result, RESTORE_GRACE_DAYS};
+
 const ADJECTIVES: &str = include_str!("adjectives");
 const NOUNS: &str = include_str!("nouns");
 
@@ -44,3 +47,13 @@ pub fn get_schema_from_crd(
 pub fn cluster_namespace(namespace: &str) -> String {
     format!("prj-{namespace}")
 }
+
+pub fn assert_restorable(deleted_at: &DateTime<Utc>) -> Result<()> {
+    if Utc::now() - *deleted_at > Duration::days(RESTORE_GRACE_DAYS) {
+        return Err(Error::CommandMalformed(format!(
+            "deleted more than {RESTORE_GRACE_DAYS} days ago, it can't be restored anymore"
+        )));
+    }
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use crate::domain::{
-    auth::{assert_permission, Credential},
+    auth::{assert_permission, Credential, Permission},
     error::Error,
     project::cache::ProjectDrivenCache,
     resource::cache::ResourceDrivenCache,
@@ -24,7 +24,7 @@ pub async fn fetch(
         project_cache.clone(),
         &cmd.credential,
         &resource.project_id,
-        None,
+        Permission::ResourceRead,
     )
     .await?;
 
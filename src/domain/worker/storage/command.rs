@@ -1,11 +1,12 @@
 use std::sync::Arc;
 
 use crate::domain::{
-    auth::{assert_permission, Credential},
+    auth::{assert_permission, Credential, Permission},
     error::Error,
+    pagination::{Cursor, Page, Paged},
     project::cache::ProjectDrivenCache,
     resource::cache::ResourceDrivenCache,
-    Result, PAGE_SIZE_DEFAULT, PAGE_SIZE_MAX,
+    Result,
 };
 
 use super::{KeyValue, WorkerKeyValueDrivenStorage};
@@ -15,7 +16,7 @@ pub async fn fetch(
     resource_cache: Arc<dyn ResourceDrivenCache>,
     key_value_storage: Arc<dyn WorkerKeyValueDrivenStorage>,
     cmd: FetchCmd,
-) -> Result<(i64, Vec<KeyValue>)> {
+) -> Result<(Option<i64>, Paged<KeyValue>)> {
     let Some(resource) = resource_cache.find_by_id(&cmd.worker_id).await? else {
         return Err(Error::CommandMalformed("invalid resource id".into()));
     };
@@ -24,15 +25,15 @@ pub async fn fetch(
         project_cache.clone(),
         &cmd.credential,
         &resource.project_id,
-        None,
+        Permission::ResourceRead,
     )
     .await?;
 
     let (count, values) = key_value_storage
-        .find(&resource.name, cmd.key, &cmd.page, &cmd.page_size)
+        .find(&resource.name, cmd.key, &cmd.page)
         .await?;
 
-    Ok((count, values))
+    Ok((count, cmd.page.paged(values, |v| Cursor::new(None, &v.key))))
 }
 
 pub async fn update(
@@ -49,7 +50,7 @@ pub async fn update(
         project_cache.clone(),
         &cmd.credential,
         &resource.project_id,
-        None,
+        Permission::ResourceUpdate,
     )
     .await?;
 
@@ -72,7 +73,7 @@ pub async fn delete(
         project_cache.clone(),
         &cmd.credential,
         &resource.project_id,
-        None,
+        Permission::ResourceUpdate,
     )
     .await?;
 
@@ -86,8 +87,7 @@ pub struct FetchCmd {
     pub credential: Credential,
     pub worker_id: String,
     pub key: Option<String>,
-    pub page: u32,
-    pub page_size: u32,
+    pub page: Page,
 }
 impl FetchCmd {
     pub fn new(
@@ -96,22 +96,13 @@ impl FetchCmd {
         key: Option<String>,
         page: Option<u32>,
         page_size: Option<u32>,
+        cursor: Option<String>,
     ) -> Result<Self> {
-        let page = page.unwrap_or(1);
-        let page_size = page_size.unwrap_or(PAGE_SIZE_DEFAULT);
-
-        if page_size >= PAGE_SIZE_MAX {
-            return Err(Error::CommandMalformed(format!(
-                "page_size exceeded the limit of {PAGE_SIZE_MAX}"
-            )));
-        }
-
         Ok(Self {
             credential,
             worker_id,
             key,
-            page,
-            page_size,
+            page: Page::new(page, page_size, cursor)?,
         })
     }
 }
@@ -164,8 +155,7 @@ mod fetch_tests {
                 credential: Credential::Auth0("user id".into()),
                 worker_id: Uuid::new_v4().to_string(),
                 key: None,
-                page: 1,
-                page_size: 12,
+                page: Page::default(),
             }
         }
     }
@@ -185,7 +175,7 @@ mod fetch_tests {
         let mut storage = MockWorkerKeyValueDrivenStorage::new();
         storage
             .expect_find()
-            .return_once(|_, _, _, _| Ok((1, vec![KeyValue::default()])));
+            .return_once(|_, _, _| Ok((Some(1), vec![KeyValue::default()])));
 
         let cmd = FetchCmd::default();
 
@@ -1,4 +1,4 @@
-use crate::domain::Result;
+use crate::domain::{pagination::Page, Result};
 
 pub mod command;
 
@@ -9,9 +9,8 @@ pub trait WorkerKeyValueDrivenStorage: Send + Sync {
         &self,
         worker_id: &str,
         key: Option<String>,
-        page: &u32,
-        page_size: &u32,
-    ) -> Result<(i64, Vec<KeyValue>)>;
+        page: &Page,
+    ) -> Result<(Option<i64>, Vec<KeyValue>)>;
     async fn update(&self, worker_id: &str, key_value: &KeyValue) -> Result<KeyValue>;
     async fn delete(&self, worker_id: &str, key: &str) -> Result<()>;
 }
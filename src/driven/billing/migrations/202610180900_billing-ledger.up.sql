@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS billing_ledger (
+  project_id TEXT NOT NULL,
+  period TEXT NOT NULL,
+  billing_provider TEXT NOT NULL,
+  billing_provider_id TEXT NOT NULL,
+  amount INTEGER NOT NULL,
+  invoice_item_id TEXT NULL,
+  created_at DATETIME NOT NULL,
+  PRIMARY KEY (project_id, period)
+);
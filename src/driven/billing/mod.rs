@@ -0,0 +1,133 @@
+use anyhow::Result as AnyhowResult;
+use sqlx::{sqlite::SqliteRow, FromRow, Row};
+use std::path::Path;
+
+use crate::domain::{
+    usage::{cache::UsageDrivenCacheInvoice, invoice::Invoice},
+    Result,
+};
+
+/// Ledger of the invoices sent to the billing provider, in a sqlite file of its own. The cache
+/// is a projection that a rebuild replaces, while the ledger is the only record of what was
+/// already charged, so it can't live in it.
+pub struct SqliteBillingLedger {
+    db: sqlx::sqlite::SqlitePool,
+}
+
+impl SqliteBillingLedger {
+    pub async fn new(path: &Path) -> AnyhowResult<Self> {
+        let url = format!("sqlite:{}?mode=rwc", path.display());
+        let db = sqlx::sqlite::SqlitePoolOptions::new().connect(&url).await?;
+
+        Ok(Self { db })
+    }
+
+    pub async fn migrate(&self) -> AnyhowResult<()> {
+        sqlx::migrate!("src/driven/billing/migrations")
+            .run(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub async fn ephemeral() -> AnyhowResult<Self> {
+        let db = sqlx::sqlite::SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await?;
+
+        let ledger = Self { db };
+        ledger.migrate().await?;
+
+        Ok(ledger)
+    }
+}
+
+#[async_trait::async_trait]
+impl UsageDrivenCacheInvoice for SqliteBillingLedger {
+    async fn find_invoice(&self, project_id: &str, period: &str) -> Result<Option<Invoice>> {
+        let invoice = sqlx::query_as::<_, Invoice>(
+            r#"
+                SELECT
+                    project_id,
+                    period,
+                    billing_provider,
+                    billing_provider_id,
+                    amount,
+                    invoice_item_id,
+                    created_at
+                FROM billing_ledger
+                WHERE project_id = $1 AND period = $2;
+            "#,
+        )
+        .bind(project_id)
+        .bind(period)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(invoice)
+    }
+
+    async fn create_invoice(&self, invoice: &Invoice) -> Result<()> {
+        sqlx::query(
+            r#"
+                INSERT INTO billing_ledger (
+                    project_id,
+                    period,
+                    billing_provider,
+                    billing_provider_id,
+                    amount,
+                    invoice_item_id,
+                    created_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7);
+            "#,
+        )
+        .bind(&invoice.project_id)
+        .bind(&invoice.period)
+        .bind(&invoice.billing_provider)
+        .bind(&invoice.billing_provider_id)
+        .bind(invoice.amount)
+        .bind(&invoice.invoice_item_id)
+        .bind(invoice.created_at)
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Invoice {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            project_id: row.try_get("project_id")?,
+            period: row.try_get("period")?,
+            billing_provider: row.try_get("billing_provider")?,
+            billing_provider_id: row.try_get("billing_provider_id")?,
+            amount: row.try_get("amount")?,
+            invoice_item_id: row.try_get("invoice_item_id")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn it_should_record_an_invoice_once_per_period() {
+        let ledger = SqliteBillingLedger::ephemeral().await.unwrap();
+
+        let invoice = Invoice::default();
+        ledger.create_invoice(&invoice).await.unwrap();
+
+        let result = ledger.find_invoice("project id", "2024-08").await.unwrap();
+        assert!(result.is_some_and(|i| i == invoice));
+        let result = ledger.find_invoice("project id", "2024-09").await.unwrap();
+        assert!(result.is_none());
+
+        let result = ledger.create_invoice(&invoice).await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,341 @@
+use anyhow::Result as AnyhowResult;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::Duration,
+};
+use tokio::time::sleep;
+
+use crate::domain::{
+    error::Error,
+    event::{Event, EventDrivenBridge, EventEnvelope, EventOffset},
+    snapshot::{ProjectSnapshot, SnapshotDrivenBridge},
+    Result,
+};
+
+use super::{BusMessage, Ends, EventConsumer, EventProducer};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A line of the topic file, the payload is kept as json so the file can be read by hand.
+#[derive(Debug, Serialize, Deserialize)]
+struct Line {
+    key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    event_id: Option<String>,
+    payload: Value,
+}
+
+fn topic_path(dir: &Path, topic: &str) -> PathBuf {
+    dir.join(format!("{topic}.jsonl"))
+}
+
+fn committed_path(dir: &Path, group: &str, topic: &str) -> PathBuf {
+    dir.join(format!("{topic}.{group}.offset"))
+}
+
+/// Appends events to `<dir>/<topic>.jsonl`, one json per line. The offset of an event is its
+/// line number, starting at zero.
+pub struct FileProducer {
+    path: PathBuf,
+    // Serializes the writes of this process, lines from other processes are kept whole by the
+    // append mode as each line is written at once.
+    file: Mutex<File>,
+}
+impl FileProducer {
+    pub fn new(dir: &Path, topic: &str) -> AnyhowResult<Self> {
+        fs::create_dir_all(dir)?;
+        let path = topic_path(dir, topic);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    fn write(&self, line: &Line) -> Result<()> {
+        let mut data = serde_json::to_vec(line)?;
+        data.push(b'\n');
+
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|err| Error::Unexpected(err.to_string()))?;
+        file.write_all(&data)
+            .and_then(|_| file.flush())
+            .map_err(|err| {
+                Error::Unexpected(format!("fail to write {}: {err}", self.path.display()))
+            })?;
+
+        Ok(())
+    }
+}
+#[async_trait::async_trait]
+impl EventProducer for FileProducer {
+    /// The line number isn't known without reading the whole file, so no offset is returned.
+    async fn publish(&self, envelope: &EventEnvelope) -> Result<Option<EventOffset>> {
+        self.write(&Line {
+            key: envelope.key(),
+            event_id: envelope.event_id.clone(),
+            payload: serde_json::from_slice(&envelope.to_payload()?)?,
+        })?;
+
+        Ok(None)
+    }
+}
+#[async_trait::async_trait]
+impl EventDrivenBridge for FileProducer {
+    async fn dispatch(&self, event: Event) -> Result<Option<EventOffset>> {
+        EventProducer::publish(self, &EventEnvelope::new(event)).await
+    }
+}
+#[async_trait::async_trait]
+impl SnapshotDrivenBridge for FileProducer {
+    async fn publish(&self, snapshot: &ProjectSnapshot) -> Result<()> {
+        self.write(&Line {
+            key: snapshot.project.id.clone(),
+            event_id: None,
+            payload: serde_json::to_value(snapshot)?,
+        })
+    }
+}
+
+/// Read position in a topic file, `bytes` is where the line with `offset` starts.
+#[derive(Debug, Clone, Copy, Default)]
+struct Position {
+    offset: i64,
+    bytes: u64,
+}
+
+/// Tails the topic files, the committed offsets of a group are stored next to them in
+/// `<dir>/<topic>.<group>.offset`.
+pub struct FileConsumer {
+    dir: PathBuf,
+    group: Option<String>,
+    positions: Mutex<HashMap<String, Position>>,
+}
+impl FileConsumer {
+    pub fn subscribe(dir: &Path, group: &str, topics: &[String]) -> AnyhowResult<Self> {
+        fs::create_dir_all(dir)?;
+
+        let mut positions = HashMap::new();
+        for topic in topics {
+            let offset = match fs::read_to_string(committed_path(dir, group, topic)) {
+                Ok(committed) => committed.trim().parse()?,
+                Err(_) => 0,
+            };
+            positions.insert(topic.clone(), locate(dir, topic, offset)?);
+        }
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            group: Some(group.to_string()),
+            positions: Mutex::new(positions),
+        })
+    }
+
+    pub fn replay(dir: &Path, topics: &[String]) -> AnyhowResult<(Self, Ends)> {
+        let mut ends = HashMap::new();
+        for topic in topics {
+            let end = locate(dir, topic, i64::MAX)?.offset;
+            if end > 0 {
+                ends.insert((topic.clone(), 0), end);
+            }
+        }
+
+        let consumer = Self {
+            dir: dir.to_path_buf(),
+            group: None,
+            positions: Mutex::new(
+                topics
+                    .iter()
+                    .map(|topic| (topic.clone(), Position::default()))
+                    .collect(),
+            ),
+        };
+
+        Ok((consumer, ends))
+    }
+
+    fn next(&self) -> Result<Option<BusMessage>> {
+        let mut positions = self
+            .positions
+            .lock()
+            .map_err(|err| Error::Unexpected(err.to_string()))?;
+
+        for (topic, position) in positions.iter_mut() {
+            let Some((line, len)) = read_line(&topic_path(&self.dir, topic), position.bytes)?
+            else {
+                continue;
+            };
+
+            let message = BusMessage {
+                topic: topic.clone(),
+                partition: 0,
+                offset: position.offset,
+                key: Some(line.key.into_bytes()),
+                payload: Some(serde_json::to_vec(&line.payload)?),
+                event_id: line.event_id,
+                encoding: None,
+            };
+            position.offset += 1;
+            position.bytes += len;
+
+            return Ok(Some(message));
+        }
+
+        Ok(None)
+    }
+}
+#[async_trait::async_trait]
+impl EventConsumer for FileConsumer {
+    async fn recv(&self) -> Result<Option<BusMessage>> {
+        loop {
+            if let Some(message) = self.next()? {
+                return Ok(Some(message));
+            }
+            sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    fn commit(&self, message: &BusMessage) -> Result<()> {
+        if let Some(group) = &self.group {
+            let path = committed_path(&self.dir, group, &message.topic);
+            fs::write(&path, (message.offset + 1).to_string()).map_err(|err| {
+                Error::Unexpected(format!("fail to write {}: {err}", path.display()))
+            })?;
+        }
+        Ok(())
+    }
+
+    fn seek(&self, topic: &str, _partition: i32, offset: i64) -> Result<()> {
+        let position =
+            locate(&self.dir, topic, offset).map_err(|err| Error::Unexpected(err.to_string()))?;
+
+        self.positions
+            .lock()
+            .map_err(|err| Error::Unexpected(err.to_string()))?
+            .insert(topic.to_string(), position);
+        Ok(())
+    }
+}
+
+/// Finds where the line `offset` starts, or the end of the complete lines when the file is
+/// shorter.
+fn locate(dir: &Path, topic: &str, offset: i64) -> AnyhowResult<Position> {
+    let mut position = Position::default();
+
+    let Ok(file) = File::open(topic_path(dir, topic)) else {
+        return Ok(position);
+    };
+    let mut reader = BufReader::new(file);
+    let mut buffer = Vec::new();
+    while position.offset < offset {
+        buffer.clear();
+        let len = reader.read_until(b'\n', &mut buffer)?;
+        if len == 0 || !buffer.ends_with(b"\n") {
+            break;
+        }
+        position.offset += 1;
+        position.bytes += len as u64;
+    }
+
+    Ok(position)
+}
+
+/// Reads the line starting at `bytes`. A line still being written, without the trailing new
+/// line, is left for the next read.
+fn read_line(path: &Path, bytes: u64) -> Result<Option<(Line, u64)>> {
+    let Ok(mut file) = File::open(path) else {
+        return Ok(None);
+    };
+    file.seek(SeekFrom::Start(bytes))
+        .map_err(|err| Error::Unexpected(err.to_string()))?;
+
+    let mut buffer = Vec::new();
+    let len = BufReader::new(file)
+        .read_until(b'\n', &mut buffer)
+        .map_err(|err| Error::Unexpected(err.to_string()))?;
+    if len == 0 || !buffer.ends_with(b"\n") {
+        return Ok(None);
+    }
+
+    Ok(Some((serde_json::from_slice(&buffer)?, len as u64)))
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::domain::event::ProjectCreated;
+
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("fabric-bus-{}", Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn it_should_read_published_events_from_the_file() {
+        let dir = temp_dir();
+        let producer = FileProducer::new(&dir, "events").unwrap();
+
+        let project = ProjectCreated::default();
+        producer.dispatch(project.clone().into()).await.unwrap();
+
+        let consumer = FileConsumer::subscribe(&dir, "cache", &["events".into()]).unwrap();
+        let message = consumer.recv().await.unwrap().unwrap();
+        assert!(message.offset == 0);
+
+        let envelope: EventEnvelope = (&message).try_into().unwrap();
+        match envelope.event {
+            Event::ProjectCreated(evt) => assert!(evt.id == project.id),
+            _ => unreachable!(),
+        }
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_should_resume_after_the_committed_offset() {
+        let dir = temp_dir();
+        let producer = FileProducer::new(&dir, "events").unwrap();
+        for _ in 0..3 {
+            producer
+                .dispatch(ProjectCreated::default().into())
+                .await
+                .unwrap();
+        }
+
+        let consumer = FileConsumer::subscribe(&dir, "cache", &["events".into()]).unwrap();
+        let message = consumer.recv().await.unwrap().unwrap();
+        consumer.commit(&message).unwrap();
+
+        let consumer = FileConsumer::subscribe(&dir, "cache", &["events".into()]).unwrap();
+        let message = consumer.recv().await.unwrap().unwrap();
+        assert!(message.offset == 1);
+
+        let (_, ends) = FileConsumer::replay(&dir, &["events".into()]).unwrap();
+        assert!(ends.get(&("events".into(), 0)) == Some(&3));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn it_should_skip_a_partial_line() {
+        let dir = temp_dir();
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(topic_path(&dir, "events"), "{\"key\":").unwrap();
+
+        assert!(read_line(&topic_path(&dir, "events"), 0).unwrap().is_none());
+        assert!(locate(&dir, "events", 1).unwrap().offset == 0);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
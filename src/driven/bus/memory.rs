@@ -0,0 +1,276 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+use tokio::sync::Notify;
+
+use crate::domain::{
+    event::{Event, EventDrivenBridge, EventEnvelope, EventOffset},
+    snapshot::{ProjectSnapshot, SnapshotDrivenBridge},
+    Result,
+};
+
+use super::{BusMessage, Ends, EventConsumer, EventProducer};
+
+/// An in-process log, every topic has a single partition and lives as long as the process.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBus {
+    inner: Arc<MemoryBusInner>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryBusInner {
+    topics: Mutex<HashMap<String, Vec<BusMessage>>>,
+    committed: Mutex<HashMap<(String, String), i64>>,
+    notify: Notify,
+}
+
+impl MemoryBus {
+    fn append(
+        &self,
+        topic: &str,
+        key: String,
+        payload: Vec<u8>,
+        event_id: Option<String>,
+    ) -> Result<i64> {
+        let mut topics = lock(&self.inner.topics);
+        let messages = topics.entry(topic.to_string()).or_default();
+        let offset = messages.len() as i64;
+        messages.push(BusMessage {
+            topic: topic.to_string(),
+            partition: 0,
+            offset,
+            key: Some(key.into_bytes()),
+            payload: Some(payload),
+            event_id,
+            encoding: None,
+        });
+        drop(topics);
+
+        self.inner.notify.notify_waiters();
+        Ok(offset)
+    }
+
+    fn get(&self, topic: &str, offset: i64) -> Option<BusMessage> {
+        lock(&self.inner.topics)
+            .get(topic)
+            .and_then(|messages| messages.get(offset as usize))
+            .cloned()
+    }
+}
+
+// The guarded maps are only read and appended, a panic while holding the lock can't leave them
+// half updated.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+pub struct MemoryProducer {
+    bus: MemoryBus,
+    topic: String,
+}
+impl MemoryProducer {
+    pub fn new(bus: &MemoryBus, topic: &str) -> Self {
+        Self {
+            bus: bus.clone(),
+            topic: topic.to_string(),
+        }
+    }
+}
+#[async_trait::async_trait]
+impl EventProducer for MemoryProducer {
+    async fn publish(&self, envelope: &EventEnvelope) -> Result<Option<EventOffset>> {
+        let offset = self.bus.append(
+            &self.topic,
+            envelope.key(),
+            envelope.to_payload()?,
+            envelope.event_id.clone(),
+        )?;
+
+        Ok(Some(EventOffset {
+            topic: self.topic.clone(),
+            partition: 0,
+            offset,
+        }))
+    }
+}
+#[async_trait::async_trait]
+impl EventDrivenBridge for MemoryProducer {
+    async fn dispatch(&self, event: Event) -> Result<Option<EventOffset>> {
+        EventProducer::publish(self, &EventEnvelope::new(event)).await
+    }
+}
+#[async_trait::async_trait]
+impl SnapshotDrivenBridge for MemoryProducer {
+    async fn publish(&self, snapshot: &ProjectSnapshot) -> Result<()> {
+        self.bus.append(
+            &self.topic,
+            snapshot.project.id.clone(),
+            serde_json::to_vec(snapshot)?,
+            None,
+        )?;
+
+        Ok(())
+    }
+}
+
+pub struct MemoryConsumer {
+    bus: MemoryBus,
+    group: Option<String>,
+    positions: Mutex<HashMap<String, i64>>,
+}
+impl MemoryConsumer {
+    pub fn subscribe(bus: &MemoryBus, group: &str, topics: &[String]) -> Self {
+        let committed = lock(&bus.inner.committed);
+        let positions = topics
+            .iter()
+            .map(|topic| {
+                let key = (group.to_string(), topic.clone());
+                (
+                    topic.clone(),
+                    committed.get(&key).copied().unwrap_or_default(),
+                )
+            })
+            .collect();
+
+        Self {
+            bus: bus.clone(),
+            group: Some(group.to_string()),
+            positions: Mutex::new(positions),
+        }
+    }
+
+    pub fn replay(bus: &MemoryBus, topics: &[String]) -> (Self, Ends) {
+        let stored = lock(&bus.inner.topics);
+        let ends = topics
+            .iter()
+            .filter_map(|topic| {
+                let len = stored.get(topic).map(|m| m.len()).unwrap_or_default();
+                (len > 0).then(|| ((topic.clone(), 0), len as i64))
+            })
+            .collect();
+
+        let consumer = Self {
+            bus: bus.clone(),
+            group: None,
+            positions: Mutex::new(topics.iter().map(|topic| (topic.clone(), 0)).collect()),
+        };
+
+        (consumer, ends)
+    }
+
+    fn next(&self) -> Option<BusMessage> {
+        let mut positions = lock(&self.positions);
+        for (topic, position) in positions.iter_mut() {
+            if let Some(message) = self.bus.get(topic, *position) {
+                *position += 1;
+                return Some(message);
+            }
+        }
+        None
+    }
+}
+#[async_trait::async_trait]
+impl EventConsumer for MemoryConsumer {
+    async fn recv(&self) -> Result<Option<BusMessage>> {
+        loop {
+            // Registered before checking the topics, so a message appended in between still
+            // wakes this consumer.
+            let notified = self.bus.inner.notify.notified();
+            if let Some(message) = self.next() {
+                return Ok(Some(message));
+            }
+            notified.await;
+        }
+    }
+
+    fn commit(&self, message: &BusMessage) -> Result<()> {
+        if let Some(group) = &self.group {
+            lock(&self.bus.inner.committed)
+                .insert((group.clone(), message.topic.clone()), message.offset + 1);
+        }
+        Ok(())
+    }
+
+    fn seek(&self, topic: &str, _partition: i32, offset: i64) -> Result<()> {
+        lock(&self.positions).insert(topic.to_string(), offset);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time::timeout;
+
+    use crate::domain::event::ProjectCreated;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn it_should_deliver_published_events_in_order() {
+        let bus = MemoryBus::default();
+        let producer = MemoryProducer::new(&bus, "events");
+        let consumer = MemoryConsumer::subscribe(&bus, "cache", &["events".into()]);
+
+        let first = ProjectCreated::default();
+        let second = ProjectCreated::default();
+        producer.dispatch(first.clone().into()).await.unwrap();
+        producer.dispatch(second.clone().into()).await.unwrap();
+
+        for (offset, expected) in [first, second].iter().enumerate() {
+            let message = consumer.recv().await.unwrap().unwrap();
+            assert!(message.offset == offset as i64);
+
+            let envelope: EventEnvelope = (&message).try_into().unwrap();
+            match envelope.event {
+                Event::ProjectCreated(evt) => assert!(evt.id == expected.id),
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_resume_after_the_committed_offset() {
+        let bus = MemoryBus::default();
+        let producer = MemoryProducer::new(&bus, "events");
+        producer
+            .dispatch(ProjectCreated::default().into())
+            .await
+            .unwrap();
+        producer
+            .dispatch(ProjectCreated::default().into())
+            .await
+            .unwrap();
+
+        let consumer = MemoryConsumer::subscribe(&bus, "cache", &["events".into()]);
+        let message = consumer.recv().await.unwrap().unwrap();
+        consumer.commit(&message).unwrap();
+
+        let consumer = MemoryConsumer::subscribe(&bus, "cache", &["events".into()]);
+        let message = consumer.recv().await.unwrap().unwrap();
+        assert!(message.offset == 1);
+
+        let other = MemoryConsumer::subscribe(&bus, "monitor", &["events".into()]);
+        let message = other.recv().await.unwrap().unwrap();
+        assert!(message.offset == 0);
+    }
+
+    #[tokio::test]
+    async fn it_should_wait_for_new_events() {
+        let bus = MemoryBus::default();
+        let producer = MemoryProducer::new(&bus, "events");
+        let consumer = MemoryConsumer::subscribe(&bus, "cache", &["events".into()]);
+
+        assert!(timeout(Duration::from_millis(50), consumer.recv())
+            .await
+            .is_err());
+
+        let (received, _) = tokio::join!(
+            timeout(Duration::from_secs(1), consumer.recv()),
+            producer.dispatch(ProjectCreated::default().into())
+        );
+        assert!(received.unwrap().unwrap().is_some());
+    }
+}
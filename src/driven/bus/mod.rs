@@ -0,0 +1,272 @@
+use anyhow::Result as AnyhowResult;
+use serde::Deserialize;
+use std::{collections::HashMap, fmt::Display, path::PathBuf, str::FromStr, sync::Arc};
+
+use crate::domain::{
+    error::Error,
+    event::{EventDrivenBridge, EventEnvelope, EventOffset},
+    snapshot::SnapshotDrivenBridge,
+    Result,
+};
+
+use super::kafka::{KafkaConsumer, KafkaProducer};
+
+pub mod file;
+pub mod memory;
+
+use file::{FileConsumer, FileProducer};
+use memory::{MemoryBus, MemoryConsumer, MemoryProducer};
+
+/// A message read from the bus, detached from the backend that delivered it.
+#[derive(Debug, Clone)]
+pub struct BusMessage {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+    pub key: Option<Vec<u8>>,
+    pub payload: Option<Vec<u8>>,
+    pub event_id: Option<String>,
+    /// Encoding announced by the producer, payloads without it are detected by their content.
+    pub encoding: Option<Encoding>,
+}
+impl TryFrom<&BusMessage> for EventEnvelope {
+    type Error = Error;
+
+    fn try_from(value: &BusMessage) -> std::result::Result<Self, Self::Error> {
+        let Some(key) = &value.key else {
+            return Err(Error::Unexpected("event with empty key".into()));
+        };
+        let key =
+            String::from_utf8(key.to_vec()).map_err(|err| Error::Unexpected(err.to_string()))?;
+
+        let Some(payload) = &value.payload else {
+            return Err(Error::Unexpected("event with empty payload".into()));
+        };
+        match value.encoding {
+            Some(encoding) => encoding.decode(&key, payload),
+            None => EventEnvelope::from_key(&key, payload),
+        }
+    }
+}
+
+/// How the events are serialized in the payload. Json is readable by hand, protobuf is smaller
+/// and has a schema in `proto/` to generate consumers in other languages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Encoding {
+    #[default]
+    Json,
+    Protobuf,
+}
+impl Encoding {
+    pub fn encode(&self, envelope: &EventEnvelope) -> Result<Vec<u8>> {
+        match self {
+            Self::Json => envelope.to_payload(),
+            Self::Protobuf => envelope.to_protobuf(),
+        }
+    }
+
+    pub fn decode(&self, key: &str, payload: &[u8]) -> Result<EventEnvelope> {
+        match self {
+            Self::Json => EventEnvelope::from_json(key, payload),
+            Self::Protobuf => EventEnvelope::from_protobuf(key, payload),
+        }
+    }
+}
+impl Display for Encoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json => write!(f, "json"),
+            Self::Protobuf => write!(f, "protobuf"),
+        }
+    }
+}
+impl FromStr for Encoding {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Self::Json),
+            "protobuf" => Ok(Self::Protobuf),
+            _ => Err(Error::Unexpected(format!("encoding {s} not supported"))),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+pub trait EventProducer: Send + Sync {
+    async fn publish(&self, envelope: &EventEnvelope) -> Result<Option<EventOffset>>;
+}
+
+#[async_trait::async_trait]
+pub trait EventConsumer: Send + Sync {
+    /// Waits for the next message. `None` means the consumer reached the end of the topics and
+    /// was configured to stop there.
+    async fn recv(&self) -> Result<Option<BusMessage>>;
+    /// Stores the message position for the consumer group, the next subscribe starts after it.
+    fn commit(&self, message: &BusMessage) -> Result<()>;
+    fn seek(&self, topic: &str, partition: i32, offset: i64) -> Result<()>;
+}
+
+/// Where events are published and consumed from. Kafka is used in production, the memory bus
+/// lets a single process run without a broker and the file bus shares the topics between
+/// processes on the same machine. Only kafka takes the encoding, the other buses always use
+/// json.
+#[derive(Debug, Clone)]
+pub enum Bus {
+    Kafka {
+        properties: HashMap<String, String>,
+        encoding: Encoding,
+    },
+    Memory(MemoryBus),
+    File {
+        path: PathBuf,
+        group: Option<String>,
+    },
+}
+impl Bus {
+    /// Picks the bus from the `[bus]` config section, kafka is used when the section is missing.
+    pub fn new(
+        config: Option<&BusConfig>,
+        kafka: &HashMap<String, String>,
+        encoding: Encoding,
+        memory: &MemoryBus,
+    ) -> Self {
+        match config {
+            None => Self::Kafka {
+                properties: kafka.clone(),
+                encoding,
+            },
+            Some(BusConfig::Memory) => Self::Memory(memory.clone()),
+            Some(BusConfig::File { path, group }) => Self::File {
+                path: path.clone(),
+                group: group.clone(),
+            },
+        }
+    }
+
+    pub fn bridge(&self, topic: &str) -> AnyhowResult<Arc<dyn EventDrivenBridge>> {
+        let bridge: Arc<dyn EventDrivenBridge> = match self {
+            Self::Kafka {
+                properties,
+                encoding,
+            } => Arc::new(KafkaProducer::new(topic, properties, *encoding)?),
+            Self::Memory(bus) => Arc::new(MemoryProducer::new(bus, topic)),
+            Self::File { path, .. } => Arc::new(FileProducer::new(path, topic)?),
+        };
+        Ok(bridge)
+    }
+
+    /// Snapshots are always published as json, keyed by the project id.
+    pub fn snapshot_bridge(&self, topic: &str) -> AnyhowResult<Arc<dyn SnapshotDrivenBridge>> {
+        let bridge: Arc<dyn SnapshotDrivenBridge> = match self {
+            Self::Kafka { properties, .. } => {
+                Arc::new(KafkaProducer::new(topic, properties, Encoding::Json)?)
+            }
+            Self::Memory(bus) => Arc::new(MemoryProducer::new(bus, topic)),
+            Self::File { path, .. } => Arc::new(FileProducer::new(path, topic)?),
+        };
+        Ok(bridge)
+    }
+
+    pub fn producer(&self, topic: &str) -> AnyhowResult<Arc<dyn EventProducer>> {
+        let producer: Arc<dyn EventProducer> = match self {
+            Self::Kafka {
+                properties,
+                encoding,
+            } => Arc::new(KafkaProducer::new(topic, properties, *encoding)?),
+            Self::Memory(bus) => Arc::new(MemoryProducer::new(bus, topic)),
+            Self::File { path, .. } => Arc::new(FileProducer::new(path, topic)?),
+        };
+        Ok(producer)
+    }
+
+    /// Consumes the topics from the last position committed by the group. Kafka takes the
+    /// group from the `group.id` property instead, the file bus prefixes it with its own group
+    /// so processes sharing the directory keep separate positions.
+    pub fn subscribe(
+        &self,
+        group: &str,
+        topics: &[String],
+    ) -> AnyhowResult<Box<dyn EventConsumer>> {
+        let consumer: Box<dyn EventConsumer> = match self {
+            Self::Kafka { properties, .. } => {
+                Box::new(KafkaConsumer::subscribe(topics, properties)?)
+            }
+            Self::Memory(bus) => Box::new(MemoryConsumer::subscribe(bus, group, topics)),
+            Self::File {
+                path,
+                group: Some(prefix),
+            } => Box::new(FileConsumer::subscribe(
+                path,
+                &format!("{prefix}-{group}"),
+                topics,
+            )?),
+            Self::File { path, group: None } => {
+                Box::new(FileConsumer::subscribe(path, group, topics)?)
+            }
+        };
+        Ok(consumer)
+    }
+
+    /// Consumes the topics from the beginning without a group, together with the end offset of
+    /// every partition that has messages at the moment of the call.
+    pub fn replay(&self, topics: &[String]) -> AnyhowResult<(Box<dyn EventConsumer>, Ends)> {
+        let (consumer, ends): (Box<dyn EventConsumer>, Ends) = match self {
+            Self::Kafka { properties, .. } => {
+                let (consumer, ends) = KafkaConsumer::replay(topics, properties)?;
+                (Box::new(consumer), ends)
+            }
+            Self::Memory(bus) => {
+                let (consumer, ends) = MemoryConsumer::replay(bus, topics);
+                (Box::new(consumer), ends)
+            }
+            Self::File { path, .. } => {
+                let (consumer, ends) = FileConsumer::replay(path, topics)?;
+                (Box::new(consumer), ends)
+            }
+        };
+        Ok((consumer, ends))
+    }
+}
+
+/// End offset (exclusive) by topic and partition.
+pub type Ends = HashMap<(String, i32), i64>;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BusConfig {
+    Memory,
+    File {
+        path: PathBuf,
+        group: Option<String>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::event::{Event, ProjectCreated};
+
+    use super::*;
+
+    #[test]
+    fn it_should_decode_the_message_with_its_encoding() {
+        let envelope = EventEnvelope::new(ProjectCreated::default().into());
+
+        for encoding in [Encoding::Json, Encoding::Protobuf] {
+            let message = BusMessage {
+                topic: "events".into(),
+                partition: 0,
+                offset: 0,
+                key: Some(envelope.key().into_bytes()),
+                payload: Some(encoding.encode(&envelope).unwrap()),
+                event_id: envelope.event_id.clone(),
+                encoding: Some(encoding),
+            };
+
+            let decoded = EventEnvelope::try_from(&message).unwrap();
+            assert!(decoded.event_id == envelope.event_id);
+            assert!(matches!(decoded.event, Event::ProjectCreated(_)));
+        }
+    }
+}
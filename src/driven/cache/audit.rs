@@ -0,0 +1,205 @@
+use sqlx::{sqlite::SqliteRow, FromRow, Row};
+use std::sync::Arc;
+
+use crate::domain::{
+    audit::{cache::AuditDrivenCache, AuditEvent, AuditFilter},
+    Result,
+};
+
+use super::SqliteCache;
+
+pub struct SqliteAuditDrivenCache {
+    sqlite: Arc<SqliteCache>,
+}
+impl SqliteAuditDrivenCache {
+    pub fn new(sqlite: Arc<SqliteCache>) -> Self {
+        Self { sqlite }
+    }
+}
+#[async_trait::async_trait]
+impl AuditDrivenCache for SqliteAuditDrivenCache {
+    async fn find(
+        &self,
+        filter: &AuditFilter,
+        page: &u32,
+        page_size: &u32,
+    ) -> Result<Vec<AuditEvent>> {
+        let offset = page_size * (page - 1);
+
+        let events = sqlx::query_as::<_, AuditEvent>(
+            r#"
+                SELECT
+                    id,
+                    event_type,
+                    project_id,
+                    resource_id,
+                    actor,
+                    payload,
+                    occurred_at
+                FROM
+                    audit_event
+                WHERE
+                    ($1 IS NULL OR project_id = $1)
+                    AND ($2 IS NULL OR resource_id = $2)
+                    AND ($3 IS NULL OR actor = $3)
+                    AND ($4 IS NULL OR event_type = $4)
+                    AND ($5 IS NULL OR occurred_at >= $5)
+                    AND ($6 IS NULL OR occurred_at <= $6)
+                ORDER BY
+                    occurred_at DESC
+                LIMIT $7
+                OFFSET $8;
+            "#,
+        )
+        .bind(&filter.project_id)
+        .bind(&filter.resource_id)
+        .bind(&filter.actor)
+        .bind(&filter.event_type)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(page_size)
+        .bind(offset)
+        .fetch_all(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(events)
+    }
+
+    // Events are recorded before they are applied to the projection, so a deleted secret can
+    // still be traced to its project, the event only carries the secret id.
+    async fn create(&self, event: &AuditEvent) -> Result<()> {
+        sqlx::query(
+            r#"
+                INSERT INTO audit_event (
+                    id,
+                    event_type,
+                    project_id,
+                    resource_id,
+                    actor,
+                    payload,
+                    occurred_at
+                )
+                VALUES (
+                    $1,
+                    $2,
+                    COALESCE(
+                        $3,
+                        (SELECT project_id FROM project_secret WHERE id = json_extract($6, '$.id'))
+                    ),
+                    $4,
+                    $5,
+                    $6,
+                    $7
+                )
+                ON CONFLICT(id) DO NOTHING;
+            "#,
+        )
+        .bind(&event.id)
+        .bind(&event.event_type)
+        .bind(&event.project_id)
+        .bind(&event.resource_id)
+        .bind(&event.actor)
+        .bind(&event.payload)
+        .bind(event.occurred_at)
+        .execute(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl FromRow<'_, SqliteRow> for AuditEvent {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            event_type: row.try_get("event_type")?,
+            project_id: row.try_get("project_id")?,
+            resource_id: row.try_get("resource_id")?,
+            actor: row.try_get("actor")?,
+            payload: row.try_get("payload")?,
+            occurred_at: row.try_get("occurred_at")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::*;
+    use crate::{
+        domain::{
+            event::{EventEnvelope, ProjectSecretDeleted, ResourceDeleted},
+            project::{cache::ProjectDrivenCache, ProjectSecret},
+        },
+        driven::cache::{project::SqliteProjectDrivenCache, tests::mock_project},
+    };
+
+    #[tokio::test]
+    async fn it_should_find_audit_events_by_filter() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let cache = SqliteAuditDrivenCache::new(sqlite_cache);
+
+        let evt = ResourceDeleted::default();
+        let event = AuditEvent::new("1", &EventEnvelope::new(evt.clone().into())).unwrap();
+        cache.create(&event).await.unwrap();
+        cache.create(&event).await.unwrap();
+
+        let other =
+            AuditEvent::new("2", &EventEnvelope::new(ResourceDeleted::default().into())).unwrap();
+        cache.create(&other).await.unwrap();
+
+        let filter = AuditFilter {
+            project_id: Some(evt.project_id.clone()),
+            ..Default::default()
+        };
+        let result = cache.find(&filter, &1, &12).await.unwrap();
+        assert!(result.len() == 1);
+        assert!(result[0].resource_id == Some(evt.id));
+
+        let filter = AuditFilter {
+            event_type: Some("ResourceDeleted".into()),
+            from: Some(Utc::now() - Duration::hours(1)),
+            ..Default::default()
+        };
+        let result = cache.find(&filter, &1, &12).await.unwrap();
+        assert!(result.len() == 2);
+
+        let filter = AuditFilter {
+            to: Some(Utc::now() - Duration::hours(1)),
+            ..Default::default()
+        };
+        let result = cache.find(&filter, &1, &12).await.unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[tokio::test]
+    async fn it_should_resolve_project_of_deleted_secret() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let cache = SqliteAuditDrivenCache::new(sqlite_cache.clone());
+
+        let project = mock_project(sqlite_cache.clone()).await;
+        let secret = ProjectSecret {
+            project_id: project.id.clone(),
+            ..Default::default()
+        };
+        SqliteProjectDrivenCache::new(sqlite_cache.clone())
+            .create_secret(&secret)
+            .await
+            .unwrap();
+
+        let evt = ProjectSecretDeleted {
+            id: secret.id,
+            ..Default::default()
+        };
+        let event = AuditEvent::new("1", &EventEnvelope::new(evt.into())).unwrap();
+        cache.create(&event).await.unwrap();
+
+        let filter = AuditFilter {
+            project_id: Some(project.id),
+            ..Default::default()
+        };
+        let result = cache.find(&filter, &1, &12).await.unwrap();
+        assert!(result.len() == 1);
+    }
+}
@@ -0,0 +1,356 @@
+use chrono::{DateTime, Utc};
+use sqlx::{Connection, Row};
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{domain::Result, driven::bus::BusMessage};
+
+use super::SqliteCache;
+
+/// Position of a message in the topic.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EventPosition {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+}
+impl EventPosition {
+    /// Id used in the ledger for events published without an envelope id.
+    pub fn event_id(&self) -> String {
+        format!("{}:{}:{}", self.topic, self.partition, self.offset)
+    }
+}
+impl From<&BusMessage> for EventPosition {
+    fn from(value: &BusMessage) -> Self {
+        Self {
+            topic: value.topic.clone(),
+            partition: value.partition,
+            offset: value.offset,
+        }
+    }
+}
+
+const PROCESSED: &str = "processed";
+const DEAD_LETTERED: &str = "dead_lettered";
+
+/// How long the applied events are kept in the ledger to discard duplicates.
+pub const PROCESSED_EVENT_RETENTION: chrono::TimeDelta = chrono::TimeDelta::days(30);
+
+/// Ledger of the events already applied to the cache, and the last offset applied per topic
+/// partition.
+pub struct SqliteEventLedger {
+    sqlite: Arc<SqliteCache>,
+}
+impl SqliteEventLedger {
+    pub fn new(sqlite: Arc<SqliteCache>) -> Self {
+        Self { sqlite }
+    }
+
+    pub async fn is_processed(&self, event_id: &str) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+                SELECT COUNT(*) as count
+                FROM processed_event
+                WHERE event_id = $1 AND status = 'processed';
+            "#,
+        )
+        .bind(event_id)
+        .fetch_one(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        let count: i64 = result.try_get("count")?;
+        Ok(count > 0)
+    }
+
+    pub async fn record(
+        &self,
+        event_id: &str,
+        event_key: &str,
+        position: &EventPosition,
+    ) -> Result<()> {
+        self.record_status(event_id, event_key, position, PROCESSED)
+            .await
+    }
+
+    /// Records an event that couldn't be applied, it isn't seen as processed so it's applied if
+    /// delivered again, e.g. replayed from the dead letter topic.
+    pub async fn dead_letter(
+        &self,
+        event_id: &str,
+        event_key: &str,
+        position: &EventPosition,
+    ) -> Result<()> {
+        self.record_status(event_id, event_key, position, DEAD_LETTERED)
+            .await
+    }
+
+    /// Deletes the events recorded before `before`, their offsets are already behind the stored
+    /// ones so only a redelivery from an older offset could apply them again.
+    pub async fn prune(&self, before: &DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+                DELETE FROM processed_event
+                WHERE processed_at < $1;
+            "#,
+        )
+        .bind(before)
+        .execute(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn record_status(
+        &self,
+        event_id: &str,
+        event_key: &str,
+        position: &EventPosition,
+        status: &str,
+    ) -> Result<()> {
+        let mut conn = self.sqlite.conn().await?;
+        let mut tx = conn.begin().await?;
+        let now = Utc::now();
+
+        sqlx::query(
+            r#"
+                INSERT INTO processed_event (
+                    event_id,
+                    event_key,
+                    topic,
+                    partition,
+                    "offset",
+                    processed_at,
+                    status
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (event_id) DO UPDATE SET
+                    topic = excluded.topic,
+                    partition = excluded.partition,
+                    "offset" = excluded."offset",
+                    processed_at = excluded.processed_at,
+                    status = excluded.status
+                WHERE processed_event.status = 'dead_lettered';
+            "#,
+        )
+        .bind(event_id)
+        .bind(event_key)
+        .bind(&position.topic)
+        .bind(position.partition)
+        .bind(position.offset)
+        .bind(now)
+        .bind(status)
+        .execute(&mut *tx)
+        .await?;
+
+        self.record_offset(&mut tx, position).await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Moves the stored offset forward for messages that are not applied, e.g. undecodable
+    /// payloads, so they are not read again when resuming.
+    pub async fn skip(&self, position: &EventPosition) -> Result<()> {
+        let mut conn = self.sqlite.conn().await?;
+        let mut tx = conn.begin().await?;
+        self.record_offset(&mut tx, position).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn find_offsets(&self) -> Result<HashMap<(String, i32), i64>> {
+        let rows = sqlx::query(
+            r#"
+                SELECT topic, partition, "offset"
+                FROM consumer_offset;
+            "#,
+        )
+        .fetch_all(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let topic: String = row.try_get("topic")?;
+                let partition: i32 = row.try_get("partition")?;
+                let offset: i64 = row.try_get("offset")?;
+                Ok(((topic, partition), offset))
+            })
+            .collect()
+    }
+
+    async fn record_offset(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        position: &EventPosition,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+                INSERT INTO consumer_offset (topic, partition, "offset", updated_at)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (topic, partition) DO UPDATE SET
+                    "offset" = MAX("offset", excluded."offset"),
+                    updated_at = excluded.updated_at;
+            "#,
+        )
+        .bind(&position.topic)
+        .bind(position.partition)
+        .bind(position.offset)
+        .bind(Utc::now())
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::domain::event::EventOffset;
+
+    use super::*;
+
+    fn position(offset: i64) -> EventPosition {
+        EventPosition {
+            topic: "events".into(),
+            partition: 0,
+            offset,
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_record_processed_event() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let ledger = SqliteEventLedger::new(sqlite_cache);
+
+        assert!(!ledger.is_processed("event id").await.unwrap());
+
+        ledger
+            .record("event id", "ProjectCreated", &position(1))
+            .await
+            .unwrap();
+        assert!(ledger.is_processed("event id").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn it_should_ignore_event_recorded_twice() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let ledger = SqliteEventLedger::new(sqlite_cache);
+
+        ledger
+            .record("event id", "ProjectCreated", &position(1))
+            .await
+            .unwrap();
+        let result = ledger
+            .record("event id", "ProjectCreated", &position(2))
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_apply_dead_lettered_event_again() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let ledger = SqliteEventLedger::new(sqlite_cache);
+
+        ledger
+            .dead_letter("event id", "ProjectCreated", &position(1))
+            .await
+            .unwrap();
+        assert!(!ledger.is_processed("event id").await.unwrap());
+
+        ledger
+            .record("event id", "ProjectCreated", &position(2))
+            .await
+            .unwrap();
+        assert!(ledger.is_processed("event id").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn it_should_prune_events_recorded_before() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let ledger = SqliteEventLedger::new(sqlite_cache);
+
+        ledger
+            .record("event id", "ProjectCreated", &position(1))
+            .await
+            .unwrap();
+
+        let pruned = ledger
+            .prune(&(Utc::now() - PROCESSED_EVENT_RETENTION))
+            .await
+            .unwrap();
+        assert!(pruned == 0);
+
+        let pruned = ledger.prune(&Utc::now()).await.unwrap();
+        assert!(pruned == 1);
+        assert!(!ledger.is_processed("event id").await.unwrap());
+        assert!(ledger.find_offsets().await.unwrap().len() == 1);
+    }
+
+    #[tokio::test]
+    async fn it_should_discard_the_record_of_a_rolled_back_event() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let ledger = SqliteEventLedger::new(sqlite_cache.clone());
+
+        sqlite_cache.begin().await.unwrap();
+        ledger
+            .record("event id", "ProjectCreated", &position(1))
+            .await
+            .unwrap();
+        sqlite_cache.rollback().await.unwrap();
+
+        assert!(!ledger.is_processed("event id").await.unwrap());
+        assert!(ledger.find_offsets().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn it_should_keep_highest_offset() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let ledger = SqliteEventLedger::new(sqlite_cache);
+
+        ledger
+            .record("a", "ProjectCreated", &position(5))
+            .await
+            .unwrap();
+        ledger.skip(&position(7)).await.unwrap();
+        ledger
+            .record("b", "ProjectCreated", &position(3))
+            .await
+            .unwrap();
+
+        let offsets = ledger.find_offsets().await.unwrap();
+        assert!(offsets.get(&("events".into(), 0)) == Some(&7));
+    }
+
+    #[tokio::test]
+    async fn it_should_wait_until_the_offset_is_applied() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let ledger = SqliteEventLedger::new(sqlite_cache.clone());
+
+        let offset = EventOffset {
+            topic: "events".into(),
+            partition: 0,
+            offset: 2,
+        };
+        let timeout = Duration::from_millis(200);
+
+        assert!(!sqlite_cache.wait_applied(&offset, timeout).await.unwrap());
+
+        let waiting = tokio::spawn({
+            let sqlite_cache = sqlite_cache.clone();
+            let offset = offset.clone();
+            async move {
+                sqlite_cache
+                    .wait_applied(&offset, Duration::from_secs(5))
+                    .await
+            }
+        });
+        ledger
+            .record("a", "ProjectCreated", &position(2))
+            .await
+            .unwrap();
+        assert!(waiting.await.unwrap().unwrap());
+    }
+}
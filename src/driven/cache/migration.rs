@@ -0,0 +1,157 @@
+use anyhow::{bail, Result};
+use sqlx::migrate::{Migrate, Migrator};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    /// False for the migrations applied by a newer release, this binary has no files for them.
+    pub known: bool,
+    pub applied: bool,
+}
+
+/// Every migration of `migrator` and every migration recorded in the database, by version.
+pub async fn status<C: Migrate>(migrator: &Migrator, conn: &mut C) -> Result<Vec<MigrationStatus>> {
+    conn.ensure_migrations_table().await?;
+    let applied: HashSet<i64> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|m| m.version)
+        .collect();
+
+    let mut migrations: Vec<MigrationStatus> = migrator
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| MigrationStatus {
+            version: m.version,
+            description: m.description.to_string(),
+            known: true,
+            applied: applied.contains(&m.version),
+        })
+        .collect();
+
+    let known: HashSet<i64> = migrations.iter().map(|m| m.version).collect();
+    migrations.extend(applied.difference(&known).map(|version| MigrationStatus {
+        version: *version,
+        description: String::new(),
+        known: false,
+        applied: true,
+    }));
+    migrations.sort_by_key(|m| m.version);
+
+    Ok(migrations)
+}
+
+/// Fails when the database was migrated by a newer release, the queries of this binary could
+/// read or write columns that no longer mean the same.
+pub fn ensure_known(migrations: &[MigrationStatus]) -> Result<()> {
+    let unknown: Vec<String> = migrations
+        .iter()
+        .filter(|m| !m.known)
+        .map(|m| m.version.to_string())
+        .collect();
+
+    if !unknown.is_empty() {
+        bail!(
+            "the database has migrations unknown to this binary ({}), it was migrated by a newer release",
+            unknown.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+/// Version to undo to when reverting only the latest applied migration.
+pub fn previous_version(migrations: &[MigrationStatus]) -> Option<i64> {
+    let mut applied = migrations.iter().filter(|m| m.applied).rev();
+    applied.next()?;
+
+    Some(applied.next().map(|m| m.version).unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, fs, path::Path};
+
+    use super::*;
+
+    const MIGRATION_DIRS: [&str; 3] = [
+        "src/driven/cache/migrations",
+        "src/driven/cache/postgres/migrations",
+        "src/driven/outbox/migrations",
+    ];
+    /// Versions written before the naming was settled, they are kept because the databases
+    /// already have them recorded.
+    const LEGACY_VERSIONS: [&str; 3] = ["20240606", "20250312", "20250318"];
+
+    #[test]
+    fn it_should_pair_every_migration_with_a_down_migration() {
+        for dir in MIGRATION_DIRS {
+            let mut pairs: HashMap<String, (bool, bool)> = HashMap::new();
+
+            for entry in fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join(dir)).unwrap() {
+                let name = entry.unwrap().file_name().into_string().unwrap();
+                let (migration, up) =
+                    match (name.strip_suffix(".up.sql"), name.strip_suffix(".down.sql")) {
+                        (Some(migration), _) => (migration.to_string(), true),
+                        (_, Some(migration)) => (migration.to_string(), false),
+                        _ => panic!("{dir}/{name} is not an .up.sql or .down.sql migration"),
+                    };
+
+                let (version, description) = migration.split_once('_').unwrap();
+                assert!(
+                    version.len() == 12 || LEGACY_VERSIONS.contains(&version),
+                    "{dir}/{name} version must be YYYYMMDDHHMM"
+                );
+                assert!(version.chars().all(|c| c.is_ascii_digit()));
+                assert!(!description.is_empty());
+
+                let pair = pairs.entry(migration).or_default();
+                if up {
+                    pair.0 = true;
+                } else {
+                    pair.1 = true;
+                }
+            }
+
+            for (migration, pair) in pairs {
+                assert!(pair == (true, true), "{dir}/{migration} is not paired");
+            }
+        }
+    }
+
+    #[test]
+    fn it_should_fail_for_unknown_migrations() {
+        let mut migrations = vec![MigrationStatus {
+            version: 1,
+            description: "tables".into(),
+            known: true,
+            applied: true,
+        }];
+        assert!(ensure_known(&migrations).is_ok());
+
+        migrations.push(MigrationStatus {
+            version: 2,
+            description: String::new(),
+            known: false,
+            applied: true,
+        });
+        assert!(ensure_known(&migrations).is_err());
+    }
+
+    #[test]
+    fn it_should_find_previous_version() {
+        let migration = |version, applied| MigrationStatus {
+            version,
+            description: "tables".into(),
+            known: true,
+            applied,
+        };
+
+        assert!(previous_version(&[migration(1, false)]).is_none());
+        assert!(previous_version(&[migration(1, true), migration(2, false)]) == Some(0));
+        assert!(previous_version(&[migration(1, true), migration(2, true)]) == Some(1));
+    }
+}
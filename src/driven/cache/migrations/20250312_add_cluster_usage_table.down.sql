@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_usage_cluster_id;
+
+ALTER TABLE "usage" DROP COLUMN cluster_id;
@@ -0,0 +1,5 @@
+-- The indexes reference the column, so they are dropped before it
+DROP INDEX IF EXISTS idx_resource_category;
+DROP INDEX IF EXISTS idx_resource_project_id_status_category;
+
+ALTER TABLE resource DROP COLUMN category;
@@ -0,0 +1,29 @@
+-- project_user
+DROP INDEX IF EXISTS idx_project_user_user_id;
+DROP INDEX IF EXISTS idx_project_user_project_id;
+DROP INDEX IF EXISTS idx_project_user_created_at;
+DROP INDEX IF EXISTS idx_project_user_user_id_project_id;
+
+-- project
+DROP INDEX IF EXISTS idx_project_status;
+DROP INDEX IF EXISTS idx_project_created_at;
+DROP INDEX IF EXISTS idx_project_status_namespace;
+
+-- project_secret
+DROP INDEX IF EXISTS idx_project_secret_project_id;
+DROP INDEX IF EXISTS idx_project_secret_created_at;
+
+-- project_user_invite
+DROP INDEX IF EXISTS idx_project_user_invite_project_expires_in_status;
+DROP INDEX IF EXISTS idx_project_user_invite_code;
+
+-- resource
+DROP INDEX IF EXISTS idx_resource_project_id;
+DROP INDEX IF EXISTS idx_resource_created_at;
+DROP INDEX IF EXISTS idx_resource_status;
+DROP INDEX IF EXISTS idx_resource_project_id_status;
+
+-- usage
+DROP INDEX IF EXISTS idx_usage_created_at;
+DROP INDEX IF EXISTS idx_usage_resource_id;
+DROP INDEX IF EXISTS idx_usage_tier;
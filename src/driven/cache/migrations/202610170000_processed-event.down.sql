@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS consumer_offset;
+DROP TABLE IF EXISTS processed_event;
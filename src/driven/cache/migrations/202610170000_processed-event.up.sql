@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS processed_event (
+  event_id TEXT PRIMARY KEY NOT NULL,
+  event_key TEXT NOT NULL,
+  topic TEXT NOT NULL,
+  partition INT NOT NULL,
+  "offset" INT NOT NULL,
+  processed_at DATETIME NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS consumer_offset (
+  topic TEXT NOT NULL,
+  partition INT NOT NULL,
+  "offset" INT NOT NULL,
+  updated_at DATETIME NOT NULL,
+  PRIMARY KEY (topic, partition)
+);
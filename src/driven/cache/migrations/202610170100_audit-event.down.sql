@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_audit_event_actor;
+DROP INDEX IF EXISTS idx_audit_event_resource_id;
+DROP INDEX IF EXISTS idx_audit_event_project_id;
+
+DROP TABLE IF EXISTS audit_event;
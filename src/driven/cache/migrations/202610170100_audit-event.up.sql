@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS audit_event (
+  id TEXT PRIMARY KEY NOT NULL,
+  event_type TEXT NOT NULL,
+  project_id TEXT NULL,
+  resource_id TEXT NULL,
+  actor TEXT NULL,
+  payload TEXT NOT NULL,
+  occurred_at DATETIME NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_event_project_id ON audit_event(project_id, occurred_at);
+CREATE INDEX IF NOT EXISTS idx_audit_event_resource_id ON audit_event(resource_id);
+CREATE INDEX IF NOT EXISTS idx_audit_event_actor ON audit_event(actor);
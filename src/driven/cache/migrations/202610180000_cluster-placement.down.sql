@@ -0,0 +1,2 @@
+ALTER TABLE resource DROP COLUMN cluster_id;
+ALTER TABLE project DROP COLUMN cluster_id;
@@ -0,0 +1,3 @@
+-- Cluster the project and its resources are placed on, NULL when every cluster applies them
+ALTER TABLE project ADD COLUMN cluster_id TEXT NULL;
+ALTER TABLE resource ADD COLUMN cluster_id TEXT NULL;
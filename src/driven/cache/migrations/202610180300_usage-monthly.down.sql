@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS idx_usage_rolled_up_at;
+
+ALTER TABLE usage DROP COLUMN rolled_up_at;
+
+DROP INDEX IF EXISTS idx_usage_monthly_project_id;
+DROP TABLE IF EXISTS usage_monthly;
@@ -0,0 +1,19 @@
+-- Totals of the closed months, the raw rows are only kept for the retention window once rolled up
+CREATE TABLE IF NOT EXISTS usage_monthly (
+  period TEXT NOT NULL,
+  cluster_id TEXT NOT NULL,
+  project_id TEXT NOT NULL,
+  resource_id TEXT NOT NULL,
+  tier TEXT NOT NULL,
+  units INT NOT NULL,
+  interval INT NOT NULL,
+  updated_at DATETIME NOT NULL,
+  PRIMARY KEY (period, cluster_id, resource_id, tier),
+  FOREIGN KEY(resource_id) REFERENCES resource(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_usage_monthly_project_id ON usage_monthly(project_id, period);
+
+ALTER TABLE usage ADD COLUMN rolled_up_at DATETIME NULL;
+
+CREATE INDEX IF NOT EXISTS idx_usage_rolled_up_at ON usage(rolled_up_at, created_at);
@@ -0,0 +1,9 @@
+DROP TRIGGER IF EXISTS resource_fts_delete;
+DROP TRIGGER IF EXISTS resource_fts_update;
+DROP TRIGGER IF EXISTS resource_fts_insert;
+DROP TRIGGER IF EXISTS project_fts_delete;
+DROP TRIGGER IF EXISTS project_fts_update;
+DROP TRIGGER IF EXISTS project_fts_insert;
+
+DROP TABLE IF EXISTS resource_fts;
+DROP TABLE IF EXISTS project_fts;
@@ -0,0 +1,63 @@
+-- Full text indexes of the projects and resources, kept in sync by triggers and keyed by the
+-- rowid of the indexed row. The resource spec is indexed by its values only.
+CREATE VIRTUAL TABLE IF NOT EXISTS project_fts USING fts5(name, namespace);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS resource_fts USING fts5(name, kind, spec);
+
+CREATE TRIGGER IF NOT EXISTS project_fts_insert AFTER INSERT ON project BEGIN
+  INSERT INTO project_fts(rowid, name, namespace) VALUES (NEW.rowid, NEW.name, NEW.namespace);
+END;
+
+CREATE TRIGGER IF NOT EXISTS project_fts_update AFTER UPDATE OF name, namespace ON project BEGIN
+  UPDATE project_fts SET name = NEW.name, namespace = NEW.namespace WHERE rowid = NEW.rowid;
+END;
+
+CREATE TRIGGER IF NOT EXISTS project_fts_delete AFTER DELETE ON project BEGIN
+  DELETE FROM project_fts WHERE rowid = OLD.rowid;
+END;
+
+CREATE TRIGGER IF NOT EXISTS resource_fts_insert AFTER INSERT ON resource BEGIN
+  INSERT INTO resource_fts(rowid, name, kind, spec)
+  VALUES (
+    NEW.rowid,
+    NEW.name,
+    NEW.kind,
+    CASE
+      WHEN json_valid(NEW.spec)
+        THEN (SELECT group_concat(atom, ' ') FROM json_tree(NEW.spec) WHERE atom IS NOT NULL)
+      ELSE NEW.spec
+    END
+  );
+END;
+
+CREATE TRIGGER IF NOT EXISTS resource_fts_update AFTER UPDATE OF name, kind, spec ON resource BEGIN
+  UPDATE resource_fts
+  SET
+    name = NEW.name,
+    kind = NEW.kind,
+    spec = CASE
+      WHEN json_valid(NEW.spec)
+        THEN (SELECT group_concat(atom, ' ') FROM json_tree(NEW.spec) WHERE atom IS NOT NULL)
+      ELSE NEW.spec
+    END
+  WHERE rowid = NEW.rowid;
+END;
+
+CREATE TRIGGER IF NOT EXISTS resource_fts_delete AFTER DELETE ON resource BEGIN
+  DELETE FROM resource_fts WHERE rowid = OLD.rowid;
+END;
+
+INSERT INTO project_fts(rowid, name, namespace)
+SELECT rowid, name, namespace FROM project;
+
+INSERT INTO resource_fts(rowid, name, kind, spec)
+SELECT
+  rowid,
+  name,
+  kind,
+  CASE
+    WHEN json_valid(spec)
+      THEN (SELECT group_concat(atom, ' ') FROM json_tree(resource.spec) WHERE atom IS NOT NULL)
+    ELSE spec
+  END
+FROM resource;
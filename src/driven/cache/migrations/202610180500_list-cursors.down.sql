@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_resource_project_id_created_at;
+DROP INDEX IF EXISTS idx_project_user_invite_project_id_created_at;
+DROP INDEX IF EXISTS idx_project_user_project_id_created_at;
+DROP INDEX IF EXISTS idx_project_created_at_id;
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS idx_project_organization_id;
+ALTER TABLE project DROP COLUMN organization_id;
+DROP INDEX IF EXISTS idx_organization_user_organization_id_created_at;
+DROP TABLE IF EXISTS organization_user;
+DROP TABLE IF EXISTS organization;
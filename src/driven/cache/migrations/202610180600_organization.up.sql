@@ -0,0 +1,23 @@
+CREATE TABLE IF NOT EXISTS organization (
+  id TEXT PRIMARY KEY NOT NULL,
+  name TEXT NOT NULL,
+  owner TEXT NOT NULL,
+  billing_provider TEXT NOT NULL,
+  billing_provider_id TEXT NOT NULL,
+  created_at DATETIME NOT NULL,
+  updated_at DATETIME NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS organization_user (
+  user_id TEXT NOT NULL,
+  organization_id TEXT NOT NULL,
+  role TEXT NOT NULL,
+  created_at DATETIME NOT NULL,
+  PRIMARY KEY (user_id, organization_id),
+  FOREIGN KEY(organization_id) REFERENCES organization(id)
+);
+CREATE INDEX IF NOT EXISTS idx_organization_user_organization_id_created_at ON organization_user(organization_id, created_at, user_id);
+
+-- Organization the project belongs to, NULL when it only has its own members
+ALTER TABLE project ADD COLUMN organization_id TEXT NULL;
+CREATE INDEX IF NOT EXISTS idx_project_organization_id ON project(organization_id);
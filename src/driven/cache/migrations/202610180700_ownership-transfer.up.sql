@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS project_ownership_transfer (
+  id TEXT PRIMARY KEY NOT NULL,
+  project_id TEXT NOT NULL,
+  previous_owner TEXT NOT NULL,
+  new_owner TEXT NOT NULL,
+  code TEXT NOT NULL,
+  status TEXT NOT NULL,
+  expires_in DATETIME NOT NULL,
+  created_at DATETIME NOT NULL,
+  updated_at DATETIME NOT NULL,
+  FOREIGN KEY(project_id) REFERENCES project(id)
+);
+CREATE INDEX IF NOT EXISTS idx_project_ownership_transfer_project_id ON project_ownership_transfer(project_id);
+CREATE INDEX IF NOT EXISTS idx_project_ownership_transfer_code ON project_ownership_transfer(code);
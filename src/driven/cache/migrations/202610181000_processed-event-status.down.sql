@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_processed_event_processed_at;
+
+ALTER TABLE processed_event DROP COLUMN status;
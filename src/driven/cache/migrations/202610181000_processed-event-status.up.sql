@@ -0,0 +1,3 @@
+ALTER TABLE processed_event ADD COLUMN status TEXT NOT NULL DEFAULT 'processed';
+
+CREATE INDEX IF NOT EXISTS idx_processed_event_processed_at ON processed_event(processed_at);
@@ -1,12 +1,47 @@
 use anyhow::Result;
-use std::path::Path;
+use sha2::{Digest, Sha256};
+use sqlx::{
+    migrate::Migrator,
+    pool::PoolConnection,
+    sqlite::{Sqlite, SqliteConnection},
+    Row, Transaction,
+};
+use std::{
+    ops::{Deref, DerefMut},
+    path::Path,
+    time::Duration,
+};
+use tokio::{
+    sync::{Mutex, MutexGuard},
+    time::{sleep, Instant},
+};
 
+use crate::domain::event::EventOffset;
+
+use self::migration::MigrationStatus;
+
+pub mod audit;
+pub mod ledger;
+pub mod migration;
+pub mod organization;
+pub mod postgres;
 pub mod project;
 pub mod resource;
+pub mod search;
+pub mod snapshot;
 pub mod usage;
 
+const APPLIED_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// Key of the default quota in the `project_quota` table, the other rows are keyed by project.
+pub const DEFAULT_QUOTA_ID: &str = "default";
+
+static MIGRATOR: Migrator = sqlx::migrate!("src/driven/cache/migrations");
+
 pub struct SqliteCache {
     db: sqlx::sqlite::SqlitePool,
+    /// Transaction opened with `begin`, the statements of every cache sharing this one run in it
+    /// until it's committed or rolled back.
+    tx: Mutex<Option<Transaction<'static, Sqlite>>>,
 }
 
 impl SqliteCache {
@@ -14,30 +49,315 @@ impl SqliteCache {
         let url = format!("sqlite:{}?mode=rwc", path.display());
         let db = sqlx::sqlite::SqlitePoolOptions::new().connect(&url).await?;
 
-        Ok(Self { db })
+        Ok(Self::from_pool(db))
+    }
+
+    fn from_pool(db: sqlx::sqlite::SqlitePool) -> Self {
+        Self {
+            db,
+            tx: Mutex::new(None),
+        }
+    }
+
+    /// Connection for the next statement, the one of the open transaction if there is one. The
+    /// transactions started on it while the cache is in a transaction are savepoints.
+    pub(crate) async fn conn(&self) -> sqlx::Result<SqliteConn<'_>> {
+        let tx = self.tx.lock().await;
+        if tx.is_some() {
+            return Ok(SqliteConn::Tx(tx));
+        }
+        drop(tx);
+
+        Ok(SqliteConn::Pool(self.db.acquire().await?))
+    }
+
+    /// Groups the writes of the following statements, e.g. the projection of an event and its
+    /// ledger row, until `commit` or `rollback`.
+    pub async fn begin(&self) -> crate::domain::Result<()> {
+        let mut tx = self.tx.lock().await;
+        if tx.is_some() {
+            return Err(crate::domain::error::Error::Unexpected(
+                "cache transaction already open".into(),
+            ));
+        }
+        *tx = Some(self.db.begin().await?);
+
+        Ok(())
+    }
+
+    pub async fn commit(&self) -> crate::domain::Result<()> {
+        if let Some(tx) = self.tx.lock().await.take() {
+            tx.commit().await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn rollback(&self) -> crate::domain::Result<()> {
+        if let Some(tx) = self.tx.lock().await.take() {
+            tx.rollback().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Marks the writes made so far in the open transaction, `rollback_to_savepoint` drops the
+    /// ones made after it, e.g. by a failed attempt at applying an event.
+    pub async fn savepoint(&self) -> crate::domain::Result<()> {
+        self.execute_in_tx("SAVEPOINT attempt;").await
+    }
+
+    pub async fn release_savepoint(&self) -> crate::domain::Result<()> {
+        self.execute_in_tx("RELEASE SAVEPOINT attempt;").await
+    }
+
+    pub async fn rollback_to_savepoint(&self) -> crate::domain::Result<()> {
+        self.execute_in_tx("ROLLBACK TO SAVEPOINT attempt;").await?;
+        self.execute_in_tx("RELEASE SAVEPOINT attempt;").await
+    }
+
+    // Outside of a transaction sqlite would open one on whichever connection of the pool runs
+    // the statement, so the savepoints are only taken in the one opened with `begin`.
+    async fn execute_in_tx(&self, sql: &str) -> crate::domain::Result<()> {
+        let mut tx = self.tx.lock().await;
+        let Some(tx) = tx.as_mut() else {
+            return Err(crate::domain::error::Error::Unexpected(
+                "no cache transaction open".into(),
+            ));
+        };
+        sqlx::query(sql).execute(&mut **tx).await?;
+
+        Ok(())
     }
 
     pub async fn migrate(&self) -> Result<()> {
-        sqlx::migrate!("src/driven/cache/migrations")
-            .run(&self.db)
+        MIGRATOR.run(&self.db).await?;
+
+        Ok(())
+    }
+
+    /// Runs the down migrations of every applied version greater than `target`.
+    pub async fn migrate_down(&self, target: i64) -> Result<()> {
+        MIGRATOR.undo(&self.db, target).await?;
+
+        Ok(())
+    }
+
+    pub async fn migrations(&self) -> Result<Vec<MigrationStatus>> {
+        let mut conn = self.db.acquire().await?;
+        migration::status(&MIGRATOR, &mut *conn).await
+    }
+
+    pub async fn check_schema(&self) -> Result<()> {
+        migration::ensure_known(&self.migrations().await?)
+    }
+
+    /// Moves the write-ahead log into the database file and truncates it, so the file has every
+    /// change once the cache is closed.
+    pub async fn checkpoint(&self) -> Result<()> {
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE);")
+            .execute(&self.db)
             .await?;
 
         Ok(())
     }
 
+    pub async fn close(&self) {
+        self.db.close().await
+    }
+
+    /// Polls the offsets stored by the ledger until the consumer of this cache reaches `offset`.
+    pub async fn wait_applied(
+        &self,
+        offset: &EventOffset,
+        timeout: Duration,
+    ) -> crate::domain::Result<bool> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let applied: Option<i64> = sqlx::query_scalar(
+                r#"
+                    SELECT "offset"
+                    FROM consumer_offset
+                    WHERE topic = $1 AND partition = $2;
+                "#,
+            )
+            .bind(&offset.topic)
+            .bind(offset.partition)
+            .fetch_optional(&self.db)
+            .await?;
+
+            if applied.is_some_and(|applied| applied >= offset.offset) {
+                return Ok(true);
+            }
+            if Instant::now() >= deadline {
+                return Ok(false);
+            }
+            sleep(APPLIED_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Hash of the projected rows, independent of the order they were inserted in. Generated
+    /// values that are not carried by the events, like the usage row id, are left out so two
+    /// caches built from the same topic always match.
+    pub async fn checksum(&self) -> Result<CacheChecksum> {
+        let mut tables = Vec::new();
+        let mut hasher = Sha256::new();
+
+        for (name, query) in CHECKSUM_QUERIES {
+            let rows = sqlx::query(query).fetch_all(&self.db).await?;
+
+            let mut table_hasher = Sha256::new();
+            for row in rows.iter() {
+                let value: String = row.try_get("row")?;
+                table_hasher.update(value.as_bytes());
+                table_hasher.update(b"\n");
+            }
+            let checksum = hex::encode(table_hasher.finalize());
+
+            hasher.update(name.as_bytes());
+            hasher.update(checksum.as_bytes());
+            tables.push(TableChecksum {
+                name: name.to_string(),
+                rows: rows.len(),
+                checksum,
+            });
+        }
+
+        Ok(CacheChecksum {
+            checksum: hex::encode(hasher.finalize()),
+            tables,
+        })
+    }
+
     #[cfg(test)]
     pub async fn ephemeral() -> Result<Self> {
         let db = sqlx::sqlite::SqlitePoolOptions::new()
             .connect("sqlite::memory:")
             .await?;
 
-        let out = Self { db };
+        let out = Self::from_pool(db);
         out.migrate().await?;
 
         Ok(out)
     }
 }
 
+/// Connection of the pool, or of the transaction open in the cache.
+pub(crate) enum SqliteConn<'a> {
+    Pool(PoolConnection<Sqlite>),
+    Tx(MutexGuard<'a, Option<Transaction<'static, Sqlite>>>),
+}
+impl Deref for SqliteConn<'_> {
+    type Target = SqliteConnection;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Pool(conn) => conn,
+            Self::Tx(tx) => tx.as_ref().expect("transaction open"),
+        }
+    }
+}
+impl DerefMut for SqliteConn<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Self::Pool(conn) => conn,
+            Self::Tx(tx) => tx.as_mut().expect("transaction open"),
+        }
+    }
+}
+
+const CHECKSUM_QUERIES: [(&str, &str); 7] = [
+    (
+        "project",
+        r#"
+            SELECT json_array(
+                id, namespace, name, owner, status, billing_provider, billing_provider_id,
+                billing_subscription_id, cluster_id, organization_id, created_at, updated_at
+            ) as row
+            FROM project
+            ORDER BY id;
+        "#,
+    ),
+    (
+        "resource",
+        r#"
+            SELECT json_array(
+                id, project_id, name, kind, category, spec, status, cluster_id, created_at,
+                updated_at
+            ) as row
+            FROM resource
+            ORDER BY id;
+        "#,
+    ),
+    (
+        "project_user",
+        r#"
+            SELECT json_array(user_id, project_id, role, created_at) as row
+            FROM project_user
+            ORDER BY project_id, user_id;
+        "#,
+    ),
+    (
+        "organization",
+        r#"
+            SELECT json_array(
+                id, name, owner, billing_provider, billing_provider_id, created_at, updated_at
+            ) as row
+            FROM organization
+            ORDER BY id;
+        "#,
+    ),
+    (
+        "organization_user",
+        r#"
+            SELECT json_array(user_id, organization_id, role, created_at) as row
+            FROM organization_user
+            ORDER BY organization_id, user_id;
+        "#,
+    ),
+    (
+        "project_quota",
+        r#"
+            SELECT json_array(project_id, resources, max_tier, workers, updated_at) as row
+            FROM project_quota
+            ORDER BY project_id;
+        "#,
+    ),
+    // Monthly totals, so a cache that purged the rows already rolled up still matches one
+    // rebuilt from the topics.
+    (
+        "usage",
+        r#"
+            SELECT json_array(
+                period, cluster_id, resource_id, tier, SUM(units), SUM(interval)
+            ) as row
+            FROM (
+                SELECT period, cluster_id, resource_id, tier, units, interval
+                FROM usage_monthly
+                UNION ALL
+                SELECT STRFTIME('%Y-%m', created_at), cluster_id, resource_id, tier, units, interval
+                FROM usage
+                WHERE rolled_up_at IS NULL
+            )
+            GROUP BY period, cluster_id, resource_id, tier
+            ORDER BY period, cluster_id, resource_id, tier;
+        "#,
+    ),
+];
+
+#[derive(Debug, Clone)]
+pub struct TableChecksum {
+    pub name: String,
+    pub rows: usize,
+    pub checksum: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheChecksum {
+    pub checksum: String,
+    pub tables: Vec<TableChecksum>,
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -76,4 +396,112 @@ mod tests {
 
         resource
     }
+
+    #[tokio::test]
+    async fn it_should_compute_same_checksum_for_same_rows() {
+        let first = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let second = Arc::new(SqliteCache::ephemeral().await.unwrap());
+
+        let project = mock_project(first.clone()).await;
+        let cache: Box<dyn ProjectDrivenCache> =
+            Box::new(SqliteProjectDrivenCache::new(second.clone()));
+        cache.create(&project).await.unwrap();
+
+        let first_checksum = first.checksum().await.unwrap();
+        let second_checksum = second.checksum().await.unwrap();
+        assert!(first_checksum.checksum == second_checksum.checksum);
+        assert!(first_checksum.tables[0].rows == 1);
+    }
+
+    #[tokio::test]
+    async fn it_should_change_checksum_when_rows_change() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let empty = sqlite_cache.checksum().await.unwrap();
+
+        let project = mock_project(sqlite_cache.clone()).await;
+        let with_project = sqlite_cache.checksum().await.unwrap();
+        assert!(empty.checksum != with_project.checksum);
+
+        mock_resource(sqlite_cache.clone(), &project.id).await;
+        let with_resource = sqlite_cache.checksum().await.unwrap();
+        assert!(with_project.tables[0].checksum == with_resource.tables[0].checksum);
+        assert!(with_project.tables[1].checksum != with_resource.tables[1].checksum);
+    }
+
+    #[tokio::test]
+    async fn it_should_group_the_writes_of_the_caches_in_a_transaction() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let cache = SqliteProjectDrivenCache::new(sqlite_cache.clone());
+
+        sqlite_cache.begin().await.unwrap();
+        let project = mock_project(sqlite_cache.clone()).await;
+        assert!(cache.find_by_id(&project.id).await.unwrap().is_some());
+        sqlite_cache.rollback().await.unwrap();
+        assert!(cache.find_by_id(&project.id).await.unwrap().is_none());
+
+        sqlite_cache.begin().await.unwrap();
+        let project = mock_project(sqlite_cache.clone()).await;
+        sqlite_cache.commit().await.unwrap();
+        assert!(cache.find_by_id(&project.id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn it_should_roll_back_the_writes_after_the_savepoint() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let cache = SqliteProjectDrivenCache::new(sqlite_cache.clone());
+
+        assert!(sqlite_cache.savepoint().await.is_err());
+
+        let project = |namespace: &str| Project {
+            id: namespace.into(),
+            namespace: namespace.into(),
+            ..Default::default()
+        };
+
+        sqlite_cache.begin().await.unwrap();
+        cache.create(&project("kept")).await.unwrap();
+        sqlite_cache.savepoint().await.unwrap();
+        cache.create(&project("dropped")).await.unwrap();
+        sqlite_cache.rollback_to_savepoint().await.unwrap();
+        sqlite_cache.savepoint().await.unwrap();
+        cache.create(&project("released")).await.unwrap();
+        sqlite_cache.release_savepoint().await.unwrap();
+        sqlite_cache.commit().await.unwrap();
+
+        assert!(cache.find_by_id("kept").await.unwrap().is_some());
+        assert!(cache.find_by_id("dropped").await.unwrap().is_none());
+        assert!(cache.find_by_id("released").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn it_should_revert_and_apply_migrations_again() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let migrations = sqlite_cache.migrations().await.unwrap();
+        assert!(migrations.iter().all(|m| m.known && m.applied));
+
+        sqlite_cache.migrate_down(0).await.unwrap();
+        let migrations = sqlite_cache.migrations().await.unwrap();
+        assert!(migrations.iter().all(|m| !m.applied));
+
+        sqlite_cache.migrate().await.unwrap();
+        mock_project(sqlite_cache.clone()).await;
+        assert!(sqlite_cache.check_schema().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_refuse_a_schema_migrated_by_a_newer_release() {
+        let sqlite_cache = SqliteCache::ephemeral().await.unwrap();
+        sqlx::query(
+            r#"
+                INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+                VALUES (999912310000, 'future', true, x'00', 0);
+            "#,
+        )
+        .execute(&sqlite_cache.db)
+        .await
+        .unwrap();
+
+        assert!(sqlite_cache.check_schema().await.is_err());
+        assert!(sqlite_cache.migrate().await.is_err());
+    }
 }
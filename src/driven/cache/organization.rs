@@ -0,0 +1,354 @@
+use sqlx::{sqlite::SqliteRow, Connection, FromRow, Row};
+use std::sync::Arc;
+
+use crate::domain::{
+    error::Error,
+    organization::{cache::OrganizationDrivenCache, Organization, OrganizationUser},
+    pagination::Page,
+    project::ProjectUserRole,
+    Result,
+};
+
+use super::SqliteCache;
+
+pub struct SqliteOrganizationDrivenCache {
+    sqlite: Arc<SqliteCache>,
+}
+impl SqliteOrganizationDrivenCache {
+    pub fn new(sqlite: Arc<SqliteCache>) -> Self {
+        Self { sqlite }
+    }
+}
+#[async_trait::async_trait]
+impl OrganizationDrivenCache for SqliteOrganizationDrivenCache {
+    async fn find(&self, user_id: &str, page: &Page) -> Result<Vec<Organization>> {
+        let organizations = sqlx::query_as::<_, Organization>(
+            r#"
+                SELECT
+                    o.id,
+                    o.name,
+                    o.owner,
+                    o.billing_provider,
+                    o.billing_provider_id,
+                    o.created_at,
+                    o.updated_at
+                FROM organization_user ou
+                INNER JOIN organization o ON o.id = ou.organization_id
+                WHERE ou.user_id = $1
+                    AND ($4 IS NULL OR (o.created_at, o.id) < ($4, $5))
+                ORDER BY o.created_at DESC, o.id DESC
+                LIMIT $2
+                OFFSET $3;
+            "#,
+        )
+        .bind(user_id)
+        .bind(page.page_size)
+        .bind(page.offset())
+        .bind(page.cursor_created_at())
+        .bind(page.cursor_id())
+        .fetch_all(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(organizations)
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<Organization>> {
+        let organization = sqlx::query_as::<_, Organization>(
+            r#"
+                SELECT
+                    o.id,
+                    o.name,
+                    o.owner,
+                    o.billing_provider,
+                    o.billing_provider_id,
+                    o.created_at,
+                    o.updated_at
+                FROM organization o
+                WHERE o.id = $1;
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(organization)
+    }
+
+    async fn find_users(
+        &self,
+        organization_id: &str,
+        page: &Page,
+    ) -> Result<Vec<OrganizationUser>> {
+        let users = sqlx::query_as::<_, OrganizationUser>(
+            r#"
+                SELECT
+                    ou.user_id,
+                    ou.organization_id,
+                    ou.role,
+                    ou.created_at
+                FROM organization_user ou
+                WHERE ou.organization_id = $1
+                    AND ($4 IS NULL OR (ou.created_at, ou.user_id) < ($4, $5))
+                ORDER BY ou.created_at DESC, ou.user_id DESC
+                LIMIT $2
+                OFFSET $3;
+            "#,
+        )
+        .bind(organization_id)
+        .bind(page.page_size)
+        .bind(page.offset())
+        .bind(page.cursor_created_at())
+        .bind(page.cursor_id())
+        .fetch_all(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(users)
+    }
+
+    async fn find_user_permission(
+        &self,
+        user_id: &str,
+        organization_id: &str,
+    ) -> Result<Option<OrganizationUser>> {
+        let user = sqlx::query_as::<_, OrganizationUser>(
+            r#"
+                SELECT
+                    ou.user_id,
+                    ou.organization_id,
+                    ou.role,
+                    ou.created_at
+                FROM organization_user ou
+                WHERE ou.user_id = $1 AND ou.organization_id = $2;
+            "#,
+        )
+        .bind(user_id)
+        .bind(organization_id)
+        .fetch_optional(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn create(&self, organization: &Organization) -> Result<()> {
+        let mut conn = self.sqlite.conn().await?;
+        let mut tx = conn.begin().await?;
+
+        sqlx::query(
+            r#"
+                INSERT INTO organization (
+                    id,
+                    name,
+                    owner,
+                    billing_provider,
+                    billing_provider_id,
+                    created_at,
+                    updated_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (id) DO NOTHING;
+            "#,
+        )
+        .bind(&organization.id)
+        .bind(&organization.name)
+        .bind(&organization.owner)
+        .bind(&organization.billing_provider)
+        .bind(&organization.billing_provider_id)
+        .bind(organization.created_at)
+        .bind(organization.updated_at)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+                INSERT INTO organization_user (user_id, organization_id, role, created_at)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (user_id, organization_id) DO NOTHING;
+            "#,
+        )
+        .bind(&organization.owner)
+        .bind(&organization.id)
+        .bind(ProjectUserRole::Owner.to_string())
+        .bind(organization.created_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn create_user(&self, user: &OrganizationUser) -> Result<()> {
+        sqlx::query(
+            r#"
+                INSERT INTO organization_user (user_id, organization_id, role, created_at)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (user_id, organization_id) DO NOTHING;
+            "#,
+        )
+        .bind(&user.user_id)
+        .bind(&user.organization_id)
+        .bind(user.role.to_string())
+        .bind(user.created_at)
+        .execute(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_user(&self, organization_id: &str, user_id: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+                DELETE FROM organization_user
+                WHERE organization_id = $1 AND user_id = $2;
+            "#,
+        )
+        .bind(organization_id)
+        .bind(user_id)
+        .execute(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl FromRow<'_, SqliteRow> for Organization {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            owner: row.try_get("owner")?,
+            billing_provider: row.try_get("billing_provider")?,
+            billing_provider_id: row.try_get("billing_provider_id")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, SqliteRow> for OrganizationUser {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        let role: &str = row.try_get("role")?;
+
+        Ok(Self {
+            user_id: row.try_get("user_id")?,
+            organization_id: row.try_get("organization_id")?,
+            role: role
+                .parse()
+                .map_err(|err: Error| sqlx::Error::Decode(err.into()))?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::{
+        domain::project::{cache::ProjectDrivenCache, Project, ProjectOrganizationChange},
+        driven::cache::project::SqliteProjectDrivenCache,
+    };
+
+    #[tokio::test]
+    async fn it_should_create_organization_with_owner() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let cache = SqliteOrganizationDrivenCache::new(sqlite_cache);
+        let organization = Organization::default();
+
+        cache.create(&organization).await.unwrap();
+        cache.create(&organization).await.unwrap();
+
+        let result = cache
+            .find(&organization.owner, &Page::default())
+            .await
+            .unwrap();
+        assert!(result.len() == 1);
+
+        let permission = cache
+            .find_user_permission(&organization.owner, &organization.id)
+            .await
+            .unwrap();
+        assert!(permission.is_some_and(|p| p.role == ProjectUserRole::Owner));
+    }
+
+    #[tokio::test]
+    async fn it_should_inherit_organization_members_in_attached_projects() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let cache = SqliteOrganizationDrivenCache::new(sqlite_cache.clone());
+        let project_cache = SqliteProjectDrivenCache::new(sqlite_cache);
+
+        let organization = Organization::default();
+        cache.create(&organization).await.unwrap();
+        cache
+            .create_user(&OrganizationUser {
+                user_id: "member id".into(),
+                organization_id: organization.id.clone(),
+                role: ProjectUserRole::Member,
+                created_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        let project = Project {
+            owner: "other user id".into(),
+            ..Default::default()
+        };
+        project_cache.create(&project).await.unwrap();
+        assert!(project_cache
+            .find_user_permission("member id", &project.id)
+            .await
+            .unwrap()
+            .is_none());
+
+        project_cache
+            .change_organization(&ProjectOrganizationChange {
+                project_id: project.id.clone(),
+                organization_id: Some(organization.id.clone()),
+                billing_provider_id: organization.billing_provider_id.clone(),
+                changed_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        let permission = project_cache
+            .find_user_permission("member id", &project.id)
+            .await
+            .unwrap();
+        assert!(permission.is_some_and(|p| p.role == ProjectUserRole::Member));
+
+        let projects = project_cache
+            .find("member id", &Page::default())
+            .await
+            .unwrap();
+        assert!(projects.len() == 1);
+        assert!(projects[0].organization_id == Some(organization.id.clone()));
+        assert!(projects[0].billing_provider_id == organization.billing_provider_id);
+
+        // The project owner keeps the owner role over the one inherited from the organization.
+        cache
+            .create_user(&OrganizationUser {
+                user_id: project.owner.clone(),
+                organization_id: organization.id.clone(),
+                role: ProjectUserRole::Member,
+                created_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+        let permission = project_cache
+            .find_user_permission(&project.owner, &project.id)
+            .await
+            .unwrap();
+        assert!(permission.is_some_and(|p| p.role == ProjectUserRole::Owner));
+
+        cache
+            .delete_user(&organization.id, "member id")
+            .await
+            .unwrap();
+        assert!(project_cache
+            .find_user_permission("member id", &project.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
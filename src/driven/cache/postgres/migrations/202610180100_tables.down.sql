@@ -0,0 +1,7 @@
+-- The indexes are dropped with their tables
+DROP TABLE IF EXISTS usage;
+DROP TABLE IF EXISTS project_secret;
+DROP TABLE IF EXISTS project_user_invite;
+DROP TABLE IF EXISTS project_user;
+DROP TABLE IF EXISTS resource;
+DROP TABLE IF EXISTS project;
@@ -0,0 +1,98 @@
+-- Same tables as the sqlite cache after its migrations, for the projections shared by replicas.
+CREATE TABLE IF NOT EXISTS project (
+  id TEXT PRIMARY KEY NOT NULL,
+  namespace TEXT NOT NULL UNIQUE,
+  name TEXT NOT NULL,
+  owner TEXT NOT NULL,
+  status TEXT NOT NULL,
+  billing_provider TEXT NOT NULL,
+  billing_provider_id TEXT NOT NULL,
+  billing_subscription_id TEXT,
+  cluster_id TEXT NULL,
+  created_at TIMESTAMPTZ NOT NULL,
+  updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS resource (
+  id TEXT PRIMARY KEY NOT NULL,
+  project_id TEXT NOT NULL REFERENCES project(id),
+  name TEXT NOT NULL,
+  kind TEXT NOT NULL,
+  category TEXT NOT NULL DEFAULT 'demeter-port',
+  spec TEXT NOT NULL,
+  status TEXT NOT NULL,
+  cluster_id TEXT NULL,
+  created_at TIMESTAMPTZ NOT NULL,
+  updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS project_user (
+  user_id TEXT NOT NULL,
+  project_id TEXT NOT NULL REFERENCES project(id),
+  role TEXT NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL,
+  PRIMARY KEY (user_id, project_id)
+);
+
+CREATE TABLE IF NOT EXISTS project_user_invite (
+  id TEXT PRIMARY KEY NOT NULL,
+  project_id TEXT NOT NULL REFERENCES project(id),
+  email TEXT NOT NULL,
+  role TEXT NOT NULL,
+  code TEXT NOT NULL,
+  status TEXT NOT NULL,
+  expires_in TIMESTAMPTZ NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL,
+  updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS project_secret (
+  id TEXT PRIMARY KEY NOT NULL,
+  project_id TEXT NOT NULL REFERENCES project(id),
+  name TEXT NOT NULL,
+  phc TEXT NOT NULL,
+  secret BYTEA NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS usage (
+  id TEXT PRIMARY KEY NOT NULL,
+  event_id TEXT NOT NULL,
+  resource_id TEXT NOT NULL REFERENCES resource(id),
+  cluster_id TEXT,
+  units BIGINT NOT NULL,
+  tier TEXT NOT NULL,
+  "interval" BIGINT NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL
+);
+
+-- project_user
+CREATE INDEX IF NOT EXISTS idx_project_user_user_id ON project_user(user_id);
+CREATE INDEX IF NOT EXISTS idx_project_user_project_id ON project_user(project_id);
+CREATE INDEX IF NOT EXISTS idx_project_user_created_at ON project_user(created_at);
+
+-- project
+CREATE INDEX IF NOT EXISTS idx_project_status ON project(status);
+CREATE INDEX IF NOT EXISTS idx_project_created_at ON project(created_at);
+CREATE INDEX IF NOT EXISTS idx_project_status_namespace ON project(status, namespace);
+
+-- project_secret
+CREATE INDEX IF NOT EXISTS idx_project_secret_project_id ON project_secret(project_id);
+CREATE INDEX IF NOT EXISTS idx_project_secret_created_at ON project_secret(created_at);
+
+-- project_user_invite
+CREATE INDEX IF NOT EXISTS idx_project_user_invite_project_expires_in_status ON project_user_invite(project_id, expires_in, status);
+CREATE INDEX IF NOT EXISTS idx_project_user_invite_code ON project_user_invite(code);
+
+-- resource
+CREATE INDEX IF NOT EXISTS idx_resource_project_id ON resource(project_id);
+CREATE INDEX IF NOT EXISTS idx_resource_created_at ON resource(created_at);
+CREATE INDEX IF NOT EXISTS idx_resource_status ON resource(status);
+CREATE INDEX IF NOT EXISTS idx_resource_category ON resource(category);
+CREATE INDEX IF NOT EXISTS idx_resource_project_id_status_category ON resource(project_id, status, category);
+
+-- usage
+CREATE INDEX IF NOT EXISTS idx_usage_created_at ON usage(created_at);
+CREATE INDEX IF NOT EXISTS idx_usage_resource_id ON usage(resource_id);
+CREATE INDEX IF NOT EXISTS idx_usage_tier ON usage(tier);
+CREATE INDEX IF NOT EXISTS idx_usage_cluster_id ON usage(cluster_id);
@@ -0,0 +1,8 @@
+-- Last offset applied per topic partition, by any of the replicas sharing the cache.
+CREATE TABLE IF NOT EXISTS consumer_offset (
+  topic TEXT NOT NULL,
+  partition INTEGER NOT NULL,
+  "offset" BIGINT NOT NULL,
+  updated_at TIMESTAMPTZ NOT NULL,
+  PRIMARY KEY (topic, partition)
+);
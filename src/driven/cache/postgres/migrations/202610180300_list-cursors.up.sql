@@ -0,0 +1,5 @@
+-- The lists are paged by (created_at, id) after a cursor, newest first.
+CREATE INDEX IF NOT EXISTS idx_project_created_at_id ON project(created_at, id);
+CREATE INDEX IF NOT EXISTS idx_project_user_project_id_created_at ON project_user(project_id, created_at, user_id);
+CREATE INDEX IF NOT EXISTS idx_project_user_invite_project_id_created_at ON project_user_invite(project_id, created_at, id);
+CREATE INDEX IF NOT EXISTS idx_resource_project_id_created_at ON resource(project_id, created_at, id);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_project_organization_id;
+ALTER TABLE project DROP COLUMN IF EXISTS organization_id;
+DROP TABLE IF EXISTS organization_user;
+DROP TABLE IF EXISTS organization;
@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS organization (
+  id TEXT PRIMARY KEY NOT NULL,
+  name TEXT NOT NULL,
+  owner TEXT NOT NULL,
+  billing_provider TEXT NOT NULL,
+  billing_provider_id TEXT NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL,
+  updated_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS organization_user (
+  user_id TEXT NOT NULL,
+  organization_id TEXT NOT NULL REFERENCES organization(id),
+  role TEXT NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL,
+  PRIMARY KEY (user_id, organization_id)
+);
+CREATE INDEX IF NOT EXISTS idx_organization_user_organization_id_created_at ON organization_user(organization_id, created_at, user_id);
+
+-- Organization the project belongs to, NULL when it only has its own members
+ALTER TABLE project ADD COLUMN IF NOT EXISTS organization_id TEXT NULL;
+CREATE INDEX IF NOT EXISTS idx_project_organization_id ON project(organization_id);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_project_ownership_transfer_code;
+DROP INDEX IF EXISTS idx_project_ownership_transfer_project_id;
+DROP TABLE IF EXISTS project_ownership_transfer;
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS project_quota (
+  project_id TEXT PRIMARY KEY NOT NULL,
+  resources TEXT NOT NULL,
+  max_tier BIGINT NULL,
+  workers BIGINT NULL,
+  updated_at TIMESTAMPTZ NOT NULL
+);
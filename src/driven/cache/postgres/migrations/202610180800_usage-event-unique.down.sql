@@ -0,0 +1 @@
+ALTER TABLE usage DROP CONSTRAINT IF EXISTS usage_event_id_resource_id_tier_key;
@@ -0,0 +1,10 @@
+-- Keeps the first row of the usages applied twice before the constraint existed.
+DELETE FROM usage u
+USING usage d
+WHERE u.event_id = d.event_id
+  AND u.resource_id = d.resource_id
+  AND u.tier = d.tier
+  AND (u.created_at, u.id) > (d.created_at, d.id);
+
+ALTER TABLE usage
+  ADD CONSTRAINT usage_event_id_resource_id_tier_key UNIQUE (event_id, resource_id, tier);
@@ -0,0 +1,7 @@
+-- Events applied to the shared projections. Every replica consumes every event, the first one
+-- to insert the row applies it in the same transaction and the others skip it.
+CREATE TABLE IF NOT EXISTS processed_event (
+  event_id TEXT PRIMARY KEY NOT NULL,
+  event_key TEXT NOT NULL,
+  processed_at TIMESTAMPTZ NOT NULL
+);
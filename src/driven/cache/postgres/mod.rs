@@ -0,0 +1,363 @@
+use anyhow::Result;
+use chrono::Utc;
+use sqlx::{
+    migrate::Migrator,
+    pool::PoolConnection,
+    postgres::{PgConnection, Postgres},
+    Transaction,
+};
+use std::{
+    ops::{Deref, DerefMut},
+    time::Duration,
+};
+use tokio::{
+    sync::{Mutex, MutexGuard},
+    time::{sleep, Instant},
+};
+
+use crate::domain::{error::Error, event::EventOffset};
+
+use super::{
+    ledger::EventPosition,
+    migration::{self, MigrationStatus},
+    APPLIED_POLL_INTERVAL,
+};
+
+pub mod organization;
+pub mod project;
+pub mod resource;
+pub mod usage;
+
+static MIGRATOR: Migrator = sqlx::migrate!("src/driven/cache/postgres/migrations");
+
+/// Read model shared by every rpc replica. Each replica consumes every event, `claim` lets only
+/// the first one apply it, and the events delivered again after a consumer group rebalance are
+/// skipped the same way.
+pub struct PostgresCache {
+    db: sqlx::postgres::PgPool,
+    /// Transaction opened with `begin`, the statements of every cache sharing this one run in it
+    /// until it's committed or rolled back.
+    tx: Mutex<Option<Transaction<'static, Postgres>>>,
+}
+
+impl PostgresCache {
+    pub async fn new(url: &str) -> Result<Self> {
+        let db = sqlx::postgres::PgPoolOptions::new().connect(url).await?;
+
+        Ok(Self::from_pool(db))
+    }
+
+    fn from_pool(db: sqlx::postgres::PgPool) -> Self {
+        Self {
+            db,
+            tx: Mutex::new(None),
+        }
+    }
+
+    /// Connection for the next statement, the one of the open transaction if there is one.
+    pub(crate) async fn conn(&self) -> sqlx::Result<PgConn<'_>> {
+        let tx = self.tx.lock().await;
+        if tx.is_some() {
+            return Ok(PgConn::Tx(tx));
+        }
+        drop(tx);
+
+        Ok(PgConn::Pool(Box::new(self.db.acquire().await?)))
+    }
+
+    /// Groups the writes of the following statements, e.g. the projection of an event and its
+    /// `processed_event` row, until `commit` or `rollback`.
+    pub async fn begin(&self) -> crate::domain::Result<()> {
+        let mut tx = self.tx.lock().await;
+        if tx.is_some() {
+            return Err(Error::Unexpected("cache transaction already open".into()));
+        }
+        *tx = Some(self.db.begin().await?);
+
+        Ok(())
+    }
+
+    pub async fn commit(&self) -> crate::domain::Result<()> {
+        if let Some(tx) = self.tx.lock().await.take() {
+            tx.commit().await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn rollback(&self) -> crate::domain::Result<()> {
+        if let Some(tx) = self.tx.lock().await.take() {
+            tx.rollback().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Same as `SqliteCache::savepoint`, a failed statement also aborts the whole postgres
+    /// transaction until it's rolled back to the savepoint.
+    pub async fn savepoint(&self) -> crate::domain::Result<()> {
+        self.execute_in_tx("SAVEPOINT attempt;").await
+    }
+
+    pub async fn release_savepoint(&self) -> crate::domain::Result<()> {
+        self.execute_in_tx("RELEASE SAVEPOINT attempt;").await
+    }
+
+    pub async fn rollback_to_savepoint(&self) -> crate::domain::Result<()> {
+        self.execute_in_tx("ROLLBACK TO SAVEPOINT attempt;").await?;
+        self.execute_in_tx("RELEASE SAVEPOINT attempt;").await
+    }
+
+    async fn execute_in_tx(&self, sql: &str) -> crate::domain::Result<()> {
+        let mut tx = self.tx.lock().await;
+        let Some(tx) = tx.as_mut() else {
+            return Err(Error::Unexpected("no cache transaction open".into()));
+        };
+        sqlx::query(sql).execute(&mut **tx).await?;
+
+        Ok(())
+    }
+
+    /// Records the event as applied to the projections, returns false when a replica already
+    /// did. In the open transaction, a replica applying the same event at the same time waits
+    /// for it to commit, so the projections are written once and in the order of the topic.
+    pub async fn claim(&self, event_id: &str, event_key: &str) -> crate::domain::Result<bool> {
+        let result = sqlx::query(
+            r#"
+                INSERT INTO processed_event (event_id, event_key, processed_at)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (event_id) DO NOTHING;
+            "#,
+        )
+        .bind(event_id)
+        .bind(event_key)
+        .bind(Utc::now())
+        .execute(&mut *self.conn().await?)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn migrate(&self) -> Result<()> {
+        MIGRATOR.run(&self.db).await?;
+
+        Ok(())
+    }
+
+    pub async fn migrate_down(&self, target: i64) -> Result<()> {
+        MIGRATOR.undo(&self.db, target).await?;
+
+        Ok(())
+    }
+
+    pub async fn migrations(&self) -> Result<Vec<MigrationStatus>> {
+        let mut conn = self.db.acquire().await?;
+        migration::status(&MIGRATOR, &mut *conn).await
+    }
+
+    /// The replicas are upgraded one at a time, an old one must not keep applying events to a
+    /// schema migrated by a new one.
+    pub async fn check_schema(&self) -> Result<()> {
+        migration::ensure_known(&self.migrations().await?)
+    }
+
+    pub async fn close(&self) {
+        self.db.close().await
+    }
+
+    /// Moves the applied offset of the partition forward. The ledger of each replica only has the
+    /// partitions it consumes, this table has all of them for the commands waiting on an event.
+    pub async fn record_offset(&self, position: &EventPosition) -> crate::domain::Result<()> {
+        sqlx::query(
+            r#"
+                INSERT INTO consumer_offset (topic, partition, "offset", updated_at)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (topic, partition) DO UPDATE SET
+                    "offset" = GREATEST(consumer_offset."offset", excluded."offset"),
+                    updated_at = excluded.updated_at;
+            "#,
+        )
+        .bind(&position.topic)
+        .bind(position.partition)
+        .bind(position.offset)
+        .bind(Utc::now())
+        .execute(&mut *self.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn wait_applied(
+        &self,
+        offset: &EventOffset,
+        timeout: Duration,
+    ) -> crate::domain::Result<bool> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let applied: Option<i64> = sqlx::query_scalar(
+                r#"
+                    SELECT "offset"
+                    FROM consumer_offset
+                    WHERE topic = $1 AND partition = $2;
+                "#,
+            )
+            .bind(&offset.topic)
+            .bind(offset.partition)
+            .fetch_optional(&self.db)
+            .await?;
+
+            if applied.is_some_and(|applied| applied >= offset.offset) {
+                return Ok(true);
+            }
+            if Instant::now() >= deadline {
+                return Ok(false);
+            }
+            sleep(APPLIED_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Migrated database created for a single test, in the server of `TEST_POSTGRES_URL`.
+    #[cfg(test)]
+    pub async fn ephemeral() -> Result<Self> {
+        let url = std::env::var("TEST_POSTGRES_URL")?;
+        let name = format!("fabric_{}", uuid::Uuid::new_v4().simple());
+
+        let admin = sqlx::postgres::PgPoolOptions::new().connect(&url).await?;
+        sqlx::query(&format!("CREATE DATABASE {name}"))
+            .execute(&admin)
+            .await?;
+        admin.close().await;
+
+        let options: sqlx::postgres::PgConnectOptions = url.parse()?;
+        let db = sqlx::postgres::PgPoolOptions::new()
+            .connect_with(options.database(&name))
+            .await?;
+
+        let out = Self::from_pool(db);
+        out.migrate().await?;
+
+        Ok(out)
+    }
+}
+
+/// Connection of the pool, or of the transaction open in the cache.
+pub(crate) enum PgConn<'a> {
+    Pool(Box<PoolConnection<Postgres>>),
+    Tx(MutexGuard<'a, Option<Transaction<'static, Postgres>>>),
+}
+impl Deref for PgConn<'_> {
+    type Target = PgConnection;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Pool(conn) => conn,
+            Self::Tx(tx) => tx.as_ref().expect("transaction open"),
+        }
+    }
+}
+impl DerefMut for PgConn<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            Self::Pool(conn) => conn,
+            Self::Tx(tx) => tx.as_mut().expect("transaction open"),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::sync::Arc;
+
+    use crate::domain::{
+        project::{cache::ProjectDrivenCache, Project},
+        resource::{cache::ResourceDrivenCache, Resource},
+    };
+
+    use super::{project::PostgresProjectDrivenCache, resource::PostgresResourceDrivenCache, *};
+
+    pub async fn mock_project(postgres_cache: Arc<PostgresCache>) -> Project {
+        let cache = PostgresProjectDrivenCache::new(postgres_cache);
+
+        let project = Project::default();
+        cache.create(&project).await.unwrap();
+
+        project
+    }
+    pub async fn mock_resource(postgres_cache: Arc<PostgresCache>, project_id: &str) -> Resource {
+        let cache = PostgresResourceDrivenCache::new(postgres_cache);
+
+        let resource = Resource {
+            project_id: project_id.to_string(),
+            ..Default::default()
+        };
+        cache.create(&resource).await.unwrap();
+
+        resource
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_revert_and_apply_migrations_again() {
+        let postgres_cache = Arc::new(PostgresCache::ephemeral().await.unwrap());
+
+        postgres_cache.migrate_down(0).await.unwrap();
+        let migrations = postgres_cache.migrations().await.unwrap();
+        assert!(migrations.iter().all(|m| m.known && !m.applied));
+
+        postgres_cache.migrate().await.unwrap();
+        mock_project(postgres_cache.clone()).await;
+        assert!(postgres_cache.check_schema().await.is_ok());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_claim_an_event_once() {
+        let postgres_cache = Arc::new(PostgresCache::ephemeral().await.unwrap());
+
+        postgres_cache.begin().await.unwrap();
+        assert!(postgres_cache
+            .claim("event id", "ProjectCreated")
+            .await
+            .unwrap());
+        postgres_cache.rollback().await.unwrap();
+
+        postgres_cache.begin().await.unwrap();
+        assert!(postgres_cache
+            .claim("event id", "ProjectCreated")
+            .await
+            .unwrap());
+        postgres_cache.commit().await.unwrap();
+
+        assert!(!postgres_cache
+            .claim("event id", "ProjectCreated")
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_roll_back_the_writes_after_the_savepoint() {
+        let postgres_cache = Arc::new(PostgresCache::ephemeral().await.unwrap());
+        let cache = PostgresProjectDrivenCache::new(postgres_cache.clone());
+
+        let project = |namespace: &str| Project {
+            id: namespace.into(),
+            namespace: namespace.into(),
+            ..Default::default()
+        };
+
+        postgres_cache.begin().await.unwrap();
+        cache.create(&project("kept")).await.unwrap();
+        postgres_cache.savepoint().await.unwrap();
+        cache.create(&project("dropped")).await.unwrap();
+        let result = postgres_cache
+            .execute_in_tx("SELECT missing FROM project;")
+            .await;
+        assert!(result.is_err());
+        postgres_cache.rollback_to_savepoint().await.unwrap();
+        postgres_cache.commit().await.unwrap();
+
+        assert!(cache.find_by_id("kept").await.unwrap().is_some());
+        assert!(cache.find_by_id("dropped").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,309 @@
+use sqlx::{postgres::PgRow, Connection, FromRow, Row};
+use std::sync::Arc;
+
+use crate::domain::{
+    error::Error,
+    organization::{cache::OrganizationDrivenCache, Organization, OrganizationUser},
+    pagination::Page,
+    project::ProjectUserRole,
+    Result,
+};
+
+use super::PostgresCache;
+
+pub struct PostgresOrganizationDrivenCache {
+    postgres: Arc<PostgresCache>,
+}
+impl PostgresOrganizationDrivenCache {
+    pub fn new(postgres: Arc<PostgresCache>) -> Self {
+        Self { postgres }
+    }
+}
+#[async_trait::async_trait]
+impl OrganizationDrivenCache for PostgresOrganizationDrivenCache {
+    async fn find(&self, user_id: &str, page: &Page) -> Result<Vec<Organization>> {
+        let organizations = sqlx::query_as::<_, Organization>(
+            r#"
+                SELECT
+                    o.id,
+                    o.name,
+                    o.owner,
+                    o.billing_provider,
+                    o.billing_provider_id,
+                    o.created_at,
+                    o.updated_at
+                FROM organization_user ou
+                INNER JOIN organization o ON o.id = ou.organization_id
+                WHERE ou.user_id = $1
+                    AND (
+                        $4::timestamptz IS NULL
+                        OR (o.created_at, o.id) < ($4::timestamptz, $5::text)
+                    )
+                ORDER BY o.created_at DESC, o.id DESC
+                LIMIT $2
+                OFFSET $3;
+            "#,
+        )
+        .bind(user_id)
+        .bind(i64::from(page.page_size))
+        .bind(i64::from(page.offset()))
+        .bind(page.cursor_created_at())
+        .bind(page.cursor_id())
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(organizations)
+    }
+
+    async fn find_by_id(&self, id: &str) -> Result<Option<Organization>> {
+        let organization = sqlx::query_as::<_, Organization>(
+            r#"
+                SELECT
+                    o.id,
+                    o.name,
+                    o.owner,
+                    o.billing_provider,
+                    o.billing_provider_id,
+                    o.created_at,
+                    o.updated_at
+                FROM organization o
+                WHERE o.id = $1;
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(organization)
+    }
+
+    async fn find_users(
+        &self,
+        organization_id: &str,
+        page: &Page,
+    ) -> Result<Vec<OrganizationUser>> {
+        let users = sqlx::query_as::<_, OrganizationUser>(
+            r#"
+                SELECT
+                    ou.user_id,
+                    ou.organization_id,
+                    ou.role,
+                    ou.created_at
+                FROM organization_user ou
+                WHERE ou.organization_id = $1
+                    AND (
+                        $4::timestamptz IS NULL
+                        OR (ou.created_at, ou.user_id) < ($4::timestamptz, $5::text)
+                    )
+                ORDER BY ou.created_at DESC, ou.user_id DESC
+                LIMIT $2
+                OFFSET $3;
+            "#,
+        )
+        .bind(organization_id)
+        .bind(i64::from(page.page_size))
+        .bind(i64::from(page.offset()))
+        .bind(page.cursor_created_at())
+        .bind(page.cursor_id())
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(users)
+    }
+
+    async fn find_user_permission(
+        &self,
+        user_id: &str,
+        organization_id: &str,
+    ) -> Result<Option<OrganizationUser>> {
+        let user = sqlx::query_as::<_, OrganizationUser>(
+            r#"
+                SELECT
+                    ou.user_id,
+                    ou.organization_id,
+                    ou.role,
+                    ou.created_at
+                FROM organization_user ou
+                WHERE ou.user_id = $1 AND ou.organization_id = $2;
+            "#,
+        )
+        .bind(user_id)
+        .bind(organization_id)
+        .fetch_optional(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn create(&self, organization: &Organization) -> Result<()> {
+        let mut conn = self.postgres.conn().await?;
+        let mut tx = conn.begin().await?;
+
+        sqlx::query(
+            r#"
+                INSERT INTO organization (
+                    id,
+                    name,
+                    owner,
+                    billing_provider,
+                    billing_provider_id,
+                    created_at,
+                    updated_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (id) DO NOTHING;
+            "#,
+        )
+        .bind(&organization.id)
+        .bind(&organization.name)
+        .bind(&organization.owner)
+        .bind(&organization.billing_provider)
+        .bind(&organization.billing_provider_id)
+        .bind(organization.created_at)
+        .bind(organization.updated_at)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+                INSERT INTO organization_user (user_id, organization_id, role, created_at)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (user_id, organization_id) DO NOTHING;
+            "#,
+        )
+        .bind(&organization.owner)
+        .bind(&organization.id)
+        .bind(ProjectUserRole::Owner.to_string())
+        .bind(organization.created_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn create_user(&self, user: &OrganizationUser) -> Result<()> {
+        sqlx::query(
+            r#"
+                INSERT INTO organization_user (user_id, organization_id, role, created_at)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (user_id, organization_id) DO NOTHING;
+            "#,
+        )
+        .bind(&user.user_id)
+        .bind(&user.organization_id)
+        .bind(user.role.to_string())
+        .bind(user.created_at)
+        .execute(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_user(&self, organization_id: &str, user_id: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+                DELETE FROM organization_user
+                WHERE organization_id = $1 AND user_id = $2;
+            "#,
+        )
+        .bind(organization_id)
+        .bind(user_id)
+        .execute(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl FromRow<'_, PgRow> for Organization {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            owner: row.try_get("owner")?,
+            billing_provider: row.try_get("billing_provider")?,
+            billing_provider_id: row.try_get("billing_provider_id")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, PgRow> for OrganizationUser {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let role: &str = row.try_get("role")?;
+
+        Ok(Self {
+            user_id: row.try_get("user_id")?,
+            organization_id: row.try_get("organization_id")?,
+            role: role
+                .parse()
+                .map_err(|err: Error| sqlx::Error::Decode(err.into()))?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::{
+        domain::project::{cache::ProjectDrivenCache, ProjectOrganizationChange},
+        driven::cache::postgres::{project::PostgresProjectDrivenCache, tests::mock_project},
+    };
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_inherit_organization_members_in_attached_projects() {
+        let postgres_cache = Arc::new(PostgresCache::ephemeral().await.unwrap());
+        let cache = PostgresOrganizationDrivenCache::new(postgres_cache.clone());
+        let project_cache = PostgresProjectDrivenCache::new(postgres_cache.clone());
+
+        let organization = Organization::default();
+        cache.create(&organization).await.unwrap();
+        cache.create(&organization).await.unwrap();
+        cache
+            .create_user(&OrganizationUser {
+                user_id: "member id".into(),
+                organization_id: organization.id.clone(),
+                role: ProjectUserRole::Member,
+                created_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        let project = mock_project(postgres_cache).await;
+        project_cache
+            .change_organization(&ProjectOrganizationChange {
+                project_id: project.id.clone(),
+                organization_id: Some(organization.id.clone()),
+                billing_provider_id: organization.billing_provider_id.clone(),
+                changed_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        let permission = project_cache
+            .find_user_permission("member id", &project.id)
+            .await
+            .unwrap();
+        assert!(permission.is_some_and(|p| p.role == ProjectUserRole::Member));
+
+        let permission = project_cache
+            .find_user_permission(&project.owner, &project.id)
+            .await
+            .unwrap();
+        assert!(permission.is_some_and(|p| p.role == ProjectUserRole::Owner));
+
+        let projects = project_cache
+            .find("member id", &Page::default())
+            .await
+            .unwrap();
+        assert!(projects.len() == 1);
+        assert!(projects[0].billing_provider_id == organization.billing_provider_id);
+    }
+}
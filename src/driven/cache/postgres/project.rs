@@ -0,0 +1,1337 @@
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::PgRow, Connection, FromRow, Postgres, Row, Transaction};
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use crate::domain::{
+    error::Error,
+    event::EventOffset,
+    pagination::Page,
+    project::{
+        cache::{ProjectDrivenCache, ProjectDrivenCacheBackoffice},
+        quota::ProjectQuota,
+        Project, ProjectOrganizationChange, ProjectOwnerChange, ProjectOwnershipTransfer,
+        ProjectOwnershipTransferStatus, ProjectSecret, ProjectStatus, ProjectUpdate, ProjectUser,
+        ProjectUserInvite, ProjectUserInviteStatus, ProjectUserProject, ProjectUserRole,
+    },
+    resource::ResourceStatus,
+    Result,
+};
+
+use super::{super::DEFAULT_QUOTA_ID, PostgresCache};
+
+pub struct PostgresProjectDrivenCache {
+    postgres: Arc<PostgresCache>,
+}
+impl PostgresProjectDrivenCache {
+    pub fn new(postgres: Arc<PostgresCache>) -> Self {
+        Self { postgres }
+    }
+}
+#[async_trait::async_trait]
+impl ProjectDrivenCache for PostgresProjectDrivenCache {
+    async fn find(&self, user_id: &str, page: &Page) -> Result<Vec<Project>> {
+        let query = String::from(
+            r#"
+                SELECT
+                    p.id,
+                    p.namespace,
+                    p.name,
+                    p.owner,
+                    p.status,
+                    p.billing_provider,
+                    p.billing_provider_id,
+                    p.billing_subscription_id,
+                    p.cluster_id,
+                    p.organization_id,
+                    p.created_at,
+                    p.updated_at
+                FROM project p
+                WHERE p.status != $2
+                    AND (
+                        p.id IN (SELECT project_id FROM project_user WHERE user_id = $1)
+                        OR p.organization_id IN (
+                            SELECT organization_id FROM organization_user WHERE user_id = $1
+                        )
+                    )
+                    AND (
+                        $5::timestamptz IS NULL
+                        OR (p.created_at, p.id) < ($5::timestamptz, $6::text)
+                    )
+                ORDER BY --ORDER--
+                LIMIT $3
+                OFFSET $4;
+            "#,
+        );
+
+        // The offset pages keep the order by membership time they always had, the cursors need
+        // the creation time and id the keyset is on.
+        let query = match page.by_cursor {
+            true => query.replace("--ORDER--", "p.created_at DESC, p.id DESC"),
+            false => query.replace(
+                "--ORDER--",
+                r#"
+                    COALESCE(
+                        (
+                            SELECT pu.created_at FROM project_user pu
+                            WHERE pu.project_id = p.id AND pu.user_id = $1
+                        ),
+                        p.created_at
+                    ) DESC,
+                    p.id DESC
+                "#,
+            ),
+        };
+
+        let projects = sqlx::query_as::<_, Project>(&query)
+            .bind(user_id)
+            .bind(ProjectStatus::Deleted.to_string())
+            .bind(i64::from(page.page_size))
+            .bind(i64::from(page.offset()))
+            .bind(page.cursor_created_at())
+            .bind(page.cursor_id())
+            .fetch_all(&mut *self.postgres.conn().await?)
+            .await?;
+
+        Ok(projects)
+    }
+    async fn find_by_namespace(&self, namespace: &str) -> Result<Option<Project>> {
+        let project = sqlx::query_as::<_, Project>(
+            r#"
+                SELECT
+                    p.id,
+                    p.namespace,
+                    p.name,
+                    p.owner,
+                    p.status,
+                    p.billing_provider,
+                    p.billing_provider_id,
+                    p.billing_subscription_id,
+                    p.cluster_id,
+                    p.organization_id,
+                    p.created_at,
+                    p.updated_at
+                FROM project p
+                WHERE p.namespace = $1 and p.status != $2;
+            "#,
+        )
+        .bind(namespace)
+        .bind(ProjectStatus::Deleted.to_string())
+        .fetch_optional(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(project)
+    }
+    async fn find_by_id(&self, id: &str) -> Result<Option<Project>> {
+        let project = sqlx::query_as::<_, Project>(
+            r#"
+                SELECT
+                    p.id,
+                    p.namespace,
+                    p.name,
+                    p.owner,
+                    p.status,
+                    p.billing_provider,
+                    p.billing_provider_id,
+                    p.billing_subscription_id,
+                    p.cluster_id,
+                    p.organization_id,
+                    p.created_at,
+                    p.updated_at
+                FROM project p
+                WHERE p.id = $1 and p.status != $2;
+            "#,
+        )
+        .bind(id)
+        .bind(ProjectStatus::Deleted.to_string())
+        .fetch_optional(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(project)
+    }
+
+    async fn find_by_billing_provider_id(&self, billing_provider_id: &str) -> Result<Vec<Project>> {
+        let projects = sqlx::query_as::<_, Project>(
+            r#"
+                SELECT
+                    p.id,
+                    p.namespace,
+                    p.name,
+                    p.owner,
+                    p.status,
+                    p.billing_provider,
+                    p.billing_provider_id,
+                    p.billing_subscription_id,
+                    p.cluster_id,
+                    p.organization_id,
+                    p.created_at,
+                    p.updated_at
+                FROM project p
+                WHERE p.billing_provider_id = $1 and p.status != $2
+                ORDER BY p.created_at;
+            "#,
+        )
+        .bind(billing_provider_id)
+        .bind(ProjectStatus::Deleted.to_string())
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(projects)
+    }
+
+    async fn find_deleted_by_id(&self, id: &str) -> Result<Option<Project>> {
+        let project = sqlx::query_as::<_, Project>(
+            r#"
+                SELECT
+                    p.id,
+                    p.namespace,
+                    p.name,
+                    p.owner,
+                    p.status,
+                    p.billing_provider,
+                    p.billing_provider_id,
+                    p.billing_subscription_id,
+                    p.cluster_id,
+                    p.organization_id,
+                    p.created_at,
+                    p.updated_at
+                FROM project p
+                WHERE p.id = $1 and p.status = $2;
+            "#,
+        )
+        .bind(id)
+        .bind(ProjectStatus::Deleted.to_string())
+        .fetch_optional(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(project)
+    }
+
+    async fn create(&self, project: &Project) -> Result<()> {
+        let mut conn = self.postgres.conn().await?;
+        let mut tx = conn.begin().await?;
+
+        sqlx::query(
+            r#"
+                INSERT INTO project (
+                    id,
+                    namespace,
+                    name,
+                    owner,
+                    status,
+                    billing_provider,
+                    billing_provider_id,
+                    billing_subscription_id,
+                    cluster_id,
+                    created_at,
+                    updated_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                ON CONFLICT (id) DO NOTHING;
+            "#,
+        )
+        .bind(&project.id)
+        .bind(&project.namespace)
+        .bind(&project.name)
+        .bind(&project.owner)
+        .bind(project.status.to_string())
+        .bind(&project.billing_provider)
+        .bind(&project.billing_provider_id)
+        .bind(&project.billing_subscription_id)
+        .bind(&project.cluster_id)
+        .bind(project.created_at)
+        .bind(project.updated_at)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+                INSERT INTO project_user (
+                    project_id,
+                    user_id,
+                    role,
+                    created_at
+                )
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (user_id, project_id) DO NOTHING;
+            "#,
+        )
+        .bind(&project.id)
+        .bind(&project.owner)
+        .bind(ProjectUserRole::Owner.to_string())
+        .bind(project.created_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn update(&self, project_update: &ProjectUpdate) -> Result<()> {
+        if project_update.name.is_none()
+            && project_update.status.is_none()
+            && project_update.billing_subscription_id.is_none()
+        {
+            return Ok(());
+        }
+
+        sqlx::query(
+            r#"
+                UPDATE project
+                SET
+                    name = COALESCE($1, name),
+                    status = COALESCE($2, status),
+                    billing_subscription_id = CASE
+                        WHEN $6 THEN NULL
+                        ELSE COALESCE($3, billing_subscription_id)
+                    END,
+                    updated_at = $4
+                WHERE id = $5;
+            "#,
+        )
+        .bind(&project_update.name)
+        .bind(
+            project_update
+                .status
+                .as_ref()
+                .map(|status| status.to_string()),
+        )
+        .bind(project_update.billing_subscription_id.clone().flatten())
+        .bind(project_update.updated_at)
+        .bind(&project_update.id)
+        .bind(project_update.billing_subscription_id == Some(None))
+        .execute(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn change_owner(&self, change: &ProjectOwnerChange) -> Result<()> {
+        let mut conn = self.postgres.conn().await?;
+        let mut tx = conn.begin().await?;
+        update_owner(&mut tx, change).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn change_organization(&self, change: &ProjectOrganizationChange) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE project
+                SET organization_id = $1, billing_provider_id = $2, updated_at = $3
+                WHERE id = $4;
+            "#,
+        )
+        .bind(&change.organization_id)
+        .bind(&change.billing_provider_id)
+        .bind(change.changed_at)
+        .bind(&change.project_id)
+        .execute(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str, deleted_at: &DateTime<Utc>) -> Result<()> {
+        let mut conn = self.postgres.conn().await?;
+        let mut tx = conn.begin().await?;
+
+        sqlx::query(
+            r#"
+                UPDATE project
+                SET status = $2, updated_at = $3
+                WHERE id = $1;
+            "#,
+        )
+        .bind(id)
+        .bind(ProjectStatus::Deleted.to_string())
+        .bind(deleted_at)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+                UPDATE resource
+                SET status = $2, updated_at = $3
+                WHERE project_id = $1 AND status != $2;
+            "#,
+        )
+        .bind(id)
+        .bind(ResourceStatus::Deleted.to_string())
+        .bind(deleted_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn restore(&self, id: &str, restored_at: &DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE project
+                SET status = $2, updated_at = $3
+                WHERE id = $1;
+            "#,
+        )
+        .bind(id)
+        .bind(ProjectStatus::Active.to_string())
+        .bind(restored_at)
+        .execute(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn create_secret(&self, secret: &ProjectSecret) -> Result<()> {
+        sqlx::query(
+            r#"
+                INSERT INTO project_secret (
+                    id,
+                    project_id,
+                    name,
+                    phc,
+                    secret,
+                    created_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (id) DO NOTHING;
+            "#,
+        )
+        .bind(&secret.id)
+        .bind(&secret.project_id)
+        .bind(&secret.name)
+        .bind(&secret.phc)
+        .bind(&secret.secret)
+        .bind(secret.created_at)
+        .execute(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+    async fn find_secrets(&self, project: &str) -> Result<Vec<ProjectSecret>> {
+        let secrets = sqlx::query_as::<_, ProjectSecret>(
+            r#"
+                SELECT
+                    ps.id,
+                    ps.project_id,
+                    ps.name,
+                    ps.phc,
+                    ps.secret,
+                    ps.created_at
+                FROM project_secret ps
+                INNER JOIN project p ON p.id = ps.project_id
+                WHERE ps.project_id = $1 OR p.namespace = $1
+                ORDER BY ps.created_at DESC;
+            "#,
+        )
+        .bind(project)
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(secrets)
+    }
+    async fn find_secret_by_id(&self, id: &str) -> Result<Option<ProjectSecret>> {
+        let secret = sqlx::query_as::<_, ProjectSecret>(
+            r#"
+                SELECT
+                    ps.id,
+                    ps.project_id,
+                    ps.name,
+                    ps.phc,
+                    ps.secret,
+                    ps.created_at
+                FROM project_secret ps
+                WHERE ps.id = $1;
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(secret)
+    }
+    async fn delete_secret(&self, id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM project_secret WHERE id = $1;")
+            .bind(id)
+            .execute(&mut *self.postgres.conn().await?)
+            .await?;
+
+        Ok(())
+    }
+    async fn find_user_permission(
+        &self,
+        user_id: &str,
+        project_id: &str,
+    ) -> Result<Option<ProjectUser>> {
+        let project_user = sqlx::query_as::<_, ProjectUser>(
+            r#"
+                SELECT user_id, project_id, role, created_at
+                FROM (
+                    SELECT
+                        pu.user_id,
+                        pu.project_id,
+                        pu.role,
+                        pu.created_at
+                    FROM project_user pu
+                    WHERE pu.user_id = $1 and pu.project_id = $2
+                    UNION ALL
+                    SELECT
+                        ou.user_id,
+                        p.id AS project_id,
+                        ou.role,
+                        ou.created_at
+                    FROM project p
+                    INNER JOIN organization_user ou ON ou.organization_id = p.organization_id
+                    WHERE ou.user_id = $1 and p.id = $2
+                ) permission
+                ORDER BY CASE role
+                    WHEN 'owner' THEN 0
+                    WHEN 'admin' THEN 1
+                    WHEN 'developer' THEN 2
+                    WHEN 'member' THEN 3
+                    WHEN 'viewer' THEN 4
+                    ELSE 5
+                END
+                LIMIT 1;
+            "#,
+        )
+        .bind(user_id)
+        .bind(project_id)
+        .fetch_optional(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(project_user)
+    }
+
+    async fn find_users(&self, project_id: &str, page: &Page) -> Result<Vec<ProjectUser>> {
+        let users = sqlx::query_as::<_, ProjectUser>(
+            r#"
+                SELECT
+                    pu.user_id,
+                    pu.project_id,
+                    pu.role,
+                    pu.created_at
+                FROM project_user pu
+                WHERE pu.project_id = $1
+                    AND (
+                        $4::timestamptz IS NULL
+                        OR (pu.created_at, pu.user_id) < ($4::timestamptz, $5::text)
+                    )
+                ORDER BY pu.created_at DESC, pu.user_id DESC
+                LIMIT $2
+                OFFSET $3;
+            "#,
+        )
+        .bind(project_id)
+        .bind(i64::from(page.page_size))
+        .bind(i64::from(page.offset()))
+        .bind(page.cursor_created_at())
+        .bind(page.cursor_id())
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(users)
+    }
+
+    async fn find_user_invites(
+        &self,
+        project_id: &str,
+        page: &Page,
+    ) -> Result<Vec<ProjectUserInvite>> {
+        let invites = sqlx::query_as::<_, ProjectUserInvite>(
+            r#"
+                SELECT
+                    pui.id,
+                    pui.project_id,
+                    pui.email,
+                    pui.role,
+                    pui.code,
+                    pui.status,
+                    pui.expires_in,
+                    pui.created_at,
+                    pui.updated_at
+                FROM project_user_invite pui
+                WHERE pui.project_id = $1 AND $5 <= pui.expires_in AND pui.status = $2
+                    AND (
+                        $6::timestamptz IS NULL
+                        OR (pui.created_at, pui.id) < ($6::timestamptz, $7::text)
+                    )
+                ORDER BY pui.created_at DESC, pui.id DESC
+                LIMIT $3
+                OFFSET $4;
+            "#,
+        )
+        .bind(project_id)
+        .bind(ProjectUserInviteStatus::Sent.to_string())
+        .bind(i64::from(page.page_size))
+        .bind(i64::from(page.offset()))
+        .bind(Utc::now())
+        .bind(page.cursor_created_at())
+        .bind(page.cursor_id())
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(invites)
+    }
+    async fn find_user_invite_by_id(&self, id: &str) -> Result<Option<ProjectUserInvite>> {
+        let invite = sqlx::query_as::<_, ProjectUserInvite>(
+            r#"
+                SELECT
+                    pui.id,
+                    pui.project_id,
+                    pui.email,
+                    pui.role,
+                    pui.code,
+                    pui.status,
+                    pui.expires_in,
+                    pui.created_at,
+                    pui.updated_at
+                FROM project_user_invite pui
+                WHERE pui.id = $1;
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(invite)
+    }
+    async fn find_user_invite_by_code(&self, code: &str) -> Result<Option<ProjectUserInvite>> {
+        let invite = sqlx::query_as::<_, ProjectUserInvite>(
+            r#"
+                SELECT
+                    pui.id,
+                    pui.project_id,
+                    pui.email,
+                    pui.role,
+                    pui.code,
+                    pui.status,
+                    pui.expires_in,
+                    pui.created_at,
+                    pui.updated_at
+                FROM project_user_invite pui
+                WHERE pui.code = $1;
+            "#,
+        )
+        .bind(code)
+        .fetch_optional(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(invite)
+    }
+
+    async fn create_user_invite(&self, invite: &ProjectUserInvite) -> Result<()> {
+        sqlx::query(
+            r#"
+                INSERT INTO project_user_invite (
+                    id,
+                    project_id,
+                    email,
+                    role,
+                    code,
+                    status,
+                    expires_in,
+                    created_at,
+                    updated_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                ON CONFLICT (id) DO NOTHING;
+            "#,
+        )
+        .bind(&invite.id)
+        .bind(&invite.project_id)
+        .bind(&invite.email)
+        .bind(invite.role.to_string())
+        .bind(&invite.code)
+        .bind(invite.status.to_string())
+        .bind(invite.expires_in)
+        .bind(invite.created_at)
+        .bind(invite.updated_at)
+        .execute(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn create_user_acceptance(&self, invite_id: &str, user: &ProjectUser) -> Result<()> {
+        let mut conn = self.postgres.conn().await?;
+        let mut tx = conn.begin().await?;
+
+        sqlx::query(
+            r#"
+                INSERT INTO project_user (
+                    project_id,
+                    user_id,
+                    role,
+                    created_at
+                )
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (user_id, project_id) DO NOTHING;
+            "#,
+        )
+        .bind(&user.project_id)
+        .bind(&user.user_id)
+        .bind(user.role.to_string())
+        .bind(user.created_at)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+                UPDATE project_user_invite
+                SET status = $1, updated_at = $2
+                WHERE id = $3;
+            "#,
+        )
+        .bind(ProjectUserInviteStatus::Accepted.to_string())
+        .bind(user.created_at)
+        .bind(invite_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn delete_user_invite(&self, invite_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM project_user_invite WHERE id = $1;")
+            .bind(invite_id)
+            .execute(&mut *self.postgres.conn().await?)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_user(&self, project_id: &str, id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM project_user WHERE project_id = $1 AND user_id = $2;")
+            .bind(project_id)
+            .bind(id)
+            .execute(&mut *self.postgres.conn().await?)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn find_ownership_transfer_by_id(
+        &self,
+        id: &str,
+    ) -> Result<Option<ProjectOwnershipTransfer>> {
+        let transfer = sqlx::query_as::<_, ProjectOwnershipTransfer>(
+            r#"
+                SELECT
+                    pot.id,
+                    pot.project_id,
+                    pot.previous_owner,
+                    pot.new_owner,
+                    pot.code,
+                    pot.status,
+                    pot.expires_in,
+                    pot.created_at,
+                    pot.updated_at
+                FROM project_ownership_transfer pot
+                WHERE pot.id = $1;
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(transfer)
+    }
+
+    async fn find_ownership_transfer_by_code(
+        &self,
+        code: &str,
+    ) -> Result<Option<ProjectOwnershipTransfer>> {
+        let transfer = sqlx::query_as::<_, ProjectOwnershipTransfer>(
+            r#"
+                SELECT
+                    pot.id,
+                    pot.project_id,
+                    pot.previous_owner,
+                    pot.new_owner,
+                    pot.code,
+                    pot.status,
+                    pot.expires_in,
+                    pot.created_at,
+                    pot.updated_at
+                FROM project_ownership_transfer pot
+                WHERE pot.code = $1;
+            "#,
+        )
+        .bind(code)
+        .fetch_optional(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(transfer)
+    }
+
+    async fn create_ownership_transfer(&self, transfer: &ProjectOwnershipTransfer) -> Result<()> {
+        sqlx::query(
+            r#"
+                INSERT INTO project_ownership_transfer (
+                    id,
+                    project_id,
+                    previous_owner,
+                    new_owner,
+                    code,
+                    status,
+                    expires_in,
+                    created_at,
+                    updated_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                ON CONFLICT (id) DO NOTHING;
+            "#,
+        )
+        .bind(&transfer.id)
+        .bind(&transfer.project_id)
+        .bind(&transfer.previous_owner)
+        .bind(&transfer.new_owner)
+        .bind(&transfer.code)
+        .bind(transfer.status.to_string())
+        .bind(transfer.expires_in)
+        .bind(transfer.created_at)
+        .bind(transfer.updated_at)
+        .execute(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn accept_ownership_transfer(
+        &self,
+        transfer_id: &str,
+        change: &ProjectOwnerChange,
+    ) -> Result<()> {
+        let mut conn = self.postgres.conn().await?;
+        let mut tx = conn.begin().await?;
+        update_owner(&mut tx, change).await?;
+
+        sqlx::query(
+            r#"
+                UPDATE project_ownership_transfer
+                SET status = $1, updated_at = $2
+                WHERE id = $3;
+            "#,
+        )
+        .bind(ProjectOwnershipTransferStatus::Accepted.to_string())
+        .bind(change.changed_at)
+        .bind(transfer_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn cancel_ownership_transfer(
+        &self,
+        transfer_id: &str,
+        cancelled_at: &DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE project_ownership_transfer
+                SET status = $1, updated_at = $2
+                WHERE id = $3;
+            "#,
+        )
+        .bind(ProjectOwnershipTransferStatus::Cancelled.to_string())
+        .bind(cancelled_at)
+        .bind(transfer_id)
+        .execute(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_quota(&self, project_id: &str) -> Result<Option<ProjectQuota>> {
+        let quota = sqlx::query_as::<_, ProjectQuota>(
+            r#"
+                SELECT project_id, resources, max_tier, workers, updated_at
+                FROM project_quota
+                WHERE project_id = $1;
+            "#,
+        )
+        .bind(project_id)
+        .fetch_optional(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(quota)
+    }
+
+    async fn find_default_quota(&self) -> Result<Option<ProjectQuota>> {
+        self.find_quota(DEFAULT_QUOTA_ID).await
+    }
+
+    async fn update_quota(&self, quota: &ProjectQuota) -> Result<()> {
+        let resources: BTreeMap<_, _> = quota.resources.iter().collect();
+
+        sqlx::query(
+            r#"
+                INSERT INTO project_quota (project_id, resources, max_tier, workers, updated_at)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (project_id) DO UPDATE SET
+                    resources = excluded.resources,
+                    max_tier = excluded.max_tier,
+                    workers = excluded.workers,
+                    updated_at = excluded.updated_at;
+            "#,
+        )
+        .bind(quota.project_id.as_deref().unwrap_or(DEFAULT_QUOTA_ID))
+        .bind(serde_json::to_string(&resources)?)
+        .bind(quota.max_tier.map(i64::from))
+        .bind(quota.workers.map(i64::from))
+        .bind(quota.updated_at)
+        .execute(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn wait_applied(&self, offset: &EventOffset, timeout: Duration) -> Result<bool> {
+        self.postgres.wait_applied(offset, timeout).await
+    }
+}
+#[async_trait::async_trait]
+impl ProjectDrivenCacheBackoffice for PostgresProjectDrivenCache {
+    async fn find_by_user_id(&self, id: &str) -> Result<Vec<Project>> {
+        let projects = sqlx::query_as::<_, Project>(
+            r#"
+                SELECT
+                    p.id,
+                    p.namespace,
+                    p.name,
+                    p.owner,
+                    p.status,
+                    p.billing_provider,
+                    p.billing_provider_id,
+                    p.billing_subscription_id,
+                    p.cluster_id,
+                    p.organization_id,
+                    p.created_at,
+                    p.updated_at
+                FROM project p
+                INNER JOIN project_user pu ON pu.project_id = p.id
+                WHERE pu.user_id = $1;
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(projects)
+    }
+
+    async fn find_by_namespace(&self, namespace: &str) -> Result<Option<Project>> {
+        let project = sqlx::query_as::<_, Project>(
+            r#"
+                SELECT
+                    p.id,
+                    p.namespace,
+                    p.name,
+                    p.owner,
+                    p.status,
+                    p.billing_provider,
+                    p.billing_provider_id,
+                    p.billing_subscription_id,
+                    p.cluster_id,
+                    p.organization_id,
+                    p.created_at,
+                    p.updated_at
+                FROM project p
+                WHERE p.namespace = $1;
+            "#,
+        )
+        .bind(namespace)
+        .fetch_optional(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(project)
+    }
+
+    async fn find_by_resource_spec(&self, value: &str) -> Result<Vec<Project>> {
+        let projects = sqlx::query_as::<_, Project>(
+            r#"
+                SELECT
+                    p.id,
+                    p.namespace,
+                    p.name,
+                    p.owner,
+                    p.status,
+                    p.billing_provider,
+                    p.billing_provider_id,
+                    p.billing_subscription_id,
+                    p.cluster_id,
+                    p.organization_id,
+                    p.created_at,
+                    p.updated_at
+                FROM project p
+                WHERE p.id IN (SELECT project_id FROM resource r WHERE r.spec LIKE $1);
+            "#,
+        )
+        .bind(format!("%{value}%"))
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(projects)
+    }
+
+    async fn find_by_resource_name(&self, resource_name: &str) -> Result<Vec<Project>> {
+        let projects = sqlx::query_as::<_, Project>(
+            r#"
+                SELECT
+                    p.id,
+                    p.namespace,
+                    p.name,
+                    p.owner,
+                    p.status,
+                    p.billing_provider,
+                    p.billing_provider_id,
+                    p.billing_subscription_id,
+                    p.cluster_id,
+                    p.organization_id,
+                    p.created_at,
+                    p.updated_at
+                FROM project p
+                WHERE p.id IN (SELECT project_id FROM resource r WHERE r.name = $1);
+            "#,
+        )
+        .bind(resource_name)
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(projects)
+    }
+
+    async fn find_new_users(&self, after: &str) -> Result<Vec<ProjectUserProject>> {
+        let users = sqlx::query_as::<_, ProjectUserProject>(
+            r#"
+                SELECT
+                    pu.user_id,
+                    pu.project_id,
+                    pu.role,
+                    pu.created_at,
+                    p.name as project_name,
+                    p.namespace as project_namespace,
+                    p.owner as project_owner,
+                    p.status as project_status,
+                    p.billing_provider as project_billing_provider,
+                    p.billing_provider_id as project_billing_provider_id
+                FROM project_user pu
+                LEFT JOIN project_user spu ON
+                    spu.user_id = pu.user_id
+                    AND spu.created_at < $1::TIMESTAMPTZ
+                    AND spu.role = 'owner'
+                INNER JOIN project p ON p.id = pu.project_id AND p.owner = pu.user_id
+                WHERE pu.created_at >= $1::TIMESTAMPTZ
+                GROUP BY pu.user_id, pu.project_id, pu.role, pu.created_at, p.id
+                HAVING COUNT(spu.user_id) = 0;
+            "#,
+        )
+        .bind(after)
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(users)
+    }
+}
+
+async fn update_owner(
+    tx: &mut Transaction<'_, Postgres>,
+    change: &ProjectOwnerChange,
+) -> Result<()> {
+    sqlx::query(
+        r#"
+            UPDATE project
+            SET owner = $1, updated_at = $2
+            WHERE id = $3;
+        "#,
+    )
+    .bind(&change.new_owner)
+    .bind(change.changed_at)
+    .bind(&change.project_id)
+    .execute(&mut **tx)
+    .await?;
+
+    for (user_id, role) in [
+        (&change.new_owner, ProjectUserRole::Owner),
+        (&change.previous_owner, ProjectUserRole::Member),
+    ] {
+        sqlx::query(
+            r#"
+                UPDATE project_user
+                SET role = $1
+                WHERE project_id = $2 AND user_id = $3;
+            "#,
+        )
+        .bind(role.to_string())
+        .bind(&change.project_id)
+        .bind(user_id)
+        .execute(&mut **tx)
+        .await?;
+    }
+
+    Ok(())
+}
+
+impl FromRow<'_, PgRow> for Project {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let status: &str = row.try_get("status")?;
+
+        Ok(Self {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            namespace: row.try_get("namespace")?,
+            owner: row.try_get("owner")?,
+            status: status
+                .parse()
+                .map_err(|err: Error| sqlx::Error::Decode(err.into()))?,
+            billing_provider: row.try_get("billing_provider")?,
+            billing_provider_id: row.try_get("billing_provider_id")?,
+            billing_subscription_id: row.try_get("billing_subscription_id")?,
+            cluster_id: row.try_get("cluster_id")?,
+            organization_id: row.try_get("organization_id")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, PgRow> for ProjectSecret {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            project_id: row.try_get("project_id")?,
+            name: row.try_get("name")?,
+            phc: row.try_get("phc")?,
+            secret: row.try_get("secret")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, PgRow> for ProjectUser {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let role: &str = row.try_get("role")?;
+
+        Ok(Self {
+            user_id: row.try_get("user_id")?,
+            project_id: row.try_get("project_id")?,
+            role: role
+                .parse()
+                .map_err(|err: Error| sqlx::Error::Decode(err.into()))?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, PgRow> for ProjectUserInvite {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let role: &str = row.try_get("role")?;
+        let status: &str = row.try_get("status")?;
+
+        Ok(Self {
+            id: row.try_get("id")?,
+            project_id: row.try_get("project_id")?,
+            email: row.try_get("email")?,
+            code: row.try_get("code")?,
+            role: role
+                .parse()
+                .map_err(|err: Error| sqlx::Error::Decode(err.into()))?,
+            status: status
+                .parse()
+                .map_err(|err: Error| sqlx::Error::Decode(err.into()))?,
+            expires_in: row.try_get("expires_in")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, PgRow> for ProjectOwnershipTransfer {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let status: &str = row.try_get("status")?;
+
+        Ok(Self {
+            id: row.try_get("id")?,
+            project_id: row.try_get("project_id")?,
+            previous_owner: row.try_get("previous_owner")?,
+            new_owner: row.try_get("new_owner")?,
+            code: row.try_get("code")?,
+            status: status
+                .parse()
+                .map_err(|err: Error| sqlx::Error::Decode(err.into()))?,
+            expires_in: row.try_get("expires_in")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, PgRow> for ProjectUserProject {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let role: &str = row.try_get("role")?;
+        let project_status: &str = row.try_get("project_status")?;
+
+        Ok(Self {
+            user_id: row.try_get("user_id")?,
+            role: role
+                .parse()
+                .map_err(|err: Error| sqlx::Error::Decode(err.into()))?,
+            created_at: row.try_get("created_at")?,
+            project_id: row.try_get("project_id")?,
+            project_name: row.try_get("project_name")?,
+            project_namespace: row.try_get("project_namespace")?,
+            project_owner: row.try_get("project_owner")?,
+            project_status: project_status
+                .parse()
+                .map_err(|err: Error| sqlx::Error::Decode(err.into()))?,
+            project_billing_provider: row.try_get("project_billing_provider")?,
+            project_billing_provider_id: row.try_get("project_billing_provider_id")?,
+        })
+    }
+}
+
+impl FromRow<'_, PgRow> for ProjectQuota {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let project_id: String = row.try_get("project_id")?;
+        let resources: &str = row.try_get("resources")?;
+        let max_tier: Option<i64> = row.try_get("max_tier")?;
+        let workers: Option<i64> = row.try_get("workers")?;
+
+        Ok(Self {
+            project_id: (project_id != DEFAULT_QUOTA_ID).then_some(project_id),
+            resources: serde_json::from_str(resources)
+                .map_err(|err| sqlx::Error::Decode(err.into()))?,
+            max_tier: max_tier.map(|v| v as u32),
+            workers: workers.map(|v| v as u32),
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driven::cache::postgres::tests::mock_project;
+
+    async fn get_cache() -> (Arc<PostgresCache>, PostgresProjectDrivenCache) {
+        let postgres_cache = Arc::new(PostgresCache::ephemeral().await.unwrap());
+        let cache = PostgresProjectDrivenCache::new(postgres_cache.clone());
+        (postgres_cache, cache)
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_find_user_projects() {
+        let (postgres_cache, cache) = get_cache().await;
+        let project = mock_project(postgres_cache).await;
+
+        let result = cache.find(&project.owner, &Page::default()).await.unwrap();
+        assert!(result.len() == 1);
+        assert!(result[0].id == project.id);
+        assert!(result[0].created_at.timestamp() == project.created_at.timestamp());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_ignore_a_project_created_again() {
+        let (postgres_cache, cache) = get_cache().await;
+        let project = mock_project(postgres_cache).await;
+
+        let result = cache.create(&project).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_update_project_and_change_owner() {
+        let (postgres_cache, cache) = get_cache().await;
+        let project = mock_project(postgres_cache).await;
+
+        cache
+            .update(&ProjectUpdate {
+                id: project.id.clone(),
+                name: Some("Renamed".into()),
+                status: None,
+                billing_subscription_id: None,
+                updated_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        let invite = ProjectUserInvite {
+            project_id: project.id.clone(),
+            ..Default::default()
+        };
+        cache.create_user_invite(&invite).await.unwrap();
+        let user = ProjectUser {
+            user_id: "new owner".into(),
+            project_id: project.id.clone(),
+            role: ProjectUserRole::Member,
+            created_at: Utc::now(),
+        };
+        cache
+            .create_user_acceptance(&invite.id, &user)
+            .await
+            .unwrap();
+
+        cache
+            .change_owner(&ProjectOwnerChange {
+                project_id: project.id.clone(),
+                previous_owner: project.owner.clone(),
+                new_owner: user.user_id.clone(),
+                changed_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        let updated = cache.find_by_id(&project.id).await.unwrap().unwrap();
+        assert!(updated.name == "Renamed");
+        assert!(updated.owner == user.user_id);
+
+        let previous = cache
+            .find_user_permission(&project.owner, &project.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(previous.role, ProjectUserRole::Member));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_find_secrets_by_namespace() {
+        let (postgres_cache, cache) = get_cache().await;
+        let project = mock_project(postgres_cache).await;
+
+        let secret = ProjectSecret {
+            project_id: project.id.clone(),
+            ..Default::default()
+        };
+        cache.create_secret(&secret).await.unwrap();
+
+        let secrets = cache.find_secrets(&project.namespace).await.unwrap();
+        assert!(secrets.len() == 1);
+        assert!(secrets[0].secret == secret.secret);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_find_new_users() {
+        let (postgres_cache, cache) = get_cache().await;
+        let project = mock_project(postgres_cache).await;
+
+        let after = (project.created_at - chrono::Duration::days(1))
+            .format("%Y-%m-%d")
+            .to_string();
+        let users = cache.find_new_users(&after).await.unwrap();
+        assert!(users.len() == 1);
+        assert!(users[0].project_id == project.id);
+    }
+}
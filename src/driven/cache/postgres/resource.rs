@@ -0,0 +1,586 @@
+use chrono::{DateTime, Utc};
+use sqlx::{postgres::PgRow, FromRow, Row};
+use std::{sync::Arc, time::Duration};
+
+use crate::domain::{
+    error::Error,
+    event::EventOffset,
+    pagination::Page,
+    resource::{
+        cache::{ResourceDrivenCache, ResourceDrivenCacheBackoffice},
+        Resource, ResourceProject, ResourceStatus, ResourceUpdate,
+    },
+    Result,
+};
+
+use super::PostgresCache;
+
+pub struct PostgresResourceDrivenCache {
+    postgres: Arc<PostgresCache>,
+}
+impl PostgresResourceDrivenCache {
+    pub fn new(postgres: Arc<PostgresCache>) -> Self {
+        Self { postgres }
+    }
+}
+#[async_trait::async_trait]
+impl ResourceDrivenCache for PostgresResourceDrivenCache {
+    async fn find(&self, project_id: &str, page: &Page, category: &str) -> Result<Vec<Resource>> {
+        let resources = sqlx::query_as::<_, Resource>(
+            r#"
+                SELECT
+                    r.id,
+                    r.project_id,
+                    r.name,
+                    r.kind,
+                    r.category,
+                    r.cluster_id,
+                    r.spec,
+                    r.status,
+                    r.created_at,
+                    r.updated_at
+                FROM resource r
+                WHERE r.project_id = $1 and r.status != $2 and r.category = $3
+                    AND (
+                        $6::timestamptz IS NULL
+                        OR (r.created_at, r.id) < ($6::timestamptz, $7::text)
+                    )
+                ORDER BY r.created_at DESC, r.id DESC
+                LIMIT $4
+                OFFSET $5;
+            "#,
+        )
+        .bind(project_id)
+        .bind(ResourceStatus::Deleted.to_string())
+        .bind(category)
+        .bind(i64::from(page.page_size))
+        .bind(i64::from(page.offset()))
+        .bind(page.cursor_created_at())
+        .bind(page.cursor_id())
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(resources)
+    }
+    async fn find_by_id(&self, id: &str) -> Result<Option<Resource>> {
+        let resource = sqlx::query_as::<_, Resource>(
+            r#"
+                SELECT
+                    r.id,
+                    r.project_id,
+                    r.name,
+                    r.kind,
+                    r.category,
+                    r.cluster_id,
+                    r.spec,
+                    r.status,
+                    r.created_at,
+                    r.updated_at
+                FROM resource r
+                WHERE r.id = $1 and r.status != $2;
+            "#,
+        )
+        .bind(id)
+        .bind(ResourceStatus::Deleted.to_string())
+        .fetch_optional(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(resource)
+    }
+    async fn find_deleted_by_id(&self, id: &str) -> Result<Option<Resource>> {
+        let resource = sqlx::query_as::<_, Resource>(
+            r#"
+                SELECT
+                    r.id,
+                    r.project_id,
+                    r.name,
+                    r.kind,
+                    r.category,
+                    r.cluster_id,
+                    r.spec,
+                    r.status,
+                    r.created_at,
+                    r.updated_at
+                FROM resource r
+                WHERE r.id = $1 and r.status = $2;
+            "#,
+        )
+        .bind(id)
+        .bind(ResourceStatus::Deleted.to_string())
+        .fetch_optional(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(resource)
+    }
+    async fn find_deleted_with_project(
+        &self,
+        project_id: &str,
+        deleted_at: &DateTime<Utc>,
+    ) -> Result<Vec<Resource>> {
+        let resources = sqlx::query_as::<_, Resource>(
+            r#"
+                SELECT
+                    r.id,
+                    r.project_id,
+                    r.name,
+                    r.kind,
+                    r.category,
+                    r.cluster_id,
+                    r.spec,
+                    r.status,
+                    r.created_at,
+                    r.updated_at
+                FROM resource r
+                WHERE r.project_id = $1 and r.status = $2 and r.updated_at = $3;
+            "#,
+        )
+        .bind(project_id)
+        .bind(ResourceStatus::Deleted.to_string())
+        .bind(deleted_at)
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(resources)
+    }
+    async fn find_by_name(&self, project_id: &str, name: &str) -> Result<Option<Resource>> {
+        let resource = sqlx::query_as::<_, Resource>(
+            r#"
+                SELECT
+                    r.id,
+                    r.project_id,
+                    r.name,
+                    r.kind,
+                    r.category,
+                    r.cluster_id,
+                    r.spec,
+                    r.status,
+                    r.created_at,
+                    r.updated_at
+                FROM resource r
+                WHERE r.project_id = $1 AND r.name = $2 AND r.status != $3;
+            "#,
+        )
+        .bind(project_id)
+        .bind(name)
+        .bind(ResourceStatus::Deleted.to_string())
+        .fetch_optional(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(resource)
+    }
+
+    async fn count_by_kind(&self, project_id: &str, kind: &str) -> Result<u64> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+                SELECT COUNT(*)
+                FROM resource r
+                WHERE r.project_id = $1 AND r.kind = $2 AND r.status != $3;
+            "#,
+        )
+        .bind(project_id)
+        .bind(kind)
+        .bind(ResourceStatus::Deleted.to_string())
+        .fetch_one(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(count as u64)
+    }
+
+    async fn count_by_category(&self, project_id: &str, category: &str) -> Result<u64> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+                SELECT COUNT(*)
+                FROM resource r
+                WHERE r.project_id = $1 AND r.category = $2 AND r.status != $3;
+            "#,
+        )
+        .bind(project_id)
+        .bind(category)
+        .bind(ResourceStatus::Deleted.to_string())
+        .fetch_one(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(count as u64)
+    }
+
+    async fn create(&self, resource: &Resource) -> Result<()> {
+        sqlx::query(
+            r#"
+                INSERT INTO resource (
+                    id,
+                    project_id,
+                    name,
+                    kind,
+                    category,
+                    spec,
+                    status,
+                    cluster_id,
+                    created_at,
+                    updated_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                ON CONFLICT (id) DO NOTHING;
+            "#,
+        )
+        .bind(&resource.id)
+        .bind(&resource.project_id)
+        .bind(&resource.name)
+        .bind(&resource.kind)
+        .bind(&resource.category)
+        .bind(&resource.spec)
+        .bind(resource.status.to_string())
+        .bind(&resource.cluster_id)
+        .bind(resource.created_at)
+        .bind(resource.updated_at)
+        .execute(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update(&self, resource_update: &ResourceUpdate) -> Result<()> {
+        let resource = match self.find_by_id(&resource_update.id).await? {
+            Some(resource) => resource,
+            None => {
+                return Err(Error::Unexpected(format!(
+                    "Resource not found: {}",
+                    resource_update.id
+                )))
+            }
+        };
+
+        let mut parsed = match serde_json::from_str(&resource.spec) {
+            Ok(parsed) => parsed,
+            Err(_) => {
+                return Err(Error::Unexpected(format!(
+                    "Invalid spec found on resource: {}",
+                    resource.id
+                )))
+            }
+        };
+        json_patch::merge(
+            &mut parsed,
+            &serde_json::from_str(&resource_update.spec_patch)?,
+        );
+
+        sqlx::query(
+            r#"
+                UPDATE resource
+                SET spec = $1, updated_at = $2
+                WHERE id = $3;
+            "#,
+        )
+        .bind(parsed.to_string())
+        .bind(resource_update.updated_at)
+        .bind(&resource.id)
+        .execute(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str, deleted_at: &DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE resource
+                SET status = $2, updated_at = $3
+                WHERE id = $1;
+            "#,
+        )
+        .bind(id)
+        .bind(ResourceStatus::Deleted.to_string())
+        .bind(deleted_at)
+        .execute(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn restore(&self, id: &str, restored_at: &DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE resource
+                SET status = $2, updated_at = $3
+                WHERE id = $1;
+            "#,
+        )
+        .bind(id)
+        .bind(ResourceStatus::Active.to_string())
+        .bind(restored_at)
+        .execute(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn wait_applied(&self, offset: &EventOffset, timeout: Duration) -> Result<bool> {
+        self.postgres.wait_applied(offset, timeout).await
+    }
+}
+#[async_trait::async_trait]
+impl ResourceDrivenCacheBackoffice for PostgresResourceDrivenCache {
+    async fn find_actives(&self) -> Result<Vec<ResourceProject>> {
+        let resources = sqlx::query_as::<_, ResourceProject>(
+            r#"
+                SELECT
+                    r.id,
+                    r.project_id,
+                    p.namespace as project_namespace,
+                    r.name,
+                    r.kind,
+                    r.category,
+                    r.cluster_id,
+                    r.spec,
+                    r.status,
+                    r.created_at,
+                    r.updated_at
+                FROM resource r
+                INNER JOIN project p ON p.id = r.project_id
+                WHERE r.status = $1;
+            "#,
+        )
+        .bind(ResourceStatus::Active.to_string())
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(resources)
+    }
+
+    async fn find_by_project_namespace(&self, namespace: &str) -> Result<Vec<Resource>> {
+        let resources = sqlx::query_as::<_, Resource>(
+            r#"
+                SELECT
+                    r.id,
+                    r.project_id,
+                    r.name,
+                    r.kind,
+                    r.category,
+                    r.cluster_id,
+                    r.spec,
+                    r.status,
+                    r.created_at,
+                    r.updated_at
+                FROM resource r
+                INNER JOIN project p ON p.id = r.project_id
+                WHERE p.namespace = $1;
+            "#,
+        )
+        .bind(namespace)
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(resources)
+    }
+
+    async fn find_by_spec(&self, value: &str) -> Result<Vec<Resource>> {
+        let resources = sqlx::query_as::<_, Resource>(
+            r#"
+                SELECT
+                    r.id,
+                    r.project_id,
+                    r.name,
+                    r.kind,
+                    r.category,
+                    r.cluster_id,
+                    r.spec,
+                    r.status,
+                    r.created_at,
+                    r.updated_at
+                FROM resource r
+                WHERE r.spec LIKE $1;
+            "#,
+        )
+        .bind(format!("%{value}%"))
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(resources)
+    }
+}
+
+impl FromRow<'_, PgRow> for Resource {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let status: &str = row.try_get("status")?;
+
+        Ok(Self {
+            id: row.try_get("id")?,
+            project_id: row.try_get("project_id")?,
+            name: row.try_get("name")?,
+            kind: row.try_get("kind")?,
+            spec: row.try_get("spec")?,
+            category: row.try_get("category")?,
+            annotations: None,
+            status: status
+                .parse()
+                .map_err(|err: Error| sqlx::Error::Decode(err.into()))?,
+            cluster_id: row.try_get("cluster_id")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, PgRow> for ResourceProject {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let status: &str = row.try_get("status")?;
+
+        Ok(Self {
+            id: row.try_get("id")?,
+            project_id: row.try_get("project_id")?,
+            project_namespace: row.try_get("project_namespace")?,
+            name: row.try_get("name")?,
+            kind: row.try_get("kind")?,
+            category: row.try_get("category")?,
+            spec: row.try_get("spec")?,
+            annotations: None,
+            status: status
+                .parse()
+                .map_err(|err: Error| sqlx::Error::Decode(err.into()))?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        domain::{pagination::Cursor, project::cache::ProjectDrivenCache, DEFAULT_CATEGORY},
+        driven::cache::postgres::{
+            project::PostgresProjectDrivenCache,
+            tests::{mock_project, mock_resource},
+        },
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_find_project_resources() {
+        let postgres_cache = Arc::new(PostgresCache::ephemeral().await.unwrap());
+        let cache = PostgresResourceDrivenCache::new(postgres_cache.clone());
+        let project = mock_project(postgres_cache.clone()).await;
+        mock_resource(postgres_cache, &project.id).await;
+
+        let result = cache
+            .find(&project.id, &Page::default(), DEFAULT_CATEGORY)
+            .await;
+        assert!(result.is_ok());
+        assert!(result.unwrap().len() == 1);
+
+        let page = Page {
+            page: 2,
+            ..Default::default()
+        };
+        let result = cache.find(&project.id, &page, DEFAULT_CATEGORY).await;
+        assert!(result.is_ok());
+        assert!(result.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_find_project_resources_after_cursor() {
+        let postgres_cache = Arc::new(PostgresCache::ephemeral().await.unwrap());
+        let cache = PostgresResourceDrivenCache::new(postgres_cache.clone());
+        let project = mock_project(postgres_cache.clone()).await;
+
+        let created_at = Utc::now();
+        for _ in 0..3 {
+            let resource = Resource {
+                project_id: project.id.clone(),
+                created_at,
+                ..Default::default()
+            };
+            cache.create(&resource).await.unwrap();
+        }
+
+        let page = Page::after(None, 2);
+        let first = cache
+            .find(&project.id, &page, DEFAULT_CATEGORY)
+            .await
+            .unwrap();
+        assert!(first.len() == 2);
+
+        let last = first.last().unwrap();
+        let page = Page::after(Some(Cursor::new(Some(last.created_at), &last.id)), 2);
+        let second = cache
+            .find(&project.id, &page, DEFAULT_CATEGORY)
+            .await
+            .unwrap();
+        assert!(second.len() == 1);
+        assert!(first.iter().all(|r| r.id != second[0].id));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_merge_resource_spec_patch() {
+        let postgres_cache = Arc::new(PostgresCache::ephemeral().await.unwrap());
+        let cache = PostgresResourceDrivenCache::new(postgres_cache.clone());
+        let project = mock_project(postgres_cache.clone()).await;
+        let resource = mock_resource(postgres_cache, &project.id).await;
+
+        let resource_update = ResourceUpdate {
+            id: resource.id.clone(),
+            spec_patch: "{\"spec\": {\"operatorVersion\": \"2\"}}".into(),
+            updated_at: Utc::now(),
+        };
+        cache.update(&resource_update).await.unwrap();
+
+        let updated = cache.find_by_id(&resource.id).await.unwrap().unwrap();
+        assert!(updated.spec.contains("\"operatorVersion\":\"2\""));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_ignore_a_resource_created_again() {
+        let postgres_cache = Arc::new(PostgresCache::ephemeral().await.unwrap());
+        let cache = PostgresResourceDrivenCache::new(postgres_cache.clone());
+        let project = mock_project(postgres_cache.clone()).await;
+        let resource = mock_resource(postgres_cache, &project.id).await;
+
+        cache.delete(&resource.id, &Utc::now()).await.unwrap();
+        let result = cache.create(&resource).await;
+        assert!(result.is_ok());
+
+        let result = cache.find_by_id(&resource.id).await.unwrap();
+        assert!(result.is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_restore_resources_deleted_with_project() {
+        let postgres_cache = Arc::new(PostgresCache::ephemeral().await.unwrap());
+        let cache = PostgresResourceDrivenCache::new(postgres_cache.clone());
+        let project_cache = PostgresProjectDrivenCache::new(postgres_cache.clone());
+        let project = mock_project(postgres_cache.clone()).await;
+        let resource = mock_resource(postgres_cache, &project.id).await;
+
+        project_cache
+            .delete(&project.id, &Utc::now())
+            .await
+            .unwrap();
+        let deleted = project_cache
+            .find_deleted_by_id(&project.id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let result = cache
+            .find_deleted_with_project(&project.id, &deleted.updated_at)
+            .await
+            .unwrap();
+        assert!(result.len() == 1);
+
+        project_cache
+            .restore(&project.id, &Utc::now())
+            .await
+            .unwrap();
+        cache.restore(&resource.id, &Utc::now()).await.unwrap();
+        assert!(project_cache
+            .find_by_id(&project.id)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(cache.find_by_id(&resource.id).await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,497 @@
+use sqlx::{postgres::PgRow, Connection, FromRow, Row};
+use std::sync::Arc;
+
+use crate::domain::{
+    resource::ResourceStatus,
+    usage::{
+        cache::{UsageDrivenCache, UsageDrivenCacheBackoffice, UsageDrivenCacheInvoice},
+        invoice::Invoice,
+        Usage, UsageReport, UsageResource,
+    },
+    Result,
+};
+
+use super::PostgresCache;
+
+pub struct PostgresUsageDrivenCache {
+    postgres: Arc<PostgresCache>,
+}
+impl PostgresUsageDrivenCache {
+    pub fn new(postgres: Arc<PostgresCache>) -> Self {
+        Self { postgres }
+    }
+}
+#[async_trait::async_trait]
+impl UsageDrivenCache for PostgresUsageDrivenCache {
+    async fn find_report(
+        &self,
+        project_id: &str,
+        page: &u32,
+        page_size: &u32,
+        cluster_id: Option<String>,
+    ) -> Result<Vec<UsageReport>> {
+        let offset = page_size * (page - 1);
+
+        let mut query = String::from(
+            r#"
+                SELECT
+                    u.cluster_id,
+                    p.id as project_id,
+                    p.namespace as project_namespace,
+                    p.billing_provider as project_billing_provider,
+                    p.billing_provider_id as project_billing_provider_id,
+                    r.id as resource_id,
+                    r.kind as resource_kind,
+                    r.name as resource_name,
+                    r.spec as resource_spec,
+                    u.tier,
+                    SUM(u."interval")::BIGINT as "interval",
+                    SUM(u.units)::BIGINT as units,
+                    TO_CHAR(u.created_at AT TIME ZONE 'UTC', 'YYYY-MM') as period
+                FROM usage u
+                INNER JOIN resource r ON r.id = u.resource_id
+                INNER JOIN project p ON p.id = r.project_id
+                WHERE
+                    TO_CHAR(u.created_at AT TIME ZONE 'UTC', 'YYYY-MM') = TO_CHAR(NOW() AT TIME ZONE 'UTC', 'YYYY-MM')
+                    AND r.project_id = $1
+                    --WHERE--
+                GROUP BY u.cluster_id, p.id, r.id, u.tier, period
+                ORDER BY units DESC
+                LIMIT $2
+                OFFSET $3;
+            "#,
+        );
+
+        if cluster_id.is_some() {
+            query = query.replace("--WHERE--", "AND u.cluster_id = $4");
+        }
+
+        let mut query = sqlx::query_as::<_, UsageReport>(&query)
+            .bind(project_id)
+            .bind(i64::from(*page_size))
+            .bind(i64::from(offset));
+
+        if let Some(cluster_id) = cluster_id {
+            query = query.bind(cluster_id);
+        }
+
+        let report = query.fetch_all(&mut *self.postgres.conn().await?).await?;
+
+        Ok(report)
+    }
+
+    async fn find_resouces(&self) -> Result<Vec<UsageResource>> {
+        let resources = sqlx::query_as::<_, UsageResource>(
+            r#"
+                SELECT
+                    p.id as project_id,
+                    p.namespace as project_namespace,
+                    r.id as resource_id,
+                    r.name as resource_name,
+                    r.spec as resource_spec
+                FROM resource r
+                INNER JOIN project p ON p.id = r.project_id
+                WHERE r.status != $1;
+            "#,
+        )
+        .bind(ResourceStatus::Deleted.to_string())
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(resources)
+    }
+
+    async fn find_clusters(
+        &self,
+        project_id: &str,
+        page: &u32,
+        page_size: &u32,
+    ) -> Result<Vec<String>> {
+        let offset = page_size * (page - 1);
+
+        let rows = sqlx::query(
+            r#"
+                SELECT
+                    u.cluster_id,
+                    SUM(u.units)::BIGINT as units
+                FROM usage u
+                INNER JOIN resource r ON r.id = u.resource_id
+                WHERE
+                    TO_CHAR(u.created_at AT TIME ZONE 'UTC', 'YYYY-MM') = TO_CHAR(NOW() AT TIME ZONE 'UTC', 'YYYY-MM')
+                    AND r.project_id = $1
+                GROUP BY u.cluster_id
+                ORDER BY units DESC
+                LIMIT $2
+                OFFSET $3;
+            "#,
+        )
+        .bind(project_id)
+        .bind(i64::from(*page_size))
+        .bind(i64::from(offset))
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        let clusters = rows.iter().map(|r| r.get("cluster_id")).collect();
+
+        Ok(clusters)
+    }
+
+    async fn create(&self, usages: Vec<Usage>) -> Result<()> {
+        let mut conn = self.postgres.conn().await?;
+        let mut tx = conn.begin().await?;
+
+        // The usage ids are generated when the event is applied, so a redelivered event is
+        // recognised by its event id instead, even when two replicas apply it at once.
+        for usage in usages {
+            sqlx::query(
+                r#"
+                    INSERT INTO usage (
+                        id,
+                        resource_id,
+                        event_id,
+                        cluster_id,
+                        units,
+                        tier,
+                        "interval",
+                        created_at
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                    ON CONFLICT (event_id, resource_id, tier) DO NOTHING;
+                "#,
+            )
+            .bind(&usage.id)
+            .bind(&usage.resource_id)
+            .bind(&usage.event_id)
+            .bind(&usage.cluster_id)
+            .bind(usage.units)
+            .bind(&usage.tier)
+            .bind(usage.interval as i64)
+            .bind(usage.created_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+#[async_trait::async_trait]
+impl UsageDrivenCacheBackoffice for PostgresUsageDrivenCache {
+    async fn find_report_aggregated(
+        &self,
+        period: &str,
+        cluster_id: &str,
+    ) -> Result<Vec<UsageReport>> {
+        let report_aggregated = sqlx::query_as::<_, UsageReport>(
+            r#"
+                SELECT
+                    u.cluster_id,
+                    p.id as project_id,
+                    p.namespace as project_namespace,
+                    p.billing_provider as project_billing_provider,
+                    p.billing_provider_id as project_billing_provider_id,
+                    r.id as resource_id,
+                    r.kind as resource_kind,
+                    r.name as resource_name,
+                    r.spec as resource_spec,
+                    u.tier as tier,
+                    SUM(u."interval")::BIGINT as "interval",
+                    SUM(u.units)::BIGINT as units,
+                    TO_CHAR(u.created_at AT TIME ZONE 'UTC', 'YYYY-MM') as period
+                FROM usage u
+                INNER JOIN resource r ON r.id = u.resource_id
+                INNER JOIN project p ON p.id = r.project_id
+                WHERE
+                    TO_CHAR(u.created_at AT TIME ZONE 'UTC', 'YYYY-MM') = $1
+                    AND u.cluster_id = $2
+                GROUP BY u.cluster_id, p.id, r.id, u.tier, period
+                ORDER BY project_namespace, resource_id ASC;
+            "#,
+        )
+        .bind(period)
+        .bind(cluster_id)
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(report_aggregated)
+    }
+
+    async fn find_clusters(&self, period: &str) -> Result<Vec<String>> {
+        let rows = sqlx::query(
+            r#"
+                SELECT u.cluster_id
+                FROM usage u
+                INNER JOIN resource r ON r.id = u.resource_id
+                WHERE TO_CHAR(u.created_at AT TIME ZONE 'UTC', 'YYYY-MM') = $1
+                GROUP BY u.cluster_id;
+            "#,
+        )
+        .bind(period)
+        .fetch_all(&mut *self.postgres.conn().await?)
+        .await?;
+
+        let clusters = rows.iter().map(|r| r.get("cluster_id")).collect();
+
+        Ok(clusters)
+    }
+}
+
+#[async_trait::async_trait]
+impl UsageDrivenCacheInvoice for PostgresUsageDrivenCache {
+    async fn find_invoice(&self, project_id: &str, period: &str) -> Result<Option<Invoice>> {
+        let invoice = sqlx::query_as::<_, Invoice>(
+            r#"
+                SELECT
+                    project_id,
+                    period,
+                    billing_provider,
+                    billing_provider_id,
+                    amount,
+                    invoice_item_id,
+                    created_at
+                FROM billing_ledger
+                WHERE project_id = $1 AND period = $2;
+            "#,
+        )
+        .bind(project_id)
+        .bind(period)
+        .fetch_optional(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(invoice)
+    }
+
+    async fn create_invoice(&self, invoice: &Invoice) -> Result<()> {
+        sqlx::query(
+            r#"
+                INSERT INTO billing_ledger (
+                    project_id,
+                    period,
+                    billing_provider,
+                    billing_provider_id,
+                    amount,
+                    invoice_item_id,
+                    created_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7);
+            "#,
+        )
+        .bind(&invoice.project_id)
+        .bind(&invoice.period)
+        .bind(&invoice.billing_provider)
+        .bind(&invoice.billing_provider_id)
+        .bind(invoice.amount)
+        .bind(&invoice.invoice_item_id)
+        .bind(invoice.created_at)
+        .execute(&mut *self.postgres.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl FromRow<'_, PgRow> for Usage {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        let interval: i64 = row.try_get("interval")?;
+
+        Ok(Self {
+            id: row.try_get("id")?,
+            event_id: row.try_get("event_id")?,
+            resource_id: row.try_get("resource_id")?,
+            cluster_id: row.try_get("cluster_id")?,
+            units: row.try_get("units")?,
+            tier: row.try_get("tier")?,
+            interval: interval as u64,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, PgRow> for UsageReport {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            cluster_id: row.try_get("cluster_id")?,
+            project_id: row.try_get("project_id")?,
+            project_namespace: row.try_get("project_namespace")?,
+            project_billing_provider: row.try_get("project_billing_provider")?,
+            project_billing_provider_id: row.try_get("project_billing_provider_id")?,
+            resource_id: row.try_get("resource_id")?,
+            resource_kind: row.try_get("resource_kind")?,
+            resource_name: row.try_get("resource_name")?,
+            resource_spec: row.try_get("resource_spec")?,
+            interval: row.try_get("interval")?,
+            units: row.try_get("units")?,
+            tier: row.try_get("tier")?,
+            period: row.try_get("period")?,
+            minimum_cost: None,
+            units_cost: None,
+        })
+    }
+}
+
+impl FromRow<'_, PgRow> for UsageResource {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            project_id: row.try_get("project_id")?,
+            project_namespace: row.try_get("project_namespace")?,
+            resource_id: row.try_get("resource_id")?,
+            resource_name: row.try_get("resource_name")?,
+            resource_spec: row.try_get("resource_spec")?,
+        })
+    }
+}
+
+impl FromRow<'_, PgRow> for Invoice {
+    fn from_row(row: &PgRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            project_id: row.try_get("project_id")?,
+            period: row.try_get("period")?,
+            billing_provider: row.try_get("billing_provider")?,
+            billing_provider_id: row.try_get("billing_provider_id")?,
+            amount: row.try_get("amount")?,
+            invoice_item_id: row.try_get("invoice_item_id")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use crate::driven::cache::postgres::tests::{mock_project, mock_resource};
+
+    use super::*;
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_find_usage_report() {
+        let postgres_cache = Arc::new(PostgresCache::ephemeral().await.unwrap());
+        let cache = PostgresUsageDrivenCache::new(postgres_cache.clone());
+        let project = mock_project(postgres_cache.clone()).await;
+        let resource = mock_resource(postgres_cache, &project.id).await;
+
+        let usages = vec![
+            Usage {
+                resource_id: resource.id.clone(),
+                tier: "0".into(),
+                ..Default::default()
+            },
+            Usage {
+                resource_id: resource.id.clone(),
+                tier: "1".into(),
+                ..Default::default()
+            },
+        ];
+        cache.create(usages).await.unwrap();
+
+        let result = cache.find_report(&project.id, &1, &12, None).await;
+        assert!(result.is_ok());
+        assert!(result.unwrap().len() == 2);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_ignore_a_redelivered_usage() {
+        let postgres_cache = Arc::new(PostgresCache::ephemeral().await.unwrap());
+        let cache = PostgresUsageDrivenCache::new(postgres_cache.clone());
+        let project = mock_project(postgres_cache.clone()).await;
+        let resource = mock_resource(postgres_cache, &project.id).await;
+
+        let usage = Usage {
+            resource_id: resource.id.clone(),
+            ..Default::default()
+        };
+        let redelivered = Usage {
+            event_id: usage.event_id.clone(),
+            resource_id: usage.resource_id.clone(),
+            ..Default::default()
+        };
+        let units = usage.units;
+        cache.create(vec![usage]).await.unwrap();
+        cache.create(vec![redelivered]).await.unwrap();
+
+        let result = cache
+            .find_report_aggregated(&Utc::now().format("%Y-%m").to_string(), "demeter")
+            .await
+            .unwrap();
+        assert!(result.len() == 1);
+        assert!(result[0].units == units);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_ignore_a_usage_applied_by_two_replicas_at_once() {
+        let postgres_cache = Arc::new(PostgresCache::ephemeral().await.unwrap());
+        let first = PostgresUsageDrivenCache::new(postgres_cache.clone());
+        let second = PostgresUsageDrivenCache::new(postgres_cache.clone());
+        let project = mock_project(postgres_cache.clone()).await;
+        let resource = mock_resource(postgres_cache, &project.id).await;
+
+        let usage = Usage {
+            resource_id: resource.id.clone(),
+            ..Default::default()
+        };
+        let redelivered = Usage {
+            event_id: usage.event_id.clone(),
+            resource_id: usage.resource_id.clone(),
+            ..Default::default()
+        };
+        let (a, b) = tokio::join!(first.create(vec![usage]), second.create(vec![redelivered]));
+        assert!(a.is_ok() && b.is_ok());
+
+        let result = first
+            .find_report_aggregated(&Utc::now().format("%Y-%m").to_string(), "demeter")
+            .await
+            .unwrap();
+        assert!(result.len() == 1);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_find_usage_clusters() {
+        let postgres_cache = Arc::new(PostgresCache::ephemeral().await.unwrap());
+        let cache = PostgresUsageDrivenCache::new(postgres_cache.clone());
+        let project = mock_project(postgres_cache.clone()).await;
+        let resource = mock_resource(postgres_cache, &project.id).await;
+
+        let usages = vec![
+            Usage {
+                cluster_id: "cluster_1".into(),
+                resource_id: resource.id.clone(),
+                ..Default::default()
+            },
+            Usage {
+                cluster_id: "cluster_2".into(),
+                resource_id: resource.id.clone(),
+                ..Default::default()
+            },
+        ];
+        cache.create(usages).await.unwrap();
+
+        let result = UsageDrivenCache::find_clusters(&cache, &project.id, &1, &12).await;
+        assert!(result.is_ok());
+        assert!(result.unwrap().len() == 2);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a postgres server in TEST_POSTGRES_URL"]
+    async fn it_should_record_an_invoice_once_per_period() {
+        let postgres_cache = Arc::new(PostgresCache::ephemeral().await.unwrap());
+        let cache = PostgresUsageDrivenCache::new(postgres_cache);
+
+        let invoice = Invoice::default();
+        cache.create_invoice(&invoice).await.unwrap();
+
+        let result = cache.find_invoice("project id", "2024-08").await.unwrap();
+        assert!(result.is_some_and(
+            |i| i.amount == invoice.amount && i.invoice_item_id == invoice.invoice_item_id
+        ));
+        let result = cache.find_invoice("project id", "2024-09").await.unwrap();
+        assert!(result.is_none());
+
+        let result = cache.create_invoice(&invoice).await;
+        assert!(result.is_err());
+    }
+}
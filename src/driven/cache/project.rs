@@ -1,19 +1,23 @@
 use chrono::{DateTime, Utc};
-use sqlx::{sqlite::SqliteRow, FromRow, Row};
-use std::sync::Arc;
+use sqlx::{sqlite::SqliteRow, Connection, FromRow, Row, Sqlite, Transaction};
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
 
 use crate::domain::{
     error::Error,
+    event::EventOffset,
+    pagination::Page,
     project::{
         cache::{ProjectDrivenCache, ProjectDrivenCacheBackoffice},
-        Project, ProjectOwnerChange, ProjectSecret, ProjectStatus, ProjectUpdate, ProjectUser,
+        quota::ProjectQuota,
+        Project, ProjectOrganizationChange, ProjectOwnerChange, ProjectOwnershipTransfer,
+        ProjectOwnershipTransferStatus, ProjectSecret, ProjectStatus, ProjectUpdate, ProjectUser,
         ProjectUserInvite, ProjectUserInviteStatus, ProjectUserProject, ProjectUserRole,
     },
     resource::ResourceStatus,
     Result,
 };
 
-use super::SqliteCache;
+use super::{SqliteCache, DEFAULT_QUOTA_ID};
 
 pub struct SqliteProjectDrivenCache {
     sqlite: Arc<SqliteCache>,
@@ -25,10 +29,8 @@ impl SqliteProjectDrivenCache {
 }
 #[async_trait::async_trait]
 impl ProjectDrivenCache for SqliteProjectDrivenCache {
-    async fn find(&self, user_id: &str, page: &u32, page_size: &u32) -> Result<Vec<Project>> {
-        let offset = page_size * (page - 1);
-
-        let projects = sqlx::query_as::<_, Project>(
+    async fn find(&self, user_id: &str, page: &Page) -> Result<Vec<Project>> {
+        let query = String::from(
             r#"
                 SELECT 
                     p.id, 
@@ -38,25 +40,57 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
                     p.status, p.billing_provider,
                     p.billing_provider_id,
                     p.billing_subscription_id,
+                    p.cluster_id,
+                    p.organization_id,
                     p.created_at, 
                     p.updated_at
-                FROM project_user pu 
-                INNER JOIN project p on p.id = pu.project_id
-                WHERE pu.user_id = $1 and p.status != $2
-                ORDER BY pu.created_at DESC
+                FROM project p
+                WHERE p.status != $2
+                    AND (
+                        p.id IN (SELECT project_id FROM project_user WHERE user_id = $1)
+                        OR p.organization_id IN (
+                            SELECT organization_id FROM organization_user WHERE user_id = $1
+                        )
+                    )
+                    AND ($5 IS NULL OR (p.created_at, p.id) < ($5, $6))
+                ORDER BY --ORDER--
                 LIMIT $3
                 OFFSET $4;
             "#,
-        )
-        .bind(user_id)
-        .bind(ProjectStatus::Deleted.to_string())
-        .bind(page_size)
-        .bind(offset)
-        .fetch_all(&self.sqlite.db)
-        .await?;
+        );
+
+        // The offset pages keep the order by membership time they always had, the cursors need
+        // the creation time and id the keyset is on.
+        let query = match page.by_cursor {
+            true => query.replace("--ORDER--", "p.created_at DESC, p.id DESC"),
+            false => query.replace(
+                "--ORDER--",
+                r#"
+                    COALESCE(
+                        (
+                            SELECT pu.created_at FROM project_user pu
+                            WHERE pu.project_id = p.id AND pu.user_id = $1
+                        ),
+                        p.created_at
+                    ) DESC,
+                    p.id DESC
+                "#,
+            ),
+        };
+
+        let projects = sqlx::query_as::<_, Project>(&query)
+            .bind(user_id)
+            .bind(ProjectStatus::Deleted.to_string())
+            .bind(page.page_size)
+            .bind(page.offset())
+            .bind(page.cursor_created_at())
+            .bind(page.cursor_id())
+            .fetch_all(&mut *self.sqlite.conn().await?)
+            .await?;
 
         Ok(projects)
     }
+
     async fn find_by_namespace(&self, namespace: &str) -> Result<Option<Project>> {
         let project = sqlx::query_as::<_, Project>(
             r#"
@@ -69,6 +103,8 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
                     p.billing_provider,
                     p.billing_provider_id,
                     p.billing_subscription_id,
+                    p.cluster_id,
+                    p.organization_id,
                     p.created_at, 
                     p.updated_at
                 FROM project p
@@ -77,7 +113,7 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
         )
         .bind(namespace)
         .bind(ProjectStatus::Deleted.to_string())
-        .fetch_optional(&self.sqlite.db)
+        .fetch_optional(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(project)
@@ -94,6 +130,8 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
                     p.billing_provider,
                     p.billing_provider_id,
                     p.billing_subscription_id,
+                    p.cluster_id,
+                    p.organization_id,
                     p.created_at, 
                     p.updated_at
                 FROM project p 
@@ -102,14 +140,72 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
         )
         .bind(id)
         .bind(ProjectStatus::Deleted.to_string())
-        .fetch_optional(&self.sqlite.db)
+        .fetch_optional(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(project)
+    }
+
+    async fn find_by_billing_provider_id(&self, billing_provider_id: &str) -> Result<Vec<Project>> {
+        let projects = sqlx::query_as::<_, Project>(
+            r#"
+                SELECT
+                    p.id,
+                    p.namespace,
+                    p.name,
+                    p.owner,
+                    p.status,
+                    p.billing_provider,
+                    p.billing_provider_id,
+                    p.billing_subscription_id,
+                    p.cluster_id,
+                    p.organization_id,
+                    p.created_at,
+                    p.updated_at
+                FROM project p
+                WHERE p.billing_provider_id = $1 and p.status != $2
+                ORDER BY p.created_at;
+            "#,
+        )
+        .bind(billing_provider_id)
+        .bind(ProjectStatus::Deleted.to_string())
+        .fetch_all(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(projects)
+    }
+
+    async fn find_deleted_by_id(&self, id: &str) -> Result<Option<Project>> {
+        let project = sqlx::query_as::<_, Project>(
+            r#"
+                SELECT
+                    p.id,
+                    p.namespace,
+                    p.name,
+                    p.owner,
+                    p.status,
+                    p.billing_provider,
+                    p.billing_provider_id,
+                    p.billing_subscription_id,
+                    p.cluster_id,
+                    p.organization_id,
+                    p.created_at,
+                    p.updated_at
+                FROM project p
+                WHERE p.id = $1 and p.status = $2;
+            "#,
+        )
+        .bind(id)
+        .bind(ProjectStatus::Deleted.to_string())
+        .fetch_optional(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(project)
     }
 
     async fn create(&self, project: &Project) -> Result<()> {
-        let mut tx = self.sqlite.db.begin().await?;
+        let mut conn = self.sqlite.conn().await?;
+        let mut tx = conn.begin().await?;
 
         let status = project.status.to_string();
 
@@ -124,10 +220,11 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
                     billing_provider,
                     billing_provider_id,
                     billing_subscription_id,
+                    cluster_id,
                     created_at,
                     updated_at
                 )
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
             "#,
             project.id,
             project.namespace,
@@ -137,6 +234,7 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
             project.billing_provider,
             project.billing_provider_id,
             project.billing_subscription_id,
+            project.cluster_id,
             project.created_at,
             project.updated_at
         )
@@ -168,115 +266,76 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
     }
 
     async fn update(&self, project_update: &ProjectUpdate) -> Result<()> {
-        match (&project_update.name, &project_update.status) {
-            (Some(new_name), Some(new_status)) => {
-                let new_status = new_status.to_string();
-                sqlx::query!(
-                    r#"
-                        UPDATE project
-                        SET name = $1, status = $2, updated_at = $3
-                        WHERE id = $4
-                    "#,
-                    new_name,
-                    new_status,
-                    project_update.updated_at,
-                    project_update.id,
-                )
-                .execute(&self.sqlite.db)
-                .await?;
-
-                Ok(())
-            }
-            (Some(new_name), None) => {
-                sqlx::query!(
-                    r#"
-                        UPDATE project
-                        SET name = $1, updated_at = $2
-                        WHERE id = $3
-                    "#,
-                    new_name,
-                    project_update.updated_at,
-                    project_update.id,
-                )
-                .execute(&self.sqlite.db)
-                .await?;
-
-                Ok(())
-            }
-            (None, Some(new_status)) => {
-                let new_status = new_status.to_string();
-                sqlx::query!(
-                    r#"
-                        UPDATE project
-                        SET status = $1, updated_at = $2
-                        WHERE id = $3
-                    "#,
-                    new_status,
-                    project_update.updated_at,
-                    project_update.id,
-                )
-                .execute(&self.sqlite.db)
-                .await?;
-
-                Ok(())
-            }
-            (None, None) => Ok(()),
+        if project_update.name.is_none()
+            && project_update.status.is_none()
+            && project_update.billing_subscription_id.is_none()
+        {
+            return Ok(());
         }
-    }
 
-    async fn change_owner(&self, change: &ProjectOwnerChange) -> Result<()> {
-        let mut tx = self.sqlite.db.begin().await?;
-
-        sqlx::query!(
+        sqlx::query(
             r#"
                 UPDATE project
-                SET owner = $1, updated_at = $2
-                WHERE id = $3
+                SET
+                    name = COALESCE($1, name),
+                    status = COALESCE($2, status),
+                    billing_subscription_id = CASE
+                        WHEN $6 THEN NULL
+                        ELSE COALESCE($3, billing_subscription_id)
+                    END,
+                    updated_at = $4
+                WHERE id = $5;
             "#,
-            change.new_owner,
-            change.changed_at,
-            change.project_id,
         )
-        .execute(&mut *tx)
-        .await?;
-
-        let owner_role = ProjectUserRole::Owner.to_string();
-        sqlx::query!(
-            r#"
-                UPDATE project_user
-                SET role = $1
-                WHERE project_id = $2 AND user_id = $3
-            "#,
-            owner_role,
-            change.project_id,
-            change.new_owner,
+        .bind(&project_update.name)
+        .bind(
+            project_update
+                .status
+                .as_ref()
+                .map(|status| status.to_string()),
         )
-        .execute(&mut *tx)
+        .bind(project_update.billing_subscription_id.clone().flatten())
+        .bind(project_update.updated_at)
+        .bind(&project_update.id)
+        .bind(project_update.billing_subscription_id == Some(None))
+        .execute(&mut *self.sqlite.conn().await?)
         .await?;
 
-        let member_role = ProjectUserRole::Member.to_string();
-        sqlx::query!(
+        Ok(())
+    }
+
+    async fn change_owner(&self, change: &ProjectOwnerChange) -> Result<()> {
+        let mut conn = self.sqlite.conn().await?;
+        let mut tx = conn.begin().await?;
+        update_owner(&mut tx, change).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn change_organization(&self, change: &ProjectOrganizationChange) -> Result<()> {
+        sqlx::query(
             r#"
-                UPDATE project_user
-                SET role = $1
-                WHERE project_id = $2 AND user_id = $3
+                UPDATE project
+                SET organization_id = $1, billing_provider_id = $2, updated_at = $3
+                WHERE id = $4;
             "#,
-            member_role,
-            change.project_id,
-            change.previous_owner,
         )
-        .execute(&mut *tx)
+        .bind(&change.organization_id)
+        .bind(&change.billing_provider_id)
+        .bind(change.changed_at)
+        .bind(&change.project_id)
+        .execute(&mut *self.sqlite.conn().await?)
         .await?;
 
-        tx.commit().await?;
-
         Ok(())
     }
 
     async fn delete(&self, id: &str, deleted_at: &DateTime<Utc>) -> Result<()> {
         let status = ProjectStatus::Deleted.to_string();
 
-        let mut tx = self.sqlite.db.begin().await?;
+        let mut conn = self.sqlite.conn().await?;
+        let mut tx = conn.begin().await?;
         sqlx::query!(
             r#"
                 UPDATE project
@@ -290,12 +349,14 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
         .execute(&mut *tx)
         .await?;
 
+        // The resources deleted before keep their own deleted_at, so a restore of the project
+        // only brings back the ones deleted together with it.
         let status = ResourceStatus::Deleted.to_string();
         sqlx::query!(
             r#"
                 UPDATE resource
                 SET status=$2, updated_at=$3
-                WHERE project_id=$1;
+                WHERE project_id=$1 AND status!=$2;
             "#,
             id,
             status,
@@ -308,6 +369,25 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
         Ok(())
     }
 
+    async fn restore(&self, id: &str, restored_at: &DateTime<Utc>) -> Result<()> {
+        let status = ProjectStatus::Active.to_string();
+
+        sqlx::query!(
+            r#"
+                UPDATE project
+                SET status=$2, updated_at=$3
+                WHERE id=$1;
+            "#,
+            id,
+            status,
+            restored_at
+        )
+        .execute(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
     async fn create_secret(&self, secret: &ProjectSecret) -> Result<()> {
         sqlx::query!(
             r#"
@@ -328,7 +408,7 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
             secret.secret,
             secret.created_at
         )
-        .execute(&self.sqlite.db)
+        .execute(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(())
@@ -350,7 +430,7 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
             "#,
         )
         .bind(project)
-        .fetch_all(&self.sqlite.db)
+        .fetch_all(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(secrets)
@@ -370,7 +450,7 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
             "#,
         )
         .bind(id)
-        .fetch_optional(&self.sqlite.db)
+        .fetch_optional(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(secret)
@@ -385,7 +465,7 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
             "#,
             id,
         )
-        .execute(&self.sqlite.db)
+        .execute(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(())
@@ -397,31 +477,45 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
     ) -> Result<Option<ProjectUser>> {
         let project_user = sqlx::query_as::<_, ProjectUser>(
             r#"
-                SELECT 
-                    pu.user_id, 
-                    pu.project_id, 
-                    pu.role, 
-                    pu.created_at
-                FROM project_user pu 
-                WHERE pu.user_id = $1 and pu.project_id = $2;
+                SELECT user_id, project_id, role, created_at
+                FROM (
+                    SELECT 
+                        pu.user_id, 
+                        pu.project_id, 
+                        pu.role, 
+                        pu.created_at
+                    FROM project_user pu 
+                    WHERE pu.user_id = $1 and pu.project_id = $2
+                    UNION ALL
+                    SELECT
+                        ou.user_id,
+                        p.id AS project_id,
+                        ou.role,
+                        ou.created_at
+                    FROM project p
+                    INNER JOIN organization_user ou ON ou.organization_id = p.organization_id
+                    WHERE ou.user_id = $1 and p.id = $2
+                ) permission
+                ORDER BY CASE role
+                    WHEN 'owner' THEN 0
+                    WHEN 'admin' THEN 1
+                    WHEN 'developer' THEN 2
+                    WHEN 'member' THEN 3
+                    WHEN 'viewer' THEN 4
+                    ELSE 5
+                END
+                LIMIT 1;
             "#,
         )
         .bind(user_id)
         .bind(project_id)
-        .fetch_optional(&self.sqlite.db)
+        .fetch_optional(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(project_user)
     }
 
-    async fn find_users(
-        &self,
-        project_id: &str,
-        page: &u32,
-        page_size: &u32,
-    ) -> Result<Vec<ProjectUser>> {
-        let offset = page_size * (page - 1);
-
+    async fn find_users(&self, project_id: &str, page: &Page) -> Result<Vec<ProjectUser>> {
         let users = sqlx::query_as::<_, ProjectUser>(
             r#"
 
@@ -432,15 +526,18 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
                     pu.created_at
                 FROM project_user pu 
                 WHERE pu.project_id = $1
-                ORDER BY pu.created_at DESC
+                    AND ($4 IS NULL OR (pu.created_at, pu.user_id) < ($4, $5))
+                ORDER BY pu.created_at DESC, pu.user_id DESC
                 LIMIT $2
                 OFFSET $3;
             "#,
         )
         .bind(project_id)
-        .bind(page_size)
-        .bind(offset)
-        .fetch_all(&self.sqlite.db)
+        .bind(page.page_size)
+        .bind(page.offset())
+        .bind(page.cursor_created_at())
+        .bind(page.cursor_id())
+        .fetch_all(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(users)
@@ -449,10 +546,8 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
     async fn find_user_invites(
         &self,
         project_id: &str,
-        page: &u32,
-        page_size: &u32,
+        page: &Page,
     ) -> Result<Vec<ProjectUserInvite>> {
-        let offset = page_size * (page - 1);
         let status = ProjectUserInviteStatus::Sent.to_string();
 
         let invites = sqlx::query_as::<_, ProjectUserInvite>(
@@ -469,17 +564,20 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
                     pui.updated_at
                 FROM project_user_invite pui
                 WHERE pui.project_id = $1 AND DATETIME($5) <= DATETIME(pui.expires_in) AND pui.status = $2
-                ORDER BY pui.created_at DESC
+                    AND ($6 IS NULL OR (pui.created_at, pui.id) < ($6, $7))
+                ORDER BY pui.created_at DESC, pui.id DESC
                 LIMIT $3
                 OFFSET $4;
             "#,
         )
         .bind(project_id)
         .bind(status)
-        .bind(page_size)
-        .bind(offset)
+        .bind(page.page_size)
+        .bind(page.offset())
         .bind(chrono::Utc::now())
-        .fetch_all(&self.sqlite.db)
+        .bind(page.cursor_created_at())
+        .bind(page.cursor_id())
+        .fetch_all(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(invites)
@@ -502,7 +600,7 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
             "#,
         )
         .bind(id)
-        .fetch_optional(&self.sqlite.db)
+        .fetch_optional(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(invite)
@@ -525,7 +623,7 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
             "#,
         )
         .bind(code)
-        .fetch_optional(&self.sqlite.db)
+        .fetch_optional(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(invite)
@@ -560,14 +658,15 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
             invite.created_at,
             invite.updated_at
         )
-        .execute(&self.sqlite.db)
+        .execute(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(())
     }
 
     async fn create_user_acceptance(&self, invite_id: &str, user: &ProjectUser) -> Result<()> {
-        let mut tx = self.sqlite.db.begin().await?;
+        let mut conn = self.sqlite.conn().await?;
+        let mut tx = conn.begin().await?;
 
         let role = user.role.to_string();
         sqlx::query!(
@@ -617,7 +716,7 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
             "#,
             invite_id,
         )
-        .execute(&self.sqlite.db)
+        .execute(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(())
@@ -634,11 +733,194 @@ impl ProjectDrivenCache for SqliteProjectDrivenCache {
             project_id,
             id,
         )
-        .execute(&self.sqlite.db)
+        .execute(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(())
     }
+
+    async fn find_ownership_transfer_by_id(
+        &self,
+        id: &str,
+    ) -> Result<Option<ProjectOwnershipTransfer>> {
+        let transfer = sqlx::query_as::<_, ProjectOwnershipTransfer>(
+            r#"
+                SELECT
+                    pot.id,
+                    pot.project_id,
+                    pot.previous_owner,
+                    pot.new_owner,
+                    pot.code,
+                    pot.status,
+                    pot.expires_in,
+                    pot.created_at,
+                    pot.updated_at
+                FROM project_ownership_transfer pot
+                WHERE pot.id = $1;
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(transfer)
+    }
+
+    async fn find_ownership_transfer_by_code(
+        &self,
+        code: &str,
+    ) -> Result<Option<ProjectOwnershipTransfer>> {
+        let transfer = sqlx::query_as::<_, ProjectOwnershipTransfer>(
+            r#"
+                SELECT
+                    pot.id,
+                    pot.project_id,
+                    pot.previous_owner,
+                    pot.new_owner,
+                    pot.code,
+                    pot.status,
+                    pot.expires_in,
+                    pot.created_at,
+                    pot.updated_at
+                FROM project_ownership_transfer pot
+                WHERE pot.code = $1;
+            "#,
+        )
+        .bind(code)
+        .fetch_optional(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(transfer)
+    }
+
+    async fn create_ownership_transfer(&self, transfer: &ProjectOwnershipTransfer) -> Result<()> {
+        sqlx::query(
+            r#"
+                INSERT INTO project_ownership_transfer (
+                    id,
+                    project_id,
+                    previous_owner,
+                    new_owner,
+                    code,
+                    status,
+                    expires_in,
+                    created_at,
+                    updated_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9);
+            "#,
+        )
+        .bind(&transfer.id)
+        .bind(&transfer.project_id)
+        .bind(&transfer.previous_owner)
+        .bind(&transfer.new_owner)
+        .bind(&transfer.code)
+        .bind(transfer.status.to_string())
+        .bind(transfer.expires_in)
+        .bind(transfer.created_at)
+        .bind(transfer.updated_at)
+        .execute(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn accept_ownership_transfer(
+        &self,
+        transfer_id: &str,
+        change: &ProjectOwnerChange,
+    ) -> Result<()> {
+        let mut conn = self.sqlite.conn().await?;
+        let mut tx = conn.begin().await?;
+        update_owner(&mut tx, change).await?;
+
+        sqlx::query(
+            r#"
+                UPDATE project_ownership_transfer
+                SET status = $1, updated_at = $2
+                WHERE id = $3;
+            "#,
+        )
+        .bind(ProjectOwnershipTransferStatus::Accepted.to_string())
+        .bind(change.changed_at)
+        .bind(transfer_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn cancel_ownership_transfer(
+        &self,
+        transfer_id: &str,
+        cancelled_at: &DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE project_ownership_transfer
+                SET status = $1, updated_at = $2
+                WHERE id = $3;
+            "#,
+        )
+        .bind(ProjectOwnershipTransferStatus::Cancelled.to_string())
+        .bind(cancelled_at)
+        .bind(transfer_id)
+        .execute(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_quota(&self, project_id: &str) -> Result<Option<ProjectQuota>> {
+        let quota = sqlx::query_as::<_, ProjectQuota>(
+            r#"
+                SELECT project_id, resources, max_tier, workers, updated_at
+                FROM project_quota
+                WHERE project_id = $1;
+            "#,
+        )
+        .bind(project_id)
+        .fetch_optional(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(quota)
+    }
+
+    async fn find_default_quota(&self) -> Result<Option<ProjectQuota>> {
+        self.find_quota(DEFAULT_QUOTA_ID).await
+    }
+
+    async fn update_quota(&self, quota: &ProjectQuota) -> Result<()> {
+        // Sorted, so the same quota is the same text in every cache and the checksums match.
+        let resources: BTreeMap<_, _> = quota.resources.iter().collect();
+
+        sqlx::query(
+            r#"
+                INSERT INTO project_quota (project_id, resources, max_tier, workers, updated_at)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (project_id) DO UPDATE SET
+                    resources = excluded.resources,
+                    max_tier = excluded.max_tier,
+                    workers = excluded.workers,
+                    updated_at = excluded.updated_at;
+            "#,
+        )
+        .bind(quota.project_id.as_deref().unwrap_or(DEFAULT_QUOTA_ID))
+        .bind(serde_json::to_string(&resources)?)
+        .bind(quota.max_tier.map(i64::from))
+        .bind(quota.workers.map(i64::from))
+        .bind(quota.updated_at)
+        .execute(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn wait_applied(&self, offset: &EventOffset, timeout: Duration) -> Result<bool> {
+        self.sqlite.wait_applied(offset, timeout).await
+    }
 }
 #[async_trait::async_trait]
 impl ProjectDrivenCacheBackoffice for SqliteProjectDrivenCache {
@@ -654,6 +936,8 @@ impl ProjectDrivenCacheBackoffice for SqliteProjectDrivenCache {
                     p.billing_provider,
                     p.billing_provider_id,
                     p.billing_subscription_id,
+                    p.cluster_id,
+                    p.organization_id,
                     p.created_at,
                     p.updated_at
                 FROM 
@@ -665,7 +949,7 @@ impl ProjectDrivenCacheBackoffice for SqliteProjectDrivenCache {
             "#,
         )
         .bind(id)
-        .fetch_all(&self.sqlite.db)
+        .fetch_all(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(projects)
@@ -683,6 +967,8 @@ impl ProjectDrivenCacheBackoffice for SqliteProjectDrivenCache {
                     p.billing_provider,
                     p.billing_provider_id,
                     p.billing_subscription_id,
+                    p.cluster_id,
+                    p.organization_id,
                     p.created_at,
                     p.updated_at
                 FROM 
@@ -692,7 +978,7 @@ impl ProjectDrivenCacheBackoffice for SqliteProjectDrivenCache {
             "#,
         )
         .bind(id)
-        .fetch_optional(&self.sqlite.db)
+        .fetch_optional(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(project)
@@ -710,6 +996,8 @@ impl ProjectDrivenCacheBackoffice for SqliteProjectDrivenCache {
 	                  p.billing_provider,
 	                  p.billing_provider_id,
 	                  p.billing_subscription_id,
+	                  p.cluster_id,
+	                  p.organization_id,
 	                  p.created_at,
 	                  p.updated_at
                 FROM
@@ -719,7 +1007,7 @@ impl ProjectDrivenCacheBackoffice for SqliteProjectDrivenCache {
             "#,
         )
         .bind(format!("%{value}%"))
-        .fetch_all(&self.sqlite.db)
+        .fetch_all(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(projects)
@@ -737,6 +1025,8 @@ impl ProjectDrivenCacheBackoffice for SqliteProjectDrivenCache {
 	                  p.billing_provider,
 	                  p.billing_provider_id,
 	                  p.billing_subscription_id,
+	                  p.cluster_id,
+	                  p.organization_id,
 	                  p.created_at,
 	                  p.updated_at
                 FROM
@@ -746,7 +1036,7 @@ impl ProjectDrivenCacheBackoffice for SqliteProjectDrivenCache {
             "#,
         )
         .bind(resource_name)
-        .fetch_all(&self.sqlite.db)
+        .fetch_all(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(projects)
@@ -783,13 +1073,58 @@ impl ProjectDrivenCacheBackoffice for SqliteProjectDrivenCache {
             "#,
         )
         .bind(after)
-        .fetch_all(&self.sqlite.db)
+        .fetch_all(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(users)
     }
 }
 
+async fn update_owner(tx: &mut Transaction<'_, Sqlite>, change: &ProjectOwnerChange) -> Result<()> {
+    sqlx::query!(
+        r#"
+            UPDATE project
+            SET owner = $1, updated_at = $2
+            WHERE id = $3
+        "#,
+        change.new_owner,
+        change.changed_at,
+        change.project_id,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    let owner_role = ProjectUserRole::Owner.to_string();
+    sqlx::query!(
+        r#"
+            UPDATE project_user
+            SET role = $1
+            WHERE project_id = $2 AND user_id = $3
+        "#,
+        owner_role,
+        change.project_id,
+        change.new_owner,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    let member_role = ProjectUserRole::Member.to_string();
+    sqlx::query!(
+        r#"
+            UPDATE project_user
+            SET role = $1
+            WHERE project_id = $2 AND user_id = $3
+        "#,
+        member_role,
+        change.project_id,
+        change.previous_owner,
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
 impl FromRow<'_, SqliteRow> for Project {
     fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
         let status: &str = row.try_get("status")?;
@@ -805,6 +1140,8 @@ impl FromRow<'_, SqliteRow> for Project {
             billing_provider: row.try_get("billing_provider")?,
             billing_provider_id: row.try_get("billing_provider_id")?,
             billing_subscription_id: row.try_get("billing_subscription_id")?,
+            cluster_id: row.try_get("cluster_id")?,
+            organization_id: row.try_get("organization_id")?,
             created_at: row.try_get("created_at")?,
             updated_at: row.try_get("updated_at")?,
         })
@@ -862,6 +1199,26 @@ impl FromRow<'_, SqliteRow> for ProjectUserInvite {
     }
 }
 
+impl FromRow<'_, SqliteRow> for ProjectOwnershipTransfer {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        let status: &str = row.try_get("status")?;
+
+        Ok(Self {
+            id: row.try_get("id")?,
+            project_id: row.try_get("project_id")?,
+            previous_owner: row.try_get("previous_owner")?,
+            new_owner: row.try_get("new_owner")?,
+            code: row.try_get("code")?,
+            status: status
+                .parse()
+                .map_err(|err: Error| sqlx::Error::Decode(err.into()))?,
+            expires_in: row.try_get("expires_in")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
 impl FromRow<'_, SqliteRow> for ProjectUserProject {
     fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
         let role: &str = row.try_get("role")?;
@@ -886,9 +1243,28 @@ impl FromRow<'_, SqliteRow> for ProjectUserProject {
     }
 }
 
+impl FromRow<'_, SqliteRow> for ProjectQuota {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        let project_id: String = row.try_get("project_id")?;
+        let resources: &str = row.try_get("resources")?;
+        let max_tier: Option<i64> = row.try_get("max_tier")?;
+        let workers: Option<i64> = row.try_get("workers")?;
+
+        Ok(Self {
+            project_id: (project_id != DEFAULT_QUOTA_ID).then_some(project_id),
+            resources: serde_json::from_str(resources)
+                .map_err(|err| sqlx::Error::Decode(err.into()))?,
+            max_tier: max_tier.map(|v| v as u32),
+            workers: workers.map(|v| v as u32),
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::pagination::Cursor;
 
     async fn get_cache() -> SqliteProjectDrivenCache {
         let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
@@ -910,7 +1286,7 @@ mod tests {
         let project = Project::default();
 
         cache.create(&project).await.unwrap();
-        let result = cache.find(&project.owner, &1, &12).await;
+        let result = cache.find(&project.owner, &Page::default()).await;
 
         assert!(result.is_ok());
         assert!(result.unwrap().len() == 1);
@@ -921,15 +1297,81 @@ mod tests {
         let project = Project::default();
 
         cache.create(&project).await.unwrap();
-        let result = cache.find(&project.owner, &2, &12).await;
+        let page = Page {
+            page: 2,
+            ..Default::default()
+        };
+        let result = cache.find(&project.owner, &page).await;
 
         assert!(result.is_ok());
         assert!(result.unwrap().is_empty());
     }
     #[tokio::test]
+    async fn it_should_find_user_projects_by_membership_time_without_cursor() {
+        let cache = get_cache().await;
+
+        let joined = Project {
+            owner: "other user id".into(),
+            ..Default::default()
+        };
+        cache.create(&joined).await.unwrap();
+        let owned = Project {
+            namespace: "other-namespace".into(),
+            created_at: joined.created_at + chrono::Duration::seconds(1),
+            ..Default::default()
+        };
+        cache.create(&owned).await.unwrap();
+
+        let member = ProjectUser {
+            user_id: owned.owner.clone(),
+            project_id: joined.id.clone(),
+            role: ProjectUserRole::Member,
+            created_at: owned.created_at + chrono::Duration::seconds(1),
+        };
+        cache
+            .create_user_acceptance("invite id", &member)
+            .await
+            .unwrap();
+
+        let result = cache.find(&owned.owner, &Page::default()).await.unwrap();
+        assert!(result[0].id == joined.id);
+
+        let result = cache
+            .find(&owned.owner, &Page::after(None, 12))
+            .await
+            .unwrap();
+        assert!(result[0].id == owned.id);
+    }
+    #[tokio::test]
+    async fn it_should_find_user_projects_after_cursor() {
+        let cache = get_cache().await;
+
+        let first = Project::default();
+        cache.create(&first).await.unwrap();
+        let second = Project {
+            namespace: "other-namespace".into(),
+            created_at: first.created_at + chrono::Duration::seconds(1),
+            ..Default::default()
+        };
+        cache.create(&second).await.unwrap();
+
+        let page = Page::after(None, 1);
+        let result = cache.find(&first.owner, &page).await.unwrap();
+        assert!(result.len() == 1);
+        assert!(result[0].id == second.id);
+
+        let page = Page::after(
+            Some(Cursor::new(Some(result[0].created_at), &result[0].id)),
+            1,
+        );
+        let result = cache.find(&first.owner, &page).await.unwrap();
+        assert!(result.len() == 1);
+        assert!(result[0].id == first.id);
+    }
+    #[tokio::test]
     async fn it_should_return_none_find_user_projects() {
         let cache = get_cache().await;
-        let result = cache.find(Default::default(), &1, &12).await;
+        let result = cache.find(Default::default(), &Page::default()).await;
 
         assert!(result.is_ok());
         assert!(result.unwrap().is_empty());
@@ -1048,6 +1490,74 @@ mod tests {
         assert!(result.unwrap().is_none());
     }
 
+    #[tokio::test]
+    async fn it_should_set_subscription_of_projects_found_by_billing_customer() {
+        let cache = get_cache().await;
+        let project = Project::default();
+        cache.create(&project).await.unwrap();
+
+        let projects = cache
+            .find_by_billing_provider_id(&project.billing_provider_id)
+            .await
+            .unwrap();
+        assert!(projects.len() == 1);
+
+        cache
+            .update(&ProjectUpdate {
+                id: project.id.clone(),
+                name: None,
+                status: Some(ProjectStatus::PaymentMethodFailed),
+                billing_subscription_id: Some(Some("sub_123".into())),
+                updated_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        let updated = cache.find_by_id(&project.id).await.unwrap().unwrap();
+        assert!(matches!(updated.status, ProjectStatus::PaymentMethodFailed));
+        assert_eq!(updated.billing_subscription_id.as_deref(), Some("sub_123"));
+        assert_eq!(updated.name, project.name);
+
+        cache
+            .update(&ProjectUpdate {
+                id: project.id.clone(),
+                name: None,
+                status: None,
+                billing_subscription_id: Some(None),
+                updated_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        let updated = cache.find_by_id(&project.id).await.unwrap().unwrap();
+        assert!(matches!(updated.status, ProjectStatus::PaymentMethodFailed));
+        assert!(updated.billing_subscription_id.is_none());
+    }
+
+    #[tokio::test]
+    async fn it_should_restore_deleted_project() {
+        let cache = get_cache().await;
+        let project = Project::default();
+        cache.create(&project).await.unwrap();
+
+        cache.delete(&project.id, &Utc::now()).await.unwrap();
+        assert!(cache.find_by_id(&project.id).await.unwrap().is_none());
+        assert!(cache
+            .find_deleted_by_id(&project.id)
+            .await
+            .unwrap()
+            .is_some());
+
+        cache.restore(&project.id, &Utc::now()).await.unwrap();
+        let restored = cache.find_by_id(&project.id).await.unwrap().unwrap();
+        assert!(matches!(restored.status, ProjectStatus::Active));
+        assert!(cache
+            .find_deleted_by_id(&project.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
     #[tokio::test]
     async fn it_should_find_project_by_namespace() {
         let cache: Box<dyn ProjectDrivenCache> = Box::new(get_cache().await);
@@ -1237,7 +1747,7 @@ mod tests {
         };
         cache.create_user_invite(&invite).await.unwrap();
 
-        let result = cache.find_user_invites(&project.id, &1, &12).await;
+        let result = cache.find_user_invites(&project.id, &Page::default()).await;
 
         assert!(result.is_ok());
         assert!(result.unwrap().len() == 1);
@@ -1245,7 +1755,9 @@ mod tests {
     #[tokio::test]
     async fn it_should_return_none_find_user_invites() {
         let cache = get_cache().await;
-        let result = cache.find_user_invites(Default::default(), &1, &12).await;
+        let result = cache
+            .find_user_invites(Default::default(), &Page::default())
+            .await;
 
         assert!(result.is_ok());
         assert!(result.unwrap().is_empty());
@@ -1315,7 +1827,7 @@ mod tests {
         let project = Project::default();
         cache.create(&project).await.unwrap();
 
-        let result = cache.find_users(&project.id, &1, &12).await;
+        let result = cache.find_users(&project.id, &Page::default()).await;
 
         assert!(result.is_ok());
         assert!(result.unwrap().len() == 1);
@@ -1323,7 +1835,7 @@ mod tests {
     #[tokio::test]
     async fn it_should_return_none_find_users() {
         let cache = get_cache().await;
-        let result = cache.find_users(Default::default(), &1, &12).await;
+        let result = cache.find_users(Default::default(), &Page::default()).await;
 
         assert!(result.is_ok());
         assert!(result.unwrap().is_empty());
@@ -1355,4 +1867,110 @@ mod tests {
 
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn it_should_accept_ownership_transfer() {
+        let cache = get_cache().await;
+        let project = Project::default();
+        cache.create(&project).await.unwrap();
+
+        let new_owner = ProjectUser {
+            user_id: "member user id".into(),
+            project_id: project.id.clone(),
+            role: ProjectUserRole::Member,
+            created_at: Utc::now(),
+        };
+        cache
+            .create_user_acceptance("invite id", &new_owner)
+            .await
+            .unwrap();
+
+        let transfer = ProjectOwnershipTransfer {
+            project_id: project.id.clone(),
+            previous_owner: project.owner.clone(),
+            new_owner: new_owner.user_id.clone(),
+            ..Default::default()
+        };
+        cache.create_ownership_transfer(&transfer).await.unwrap();
+
+        let change = ProjectOwnerChange {
+            project_id: project.id.clone(),
+            previous_owner: project.owner.clone(),
+            new_owner: new_owner.user_id.clone(),
+            changed_at: Utc::now(),
+        };
+        let result = cache.accept_ownership_transfer(&transfer.id, &change).await;
+        assert!(result.is_ok());
+
+        let updated = cache.find_by_id(&project.id).await.unwrap().unwrap();
+        assert_eq!(updated.owner, new_owner.user_id);
+
+        let accepted = cache
+            .find_ownership_transfer_by_code(&transfer.code)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(accepted.status, ProjectOwnershipTransferStatus::Accepted);
+    }
+
+    #[tokio::test]
+    async fn it_should_cancel_ownership_transfer() {
+        let cache = get_cache().await;
+        let project = Project::default();
+        cache.create(&project).await.unwrap();
+
+        let transfer = ProjectOwnershipTransfer {
+            project_id: project.id.clone(),
+            ..Default::default()
+        };
+        cache.create_ownership_transfer(&transfer).await.unwrap();
+
+        let result = cache
+            .cancel_ownership_transfer(&transfer.id, &Utc::now())
+            .await;
+        assert!(result.is_ok());
+
+        let cancelled = cache
+            .find_ownership_transfer_by_id(&transfer.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(cancelled.status, ProjectOwnershipTransferStatus::Cancelled);
+
+        let unchanged = cache.find_by_id(&project.id).await.unwrap().unwrap();
+        assert_eq!(unchanged.owner, project.owner);
+    }
+
+    #[tokio::test]
+    async fn it_should_update_project_and_default_quota() {
+        let cache = get_cache().await;
+        let project = Project::default();
+        cache.create(&project).await.unwrap();
+
+        let default = ProjectQuota {
+            max_tier: Some(1),
+            updated_at: Utc::now(),
+            ..Default::default()
+        };
+        cache.update_quota(&default).await.unwrap();
+
+        let quota = ProjectQuota {
+            project_id: Some(project.id.clone()),
+            resources: [("CardanoNodePort".to_string(), 2)].into(),
+            workers: Some(3),
+            updated_at: Utc::now(),
+            ..Default::default()
+        };
+        cache.update_quota(&quota).await.unwrap();
+        cache.update_quota(&quota).await.unwrap();
+
+        let result = cache.find_default_quota().await.unwrap().unwrap();
+        assert_eq!(result.project_id, None);
+        assert_eq!(result.max_tier, Some(1));
+
+        let result = cache.find_quota(&project.id).await.unwrap().unwrap();
+        assert_eq!(result.resources.get("CardanoNodePort"), Some(&2));
+        assert_eq!(result.workers, Some(3));
+        assert_eq!(result.max_tier, None);
+    }
 }
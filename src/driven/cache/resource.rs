@@ -1,9 +1,11 @@
 use chrono::{DateTime, Utc};
 use sqlx::{sqlite::SqliteRow, FromRow, Row};
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use crate::domain::{
     error::Error,
+    event::EventOffset,
+    pagination::Page,
     resource::{
         cache::{ResourceDrivenCache, ResourceDrivenCacheBackoffice},
         Resource, ResourceProject, ResourceStatus, ResourceUpdate,
@@ -23,9 +25,7 @@ impl SqliteResourceDrivenCache {
 }
 #[async_trait::async_trait]
 impl ResourceDrivenCache for SqliteResourceDrivenCache {
-    async fn find(&self, project_id: &str, page: &u32, page_size: &u32, category: &str) -> Result<Vec<Resource>> {
-        let offset = page_size * (page - 1);
-
+    async fn find(&self, project_id: &str, page: &Page, category: &str) -> Result<Vec<Resource>> {
         let resources = sqlx::query_as::<_, Resource>(
             r#"
                 SELECT
@@ -34,13 +34,15 @@ impl ResourceDrivenCache for SqliteResourceDrivenCache {
                     r.name,
                     r.kind,
                     r.category,
+                    r.cluster_id,
                     r.spec,
                     r.status,
                     r.created_at,
                     r.updated_at
                 FROM resource r
                 WHERE r.project_id = $1 and r.status != $2 and r.category = $3
-                ORDER BY r.created_at DESC
+                    AND ($6 IS NULL OR (r.created_at, r.id) < ($6, $7))
+                ORDER BY r.created_at DESC, r.id DESC
                 LIMIT $4
                 OFFSET $5;
             "#
@@ -48,9 +50,11 @@ impl ResourceDrivenCache for SqliteResourceDrivenCache {
         .bind(project_id)
         .bind(ResourceStatus::Deleted.to_string())
         .bind(category)
-        .bind(page_size)
-        .bind(offset)
-        .fetch_all(&self.sqlite.db)
+        .bind(page.page_size)
+        .bind(page.offset())
+        .bind(page.cursor_created_at())
+        .bind(page.cursor_id())
+        .fetch_all(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(resources)
@@ -64,6 +68,7 @@ impl ResourceDrivenCache for SqliteResourceDrivenCache {
                     r.name,
                     r.kind,
                     r.category,
+                    r.cluster_id,
                     r.spec,
                     r.status,
                     r.created_at,
@@ -74,11 +79,66 @@ impl ResourceDrivenCache for SqliteResourceDrivenCache {
         )
         .bind(id)
         .bind(ResourceStatus::Deleted.to_string())
-        .fetch_optional(&self.sqlite.db)
+        .fetch_optional(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(resource)
     }
+    async fn find_deleted_by_id(&self, id: &str) -> Result<Option<Resource>> {
+        let resource = sqlx::query_as::<_, Resource>(
+            r#"
+                SELECT
+                    r.id,
+                    r.project_id,
+                    r.name,
+                    r.kind,
+                    r.category,
+                    r.cluster_id,
+                    r.spec,
+                    r.status,
+                    r.created_at,
+                    r.updated_at
+                FROM resource r
+                WHERE r.id = $1 and r.status = $2;
+            "#,
+        )
+        .bind(id)
+        .bind(ResourceStatus::Deleted.to_string())
+        .fetch_optional(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(resource)
+    }
+    async fn find_deleted_with_project(
+        &self,
+        project_id: &str,
+        deleted_at: &DateTime<Utc>,
+    ) -> Result<Vec<Resource>> {
+        let resources = sqlx::query_as::<_, Resource>(
+            r#"
+                SELECT
+                    r.id,
+                    r.project_id,
+                    r.name,
+                    r.kind,
+                    r.category,
+                    r.cluster_id,
+                    r.spec,
+                    r.status,
+                    r.created_at,
+                    r.updated_at
+                FROM resource r
+                WHERE r.project_id = $1 and r.status = $2 and r.updated_at = $3;
+            "#,
+        )
+        .bind(project_id)
+        .bind(ResourceStatus::Deleted.to_string())
+        .bind(deleted_at)
+        .fetch_all(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(resources)
+    }
     async fn find_by_name(&self, project_id: &str, name: &str) -> Result<Option<Resource>> {
         let resource = sqlx::query_as::<_, Resource>(
             r#"
@@ -88,6 +148,7 @@ impl ResourceDrivenCache for SqliteResourceDrivenCache {
                     r.name,
                     r.kind,
                     r.category,
+                    r.cluster_id,
                     r.spec,
                     r.status,
                     r.created_at,
@@ -99,12 +160,46 @@ impl ResourceDrivenCache for SqliteResourceDrivenCache {
         .bind(project_id)
         .bind(name)
         .bind(ResourceStatus::Deleted.to_string())
-        .fetch_optional(&self.sqlite.db)
+        .fetch_optional(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(resource)
     }
 
+    async fn count_by_kind(&self, project_id: &str, kind: &str) -> Result<u64> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+                SELECT COUNT(*)
+                FROM resource r
+                WHERE r.project_id = $1 AND r.kind = $2 AND r.status != $3;
+            "#,
+        )
+        .bind(project_id)
+        .bind(kind)
+        .bind(ResourceStatus::Deleted.to_string())
+        .fetch_one(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(count as u64)
+    }
+
+    async fn count_by_category(&self, project_id: &str, category: &str) -> Result<u64> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+                SELECT COUNT(*)
+                FROM resource r
+                WHERE r.project_id = $1 AND r.category = $2 AND r.status != $3;
+            "#,
+        )
+        .bind(project_id)
+        .bind(category)
+        .bind(ResourceStatus::Deleted.to_string())
+        .fetch_one(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(count as u64)
+    }
+
     async fn create(&self, resource: &Resource) -> Result<()> {
         let status = resource.status.to_string();
 
@@ -118,10 +213,11 @@ impl ResourceDrivenCache for SqliteResourceDrivenCache {
                     category,
                     spec,
                     status,
+                    cluster_id,
                     created_at,
                     updated_at
                 )
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
             "#,
             resource.id,
             resource.project_id,
@@ -130,10 +226,11 @@ impl ResourceDrivenCache for SqliteResourceDrivenCache {
             resource.category,
             resource.spec,
             status,
+            resource.cluster_id,
             resource.created_at,
             resource.updated_at
         )
-        .execute(&self.sqlite.db)
+        .execute(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(())
@@ -176,7 +273,7 @@ impl ResourceDrivenCache for SqliteResourceDrivenCache {
             resource_update.updated_at,
             resource.id,
         )
-        .execute(&self.sqlite.db)
+        .execute(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(())
@@ -197,11 +294,36 @@ impl ResourceDrivenCache for SqliteResourceDrivenCache {
             status,
             deleted_at
         )
-        .execute(&self.sqlite.db)
+        .execute(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn restore(&self, id: &str, restored_at: &DateTime<Utc>) -> Result<()> {
+        let status = ResourceStatus::Active.to_string();
+
+        sqlx::query!(
+            r#"
+                UPDATE resource
+                SET
+                    status=$2,
+                    updated_at=$3
+                WHERE id=$1;
+            "#,
+            id,
+            status,
+            restored_at
+        )
+        .execute(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(())
     }
+
+    async fn wait_applied(&self, offset: &EventOffset, timeout: Duration) -> Result<bool> {
+        self.sqlite.wait_applied(offset, timeout).await
+    }
 }
 
 #[async_trait::async_trait]
@@ -218,6 +340,7 @@ impl ResourceDrivenCacheBackoffice for SqliteResourceDrivenCache {
                     r.name,
                     r.kind,
                     r.category,
+                    r.cluster_id,
                     r.spec,
                     r.status,
                     r.created_at,
@@ -230,7 +353,7 @@ impl ResourceDrivenCacheBackoffice for SqliteResourceDrivenCache {
             "#,
         )
         .bind(status)
-        .fetch_all(&self.sqlite.db)
+        .fetch_all(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(resources)
@@ -245,6 +368,7 @@ impl ResourceDrivenCacheBackoffice for SqliteResourceDrivenCache {
                     r.name,
                     r.kind,
                     r.category,
+                    r.cluster_id,
                     r.spec,
                     r.status,
                     r.created_at,
@@ -258,7 +382,7 @@ impl ResourceDrivenCacheBackoffice for SqliteResourceDrivenCache {
             "#,
         )
         .bind(namespace)
-        .fetch_all(&self.sqlite.db)
+        .fetch_all(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(resources)
@@ -273,6 +397,7 @@ impl ResourceDrivenCacheBackoffice for SqliteResourceDrivenCache {
                     r.name,
                     r.kind,
                     r.category,
+                    r.cluster_id,
                     r.spec,
                     r.status,
                     r.created_at,
@@ -284,7 +409,7 @@ impl ResourceDrivenCacheBackoffice for SqliteResourceDrivenCache {
             "#,
         )
         .bind(format!("%{value}%"))
-        .fetch_all(&self.sqlite.db)
+        .fetch_all(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(resources)
@@ -306,6 +431,7 @@ impl FromRow<'_, SqliteRow> for Resource {
             status: status
                 .parse()
                 .map_err(|err: Error| sqlx::Error::Decode(err.into()))?,
+            cluster_id: row.try_get("cluster_id")?,
             created_at: row.try_get("created_at")?,
             updated_at: row.try_get("updated_at")?,
         })
@@ -336,7 +462,10 @@ impl FromRow<'_, SqliteRow> for ResourceProject {
 
 #[cfg(test)]
 mod tests {
-    use crate::{domain::DEFAULT_CATEGORY, driven::cache::tests::mock_project};
+    use crate::{
+        domain::{pagination::Cursor, project::cache::ProjectDrivenCache, DEFAULT_CATEGORY},
+        driven::cache::{project::SqliteProjectDrivenCache, tests::mock_project},
+    };
 
     use super::*;
 
@@ -353,12 +482,48 @@ mod tests {
         };
         cache.create(&resource).await.unwrap();
 
-        let result = cache.find(&project.id, &1, &12, DEFAULT_CATEGORY).await;
+        let result = cache
+            .find(&project.id, &Page::default(), DEFAULT_CATEGORY)
+            .await;
 
         assert!(result.is_ok());
         assert!(result.unwrap().len() == 1);
     }
     #[tokio::test]
+    async fn it_should_find_project_resources_after_cursor() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let cache = SqliteResourceDrivenCache::new(sqlite_cache.clone());
+
+        let project = mock_project(sqlite_cache.clone()).await;
+
+        // Same creation time, the id breaks the tie.
+        let created_at = Utc::now();
+        for _ in 0..3 {
+            let resource = Resource {
+                project_id: project.id.clone(),
+                created_at,
+                ..Default::default()
+            };
+            cache.create(&resource).await.unwrap();
+        }
+
+        let page = Page::after(None, 2);
+        let first = cache
+            .find(&project.id, &page, DEFAULT_CATEGORY)
+            .await
+            .unwrap();
+        assert!(first.len() == 2);
+
+        let last = first.last().unwrap();
+        let page = Page::after(Some(Cursor::new(Some(last.created_at), &last.id)), 2);
+        let second = cache
+            .find(&project.id, &page, DEFAULT_CATEGORY)
+            .await
+            .unwrap();
+        assert!(second.len() == 1);
+        assert!(first.iter().all(|r| r.id != second[0].id));
+    }
+    #[tokio::test]
     async fn it_should_return_none_find_project_resources_when_resource_was_deleted() {
         let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
         let cache = SqliteResourceDrivenCache::new(sqlite_cache.clone());
@@ -372,7 +537,9 @@ mod tests {
         cache.create(&resource).await.unwrap();
         cache.delete(&resource.id, &Utc::now()).await.unwrap();
 
-        let result = cache.find(&project.id, &1, &12, DEFAULT_CATEGORY).await;
+        let result = cache
+            .find(&project.id, &Page::default(), DEFAULT_CATEGORY)
+            .await;
 
         assert!(result.is_ok());
         assert!(result.unwrap().is_empty());
@@ -390,7 +557,11 @@ mod tests {
         };
         cache.create(&resource).await.unwrap();
 
-        let result = cache.find(&project.id, &2, &12, DEFAULT_CATEGORY).await;
+        let page = Page {
+            page: 2,
+            ..Default::default()
+        };
+        let result = cache.find(&project.id, &page, DEFAULT_CATEGORY).await;
 
         assert!(result.is_ok());
         assert!(result.unwrap().is_empty());
@@ -400,7 +571,9 @@ mod tests {
         let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
         let cache = SqliteResourceDrivenCache::new(sqlite_cache.clone());
 
-        let result = cache.find(Default::default(), &1, &12, DEFAULT_CATEGORY).await;
+        let result = cache
+            .find(Default::default(), &Page::default(), DEFAULT_CATEGORY)
+            .await;
 
         assert!(result.is_ok());
         assert!(result.unwrap().is_empty());
@@ -480,4 +653,91 @@ mod tests {
 
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn it_should_restore_only_resources_deleted_with_project() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let cache = SqliteResourceDrivenCache::new(sqlite_cache.clone());
+        let project_cache = SqliteProjectDrivenCache::new(sqlite_cache.clone());
+
+        let project = mock_project(sqlite_cache.clone()).await;
+
+        let deleted_before = Resource {
+            project_id: project.id.clone(),
+            ..Default::default()
+        };
+        cache.create(&deleted_before).await.unwrap();
+        cache
+            .delete(
+                &deleted_before.id,
+                &(Utc::now() - chrono::Duration::days(1)),
+            )
+            .await
+            .unwrap();
+
+        let resource = Resource {
+            project_id: project.id.clone(),
+            ..Default::default()
+        };
+        cache.create(&resource).await.unwrap();
+
+        project_cache
+            .delete(&project.id, &Utc::now())
+            .await
+            .unwrap();
+        let deleted = project_cache
+            .find_deleted_by_id(&project.id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let result = cache
+            .find_deleted_with_project(&project.id, &deleted.updated_at)
+            .await
+            .unwrap();
+        assert!(result.len() == 1);
+        assert!(result[0].id == resource.id);
+
+        cache.restore(&resource.id, &Utc::now()).await.unwrap();
+        assert!(cache.find_by_id(&resource.id).await.unwrap().is_some());
+        assert!(cache
+            .find_deleted_by_id(&resource.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn it_should_count_active_resources_by_kind() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let cache = SqliteResourceDrivenCache::new(sqlite_cache.clone());
+
+        let project = mock_project(sqlite_cache.clone()).await;
+
+        for _ in 0..2 {
+            let resource = Resource {
+                project_id: project.id.clone(),
+                ..Default::default()
+            };
+            cache.create(&resource).await.unwrap();
+        }
+        let deleted = Resource {
+            project_id: project.id.clone(),
+            ..Default::default()
+        };
+        cache.create(&deleted).await.unwrap();
+        cache.delete(&deleted.id, &Utc::now()).await.unwrap();
+
+        let count = cache
+            .count_by_kind(&project.id, &deleted.kind)
+            .await
+            .unwrap();
+        assert!(count == 2);
+
+        let count = cache
+            .count_by_category(&project.id, DEFAULT_CATEGORY)
+            .await
+            .unwrap();
+        assert!(count == 2);
+    }
 }
@@ -0,0 +1,293 @@
+use sqlx::{sqlite::SqliteRow, FromRow, Row};
+use std::sync::Arc;
+
+use crate::domain::{
+    search::{cache::SearchDrivenCache, SearchFilter, SearchResult},
+    Result,
+};
+
+use super::SqliteCache;
+
+pub struct SqliteSearchDrivenCache {
+    sqlite: Arc<SqliteCache>,
+}
+impl SqliteSearchDrivenCache {
+    pub fn new(sqlite: Arc<SqliteCache>) -> Self {
+        Self { sqlite }
+    }
+}
+#[async_trait::async_trait]
+impl SearchDrivenCache for SqliteSearchDrivenCache {
+    async fn search(
+        &self,
+        filter: &SearchFilter,
+        page: &u32,
+        page_size: &u32,
+    ) -> Result<Vec<SearchResult>> {
+        let offset = page_size * (page - 1);
+
+        let results = sqlx::query_as::<_, SearchResult>(
+            r#"
+                SELECT
+                    p.id as project_id,
+                    p.namespace as project_namespace,
+                    p.name as project_name,
+                    p.owner as project_owner,
+                    r.id as resource_id,
+                    r.name as resource_name,
+                    r.kind,
+                    r.spec,
+                    r.status,
+                    r.created_at
+                FROM
+                    resource r
+                INNER JOIN
+                    project p ON p.id = r.project_id
+                WHERE
+                    (
+                        $1 IS NULL
+                        OR r.rowid IN (SELECT rowid FROM resource_fts WHERE resource_fts MATCH $1)
+                        OR p.rowid IN (SELECT rowid FROM project_fts WHERE project_fts MATCH $1)
+                    )
+                    AND ($2 IS NULL OR LOWER(r.kind) = LOWER($2))
+                    AND ($3 IS NULL OR CAST(json_extract(r.spec, '$.network') AS TEXT) = $3)
+                    AND ($4 IS NULL OR CAST(json_extract(r.spec, '$.throughputTier') AS TEXT) = $4)
+                    AND ($5 IS NULL OR r.status = $5)
+                    AND ($6 IS NULL OR r.created_at >= $6)
+                    AND ($7 IS NULL OR r.created_at <= $7)
+                    AND ($8 IS NULL OR p.owner = $8)
+                    AND ($9 IS NULL OR p.id IN (SELECT project_id FROM project_user WHERE user_id = $9))
+                    AND ($10 IS NULL OR p.id = $10)
+                ORDER BY
+                    r.created_at DESC
+                LIMIT $11
+                OFFSET $12;
+            "#,
+        )
+        .bind(filter.text.as_deref().and_then(fts_query))
+        .bind(&filter.kind)
+        .bind(&filter.network)
+        .bind(&filter.tier)
+        .bind(&filter.status)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(&filter.owner)
+        .bind(&filter.member)
+        .bind(&filter.project_id)
+        .bind(page_size)
+        .bind(offset)
+        .fetch_all(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(results)
+    }
+}
+
+/// Turns free text into an FTS5 query where every word must match the start of a token. The
+/// words are quoted so the FTS5 operators typed by the user are searched as plain text.
+fn fts_query(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+
+    if terms.is_empty() {
+        return None;
+    }
+
+    Some(terms.join(" "))
+}
+
+impl FromRow<'_, SqliteRow> for SearchResult {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            project_id: row.try_get("project_id")?,
+            project_namespace: row.try_get("project_namespace")?,
+            project_name: row.try_get("project_name")?,
+            project_owner: row.try_get("project_owner")?,
+            resource_id: row.try_get("resource_id")?,
+            resource_name: row.try_get("resource_name")?,
+            kind: row.try_get("kind")?,
+            spec: row.try_get("spec")?,
+            status: row.try_get("status")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::{
+        domain::{
+            project::{cache::ProjectDrivenCache, Project},
+            resource::{cache::ResourceDrivenCache, Resource},
+        },
+        driven::cache::{
+            project::SqliteProjectDrivenCache, resource::SqliteResourceDrivenCache,
+            tests::mock_project,
+        },
+    };
+
+    async fn create_resource(sqlite_cache: Arc<SqliteCache>, resource: Resource) -> Resource {
+        SqliteResourceDrivenCache::new(sqlite_cache)
+            .create(&resource)
+            .await
+            .unwrap();
+        resource
+    }
+
+    #[tokio::test]
+    async fn it_should_search_resources_by_text() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let cache = SqliteSearchDrivenCache::new(sqlite_cache.clone());
+
+        let project = mock_project(sqlite_cache.clone()).await;
+        let resource = create_resource(
+            sqlite_cache.clone(),
+            Resource {
+                project_id: project.id.clone(),
+                kind: "KupoPort".into(),
+                spec: "{\"network\":\"preprod\",\"throughputTier\":\"0\"}".into(),
+                ..Default::default()
+            },
+        )
+        .await;
+        create_resource(
+            sqlite_cache.clone(),
+            Resource {
+                project_id: project.id.clone(),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        let filter = SearchFilter {
+            text: Some("prepr".into()),
+            ..Default::default()
+        };
+        let result = cache.search(&filter, &1, &12).await.unwrap();
+        assert!(result.len() == 1);
+        assert!(result[0].resource_id == resource.id);
+
+        // The project name matches every resource of the project.
+        let filter = SearchFilter {
+            text: Some("new proj".into()),
+            ..Default::default()
+        };
+        let result = cache.search(&filter, &1, &12).await.unwrap();
+        assert!(result.len() == 2);
+
+        // FTS5 syntax is searched as text instead of failing the query.
+        let filter = SearchFilter {
+            text: Some("\"mainnet OR (".into()),
+            ..Default::default()
+        };
+        let result = cache.search(&filter, &1, &12).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_search_resources_by_structured_filters() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let cache = SqliteSearchDrivenCache::new(sqlite_cache.clone());
+
+        let project = mock_project(sqlite_cache.clone()).await;
+        let resource = create_resource(
+            sqlite_cache.clone(),
+            Resource {
+                project_id: project.id.clone(),
+                ..Default::default()
+            },
+        )
+        .await;
+        create_resource(
+            sqlite_cache.clone(),
+            Resource {
+                project_id: project.id.clone(),
+                spec: "{\"network\":\"preview\",\"throughputTier\":\"2\"}".into(),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        let filter = SearchFilter {
+            kind: Some("cardanonodeport".into()),
+            network: Some("mainnet".into()),
+            tier: Some("1".into()),
+            status: Some("active".into()),
+            to: Some(Utc::now()),
+            owner: Some(project.owner.clone()),
+            ..Default::default()
+        };
+        let result = cache.search(&filter, &1, &12).await.unwrap();
+        assert!(result.len() == 1);
+        assert!(result[0].resource_id == resource.id);
+        assert!(result[0].project_namespace == project.namespace);
+
+        let filter = SearchFilter {
+            status: Some("deleted".into()),
+            ..Default::default()
+        };
+        let result = cache.search(&filter, &1, &12).await.unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[tokio::test]
+    async fn it_should_search_only_member_projects() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let cache = SqliteSearchDrivenCache::new(sqlite_cache.clone());
+
+        let project = mock_project(sqlite_cache.clone()).await;
+        create_resource(
+            sqlite_cache.clone(),
+            Resource {
+                project_id: project.id.clone(),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        let other = Project {
+            namespace: "other-namespace".into(),
+            owner: "other user id".into(),
+            ..Default::default()
+        };
+        SqliteProjectDrivenCache::new(sqlite_cache.clone())
+            .create(&other)
+            .await
+            .unwrap();
+        create_resource(
+            sqlite_cache.clone(),
+            Resource {
+                project_id: other.id.clone(),
+                ..Default::default()
+            },
+        )
+        .await;
+
+        let filter = SearchFilter {
+            member: Some(project.owner.clone()),
+            ..Default::default()
+        };
+        let result = cache.search(&filter, &1, &12).await.unwrap();
+        assert!(result.len() == 1);
+        assert!(result[0].project_id == project.id);
+
+        let filter = SearchFilter {
+            project_id: Some(other.id.clone()),
+            ..Default::default()
+        };
+        let result = cache.search(&filter, &1, &12).await.unwrap();
+        assert!(result.len() == 1);
+        assert!(result[0].project_id == other.id);
+    }
+
+    #[test]
+    fn it_should_quote_fts_query_terms() {
+        assert!(fts_query("  ").is_none());
+        assert!(fts_query("kupo main\"net") == Some("\"kupo\"* \"main\"\"net\"*".into()));
+    }
+}
@@ -0,0 +1,658 @@
+use chrono::{Duration, Utc};
+use sqlx::{sqlite::SqliteRow, Connection, FromRow, Row};
+use std::sync::Arc;
+
+use crate::domain::{
+    snapshot::{
+        cache::SnapshotDrivenCache, ProjectSnapshot, SnapshotInvite, SnapshotOrganization,
+        SnapshotProject, SnapshotQuota, SnapshotResource, SnapshotSecret, SnapshotUser,
+    },
+    Result, RESTORE_GRACE_DAYS,
+};
+
+use super::{SqliteCache, DEFAULT_QUOTA_ID};
+
+pub struct SqliteSnapshotDrivenCache {
+    sqlite: Arc<SqliteCache>,
+}
+impl SqliteSnapshotDrivenCache {
+    pub fn new(sqlite: Arc<SqliteCache>) -> Self {
+        Self { sqlite }
+    }
+
+    async fn find_organization(&self, id: &str) -> Result<Option<SnapshotOrganization>> {
+        let Some(mut organization) = sqlx::query_as::<_, SnapshotOrganization>(
+            r#"
+                SELECT id, name, owner, billing_provider, billing_provider_id, created_at, updated_at
+                FROM organization
+                WHERE id = $1;
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&mut *self.sqlite.conn().await?)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        organization.users = sqlx::query_as::<_, SnapshotUser>(
+            r#"
+                SELECT user_id, role, created_at
+                FROM organization_user
+                WHERE organization_id = $1
+                ORDER BY created_at;
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(Some(organization))
+    }
+}
+#[async_trait::async_trait]
+impl SnapshotDrivenCache for SqliteSnapshotDrivenCache {
+    async fn find_project_ids(&self) -> Result<Vec<String>> {
+        let rows = sqlx::query(
+            r#"
+                SELECT id
+                FROM project
+                ORDER BY created_at;
+            "#,
+        )
+        .fetch_all(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| row.try_get("id"))
+            .collect::<sqlx::Result<_>>()?)
+    }
+
+    async fn find(&self, project_id: &str) -> Result<Option<ProjectSnapshot>> {
+        let Some(project) = sqlx::query_as::<_, SnapshotProject>(
+            r#"
+                SELECT
+                    id,
+                    name,
+                    namespace,
+                    owner,
+                    status,
+                    billing_provider,
+                    billing_provider_id,
+                    billing_subscription_id,
+                    cluster_id,
+                    organization_id,
+                    created_at,
+                    updated_at
+                FROM project
+                WHERE id = $1;
+            "#,
+        )
+        .bind(project_id)
+        .fetch_optional(&mut *self.sqlite.conn().await?)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let users = sqlx::query_as::<_, SnapshotUser>(
+            r#"
+                SELECT user_id, role, created_at
+                FROM project_user
+                WHERE project_id = $1
+                ORDER BY created_at;
+            "#,
+        )
+        .bind(project_id)
+        .fetch_all(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        let invites = sqlx::query_as::<_, SnapshotInvite>(
+            r#"
+                SELECT id, email, role, code, status, expires_in, created_at, updated_at
+                FROM project_user_invite
+                WHERE project_id = $1 AND status = 'sent'
+                ORDER BY created_at;
+            "#,
+        )
+        .bind(project_id)
+        .fetch_all(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        let secrets = sqlx::query_as::<_, SnapshotSecret>(
+            r#"
+                SELECT id, name, phc, secret, created_at
+                FROM project_secret
+                WHERE project_id = $1
+                ORDER BY created_at;
+            "#,
+        )
+        .bind(project_id)
+        .fetch_all(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        // The resources deleted within the grace window are kept, they can still be restored.
+        let resources = sqlx::query_as::<_, SnapshotResource>(
+            r#"
+                SELECT id, name, kind, category, spec, status, cluster_id, created_at, updated_at
+                FROM resource
+                WHERE project_id = $1 AND (status = 'active' OR updated_at >= $2)
+                ORDER BY created_at;
+            "#,
+        )
+        .bind(project_id)
+        .bind(Utc::now() - Duration::days(RESTORE_GRACE_DAYS))
+        .fetch_all(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        let organization = match &project.organization_id {
+            Some(organization_id) => self.find_organization(organization_id).await?,
+            None => None,
+        };
+
+        let quotas = sqlx::query_as::<_, SnapshotQuota>(
+            r#"
+                SELECT project_id, resources, max_tier, workers, updated_at
+                FROM project_quota
+                WHERE project_id IN ($1, $2);
+            "#,
+        )
+        .bind(project_id)
+        .bind(DEFAULT_QUOTA_ID)
+        .fetch_all(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(Some(ProjectSnapshot {
+            project,
+            users,
+            invites,
+            secrets,
+            resources,
+            organization,
+            quotas,
+            positions: Vec::new(),
+            taken_at: Utc::now(),
+        }))
+    }
+
+    async fn restore(&self, snapshot: &ProjectSnapshot) -> Result<()> {
+        let mut conn = self.sqlite.conn().await?;
+        let mut tx = conn.begin().await?;
+
+        // Shared by the other projects of the organization, it may already be restored.
+        if let Some(organization) = &snapshot.organization {
+            sqlx::query(
+                r#"
+                    INSERT INTO organization (
+                        id,
+                        name,
+                        owner,
+                        billing_provider,
+                        billing_provider_id,
+                        created_at,
+                        updated_at
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7)
+                    ON CONFLICT (id) DO NOTHING;
+                "#,
+            )
+            .bind(&organization.id)
+            .bind(&organization.name)
+            .bind(&organization.owner)
+            .bind(&organization.billing_provider)
+            .bind(&organization.billing_provider_id)
+            .bind(organization.created_at)
+            .bind(organization.updated_at)
+            .execute(&mut *tx)
+            .await?;
+
+            for user in organization.users.iter() {
+                sqlx::query(
+                    r#"
+                        INSERT INTO organization_user (user_id, organization_id, role, created_at)
+                        VALUES ($1, $2, $3, $4)
+                        ON CONFLICT (user_id, organization_id) DO NOTHING;
+                    "#,
+                )
+                .bind(&user.user_id)
+                .bind(&organization.id)
+                .bind(&user.role)
+                .bind(user.created_at)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+
+        let project = &snapshot.project;
+        sqlx::query(
+            r#"
+                INSERT INTO project (
+                    id,
+                    name,
+                    namespace,
+                    owner,
+                    status,
+                    billing_provider,
+                    billing_provider_id,
+                    billing_subscription_id,
+                    cluster_id,
+                    organization_id,
+                    created_at,
+                    updated_at
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12);
+            "#,
+        )
+        .bind(&project.id)
+        .bind(&project.name)
+        .bind(&project.namespace)
+        .bind(&project.owner)
+        .bind(&project.status)
+        .bind(&project.billing_provider)
+        .bind(&project.billing_provider_id)
+        .bind(&project.billing_subscription_id)
+        .bind(&project.cluster_id)
+        .bind(&project.organization_id)
+        .bind(project.created_at)
+        .bind(project.updated_at)
+        .execute(&mut *tx)
+        .await?;
+
+        for user in snapshot.users.iter() {
+            sqlx::query(
+                r#"
+                    INSERT INTO project_user (user_id, project_id, role, created_at)
+                    VALUES ($1, $2, $3, $4);
+                "#,
+            )
+            .bind(&user.user_id)
+            .bind(&project.id)
+            .bind(&user.role)
+            .bind(user.created_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for invite in snapshot.invites.iter() {
+            sqlx::query(
+                r#"
+                    INSERT INTO project_user_invite (
+                        id,
+                        project_id,
+                        email,
+                        role,
+                        code,
+                        status,
+                        expires_in,
+                        created_at,
+                        updated_at
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9);
+                "#,
+            )
+            .bind(&invite.id)
+            .bind(&project.id)
+            .bind(&invite.email)
+            .bind(&invite.role)
+            .bind(&invite.code)
+            .bind(&invite.status)
+            .bind(invite.expires_in)
+            .bind(invite.created_at)
+            .bind(invite.updated_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for secret in snapshot.secrets.iter() {
+            sqlx::query(
+                r#"
+                    INSERT INTO project_secret (id, project_id, name, phc, secret, created_at)
+                    VALUES ($1, $2, $3, $4, $5, $6);
+                "#,
+            )
+            .bind(&secret.id)
+            .bind(&project.id)
+            .bind(&secret.name)
+            .bind(&secret.phc)
+            .bind(&secret.secret)
+            .bind(secret.created_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for resource in snapshot.resources.iter() {
+            sqlx::query(
+                r#"
+                    INSERT INTO resource (
+                        id,
+                        project_id,
+                        name,
+                        kind,
+                        category,
+                        spec,
+                        status,
+                        cluster_id,
+                        created_at,
+                        updated_at
+                    )
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10);
+                "#,
+            )
+            .bind(&resource.id)
+            .bind(&project.id)
+            .bind(&resource.name)
+            .bind(&resource.kind)
+            .bind(&resource.category)
+            .bind(&resource.spec)
+            .bind(&resource.status)
+            .bind(&resource.cluster_id)
+            .bind(resource.created_at)
+            .bind(resource.updated_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        // The default quota is in every snapshot, the newest one wins.
+        for quota in snapshot.quotas.iter() {
+            sqlx::query(
+                r#"
+                    INSERT INTO project_quota (project_id, resources, max_tier, workers, updated_at)
+                    VALUES ($1, $2, $3, $4, $5)
+                    ON CONFLICT (project_id) DO UPDATE SET
+                        resources = excluded.resources,
+                        max_tier = excluded.max_tier,
+                        workers = excluded.workers,
+                        updated_at = excluded.updated_at
+                    WHERE excluded.updated_at > project_quota.updated_at;
+                "#,
+            )
+            .bind(&quota.project_id)
+            .bind(&quota.resources)
+            .bind(quota.max_tier)
+            .bind(quota.workers)
+            .bind(quota.updated_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+}
+
+impl FromRow<'_, SqliteRow> for SnapshotProject {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            namespace: row.try_get("namespace")?,
+            owner: row.try_get("owner")?,
+            status: row.try_get("status")?,
+            billing_provider: row.try_get("billing_provider")?,
+            billing_provider_id: row.try_get("billing_provider_id")?,
+            billing_subscription_id: row.try_get("billing_subscription_id")?,
+            cluster_id: row.try_get("cluster_id")?,
+            organization_id: row.try_get("organization_id")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, SqliteRow> for SnapshotOrganization {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            owner: row.try_get("owner")?,
+            billing_provider: row.try_get("billing_provider")?,
+            billing_provider_id: row.try_get("billing_provider_id")?,
+            users: Vec::new(),
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, SqliteRow> for SnapshotUser {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            user_id: row.try_get("user_id")?,
+            role: row.try_get("role")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, SqliteRow> for SnapshotInvite {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            email: row.try_get("email")?,
+            role: row.try_get("role")?,
+            code: row.try_get("code")?,
+            status: row.try_get("status")?,
+            expires_in: row.try_get("expires_in")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, SqliteRow> for SnapshotSecret {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            phc: row.try_get("phc")?,
+            secret: row.try_get("secret")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, SqliteRow> for SnapshotResource {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            kind: row.try_get("kind")?,
+            category: row.try_get("category")?,
+            spec: row.try_get("spec")?,
+            status: row.try_get("status")?,
+            cluster_id: row.try_get("cluster_id")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+impl FromRow<'_, SqliteRow> for SnapshotQuota {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            project_id: row.try_get("project_id")?,
+            resources: row.try_get("resources")?,
+            max_tier: row.try_get("max_tier")?,
+            workers: row.try_get("workers")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{
+            organization::{cache::OrganizationDrivenCache, Organization},
+            project::{
+                cache::ProjectDrivenCache, quota::ProjectQuota, ProjectOrganizationChange,
+                ProjectSecret,
+            },
+            resource::{cache::ResourceDrivenCache, ResourceStatus},
+        },
+        driven::cache::{
+            organization::SqliteOrganizationDrivenCache,
+            project::SqliteProjectDrivenCache,
+            resource::SqliteResourceDrivenCache,
+            tests::{mock_project, mock_resource},
+        },
+    };
+
+    #[tokio::test]
+    async fn it_should_restore_the_snapshot_of_a_project() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let cache = SqliteSnapshotDrivenCache::new(sqlite_cache.clone());
+
+        let project = mock_project(sqlite_cache.clone()).await;
+        let resource = mock_resource(sqlite_cache.clone(), &project.id).await;
+        let deleted = mock_resource(sqlite_cache.clone(), &project.id).await;
+        SqliteResourceDrivenCache::new(sqlite_cache.clone())
+            .delete(
+                &deleted.id,
+                &(Utc::now() - Duration::days(RESTORE_GRACE_DAYS + 1)),
+            )
+            .await
+            .unwrap();
+        let restorable = mock_resource(sqlite_cache.clone(), &project.id).await;
+        SqliteResourceDrivenCache::new(sqlite_cache.clone())
+            .delete(&restorable.id, &Utc::now())
+            .await
+            .unwrap();
+        let secret = ProjectSecret {
+            project_id: project.id.clone(),
+            ..Default::default()
+        };
+        SqliteProjectDrivenCache::new(sqlite_cache.clone())
+            .create_secret(&secret)
+            .await
+            .unwrap();
+
+        let snapshot = cache.find(&project.id).await.unwrap().unwrap();
+        assert!(snapshot.resources.len() == 2);
+        assert!(snapshot.resources.iter().all(|r| r.id != deleted.id));
+        assert!(snapshot.secrets.len() == 1);
+
+        let restored_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let restored = SqliteSnapshotDrivenCache::new(restored_cache.clone());
+        restored.restore(&snapshot).await.unwrap();
+        assert!(restored.find_project_ids().await.unwrap() == vec![project.id.clone()]);
+
+        let project_cache = SqliteProjectDrivenCache::new(restored_cache.clone());
+        let secrets = project_cache.find_secrets(&project.id).await.unwrap();
+        assert!(secrets.len() == 1);
+        assert!(secrets[0].phc == secret.phc);
+        assert!(secrets[0].secret == secret.secret);
+
+        let resource_cache = SqliteResourceDrivenCache::new(restored_cache);
+        let restored_resource = resource_cache.find_by_id(&resource.id).await.unwrap();
+        assert!(matches!(
+            restored_resource.map(|r| r.status),
+            Some(ResourceStatus::Active)
+        ));
+        assert!(resource_cache
+            .find_by_id(&deleted.id)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(resource_cache
+            .find_deleted_by_id(&restorable.id)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn it_should_restore_the_organization_of_a_project() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let cache = SqliteSnapshotDrivenCache::new(sqlite_cache.clone());
+
+        let organization = Organization::default();
+        SqliteOrganizationDrivenCache::new(sqlite_cache.clone())
+            .create(&organization)
+            .await
+            .unwrap();
+        let project = mock_project(sqlite_cache.clone()).await;
+        SqliteProjectDrivenCache::new(sqlite_cache.clone())
+            .change_organization(&ProjectOrganizationChange {
+                project_id: project.id.clone(),
+                organization_id: Some(organization.id.clone()),
+                billing_provider_id: organization.billing_provider_id.clone(),
+                changed_at: Utc::now(),
+            })
+            .await
+            .unwrap();
+
+        let snapshot = cache.find(&project.id).await.unwrap().unwrap();
+        assert!(snapshot.organization.as_ref().unwrap().users.len() == 1);
+
+        let restored_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let restored = SqliteSnapshotDrivenCache::new(restored_cache.clone());
+        restored.restore(&snapshot).await.unwrap();
+
+        let restored_project = SqliteProjectDrivenCache::new(restored_cache.clone())
+            .find_by_id(&project.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(restored_project.organization_id == Some(organization.id.clone()));
+        let permission = SqliteOrganizationDrivenCache::new(restored_cache)
+            .find_user_permission(&organization.owner, &organization.id)
+            .await
+            .unwrap();
+        assert!(permission.is_some());
+    }
+
+    #[tokio::test]
+    async fn it_should_restore_the_quotas_of_a_project() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let cache = SqliteSnapshotDrivenCache::new(sqlite_cache.clone());
+
+        let project = mock_project(sqlite_cache.clone()).await;
+        let project_cache = SqliteProjectDrivenCache::new(sqlite_cache.clone());
+        project_cache
+            .update_quota(&ProjectQuota {
+                max_tier: Some(1),
+                updated_at: Utc::now(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        project_cache
+            .update_quota(&ProjectQuota {
+                project_id: Some(project.id.clone()),
+                workers: Some(2),
+                updated_at: Utc::now(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let snapshot = cache.find(&project.id).await.unwrap().unwrap();
+        assert!(snapshot.quotas.len() == 2);
+
+        let restored_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let restored = SqliteSnapshotDrivenCache::new(restored_cache.clone());
+        restored.restore(&snapshot).await.unwrap();
+
+        let project_cache = SqliteProjectDrivenCache::new(restored_cache);
+        let default = project_cache.find_default_quota().await.unwrap().unwrap();
+        assert!(default.max_tier == Some(1));
+        let quota = project_cache
+            .find_quota(&project.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(quota.workers == Some(2));
+    }
+
+    #[tokio::test]
+    async fn it_should_not_find_a_missing_project() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let cache = SqliteSnapshotDrivenCache::new(sqlite_cache);
+
+        assert!(cache.find("missing").await.unwrap().is_none());
+    }
+}
@@ -1,10 +1,11 @@
-use sqlx::{sqlite::SqliteRow, FromRow, Row};
+use chrono::{DateTime, Utc};
+use sqlx::{sqlite::SqliteRow, Connection, FromRow, Row};
 use std::sync::Arc;
 
 use crate::domain::{
     resource::ResourceStatus,
     usage::{
-        cache::{UsageDrivenCache, UsageDrivenCacheBackoffice},
+        cache::{UsageDrivenCache, UsageDrivenCacheBackoffice, UsageDrivenCacheRollup},
         Usage, UsageReport, UsageResource,
     },
     Result,
@@ -80,7 +81,7 @@ impl UsageDrivenCache for SqliteUsageDrivenCache {
             query = query.bind(cluster_id);
         }
 
-        let report = query.fetch_all(&self.sqlite.db).await?;
+        let report = query.fetch_all(&mut *self.sqlite.conn().await?).await?;
 
         Ok(report)
     }
@@ -102,7 +103,7 @@ impl UsageDrivenCache for SqliteUsageDrivenCache {
             "#,
         )
         .bind(ResourceStatus::Deleted.to_string())
-        .fetch_all(&self.sqlite.db)
+        .fetch_all(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(resources)
@@ -139,7 +140,7 @@ impl UsageDrivenCache for SqliteUsageDrivenCache {
         .bind(project_id)
         .bind(page_size)
         .bind(offset)
-        .fetch_all(&self.sqlite.db)
+        .fetch_all(&mut *self.sqlite.conn().await?)
         .await?;
 
         let clusters = rows.iter().map(|r| r.get("cluster_id")).collect();
@@ -147,7 +148,8 @@ impl UsageDrivenCache for SqliteUsageDrivenCache {
     }
 
     async fn create(&self, usages: Vec<Usage>) -> Result<()> {
-        let mut tx = self.sqlite.db.begin().await?;
+        let mut conn = self.sqlite.conn().await?;
+        let mut tx = conn.begin().await?;
 
         for usage in usages {
             let interval = usage.interval as i64;
@@ -190,6 +192,8 @@ impl UsageDrivenCacheBackoffice for SqliteUsageDrivenCache {
         period: &str,
         cluster_id: &str,
     ) -> Result<Vec<UsageReport>> {
+        // The closed months are read from the monthly totals, together with the rows that
+        // arrived after their rollup.
         let report_aggregated = sqlx::query_as::<_, UsageReport>(
             r#"
                 SELECT
@@ -205,16 +209,20 @@ impl UsageDrivenCacheBackoffice for SqliteUsageDrivenCache {
                 	u.tier as tier,
                 	SUM(u.interval) as interval,
                 	SUM(u.units) as units,
-                	STRFTIME('%Y-%m', u.created_at) as period
-                FROM
-                	"usage" u
+                	u.period
+                FROM (
+                	SELECT cluster_id, resource_id, tier, units, interval, period
+                	FROM usage_monthly
+                	WHERE period = $1 AND cluster_id = $2
+                	UNION ALL
+                	SELECT cluster_id, resource_id, tier, units, interval, STRFTIME('%Y-%m', created_at) as period
+                	FROM "usage"
+                	WHERE rolled_up_at IS NULL AND STRFTIME('%Y-%m', created_at) = $1 AND cluster_id = $2
+                ) u
                 INNER JOIN resource r ON
                 	r.id == u.resource_id
                 INNER JOIN project p ON
                 	p.id == r.project_id
-                WHERE
-                	STRFTIME('%Y-%m', u.created_at) = $1
-                  AND u.cluster_id = $2
                 GROUP BY
                 	resource_id,
                 	tier
@@ -225,7 +233,7 @@ impl UsageDrivenCacheBackoffice for SqliteUsageDrivenCache {
         )
         .bind(period)
         .bind(cluster_id)
-        .fetch_all(&self.sqlite.db)
+        .fetch_all(&mut *self.sqlite.conn().await?)
         .await?;
 
         Ok(report_aggregated)
@@ -234,6 +242,13 @@ impl UsageDrivenCacheBackoffice for SqliteUsageDrivenCache {
     async fn find_clusters(&self, period: &str) -> Result<Vec<String>> {
         let rows = sqlx::query(
             r#"
+                SELECT
+                	m.cluster_id
+                FROM
+                	usage_monthly m
+                WHERE
+                	m.period = $1
+                UNION
                 SELECT
                 	u.cluster_id
                 FROM
@@ -241,13 +256,12 @@ impl UsageDrivenCacheBackoffice for SqliteUsageDrivenCache {
                 INNER JOIN resource r ON
 	                r.id == u.resource_id
                 WHERE
-                	STRFTIME('%Y-%m', u.created_at) = $1
-                GROUP BY
-                	u.cluster_id;
+                	u.rolled_up_at IS NULL
+                  AND STRFTIME('%Y-%m', u.created_at) = $1;
             "#,
         )
         .bind(period)
-        .fetch_all(&self.sqlite.db)
+        .fetch_all(&mut *self.sqlite.conn().await?)
         .await?;
 
         let clusters = rows.iter().map(|r| r.get("cluster_id")).collect();
@@ -255,6 +269,89 @@ impl UsageDrivenCacheBackoffice for SqliteUsageDrivenCache {
     }
 }
 
+#[async_trait::async_trait]
+impl UsageDrivenCacheRollup for SqliteUsageDrivenCache {
+    async fn rollup(&self, period: &str, rolled_up_at: &DateTime<Utc>) -> Result<u64> {
+        let mut conn = self.sqlite.conn().await?;
+        let mut tx = conn.begin().await?;
+
+        // Both statements select the same rows, the transaction keeps the consumer from adding
+        // rows between them.
+        sqlx::query(
+            r#"
+                INSERT INTO usage_monthly (
+                    period,
+                    cluster_id,
+                    project_id,
+                    resource_id,
+                    tier,
+                    units,
+                    interval,
+                    updated_at
+                )
+                SELECT
+                    STRFTIME('%Y-%m', u.created_at) as period,
+                    u.cluster_id,
+                    r.project_id,
+                    u.resource_id,
+                    u.tier,
+                    SUM(u.units),
+                    SUM(u.interval),
+                    $2
+                FROM "usage" u
+                INNER JOIN resource r ON r.id == u.resource_id
+                WHERE
+                    u.rolled_up_at IS NULL
+                    AND u.cluster_id IS NOT NULL
+                    AND STRFTIME('%Y-%m', u.created_at) < $1
+                GROUP BY period, u.cluster_id, u.resource_id, u.tier
+                ON CONFLICT (period, cluster_id, resource_id, tier) DO UPDATE SET
+                    units = usage_monthly.units + excluded.units,
+                    interval = usage_monthly.interval + excluded.interval,
+                    updated_at = excluded.updated_at;
+            "#,
+        )
+        .bind(period)
+        .bind(rolled_up_at)
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query(
+            r#"
+                UPDATE "usage"
+                SET rolled_up_at = $2
+                WHERE
+                    rolled_up_at IS NULL
+                    AND cluster_id IS NOT NULL
+                    AND STRFTIME('%Y-%m', created_at) < $1
+                    AND EXISTS (SELECT 1 FROM resource r WHERE r.id == "usage".resource_id);
+            "#,
+        )
+        .bind(period)
+        .bind(rolled_up_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn purge(&self, before: &DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+                DELETE FROM "usage"
+                WHERE rolled_up_at IS NOT NULL AND created_at < $1;
+            "#,
+        )
+        .bind(before)
+        .execute(&mut *self.sqlite.conn().await?)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
 impl FromRow<'_, SqliteRow> for Usage {
     fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
         let interval: i64 = row.try_get("interval")?;
@@ -459,4 +556,76 @@ mod tests {
         assert!(result.is_ok());
         assert!(result.unwrap().len() == 2);
     }
+
+    #[tokio::test]
+    async fn it_should_rollup_closed_months() {
+        let sqlite_cache = Arc::new(SqliteCache::ephemeral().await.unwrap());
+        let cache = SqliteUsageDrivenCache::new(sqlite_cache.clone());
+
+        let project = mock_project(sqlite_cache.clone()).await;
+        let resource = mock_resource(sqlite_cache.clone(), &project.id).await;
+
+        let last_month = Utc::now() - chrono::Duration::days(40);
+        let period = last_month.format("%Y-%m").to_string();
+        let usages = vec![
+            Usage {
+                resource_id: resource.id.clone(),
+                created_at: last_month,
+                ..Default::default()
+            },
+            Usage {
+                resource_id: resource.id.clone(),
+                created_at: last_month,
+                ..Default::default()
+            },
+            Usage {
+                resource_id: resource.id.clone(),
+                ..Default::default()
+            },
+        ];
+        cache.create(usages).await.unwrap();
+        let before = cache
+            .find_report_aggregated(&period, "demeter")
+            .await
+            .unwrap();
+
+        let current_period = Utc::now().format("%Y-%m").to_string();
+        let rolled_up = cache.rollup(&current_period, &Utc::now()).await.unwrap();
+        assert!(rolled_up == 2);
+
+        // A late row of the rolled up month is added to the totals by the next rollup.
+        let late = Usage {
+            resource_id: resource.id.clone(),
+            created_at: last_month,
+            ..Default::default()
+        };
+        cache.create(vec![late]).await.unwrap();
+        let purged = cache.purge(&Utc::now()).await.unwrap();
+        assert!(purged == 2);
+
+        let after = cache
+            .find_report_aggregated(&period, "demeter")
+            .await
+            .unwrap();
+        assert!(after.len() == 1);
+        assert!(after[0].units == before[0].units + 120);
+        assert!(after[0].interval == before[0].interval + 10);
+
+        let rolled_up = cache.rollup(&current_period, &Utc::now()).await.unwrap();
+        assert!(rolled_up == 1);
+        let after = cache
+            .find_report_aggregated(&period, "demeter")
+            .await
+            .unwrap();
+        assert!(after[0].units == before[0].units + 120);
+
+        let clusters = UsageDrivenCacheBackoffice::find_clusters(&cache, &period)
+            .await
+            .unwrap();
+        assert!(clusters == vec!["demeter".to_string()]);
+
+        let report = cache.find_report(&project.id, &1, &12, None).await.unwrap();
+        assert!(report.len() == 1);
+        assert!(report[0].units == 120);
+    }
 }
@@ -2,12 +2,14 @@ use anyhow::Result as AnyhowResult;
 use k8s_openapi::api::core::v1::Namespace;
 use kube::{
     api::{ApiResource, DeleteParams, DynamicObject, Patch, PatchParams, PostParams},
-    discovery, Api, Client, Error, ResourceExt,
+    discovery::{self, Scope},
+    Api, Client, Error, ResourceExt,
 };
+use serde_json::json;
 use tracing::{info, warn};
 
 use crate::domain::{
-    project::cluster::ProjectDrivenCluster,
+    project::cluster::{ProjectDrivenCluster, SUSPENDED_ANNOTATION},
     resource::cluster::{ResourceDrivenCluster, ResourceDrivenClusterBackoffice},
     Result,
 };
@@ -21,6 +23,36 @@ impl K8sCluster {
 
         Ok(Self { client })
     }
+
+    /// Merges the annotation into every demeter resource of the namespace, a null value removes
+    /// it.
+    async fn annotate_namespace(
+        &self,
+        namespace: &str,
+        annotation: &str,
+        value: Option<&str>,
+    ) -> Result<()> {
+        let apigroup = discovery::group(&self.client, "demeter.run").await?;
+        let patch = json!({ "metadata": { "annotations": { annotation: value } } });
+
+        for (ar, caps) in apigroup.recommended_resources() {
+            if !matches!(caps.scope, Scope::Namespaced) {
+                continue;
+            }
+
+            let api: Api<DynamicObject> = Api::namespaced_with(self.client.clone(), namespace, &ar);
+            for obj in api.list(&Default::default()).await? {
+                api.patch(
+                    &obj.name_any(),
+                    &PatchParams::default(),
+                    &Patch::Merge(&patch),
+                )
+                .await?;
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
@@ -63,6 +95,16 @@ impl ProjectDrivenCluster for K8sCluster {
 
         Ok(())
     }
+
+    async fn suspend(&self, namespace: &str) -> Result<()> {
+        self.annotate_namespace(namespace, SUSPENDED_ANNOTATION, Some("true"))
+            .await
+    }
+
+    async fn resume(&self, namespace: &str) -> Result<()> {
+        self.annotate_namespace(namespace, SUSPENDED_ANNOTATION, None)
+            .await
+    }
 }
 
 #[async_trait::async_trait]
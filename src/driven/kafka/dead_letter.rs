@@ -0,0 +1,221 @@
+use anyhow::Result as AnyhowResult;
+use chrono::{DateTime, Utc};
+use rdkafka::{
+    consumer::{BaseConsumer, Consumer},
+    message::{BorrowedMessage, Header, OwnedHeaders},
+    producer::{FutureProducer, FutureRecord},
+    ClientConfig, Message, Offset, TopicPartitionList,
+};
+use std::{collections::HashMap, time::Duration};
+
+use crate::{
+    domain::{error::Error, Result},
+    driven::bus::{BusMessage, Encoding},
+    drivers::retry::RetryExhausted,
+};
+
+use super::{find_header, ENCODING_HEADER, EVENT_ID_HEADER};
+
+pub const DLQ_SOURCE_TOPIC_HEADER: &str = "dlq-source-topic";
+pub const DLQ_SOURCE_PARTITION_HEADER: &str = "dlq-source-partition";
+pub const DLQ_SOURCE_OFFSET_HEADER: &str = "dlq-source-offset";
+pub const DLQ_CONSUMER_HEADER: &str = "dlq-consumer";
+pub const DLQ_ERROR_KIND_HEADER: &str = "dlq-error-kind";
+pub const DLQ_ERROR_HEADER: &str = "dlq-error";
+pub const DLQ_ATTEMPTS_HEADER: &str = "dlq-attempts";
+pub const DLQ_FAILED_AT_HEADER: &str = "dlq-failed-at";
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone)]
+pub struct DeadLetterConfig {
+    pub topic: String,
+    pub kafka: HashMap<String, String>,
+}
+
+/// A message that a consumer could not apply, as read back from the dead-letter topic.
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    pub partition: i32,
+    pub offset: i64,
+    pub key: String,
+    pub payload: Vec<u8>,
+    pub event_id: Option<String>,
+    pub encoding: Option<Encoding>,
+    pub source_topic: String,
+    pub consumer: String,
+    pub error_kind: String,
+    pub error: String,
+    pub attempts: u32,
+    pub failed_at: Option<DateTime<Utc>>,
+}
+
+pub struct DeadLetterProducer {
+    producer: FutureProducer,
+    topic: String,
+    consumer: String,
+}
+impl DeadLetterProducer {
+    pub fn new(consumer: &str, config: &DeadLetterConfig) -> AnyhowResult<Self> {
+        let producer: FutureProducer = {
+            let mut client_config = ClientConfig::new();
+            for (k, v) in config.kafka.iter() {
+                client_config.set(k, v);
+            }
+            client_config.create()?
+        };
+
+        Ok(Self {
+            producer,
+            topic: config.topic.clone(),
+            consumer: consumer.to_string(),
+        })
+    }
+
+    /// Forwards the original key and payload, adding the failure reason to the headers.
+    pub async fn send(&self, message: &BusMessage, failure: &RetryExhausted) -> Result<()> {
+        let mut headers = source_headers(&message.event_id, &message.encoding);
+
+        let partition = message.partition.to_string();
+        let offset = message.offset.to_string();
+        let error_kind = failure.error.kind().to_string();
+        let error = failure.error.to_string();
+        let attempts = failure.attempts.to_string();
+        let failed_at = Utc::now().to_rfc3339();
+        for (key, value) in [
+            (DLQ_SOURCE_TOPIC_HEADER, &message.topic),
+            (DLQ_SOURCE_PARTITION_HEADER, &partition),
+            (DLQ_SOURCE_OFFSET_HEADER, &offset),
+            (DLQ_CONSUMER_HEADER, &self.consumer),
+            (DLQ_ERROR_KIND_HEADER, &error_kind),
+            (DLQ_ERROR_HEADER, &error),
+            (DLQ_ATTEMPTS_HEADER, &attempts),
+            (DLQ_FAILED_AT_HEADER, &failed_at),
+        ] {
+            headers = headers.insert(Header {
+                key,
+                value: Some(value),
+            });
+        }
+
+        let mut record = FutureRecord::to(&self.topic).headers(headers);
+        if let Some(key) = &message.key {
+            record = record.key(key);
+        }
+        if let Some(payload) = &message.payload {
+            record = record.payload(payload);
+        }
+
+        self.producer
+            .send(record, Duration::from_secs(0))
+            .await
+            .map_err(|err| Error::Unexpected(err.0.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Publishes the dead-lettered message again on the topic it was consumed from.
+    pub async fn redrive(&self, dead_letter: &DeadLetter) -> Result<()> {
+        let record = FutureRecord::to(&dead_letter.source_topic)
+            .key(&dead_letter.key)
+            .payload(&dead_letter.payload)
+            .headers(source_headers(&dead_letter.event_id, &dead_letter.encoding));
+
+        self.producer
+            .send(record, Duration::from_secs(0))
+            .await
+            .map_err(|err| Error::Unexpected(err.0.to_string()))?;
+
+        Ok(())
+    }
+}
+
+/// Headers set by the producer of the original message.
+fn source_headers(event_id: &Option<String>, encoding: &Option<Encoding>) -> OwnedHeaders {
+    let mut headers = OwnedHeaders::new();
+    if let Some(event_id) = event_id {
+        headers = headers.insert(Header {
+            key: EVENT_ID_HEADER,
+            value: Some(event_id),
+        });
+    }
+    if let Some(encoding) = encoding {
+        headers = headers.insert(Header {
+            key: ENCODING_HEADER,
+            value: Some(&encoding.to_string()),
+        });
+    }
+    headers
+}
+
+/// Reads the whole dead-letter topic, from the beginning up to the current end of each partition.
+pub fn fetch_dead_letters(
+    topic: &str,
+    properties: &HashMap<String, String>,
+) -> AnyhowResult<Vec<DeadLetter>> {
+    let mut client_config = ClientConfig::new();
+    for (k, v) in properties.iter() {
+        client_config.set(k, v);
+    }
+    client_config.set("enable.auto.commit", "false");
+    let consumer: BaseConsumer = client_config.create()?;
+
+    let metadata = consumer.fetch_metadata(Some(topic), TIMEOUT)?;
+    let mut assignment = TopicPartitionList::new();
+    let mut ends = HashMap::new();
+    for partition in metadata.topics().iter().flat_map(|t| t.partitions()) {
+        let (low, high) = consumer.fetch_watermarks(topic, partition.id(), TIMEOUT)?;
+        if high > low {
+            assignment.add_partition_offset(topic, partition.id(), Offset::Beginning)?;
+            ends.insert(partition.id(), high);
+        }
+    }
+    if ends.is_empty() {
+        return Ok(Vec::new());
+    }
+    consumer.assign(&assignment)?;
+
+    let mut dead_letters = Vec::new();
+    while !ends.is_empty() {
+        let Some(message) = consumer.poll(TIMEOUT) else {
+            break;
+        };
+        let message = message?;
+
+        if let Some(end) = ends.get(&message.partition()) {
+            if message.offset() + 1 >= *end {
+                ends.remove(&message.partition());
+            }
+        }
+
+        dead_letters.push(DeadLetter::from(&message));
+    }
+
+    Ok(dead_letters)
+}
+
+impl From<&BorrowedMessage<'_>> for DeadLetter {
+    fn from(value: &BorrowedMessage<'_>) -> Self {
+        let header = |key: &str| find_header(value, key).unwrap_or_default();
+
+        Self {
+            partition: value.partition(),
+            offset: value.offset(),
+            key: value
+                .key()
+                .map(|key| String::from_utf8_lossy(key).to_string())
+                .unwrap_or_default(),
+            payload: value.payload().map(|p| p.to_vec()).unwrap_or_default(),
+            event_id: find_header(value, EVENT_ID_HEADER),
+            encoding: find_header(value, ENCODING_HEADER).and_then(|e| e.parse().ok()),
+            source_topic: header(DLQ_SOURCE_TOPIC_HEADER),
+            consumer: header(DLQ_CONSUMER_HEADER),
+            error_kind: header(DLQ_ERROR_KIND_HEADER),
+            error: header(DLQ_ERROR_HEADER),
+            attempts: header(DLQ_ATTEMPTS_HEADER).parse().unwrap_or_default(),
+            failed_at: DateTime::parse_from_rfc3339(&header(DLQ_FAILED_AT_HEADER))
+                .ok()
+                .map(|failed_at| failed_at.to_utc()),
+        }
+    }
+}
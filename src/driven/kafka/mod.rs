@@ -1,25 +1,39 @@
 use anyhow::Result as AnyhowResult;
 use rdkafka::{
+    consumer::{CommitMode, Consumer, StreamConsumer},
+    error::KafkaError,
+    message::{BorrowedMessage, Header, Headers, OwnedHeaders},
     producer::{FutureProducer, FutureRecord},
-    ClientConfig, Message,
+    ClientConfig, Message, Offset, TopicPartitionList,
 };
 use std::{collections::HashMap, time::Duration};
 
-use crate::domain::{
-    error::Error,
-    event::{Event, EventDrivenBridge},
-    Result,
+use crate::{
+    domain::{
+        error::Error,
+        event::{Event, EventDrivenBridge, EventEnvelope, EventOffset},
+        snapshot::{ProjectSnapshot, SnapshotDrivenBridge},
+        Result,
+    },
+    driven::bus::{BusMessage, Encoding, Ends, EventConsumer, EventProducer},
 };
 
-const NAME: &str = env!("CARGO_PKG_NAME");
-const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub mod dead_letter;
+
+pub const EVENT_ID_HEADER: &str = "event-id";
+pub const ENCODING_HEADER: &str = "encoding";
 
 pub struct KafkaProducer {
     producer: FutureProducer,
     topic: String,
+    encoding: Encoding,
 }
 impl KafkaProducer {
-    pub fn new(topic: &str, properties: &HashMap<String, String>) -> AnyhowResult<Self> {
+    pub fn new(
+        topic: &str,
+        properties: &HashMap<String, String>,
+        encoding: Encoding,
+    ) -> AnyhowResult<Self> {
         let producer: FutureProducer = {
             let mut client_config = ClientConfig::new();
             for (k, v) in properties.iter() {
@@ -31,49 +45,173 @@ impl KafkaProducer {
         Ok(Self {
             producer,
             topic: topic.to_string(),
+            encoding,
         })
     }
 }
 #[async_trait::async_trait]
-impl EventDrivenBridge for KafkaProducer {
-    async fn dispatch(&self, event: Event) -> Result<()> {
-        let annotation = HashMap::from([("source", format!("{NAME}-{VERSION}"))]);
+impl EventProducer for KafkaProducer {
+    /// Sends the envelope with its id in the `event-id` header, so consumers can detect
+    /// events delivered more than once, and the payload format in the `encoding` header.
+    async fn publish(&self, envelope: &EventEnvelope) -> Result<Option<EventOffset>> {
+        let data = self.encoding.encode(envelope)?;
+        let key = envelope.key();
+        let encoding = self.encoding.to_string();
 
-        let Some(mut payload) = serde_json::to_value(&event)?.as_object().cloned() else {
-            return Err(Error::Unexpected("invalid event structure".into()));
-        };
-        payload.insert("annotation".into(), serde_json::to_value(annotation)?);
+        let mut headers = OwnedHeaders::new().insert(Header {
+            key: ENCODING_HEADER,
+            value: Some(&encoding),
+        });
+        if let Some(event_id) = &envelope.event_id {
+            headers = headers.insert(Header {
+                key: EVENT_ID_HEADER,
+                value: Some(event_id),
+            });
+        }
 
-        let data = serde_json::to_vec(&payload)?;
-        let key = event.key();
+        let record = FutureRecord::to(&self.topic)
+            .payload(&data)
+            .key(&key)
+            .headers(headers);
+
+        let (partition, offset) = self
+            .producer
+            .send(record, Duration::from_secs(0))
+            .await
+            .map_err(|err| Error::Unexpected(err.0.to_string()))?;
+
+        Ok(Some(EventOffset {
+            topic: self.topic.clone(),
+            partition,
+            offset,
+        }))
+    }
+}
+#[async_trait::async_trait]
+impl EventDrivenBridge for KafkaProducer {
+    async fn dispatch(&self, event: Event) -> Result<Option<EventOffset>> {
+        EventProducer::publish(self, &EventEnvelope::new(event)).await
+    }
+}
+#[async_trait::async_trait]
+impl SnapshotDrivenBridge for KafkaProducer {
+    /// Keyed by the project id, so a compacted topic keeps the latest snapshot of each project.
+    async fn publish(&self, snapshot: &ProjectSnapshot) -> Result<()> {
+        let data = serde_json::to_vec(snapshot)?;
+        let record = FutureRecord::to(&self.topic)
+            .payload(&data)
+            .key(&snapshot.project.id);
 
         self.producer
-            .send(
-                FutureRecord::to(&self.topic).payload(&data).key(&key),
-                Duration::from_secs(0),
-            )
+            .send(record, Duration::from_secs(0))
             .await
             .map_err(|err| Error::Unexpected(err.0.to_string()))?;
 
         Ok(())
     }
 }
-impl TryFrom<&rdkafka::message::BorrowedMessage<'_>> for Event {
-    type Error = Error;
-
-    fn try_from(
-        value: &rdkafka::message::BorrowedMessage<'_>,
-    ) -> std::result::Result<Self, Self::Error> {
-        let Some(key) = value.key() else {
-            return Err(Error::Unexpected("event with empty key".into()));
-        };
-        let key =
-            String::from_utf8(key.to_vec()).map_err(|err| Error::Unexpected(err.to_string()))?;
 
-        let Some(payload) = value.payload() else {
-            return Err(Error::Unexpected("event with empty payload".into()));
-        };
-        let event = Event::from_key(&key, payload)?;
-        Ok(event)
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct KafkaConsumer {
+    consumer: StreamConsumer,
+}
+impl KafkaConsumer {
+    pub fn subscribe(
+        topics: &[String],
+        properties: &HashMap<String, String>,
+    ) -> AnyhowResult<Self> {
+        let consumer = create_consumer(properties)?;
+        let topics: Vec<&str> = topics.iter().map(String::as_str).collect();
+        consumer.subscribe(&topics)?;
+
+        Ok(Self { consumer })
     }
+
+    /// Assigns every partition of the topics from the beginning, offsets are never committed.
+    pub fn replay(
+        topics: &[String],
+        properties: &HashMap<String, String>,
+    ) -> AnyhowResult<(Self, Ends)> {
+        let mut properties = properties.clone();
+        properties.insert("enable.auto.commit".into(), "false".into());
+        let consumer = create_consumer(&properties)?;
+
+        let mut assignment = TopicPartitionList::new();
+        let mut ends = HashMap::new();
+        for topic in topics.iter() {
+            let metadata = consumer.fetch_metadata(Some(topic), TIMEOUT)?;
+            for partition in metadata.topics().iter().flat_map(|t| t.partitions()) {
+                let (low, high) = consumer.fetch_watermarks(topic, partition.id(), TIMEOUT)?;
+                if high > low {
+                    assignment.add_partition_offset(topic, partition.id(), Offset::Beginning)?;
+                    ends.insert((topic.clone(), partition.id()), high);
+                }
+            }
+        }
+        if !ends.is_empty() {
+            consumer.assign(&assignment)?;
+        }
+
+        Ok((Self { consumer }, ends))
+    }
+}
+#[async_trait::async_trait]
+impl EventConsumer for KafkaConsumer {
+    async fn recv(&self) -> Result<Option<BusMessage>> {
+        match self.consumer.recv().await {
+            Ok(message) => Ok(Some(BusMessage::from(&message))),
+            Err(KafkaError::PartitionEOF(_)) => Ok(None),
+            Err(error) => Err(Error::Unexpected(error.to_string())),
+        }
+    }
+
+    fn commit(&self, message: &BusMessage) -> Result<()> {
+        let mut position = TopicPartitionList::new();
+        position
+            .add_partition_offset(
+                &message.topic,
+                message.partition,
+                Offset::Offset(message.offset + 1),
+            )
+            .and_then(|_| self.consumer.commit(&position, CommitMode::Async))
+            .map_err(|err| Error::Unexpected(err.to_string()))
+    }
+
+    fn seek(&self, topic: &str, partition: i32, offset: i64) -> Result<()> {
+        self.consumer
+            .seek(topic, partition, Offset::Offset(offset), TIMEOUT)
+            .map_err(|err| Error::Unexpected(err.to_string()))
+    }
+}
+
+fn create_consumer(properties: &HashMap<String, String>) -> AnyhowResult<StreamConsumer> {
+    let mut client_config = ClientConfig::new();
+    for (k, v) in properties.iter() {
+        client_config.set(k, v);
+    }
+    Ok(client_config.create()?)
+}
+
+impl From<&BorrowedMessage<'_>> for BusMessage {
+    fn from(value: &BorrowedMessage<'_>) -> Self {
+        Self {
+            topic: value.topic().to_string(),
+            partition: value.partition(),
+            offset: value.offset(),
+            key: value.key().map(|key| key.to_vec()),
+            payload: value.payload().map(|payload| payload.to_vec()),
+            event_id: find_header(value, EVENT_ID_HEADER),
+            encoding: find_header(value, ENCODING_HEADER).and_then(|e| e.parse().ok()),
+        }
+    }
+}
+
+pub(crate) fn find_header(message: &BorrowedMessage<'_>, key: &str) -> Option<String> {
+    message
+        .headers()?
+        .iter()
+        .find(|header| header.key == key)
+        .and_then(|header| header.value)
+        .map(|value| String::from_utf8_lossy(value).to_string())
 }
@@ -1,8 +1,11 @@
 pub mod auth0;
+pub mod billing;
+pub mod bus;
 pub mod cache;
 pub mod k8s;
 pub mod kafka;
 pub mod metadata;
+pub mod outbox;
 pub mod prometheus;
 pub mod ses;
 pub mod slack;
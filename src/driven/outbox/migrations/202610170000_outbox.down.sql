@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_outbox_dispatched_at;
+
+DROP TABLE IF EXISTS outbox;
@@ -0,0 +1,3 @@
+-- Entries that can never be published, e.g. a payload that no longer decodes, are set aside
+-- so the relay moves on to the next ones.
+ALTER TABLE outbox ADD COLUMN dead_lettered_at DATETIME;
@@ -0,0 +1,350 @@
+use anyhow::Result as AnyhowResult;
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::{sqlite::SqliteRow, FromRow, Row};
+use std::path::Path;
+
+use crate::domain::{
+    error::Error,
+    event::{Event, EventDrivenBridge, EventEnvelope, EventOffset},
+    Result,
+};
+
+/// Local durable table where events are written before they reach kafka. The relay driver
+/// (`drivers::outbox::relay`) publishes the pending rows in insertion order and only marks them
+/// as dispatched when kafka acknowledges them.
+pub struct SqliteOutbox {
+    db: sqlx::sqlite::SqlitePool,
+}
+
+impl SqliteOutbox {
+    pub async fn new(path: &Path) -> AnyhowResult<Self> {
+        let url = format!("sqlite:{}?mode=rwc", path.display());
+        let db = sqlx::sqlite::SqlitePoolOptions::new().connect(&url).await?;
+
+        Ok(Self { db })
+    }
+
+    pub async fn migrate(&self) -> AnyhowResult<()> {
+        sqlx::migrate!("src/driven/outbox/migrations")
+            .run(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub async fn ephemeral() -> AnyhowResult<Self> {
+        let db = sqlx::sqlite::SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await?;
+
+        let out = Self { db };
+        out.migrate().await?;
+
+        Ok(out)
+    }
+
+    /// Stores the entry, returns false when an entry with the same event id was already stored.
+    pub async fn persist(&self, entry: &OutboxEntry) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+                INSERT INTO outbox (
+                    event_id,
+                    key,
+                    payload,
+                    attempts,
+                    created_at
+                )
+                VALUES ($1, $2, $3, 0, $4)
+                ON CONFLICT (event_id) DO NOTHING;
+            "#,
+        )
+        .bind(&entry.event_id)
+        .bind(&entry.key)
+        .bind(&entry.payload)
+        .bind(entry.created_at)
+        .execute(&self.db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn find_pending(&self, limit: u32) -> Result<Vec<OutboxEntry>> {
+        let entries = sqlx::query_as::<_, OutboxEntry>(
+            r#"
+                SELECT
+                    event_id,
+                    key,
+                    payload,
+                    attempts,
+                    last_error,
+                    created_at,
+                    dispatched_at,
+                    dead_lettered_at
+                FROM outbox
+                WHERE dispatched_at IS NULL AND dead_lettered_at IS NULL
+                ORDER BY seq
+                LIMIT $1;
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(entries)
+    }
+
+    pub async fn mark_dispatched(
+        &self,
+        event_id: &str,
+        dispatched_at: &DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE outbox
+                SET dispatched_at = $2, last_error = NULL
+                WHERE event_id = $1;
+            "#,
+        )
+        .bind(event_id)
+        .bind(dispatched_at)
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn mark_failed(&self, event_id: &str, error: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE outbox
+                SET attempts = attempts + 1, last_error = $2
+                WHERE event_id = $1;
+            "#,
+        )
+        .bind(event_id)
+        .bind(error)
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Sets the entry aside, it's no longer pending and the relay publishes the next ones.
+    pub async fn mark_dead_lettered(
+        &self,
+        event_id: &str,
+        error: &str,
+        dead_lettered_at: &DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+                UPDATE outbox
+                SET attempts = attempts + 1, last_error = $2, dead_lettered_at = $3
+                WHERE event_id = $1;
+            "#,
+        )
+        .bind(event_id)
+        .bind(error)
+        .bind(dead_lettered_at)
+        .execute(&self.db)
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// The offset is only known once the relay publishes the event, so no offset is returned and the
+/// commands read the cache without waiting, the response can still have the previous state.
+#[async_trait::async_trait]
+impl EventDrivenBridge for SqliteOutbox {
+    async fn dispatch(&self, event: Event) -> Result<Option<EventOffset>> {
+        let mut envelope = EventEnvelope::new(event);
+        envelope.event_id = Some(stable_event_id(&envelope.event)?);
+
+        let entry = OutboxEntry::try_new(&envelope)?;
+        self.persist(&entry).await?;
+        Ok(None)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OutboxEntry {
+    pub event_id: String,
+    pub key: String,
+    pub payload: Vec<u8>,
+    pub attempts: i64,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub dispatched_at: Option<DateTime<Utc>>,
+    pub dead_lettered_at: Option<DateTime<Utc>>,
+}
+/// Events carry the time they happened, so the digest of the event is only repeated when the
+/// same event is dispatched again, and the outbox stores it once.
+fn stable_event_id(event: &Event) -> Result<String> {
+    let digest = Sha256::digest(serde_json::to_vec(event)?);
+    Ok(hex::encode(digest))
+}
+
+impl OutboxEntry {
+    pub fn try_new(envelope: &EventEnvelope) -> Result<Self> {
+        let Some(event_id) = envelope.event_id.clone() else {
+            return Err(Error::Unexpected("event envelope without id".into()));
+        };
+
+        Ok(Self {
+            event_id,
+            key: envelope.key(),
+            payload: envelope.to_payload()?,
+            attempts: 0,
+            last_error: None,
+            created_at: Utc::now(),
+            dispatched_at: None,
+            dead_lettered_at: None,
+        })
+    }
+
+    pub fn envelope(&self) -> Result<EventEnvelope> {
+        EventEnvelope::from_key(&self.key, &self.payload)
+    }
+}
+impl FromRow<'_, SqliteRow> for OutboxEntry {
+    fn from_row(row: &SqliteRow) -> sqlx::Result<Self> {
+        Ok(Self {
+            event_id: row.try_get("event_id")?,
+            key: row.try_get("key")?,
+            payload: row.try_get("payload")?,
+            attempts: row.try_get("attempts")?,
+            last_error: row.try_get("last_error")?,
+            created_at: row.try_get("created_at")?,
+            dispatched_at: row.try_get("dispatched_at")?,
+            dead_lettered_at: row.try_get("dead_lettered_at")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::event::ProjectCreated;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn it_should_persist_event_as_pending() {
+        let outbox = SqliteOutbox::ephemeral().await.unwrap();
+
+        let event: Event = ProjectCreated::default().into();
+        outbox.dispatch(event.clone()).await.unwrap();
+
+        let pending = outbox.find_pending(10).await.unwrap();
+        assert!(pending.len() == 1);
+        assert!(pending[0].key == event.key());
+        assert!(pending[0].envelope().unwrap().event_id == Some(pending[0].event_id.clone()));
+    }
+
+    #[tokio::test]
+    async fn it_should_ignore_duplicated_event_id() {
+        let outbox = SqliteOutbox::ephemeral().await.unwrap();
+
+        let entry =
+            OutboxEntry::try_new(&EventEnvelope::new(ProjectCreated::default().into())).unwrap();
+        assert!(outbox.persist(&entry).await.unwrap());
+        assert!(!outbox.persist(&entry).await.unwrap());
+
+        let pending = outbox.find_pending(10).await.unwrap();
+        assert!(pending.len() == 1);
+    }
+
+    #[tokio::test]
+    async fn it_should_dispatch_without_offset() {
+        let outbox = SqliteOutbox::ephemeral().await.unwrap();
+
+        let offset = outbox
+            .dispatch(ProjectCreated::default().into())
+            .await
+            .unwrap();
+        assert!(offset.is_none());
+    }
+
+    #[tokio::test]
+    async fn it_should_keep_the_event_id_of_a_retried_dispatch() {
+        let outbox = SqliteOutbox::ephemeral().await.unwrap();
+
+        let event: Event = ProjectCreated::default().into();
+        outbox.dispatch(event.clone()).await.unwrap();
+        outbox.dispatch(event).await.unwrap();
+        outbox
+            .dispatch(ProjectCreated::default().into())
+            .await
+            .unwrap();
+
+        let pending = outbox.find_pending(10).await.unwrap();
+        assert!(pending.len() == 2);
+    }
+
+    #[tokio::test]
+    async fn it_should_find_pending_in_insertion_order() {
+        let outbox = SqliteOutbox::ephemeral().await.unwrap();
+
+        let first =
+            OutboxEntry::try_new(&EventEnvelope::new(ProjectCreated::default().into())).unwrap();
+        let second =
+            OutboxEntry::try_new(&EventEnvelope::new(ProjectCreated::default().into())).unwrap();
+        outbox.persist(&first).await.unwrap();
+        outbox.persist(&second).await.unwrap();
+
+        let pending = outbox.find_pending(10).await.unwrap();
+        assert!(pending[0].event_id == first.event_id);
+        assert!(pending[1].event_id == second.event_id);
+    }
+
+    #[tokio::test]
+    async fn it_should_skip_dispatched_events() {
+        let outbox = SqliteOutbox::ephemeral().await.unwrap();
+
+        let entry =
+            OutboxEntry::try_new(&EventEnvelope::new(ProjectCreated::default().into())).unwrap();
+        outbox.persist(&entry).await.unwrap();
+        outbox
+            .mark_dispatched(&entry.event_id, &Utc::now())
+            .await
+            .unwrap();
+
+        let pending = outbox.find_pending(10).await.unwrap();
+        assert!(pending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn it_should_skip_dead_lettered_events() {
+        let outbox = SqliteOutbox::ephemeral().await.unwrap();
+
+        let entry =
+            OutboxEntry::try_new(&EventEnvelope::new(ProjectCreated::default().into())).unwrap();
+        outbox.persist(&entry).await.unwrap();
+        outbox
+            .mark_dead_lettered(&entry.event_id, "invalid payload", &Utc::now())
+            .await
+            .unwrap();
+
+        let pending = outbox.find_pending(10).await.unwrap();
+        assert!(pending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn it_should_record_failed_attempts() {
+        let outbox = SqliteOutbox::ephemeral().await.unwrap();
+
+        let entry =
+            OutboxEntry::try_new(&EventEnvelope::new(ProjectCreated::default().into())).unwrap();
+        outbox.persist(&entry).await.unwrap();
+        outbox
+            .mark_failed(&entry.event_id, "broker unavailable")
+            .await
+            .unwrap();
+
+        let pending = outbox.find_pending(10).await.unwrap();
+        assert!(pending[0].attempts == 1);
+        assert!(pending[0].last_error == Some("broker unavailable".into()));
+    }
+}
@@ -70,4 +70,37 @@ impl ProjectEmailDriven for SESDrivenImpl {
 
         Ok(())
     }
+
+    async fn send_ownership_transfer(
+        &self,
+        project_name: &str,
+        email: &str,
+        code: &str,
+        expires_in: &DateTime<Utc>,
+    ) -> Result<()> {
+        let destination = Destination::builder().to_addresses(email).build();
+        let template = Template::builder()
+            .template_name("ownership-transfer")
+            .template_data(
+                json!({
+                    "project_name": project_name,
+                    "code": code,
+                    "expires_in": expires_in.to_rfc2822()
+                })
+                .to_string(),
+            )
+            .build();
+        let email_content = EmailContent::builder().template(template).build();
+
+        self.client
+            .send_email()
+            .from_email_address(&self.verified_email)
+            .destination(destination)
+            .content(email_content)
+            .send()
+            .await
+            .map_err(|err| Error::Unexpected(err.to_string()))?;
+
+        Ok(())
+    }
 }
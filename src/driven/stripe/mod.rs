@@ -82,9 +82,53 @@ impl StripeDriven for StripeDrivenImpl {
 
         Ok(())
     }
+
+    async fn create_invoice_item(
+        &self,
+        customer_id: &str,
+        amount: i64,
+        description: &str,
+        idempotency_key: &str,
+    ) -> Result<String> {
+        let amount = amount.to_string();
+        let mut params = HashMap::new();
+        params.insert("customer", customer_id);
+        params.insert("amount", &amount);
+        params.insert("currency", "usd");
+        params.insert("description", description);
+
+        let response = self
+            .client
+            .post(format!("{}/invoiceitems", &self.url))
+            .basic_auth(&self.api_key, Some(""))
+            .header("Idempotency-Key", idempotency_key)
+            .form(&params)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if status.is_client_error() || status.is_server_error() {
+            error!(
+                status = status.to_string(),
+                "request status code fail to create stripe invoice item"
+            );
+            return Err(Error::Unexpected(format!(
+                "stripe create invoice item request error. Status: {status}"
+            )));
+        }
+
+        let invoice_item: StripeInvoiceItem = response.json().await?;
+
+        Ok(invoice_item.id)
+    }
 }
 
 #[derive(Deserialize)]
 struct StripeCustomer {
     id: String,
 }
+
+#[derive(Deserialize)]
+struct StripeInvoiceItem {
+    id: String,
+}
@@ -4,6 +4,7 @@ use sqlx::{postgres::PgRow, FromRow, Postgres, Row};
 
 use crate::domain::{
     error::Error,
+    pagination::Page,
     worker::storage::{KeyValue, WorkerKeyValueDrivenStorage},
     Result,
 };
@@ -25,30 +26,40 @@ impl WorkerKeyValueDrivenStorage for PostgresWorkerKeyValueDrivenStorage {
         &self,
         worker_id: &str,
         key: Option<String>,
-        page: &u32,
-        page_size: &u32,
-    ) -> Result<(i64, Vec<KeyValue>)> {
-        let offset = (page_size * (page - 1)) as i64;
-        let page_size = *page_size as i64;
+        page: &Page,
+    ) -> Result<(Option<i64>, Vec<KeyValue>)> {
+        let offset = i64::from(page.offset());
+        let page_size = i64::from(page.page_size);
+
+        // The total is only counted for the first page or offset pages, the window function reads
+        // every key of the worker.
+        let counted = page.cursor.is_none();
 
         let mut query = String::from(
             r#"
             SELECT 
                 kv.worker, 
                 kv."key", 
-                kv.value,
-                COUNT(*) OVER () AS total_count
-            FROM
-                kv
-            WHERE kv.worker = $1
+                kv.value
         "#,
         );
 
+        if counted {
+            query.push_str(", COUNT(*) OVER () AS total_count");
+        }
+
+        query.push_str(" FROM kv WHERE kv.worker = $1");
+
+        let mut param = 4;
         if key.is_some() {
-            query.push_str(" AND kv.\"key\" ILIKE $4");
+            query.push_str(&format!(" AND kv.\"key\" ILIKE ${param}"));
+            param += 1;
+        }
+        if page.cursor.is_some() {
+            query.push_str(&format!(" AND kv.\"key\" > ${param}"));
         }
 
-        query.push_str(" LIMIT $2 OFFSET $3;");
+        query.push_str(" ORDER BY kv.\"key\" LIMIT $2 OFFSET $3;");
 
         let mut q = sqlx::query(&query)
             .bind(worker_id)
@@ -58,13 +69,17 @@ impl WorkerKeyValueDrivenStorage for PostgresWorkerKeyValueDrivenStorage {
         if let Some(k) = &key {
             q = q.bind(format!("%{k}%"));
         }
+        if let Some(cursor) = page.cursor_id() {
+            q = q.bind(cursor.to_string());
+        }
 
         let rows = q.fetch_all(&self.storage.pool).await?;
 
-        let count = rows
-            .first()
-            .map(|r| r.get::<i64, _>("total_count"))
-            .unwrap_or_default();
+        let count = counted.then(|| {
+            rows.first()
+                .map(|r| r.get::<i64, _>("total_count"))
+                .unwrap_or_default()
+        });
 
         let values = rows
             .into_iter()
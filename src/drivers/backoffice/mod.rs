@@ -12,21 +12,23 @@ use tracing::{error, info};
 
 use crate::{
     domain::{
-        DEFAULT_CATEGORY, PAGE_SIZE_MAX, auth::{Auth0Driven, Auth0Profile}, event::{
-            EventDrivenBridge, ProjectDeleted, ProjectUpdated, ResourceCreated, ResourceDeleted, ResourceUpdated
-        }, metadata::{KnownField, MetadataDriven}, project::{
-            self, ProjectEmailDriven, ProjectStatus, ProjectUserAggregated, ProjectUserProject, ProjectUserRole, StripeDriven, cache::{ProjectDrivenCache, ProjectDrivenCacheBackoffice}
+        DEFAULT_CATEGORY, PAGE_SIZE_MAX, audit::{AuditEvent, AuditFilter, cache::AuditDrivenCache}, auth::{Auth0Driven, Auth0Profile}, event::{
+            ProjectDeleted, ProjectUpdated, ResourceCreated, ResourceDeleted, ResourceUpdated
+        }, metadata::{KnownField, MetadataDriven}, organization::{self, cache::OrganizationDrivenCache}, pagination::Page, project::{
+            self, ProjectEmailDriven, ProjectStatus, ProjectUserAggregated, ProjectUserProject, ProjectUserRole, StripeDriven, cache::{ProjectDrivenCache, ProjectDrivenCacheBackoffice}, quota::ProjectQuota
         }, resource::{
-            ResourceStatus, cache::{ResourceDrivenCache, ResourceDrivenCacheBackoffice}, cluster::ResourceDrivenClusterBackoffice, command::{build_key, encode_key}
-        }, usage::{UsageReport, UsageReportImpl, cache::UsageDrivenCacheBackoffice}, utils::{self, get_schema_from_crd}
+            self, ResourceStatus, cache::{ResourceDrivenCache, ResourceDrivenCacheBackoffice}, cluster::ResourceDrivenClusterBackoffice, command::{build_key, encode_key}
+        }, search::{SearchFilter, SearchResult, cache::SearchDrivenCache}, usage::{self, UsageReport, UsageReportImpl, cache::{UsageDrivenCacheBackoffice, UsageDrivenCacheInvoice}, invoice::Invoice}, utils::{self, get_schema_from_crd}
     },
     driven::{
         auth0::Auth0DrivenImpl,
+        billing::SqliteBillingLedger,
+        bus::{Bus, Encoding},
         cache::{
-            SqliteCache, project::SqliteProjectDrivenCache, resource::SqliteResourceDrivenCache, usage::SqliteUsageDrivenCache
+            CacheChecksum, SqliteCache, audit::SqliteAuditDrivenCache, migration::{self, MigrationStatus}, organization::SqliteOrganizationDrivenCache, postgres::{PostgresCache, usage::PostgresUsageDrivenCache}, project::SqliteProjectDrivenCache, resource::SqliteResourceDrivenCache, search::SqliteSearchDrivenCache, usage::SqliteUsageDrivenCache
         },
         k8s::K8sCluster,
-        kafka::KafkaProducer,
+        kafka::dead_letter::{self, DeadLetter, DeadLetterConfig, DeadLetterProducer},
         metadata::FileMetadata,
         ses::SESDrivenImpl,
         stripe::StripeDrivenImpl,
@@ -66,6 +68,62 @@ pub async fn fetch_usage(
     Ok(())
 }
 
+/// Sends the usage of the closed month `period` to stripe, one invoice item per project, reading
+/// it from the postgres cache when `postgres_url` is set. The projects already invoiced for the
+/// period are skipped.
+pub async fn invoice(
+    config: BackofficeConfig,
+    period: &str,
+    postgres_url: Option<String>,
+    output: OutputFormat,
+    dry_run: bool,
+) -> Result<()> {
+    let (usage_cache, invoice_cache): (
+        Arc<dyn UsageDrivenCacheBackoffice>,
+        Arc<dyn UsageDrivenCacheInvoice>,
+    ) = match postgres_url {
+        Some(url) => {
+            let postgres_cache = Arc::new(PostgresCache::new(&url).await?);
+            postgres_cache.migrate().await?;
+            let cache = Arc::new(PostgresUsageDrivenCache::new(postgres_cache));
+            (cache.clone(), cache)
+        }
+        None => {
+            let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
+            sqlite_cache.migrate().await?;
+            let ledger = Arc::new(SqliteBillingLedger::new(Path::new(&config.ledger_path)).await?);
+            ledger.migrate().await?;
+            (Arc::new(SqliteUsageDrivenCache::new(sqlite_cache)), ledger)
+        }
+    };
+
+    let (Some(stripe_url), Some(stripe_api_key)) = (&config.stripe_url, &config.stripe_api_key)
+    else {
+        bail!("a [stripe] section is required in the cli config to invoice the usage")
+    };
+    let stripe: Arc<dyn StripeDriven> = Arc::new(StripeDrivenImpl::new(stripe_url, stripe_api_key));
+
+    let metadata = Arc::new(FileMetadata::from_dir(METADATA.clone())?);
+
+    let invoices = usage::invoice::apply_invoice(
+        usage_cache,
+        invoice_cache,
+        stripe,
+        metadata,
+        period,
+        dry_run,
+    )
+    .await?;
+
+    match output {
+        OutputFormat::Table => output_table_invoice(invoices),
+        OutputFormat::Json => output_json_invoice(invoices),
+        OutputFormat::Csv => output_csv_invoice(invoices, period),
+    };
+
+    Ok(())
+}
+
 pub async fn fetch_projects(
     config: BackofficeConfig,
     namespace: Option<String>,
@@ -183,10 +241,7 @@ pub async fn rename_project(
     let cache: Box<dyn ProjectDrivenCache> =
         Box::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
 
-    let event = Arc::new(KafkaProducer::new(
-        &config.topic_events,
-        &config.kafka_producer,
-    )?);
+    let event = config.bus.bridge(&config.topic_events)?;
 
     if cache.find_by_id(&id).await?.is_none() {
         error!("Failed to locate project");
@@ -197,6 +252,8 @@ pub async fn rename_project(
         id: id.clone(),
         name: Some(new_name),
         status: None,
+        billing_subscription_id: None,
+        billing_subscription_deleted: false,
         updated_at: Utc::now(),
     };
 
@@ -238,22 +295,15 @@ pub async fn fetch_project_users(
     // Page to the end rather than taking the first page: this is the whole membership or it is
     // misleading, and a caller cannot tell a short list from a truncated one.
     let mut project_users = Vec::new();
-    let mut page = 1;
+    let mut page = Page::after(None, PAGE_SIZE_MAX);
     loop {
-        let batch = project::command::list_users(
-            cache.clone(),
-            auth0.clone(),
-            &project_id,
-            &page,
-            &PAGE_SIZE_MAX,
-        )
-        .await?;
-        let is_last = (batch.len() as u32) < PAGE_SIZE_MAX;
-        project_users.extend(batch);
-        if is_last {
+        let batch =
+            project::command::list_users(cache.clone(), auth0.clone(), &project_id, &page).await?;
+        project_users.extend(batch.records);
+        if batch.next_cursor.is_none() {
             break;
         }
-        page += 1;
+        page = Page::after(batch.next_cursor, PAGE_SIZE_MAX);
     }
 
     if project_users.is_empty() {
@@ -305,10 +355,7 @@ pub async fn transfer_project(
         Some(Arc::new(StripeDrivenImpl::new(stripe_url, stripe_api_key)))
     };
 
-    let event = Arc::new(KafkaProducer::new(
-        &config.topic_events,
-        &config.kafka_producer,
-    )?);
+    let event = config.bus.bridge(&config.topic_events)?;
 
     // The CLI takes an email; the domain command takes an auth0 user id.
     let profile = auth0.find_info(&format!("email:{new_owner_email}")).await?;
@@ -403,10 +450,7 @@ pub async fn invite_user(
         ses_verified_email,
     ));
 
-    let event = Arc::new(KafkaProducer::new(
-        &config.topic_events,
-        &config.kafka_producer,
-    )?);
+    let event = config.bus.bridge(&config.topic_events)?;
 
     if cache.find_by_id(&project_id).await?.is_none() {
         bail!("Failed to locate project")
@@ -447,224 +491,581 @@ pub async fn invite_user(
     Ok(())
 }
 
-pub async fn delete_project(config: BackofficeConfig, id: String, dry_run: bool) -> Result<()> {
-    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
-    sqlite_cache.migrate().await?;
-
-    let cache: Box<dyn ProjectDrivenCache> =
-        Box::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
-
-    let event = Arc::new(KafkaProducer::new(
-        &config.topic_events,
-        &config.kafka_producer,
-    )?);
+pub async fn create_organization(
+    config: BackofficeConfig,
+    name: String,
+    owner_email: String,
+    dry_run: bool,
+) -> Result<()> {
+    let auth0: Arc<dyn Auth0Driven> = Arc::new(
+        Auth0DrivenImpl::try_new(
+            &config.auth_url,
+            &config.auth_client_id,
+            &config.auth_client_secret,
+            &config.auth_audience,
+        )
+        .await?,
+    );
 
-    let project = match cache.find_by_id(&id).await? {
-        Some(project) => project,
-        None => {
-            error!("Failed to locate project");
-            return Ok(());
-        }
+    let (Some(stripe_url), Some(stripe_api_key)) = (&config.stripe_url, &config.stripe_api_key)
+    else {
+        bail!("a [stripe] section is required in the cli config to create an organization; its billing goes through a stripe customer of its own")
     };
+    let stripe: Arc<dyn StripeDriven> = Arc::new(StripeDrivenImpl::new(stripe_url, stripe_api_key));
 
-    let evt = ProjectDeleted {
-        id: id.clone(),
-        namespace: project.namespace,
-        deleted_at: Utc::now(),
-    };
+    let event = config.bus.bridge(&config.topic_events)?;
 
-    if dry_run {
-        info!("event to dispath: {:?}", evt)
-    } else {
-        event.dispatch(evt.into()).await?;
-        info!(project = &id, "project deleted");
+    let profile = auth0.find_info(&format!("email:{owner_email}")).await?;
+    if profile.is_empty() {
+        bail!("No one user was found")
     }
+    let profile = profile.first().unwrap();
+
+    let id = Uuid::new_v4().to_string();
+    organization::command::apply_create(
+        event,
+        auth0.clone(),
+        stripe,
+        &id,
+        &name,
+        &profile.user_id,
+        dry_run,
+    )
+    .await?;
 
     Ok(())
 }
 
-pub async fn create_resource(
+pub async fn add_organization_user(
     config: BackofficeConfig,
-    project_id: String,
-    kind: String,
-    spec: String,
+    organization_id: String,
+    email: String,
+    role: String,
     dry_run: bool,
 ) -> Result<()> {
+    let role: ProjectUserRole = role.parse()?;
+
     let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
     sqlite_cache.migrate().await?;
 
-    let resource_cache: Box<dyn ResourceDrivenCache> = Box::new(SqliteResourceDrivenCache::new(sqlite_cache.clone()));
-    let project_cache: Box<dyn ProjectDrivenCache> = Box::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
-    let metadata = Box::new(FileMetadata::new(&config.crds_path)?);
-
-    let event = Arc::new(KafkaProducer::new(
-        &config.topic_events,
-        &config.kafka_producer,
-    )?);
+    let cache: Arc<dyn OrganizationDrivenCache> =
+        Arc::new(SqliteOrganizationDrivenCache::new(sqlite_cache.clone()));
 
-    let name = format!(
-        "{}-{}",
-        kind.to_lowercase().replace("port", ""),
-        utils::get_random_salt()
+    let auth0: Arc<dyn Auth0Driven> = Arc::new(
+        Auth0DrivenImpl::try_new(
+            &config.auth_url,
+            &config.auth_client_id,
+            &config.auth_client_secret,
+            &config.auth_audience,
+        )
+        .await?,
     );
 
-    if resource_cache
-        .find_by_name(&project_id, &name)
-        .await?
-        .is_some()
-    {
-        error!("Invalid random name, try again");
-        return Ok(());
-    }
-
-    let Some(metadata) = metadata.find_by_kind(&kind)? else {
-        error!("Kind not supported");
-        return Ok(());
-    };
-
-    let project = match project_cache.find_by_id(&project_id).await? {
-        Some(project) => project,
-        None => {
-            error!("Failed to locate project");
-            return Ok(());
-        }
-    };
-    let resource_id = Uuid::new_v4().to_string();
-
-    let mut spec_json = match serde_json::from_str(&spec)? {
-        serde_json::Value::Object(v) => v,
-        _ => {
-            error!("invalid spec json");
-            return Ok(());
-        }
-    };
+    let event = config.bus.bridge(&config.topic_events)?;
 
-    if let Some(status_schema) = get_schema_from_crd(&metadata.crd, "status") {
-        for (key, _) in status_schema {
-            if let Ok(status_field) = key.parse::<KnownField>() {
-                let value = match status_field {
-                    KnownField::AuthToken => {
-                        let key = build_key(&project.id, &resource_id)?;
-                        encode_key(key, &kind)?
-                    }
-                    KnownField::Username => {
-                        let user_key = build_key(&project.id, &resource_id)?;
-                        encode_key(user_key, &kind)?
-                    }
-                    KnownField::Password => {
-                        let password_key = build_key(&project.id, &resource_id)?;
-                        BASE64_STANDARD_NO_PAD.encode(password_key)
-                    }
-                };
-                spec_json.insert(key, serde_json::Value::String(value));
-            }
-        }
-    };
+    // Unlike a project invite there is no code to accept, so the member needs an account already.
+    let profile = auth0.find_info(&format!("email:{email}")).await?;
+    if profile.is_empty() {
+        bail!("No one user was found")
+    }
+    let profile = profile.first().unwrap();
 
-    let evt = ResourceCreated {
-        id: resource_id.clone(),
-        project_id: project.id,
-        project_namespace: project.namespace,
-        name,
-        kind: kind.clone(),
-        category: metadata
-            .crd
-            .spec
-            .names
-            .categories
-            .and_then(|c| c.first().map(String::to_owned))
-            .unwrap_or(DEFAULT_CATEGORY.to_string()),
-        spec: serde_json::to_string(&spec_json)?,
-        status: ResourceStatus::Active.to_string(),
-        created_at: Utc::now(),
-        updated_at: Utc::now(),
-    };
+    organization::command::apply_add_user(
+        cache,
+        event,
+        &organization_id,
+        &profile.user_id,
+        &role,
+        "backoffice",
+        dry_run,
+    )
+    .await?;
 
-    if dry_run {
-        info!("event to dispath: {:?}", evt)
-    } else {
-        event.dispatch(evt.into()).await?;
-        info!(resource_id, "resource created");
-    }
     Ok(())
 }
 
-pub async fn delete_resource(
+pub async fn delete_organization_user(
     config: BackofficeConfig,
-    id: String,
-    project_id: String,
+    organization_id: String,
+    email: String,
     dry_run: bool,
 ) -> Result<()> {
     let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
     sqlite_cache.migrate().await?;
 
-    let project_cache: Box<dyn ProjectDrivenCache> =
-        Box::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
-
-    let resource_cache: Box<dyn ResourceDrivenCache> =
-        Box::new(SqliteResourceDrivenCache::new(sqlite_cache.clone()));
-
-    let event = Arc::new(KafkaProducer::new(
-        &config.topic_events,
-        &config.kafka_producer,
-    )?);
-
-    let resource = match resource_cache.find_by_id(&id).await? {
-        Some(resource) => resource,
-        None => {
-            error!("Failed to locate resource");
-            return Ok(());
-        }
-    };
+    let cache: Arc<dyn OrganizationDrivenCache> =
+        Arc::new(SqliteOrganizationDrivenCache::new(sqlite_cache.clone()));
 
-    let project = match project_cache.find_by_id(&project_id).await? {
-        Some(project) => project,
-        None => {
-            error!("Failed to locate project");
-            return Ok(());
-        }
-    };
+    let auth0: Arc<dyn Auth0Driven> = Arc::new(
+        Auth0DrivenImpl::try_new(
+            &config.auth_url,
+            &config.auth_client_id,
+            &config.auth_client_secret,
+            &config.auth_audience,
+        )
+        .await?,
+    );
 
-    let evt = ResourceDeleted {
-        id,
-        project_id: project.id,
-        project_namespace: project.namespace,
-        name: resource.name,
-        kind: resource.kind.clone(),
-        status: ResourceStatus::Deleted.to_string(),
-        deleted_at: Utc::now(),
-    };
+    let event = config.bus.bridge(&config.topic_events)?;
 
-    if dry_run {
-        info!("event to dispath: {:?}", evt)
-    } else {
-        event.dispatch(evt.into()).await?;
-        info!(resource = resource.kind, "resource deleted");
+    let profile = auth0.find_info(&format!("email:{email}")).await?;
+    if profile.is_empty() {
+        bail!("No one user was found")
     }
+    let profile = profile.first().unwrap();
+
+    organization::command::apply_delete_user(
+        cache,
+        event,
+        &organization_id,
+        &profile.user_id,
+        "backoffice",
+        dry_run,
+    )
+    .await?;
 
     Ok(())
 }
 
-pub async fn patch_resource(
+pub async fn attach_project(
     config: BackofficeConfig,
-    id: String,
+    organization_id: String,
     project_id: String,
-    patch: String,
     dry_run: bool,
 ) -> Result<()> {
     let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
     sqlite_cache.migrate().await?;
 
-    let project_cache: Box<dyn ProjectDrivenCache> =
-        Box::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
+    let organization_cache: Arc<dyn OrganizationDrivenCache> =
+        Arc::new(SqliteOrganizationDrivenCache::new(sqlite_cache.clone()));
+    let project_cache: Arc<dyn ProjectDrivenCache> =
+        Arc::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
 
-    let resource_cache: Box<dyn ResourceDrivenCache> =
-        Box::new(SqliteResourceDrivenCache::new(sqlite_cache.clone()));
+    let event = config.bus.bridge(&config.topic_events)?;
 
-    let event = Arc::new(KafkaProducer::new(
-        &config.topic_events,
-        &config.kafka_producer,
-    )?);
+    organization::command::apply_attach_project(
+        organization_cache,
+        project_cache,
+        event,
+        &organization_id,
+        &project_id,
+        "backoffice",
+        dry_run,
+    )
+    .await?;
+
+    Ok(())
+}
+
+pub async fn detach_project(
+    config: BackofficeConfig,
+    project_id: String,
+    dry_run: bool,
+) -> Result<()> {
+    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
+    sqlite_cache.migrate().await?;
+
+    let project_cache: Arc<dyn ProjectDrivenCache> =
+        Arc::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
+
+    let auth0: Arc<dyn Auth0Driven> = Arc::new(
+        Auth0DrivenImpl::try_new(
+            &config.auth_url,
+            &config.auth_client_id,
+            &config.auth_client_secret,
+            &config.auth_audience,
+        )
+        .await?,
+    );
+
+    let (Some(stripe_url), Some(stripe_api_key)) = (&config.stripe_url, &config.stripe_api_key)
+    else {
+        bail!("a [stripe] section is required in the cli config to detach a project; it goes back to a stripe customer of its own")
+    };
+    let stripe: Arc<dyn StripeDriven> = Arc::new(StripeDrivenImpl::new(stripe_url, stripe_api_key));
+
+    let event = config.bus.bridge(&config.topic_events)?;
+
+    organization::command::apply_detach_project(
+        project_cache,
+        event,
+        auth0,
+        stripe,
+        &project_id,
+        "backoffice",
+        dry_run,
+    )
+    .await?;
+
+    Ok(())
+}
+
+pub async fn suspend_project(
+    config: BackofficeConfig,
+    id: String,
+    reason: String,
+    dry_run: bool,
+) -> Result<()> {
+    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
+    sqlite_cache.migrate().await?;
+
+    let project_cache: Arc<dyn ProjectDrivenCache> =
+        Arc::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
+
+    let event = config.bus.bridge(&config.topic_events)?;
+
+    project::command::apply_suspend(project_cache, event, &id, &reason, "backoffice", dry_run)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn resume_project(config: BackofficeConfig, id: String, dry_run: bool) -> Result<()> {
+    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
+    sqlite_cache.migrate().await?;
+
+    let project_cache: Arc<dyn ProjectDrivenCache> =
+        Arc::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
+
+    let event = config.bus.bridge(&config.topic_events)?;
+
+    project::command::apply_resume(project_cache, event, &id, "backoffice", dry_run).await?;
+
+    Ok(())
+}
+
+/// Replaces the quota of the project, or the default one of every project without `project_id`.
+/// The resource limits are `KIND=N` pairs.
+pub async fn set_quota(
+    config: BackofficeConfig,
+    project_id: Option<String>,
+    resources: Vec<String>,
+    max_tier: Option<u32>,
+    workers: Option<u32>,
+    dry_run: bool,
+) -> Result<()> {
+    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
+    sqlite_cache.migrate().await?;
+
+    let project_cache: Arc<dyn ProjectDrivenCache> =
+        Arc::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
+
+    let event = config.bus.bridge(&config.topic_events)?;
+
+    let mut limits = HashMap::new();
+    for resource in resources.iter() {
+        let Some((kind, max)) = resource.split_once('=') else {
+            bail!("invalid resource limit {resource}, the format is KIND=N")
+        };
+        let Ok(max) = max.parse::<u32>() else {
+            bail!("invalid resource limit {resource}, the limit must be a number")
+        };
+        limits.insert(kind.to_string(), max);
+    }
+
+    let quota = ProjectQuota {
+        project_id,
+        resources: limits,
+        max_tier,
+        workers,
+        updated_at: Utc::now(),
+    };
+
+    project::quota::apply_update(project_cache, event, quota, "backoffice", dry_run).await?;
+
+    Ok(())
+}
+
+/// Limits of the project, with the default ones it doesn't override, or the default quota
+/// without `project_id`.
+pub async fn fetch_quota(
+    config: BackofficeConfig,
+    project_id: Option<String>,
+    output: OutputFormat,
+) -> Result<()> {
+    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
+    sqlite_cache.migrate().await?;
+
+    let project_cache: Arc<dyn ProjectDrivenCache> =
+        Arc::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
+
+    let quota = match &project_id {
+        Some(project_id) => project::quota::find_limits(project_cache, project_id).await?,
+        None => project_cache
+            .find_default_quota()
+            .await?
+            .unwrap_or_default(),
+    };
+
+    match output {
+        OutputFormat::Table => output_table_quota(quota),
+        OutputFormat::Json => output_json_quota(quota),
+        OutputFormat::Csv => todo!("not implemented"),
+    };
+
+    Ok(())
+}
+
+pub async fn delete_project(config: BackofficeConfig, id: String, dry_run: bool) -> Result<()> {
+    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
+    sqlite_cache.migrate().await?;
+
+    let cache: Box<dyn ProjectDrivenCache> =
+        Box::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
+
+    let event = config.bus.bridge(&config.topic_events)?;
+
+    let project = match cache.find_by_id(&id).await? {
+        Some(project) => project,
+        None => {
+            error!("Failed to locate project");
+            return Ok(());
+        }
+    };
+
+    let evt = ProjectDeleted {
+        id: id.clone(),
+        namespace: project.namespace,
+        deleted_by: Some("backoffice".into()),
+        deleted_at: Utc::now(),
+    };
+
+    if dry_run {
+        info!("event to dispath: {:?}", evt)
+    } else {
+        event.dispatch(evt.into()).await?;
+        info!(project = &id, "project deleted");
+    }
+
+    Ok(())
+}
+
+pub async fn restore_project(config: BackofficeConfig, id: String, dry_run: bool) -> Result<()> {
+    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
+    sqlite_cache.migrate().await?;
+
+    let project_cache: Arc<dyn ProjectDrivenCache> =
+        Arc::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
+    let resource_cache: Arc<dyn ResourceDrivenCache> =
+        Arc::new(SqliteResourceDrivenCache::new(sqlite_cache.clone()));
+
+    let event = config.bus.bridge(&config.topic_events)?;
+
+    project::command::apply_restore(
+        project_cache,
+        resource_cache,
+        event,
+        &id,
+        "backoffice",
+        dry_run,
+    )
+    .await?;
+
+    Ok(())
+}
+
+pub async fn create_resource(
+    config: BackofficeConfig,
+    project_id: String,
+    kind: String,
+    spec: String,
+    cluster_id: Option<String>,
+    dry_run: bool,
+) -> Result<()> {
+    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
+    sqlite_cache.migrate().await?;
+
+    let resource_cache: Box<dyn ResourceDrivenCache> = Box::new(SqliteResourceDrivenCache::new(sqlite_cache.clone()));
+    let project_cache: Box<dyn ProjectDrivenCache> = Box::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
+    let metadata = Box::new(FileMetadata::new(&config.crds_path)?);
+
+    let event = config.bus.bridge(&config.topic_events)?;
+
+    let name = format!(
+        "{}-{}",
+        kind.to_lowercase().replace("port", ""),
+        utils::get_random_salt()
+    );
+
+    if resource_cache
+        .find_by_name(&project_id, &name)
+        .await?
+        .is_some()
+    {
+        error!("Invalid random name, try again");
+        return Ok(());
+    }
+
+    let Some(metadata) = metadata.find_by_kind(&kind)? else {
+        error!("Kind not supported");
+        return Ok(());
+    };
+
+    let project = match project_cache.find_by_id(&project_id).await? {
+        Some(project) => project,
+        None => {
+            error!("Failed to locate project");
+            return Ok(());
+        }
+    };
+    let resource_id = Uuid::new_v4().to_string();
+
+    let mut spec_json = match serde_json::from_str(&spec)? {
+        serde_json::Value::Object(v) => v,
+        _ => {
+            error!("invalid spec json");
+            return Ok(());
+        }
+    };
+
+    if let Some(status_schema) = get_schema_from_crd(&metadata.crd, "status") {
+        for (key, _) in status_schema {
+            if let Ok(status_field) = key.parse::<KnownField>() {
+                let value = match status_field {
+                    KnownField::AuthToken => {
+                        let key = build_key(&project.id, &resource_id)?;
+                        encode_key(key, &kind)?
+                    }
+                    KnownField::Username => {
+                        let user_key = build_key(&project.id, &resource_id)?;
+                        encode_key(user_key, &kind)?
+                    }
+                    KnownField::Password => {
+                        let password_key = build_key(&project.id, &resource_id)?;
+                        BASE64_STANDARD_NO_PAD.encode(password_key)
+                    }
+                };
+                spec_json.insert(key, serde_json::Value::String(value));
+            }
+        }
+    };
+
+    let evt = ResourceCreated {
+        id: resource_id.clone(),
+        project_id: project.id,
+        project_namespace: project.namespace,
+        name,
+        kind: kind.clone(),
+        category: metadata
+            .crd
+            .spec
+            .names
+            .categories
+            .and_then(|c| c.first().map(String::to_owned))
+            .unwrap_or(DEFAULT_CATEGORY.to_string()),
+        spec: serde_json::to_string(&spec_json)?,
+        status: ResourceStatus::Active.to_string(),
+        cluster_id: cluster_id.or(project.cluster_id),
+        created_by: Some("backoffice".into()),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    };
+
+    if dry_run {
+        info!("event to dispath: {:?}", evt)
+    } else {
+        event.dispatch(evt.into()).await?;
+        info!(resource_id, "resource created");
+    }
+    Ok(())
+}
+
+pub async fn delete_resource(
+    config: BackofficeConfig,
+    id: String,
+    project_id: String,
+    dry_run: bool,
+) -> Result<()> {
+    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
+    sqlite_cache.migrate().await?;
+
+    let project_cache: Box<dyn ProjectDrivenCache> =
+        Box::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
+
+    let resource_cache: Box<dyn ResourceDrivenCache> =
+        Box::new(SqliteResourceDrivenCache::new(sqlite_cache.clone()));
+
+    let event = config.bus.bridge(&config.topic_events)?;
+
+    let resource = match resource_cache.find_by_id(&id).await? {
+        Some(resource) => resource,
+        None => {
+            error!("Failed to locate resource");
+            return Ok(());
+        }
+    };
+
+    let project = match project_cache.find_by_id(&project_id).await? {
+        Some(project) => project,
+        None => {
+            error!("Failed to locate project");
+            return Ok(());
+        }
+    };
+
+    let evt = ResourceDeleted {
+        id,
+        project_id: project.id,
+        project_namespace: project.namespace,
+        name: resource.name,
+        kind: resource.kind.clone(),
+        status: ResourceStatus::Deleted.to_string(),
+        cluster_id: resource.cluster_id,
+        deleted_by: Some("backoffice".into()),
+        deleted_at: Utc::now(),
+    };
+
+    if dry_run {
+        info!("event to dispath: {:?}", evt)
+    } else {
+        event.dispatch(evt.into()).await?;
+        info!(resource = resource.kind, "resource deleted");
+    }
+
+    Ok(())
+}
+
+pub async fn restore_resource(config: BackofficeConfig, id: String, dry_run: bool) -> Result<()> {
+    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
+    sqlite_cache.migrate().await?;
+
+    let project_cache: Arc<dyn ProjectDrivenCache> =
+        Arc::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
+    let resource_cache: Arc<dyn ResourceDrivenCache> =
+        Arc::new(SqliteResourceDrivenCache::new(sqlite_cache.clone()));
+
+    let event = config.bus.bridge(&config.topic_events)?;
+
+    resource::command::apply_restore(
+        project_cache,
+        resource_cache,
+        event,
+        &id,
+        "backoffice",
+        dry_run,
+    )
+    .await?;
+
+    Ok(())
+}
+
+pub async fn patch_resource(
+    config: BackofficeConfig,
+    id: String,
+    project_id: String,
+    patch: String,
+    dry_run: bool,
+) -> Result<()> {
+    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
+    sqlite_cache.migrate().await?;
+
+    let project_cache: Box<dyn ProjectDrivenCache> =
+        Box::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
+
+    let resource_cache: Box<dyn ResourceDrivenCache> =
+        Box::new(SqliteResourceDrivenCache::new(sqlite_cache.clone()));
+
+    let event = config.bus.bridge(&config.topic_events)?;
 
     let resource = match resource_cache.find_by_id(&id).await? {
         Some(resource) => resource,
@@ -699,6 +1100,8 @@ pub async fn patch_resource(
         name: resource.name,
         kind: resource.kind.clone(),
         spec_patch: patch,
+        cluster_id: resource.cluster_id,
+        updated_by: Some("backoffice".into()),
         updated_at: Utc::now(),
     };
 
@@ -832,70 +1235,372 @@ pub async fn fetch_new_users(
     Ok(())
 }
 
-pub async fn fetch_diff(config: BackofficeConfig, output: OutputFormat) -> Result<()> {
-    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
-    sqlite_cache.migrate().await?;
+pub async fn fetch_diff(config: BackofficeConfig, output: OutputFormat) -> Result<()> {
+    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
+    sqlite_cache.migrate().await?;
+
+    let backoffice_cache: Box<dyn ResourceDrivenCacheBackoffice> =
+        Box::new(SqliteResourceDrivenCache::new(sqlite_cache.clone()));
+
+    let backoffice_cluster: Box<dyn ResourceDrivenClusterBackoffice> =
+        Box::new(K8sCluster::new().await?);
+
+    let metadata = Arc::new(FileMetadata::from_dir(METADATA.clone())?);
+    let resources_state = backoffice_cache.find_actives().await?;
+    let mut resources_cluster = Vec::new();
+
+    for metadata_resource in metadata.find()? {
+        let kind = metadata_resource.crd.spec.names.kind;
+        let mut items = backoffice_cluster.find_all(&kind).await?;
+        resources_cluster.append(&mut items);
+    }
+
+    let mut report: HashMap<String, (bool, bool)> = HashMap::new();
+    for resource in resources_state.iter() {
+        let exist = resources_cluster.iter().any(|d| {
+            let namespace = d.metadata.namespace.as_ref().unwrap().replace("prj-", "");
+            let name = d.name_any();
+
+            namespace.eq(&resource.project_namespace) && name.eq(&resource.name)
+        });
+
+        report.insert(
+            format!("{}/{}", resource.project_namespace, resource.name),
+            (true, exist),
+        );
+    }
+
+    for resource in resources_cluster {
+        let namespace = resource
+            .metadata
+            .namespace
+            .as_ref()
+            .unwrap()
+            .replace("prj-", "");
+        let name = resource.name_any();
+
+        let exist = resources_state
+            .iter()
+            .any(|r| r.project_namespace.eq(&namespace) && r.name.eq(&name));
+
+        report
+            .entry(format!("{namespace}/{name}"))
+            .and_modify(|r| r.1 = exist)
+            .or_insert((exist, true));
+    }
+
+    let report: Vec<(String, (bool, bool))> = report
+        .into_iter()
+        .filter(|(_, (in_state, in_cluster))| !(*in_state && *in_cluster))
+        .collect();
+
+    match output {
+        OutputFormat::Table => output_table_diff(report),
+        OutputFormat::Json => todo!("not implemented"),
+        OutputFormat::Csv => output_csv_diff(report),
+    };
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn fetch_audit(
+    config: BackofficeConfig,
+    project_id: Option<String>,
+    resource_id: Option<String>,
+    actor: Option<String>,
+    event_type: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    page: u32,
+    page_size: u32,
+    output: OutputFormat,
+) -> Result<()> {
+    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
+    sqlite_cache.migrate().await?;
+
+    let audit_cache: Box<dyn AuditDrivenCache> =
+        Box::new(SqliteAuditDrivenCache::new(sqlite_cache.clone()));
+
+    let filter = AuditFilter {
+        project_id,
+        resource_id,
+        actor,
+        event_type,
+        from: from.as_deref().map(parse_datetime).transpose()?,
+        to: to.as_deref().map(parse_datetime).transpose()?,
+    };
+    let events = audit_cache.find(&filter, &page, &page_size).await?;
+
+    match output {
+        OutputFormat::Table => output_table_audit(events),
+        OutputFormat::Json => output_json_audit(events),
+        OutputFormat::Csv => todo!("not implemented"),
+    };
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn search(
+    config: BackofficeConfig,
+    text: Option<String>,
+    kind: Option<String>,
+    network: Option<String>,
+    tier: Option<String>,
+    status: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    owner_email: Option<String>,
+    page: u32,
+    page_size: u32,
+    output: OutputFormat,
+) -> Result<()> {
+    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
+    sqlite_cache.migrate().await?;
+
+    let search_cache: Box<dyn SearchDrivenCache> =
+        Box::new(SqliteSearchDrivenCache::new(sqlite_cache.clone()));
+
+    // Auth0 is only reached when the owner is filtered, the projects keep the owner user id.
+    let owner = match owner_email {
+        Some(email) => {
+            let auth0: Box<dyn Auth0Driven> = Box::new(
+                Auth0DrivenImpl::try_new(
+                    &config.auth_url,
+                    &config.auth_client_id,
+                    &config.auth_client_secret,
+                    &config.auth_audience,
+                )
+                .await?,
+            );
+            let profile = auth0.find_info(&format!("email:{email}")).await?;
+            let Some(profile) = profile.first() else {
+                bail!("No one user was found")
+            };
+            Some(profile.user_id.clone())
+        }
+        None => None,
+    };
+
+    let filter = SearchFilter {
+        text,
+        kind,
+        network,
+        tier,
+        status,
+        from: from.as_deref().map(parse_datetime).transpose()?,
+        to: to.as_deref().map(parse_datetime).transpose()?,
+        owner,
+        ..Default::default()
+    };
+    let results = search_cache.search(&filter, &page, &page_size).await?;
+
+    match output {
+        OutputFormat::Table => output_table_search(results),
+        OutputFormat::Json => output_json_search(results),
+        OutputFormat::Csv => todo!("not implemented"),
+    };
+
+    Ok(())
+}
+
+/// Accepts a full rfc3339 datetime or a day (year-month-day), read as midnight UTC.
+fn parse_datetime(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Ok(datetime.to_utc());
+    }
+    let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") else {
+        bail!("invalid date {value}, expected year-month-day or rfc3339");
+    };
+    Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+}
+
+pub async fn fetch_checksum(config: BackofficeConfig, output: OutputFormat) -> Result<()> {
+    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
+    sqlite_cache.migrate().await?;
+
+    let checksum = sqlite_cache.checksum().await?;
+
+    match output {
+        OutputFormat::Table => output_table_checksum(checksum),
+        OutputFormat::Json => output_json_checksum(checksum),
+        OutputFormat::Csv => todo!("not implemented"),
+    };
+
+    Ok(())
+}
+
+/// Migrations of the sqlite cache at `db_path`, or of the postgres cache when `postgres_url` is
+/// set, with the ones applied by a newer release.
+pub async fn fetch_migrations(
+    config: BackofficeConfig,
+    postgres_url: Option<String>,
+    output: OutputFormat,
+) -> Result<()> {
+    let migrations = match postgres_url {
+        Some(url) => PostgresCache::new(&url).await?.migrations().await?,
+        None => {
+            SqliteCache::new(Path::new(&config.db_path))
+                .await?
+                .migrations()
+                .await?
+        }
+    };
+
+    match output {
+        OutputFormat::Table => output_table_migrations(migrations),
+        OutputFormat::Json => output_json_migrations(migrations),
+        OutputFormat::Csv => todo!("not implemented"),
+    };
+
+    Ok(())
+}
+
+pub async fn migrate_up(config: BackofficeConfig, postgres_url: Option<String>) -> Result<()> {
+    match postgres_url {
+        Some(url) => PostgresCache::new(&url).await?.migrate().await?,
+        None => {
+            SqliteCache::new(Path::new(&config.db_path))
+                .await?
+                .migrate()
+                .await?
+        }
+    };
 
-    let backoffice_cache: Box<dyn ResourceDrivenCacheBackoffice> =
-        Box::new(SqliteResourceDrivenCache::new(sqlite_cache.clone()));
+    info!("Migrations applied");
 
-    let backoffice_cluster: Box<dyn ResourceDrivenClusterBackoffice> =
-        Box::new(K8sCluster::new().await?);
+    Ok(())
+}
 
-    let metadata = Arc::new(FileMetadata::from_dir(METADATA.clone())?);
-    let resources_state = backoffice_cache.find_actives().await?;
-    let mut resources_cluster = Vec::new();
+/// Reverts the migrations applied after `target`, only the latest one when it is omitted.
+pub async fn migrate_down(
+    config: BackofficeConfig,
+    postgres_url: Option<String>,
+    target: Option<i64>,
+) -> Result<()> {
+    let target = match postgres_url {
+        Some(url) => {
+            let postgres_cache = PostgresCache::new(&url).await?;
+            let target = down_target(&postgres_cache.migrations().await?, target)?;
+            if let Some(target) = target {
+                postgres_cache.migrate_down(target).await?;
+            }
+            target
+        }
+        None => {
+            let sqlite_cache = SqliteCache::new(Path::new(&config.db_path)).await?;
+            let target = down_target(&sqlite_cache.migrations().await?, target)?;
+            if let Some(target) = target {
+                sqlite_cache.migrate_down(target).await?;
+            }
+            target
+        }
+    };
 
-    for metadata_resource in metadata.find()? {
-        let kind = metadata_resource.crd.spec.names.kind;
-        let mut items = backoffice_cluster.find_all(&kind).await?;
-        resources_cluster.append(&mut items);
+    match target {
+        Some(target) => info!(target, "Migrations reverted"),
+        None => info!("No one migration is applied"),
     }
 
-    let mut report: HashMap<String, (bool, bool)> = HashMap::new();
-    for resource in resources_state.iter() {
-        let exist = resources_cluster.iter().any(|d| {
-            let namespace = d.metadata.namespace.as_ref().unwrap().replace("prj-", "");
-            let name = d.name_any();
+    Ok(())
+}
 
-            namespace.eq(&resource.project_namespace) && name.eq(&resource.name)
-        });
+fn down_target(migrations: &[MigrationStatus], target: Option<i64>) -> Result<Option<i64>> {
+    // The down migrations of a newer release aren't in this binary.
+    migration::ensure_known(migrations)?;
 
-        report.insert(
-            format!("{}/{}", resource.project_namespace, resource.name),
-            (true, exist),
-        );
-    }
+    Ok(target.or_else(|| migration::previous_version(migrations)))
+}
 
-    for resource in resources_cluster {
-        let namespace = resource
-            .metadata
-            .namespace
-            .as_ref()
-            .unwrap()
-            .replace("prj-", "");
-        let name = resource.name_any();
+pub async fn fetch_dead_letters(config: BackofficeConfig, output: OutputFormat) -> Result<()> {
+    let Some(topic) = config.topic_dead_letter.clone() else {
+        bail!("topic_dead_letter is required to list dead letters")
+    };
 
-        let exist = resources_state
-            .iter()
-            .any(|r| r.project_namespace.eq(&namespace) && r.name.eq(&name));
+    let dead_letters = tokio::task::spawn_blocking(move || {
+        dead_letter::fetch_dead_letters(&topic, &config.kafka_consumer)
+    })
+    .await??;
 
-        report
-            .entry(format!("{namespace}/{name}"))
-            .and_modify(|r| r.1 = exist)
-            .or_insert((exist, true));
+    if dead_letters.is_empty() {
+        info!("No one dead letter was found");
+        return Ok(());
     }
 
-    let report: Vec<(String, (bool, bool))> = report
+    match output {
+        OutputFormat::Table => output_table_dead_letters(dead_letters),
+        OutputFormat::Json => output_json_dead_letters(dead_letters),
+        OutputFormat::Csv => todo!("not implemented"),
+    };
+
+    Ok(())
+}
+
+/// Publishes dead-lettered messages again on their source topic. `positions` selects messages
+/// by `partition:offset` in the dead-letter topic, when empty every message is re-driven.
+pub async fn redrive_dead_letters(
+    config: BackofficeConfig,
+    positions: Vec<String>,
+    dry_run: bool,
+) -> Result<()> {
+    let Some(topic) = config.topic_dead_letter.clone() else {
+        bail!("topic_dead_letter is required to re-drive dead letters")
+    };
+
+    let producer = DeadLetterProducer::new(
+        "backoffice",
+        &DeadLetterConfig {
+            topic: topic.clone(),
+            kafka: config.kafka_producer.clone(),
+        },
+    )?;
+
+    let kafka_consumer = config.kafka_consumer.clone();
+    let dead_letters = tokio::task::spawn_blocking(move || {
+        dead_letter::fetch_dead_letters(&topic, &kafka_consumer)
+    })
+    .await??;
+
+    let dead_letters: Vec<DeadLetter> = dead_letters
         .into_iter()
-        .filter(|(_, (in_state, in_cluster))| !(*in_state && *in_cluster))
+        .filter(|d| {
+            positions.is_empty() || positions.contains(&format!("{}:{}", d.partition, d.offset))
+        })
         .collect();
 
-    match output {
-        OutputFormat::Table => output_table_diff(report),
-        OutputFormat::Json => todo!("not implemented"),
-        OutputFormat::Csv => output_csv_diff(report),
-    };
+    if dead_letters.is_empty() {
+        bail!("No one dead letter was found")
+    }
+
+    for dead_letter in dead_letters {
+        if dead_letter.source_topic.is_empty() {
+            error!(
+                partition = dead_letter.partition,
+                offset = dead_letter.offset,
+                "dead letter without source topic, skipping"
+            );
+            continue;
+        }
+
+        if dry_run {
+            info!(
+                "event to re-drive: {}:{} {} to {}",
+                dead_letter.partition,
+                dead_letter.offset,
+                dead_letter.key,
+                dead_letter.source_topic
+            )
+        } else {
+            producer.redrive(&dead_letter).await?;
+            info!(
+                partition = dead_letter.partition,
+                offset = dead_letter.offset,
+                topic = dead_letter.source_topic,
+                "dead letter re-driven"
+            );
+        }
+    }
 
     Ok(())
 }
@@ -1015,6 +1720,344 @@ fn output_table_usage(report: Vec<UsageReport>, cluster_id: &str) {
     println!("{table}");
 }
 
+fn output_table_audit(events: Vec<AuditEvent>) {
+    let mut table = Table::new();
+    table.set_header(vec![
+        "occurred_at",
+        "event_type",
+        "project_id",
+        "resource_id",
+        "actor",
+        "id",
+    ]);
+
+    for e in events.iter() {
+        table.add_row(vec![
+            &e.occurred_at.to_rfc3339(),
+            &e.event_type,
+            e.project_id.as_deref().unwrap_or("-"),
+            e.resource_id.as_deref().unwrap_or("-"),
+            e.actor.as_deref().unwrap_or("-"),
+            &e.id,
+        ]);
+    }
+
+    println!("{table}");
+}
+
+fn output_json_audit(events: Vec<AuditEvent>) {
+    let mut json = vec![];
+
+    for e in events {
+        json.push(json!({
+            "id": e.id,
+            "event_type": e.event_type,
+            "project_id": e.project_id,
+            "resource_id": e.resource_id,
+            "actor": e.actor,
+            "occurred_at": e.occurred_at,
+            "payload": serde_json::from_str::<serde_json::Value>(&e.payload).unwrap_or_default(),
+        }));
+    }
+
+    println!("{}", serde_json::to_string_pretty(&json).unwrap());
+}
+
+fn output_table_search(results: Vec<SearchResult>) {
+    let mut table = Table::new();
+    table.set_header(vec![
+        "namespace",
+        "resource",
+        "kind",
+        "status",
+        "created_at",
+        "project_id",
+        "resource_id",
+    ]);
+
+    for r in results.iter() {
+        table.add_row(vec![
+            &r.project_namespace,
+            &r.resource_name,
+            &r.kind,
+            &r.status,
+            &r.created_at.to_rfc3339(),
+            &r.project_id,
+            &r.resource_id,
+        ]);
+    }
+
+    println!("{table}");
+}
+
+fn output_json_search(results: Vec<SearchResult>) {
+    let mut json = vec![];
+
+    for r in results {
+        json.push(json!({
+            "project": {
+                "id": r.project_id,
+                "namespace": r.project_namespace,
+                "name": r.project_name,
+                "owner": r.project_owner,
+            },
+            "resource": {
+                "id": r.resource_id,
+                "name": r.resource_name,
+                "kind": r.kind,
+                "status": r.status,
+                "created_at": r.created_at,
+                "spec": serde_json::from_str::<serde_json::Value>(&r.spec).unwrap_or_default(),
+            },
+        }));
+    }
+
+    println!("{}", serde_json::to_string_pretty(&json).unwrap());
+}
+
+fn output_table_quota(quota: ProjectQuota) {
+    let mut table = Table::new();
+    table.set_header(vec!["limit", "max"]);
+
+    let mut resources: Vec<_> = quota.resources.iter().collect();
+    resources.sort();
+    for (kind, max) in resources {
+        table.add_row(vec![kind, &max.to_string()]);
+    }
+
+    let unlimited = String::from("-");
+    let max_tier = quota.max_tier.map(|t| t.to_string());
+    let workers = quota.workers.map(|w| w.to_string());
+    table.add_row(vec![
+        "throughputTier",
+        max_tier.as_ref().unwrap_or(&unlimited),
+    ]);
+    table.add_row(vec!["workers", workers.as_ref().unwrap_or(&unlimited)]);
+
+    println!("{table}");
+}
+
+fn output_json_quota(quota: ProjectQuota) {
+    let json = json!({
+        "project_id": quota.project_id,
+        "resources": quota.resources,
+        "max_tier": quota.max_tier,
+        "workers": quota.workers,
+    });
+
+    println!("{}", serde_json::to_string_pretty(&json).unwrap());
+}
+
+fn output_table_invoice(invoices: Vec<Invoice>) {
+    let mut table = Table::new();
+    table.set_header(vec![
+        "project",
+        "period",
+        "stripe_id",
+        "amount",
+        "invoice_item",
+    ]);
+
+    for i in invoices.iter() {
+        table.add_row(vec![
+            &i.project_id,
+            &i.period,
+            &i.billing_provider_id,
+            &format!("${:.2}", (i.amount as f64) / 100.),
+            i.invoice_item_id.as_deref().unwrap_or("-"),
+        ]);
+    }
+
+    println!("{table}");
+}
+
+fn output_json_invoice(invoices: Vec<Invoice>) {
+    let json = invoices
+        .iter()
+        .map(|i| {
+            json!({
+                "project_id": i.project_id,
+                "period": i.period,
+                "billing_provider": i.billing_provider,
+                "billing_provider_id": i.billing_provider_id,
+                "amount": i.amount,
+                "invoice_item_id": i.invoice_item_id,
+                "created_at": i.created_at,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    println!("{}", serde_json::to_string_pretty(&json).unwrap());
+}
+
+fn output_csv_invoice(invoices: Vec<Invoice>, period: &str) {
+    let path = format!("invoices.{period}.csv");
+    let result = csv::Writer::from_path(&path);
+    if let Err(error) = result {
+        error!(?error);
+        return;
+    }
+
+    let mut wtr = result.unwrap();
+
+    let result = wtr.write_record([
+        "",
+        "project",
+        "period",
+        "stripe_id",
+        "amount",
+        "invoice_item",
+    ]);
+    if let Err(error) = result {
+        error!(?error);
+        return;
+    }
+
+    for (i, invoice) in invoices.iter().enumerate() {
+        let result = wtr.write_record([
+            &(i + 1).to_string(),
+            &invoice.project_id,
+            &invoice.period,
+            &invoice.billing_provider_id,
+            &format!("${:.2}", (invoice.amount as f64) / 100.),
+            invoice.invoice_item_id.as_deref().unwrap_or("-"),
+        ]);
+        if let Err(error) = result {
+            error!(?error);
+            return;
+        }
+    }
+
+    let result = wtr.flush();
+    if let Err(error) = result {
+        error!(?error);
+        return;
+    }
+
+    info!("File {} created", path)
+}
+
+fn output_table_checksum(checksum: CacheChecksum) {
+    let mut table = Table::new();
+    table.set_header(vec!["table", "rows", "checksum"]);
+
+    for c in checksum.tables.iter() {
+        table.add_row(vec![&c.name, &c.rows.to_string(), &c.checksum]);
+    }
+    table.add_row(vec!["", "", &checksum.checksum]);
+
+    println!("{table}");
+}
+
+fn output_json_checksum(checksum: CacheChecksum) {
+    let tables: Vec<_> = checksum
+        .tables
+        .iter()
+        .map(|c| {
+            json!({
+                "table": c.name,
+                "rows": c.rows,
+                "checksum": c.checksum,
+            })
+        })
+        .collect();
+
+    let json = json!({
+        "checksum": checksum.checksum,
+        "tables": tables,
+    });
+
+    println!("{}", serde_json::to_string_pretty(&json).unwrap());
+}
+
+fn output_table_migrations(migrations: Vec<MigrationStatus>) {
+    let mut table = Table::new();
+    table.set_header(vec!["version", "description", "status"]);
+
+    for m in migrations.iter() {
+        let status = match (m.known, m.applied) {
+            (false, _) => "unknown",
+            (true, true) => "applied",
+            (true, false) => "pending",
+        };
+        table.add_row(vec![&m.version.to_string(), &m.description, status]);
+    }
+
+    println!("{table}");
+}
+
+fn output_json_migrations(migrations: Vec<MigrationStatus>) {
+    let json: Vec<_> = migrations
+        .iter()
+        .map(|m| {
+            json!({
+                "version": m.version,
+                "description": m.description,
+                "known": m.known,
+                "applied": m.applied,
+            })
+        })
+        .collect();
+
+    println!("{}", serde_json::to_string_pretty(&json).unwrap());
+}
+
+fn output_table_dead_letters(dead_letters: Vec<DeadLetter>) {
+    let mut table = Table::new();
+    table.set_header(vec![
+        "position",
+        "key",
+        "event_id",
+        "source",
+        "consumer",
+        "attempts",
+        "error_kind",
+        "error",
+        "failed_at",
+    ]);
+
+    for d in dead_letters.iter() {
+        table.add_row(vec![
+            &format!("{}:{}", d.partition, d.offset),
+            &d.key,
+            d.event_id.as_deref().unwrap_or("-"),
+            &d.source_topic,
+            &d.consumer,
+            &d.attempts.to_string(),
+            &d.error_kind,
+            &d.error,
+            &d.failed_at.map(|f| f.to_rfc3339()).unwrap_or_default(),
+        ]);
+    }
+
+    println!("{table}");
+}
+
+fn output_json_dead_letters(dead_letters: Vec<DeadLetter>) {
+    let mut json = vec![];
+
+    for d in dead_letters {
+        json.push(json!({
+            "position": format!("{}:{}", d.partition, d.offset),
+            "key": d.key,
+            "event_id": d.event_id,
+            "source_topic": d.source_topic,
+            "consumer": d.consumer,
+            "attempts": d.attempts,
+            "error_kind": d.error_kind,
+            "error": d.error,
+            "failed_at": d.failed_at,
+            "encoding": d.encoding.map(|e| e.to_string()),
+            "payload": match d.encoding {
+                Some(Encoding::Protobuf) => BASE64_STANDARD_NO_PAD.encode(&d.payload),
+                _ => String::from_utf8_lossy(&d.payload).to_string(),
+            },
+        }))
+    }
+
+    println!("{}", serde_json::to_string_pretty(&json).unwrap());
+}
+
 fn output_table_project(projects: Vec<ProjectTable>) {
     let mut table = Table::new();
     table.set_header(vec![
@@ -1266,6 +2309,8 @@ struct ProjectTable {
 #[derive(Debug)]
 pub struct BackofficeConfig {
     pub db_path: String,
+    /// Billing ledger of `invoice`, out of the cache since a rebuild replaces the cache file.
+    pub ledger_path: String,
     pub crds_path: PathBuf,
 
     pub auth_url: String,
@@ -1286,5 +2331,9 @@ pub struct BackofficeConfig {
     pub invite_ttl_min: Option<u64>,
 
     pub topic_events: String,
+    pub bus: Bus,
+    /// Only required by the dead-letter commands.
+    pub topic_dead_letter: Option<String>,
     pub kafka_producer: HashMap<String, String>,
+    pub kafka_consumer: HashMap<String, String>,
 }
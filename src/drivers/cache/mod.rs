@@ -1,31 +1,132 @@
-use anyhow::{bail, Result};
-use rdkafka::{
-    consumer::{CommitMode, Consumer, StreamConsumer},
-    error::KafkaError,
-    ClientConfig, Message,
+use anyhow::Result;
+use chrono::Utc;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
 };
-use std::{borrow::Borrow, collections::HashMap, path::Path, sync::Arc};
+use tokio::time::{interval_at, Instant, MissedTickBehavior};
 use tracing::{error, info, warn};
 
 use crate::{
-    domain::{event::Event, notify::NotifyDriven, project, resource, usage},
+    domain::{
+        audit,
+        error::Error,
+        event::{Event, EventEnvelope},
+        notify::NotifyDriven,
+        organization::{self, cache::OrganizationDrivenCache},
+        project::{self, cache::ProjectDrivenCache},
+        resource::{self, cache::ResourceDrivenCache},
+        snapshot::{
+            self, cache::SnapshotDrivenCache, ProjectSnapshot, SnapshotPosition, Snapshots,
+        },
+        usage::{self, cache::UsageDrivenCache},
+    },
     driven::{
         auth0::Auth0DrivenImpl,
+        bus::Bus,
         cache::{
-            project::SqliteProjectDrivenCache, resource::SqliteResourceDrivenCache,
-            usage::SqliteUsageDrivenCache, SqliteCache,
+            audit::SqliteAuditDrivenCache,
+            ledger::{EventPosition, SqliteEventLedger, PROCESSED_EVENT_RETENTION},
+            organization::SqliteOrganizationDrivenCache,
+            postgres::{
+                organization::PostgresOrganizationDrivenCache, project::PostgresProjectDrivenCache,
+                resource::PostgresResourceDrivenCache, usage::PostgresUsageDrivenCache,
+                PostgresCache,
+            },
+            project::SqliteProjectDrivenCache,
+            resource::SqliteResourceDrivenCache,
+            snapshot::SqliteSnapshotDrivenCache,
+            usage::SqliteUsageDrivenCache,
+            SqliteCache,
         },
+        kafka::dead_letter::{DeadLetterConfig, DeadLetterProducer},
         slack::SlackNotifyDrivenImpl,
     },
+    drivers::retry::{RetryExhausted, RetryPolicy},
 };
 
+const LEDGER_PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Refuses to start against a cache migrated by a newer release, e.g. after a rollback. A missing
+/// sqlite file is left alone, it is created or restored when the consumer starts.
+pub async fn check_schema(db_path: &str, postgres_url: Option<&str>) -> Result<()> {
+    if Path::new(db_path).exists() {
+        let sqlite_cache = SqliteCache::new(Path::new(db_path)).await?;
+        let result = sqlite_cache.check_schema().await;
+        sqlite_cache.close().await;
+        result?;
+    }
+
+    if let Some(url) = postgres_url {
+        let postgres_cache = PostgresCache::new(url).await?;
+        let result = postgres_cache.check_schema().await;
+        postgres_cache.close().await;
+        result?;
+    }
+
+    Ok(())
+}
+
 pub async fn subscribe(config: CacheConfig) -> Result<()> {
+    if config.postgres_url.is_some() && config.snapshot.is_some() {
+        anyhow::bail!("the snapshots are only supported by the sqlite cache");
+    }
+
+    if config.snapshot.is_some() && !Path::new(&config.db_path).exists() {
+        info!("Cache not found, restoring it from the snapshots");
+        build(&config, true).await?;
+    }
+
     let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
     sqlite_cache.migrate().await?;
 
-    let project_cache = Arc::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
-    let resource_cache = Arc::new(SqliteResourceDrivenCache::new(sqlite_cache.clone()));
-    let usage_cache = Arc::new(SqliteUsageDrivenCache::new(sqlite_cache.clone()));
+    // The projections can live in postgres, shared with the other replicas, while the audit log
+    // and the ledger of applied events stay in the local sqlite file.
+    let postgres_cache = match &config.postgres_url {
+        Some(url) => {
+            let postgres_cache = Arc::new(PostgresCache::new(url).await?);
+            postgres_cache.migrate().await?;
+            Some(postgres_cache)
+        }
+        None => None,
+    };
+    let (project_cache, resource_cache, usage_cache): (
+        Arc<dyn ProjectDrivenCache>,
+        Arc<dyn ResourceDrivenCache>,
+        Arc<dyn UsageDrivenCache>,
+    ) = match &postgres_cache {
+        Some(postgres_cache) => (
+            Arc::new(PostgresProjectDrivenCache::new(postgres_cache.clone())),
+            Arc::new(PostgresResourceDrivenCache::new(postgres_cache.clone())),
+            Arc::new(PostgresUsageDrivenCache::new(postgres_cache.clone())),
+        ),
+        None => (
+            Arc::new(SqliteProjectDrivenCache::new(sqlite_cache.clone())),
+            Arc::new(SqliteResourceDrivenCache::new(sqlite_cache.clone())),
+            Arc::new(SqliteUsageDrivenCache::new(sqlite_cache.clone())),
+        ),
+    };
+    let organization_cache: Arc<dyn OrganizationDrivenCache> = match &postgres_cache {
+        Some(postgres_cache) => {
+            Arc::new(PostgresOrganizationDrivenCache::new(postgres_cache.clone()))
+        }
+        None => Arc::new(SqliteOrganizationDrivenCache::new(sqlite_cache.clone())),
+    };
+    let audit_cache = Arc::new(SqliteAuditDrivenCache::new(sqlite_cache.clone()));
+    let ledger = SqliteEventLedger::new(sqlite_cache.clone());
+
+    let dead_letter = match &config.dead_letter {
+        Some(dead_letter) => Some(DeadLetterProducer::new("cache", dead_letter)?),
+        None => None,
+    };
+
+    // Offsets already applied to this cache, the consumer is moved past them the first time a
+    // partition delivers an older message, e.g. when the consumer group offsets were reset.
+    let mut offsets = ledger.find_offsets().await?;
+    let mut resumed = HashSet::new();
 
     let mut slack_notify_driven = None;
     let mut auth0_driven = None;
@@ -44,78 +145,179 @@ pub async fn subscribe(config: CacheConfig) -> Result<()> {
         ));
     }
 
-    let mut client_config = ClientConfig::new();
-    for (k, v) in config.kafka.iter() {
-        client_config.set(k, v);
-    }
+    let consumer = config.bus.subscribe("cache", &config.topics)?;
 
-    let consumer: StreamConsumer = client_config.create()?;
-    let topic: Vec<&str> = config.topics.iter().map(String::as_str).collect();
-    consumer.subscribe(&topic)?;
+    // Snapshots are taken between two messages, so they always match the stored offsets.
+    let mut snapshot_publisher = match &config.snapshot {
+        Some(CacheSnapshotConfig {
+            topic,
+            interval: Some(period),
+            usage_topic,
+        }) => {
+            let mut ticker = interval_at(Instant::now() + *period, *period);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            let snapshot_cache: Arc<dyn SnapshotDrivenCache> =
+                Arc::new(SqliteSnapshotDrivenCache::new(sqlite_cache.clone()));
+            Some((
+                ticker,
+                snapshot_cache,
+                config.bus.snapshot_bridge(topic)?,
+                usage_topic,
+            ))
+        }
+        _ => None,
+    };
 
     info!("Cache subscribe running");
+    let mut pruned_at: Option<Instant> = None;
     loop {
-        let result = consumer.recv().await;
-        if let Err(error) = result {
-            return match error {
-                KafkaError::PartitionEOF(_) => Ok(()),
-                _ => bail!(error),
-            };
+        if pruned_at.is_none_or(|at| at.elapsed() >= LEDGER_PRUNE_INTERVAL) {
+            let pruned = ledger
+                .prune(&(Utc::now() - PROCESSED_EVENT_RETENTION))
+                .await?;
+            if pruned > 0 {
+                info!(pruned, "Events past the retention pruned from the ledger");
+            }
+            pruned_at = Some(Instant::now());
         }
 
-        let message = result.unwrap();
-
-        info!("Consuming from kafka, current offset: {}", message.offset());
-        match message.borrow().try_into() {
-            Ok(event) => {
-                let event_application = match &event {
-                    Event::ProjectCreated(evt) => {
-                        project::cache::create(project_cache.clone(), evt.clone()).await
-                    }
-                    Event::ProjectUpdated(evt) => {
-                        project::cache::update(project_cache.clone(), evt.clone()).await
-                    }
-                    Event::ProjectDeleted(evt) => {
-                        project::cache::delete(project_cache.clone(), evt.clone()).await
-                    }
-                    Event::ProjectOwnerChanged(evt) => {
-                        project::cache::change_owner(project_cache.clone(), evt.clone()).await
-                    }
-                    Event::ProjectSecretCreated(evt) => {
-                        project::cache::create_secret(project_cache.clone(), evt.clone()).await
+        let message = match &mut snapshot_publisher {
+            Some((ticker, snapshot_cache, bridge, usage_topic)) => tokio::select! {
+                message = consumer.recv() => message?,
+                _ = ticker.tick() => {
+                    let positions = offsets
+                        .iter()
+                        .filter(|((topic, _), _)| usage_topic.as_ref() != Some(topic))
+                        .map(|((topic, partition), offset)| SnapshotPosition {
+                            topic: topic.clone(),
+                            partition: *partition,
+                            offset: *offset,
+                        })
+                        .collect();
+                    if let Err(error) =
+                        snapshot::publish(snapshot_cache.clone(), bridge.clone(), positions).await
+                    {
+                        error!(?error, "fail to publish the project snapshots");
                     }
-                    Event::ProjectSecretDeleted(evt) => {
-                        project::cache::delete_secret(project_cache.clone(), evt.clone()).await
+                    continue;
+                }
+            },
+            None => consumer.recv().await?,
+        };
+        let Some(message) = message else {
+            return Ok(());
+        };
+
+        let position = EventPosition::from(&message);
+        let partition_key = (position.topic.clone(), position.partition);
+
+        if let Some(stored) = offsets.get(&partition_key) {
+            if position.offset <= *stored {
+                if resumed.insert(partition_key.clone()) {
+                    info!(
+                        topic = position.topic,
+                        partition = position.partition,
+                        offset = stored + 1,
+                        "Resuming from cache stored offset"
+                    );
+                    consumer.seek(&position.topic, position.partition, stored + 1)?;
+                }
+                continue;
+            }
+        }
+
+        info!("Consuming event, current offset: {}", message.offset);
+        match TryInto::<EventEnvelope>::try_into(&message) {
+            Ok(envelope) => {
+                let event_id = envelope
+                    .event_id
+                    .clone()
+                    .unwrap_or_else(|| position.event_id());
+
+                // The audit, the ledger and the projections in sqlite are written in one
+                // transaction, so a crash never leaves an event applied without being recorded.
+                sqlite_cache.begin().await?;
+
+                if ledger.is_processed(&event_id).await? {
+                    info!(event_id, "Event already applied, skipping");
+                    ledger.skip(&position).await?;
+                    sqlite_cache.commit().await?;
+                    if let Some(postgres_cache) = &postgres_cache {
+                        postgres_cache.record_offset(&position).await?;
                     }
-                    Event::ProjectUserInviteCreated(evt) => {
-                        project::cache::create_user_invite(project_cache.clone(), evt.clone()).await
+                    offsets.insert(partition_key, position.offset);
+                    consumer.commit(&message)?;
+                    continue;
+                }
+
+                let event = &envelope.event;
+
+                // Every replica consumes the event, the projections in postgres are only written
+                // by the one claiming it, in a transaction committed before the sqlite one. A
+                // crash in between applies the event to sqlite only when it's delivered again.
+                let claimed = match &postgres_cache {
+                    Some(postgres_cache) => {
+                        postgres_cache.begin().await?;
+                        postgres_cache.claim(&event_id, &event.key()).await?
                     }
-                    Event::ProjectUserInviteAccepted(evt) => {
-                        project::cache::create_user_invite_acceptance(
-                            project_cache.clone(),
-                            evt.clone(),
-                        )
+                    None => true,
+                };
+
+                let event_application = if claimed {
+                    config
+                        .retry
+                        .run(|| {
+                            apply_attempt(
+                                sqlite_cache.clone(),
+                                postgres_cache.clone(),
+                                event,
+                                project_cache.clone(),
+                                organization_cache.clone(),
+                                resource_cache.clone(),
+                                usage_cache.clone(),
+                            )
+                        })
                         .await
+                } else {
+                    info!(event_id, "Event already applied by another replica");
+                    Ok(())
+                };
+
+                match event_application {
+                    Ok(_) => {
+                        audit::cache::create(audit_cache.clone(), &event_id, &envelope).await?;
+                        ledger.record(&event_id, &event.key(), &position).await?;
+                        if let Some(postgres_cache) = &postgres_cache {
+                            postgres_cache.commit().await?;
+                        }
+                        sqlite_cache.commit().await?;
+                        info!("Succesfully handled event {:?}", event);
                     }
-                    Event::ProjectUserInviteDeleted(evt) => {
-                        project::cache::delete_user_invite(project_cache.clone(), evt.clone()).await
-                    }
-                    Event::ProjectUserDeleted(evt) => {
-                        project::cache::delete_user(project_cache.clone(), evt.clone()).await
-                    }
-                    Event::ResourceCreated(evt) => {
-                        resource::cache::create(resource_cache.clone(), evt.clone()).await
-                    }
-                    Event::ResourceDeleted(evt) => {
-                        resource::cache::delete(resource_cache.clone(), evt.clone()).await
-                    }
-                    Event::UsageCreated(evt) => {
-                        usage::cache::create(usage_cache.clone(), evt.clone()).await
-                    }
-                    Event::ResourceUpdated(evt) => {
-                        resource::cache::update(resource_cache.clone(), evt.clone()).await
+                    Err(failure) => {
+                        // The writes of the failed attempts are dropped, the event is only
+                        // audited and recorded as dead lettered.
+                        sqlite_cache.rollback().await?;
+                        if let Some(postgres_cache) = &postgres_cache {
+                            postgres_cache.rollback().await?;
+                        }
+                        error!(
+                            error = failure.error.to_string(),
+                            attempts = failure.attempts,
+                            "Failed to handle event: {:?}",
+                            event
+                        );
+                        if let Some(dead_letter) = &dead_letter {
+                            dead_letter.send(&message, &failure).await?;
+                        }
+
+                        sqlite_cache.begin().await?;
+                        audit::cache::create(audit_cache.clone(), &event_id, &envelope).await?;
+                        ledger
+                            .dead_letter(&event_id, &event.key(), &position)
+                            .await?;
+                        sqlite_cache.commit().await?;
                     }
-                };
+                }
 
                 if let Some(notify) = &slack_notify_driven {
                     if let Err(err) = notify
@@ -131,20 +333,362 @@ pub async fn subscribe(config: CacheConfig) -> Result<()> {
                     }
                 }
 
-                match event_application {
-                    Ok(_) => info!("Succesfully handled event {:?}", event),
-                    Err(err) => error!(
-                        error = err.to_string(),
-                        "Failed to handle event: {:?}", event
-                    ),
+                if let Some(postgres_cache) = &postgres_cache {
+                    postgres_cache.record_offset(&position).await?;
+                }
+                offsets.insert(partition_key, position.offset);
+                consumer.commit(&message)?;
+            }
+            Err(error) => {
+                error!(?error, "fail to convert message to event");
+                if let Some(dead_letter) = &dead_letter {
+                    dead_letter
+                        .send(&message, &RetryExhausted { attempts: 1, error })
+                        .await?;
+                }
+                ledger.skip(&position).await?;
+                if let Some(postgres_cache) = &postgres_cache {
+                    postgres_cache.record_offset(&position).await?;
+                }
+                offsets.insert(partition_key, position.offset);
+                consumer.commit(&message)?;
+            }
+        };
+    }
+}
+
+/// Replays the topics from offset zero into a new database next to `db_path` and swaps it in
+/// once the end of every partition is reached. The consumers using the cache must be stopped,
+/// otherwise they keep the previous file open. With `from_snapshots`, the projects are restored
+/// from the snapshot topic and only the events after them are replayed.
+pub async fn rebuild(config: CacheConfig, from_snapshots: bool) -> Result<()> {
+    build(&config, from_snapshots).await
+}
+
+async fn build(config: &CacheConfig, from_snapshots: bool) -> Result<()> {
+    let db_path = PathBuf::from(&config.db_path);
+    let rebuild_path = PathBuf::from(format!("{}.rebuild", config.db_path));
+    if rebuild_path.exists() {
+        fs::remove_file(&rebuild_path)?;
+    }
+    remove_sidecars(&rebuild_path)?;
+
+    let sqlite_cache = Arc::new(SqliteCache::new(&rebuild_path).await?);
+    sqlite_cache.migrate().await?;
+
+    let project_cache = Arc::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
+    let organization_cache = Arc::new(SqliteOrganizationDrivenCache::new(sqlite_cache.clone()));
+    let resource_cache = Arc::new(SqliteResourceDrivenCache::new(sqlite_cache.clone()));
+    let usage_cache = Arc::new(SqliteUsageDrivenCache::new(sqlite_cache.clone()));
+    let audit_cache = Arc::new(SqliteAuditDrivenCache::new(sqlite_cache.clone()));
+    let ledger = SqliteEventLedger::new(sqlite_cache.clone());
+
+    let snapshots = match (&config.snapshot, from_snapshots) {
+        (Some(snapshot_config), true) => {
+            read_snapshots(&config.bus, &snapshot_config.topic).await?
+        }
+        (None, true) => anyhow::bail!("the snapshot topic is not configured"),
+        (_, false) => Snapshots::default(),
+    };
+
+    // Offset of each partition already applied by every snapshot, the replay of the partition
+    // starts after it.
+    let mut starts: HashMap<(String, i32), i64> = HashMap::new();
+    if !snapshots.is_empty() {
+        let snapshot_cache = SqliteSnapshotDrivenCache::new(sqlite_cache.clone());
+        for snapshot in snapshots.iter() {
+            snapshot_cache.restore(snapshot).await?;
+            for position in snapshot.positions.iter() {
+                // Snapshots taken before the usage topic was left out still carry its positions.
+                if config
+                    .snapshot
+                    .as_ref()
+                    .is_some_and(|s| s.usage_topic.as_ref() == Some(&position.topic))
+                {
+                    continue;
+                }
+                if let Some(offset) = snapshots.start(&position.topic, position.partition) {
+                    starts.insert((position.topic.clone(), position.partition), offset);
+                }
+            }
+        }
+
+        for ((topic, partition), offset) in starts.iter() {
+            let position = EventPosition {
+                topic: topic.clone(),
+                partition: *partition,
+                offset: *offset,
+            };
+            ledger.skip(&position).await?;
+        }
+        info!(
+            projects = snapshots.iter().count(),
+            "Projects restored from the snapshots"
+        );
+    }
+
+    let (consumer, mut ends) = config.bus.replay(&config.topics)?;
+    ends.retain(|partition_key, end| {
+        starts
+            .get(partition_key)
+            .is_none_or(|start| *end > start + 1)
+    });
+    let mut resumed = HashSet::new();
+
+    info!(partitions = ends.len(), "Cache rebuild running");
+    while !ends.is_empty() {
+        let Some(message) = consumer.recv().await? else {
+            break;
+        };
+        let position = EventPosition::from(&message);
+        let partition_key = (position.topic.clone(), position.partition);
+        if let Some(end) = ends.get(&partition_key) {
+            if position.offset + 1 >= *end {
+                ends.remove(&partition_key);
+            }
+        }
+
+        if let Some(start) = starts.get(&partition_key) {
+            if position.offset <= *start {
+                if resumed.insert(partition_key.clone()) {
+                    consumer.seek(&position.topic, position.partition, start + 1)?;
                 }
-                consumer.commit_message(&message, CommitMode::Async)?;
+                continue;
             }
+        }
+
+        let envelope: EventEnvelope = match (&message).try_into() {
+            Ok(envelope) => envelope,
             Err(error) => {
                 error!(?error, "fail to convert message to event");
-                consumer.commit_message(&message, CommitMode::Async)?;
+                ledger.skip(&position).await?;
+                continue;
+            }
+        };
+
+        if snapshots.contains(
+            &envelope.event,
+            &position.topic,
+            position.partition,
+            position.offset,
+        ) {
+            ledger.skip(&position).await?;
+            continue;
+        }
+
+        let event_id = envelope
+            .event_id
+            .clone()
+            .unwrap_or_else(|| position.event_id());
+        sqlite_cache.begin().await?;
+        if ledger.is_processed(&event_id).await? {
+            ledger.skip(&position).await?;
+            sqlite_cache.commit().await?;
+            continue;
+        }
+
+        let event = &envelope.event;
+        let result = config
+            .retry
+            .run(|| {
+                apply_attempt(
+                    sqlite_cache.clone(),
+                    None,
+                    event,
+                    project_cache.clone(),
+                    organization_cache.clone(),
+                    resource_cache.clone(),
+                    usage_cache.clone(),
+                )
+            })
+            .await;
+
+        match result {
+            Ok(_) => {
+                audit::cache::create(audit_cache.clone(), &event_id, &envelope).await?;
+                ledger.record(&event_id, &event.key(), &position).await?;
             }
+            Err(failure) => {
+                error!(
+                    error = failure.error.to_string(),
+                    "Failed to handle event: {:?}", event
+                );
+                sqlite_cache.rollback().await?;
+                sqlite_cache.begin().await?;
+                audit::cache::create(audit_cache.clone(), &event_id, &envelope).await?;
+                ledger
+                    .dead_letter(&event_id, &event.key(), &position)
+                    .await?;
+            }
+        }
+        sqlite_cache.commit().await?;
+    }
+
+    let checksum = sqlite_cache.checksum().await?;
+    sqlite_cache.checkpoint().await?;
+    sqlite_cache.close().await;
+
+    // Only the database file is moved, the sidecars left by the previous one would be read as
+    // part of the new one.
+    remove_sidecars(&rebuild_path)?;
+    remove_sidecars(&db_path)?;
+    fs::rename(&rebuild_path, &db_path)?;
+
+    info!(
+        checksum = checksum.checksum,
+        path = config.db_path,
+        "Cache rebuilt"
+    );
+
+    Ok(())
+}
+
+/// Removes the write-ahead log and shared memory files sqlite keeps next to `path`.
+fn remove_sidecars(path: &Path) -> Result<()> {
+    for suffix in ["-wal", "-shm"] {
+        let sidecar = PathBuf::from(format!("{}{suffix}", path.display()));
+        if sidecar.exists() {
+            fs::remove_file(sidecar)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the snapshot topic up to its current end, keeping the latest snapshot of each project.
+async fn read_snapshots(bus: &Bus, topic: &str) -> Result<Snapshots> {
+    let mut snapshots = Snapshots::default();
+
+    let (consumer, mut ends) = bus.replay(&[topic.to_string()])?;
+    while !ends.is_empty() {
+        let Some(message) = consumer.recv().await? else {
+            break;
+        };
+        let partition_key = (message.topic.clone(), message.partition);
+        if let Some(end) = ends.get(&partition_key) {
+            if message.offset + 1 >= *end {
+                ends.remove(&partition_key);
+            }
+        }
+
+        let Some(payload) = &message.payload else {
+            continue;
         };
+        match serde_json::from_slice::<ProjectSnapshot>(payload) {
+            Ok(snapshot) => snapshots.insert(snapshot),
+            Err(error) => error!(?error, offset = message.offset, "fail to decode snapshot"),
+        }
+    }
+
+    Ok(snapshots)
+}
+
+/// Applies the event in a savepoint of the open cache transactions, the writes of a failed
+/// attempt are rolled back so the next one starts from the state before the event.
+async fn apply_attempt(
+    sqlite_cache: Arc<SqliteCache>,
+    postgres_cache: Option<Arc<PostgresCache>>,
+    event: &Event,
+    project_cache: Arc<dyn ProjectDrivenCache>,
+    organization_cache: Arc<dyn OrganizationDrivenCache>,
+    resource_cache: Arc<dyn ResourceDrivenCache>,
+    usage_cache: Arc<dyn UsageDrivenCache>,
+) -> std::result::Result<(), Error> {
+    sqlite_cache.savepoint().await?;
+    if let Some(postgres_cache) = &postgres_cache {
+        postgres_cache.savepoint().await?;
+    }
+
+    let result = apply(
+        event,
+        project_cache,
+        organization_cache,
+        resource_cache,
+        usage_cache,
+    )
+    .await;
+
+    match result {
+        Ok(()) => {
+            if let Some(postgres_cache) = &postgres_cache {
+                postgres_cache.release_savepoint().await?;
+            }
+            sqlite_cache.release_savepoint().await
+        }
+        Err(error) => {
+            if let Some(postgres_cache) = &postgres_cache {
+                postgres_cache.rollback_to_savepoint().await?;
+            }
+            sqlite_cache.rollback_to_savepoint().await?;
+            Err(error)
+        }
+    }
+}
+
+async fn apply(
+    event: &Event,
+    project_cache: Arc<dyn ProjectDrivenCache>,
+    organization_cache: Arc<dyn OrganizationDrivenCache>,
+    resource_cache: Arc<dyn ResourceDrivenCache>,
+    usage_cache: Arc<dyn UsageDrivenCache>,
+) -> std::result::Result<(), Error> {
+    match event {
+        Event::ProjectCreated(evt) => project::cache::create(project_cache, evt.clone()).await,
+        Event::ProjectUpdated(evt) => project::cache::update(project_cache, evt.clone()).await,
+        Event::ProjectDeleted(evt) => project::cache::delete(project_cache, evt.clone()).await,
+        Event::ProjectRestored(evt) => project::cache::restore(project_cache, evt.clone()).await,
+        Event::ProjectSuspended(evt) => project::cache::suspend(project_cache, evt.clone()).await,
+        Event::ProjectResumed(evt) => project::cache::resume(project_cache, evt.clone()).await,
+        Event::ProjectOwnerChanged(evt) => {
+            project::cache::change_owner(project_cache, evt.clone()).await
+        }
+        Event::ProjectSecretCreated(evt) => {
+            project::cache::create_secret(project_cache, evt.clone()).await
+        }
+        Event::ProjectSecretDeleted(evt) => {
+            project::cache::delete_secret(project_cache, evt.clone()).await
+        }
+        Event::ProjectUserInviteCreated(evt) => {
+            project::cache::create_user_invite(project_cache, evt.clone()).await
+        }
+        Event::ProjectUserInviteAccepted(evt) => {
+            project::cache::create_user_invite_acceptance(project_cache, evt.clone()).await
+        }
+        Event::ProjectUserInviteDeleted(evt) => {
+            project::cache::delete_user_invite(project_cache, evt.clone()).await
+        }
+        Event::ProjectUserDeleted(evt) => {
+            project::cache::delete_user(project_cache, evt.clone()).await
+        }
+        Event::ResourceCreated(evt) => resource::cache::create(resource_cache, evt.clone()).await,
+        Event::ResourceDeleted(evt) => resource::cache::delete(resource_cache, evt.clone()).await,
+        Event::ResourceRestored(evt) => resource::cache::restore(resource_cache, evt.clone()).await,
+        Event::UsageCreated(evt) => usage::cache::create(usage_cache, evt.clone()).await,
+        Event::ResourceUpdated(evt) => resource::cache::update(resource_cache, evt.clone()).await,
+        Event::OrganizationCreated(evt) => {
+            organization::cache::create(organization_cache, evt.clone()).await
+        }
+        Event::OrganizationUserAdded(evt) => {
+            organization::cache::create_user(organization_cache, evt.clone()).await
+        }
+        Event::OrganizationUserDeleted(evt) => {
+            organization::cache::delete_user(organization_cache, evt.clone()).await
+        }
+        Event::ProjectOrganizationChanged(evt) => {
+            project::cache::change_organization(project_cache, evt.clone()).await
+        }
+        Event::ProjectOwnershipTransferProposed(evt) => {
+            project::cache::create_ownership_transfer(project_cache, evt.clone()).await
+        }
+        Event::ProjectOwnershipTransferAccepted(evt) => {
+            project::cache::accept_ownership_transfer(project_cache, evt.clone()).await
+        }
+        Event::ProjectOwnershipTransferCancelled(evt) => {
+            project::cache::cancel_ownership_transfer(project_cache, evt.clone()).await
+        }
+        Event::ProjectQuotaUpdated(evt) => {
+            project::cache::update_quota(project_cache, evt.clone()).await
+        }
     }
 }
 
@@ -156,9 +700,360 @@ pub struct CacheNotifyConfig {
     pub auth_audience: String,
 }
 
+/// Topic with the latest snapshot of each project. Without `interval` the snapshots are only
+/// read, to restore a missing cache.
+pub struct CacheSnapshotConfig {
+    pub topic: String,
+    pub interval: Option<Duration>,
+    /// Topic of the usage events. The usage isn't part of the snapshots, so the topic is left out
+    /// of their positions and always replayed from the start.
+    pub usage_topic: Option<String>,
+}
+
 pub struct CacheConfig {
     pub db_path: String,
+    /// Postgres database for the projections instead of the sqlite file at `db_path`.
+    pub postgres_url: Option<String>,
     pub topics: Vec<String>,
-    pub kafka: HashMap<String, String>,
+    pub bus: Bus,
     pub notify: Option<CacheNotifyConfig>,
+    pub retry: RetryPolicy,
+    pub dead_letter: Option<DeadLetterConfig>,
+    pub snapshot: Option<CacheSnapshotConfig>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::time::{sleep, timeout};
+    use uuid::Uuid;
+
+    use crate::{
+        domain::{
+            audit::cache::AuditDrivenCache,
+            auth::{Auth0Profile, Credential, MockAuth0Driven},
+            event::{ProjectCreated, ResourceCreated, UsageCreated},
+            pagination::Page,
+            project::{cache::ProjectDrivenCache, MockStripeDriven},
+            resource::cache::ResourceDrivenCache,
+            usage::cache::UsageDrivenCache,
+            DEFAULT_CATEGORY,
+        },
+        driven::{bus::memory::MemoryBus, metadata::FileMetadata},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn it_should_apply_commands_dispatched_on_the_memory_bus() {
+        let bus = Bus::Memory(MemoryBus::default());
+        let topics = vec!["events".to_string()];
+        let db_path = std::env::temp_dir().join(format!("fabric-cache-{}.db", Uuid::new_v4()));
+
+        let sqlite_cache = Arc::new(SqliteCache::new(&db_path).await.unwrap());
+        sqlite_cache.migrate().await.unwrap();
+        let project_cache: Arc<dyn ProjectDrivenCache> =
+            Arc::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
+        let resource_cache: Arc<dyn ResourceDrivenCache> =
+            Arc::new(SqliteResourceDrivenCache::new(sqlite_cache.clone()));
+
+        let subscribe = tokio::spawn(subscribe(CacheConfig {
+            db_path: db_path.display().to_string(),
+            postgres_url: None,
+            topics: topics.clone(),
+            bus: bus.clone(),
+            notify: None,
+            retry: RetryPolicy::default(),
+            dead_letter: None,
+            snapshot: None,
+        }));
+
+        let event = bus.bridge(&topics[0]).unwrap();
+        let credential = Credential::Auth0("user id".into());
+
+        let mut auth0 = MockAuth0Driven::new();
+        auth0
+            .expect_find_info()
+            .return_once(|_| Ok(vec![Auth0Profile::default()]));
+        let mut stripe = MockStripeDriven::new();
+        stripe
+            .expect_create_customer()
+            .return_once(|_, _| Ok("stripe id".into()));
+
+        let cmd = project::command::CreateCmd::new(
+            credential.clone(),
+            "New Project".into(),
+            Some("cluster-a".into()),
+        );
+        let project_id = cmd.id.clone();
+        project::command::create(
+            project_cache.clone(),
+            event.clone(),
+            Arc::new(auth0),
+            Arc::new(stripe),
+            cmd,
+        )
+        .await
+        .unwrap();
+
+        let project = timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(Some(project)) = project_cache.find_by_id(&project_id).await {
+                    break project;
+                }
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        let metadata = Arc::new(FileMetadata::new(Path::new("bootstrap/rpc/crds")).unwrap());
+        let cmd = resource::command::CreateCmd::new(
+            credential.clone(),
+            project.id.clone(),
+            "CardanoNodePort".into(),
+            "{\"network\":\"mainnet\",\"throughputTier\":\"0\"}".into(),
+            None,
+        )
+        .unwrap();
+        let resource_id = cmd.id.clone();
+        resource::command::create(
+            resource_cache.clone(),
+            project_cache.clone(),
+            metadata,
+            event.clone(),
+            cmd,
+        )
+        .await
+        .unwrap();
+
+        let resource = timeout(Duration::from_secs(5), async {
+            loop {
+                if let Ok(Some(resource)) = resource_cache.find_by_id(&resource_id).await {
+                    break resource;
+                }
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert!(resource.project_id == project.id);
+        assert!(resource.cluster_id.as_deref() == Some("cluster-a"));
+
+        let cmd = project::command::UpdateCmd::new(credential, project.id, "Renamed".into());
+        let project = project::command::update(project_cache.clone(), event, cmd)
+            .await
+            .unwrap();
+        assert!(project.name == "Renamed");
+
+        subscribe.abort();
+        sqlite_cache.close().await;
+        fs::remove_file(db_path).ok();
+    }
+
+    #[tokio::test]
+    async fn it_should_restore_from_snapshots_and_replay_the_tail() {
+        let bus = Bus::Memory(MemoryBus::default());
+        let config = |db_path: &Path| CacheConfig {
+            db_path: db_path.display().to_string(),
+            postgres_url: None,
+            topics: vec!["events".into()],
+            bus: bus.clone(),
+            notify: None,
+            retry: RetryPolicy::default(),
+            dead_letter: None,
+            snapshot: Some(CacheSnapshotConfig {
+                topic: "snapshots".into(),
+                interval: None,
+                usage_topic: None,
+            }),
+        };
+        let events = bus.bridge("events").unwrap();
+
+        let project = ProjectCreated::default();
+        let resource = ResourceCreated {
+            project_id: project.id.clone(),
+            ..Default::default()
+        };
+        events.dispatch(project.clone().into()).await.unwrap();
+        events.dispatch(resource.into()).await.unwrap();
+
+        let source_path = std::env::temp_dir().join(format!("fabric-cache-{}.db", Uuid::new_v4()));
+        rebuild(config(&source_path), false).await.unwrap();
+
+        let source = Arc::new(SqliteCache::new(&source_path).await.unwrap());
+        let positions = SqliteEventLedger::new(source.clone())
+            .find_offsets()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|((topic, partition), offset)| SnapshotPosition {
+                topic,
+                partition,
+                offset,
+            })
+            .collect();
+        snapshot::publish(
+            Arc::new(SqliteSnapshotDrivenCache::new(source.clone())),
+            bus.snapshot_bridge("snapshots").unwrap(),
+            positions,
+        )
+        .await
+        .unwrap();
+        source.close().await;
+
+        let tail = ResourceCreated {
+            project_id: project.id.clone(),
+            ..Default::default()
+        };
+        events.dispatch(tail.clone().into()).await.unwrap();
+
+        let restored_path =
+            std::env::temp_dir().join(format!("fabric-cache-{}.db", Uuid::new_v4()));
+        rebuild(config(&restored_path), true).await.unwrap();
+
+        let restored = Arc::new(SqliteCache::new(&restored_path).await.unwrap());
+        let resource_cache = SqliteResourceDrivenCache::new(restored.clone());
+        let resources = resource_cache
+            .find(&project.id, &Page::default(), DEFAULT_CATEGORY)
+            .await
+            .unwrap();
+        assert!(resources.len() == 2);
+
+        // Only the event after the snapshot is applied, and so audited.
+        let audit = SqliteAuditDrivenCache::new(restored.clone())
+            .find(&Default::default(), &1, &12)
+            .await
+            .unwrap();
+        assert!(audit.len() == 1);
+        assert!(audit[0].resource_id == Some(tail.id));
+
+        restored.close().await;
+        fs::remove_file(source_path).ok();
+        fs::remove_file(restored_path).ok();
+    }
+
+    #[tokio::test]
+    async fn it_should_restore_from_snapshots_and_replay_the_usage() {
+        let bus = Bus::Memory(MemoryBus::default());
+        let config = |db_path: &Path| CacheConfig {
+            db_path: db_path.display().to_string(),
+            postgres_url: None,
+            topics: vec!["events".into(), "usage".into()],
+            bus: bus.clone(),
+            notify: None,
+            retry: RetryPolicy::default(),
+            dead_letter: None,
+            snapshot: Some(CacheSnapshotConfig {
+                topic: "snapshots".into(),
+                interval: None,
+                usage_topic: Some("usage".into()),
+            }),
+        };
+        let events = bus.bridge("events").unwrap();
+
+        let project = ProjectCreated::default();
+        let resource = ResourceCreated {
+            project_id: project.id.clone(),
+            ..Default::default()
+        };
+        let mut usage = UsageCreated {
+            project_id: project.id.clone(),
+            ..Default::default()
+        };
+        usage.usages[0].resource_id = resource.id.clone();
+        events.dispatch(project.clone().into()).await.unwrap();
+        events.dispatch(resource.into()).await.unwrap();
+        bus.bridge("usage")
+            .unwrap()
+            .dispatch(usage.into())
+            .await
+            .unwrap();
+
+        let source_path = std::env::temp_dir().join(format!("fabric-cache-{}.db", Uuid::new_v4()));
+        rebuild(config(&source_path), false).await.unwrap();
+
+        // The positions of the usage topic are kept, as in the snapshots taken before it was left
+        // out of them.
+        let source = Arc::new(SqliteCache::new(&source_path).await.unwrap());
+        let positions = SqliteEventLedger::new(source.clone())
+            .find_offsets()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|((topic, partition), offset)| SnapshotPosition {
+                topic,
+                partition,
+                offset,
+            })
+            .collect();
+        snapshot::publish(
+            Arc::new(SqliteSnapshotDrivenCache::new(source.clone())),
+            bus.snapshot_bridge("snapshots").unwrap(),
+            positions,
+        )
+        .await
+        .unwrap();
+        source.close().await;
+
+        let restored_path =
+            std::env::temp_dir().join(format!("fabric-cache-{}.db", Uuid::new_v4()));
+        rebuild(config(&restored_path), true).await.unwrap();
+
+        let restored = Arc::new(SqliteCache::new(&restored_path).await.unwrap());
+        let report = SqliteUsageDrivenCache::new(restored.clone())
+            .find_report(&project.id, &1, &12, None)
+            .await
+            .unwrap();
+        assert!(report.len() == 1);
+        assert!(report[0].units == 120);
+
+        restored.close().await;
+        fs::remove_file(source_path).ok();
+        fs::remove_file(restored_path).ok();
+    }
+
+    #[tokio::test]
+    async fn it_should_replace_the_sidecars_of_the_previous_cache_on_rebuild() {
+        let bus = Bus::Memory(MemoryBus::default());
+        let events = bus.bridge("events").unwrap();
+        let project = ProjectCreated::default();
+        events.dispatch(project.clone().into()).await.unwrap();
+
+        let db_path = std::env::temp_dir().join(format!("fabric-cache-{}.db", Uuid::new_v4()));
+        let sidecar = |suffix: &str| PathBuf::from(format!("{}{suffix}", db_path.display()));
+        fs::write(&db_path, b"").unwrap();
+        fs::write(sidecar("-wal"), b"stale").unwrap();
+        fs::write(sidecar("-shm"), b"stale").unwrap();
+
+        rebuild(
+            CacheConfig {
+                db_path: db_path.display().to_string(),
+                postgres_url: None,
+                topics: vec!["events".into()],
+                bus: bus.clone(),
+                notify: None,
+                retry: RetryPolicy::default(),
+                dead_letter: None,
+                snapshot: None,
+            },
+            false,
+        )
+        .await
+        .unwrap();
+        assert!(!sidecar("-wal").exists());
+        assert!(!sidecar("-shm").exists());
+
+        let sqlite_cache = Arc::new(SqliteCache::new(&db_path).await.unwrap());
+        let project_cache = SqliteProjectDrivenCache::new(sqlite_cache.clone());
+        assert!(project_cache
+            .find_by_id(&project.id)
+            .await
+            .unwrap()
+            .is_some());
+
+        sqlite_cache.close().await;
+        fs::remove_file(db_path).ok();
+    }
 }
@@ -0,0 +1,174 @@
+use std::sync::Arc;
+use tonic::{async_trait, Status};
+
+use crate::{
+    domain::{
+        audit::{self, cache::AuditDrivenCache, AuditEvent},
+        auth::Credential,
+        project::cache::ProjectDrivenCache,
+    },
+    driven::prometheus::metrics::MetricsDriven,
+};
+
+use super::{handle_error_metric, ops as proto, parse_datetime};
+
+pub struct AuditServiceImpl {
+    project_cache: Arc<dyn ProjectDrivenCache>,
+    audit_cache: Arc<dyn AuditDrivenCache>,
+    metrics: Arc<MetricsDriven>,
+}
+
+impl AuditServiceImpl {
+    pub fn new(
+        project_cache: Arc<dyn ProjectDrivenCache>,
+        audit_cache: Arc<dyn AuditDrivenCache>,
+        metrics: Arc<MetricsDriven>,
+    ) -> Self {
+        Self {
+            project_cache,
+            audit_cache,
+            metrics,
+        }
+    }
+}
+
+#[async_trait]
+impl proto::audit_service_server::AuditService for AuditServiceImpl {
+    async fn fetch_audit_events(
+        &self,
+        request: tonic::Request<proto::FetchAuditEventsRequest>,
+    ) -> Result<tonic::Response<proto::FetchAuditEventsResponse>, tonic::Status> {
+        let credential = match request.extensions().get::<Credential>() {
+            Some(credential) => credential.clone(),
+            None => return Err(Status::unauthenticated("invalid credential")),
+        };
+
+        let req = request.into_inner();
+
+        let cmd = audit::command::FetchCmd::new(
+            credential,
+            req.project_id,
+            req.resource_id,
+            req.actor,
+            req.event_type,
+            parse_datetime(req.from, "from")?,
+            parse_datetime(req.to, "to")?,
+            req.page,
+            req.page_size,
+        )
+        .inspect_err(|err| handle_error_metric(self.metrics.clone(), "audit", err))?;
+
+        let events =
+            audit::command::fetch(self.project_cache.clone(), self.audit_cache.clone(), cmd)
+                .await
+                .inspect_err(|err| handle_error_metric(self.metrics.clone(), "audit", err))?;
+
+        let records = events.into_iter().map(|v| v.into()).collect();
+        let message = proto::FetchAuditEventsResponse { records };
+
+        Ok(tonic::Response::new(message))
+    }
+}
+
+impl From<AuditEvent> for proto::AuditEvent {
+    fn from(value: AuditEvent) -> Self {
+        Self {
+            id: value.id,
+            event_type: value.event_type,
+            project_id: value.project_id,
+            resource_id: value.resource_id,
+            actor: value.actor,
+            payload: value.payload,
+            occurred_at: value.occurred_at.to_rfc3339(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use crate::domain::{
+        audit::cache::MockAuditDrivenCache,
+        project::{cache::MockProjectDrivenCache, ProjectUser, ProjectUserRole},
+    };
+
+    use super::{proto::audit_service_server::AuditService, *};
+
+    fn service(
+        project_cache: MockProjectDrivenCache,
+        audit_cache: MockAuditDrivenCache,
+    ) -> AuditServiceImpl {
+        AuditServiceImpl::new(
+            Arc::new(project_cache),
+            Arc::new(audit_cache),
+            Arc::new(MetricsDriven::new().unwrap()),
+        )
+    }
+
+    fn request(credential: Option<Credential>) -> tonic::Request<proto::FetchAuditEventsRequest> {
+        let mut request = tonic::Request::new(proto::FetchAuditEventsRequest {
+            project_id: "project id".into(),
+            ..Default::default()
+        });
+        if let Some(credential) = credential {
+            request.extensions_mut().insert(credential);
+        }
+        request
+    }
+
+    #[tokio::test]
+    async fn it_should_fetch_audit_events() {
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+
+        let mut audit_cache = MockAuditDrivenCache::new();
+        audit_cache.expect_find().return_once(|filter, _, _| {
+            assert!(filter.project_id.as_deref() == Some("project id"));
+            Ok(vec![AuditEvent {
+                id: "event id".into(),
+                event_type: "ProjectUpdated".into(),
+                project_id: filter.project_id.clone(),
+                resource_id: None,
+                actor: None,
+                payload: "{}".into(),
+                occurred_at: Utc::now(),
+            }])
+        });
+
+        let service = service(project_cache, audit_cache);
+        let result = service
+            .fetch_audit_events(request(Some(Credential::Auth0("user id".into()))))
+            .await;
+        assert!(result.is_ok());
+        assert!(result.unwrap().into_inner().records.len() == 1);
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_fetch_audit_events_when_user_is_not_owner() {
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| {
+                Ok(Some(ProjectUser {
+                    role: ProjectUserRole::Member,
+                    ..Default::default()
+                }))
+            });
+
+        let service = service(project_cache, MockAuditDrivenCache::new());
+        let result = service
+            .fetch_audit_events(request(Some(Credential::Auth0("user id".into()))))
+            .await;
+        assert!(result.is_err_and(|status| status.code() == tonic::Code::PermissionDenied));
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_fetch_audit_events_without_credential() {
+        let service = service(MockProjectDrivenCache::new(), MockAuditDrivenCache::new());
+        let result = service.fetch_audit_events(request(None)).await;
+        assert!(result.is_err_and(|status| status.code() == tonic::Code::Unauthenticated));
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use dmtri::demeter::ops::v1alpha::key_value_service_server::KeyValueServiceServer;
 use dmtri::demeter::ops::v1alpha::logs_service_server::LogsServiceServer;
 use dmtri::demeter::ops::v1alpha::metadata_service_server::MetadataServiceServer;
@@ -6,13 +7,20 @@ use dmtri::demeter::ops::v1alpha::resource_service_server::ResourceServiceServer
 use dmtri::demeter::ops::v1alpha::signer_service_server::SignerServiceServer;
 use dmtri::demeter::ops::v1alpha::usage_service_server::UsageServiceServer;
 use middlewares::auth::AuthenticatorImpl;
-use std::collections::HashMap;
+use ops::audit_service_server::AuditServiceServer;
+use ops::ownership_service_server::OwnershipServiceServer;
+use ops::pricing_service_server::PricingServiceServer;
+use ops::quota_service_server::QuotaServiceServer;
+use ops::restore_service_server::RestoreServiceServer;
+use ops::search_service_server::SearchServiceServer;
+use prost::Message;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
 use std::{path::Path, sync::Arc};
 use tonic::{
+    metadata::{MetadataMap, MetadataValue},
     transport::{Identity, Server, ServerTlsConfig},
     Status,
 };
@@ -21,14 +29,27 @@ use tracing::{error, info};
 
 use dmtri::demeter::ops::v1alpha::project_service_server::ProjectServiceServer;
 
+use crate::domain::audit::cache::AuditDrivenCache;
 use crate::domain::error::Error;
+use crate::domain::event::EventDrivenBridge;
+use crate::domain::pagination::Cursor;
+use crate::domain::project::cache::ProjectDrivenCache;
+use crate::domain::resource::cache::ResourceDrivenCache;
+use crate::domain::usage::cache::UsageDrivenCache;
 use crate::driven::auth0::Auth0DrivenImpl;
+use crate::driven::bus::Bus;
+use crate::driven::cache::audit::SqliteAuditDrivenCache;
+use crate::driven::cache::postgres::project::PostgresProjectDrivenCache;
+use crate::driven::cache::postgres::resource::PostgresResourceDrivenCache;
+use crate::driven::cache::postgres::usage::PostgresUsageDrivenCache;
+use crate::driven::cache::postgres::PostgresCache;
 use crate::driven::cache::project::SqliteProjectDrivenCache;
 use crate::driven::cache::resource::SqliteResourceDrivenCache;
+use crate::driven::cache::search::SqliteSearchDrivenCache;
 use crate::driven::cache::usage::SqliteUsageDrivenCache;
 use crate::driven::cache::SqliteCache;
-use crate::driven::kafka::KafkaProducer;
 use crate::driven::metadata::FileMetadata;
+use crate::driven::outbox::SqliteOutbox;
 use crate::driven::prometheus::metrics::MetricsDriven;
 use crate::driven::ses::SESDrivenImpl;
 use crate::driven::stripe::StripeDrivenImpl;
@@ -37,20 +58,64 @@ use crate::driven::worker::storage::keyvalue::PostgresWorkerKeyValueDrivenStorag
 use crate::driven::worker::storage::logs::PostgresWorkerLogsDrivenStorage;
 use crate::driven::worker::storage::PostgresStorage;
 
+mod audit;
 mod metadata;
 mod middlewares;
+mod ownership;
+mod pricing;
 mod project;
+mod quota;
 mod resource;
+mod restore;
+mod search;
 mod usage;
 mod worker;
 
+/// Services of fabric that aren't part of the demeter specs, generated from `proto/fabric/ops`.
+pub mod ops {
+    tonic::include_proto!("fabric.ops.v1");
+
+    pub const FILE_DESCRIPTOR_SET: &[u8] =
+        tonic::include_file_descriptor_set!("fabric_ops_descriptor");
+}
+
+const PAGE_CURSOR_KEY: &str = "page-cursor-bin";
+
 pub async fn server(config: GrpcConfig, metrics: Arc<MetricsDriven>) -> Result<()> {
     let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
-    let project_cache = Arc::new(SqliteProjectDrivenCache::new(sqlite_cache.clone()));
-    let resource_cache = Arc::new(SqliteResourceDrivenCache::new(sqlite_cache.clone()));
-    let usage_cache = Arc::new(SqliteUsageDrivenCache::new(sqlite_cache.clone()));
 
-    let event_bridge = Arc::new(KafkaProducer::new(&config.topic, &config.kafka)?);
+    let (project_cache, resource_cache, usage_cache): (
+        Arc<dyn ProjectDrivenCache>,
+        Arc<dyn ResourceDrivenCache>,
+        Arc<dyn UsageDrivenCache>,
+    ) = match &config.postgres_url {
+        Some(url) => {
+            let postgres_cache = Arc::new(PostgresCache::new(url).await?);
+            (
+                Arc::new(PostgresProjectDrivenCache::new(postgres_cache.clone())),
+                Arc::new(PostgresResourceDrivenCache::new(postgres_cache.clone())),
+                Arc::new(PostgresUsageDrivenCache::new(postgres_cache)),
+            )
+        }
+        None => (
+            Arc::new(SqliteProjectDrivenCache::new(sqlite_cache.clone())),
+            Arc::new(SqliteResourceDrivenCache::new(sqlite_cache.clone())),
+            Arc::new(SqliteUsageDrivenCache::new(sqlite_cache.clone())),
+        ),
+    };
+
+    // The audit log is always in the sqlite file, written by the cache consumer of the replica.
+    let audit_cache: Arc<dyn AuditDrivenCache> =
+        Arc::new(SqliteAuditDrivenCache::new(sqlite_cache.clone()));
+
+    let event_bridge: Arc<dyn EventDrivenBridge> = match &config.outbox_path {
+        Some(outbox_path) => {
+            let outbox = SqliteOutbox::new(Path::new(outbox_path)).await?;
+            outbox.migrate().await?;
+            Arc::new(outbox)
+        }
+        None => config.bus.bridge(&config.topic)?,
+    };
 
     let metadata = Arc::new(FileMetadata::new(&config.crds_path)?);
 
@@ -76,6 +141,7 @@ pub async fn server(config: GrpcConfig, metrics: Arc<MetricsDriven>) -> Result<(
 
     let reflection = tonic_reflection::server::Builder::configure()
         .register_encoded_file_descriptor_set(dmtri::demeter::ops::v1alpha::FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(ops::FILE_DESCRIPTOR_SET)
         .register_encoded_file_descriptor_set(protoc_wkt::google::protobuf::FILE_DESCRIPTOR_SET)
         .build_v1alpha()
         .unwrap();
@@ -92,17 +158,36 @@ pub async fn server(config: GrpcConfig, metrics: Arc<MetricsDriven>) -> Result<(
         metrics.clone(),
         config.secret.clone(),
         config.invite_ttl,
+        config.clusters.clone(),
     );
     let project_service =
         ProjectServiceServer::with_interceptor(project_inner, auth_interceptor.clone());
     let project_service = tonic_web::enable(project_service);
 
+    let ownership_inner = ownership::OwnershipServiceImpl::new(
+        project_cache.clone(),
+        event_bridge.clone(),
+        auth0.clone(),
+        stripe.clone(),
+        email.clone(),
+        metrics.clone(),
+        config.invite_ttl,
+    );
+    let ownership_service =
+        OwnershipServiceServer::with_interceptor(ownership_inner, auth_interceptor.clone());
+    let ownership_service = tonic_web::enable(ownership_service);
+
+    let quota_inner = quota::QuotaServiceImpl::new(project_cache.clone(), metrics.clone());
+    let quota_service = QuotaServiceServer::with_interceptor(quota_inner, auth_interceptor.clone());
+    let quota_service = tonic_web::enable(quota_service);
+
     let resource_inner = resource::ResourceServiceImpl::new(
         project_cache.clone(),
         resource_cache.clone(),
         event_bridge.clone(),
         metadata.clone(),
         metrics.clone(),
+        config.clusters.clone(),
     );
     let resource_service =
         ResourceServiceServer::with_interceptor(resource_inner, auth_interceptor.clone());
@@ -112,6 +197,10 @@ pub async fn server(config: GrpcConfig, metrics: Arc<MetricsDriven>) -> Result<(
     let metadata_service = MetadataServiceServer::new(metadata_inner);
     let metadata_service = tonic_web::enable(metadata_service);
 
+    let pricing_inner = pricing::PricingServiceImpl::new(metadata.clone(), metrics.clone());
+    let pricing_service = PricingServiceServer::new(pricing_inner);
+    let pricing_service = tonic_web::enable(pricing_service);
+
     let usage_inner = usage::UsageServiceImpl::new(
         project_cache.clone(),
         usage_cache.clone(),
@@ -121,6 +210,34 @@ pub async fn server(config: GrpcConfig, metrics: Arc<MetricsDriven>) -> Result<(
     let usage_service = UsageServiceServer::with_interceptor(usage_inner, auth_interceptor.clone());
     let usage_service = tonic_web::enable(usage_service);
 
+    let audit_inner =
+        audit::AuditServiceImpl::new(project_cache.clone(), audit_cache.clone(), metrics.clone());
+    let audit_service = AuditServiceServer::with_interceptor(audit_inner, auth_interceptor.clone());
+    let audit_service = tonic_web::enable(audit_service);
+
+    let restore_inner = restore::RestoreServiceImpl::new(
+        project_cache.clone(),
+        resource_cache.clone(),
+        event_bridge.clone(),
+        metrics.clone(),
+    );
+    let restore_service =
+        RestoreServiceServer::with_interceptor(restore_inner, auth_interceptor.clone());
+    let restore_service = tonic_web::enable(restore_service);
+
+    // The search indexes are only kept by the sqlite cache.
+    let search_service = match &config.postgres_url {
+        Some(_) => None,
+        None => {
+            let search_cache = Arc::new(SqliteSearchDrivenCache::new(sqlite_cache.clone()));
+            let search_inner =
+                search::SearchServiceImpl::new(search_cache, auth0.clone(), metrics.clone());
+            let search_service =
+                SearchServiceServer::with_interceptor(search_inner, auth_interceptor.clone());
+            Some(tonic_web::enable(search_service))
+        }
+    };
+
     let (worker_kv_service, worker_logs_service) = if let Some(pg_url) = config.balius_pg_url {
         let storage = Arc::new(PostgresStorage::new(&pg_url).await?);
         let kv_storage = Arc::new(PostgresWorkerKeyValueDrivenStorage::new(storage.clone()));
@@ -188,10 +305,16 @@ pub async fn server(config: GrpcConfig, metrics: Arc<MetricsDriven>) -> Result<(
 
     server
         .add_service(project_service)
+        .add_service(ownership_service)
+        .add_service(quota_service)
         .add_service(resource_service)
         .add_service(usage_service)
         .add_service(metadata_service)
+        .add_service(pricing_service)
+        .add_service(audit_service)
+        .add_service(restore_service)
         .add_service(reflection)
+        .add_optional_service(search_service)
         .add_optional_service(worker_kv_service)
         .add_optional_service(worker_logs_service)
         .add_optional_service(worker_signer_service)
@@ -209,6 +332,8 @@ pub struct GrpcTlsConfig {
 pub struct GrpcConfig {
     pub addr: String,
     pub db_path: String,
+    /// Shared postgres cache, used instead of the sqlite file at `db_path` when set.
+    pub postgres_url: Option<String>,
     pub crds_path: PathBuf,
     pub auth_url: String,
     pub auth_client_id: String,
@@ -218,7 +343,8 @@ pub struct GrpcConfig {
     pub stripe_api_key: String,
     pub secret: String,
     pub topic: String,
-    pub kafka: HashMap<String, String>,
+    pub bus: Bus,
+    pub outbox_path: Option<String>,
     pub invite_ttl: Duration,
     pub ses_access_key_id: String,
     pub ses_secret_access_key: String,
@@ -228,6 +354,9 @@ pub struct GrpcConfig {
     pub balius_pg_url: Option<String>,
     pub balius_vault_token: Option<String>,
     pub balius_vault_address: Option<String>,
+    /// Clusters the projects and resources can be placed on, the first one is the default of
+    /// new projects. Empty when the deployment has a single cluster.
+    pub clusters: Vec<String>,
 }
 
 impl From<Error> for Status {
@@ -244,8 +373,98 @@ impl From<Error> for Status {
     }
 }
 
+/// Cluster chosen with the `cluster-id` metadata, the request messages don't have a field for
+/// the placement.
+fn cluster_placement(metadata: &MetadataMap, clusters: &[String]) -> Result<Option<String>, Error> {
+    let Some(value) = metadata.get("cluster-id") else {
+        return Ok(None);
+    };
+
+    match value.to_str() {
+        Ok(cluster_id) if clusters.iter().any(|c| c == cluster_id) => {
+            Ok(Some(cluster_id.to_string()))
+        }
+        _ => Err(Error::CommandMalformed("invalid cluster id".into())),
+    }
+}
+
+/// Cursor of a list from the `page-cursor-bin` metadata, an empty one for the first page. `None`
+/// without the metadata, the list then keeps the offset paging of the request.
+fn page_cursor(metadata: &MetadataMap) -> Result<Option<String>, Error> {
+    let Some(value) = metadata.get_bin(PAGE_CURSOR_KEY) else {
+        return Ok(None);
+    };
+
+    value
+        .to_bytes()
+        .ok()
+        .and_then(|bytes| ops::PageCursor::decode(bytes).ok())
+        .map(|page| Some(page.cursor.unwrap_or_default()))
+        .ok_or(Error::CommandMalformed("invalid page cursor".into()))
+}
+
+/// Response of a list with the cursor of the next page in the `page-cursor-bin` metadata, left
+/// out on the last page.
+fn paged_response<T>(message: T, next_cursor: Option<Cursor>) -> tonic::Response<T> {
+    let mut response = tonic::Response::new(message);
+    if let Some(cursor) = next_cursor {
+        let page = ops::PageCursor {
+            cursor: Some(cursor.encode()),
+        };
+        response.metadata_mut().insert_bin(
+            PAGE_CURSOR_KEY,
+            MetadataValue::from_bytes(&page.encode_to_vec()),
+        );
+    }
+    response
+}
+
+/// Optional date of a request in RFC 3339.
+fn parse_datetime(value: Option<String>, field: &str) -> Result<Option<DateTime<Utc>>, Error> {
+    value
+        .map(|value| {
+            DateTime::parse_from_rfc3339(&value)
+                .map(|datetime| datetime.to_utc())
+                .map_err(|_| Error::CommandMalformed(format!("invalid {field}, expected rfc3339")))
+        })
+        .transpose()
+}
+
 fn handle_error_metric(metrics: Arc<MetricsDriven>, domain: &str, error: &Error) {
     if let Error::Unexpected(err) = error {
         metrics.domain_error("grpc", domain, &err.to_string());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_send_back_the_cursor_of_the_next_page() {
+        assert!(matches!(page_cursor(&MetadataMap::new()), Ok(None)));
+
+        let cursor = Cursor::new(Some(Utc::now()), "project id");
+        let response = paged_response((), Some(cursor.clone()));
+        let page = page_cursor(response.metadata()).unwrap();
+        assert!(page == Some(cursor.encode()));
+
+        let response = paged_response((), None);
+        assert!(response.metadata().get_bin(PAGE_CURSOR_KEY).is_none());
+    }
+
+    #[test]
+    fn it_should_ask_the_first_page_with_an_empty_cursor() {
+        let mut metadata = MetadataMap::new();
+        let page = ops::PageCursor { cursor: None };
+        metadata.insert_bin(
+            PAGE_CURSOR_KEY,
+            MetadataValue::from_bytes(&page.encode_to_vec()),
+        );
+        assert!(matches!(page_cursor(&metadata), Ok(Some(cursor)) if cursor.is_empty()));
+
+        let mut metadata = MetadataMap::new();
+        metadata.insert_bin(PAGE_CURSOR_KEY, MetadataValue::from_bytes(b"\xff"));
+        assert!(page_cursor(&metadata).is_err());
+    }
+}
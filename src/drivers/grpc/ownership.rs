@@ -0,0 +1,304 @@
+use std::{sync::Arc, time::Duration};
+use tonic::{async_trait, Status};
+
+use crate::{
+    domain::{
+        auth::{Auth0Driven, Credential},
+        event::EventDrivenBridge,
+        project::{self, cache::ProjectDrivenCache, ProjectEmailDriven, StripeDriven},
+    },
+    driven::prometheus::metrics::MetricsDriven,
+};
+
+use super::{handle_error_metric, ops as proto};
+
+pub struct OwnershipServiceImpl {
+    cache: Arc<dyn ProjectDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    auth0: Arc<dyn Auth0Driven>,
+    stripe: Arc<dyn StripeDriven>,
+    email: Arc<dyn ProjectEmailDriven>,
+    metrics: Arc<MetricsDriven>,
+    transfer_ttl: Duration,
+}
+
+impl OwnershipServiceImpl {
+    pub fn new(
+        cache: Arc<dyn ProjectDrivenCache>,
+        event: Arc<dyn EventDrivenBridge>,
+        auth0: Arc<dyn Auth0Driven>,
+        stripe: Arc<dyn StripeDriven>,
+        email: Arc<dyn ProjectEmailDriven>,
+        metrics: Arc<MetricsDriven>,
+        transfer_ttl: Duration,
+    ) -> Self {
+        Self {
+            cache,
+            event,
+            auth0,
+            stripe,
+            email,
+            metrics,
+            transfer_ttl,
+        }
+    }
+}
+
+#[async_trait]
+impl proto::ownership_service_server::OwnershipService for OwnershipServiceImpl {
+    async fn propose_project_ownership_transfer(
+        &self,
+        request: tonic::Request<proto::ProposeProjectOwnershipTransferRequest>,
+    ) -> Result<tonic::Response<proto::ProposeProjectOwnershipTransferResponse>, tonic::Status>
+    {
+        let credential = match request.extensions().get::<Credential>() {
+            Some(credential) => credential.clone(),
+            None => return Err(Status::unauthenticated("invalid credential")),
+        };
+
+        let req = request.into_inner();
+
+        let cmd = project::command::ProposeOwnershipTransferCmd::new(
+            credential,
+            self.transfer_ttl,
+            req.project_id,
+            req.new_owner,
+        );
+
+        project::command::propose_ownership_transfer(
+            self.cache.clone(),
+            self.email.clone(),
+            self.event.clone(),
+            self.auth0.clone(),
+            cmd.clone(),
+        )
+        .await
+        .inspect_err(|err| handle_error_metric(self.metrics.clone(), "project", err))?;
+
+        let message = proto::ProposeProjectOwnershipTransferResponse { id: cmd.id };
+
+        Ok(tonic::Response::new(message))
+    }
+    async fn accept_project_ownership_transfer(
+        &self,
+        request: tonic::Request<proto::AcceptProjectOwnershipTransferRequest>,
+    ) -> Result<tonic::Response<proto::AcceptProjectOwnershipTransferResponse>, tonic::Status> {
+        let credential = match request.extensions().get::<Credential>() {
+            Some(credential) => credential.clone(),
+            None => return Err(Status::unauthenticated("invalid credential")),
+        };
+
+        let req = request.into_inner();
+
+        let cmd = project::command::AcceptOwnershipTransferCmd::new(credential, req.code);
+
+        project::command::accept_ownership_transfer(
+            self.cache.clone(),
+            self.event.clone(),
+            self.auth0.clone(),
+            self.stripe.clone(),
+            cmd,
+        )
+        .await
+        .inspect_err(|err| handle_error_metric(self.metrics.clone(), "project", err))?;
+
+        let message = proto::AcceptProjectOwnershipTransferResponse {};
+
+        Ok(tonic::Response::new(message))
+    }
+    async fn cancel_project_ownership_transfer(
+        &self,
+        request: tonic::Request<proto::CancelProjectOwnershipTransferRequest>,
+    ) -> Result<tonic::Response<proto::CancelProjectOwnershipTransferResponse>, tonic::Status> {
+        let credential = match request.extensions().get::<Credential>() {
+            Some(credential) => credential.clone(),
+            None => return Err(Status::unauthenticated("invalid credential")),
+        };
+
+        let req = request.into_inner();
+
+        let cmd = project::command::CancelOwnershipTransferCmd::new(credential, req.id);
+
+        project::command::cancel_ownership_transfer(self.cache.clone(), self.event.clone(), cmd)
+            .await
+            .inspect_err(|err| handle_error_metric(self.metrics.clone(), "project", err))?;
+
+        let message = proto::CancelProjectOwnershipTransferResponse {};
+
+        Ok(tonic::Response::new(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{
+        auth::{Auth0Profile, MockAuth0Driven},
+        event::MockEventDrivenBridge,
+        project::{
+            cache::MockProjectDrivenCache, MockProjectEmailDriven, MockStripeDriven, Project,
+            ProjectOwnershipTransfer, ProjectUser, ProjectUserRole,
+        },
+    };
+
+    use super::{proto::ownership_service_server::OwnershipService, *};
+
+    fn service(
+        cache: MockProjectDrivenCache,
+        event: MockEventDrivenBridge,
+        auth0: MockAuth0Driven,
+        email: MockProjectEmailDriven,
+    ) -> OwnershipServiceImpl {
+        OwnershipServiceImpl::new(
+            Arc::new(cache),
+            Arc::new(event),
+            Arc::new(auth0),
+            Arc::new(MockStripeDriven::new()),
+            Arc::new(email),
+            Arc::new(MetricsDriven::new().unwrap()),
+            Duration::from_secs(15 * 60),
+        )
+    }
+
+    fn request<T>(message: T, credential: Option<Credential>) -> tonic::Request<T> {
+        let mut request = tonic::Request::new(message);
+        if let Some(credential) = credential {
+            request.extensions_mut().insert(credential);
+        }
+        request
+    }
+
+    #[tokio::test]
+    async fn it_should_propose_project_ownership_transfer() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_user_permission()
+            .times(2)
+            .returning(|_, _| Ok(Some(ProjectUser::default())));
+        cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Project::default())));
+
+        let mut auth0 = MockAuth0Driven::new();
+        auth0
+            .expect_find_info()
+            .return_once(|_| Ok(vec![Auth0Profile::default()]));
+
+        let mut email = MockProjectEmailDriven::new();
+        email
+            .expect_send_ownership_transfer()
+            .return_once(|_, _, _, _| Ok(()));
+
+        let mut event = MockEventDrivenBridge::new();
+        event.expect_dispatch().return_once(|_| Ok(None));
+
+        let service = service(cache, event, auth0, email);
+        let message = proto::ProposeProjectOwnershipTransferRequest {
+            project_id: "project id".into(),
+            new_owner: "member user id".into(),
+        };
+        let result = service
+            .propose_project_ownership_transfer(request(
+                message,
+                Some(Credential::Auth0("user id".into())),
+            ))
+            .await;
+        assert!(result.is_ok_and(|response| !response.into_inner().id.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_propose_project_ownership_transfer_when_user_is_not_owner() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache.expect_find_user_permission().return_once(|_, _| {
+            Ok(Some(ProjectUser {
+                role: ProjectUserRole::Member,
+                ..Default::default()
+            }))
+        });
+
+        let service = service(
+            cache,
+            MockEventDrivenBridge::new(),
+            MockAuth0Driven::new(),
+            MockProjectEmailDriven::new(),
+        );
+        let message = proto::ProposeProjectOwnershipTransferRequest {
+            project_id: "project id".into(),
+            new_owner: "member user id".into(),
+        };
+        let result = service
+            .propose_project_ownership_transfer(request(
+                message,
+                Some(Credential::Auth0("user id".into())),
+            ))
+            .await;
+        assert!(result.is_err_and(|status| status.code() == tonic::Code::PermissionDenied));
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_accept_project_ownership_transfer_when_user_is_not_the_new_owner() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_ownership_transfer_by_code()
+            .return_once(|_| Ok(Some(ProjectOwnershipTransfer::default())));
+
+        let service = service(
+            cache,
+            MockEventDrivenBridge::new(),
+            MockAuth0Driven::new(),
+            MockProjectEmailDriven::new(),
+        );
+        let message = proto::AcceptProjectOwnershipTransferRequest { code: "123".into() };
+        let result = service
+            .accept_project_ownership_transfer(request(
+                message,
+                Some(Credential::Auth0("other user id".into())),
+            ))
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn it_should_cancel_project_ownership_transfer() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_ownership_transfer_by_id()
+            .return_once(|_| Ok(Some(ProjectOwnershipTransfer::default())));
+
+        let mut event = MockEventDrivenBridge::new();
+        event.expect_dispatch().return_once(|_| Ok(None));
+
+        let service = service(
+            cache,
+            event,
+            MockAuth0Driven::new(),
+            MockProjectEmailDriven::new(),
+        );
+        let message = proto::CancelProjectOwnershipTransferRequest {
+            id: "transfer id".into(),
+        };
+        let result = service
+            .cancel_project_ownership_transfer(request(
+                message,
+                Some(Credential::Auth0("member user id".into())),
+            ))
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_cancel_project_ownership_transfer_when_credential_is_missing() {
+        let service = service(
+            MockProjectDrivenCache::new(),
+            MockEventDrivenBridge::new(),
+            MockAuth0Driven::new(),
+            MockProjectEmailDriven::new(),
+        );
+        let message = proto::CancelProjectOwnershipTransferRequest {
+            id: "transfer id".into(),
+        };
+        let result = service
+            .cancel_project_ownership_transfer(request(message, None))
+            .await;
+        assert!(result.is_err_and(|status| status.code() == tonic::Code::Unauthenticated));
+    }
+}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+use tonic::async_trait;
+
+use crate::{
+    domain::metadata::{self, command::CostEstimate, MetadataDriven},
+    driven::prometheus::metrics::MetricsDriven,
+};
+
+use super::{handle_error_metric, ops as proto};
+
+pub struct PricingServiceImpl {
+    metadata: Arc<dyn MetadataDriven>,
+    metrics: Arc<MetricsDriven>,
+}
+impl PricingServiceImpl {
+    pub fn new(metadata: Arc<dyn MetadataDriven>, metrics: Arc<MetricsDriven>) -> Self {
+        Self { metadata, metrics }
+    }
+}
+
+#[async_trait]
+impl proto::pricing_service_server::PricingService for PricingServiceImpl {
+    async fn estimate_cost(
+        &self,
+        request: tonic::Request<proto::EstimateCostRequest>,
+    ) -> Result<tonic::Response<proto::EstimateCostResponse>, tonic::Status> {
+        let req = request.into_inner();
+
+        let cmd = metadata::command::EstimateCostCmd::new(req.kind, req.spec, req.units)
+            .inspect_err(|err| handle_error_metric(self.metrics.clone(), "metadata", err))?;
+
+        let estimate = metadata::command::estimate_cost(self.metadata.clone(), cmd)
+            .await
+            .inspect_err(|err| handle_error_metric(self.metrics.clone(), "metadata", err))?;
+
+        let message: proto::EstimateCostResponse = estimate.into();
+
+        Ok(tonic::Response::new(message))
+    }
+}
+
+impl From<CostEstimate> for proto::EstimateCostResponse {
+    fn from(value: CostEstimate) -> Self {
+        Self {
+            kind: value.kind,
+            tier: value.tier,
+            minimum_cost: value.minimum_cost,
+            unit_cost: value.unit_cost,
+            units: value.units,
+            total_cost: value.total_cost,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::metadata::{MockMetadataDriven, ResourceMetadata};
+
+    use super::{proto::pricing_service_server::PricingService, *};
+
+    fn service() -> PricingServiceImpl {
+        let mut metadata = MockMetadataDriven::new();
+        metadata
+            .expect_find_by_kind()
+            .returning(|_| Ok(Some(ResourceMetadata::default())));
+
+        PricingServiceImpl::new(Arc::new(metadata), Arc::new(MetricsDriven::new().unwrap()))
+    }
+
+    fn cents(value: f64) -> i64 {
+        (value * 100.).round() as i64
+    }
+
+    #[tokio::test]
+    async fn it_should_estimate_cost() {
+        let request = tonic::Request::new(proto::EstimateCostRequest {
+            kind: "CardanoNodePort".into(),
+            spec: "{\"network\":\"mainnet\",\"throughputTier\":\"0\"}".into(),
+            units: 1000,
+        });
+
+        let result = service().estimate_cost(request).await;
+        assert!(result.is_ok());
+
+        let estimate = result.unwrap().into_inner();
+        assert!(estimate.tier == "0");
+        assert!(cents(estimate.total_cost) == 30000);
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_estimate_cost_when_spec_is_invalid() {
+        let request = tonic::Request::new(proto::EstimateCostRequest {
+            kind: "CardanoNodePort".into(),
+            spec: "invalid".into(),
+            units: 1000,
+        });
+
+        let result = service().estimate_cost(request).await;
+        assert!(result.is_err_and(|status| status.code() == tonic::Code::FailedPrecondition));
+    }
+}
@@ -15,7 +15,7 @@ use crate::{
     driven::prometheus::metrics::MetricsDriven,
 };
 
-use super::handle_error_metric;
+use super::{cluster_placement, handle_error_metric, page_cursor, paged_response};
 
 pub struct ProjectServiceImpl {
     cache: Arc<dyn ProjectDrivenCache>,
@@ -26,6 +26,7 @@ pub struct ProjectServiceImpl {
     metrics: Arc<MetricsDriven>,
     secret: String,
     invite_ttl: Duration,
+    clusters: Vec<String>,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -39,6 +40,7 @@ impl ProjectServiceImpl {
         metrics: Arc<MetricsDriven>,
         secret: String,
         invite_ttl: Duration,
+        clusters: Vec<String>,
     ) -> Self {
         Self {
             cache,
@@ -49,6 +51,7 @@ impl ProjectServiceImpl {
             metrics,
             secret,
             invite_ttl,
+            clusters,
         }
     }
 }
@@ -64,19 +67,22 @@ impl proto::project_service_server::ProjectService for ProjectServiceImpl {
             None => return Err(Status::unauthenticated("invalid credential")),
         };
 
+        let cursor = page_cursor(request.metadata())
+            .inspect_err(|err| handle_error_metric(self.metrics.clone(), "project", err))?;
+
         let req = request.into_inner();
 
-        let cmd = project::command::FetchCmd::new(credential, req.page, req.page_size)
+        let cmd = project::command::FetchCmd::new(credential, req.page, req.page_size, cursor)
             .inspect_err(|err| handle_error_metric(self.metrics.clone(), "project", err))?;
 
         let projects = project::command::fetch(self.cache.clone(), cmd.clone())
             .await
             .inspect_err(|err| handle_error_metric(self.metrics.clone(), "project", err))?;
 
-        let records = projects.into_iter().map(|v| v.into()).collect();
+        let records = projects.records.into_iter().map(|v| v.into()).collect();
         let message = proto::FetchProjectsResponse { records };
 
-        Ok(tonic::Response::new(message))
+        Ok(paged_response(message, projects.next_cursor))
     }
     async fn fetch_project_by_namespace(
         &self,
@@ -134,9 +140,13 @@ impl proto::project_service_server::ProjectService for ProjectServiceImpl {
             None => return Err(Status::unauthenticated("invalid credential")),
         };
 
+        let cluster_id = cluster_placement(request.metadata(), &self.clusters)
+            .inspect_err(|err| handle_error_metric(self.metrics.clone(), "project", err))?
+            .or_else(|| self.clusters.first().cloned());
+
         let req = request.into_inner();
 
-        let cmd = project::command::CreateCmd::new(credential, req.name);
+        let cmd = project::command::CreateCmd::new(credential, req.name, cluster_id);
 
         project::command::create(
             self.cache.clone(),
@@ -287,12 +297,16 @@ impl proto::project_service_server::ProjectService for ProjectServiceImpl {
             None => return Err(Status::unauthenticated("invalid credential")),
         };
 
+        let cursor = page_cursor(request.metadata())
+            .inspect_err(|err| handle_error_metric(self.metrics.clone(), "project", err))?;
+
         let req = request.into_inner();
 
         let cmd = project::command::FetchUserCmd::new(
             credential,
             req.page,
             req.page_size,
+            cursor,
             req.project_id,
         )
         .inspect_err(|err| handle_error_metric(self.metrics.clone(), "project", err))?;
@@ -302,10 +316,10 @@ impl proto::project_service_server::ProjectService for ProjectServiceImpl {
                 .await
                 .inspect_err(|err| handle_error_metric(self.metrics.clone(), "project", err))?;
 
-        let records = users.into_iter().map(|v| v.into()).collect();
+        let records = users.records.into_iter().map(|v| v.into()).collect();
         let message = proto::FetchProjectUsersResponse { records };
 
-        Ok(tonic::Response::new(message))
+        Ok(paged_response(message, users.next_cursor))
     }
     async fn fetch_me_project_user(
         &self,
@@ -341,12 +355,16 @@ impl proto::project_service_server::ProjectService for ProjectServiceImpl {
             None => return Err(Status::unauthenticated("invalid credential")),
         };
 
+        let cursor = page_cursor(request.metadata())
+            .inspect_err(|err| handle_error_metric(self.metrics.clone(), "project", err))?;
+
         let req = request.into_inner();
 
         let cmd = project::command::FetchUserInviteCmd::new(
             credential,
             req.page,
             req.page_size,
+            cursor,
             req.project_id,
         )
         .inspect_err(|err| handle_error_metric(self.metrics.clone(), "project", err))?;
@@ -355,10 +373,10 @@ impl proto::project_service_server::ProjectService for ProjectServiceImpl {
             .await
             .inspect_err(|err| handle_error_metric(self.metrics.clone(), "project", err))?;
 
-        let records = invites.into_iter().map(|v| v.into()).collect();
+        let records = invites.records.into_iter().map(|v| v.into()).collect();
         let message = proto::FetchProjectUserInvitesResponse { records };
 
-        Ok(tonic::Response::new(message))
+        Ok(paged_response(message, invites.next_cursor))
     }
 
     async fn create_project_user_invite(
@@ -538,3 +556,85 @@ impl From<ProjectSecret> for proto::ProjectSecret {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use prost::Message;
+    use tonic::metadata::MetadataValue;
+
+    use crate::{
+        domain::{
+            auth::MockAuth0Driven,
+            event::MockEventDrivenBridge,
+            project::{cache::MockProjectDrivenCache, MockProjectEmailDriven, MockStripeDriven},
+        },
+        drivers::grpc::{ops, PAGE_CURSOR_KEY},
+    };
+
+    use super::{proto::project_service_server::ProjectService, *};
+
+    fn request(cursor: Option<String>) -> tonic::Request<proto::FetchProjectsRequest> {
+        let mut request = tonic::Request::new(proto::FetchProjectsRequest {
+            page: None,
+            page_size: Some(2),
+        });
+        request
+            .extensions_mut()
+            .insert(Credential::Auth0("user id".into()));
+        let page = ops::PageCursor { cursor };
+        request.metadata_mut().insert_bin(
+            PAGE_CURSOR_KEY,
+            MetadataValue::from_bytes(&page.encode_to_vec()),
+        );
+        request
+    }
+
+    fn next_cursor(response: &tonic::Response<proto::FetchProjectsResponse>) -> Option<String> {
+        response
+            .metadata()
+            .get_bin(PAGE_CURSOR_KEY)
+            .and_then(|value| value.to_bytes().ok())
+            .and_then(|bytes| ops::PageCursor::decode(bytes).ok())
+            .and_then(|page| page.cursor)
+    }
+
+    #[tokio::test]
+    async fn it_should_page_through_projects_by_cursor() {
+        let projects: Vec<Project> = (0..3).map(|_| Project::default()).collect();
+        let first = projects[..2].to_vec();
+        let last = projects[2..].to_vec();
+        let last_of_first = projects[1].id.clone();
+
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find()
+            .withf(|_, page| page.by_cursor && page.cursor.is_none())
+            .return_once(|_, _| Ok(first));
+        cache
+            .expect_find()
+            .withf(move |_, page| page.cursor_id() == Some(last_of_first.as_str()))
+            .return_once(|_, _| Ok(last));
+
+        let service = ProjectServiceImpl::new(
+            Arc::new(cache),
+            Arc::new(MockEventDrivenBridge::new()),
+            Arc::new(MockAuth0Driven::new()),
+            Arc::new(MockStripeDriven::new()),
+            Arc::new(MockProjectEmailDriven::new()),
+            Arc::new(MetricsDriven::new().unwrap()),
+            "secret".into(),
+            Duration::from_secs(15 * 60),
+            Vec::new(),
+        );
+
+        let response = service.fetch_projects(request(None)).await.unwrap();
+        let cursor = next_cursor(&response);
+        assert!(response.get_ref().records.len() == 2);
+        assert!(cursor.is_some());
+
+        let response = service.fetch_projects(request(cursor)).await.unwrap();
+        assert!(response.get_ref().records.len() == 1);
+        assert!(response.get_ref().records[0].id == projects[2].id);
+        assert!(next_cursor(&response).is_none());
+    }
+}
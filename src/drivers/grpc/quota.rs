@@ -0,0 +1,138 @@
+use std::sync::Arc;
+use tonic::{async_trait, Status};
+
+use crate::{
+    domain::{
+        auth::Credential,
+        project::{self, cache::ProjectDrivenCache, quota::ProjectQuota},
+    },
+    driven::prometheus::metrics::MetricsDriven,
+};
+
+use super::{handle_error_metric, ops as proto};
+
+pub struct QuotaServiceImpl {
+    cache: Arc<dyn ProjectDrivenCache>,
+    metrics: Arc<MetricsDriven>,
+}
+
+impl QuotaServiceImpl {
+    pub fn new(cache: Arc<dyn ProjectDrivenCache>, metrics: Arc<MetricsDriven>) -> Self {
+        Self { cache, metrics }
+    }
+}
+
+#[async_trait]
+impl proto::quota_service_server::QuotaService for QuotaServiceImpl {
+    async fn fetch_project_quota(
+        &self,
+        request: tonic::Request<proto::FetchProjectQuotaRequest>,
+    ) -> Result<tonic::Response<proto::FetchProjectQuotaResponse>, tonic::Status> {
+        let credential = match request.extensions().get::<Credential>() {
+            Some(credential) => credential.clone(),
+            None => return Err(Status::unauthenticated("invalid credential")),
+        };
+
+        let req = request.into_inner();
+
+        let cmd = project::quota::FetchCmd::new(credential, req.project_id);
+
+        let quota = project::quota::fetch(self.cache.clone(), cmd)
+            .await
+            .inspect_err(|err| handle_error_metric(self.metrics.clone(), "project", err))?;
+
+        let message = proto::FetchProjectQuotaResponse {
+            quota: Some(quota.into()),
+        };
+
+        Ok(tonic::Response::new(message))
+    }
+}
+
+impl From<ProjectQuota> for proto::ProjectQuota {
+    fn from(value: ProjectQuota) -> Self {
+        Self {
+            resources: value.resources,
+            max_tier: value.max_tier,
+            workers: value.workers,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::domain::project::{cache::MockProjectDrivenCache, ProjectUser};
+
+    use super::{proto::quota_service_server::QuotaService, *};
+
+    fn request(credential: Option<Credential>) -> tonic::Request<proto::FetchProjectQuotaRequest> {
+        let mut request = tonic::Request::new(proto::FetchProjectQuotaRequest {
+            project_id: "project id".into(),
+        });
+        if let Some(credential) = credential {
+            request.extensions_mut().insert(credential);
+        }
+        request
+    }
+
+    #[tokio::test]
+    async fn it_should_fetch_project_quota() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        cache.expect_find_default_quota().return_once(|| {
+            Ok(Some(ProjectQuota {
+                resources: HashMap::from([("CardanoNodePort".into(), 2)]),
+                max_tier: Some(1),
+                ..Default::default()
+            }))
+        });
+        cache.expect_find_quota().return_once(|_| {
+            Ok(Some(ProjectQuota {
+                project_id: Some("project id".into()),
+                workers: Some(3),
+                ..Default::default()
+            }))
+        });
+
+        let service =
+            QuotaServiceImpl::new(Arc::new(cache), Arc::new(MetricsDriven::new().unwrap()));
+        let result = service
+            .fetch_project_quota(request(Some(Credential::Auth0("user id".into()))))
+            .await;
+        assert!(result.is_ok());
+
+        let quota = result.unwrap().into_inner().quota.unwrap();
+        assert!(quota.resources.get("CardanoNodePort") == Some(&2));
+        assert!(quota.max_tier == Some(1));
+        assert!(quota.workers == Some(3));
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_fetch_project_quota_when_user_is_not_member() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(None));
+
+        let service =
+            QuotaServiceImpl::new(Arc::new(cache), Arc::new(MetricsDriven::new().unwrap()));
+        let result = service
+            .fetch_project_quota(request(Some(Credential::Auth0("user id".into()))))
+            .await;
+        assert!(result.is_err_and(|status| status.code() == tonic::Code::PermissionDenied));
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_fetch_project_quota_when_credential_is_missing() {
+        let service = QuotaServiceImpl::new(
+            Arc::new(MockProjectDrivenCache::new()),
+            Arc::new(MetricsDriven::new().unwrap()),
+        );
+        let result = service.fetch_project_quota(request(None)).await;
+        assert!(result.is_err_and(|status| status.code() == tonic::Code::Unauthenticated));
+    }
+}
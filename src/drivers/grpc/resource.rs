@@ -13,7 +13,7 @@ use crate::{
     driven::prometheus::metrics::MetricsDriven,
 };
 
-use super::handle_error_metric;
+use super::{cluster_placement, handle_error_metric, page_cursor, paged_response};
 
 pub struct ResourceServiceImpl {
     project_cache: Arc<dyn ProjectDrivenCache>,
@@ -21,6 +21,7 @@ pub struct ResourceServiceImpl {
     event: Arc<dyn EventDrivenBridge>,
     metadata: Arc<dyn MetadataDriven>,
     metrics: Arc<MetricsDriven>,
+    clusters: Vec<String>,
 }
 impl ResourceServiceImpl {
     pub fn new(
@@ -29,6 +30,7 @@ impl ResourceServiceImpl {
         event: Arc<dyn EventDrivenBridge>,
         metadata: Arc<dyn MetadataDriven>,
         metrics: Arc<MetricsDriven>,
+        clusters: Vec<String>,
     ) -> Self {
         Self {
             project_cache,
@@ -36,6 +38,7 @@ impl ResourceServiceImpl {
             event,
             metadata,
             metrics,
+            clusters,
         }
     }
 }
@@ -51,10 +54,20 @@ impl proto::resource_service_server::ResourceService for ResourceServiceImpl {
             None => return Err(Status::unauthenticated("invalid credential")),
         };
 
+        let cursor = page_cursor(request.metadata())
+            .inspect_err(|err| handle_error_metric(self.metrics.clone(), "resource", err))?;
+
         let req = request.into_inner();
 
-        let cmd = command::FetchCmd::new(credential, req.project_id, req.page, req.page_size, req.category)
-            .inspect_err(|err| handle_error_metric(self.metrics.clone(), "resource", err))?;
+        let cmd = command::FetchCmd::new(
+            credential,
+            req.project_id,
+            req.page,
+            req.page_size,
+            cursor,
+            req.category,
+        )
+        .inspect_err(|err| handle_error_metric(self.metrics.clone(), "resource", err))?;
 
         let resources = command::fetch(
             self.project_cache.clone(),
@@ -65,10 +78,10 @@ impl proto::resource_service_server::ResourceService for ResourceServiceImpl {
         .await
         .inspect_err(|err| handle_error_metric(self.metrics.clone(), "resource", err))?;
 
-        let records = resources.into_iter().map(|v| v.into()).collect();
+        let records = resources.records.into_iter().map(|v| v.into()).collect();
         let message = proto::FetchResourcesResponse { records };
 
-        Ok(tonic::Response::new(message))
+        Ok(paged_response(message, resources.next_cursor))
     }
     async fn fetch_resources_by_id(
         &self,
@@ -110,10 +123,14 @@ impl proto::resource_service_server::ResourceService for ResourceServiceImpl {
             None => return Err(Status::unauthenticated("invalid credential")),
         };
 
+        let cluster_id = cluster_placement(request.metadata(), &self.clusters)
+            .inspect_err(|err| handle_error_metric(self.metrics.clone(), "resource", err))?;
+
         let req = request.into_inner();
 
-        let cmd = command::CreateCmd::new(credential, req.project_id, req.kind, req.spec)
-            .inspect_err(|err| handle_error_metric(self.metrics.clone(), "resource", err))?;
+        let cmd =
+            command::CreateCmd::new(credential, req.project_id, req.kind, req.spec, cluster_id)
+                .inspect_err(|err| handle_error_metric(self.metrics.clone(), "resource", err))?;
 
         command::create(
             self.resource_cache.clone(),
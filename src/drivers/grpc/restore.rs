@@ -0,0 +1,242 @@
+use std::sync::Arc;
+use tonic::{async_trait, Status};
+
+use crate::{
+    domain::{
+        auth::Credential, event::EventDrivenBridge, project, project::cache::ProjectDrivenCache,
+        resource, resource::cache::ResourceDrivenCache,
+    },
+    driven::prometheus::metrics::MetricsDriven,
+};
+
+use super::{handle_error_metric, ops as proto};
+
+pub struct RestoreServiceImpl {
+    project_cache: Arc<dyn ProjectDrivenCache>,
+    resource_cache: Arc<dyn ResourceDrivenCache>,
+    event: Arc<dyn EventDrivenBridge>,
+    metrics: Arc<MetricsDriven>,
+}
+
+impl RestoreServiceImpl {
+    pub fn new(
+        project_cache: Arc<dyn ProjectDrivenCache>,
+        resource_cache: Arc<dyn ResourceDrivenCache>,
+        event: Arc<dyn EventDrivenBridge>,
+        metrics: Arc<MetricsDriven>,
+    ) -> Self {
+        Self {
+            project_cache,
+            resource_cache,
+            event,
+            metrics,
+        }
+    }
+}
+
+#[async_trait]
+impl proto::restore_service_server::RestoreService for RestoreServiceImpl {
+    async fn restore_project(
+        &self,
+        request: tonic::Request<proto::RestoreProjectRequest>,
+    ) -> Result<tonic::Response<proto::RestoreProjectResponse>, tonic::Status> {
+        let credential = match request.extensions().get::<Credential>() {
+            Some(credential) => credential.clone(),
+            None => return Err(Status::unauthenticated("invalid credential")),
+        };
+
+        let req = request.into_inner();
+
+        let cmd = project::command::RestoreCmd::new(credential, req.id);
+
+        project::command::restore(
+            self.project_cache.clone(),
+            self.resource_cache.clone(),
+            self.event.clone(),
+            cmd,
+        )
+        .await
+        .inspect_err(|err| handle_error_metric(self.metrics.clone(), "project", err))?;
+
+        let message = proto::RestoreProjectResponse {};
+
+        Ok(tonic::Response::new(message))
+    }
+    async fn restore_resource(
+        &self,
+        request: tonic::Request<proto::RestoreResourceRequest>,
+    ) -> Result<tonic::Response<proto::RestoreResourceResponse>, tonic::Status> {
+        let credential = match request.extensions().get::<Credential>() {
+            Some(credential) => credential.clone(),
+            None => return Err(Status::unauthenticated("invalid credential")),
+        };
+
+        let req = request.into_inner();
+
+        let cmd = resource::command::RestoreCmd::new(credential, req.id);
+
+        resource::command::restore(
+            self.project_cache.clone(),
+            self.resource_cache.clone(),
+            self.event.clone(),
+            cmd,
+        )
+        .await
+        .inspect_err(|err| handle_error_metric(self.metrics.clone(), "resource", err))?;
+
+        let message = proto::RestoreResourceResponse {};
+
+        Ok(tonic::Response::new(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{
+        event::MockEventDrivenBridge,
+        project::{cache::MockProjectDrivenCache, Project, ProjectUser, ProjectUserRole},
+        resource::{cache::MockResourceDrivenCache, Resource},
+    };
+
+    use super::{proto::restore_service_server::RestoreService, *};
+
+    fn service(
+        project_cache: MockProjectDrivenCache,
+        resource_cache: MockResourceDrivenCache,
+        event: MockEventDrivenBridge,
+    ) -> RestoreServiceImpl {
+        RestoreServiceImpl::new(
+            Arc::new(project_cache),
+            Arc::new(resource_cache),
+            Arc::new(event),
+            Arc::new(MetricsDriven::new().unwrap()),
+        )
+    }
+
+    fn request<T>(message: T, credential: Option<Credential>) -> tonic::Request<T> {
+        let mut request = tonic::Request::new(message);
+        if let Some(credential) = credential {
+            request.extensions_mut().insert(credential);
+        }
+        request
+    }
+
+    fn admin() -> MockProjectDrivenCache {
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| {
+                Ok(Some(ProjectUser {
+                    role: ProjectUserRole::Admin,
+                    ..Default::default()
+                }))
+            });
+        project_cache
+    }
+
+    #[tokio::test]
+    async fn it_should_restore_project() {
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        project_cache
+            .expect_find_deleted_by_id()
+            .return_once(|_| Ok(Some(Project::default())));
+
+        let mut resource_cache = MockResourceDrivenCache::new();
+        resource_cache
+            .expect_find_deleted_with_project()
+            .return_once(|_, _| Ok(vec![]));
+
+        let mut event = MockEventDrivenBridge::new();
+        event.expect_dispatch().return_once(|_| Ok(None));
+
+        let service = service(project_cache, resource_cache, event);
+        let message = proto::RestoreProjectRequest {
+            id: "project id".into(),
+        };
+        let result = service
+            .restore_project(request(message, Some(Credential::Auth0("user id".into()))))
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_restore_project_when_user_is_not_owner() {
+        let service = service(
+            admin(),
+            MockResourceDrivenCache::new(),
+            MockEventDrivenBridge::new(),
+        );
+        let message = proto::RestoreProjectRequest {
+            id: "project id".into(),
+        };
+        let result = service
+            .restore_project(request(message, Some(Credential::Auth0("user id".into()))))
+            .await;
+        assert!(result.is_err_and(|status| status.code() == tonic::Code::PermissionDenied));
+    }
+
+    #[tokio::test]
+    async fn it_should_restore_resource() {
+        let mut resource_cache = MockResourceDrivenCache::new();
+        resource_cache
+            .expect_find_deleted_by_id()
+            .returning(|_| Ok(Some(Resource::default())));
+        resource_cache
+            .expect_find_by_name()
+            .return_once(|_, _| Ok(None));
+
+        let mut project_cache = MockProjectDrivenCache::new();
+        project_cache
+            .expect_find_user_permission()
+            .return_once(|_, _| Ok(Some(ProjectUser::default())));
+        project_cache
+            .expect_find_by_id()
+            .return_once(|_| Ok(Some(Project::default())));
+
+        let mut event = MockEventDrivenBridge::new();
+        event.expect_dispatch().return_once(|_| Ok(None));
+
+        let service = service(project_cache, resource_cache, event);
+        let message = proto::RestoreResourceRequest {
+            id: "resource id".into(),
+        };
+        let result = service
+            .restore_resource(request(message, Some(Credential::Auth0("user id".into()))))
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_restore_resource_when_user_is_not_owner() {
+        let mut resource_cache = MockResourceDrivenCache::new();
+        resource_cache
+            .expect_find_deleted_by_id()
+            .returning(|_| Ok(Some(Resource::default())));
+
+        let service = service(admin(), resource_cache, MockEventDrivenBridge::new());
+        let message = proto::RestoreResourceRequest {
+            id: "resource id".into(),
+        };
+        let result = service
+            .restore_resource(request(message, Some(Credential::Auth0("user id".into()))))
+            .await;
+        assert!(result.is_err_and(|status| status.code() == tonic::Code::PermissionDenied));
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_restore_without_credential() {
+        let service = service(
+            MockProjectDrivenCache::new(),
+            MockResourceDrivenCache::new(),
+            MockEventDrivenBridge::new(),
+        );
+        let message = proto::RestoreProjectRequest {
+            id: "project id".into(),
+        };
+        let result = service.restore_project(request(message, None)).await;
+        assert!(result.is_err_and(|status| status.code() == tonic::Code::Unauthenticated));
+    }
+}
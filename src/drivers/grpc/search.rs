@@ -0,0 +1,165 @@
+use std::sync::Arc;
+use tonic::{async_trait, Status};
+
+use crate::{
+    domain::{
+        auth::{Auth0Driven, Credential},
+        search::{self, cache::SearchDrivenCache, SearchResult},
+    },
+    driven::prometheus::metrics::MetricsDriven,
+};
+
+use super::{handle_error_metric, ops as proto, parse_datetime};
+
+pub struct SearchServiceImpl {
+    search_cache: Arc<dyn SearchDrivenCache>,
+    auth0: Arc<dyn Auth0Driven>,
+    metrics: Arc<MetricsDriven>,
+}
+
+impl SearchServiceImpl {
+    pub fn new(
+        search_cache: Arc<dyn SearchDrivenCache>,
+        auth0: Arc<dyn Auth0Driven>,
+        metrics: Arc<MetricsDriven>,
+    ) -> Self {
+        Self {
+            search_cache,
+            auth0,
+            metrics,
+        }
+    }
+}
+
+#[async_trait]
+impl proto::search_service_server::SearchService for SearchServiceImpl {
+    async fn search_resources(
+        &self,
+        request: tonic::Request<proto::SearchResourcesRequest>,
+    ) -> Result<tonic::Response<proto::SearchResourcesResponse>, tonic::Status> {
+        let credential = match request.extensions().get::<Credential>() {
+            Some(credential) => credential.clone(),
+            None => return Err(Status::unauthenticated("invalid credential")),
+        };
+
+        let req = request.into_inner();
+
+        let cmd = search::command::FetchCmd::new(
+            credential,
+            req.text,
+            req.kind,
+            req.network,
+            req.tier,
+            req.status,
+            parse_datetime(req.from, "from")?,
+            parse_datetime(req.to, "to")?,
+            req.owner_email,
+            req.page,
+            req.page_size,
+        )
+        .inspect_err(|err| handle_error_metric(self.metrics.clone(), "search", err))?;
+
+        let results = search::command::fetch(self.search_cache.clone(), self.auth0.clone(), cmd)
+            .await
+            .inspect_err(|err| handle_error_metric(self.metrics.clone(), "search", err))?;
+
+        let records = results.into_iter().map(|v| v.into()).collect();
+        let message = proto::SearchResourcesResponse { records };
+
+        Ok(tonic::Response::new(message))
+    }
+}
+
+impl From<SearchResult> for proto::SearchResult {
+    fn from(value: SearchResult) -> Self {
+        Self {
+            project_id: value.project_id,
+            project_namespace: value.project_namespace,
+            project_name: value.project_name,
+            project_owner: value.project_owner,
+            resource_id: value.resource_id,
+            resource_name: value.resource_name,
+            kind: value.kind,
+            spec: value.spec,
+            status: value.status,
+            created_at: value.created_at.to_rfc3339(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{auth::MockAuth0Driven, search::cache::MockSearchDrivenCache};
+
+    use super::{proto::search_service_server::SearchService, *};
+
+    fn service(search_cache: MockSearchDrivenCache) -> SearchServiceImpl {
+        SearchServiceImpl::new(
+            Arc::new(search_cache),
+            Arc::new(MockAuth0Driven::new()),
+            Arc::new(MetricsDriven::new().unwrap()),
+        )
+    }
+
+    fn request(credential: Option<Credential>) -> tonic::Request<proto::SearchResourcesRequest> {
+        let mut request = tonic::Request::new(proto::SearchResourcesRequest {
+            text: Some("mainnet".into()),
+            ..Default::default()
+        });
+        if let Some(credential) = credential {
+            request.extensions_mut().insert(credential);
+        }
+        request
+    }
+
+    #[tokio::test]
+    async fn it_should_search_the_projects_of_the_user() {
+        let mut search_cache = MockSearchDrivenCache::new();
+        search_cache
+            .expect_search()
+            .withf(|filter, _, _| {
+                filter.member.as_deref() == Some("user id") && filter.project_id.is_none()
+            })
+            .return_once(|_, _, _| Ok(vec![]));
+
+        let result = service(search_cache)
+            .search_resources(request(Some(Credential::Auth0("user id".into()))))
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_search_the_project_of_the_api_key() {
+        let mut search_cache = MockSearchDrivenCache::new();
+        search_cache
+            .expect_search()
+            .withf(|filter, _, _| {
+                filter.project_id.as_deref() == Some("project id") && filter.member.is_none()
+            })
+            .return_once(|_, _, _| Ok(vec![]));
+
+        let result = service(search_cache)
+            .search_resources(request(Some(Credential::ApiKey("project id".into()))))
+            .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_search_without_credential() {
+        let result = service(MockSearchDrivenCache::new())
+            .search_resources(request(None))
+            .await;
+        assert!(result.is_err_and(|status| status.code() == tonic::Code::Unauthenticated));
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_search_when_dates_are_invalid() {
+        let mut request = request(Some(Credential::Auth0("user id".into())));
+        request.get_mut().from = Some("yesterday".into());
+
+        let result = service(MockSearchDrivenCache::new())
+            .search_resources(request)
+            .await;
+        assert!(result.is_err_and(|status| status.code() == tonic::Code::FailedPrecondition));
+    }
+}
@@ -17,7 +17,7 @@ use crate::{
     driven::prometheus::metrics::MetricsDriven,
 };
 
-use super::handle_error_metric;
+use super::{handle_error_metric, page_cursor, paged_response};
 
 pub struct WorkerKeyValueServiceImpl {
     project_cache: Arc<dyn ProjectDrivenCache>,
@@ -51,6 +51,10 @@ impl proto::key_value_service_server::KeyValueService for WorkerKeyValueServiceI
             None => return Err(Status::unauthenticated("invalid credential")),
         };
 
+        let cursor = page_cursor(request.metadata()).inspect_err(|err| {
+            handle_error_metric(self.metrics.clone(), "worker-key-value-storage", err)
+        })?;
+
         let req = request.into_inner();
 
         let cmd = storage::command::FetchCmd::new(
@@ -59,6 +63,7 @@ impl proto::key_value_service_server::KeyValueService for WorkerKeyValueServiceI
             req.key,
             req.page,
             req.page_size,
+            cursor,
         )
         .inspect_err(|err| {
             handle_error_metric(self.metrics.clone(), "worker-key-value-storage", err)
@@ -75,13 +80,13 @@ impl proto::key_value_service_server::KeyValueService for WorkerKeyValueServiceI
             handle_error_metric(self.metrics.clone(), "worker-key-value-storage", err)
         })?;
 
-        let records = values.into_iter().map(|v| v.into()).collect();
+        let records = values.records.into_iter().map(|v| v.into()).collect();
         let message = proto::FetchKeyValueResponse {
-            total_records: count as u32,
+            total_records: count.unwrap_or_default() as u32,
             records,
         };
 
-        Ok(tonic::Response::new(message))
+        Ok(paged_response(message, values.next_cursor))
     }
 
     async fn update_key_value(
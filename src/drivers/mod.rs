@@ -3,4 +3,7 @@ pub mod cache;
 pub mod grpc;
 pub mod metrics;
 pub mod monitor;
+pub mod outbox;
+pub mod retry;
 pub mod usage;
+pub mod webhook;
@@ -1,105 +1,131 @@
 use anyhow::Result;
-use rdkafka::{
-    consumer::{CommitMode, Consumer, StreamConsumer},
-    ClientConfig,
-};
-use std::{borrow::Borrow, collections::HashMap, sync::Arc};
+use std::sync::Arc;
 use tracing::{error, info, warn};
 
 use crate::{
-    domain::{error::Error, event::Event, project, resource},
-    driven::{k8s::K8sCluster, prometheus::metrics::MetricsDriven},
+    domain::{
+        error::Error,
+        event::{Event, EventEnvelope},
+        project, resource,
+    },
+    driven::{
+        bus::Bus,
+        k8s::K8sCluster,
+        kafka::dead_letter::{DeadLetterConfig, DeadLetterProducer},
+        prometheus::metrics::MetricsDriven,
+    },
+    drivers::retry::{RetryExhausted, RetryPolicy},
 };
 
 pub async fn subscribe(config: MonitorConfig, metrics: Arc<MetricsDriven>) -> Result<()> {
     let cluster = Arc::new(K8sCluster::new().await?);
 
-    let mut client_config = ClientConfig::new();
-    for (k, v) in config.kafka.iter() {
-        client_config.set(k, v);
-    }
-    let consumer: StreamConsumer = client_config.create()?;
-    consumer.subscribe(&[&config.topic])?;
+    let dead_letter = match &config.dead_letter {
+        Some(dead_letter) => Some(DeadLetterProducer::new("monitor", dead_letter)?),
+        None => None,
+    };
+
+    let consumer = config
+        .bus
+        .subscribe("monitor", std::slice::from_ref(&config.topic))?;
 
     info!("Monitor subscribe running");
     loop {
         match consumer.recv().await {
-            Err(error) => error!(?error, "kafka subscribe error"),
-            Ok(message) => {
-                let message = message.borrow();
-                match message.try_into() {
-                    Ok(event) => {
-                        let result = {
-                            match &event {
-                                Event::ProjectCreated(evt) => {
-                                    project::cluster::apply_manifest(cluster.clone(), evt.clone())
-                                        .await
-                                        .inspect_err(|err| {
-                                            handle_error_metric(metrics.clone(), "project", err)
-                                        })
-                                }
-                                Event::ProjectDeleted(evt) => {
-                                    project::cluster::delete_manifest(cluster.clone(), evt.clone())
-                                        .await
-                                        .inspect_err(|err| {
-                                            handle_error_metric(metrics.clone(), "project", err)
-                                        })
-                                }
-                                Event::ResourceCreated(evt) => {
-                                    resource::cluster::apply_manifest(cluster.clone(), evt.clone())
-                                        .await
-                                        .inspect_err(|err| {
-                                            handle_error_metric(metrics.clone(), "resource", err)
-                                        })
-                                }
-                                Event::ResourceUpdated(evt) => {
-                                    resource::cluster::patch_manifest(cluster.clone(), evt.clone())
-                                        .await
-                                        .inspect_err(|err| {
-                                            handle_error_metric(metrics.clone(), "resource", err)
-                                        })
-                                }
-                                Event::ResourceDeleted(evt) => {
-                                    resource::cluster::delete_manifest(cluster.clone(), evt.clone())
-                                        .await
-                                        .inspect_err(|err| {
-                                            handle_error_metric(metrics.clone(), "resource", err)
-                                        })
-                                }
-                                _ => {
-                                    info!(event = event.key(), "bypass event");
-                                    Ok(())
-                                }
-                            }
-                        };
-
-                        match result {
-                            Ok(()) => {
-                                info!(event = event.key(), "Successfully handled event")
-                            }
-                            Err(err) => warn!(
-                                event = event.key(),
-                                error = err.to_string(),
-                                "Error running event."
-                            ),
-                        }
-
-                        consumer.commit_message(message, CommitMode::Async)?;
-                    }
+            Err(error) => error!(?error, "bus subscribe error"),
+            Ok(None) => return Ok(()),
+            Ok(Some(message)) => {
+                let result = match TryInto::<EventEnvelope>::try_into(&message) {
+                    Ok(EventEnvelope { event, .. }) => config
+                        .retry
+                        .run(|| apply(cluster.clone(), metrics.clone(), &config.cluster_id, &event))
+                        .await
+                        .inspect(|_| info!(event = event.key(), "Successfully handled event")),
                     Err(error) => {
                         error!(?error, "fail to convert message to event");
-                        consumer.commit_message(message, CommitMode::Async)?;
+                        Err(RetryExhausted { attempts: 1, error })
+                    }
+                };
+
+                if let Err(failure) = result {
+                    warn!(
+                        error = failure.error.to_string(),
+                        attempts = failure.attempts,
+                        "Error running event."
+                    );
+
+                    if let Some(dead_letter) = &dead_letter {
+                        dead_letter.send(&message, &failure).await?;
                     }
                 }
+
+                consumer.commit(&message)?;
             }
         };
     }
 }
 
+async fn apply(
+    cluster: Arc<K8sCluster>,
+    metrics: Arc<MetricsDriven>,
+    cluster_id: &str,
+    event: &Event,
+) -> std::result::Result<(), Error> {
+    if event
+        .cluster_id()
+        .is_some_and(|target| target != cluster_id)
+    {
+        info!(
+            event = event.key(),
+            "bypass event placed on another cluster"
+        );
+        return Ok(());
+    }
+
+    match event {
+        Event::ProjectCreated(evt) => project::cluster::apply_manifest(cluster, evt.clone())
+            .await
+            .inspect_err(|err| handle_error_metric(metrics, "project", err)),
+        Event::ProjectDeleted(evt) => project::cluster::delete_manifest(cluster, evt.clone())
+            .await
+            .inspect_err(|err| handle_error_metric(metrics, "project", err)),
+        Event::ProjectRestored(evt) => project::cluster::restore_manifest(cluster, evt.clone())
+            .await
+            .inspect_err(|err| handle_error_metric(metrics, "project", err)),
+        Event::ProjectSuspended(evt) => project::cluster::suspend_manifest(cluster, evt.clone())
+            .await
+            .inspect_err(|err| handle_error_metric(metrics, "project", err)),
+        Event::ProjectResumed(evt) => project::cluster::resume_manifest(cluster, evt.clone())
+            .await
+            .inspect_err(|err| handle_error_metric(metrics, "project", err)),
+        Event::ResourceCreated(evt) => resource::cluster::apply_manifest(cluster, evt.clone())
+            .await
+            .inspect_err(|err| handle_error_metric(metrics, "resource", err)),
+        Event::ResourceUpdated(evt) => resource::cluster::patch_manifest(cluster, evt.clone())
+            .await
+            .inspect_err(|err| handle_error_metric(metrics, "resource", err)),
+        Event::ResourceDeleted(evt) => resource::cluster::delete_manifest(cluster, evt.clone())
+            .await
+            .inspect_err(|err| handle_error_metric(metrics, "resource", err)),
+        Event::ResourceRestored(evt) => resource::cluster::restore_manifest(cluster, evt.clone())
+            .await
+            .inspect_err(|err| handle_error_metric(metrics, "resource", err)),
+        _ => {
+            info!(event = event.key(), "bypass event");
+            Ok(())
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct MonitorConfig {
+    /// Resources placed on other clusters are skipped, the unplaced ones are applied by every
+    /// cluster.
+    pub cluster_id: String,
     pub topic: String,
-    pub kafka: HashMap<String, String>,
+    pub bus: Bus,
+    pub retry: RetryPolicy,
+    pub dead_letter: Option<DeadLetterConfig>,
 }
 
 fn handle_error_metric(metrics: Arc<MetricsDriven>, domain: &str, error: &Error) {
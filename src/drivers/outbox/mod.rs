@@ -0,0 +1,121 @@
+use anyhow::Result;
+use chrono::Utc;
+use std::{path::Path, time::Duration};
+use tokio::time::sleep;
+use tracing::{error, info, warn};
+
+use crate::driven::{
+    bus::{Bus, EventProducer},
+    outbox::SqliteOutbox,
+};
+
+const BATCH_SIZE: u32 = 100;
+
+pub async fn relay(config: RelayConfig) -> Result<()> {
+    let outbox = SqliteOutbox::new(Path::new(&config.db_path)).await?;
+    outbox.migrate().await?;
+
+    let producer = config.bus.producer(&config.topic)?;
+
+    info!("Outbox relay running");
+    let mut delay = config.delay;
+    loop {
+        match relay_pending(&outbox, producer.as_ref()).await {
+            // The next batch is read right away until there is nothing pending.
+            Ok(count) if count > 0 => {
+                info!(count, "Outbox events relayed");
+                delay = config.delay;
+                continue;
+            }
+            Ok(_) => delay = config.delay,
+            Err(error) => {
+                delay = (delay * 2).min(config.max_backoff);
+                error!(?error, ?delay, "fail to relay outbox events, backing off");
+            }
+        }
+
+        sleep(delay).await;
+    }
+}
+
+// Entries are sent strictly in order, a failure stops the batch so a later event never reaches
+// the bus before an earlier one. An entry that doesn't decode would stop it forever, it's
+// dead-lettered instead.
+async fn relay_pending(outbox: &SqliteOutbox, producer: &dyn EventProducer) -> Result<usize> {
+    let pending = outbox.find_pending(BATCH_SIZE).await?;
+
+    for entry in pending.iter() {
+        let envelope = match entry.envelope() {
+            Ok(envelope) => envelope,
+            Err(err) => {
+                error!(
+                    event_id = entry.event_id,
+                    error = err.to_string(),
+                    "fail to decode outbox event, dead-lettering it"
+                );
+                outbox
+                    .mark_dead_lettered(&entry.event_id, &err.to_string(), &Utc::now())
+                    .await?;
+                continue;
+            }
+        };
+
+        if let Err(err) = producer.publish(&envelope).await {
+            warn!(
+                event_id = entry.event_id,
+                attempts = entry.attempts + 1,
+                error = err.to_string(),
+                "fail to publish outbox event"
+            );
+            outbox
+                .mark_failed(&entry.event_id, &err.to_string())
+                .await?;
+            return Err(err.into());
+        }
+
+        outbox.mark_dispatched(&entry.event_id, &Utc::now()).await?;
+    }
+
+    Ok(pending.len())
+}
+
+#[derive(Debug)]
+pub struct RelayConfig {
+    pub db_path: String,
+    pub topic: String,
+    pub bus: Bus,
+    pub delay: Duration,
+    pub max_backoff: Duration,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        domain::event::{EventEnvelope, ProjectCreated},
+        driven::{bus::memory::MemoryBus, outbox::OutboxEntry},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn it_should_dead_letter_undecodable_entries_and_relay_the_next_ones() {
+        let outbox = SqliteOutbox::ephemeral().await.unwrap();
+
+        let mut invalid =
+            OutboxEntry::try_new(&EventEnvelope::new(ProjectCreated::default().into())).unwrap();
+        invalid.payload = b"{\"invalid\"".to_vec();
+        let valid =
+            OutboxEntry::try_new(&EventEnvelope::new(ProjectCreated::default().into())).unwrap();
+        outbox.persist(&invalid).await.unwrap();
+        outbox.persist(&valid).await.unwrap();
+
+        let producer = Bus::Memory(MemoryBus::default())
+            .producer("events")
+            .unwrap();
+        let count = relay_pending(&outbox, producer.as_ref()).await.unwrap();
+        assert!(count == 2);
+
+        let pending = outbox.find_pending(10).await.unwrap();
+        assert!(pending.is_empty());
+    }
+}
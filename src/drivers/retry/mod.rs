@@ -0,0 +1,132 @@
+use serde::Deserialize;
+use std::{future::Future, time::Duration};
+use tokio::time::sleep;
+use tracing::warn;
+
+use crate::domain::error::{Error, ErrorKind};
+
+/// How many times an event application is attempted, and for which errors, before the consumer
+/// gives up on it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    pub retryable: Vec<ErrorKind>,
+}
+impl RetryPolicy {
+    pub fn is_retryable(&self, error: &Error) -> bool {
+        self.retryable.contains(&error.kind())
+    }
+
+    pub async fn run<T, F, Fut>(&self, mut f: F) -> Result<T, RetryExhausted>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Error>>,
+    {
+        let mut attempt = 1;
+        let mut backoff = Duration::from_millis(self.backoff_ms);
+        let max_backoff = Duration::from_millis(self.max_backoff_ms);
+
+        loop {
+            match f().await {
+                Ok(value) => return Ok(value),
+                Err(error) => {
+                    if attempt >= self.attempts || !self.is_retryable(&error) {
+                        return Err(RetryExhausted {
+                            attempts: attempt,
+                            error,
+                        });
+                    }
+
+                    warn!(
+                        attempt,
+                        error = error.to_string(),
+                        ?backoff,
+                        "Retrying event application"
+                    );
+                    sleep(backoff).await;
+
+                    attempt += 1;
+                    backoff = (backoff * 2).min(max_backoff);
+                }
+            }
+        }
+    }
+}
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            backoff_ms: 1_000,
+            max_backoff_ms: 60_000,
+            retryable: vec![ErrorKind::Unexpected],
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RetryExhausted {
+    pub attempts: u32,
+    pub error: Error,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            attempts: 3,
+            backoff_ms: 1,
+            max_backoff_ms: 2,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn it_should_retry_until_success() {
+        let calls = AtomicU32::new(0);
+
+        let result = policy()
+            .run(|| async {
+                if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                    return Err(Error::Unexpected("transient".into()));
+                }
+                Ok(())
+            })
+            .await;
+
+        assert!(result.is_ok());
+        assert!(calls.load(Ordering::SeqCst) == 3);
+    }
+
+    #[tokio::test]
+    async fn it_should_fail_when_attempts_are_exhausted() {
+        let result = policy()
+            .run(|| async { Err::<(), _>(Error::Unexpected("transient".into())) })
+            .await;
+
+        let Err(exhausted) = result else {
+            unreachable!("expected retry to be exhausted")
+        };
+        assert!(exhausted.attempts == 3);
+    }
+
+    #[tokio::test]
+    async fn it_should_not_retry_when_error_is_not_retryable() {
+        let calls = AtomicU32::new(0);
+
+        let result = policy()
+            .run(|| async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err::<(), _>(Error::CommandMalformed("invalid spec".into()))
+            })
+            .await;
+
+        assert!(result.is_err());
+        assert!(calls.load(Ordering::SeqCst) == 1);
+    }
+}
@@ -1,15 +1,16 @@
-use std::{collections::HashMap, path::Path, sync::Arc, time::Duration};
+use std::{path::Path, sync::Arc, time::Duration};
 
 use anyhow::Result;
 use chrono::Utc;
+use serde::Deserialize;
 use tokio::time::sleep;
 use tracing::{error, info};
 
 use crate::{
     domain::{error::Error, usage},
     driven::{
+        bus::Bus,
         cache::{usage::SqliteUsageDrivenCache, SqliteCache},
-        kafka::KafkaProducer,
         prometheus::{metrics::MetricsDriven, PrometheusUsageDriven},
     },
 };
@@ -19,7 +20,7 @@ pub async fn schedule(config: UsageConfig, metrics: Arc<MetricsDriven>) -> Resul
     let usage_cache = Arc::new(SqliteUsageDrivenCache::new(sqlite_cache.clone()));
 
     let prometheus_driven = Arc::new(PrometheusUsageDriven::new(&config.prometheus_url));
-    let event_bridge = Arc::new(KafkaProducer::new(&config.topic, &config.kafka)?);
+    let event_bridge = config.bus.bridge(&config.topic)?;
 
     let mut cursor = Utc::now();
 
@@ -51,6 +52,23 @@ pub async fn schedule(config: UsageConfig, metrics: Arc<MetricsDriven>) -> Resul
     }
 }
 
+/// Rolls up the usage of the closed months in the cache at `db_path` on every `interval`, the
+/// cache consumer running alongside is the one migrating it.
+pub async fn rollup(config: UsageRollupConfig) -> Result<()> {
+    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
+    let usage_cache = Arc::new(SqliteUsageDrivenCache::new(sqlite_cache.clone()));
+    let retention = chrono::Duration::from_std(config.retention)?;
+
+    info!("Usage rollup running");
+    loop {
+        sleep(config.interval).await;
+
+        if let Err(err) = usage::cache::rollup(usage_cache.clone(), retention).await {
+            error!(error = err.to_string(), "Error running usage rollup");
+        }
+    }
+}
+
 pub struct UsageConfig {
     pub db_path: String,
     pub cluster_id: String,
@@ -58,7 +76,32 @@ pub struct UsageConfig {
     pub prometheus_query_step: String,
     pub delay: Duration,
     pub topic: String,
-    pub kafka: HashMap<String, String>,
+    pub bus: Bus,
+}
+
+pub struct UsageRollupConfig {
+    pub db_path: String,
+    pub interval: Duration,
+    /// How long the rows already rolled up are kept.
+    pub retention: Duration,
+}
+impl UsageRollupConfig {
+    pub fn new(db_path: String, schedule: RollupSchedule) -> Self {
+        Self {
+            db_path,
+            interval: Duration::from_secs(schedule.interval_sec.unwrap_or(3600)),
+            retention: Duration::from_secs(schedule.retention_days * 24 * 60 * 60),
+        }
+    }
+}
+
+/// `[usage_rollup]` section of the rpc and daemon configs.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RollupSchedule {
+    /// Defaults to one hour.
+    pub interval_sec: Option<u64>,
+    /// Days the raw usage rows are kept after their month is rolled up.
+    pub retention_days: u64,
 }
 
 fn handle_error_metric(metrics: Arc<MetricsDriven>, domain: &str, error: &Error) {
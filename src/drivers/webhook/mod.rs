@@ -0,0 +1,480 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use anyhow::bail;
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::post,
+    Router,
+};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use serde_json::Value;
+use sha2::Sha256;
+use tracing::{error, info, warn};
+
+use crate::{
+    domain::{
+        audit::cache::AuditDrivenCache,
+        error::Error,
+        event::EventDrivenBridge,
+        project::{
+            billing::{self, PaymentFailure, Subscription},
+            cache::ProjectDrivenCache,
+        },
+        Result,
+    },
+    driven::{
+        bus::Bus,
+        cache::{
+            audit::SqliteAuditDrivenCache,
+            postgres::{project::PostgresProjectDrivenCache, PostgresCache},
+            project::SqliteProjectDrivenCache,
+            SqliteCache,
+        },
+        outbox::SqliteOutbox,
+    },
+};
+
+/// Oldest signature accepted, in seconds, the default of the Stripe libraries.
+const SIGNATURE_TOLERANCE: i64 = 300;
+
+pub async fn server(config: WebhookConfig) -> anyhow::Result<()> {
+    let sqlite_cache = Arc::new(SqliteCache::new(Path::new(&config.db_path)).await?);
+
+    let cache: Arc<dyn ProjectDrivenCache> = match &config.postgres_url {
+        Some(url) => Arc::new(PostgresProjectDrivenCache::new(Arc::new(
+            PostgresCache::new(url).await?,
+        ))),
+        None => Arc::new(SqliteProjectDrivenCache::new(sqlite_cache.clone())),
+    };
+
+    // The audit log is always kept by the sqlite cache, it tells who suspended a project.
+    let audit: Arc<dyn AuditDrivenCache> = Arc::new(SqliteAuditDrivenCache::new(sqlite_cache));
+
+    let event: Arc<dyn EventDrivenBridge> = match &config.outbox_path {
+        Some(outbox_path) => {
+            let outbox = SqliteOutbox::new(Path::new(outbox_path)).await?;
+            outbox.migrate().await?;
+            Arc::new(outbox)
+        }
+        None => config.bus.bridge(&config.topic)?,
+    };
+
+    let app = router(WebhookState {
+        cache,
+        audit,
+        event,
+        stripe_secret: config.stripe_secret,
+    });
+
+    let listener = tokio::net::TcpListener::bind(&config.addr).await?;
+
+    info!(address = config.addr, "Webhook server running");
+    if let Err(err) = axum::serve(listener, app).await {
+        bail!(err);
+    }
+
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct WebhookState {
+    pub cache: Arc<dyn ProjectDrivenCache>,
+    pub audit: Arc<dyn AuditDrivenCache>,
+    pub event: Arc<dyn EventDrivenBridge>,
+    pub stripe_secret: String,
+}
+
+pub fn router(state: WebhookState) -> Router {
+    Router::new()
+        .route("/webhooks/stripe", post(stripe))
+        .with_state(state)
+}
+
+async fn stripe(
+    State(state): State<WebhookState>,
+    headers: HeaderMap,
+    payload: Bytes,
+) -> impl IntoResponse {
+    let Some(signature) = headers
+        .get("stripe-signature")
+        .and_then(|value| value.to_str().ok())
+    else {
+        return (StatusCode::BAD_REQUEST, "missing signature");
+    };
+
+    if let Err(error) = verify_signature(
+        &state.stripe_secret,
+        signature,
+        &payload,
+        Utc::now().timestamp(),
+    ) {
+        warn!(?error, "invalid stripe signature");
+        return (StatusCode::BAD_REQUEST, "invalid signature");
+    }
+
+    let evt: StripeEvent = match serde_json::from_slice(&payload) {
+        Ok(evt) => evt,
+        Err(error) => {
+            warn!(?error, "invalid stripe event");
+            return (StatusCode::BAD_REQUEST, "invalid event");
+        }
+    };
+
+    match handle(&state, &evt).await {
+        Ok(()) => (StatusCode::OK, "ok"),
+        // Stripe retries the deliveries that fail, only the ones that could succeed later.
+        Err(Error::CommandMalformed(error)) => {
+            warn!(event = evt.id, error, "stripe event not supported");
+            (StatusCode::BAD_REQUEST, "event not supported")
+        }
+        Err(error) => {
+            error!(event = evt.id, ?error, "fail to handle stripe event");
+            (StatusCode::INTERNAL_SERVER_ERROR, "internal error")
+        }
+    }
+}
+
+async fn handle(state: &WebhookState, evt: &StripeEvent) -> Result<()> {
+    match evt.kind.as_str() {
+        "customer.subscription.created"
+        | "customer.subscription.updated"
+        | "customer.subscription.deleted" => {
+            let subscription: StripeSubscription = serde_json::from_value(evt.data.object.clone())?;
+
+            let subscription = Subscription {
+                id: subscription.id,
+                customer_id: subscription.customer,
+                project_id: subscription.metadata.get("project_id").cloned(),
+                status: subscription.status.parse()?,
+            };
+
+            billing::apply_subscription(
+                state.cache.clone(),
+                state.audit.clone(),
+                state.event.clone(),
+                subscription,
+            )
+            .await
+        }
+        "invoice.payment_failed" => {
+            let invoice: StripeInvoice = serde_json::from_value(evt.data.object.clone())?;
+
+            let failure = PaymentFailure {
+                customer_id: invoice.customer,
+                subscription_id: invoice.subscription.or_else(|| {
+                    invoice
+                        .parent
+                        .and_then(|parent| parent.subscription_details)
+                        .map(|details| details.subscription)
+                }),
+            };
+
+            billing::apply_payment_failure(state.cache.clone(), state.event.clone(), failure).await
+        }
+        kind => {
+            info!(event = evt.id, kind, "bypass stripe event");
+            Ok(())
+        }
+    }
+}
+
+/// Checks the `Stripe-Signature` header, an HMAC-SHA256 of `{timestamp}.{payload}` with the
+/// endpoint secret in any of its `v1` entries.
+fn verify_signature(secret: &str, header: &str, payload: &[u8], now: i64) -> Result<()> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in header.split(',') {
+        match part.split_once('=') {
+            Some(("t", value)) => timestamp = Some(value),
+            Some(("v1", value)) => signatures.push(value),
+            _ => {}
+        }
+    }
+
+    let Some(timestamp) = timestamp else {
+        return Err(Error::Unauthorized("signature without timestamp".into()));
+    };
+    let Ok(signed_at) = timestamp.parse::<i64>() else {
+        return Err(Error::Unauthorized("invalid signature timestamp".into()));
+    };
+    if (now - signed_at).abs() > SIGNATURE_TOLERANCE {
+        return Err(Error::Unauthorized("signature expired".into()));
+    }
+
+    let valid = signatures.into_iter().any(|signature| {
+        let Ok(signature) = hex::decode(signature) else {
+            return false;
+        };
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("hmac accepts keys of any size");
+        mac.update(timestamp.as_bytes());
+        mac.update(b".");
+        mac.update(payload);
+        mac.verify_slice(&signature).is_ok()
+    });
+
+    if !valid {
+        return Err(Error::Unauthorized("signature mismatch".into()));
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+struct StripeEvent {
+    id: String,
+    #[serde(rename = "type")]
+    kind: String,
+    data: StripeEventData,
+}
+
+#[derive(Debug, Deserialize)]
+struct StripeEventData {
+    object: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct StripeSubscription {
+    id: String,
+    customer: String,
+    status: String,
+    #[serde(default)]
+    metadata: HashMap<String, String>,
+}
+
+/// The subscription of the invoice moved under `parent` in the newer API versions.
+#[derive(Debug, Deserialize)]
+struct StripeInvoice {
+    customer: String,
+    subscription: Option<String>,
+    parent: Option<StripeInvoiceParent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StripeInvoiceParent {
+    subscription_details: Option<StripeInvoiceSubscription>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StripeInvoiceSubscription {
+    subscription: String,
+}
+
+pub struct WebhookConfig {
+    pub addr: String,
+    /// Signing secret of the Stripe endpoint, `whsec_...`.
+    pub stripe_secret: String,
+    pub db_path: String,
+    pub postgres_url: Option<String>,
+    pub outbox_path: Option<String>,
+    pub bus: Bus,
+    pub topic: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{
+        audit::cache::MockAuditDrivenCache,
+        event::{Event, MockEventDrivenBridge},
+        project::{cache::MockProjectDrivenCache, Project, ProjectStatus},
+    };
+
+    use super::*;
+
+    const SECRET: &str = "whsec_test";
+
+    const SUBSCRIPTION_CREATED: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/test/stripe/customer.subscription.created.json"
+    ));
+    const SUBSCRIPTION_UPDATED: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/test/stripe/customer.subscription.updated.json"
+    ));
+    const SUBSCRIPTION_DELETED: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/test/stripe/customer.subscription.deleted.json"
+    ));
+    const INVOICE_PAYMENT_FAILED: &str = include_str!(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/test/stripe/invoice.payment_failed.json"
+    ));
+
+    fn sign(payload: &str, timestamp: i64) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+        mac.update(format!("{timestamp}.{payload}").as_bytes());
+        let signature = hex::encode(mac.finalize().into_bytes());
+
+        format!("t={timestamp},v1={signature}")
+    }
+
+    /// Serves the router on a local port, the way Stripe reaches it, and replays the recorded
+    /// payload signed with the test secret.
+    async fn replay(
+        cache: MockProjectDrivenCache,
+        event: MockEventDrivenBridge,
+        payload: &'static str,
+        signature: String,
+    ) -> reqwest::StatusCode {
+        let app = router(WebhookState {
+            cache: Arc::new(cache),
+            audit: Arc::new(MockAuditDrivenCache::new()),
+            event: Arc::new(event),
+            stripe_secret: SECRET.into(),
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        reqwest::Client::new()
+            .post(format!("http://{addr}/webhooks/stripe"))
+            .header("stripe-signature", signature)
+            .body(payload)
+            .send()
+            .await
+            .unwrap()
+            .status()
+    }
+
+    fn customer_project() -> Project {
+        Project {
+            billing_provider_id: "cus_R3GdnP7kq2FzWb".into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_should_verify_signature() {
+        let now = Utc::now().timestamp();
+        let header = format!("{},v0=legacy", sign("{}", now));
+
+        assert!(verify_signature(SECRET, &header, b"{}", now).is_ok());
+        assert!(verify_signature("whsec_other", &header, b"{}", now).is_err());
+        assert!(verify_signature(SECRET, &header, b"{ }", now).is_err());
+        assert!(verify_signature(SECRET, &header, b"{}", now + SIGNATURE_TOLERANCE + 1).is_err());
+    }
+
+    #[tokio::test]
+    async fn it_should_reject_payload_with_invalid_signature() {
+        let cache = MockProjectDrivenCache::new();
+        let event = MockEventDrivenBridge::new();
+
+        let signature = sign(SUBSCRIPTION_UPDATED, Utc::now().timestamp()).replace("v1=", "v1=00");
+
+        let status = replay(cache, event, SUBSCRIPTION_UPDATED, signature).await;
+        assert_eq!(status, reqwest::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn it_should_set_subscription_when_created() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_by_billing_provider_id()
+            .withf(|customer| customer == "cus_R3GdnP7kq2FzWb")
+            .return_once(|_| Ok(vec![customer_project()]));
+
+        let mut event = MockEventDrivenBridge::new();
+        event
+            .expect_dispatch()
+            .withf(|evt| match evt {
+                Event::ProjectUpdated(evt) => {
+                    evt.billing_subscription_id.as_deref() == Some("sub_1QB8cPKpfcH2v7IxQ0c0vH1r")
+                }
+                _ => false,
+            })
+            .return_once(|_| Ok(None));
+
+        let signature = sign(SUBSCRIPTION_CREATED, Utc::now().timestamp());
+
+        let status = replay(cache, event, SUBSCRIPTION_CREATED, signature).await;
+        assert_eq!(status, reqwest::StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn it_should_flag_payment_failure_when_subscription_is_past_due() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_by_billing_provider_id()
+            .return_once(|_| Ok(vec![customer_project()]));
+
+        let mut event = MockEventDrivenBridge::new();
+        event
+            .expect_dispatch()
+            .withf(|evt| match evt {
+                Event::ProjectUpdated(evt) => {
+                    evt.status == Some(ProjectStatus::PaymentMethodFailed.to_string())
+                }
+                _ => false,
+            })
+            .return_once(|_| Ok(None));
+
+        let signature = sign(SUBSCRIPTION_UPDATED, Utc::now().timestamp());
+
+        let status = replay(cache, event, SUBSCRIPTION_UPDATED, signature).await;
+        assert_eq!(status, reqwest::StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn it_should_clear_subscription_and_suspend_when_subscription_is_deleted() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache.expect_find_by_billing_provider_id().return_once(|_| {
+            Ok(vec![Project {
+                billing_subscription_id: Some("sub_1QB8cPKpfcH2v7IxQ0c0vH1r".into()),
+                ..customer_project()
+            }])
+        });
+
+        let mut event = MockEventDrivenBridge::new();
+        event
+            .expect_dispatch()
+            .withf(|evt| match evt {
+                Event::ProjectUpdated(evt) => {
+                    evt.status == Some(ProjectStatus::PaymentMethodFailed.to_string())
+                        && evt.billing_subscription_id.is_none()
+                        && evt.billing_subscription_deleted
+                }
+                _ => false,
+            })
+            .return_once(|_| Ok(None));
+        event
+            .expect_dispatch()
+            .withf(|evt| matches!(evt, Event::ProjectSuspended(_)))
+            .return_once(|_| Ok(None));
+
+        let signature = sign(SUBSCRIPTION_DELETED, Utc::now().timestamp());
+
+        let status = replay(cache, event, SUBSCRIPTION_DELETED, signature).await;
+        assert_eq!(status, reqwest::StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn it_should_flag_payment_failure_when_invoice_fails() {
+        let mut cache = MockProjectDrivenCache::new();
+        cache
+            .expect_find_by_billing_provider_id()
+            .return_once(|_| Ok(vec![customer_project()]));
+
+        let mut event = MockEventDrivenBridge::new();
+        event
+            .expect_dispatch()
+            .withf(|evt| match evt {
+                Event::ProjectUpdated(evt) => {
+                    evt.status == Some(ProjectStatus::PaymentMethodFailed.to_string())
+                        && evt.billing_subscription_id.as_deref()
+                            == Some("sub_1QB8cPKpfcH2v7IxQ0c0vH1r")
+                }
+                _ => false,
+            })
+            .return_once(|_| Ok(None));
+
+        let signature = sign(INVOICE_PAYMENT_FAILED, Utc::now().timestamp());
+
+        let status = replay(cache, event, INVOICE_PAYMENT_FAILED, signature).await;
+        assert_eq!(status, reqwest::StatusCode::OK);
+    }
+}